@@ -6,10 +6,11 @@
  * ORACLE-005: Detects oracle mode from config
  * ORACLE-006: Returns mode label correctly
  * ORACLE-007: Admin mode with zero timestamp uses authorityPriceE6 fallback
+ * ORACLE-008: Pyth mode reads publish time and confidence from the price account
  */
 
 import { describe, it, expect, vi, beforeEach } from "vitest";
-import { renderHook } from "@testing-library/react";
+import { renderHook, waitFor } from "@testing-library/react";
 import { PublicKey } from "@solana/web3.js";
 
 // Dynamic mock config — reassign before each test as needed
@@ -22,6 +23,15 @@ vi.mock("@/components/providers/SlabProvider", () => ({
   }),
 }));
 
+const { mockConnection, mockGetAccountInfo } = vi.hoisted(() => {
+  const mockGetAccountInfo = vi.fn();
+  return { mockGetAccountInfo, mockConnection: { getAccountInfo: mockGetAccountInfo } };
+});
+
+vi.mock("@/hooks/useWalletCompat", () => ({
+  useConnectionCompat: () => ({ connection: mockConnection }),
+}));
+
 import { useOracleFreshness } from "@/hooks/useOracleFreshness";
 
 // Non-zero authority key for admin mode
@@ -97,4 +107,36 @@ describe("useOracleFreshness", () => {
     expect(result.current.ready).toBe(true);
     expect(result.current.lastUpdateMs).toBe(Number(nowSecs) * 1000);
   });
+
+  it("ORACLE-008: pyth mode uses the PriceUpdateV2 publish time and flags wide confidence", async () => {
+    const publishTime = Math.floor(Date.now() / 1000) - 12;
+    // PriceUpdateV2, fully verified: price $150.00000000 ± $1.50 (100 bps), expo -8
+    const data = new Uint8Array(133);
+    const dv = new DataView(data.buffer);
+    data.set([0x22, 0xf1, 0x23, 0x63, 0x9d, 0x7e, 0xf4, 0xcd], 0);
+    data[40] = 1;
+    dv.setBigInt64(73, 15_000_000_000n, true);
+    dv.setBigUint64(81, 150_000_000n, true);
+    dv.setInt32(89, -8, true);
+    dv.setBigInt64(93, BigInt(publishTime), true);
+    mockGetAccountInfo.mockResolvedValueOnce({ data });
+
+    currentConfig = {
+      oracleAuthority: PublicKey.default,
+      indexFeedId: NON_ZERO_FEED,
+      authorityPriceE6: 0n,
+      authorityTimestamp: 0n,
+      lastEffectivePriceE6: 150_000000n,
+      maxStalenessSlots: 60n,
+      confFilterBps: 50,
+      collateralMint: new PublicKey("So11111111111111111111111111111111111111112"),
+    };
+
+    const { result } = renderHook(() => useOracleFreshness());
+    expect(result.current.mode).toBe("pyth-pinned");
+
+    await waitFor(() => expect(result.current.lastUpdateMs).toBe(publishTime * 1000));
+    expect(result.current.confBps).toBe(100);
+    expect(result.current.oracleRejection).toMatch(/confidence/);
+  });
 });
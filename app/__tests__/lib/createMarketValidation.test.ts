@@ -1,7 +1,10 @@
 import { describe, it, expect } from "vitest";
 import {
   validateCreateForm,
+  assessOracleManipulation,
+  checkOracleManipulation,
   type CreateFormValues,
+  type OracleManipulationInput,
 } from "@/lib/createMarketValidation";
 
 function validForm(overrides: Partial<CreateFormValues> = {}): CreateFormValues {
@@ -272,3 +275,45 @@ describe("validateCreateForm", () => {
     expect(errors.some((e) => e.field === "Token Balance")).toBe(false);
   });
 });
+
+describe("checkOracleManipulation", () => {
+  // 100 SOL LP at $150 with 500 bps IM → $300k max OI at 20x
+  function dexInput(overrides: Partial<OracleManipulationInput> = {}): OracleManipulationInput {
+    return {
+      oraclePoolDepthUsd: 50_000_000,
+      tokenPriceUsd: 150,
+      lpCollateral: "100",
+      initialMarginBps: 500,
+      ...overrides,
+    };
+  }
+
+  it("passes a pool deep enough for the market", () => {
+    const check = checkOracleManipulation(dexInput());
+    expect(check.status).toBe("ok");
+    expect(check.risk?.level).toBe("low");
+    expect(check.risk?.maxProfitUsd).toBe(15_000);
+  });
+
+  it("blocks a pool cheaper to move than the market's OI can earn", () => {
+    const check = checkOracleManipulation(dexInput({ oraclePoolDepthUsd: 20_000 }));
+    expect(check.status).toBe("blocked");
+    expect(check.message).toContain("deeper pool");
+  });
+
+  it("holds launch while depth is loading, unreadable, or unpriced", () => {
+    expect(checkOracleManipulation(dexInput({ depthLoading: true })).status).toBe("pending");
+    expect(checkOracleManipulation(dexInput({ oraclePoolDepthUsd: undefined })).status).toBe("pending");
+    expect(checkOracleManipulation(dexInput({ oraclePoolDepthUsd: null })).status).toBe("unknown");
+    expect(checkOracleManipulation(dexInput({ tokenPriceUsd: null })).status).toBe("unknown");
+  });
+
+  it("sizes OI by the configured cap and the oracle price cap", () => {
+    // 2x cap → $30k OI; a 1% price cap limits the move to 100 bps
+    const risk = assessOracleManipulation(
+      dexInput({ oiCapMultiplierBps: 20_000, oraclePriceCapE2bps: 10_000 }),
+    );
+    expect(risk?.moveBps).toBe(100);
+    expect(risk?.maxProfitUsd).toBe(300);
+  });
+});
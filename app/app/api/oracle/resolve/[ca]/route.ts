@@ -1,6 +1,8 @@
 import { NextRequest, NextResponse } from "next/server";
-import { PublicKey } from "@solana/web3.js";
+import { Connection, PublicKey } from "@solana/web3.js";
+import { fetchDexPoolDepth, poolSideValueUsd } from "@percolator/sdk";
 import { SUPPORTED_DEX_IDS } from "@/lib/dex-constants";
+import { getConfig } from "@/lib/config";
 
 export const dynamic = "force-dynamic";
 
@@ -10,11 +12,14 @@ export const dynamic = "force-dynamic";
  * Given a Solana token mint (base58), resolves oracle config using a
  * Pyth Hermes → Jupiter → DexScreener fallback chain.
  *
- * Returns: { feedId, symbol, price, source }
+ * Returns: { feedId, symbol, price, source, poolDepthUsd }
  *   feedId — Pyth feed ID (hex64) if found, else null
  *   symbol — token ticker
  *   price  — USD price (number)
  *   source — "pyth" | "jupiter" | "dexscreener"
+ *   poolDepthUsd — USD value of the token's side of the DEX pool, read
+ *                  on-chain, for the manipulation-cost check. `?pool=` reads
+ *                  a specific pool instead of the best one found.
  *
  * Bug: PERC-oracle-resolve — route was missing, causing 404 on Create Market flow.
  */
@@ -87,6 +92,10 @@ interface CacheEntry {
 const cache = new Map<string, CacheEntry>();
 const CACHE_TTL_MS = 5 * 60 * 1000;
 
+// Pool depth moves faster than the source list — keep it for a minute, per pool
+const depthCache = new Map<string, { depthUsd: number | null; expiresAt: number }>();
+const DEPTH_CACHE_TTL_MS = 60 * 1000;
+
 interface OracleResolveResult {
   feedId: string | null;
   symbol: string;
@@ -98,6 +107,8 @@ interface OracleResolveResult {
   dexType?: string | null;
   /** PERC-470: Recommended oracle mode */
   oracleMode?: "pyth" | "hyperp" | "admin";
+  /** USD value of the token's side of the DEX pool; null if unreadable */
+  poolDepthUsd?: number | null;
 }
 
 // ---------------------------------------------------------------------------
@@ -191,15 +202,33 @@ async function fetchDexScreenerInfo(
   }
 }
 
+/** Token-side USD depth of `pool`, read on-chain. Null when it can't be read. */
+async function readPoolDepthUsd(pool: string, ca: string, price: number): Promise<number | null> {
+  const cached = depthCache.get(pool);
+  if (cached && Date.now() < cached.expiresAt) return cached.depthUsd;
+
+  let depthUsd: number | null = null;
+  try {
+    const connection = new Connection(getConfig().rpcUrl, "confirmed");
+    const snapshot = await fetchDexPoolDepth(connection, pool);
+    depthUsd = snapshot ? poolSideValueUsd(snapshot, ca, price) : null;
+  } catch {
+    depthUsd = null;
+  }
+  depthCache.set(pool, { depthUsd, expiresAt: Date.now() + DEPTH_CACHE_TTL_MS });
+  return depthUsd;
+}
+
 // ---------------------------------------------------------------------------
 // Route handler
 // ---------------------------------------------------------------------------
 
 export async function GET(
-  _req: NextRequest,
+  req: NextRequest,
   { params }: { params: Promise<{ ca: string }> },
 ): Promise<NextResponse> {
   const { ca } = await params;
+  const poolParam = req.nextUrl.searchParams.get("pool");
 
   // Reject URLs immediately — clear, actionable error
   if (isUrl(ca)) {
@@ -217,10 +246,14 @@ export async function GET(
     );
   }
 
+  if (poolParam && !isValidBase58Pubkey(poolParam)) {
+    return NextResponse.json({ error: "Invalid pool address" }, { status: 400 });
+  }
+
   // Cache hit
   const cached = cache.get(ca);
   if (cached && Date.now() < cached.expiresAt) {
-    return NextResponse.json({ ...cached.data, cached: true });
+    return NextResponse.json({ ...(await withPoolDepth(cached.data, ca, poolParam)), cached: true });
   }
 
   // --- 1. Check static Pyth feed map ---
@@ -273,5 +306,15 @@ export async function GET(
 
   // Cache and return
   cache.set(ca, { data: result, expiresAt: Date.now() + CACHE_TTL_MS });
-  return NextResponse.json({ ...result, cached: false });
+  return NextResponse.json({ ...(await withPoolDepth(result, ca, poolParam)), cached: false });
+}
+
+async function withPoolDepth(
+  result: OracleResolveResult,
+  ca: string,
+  poolParam: string | null,
+): Promise<OracleResolveResult> {
+  const pool = poolParam ?? result.dexPoolAddress ?? null;
+  if (!pool || result.price <= 0) return { ...result, poolDepthUsd: null };
+  return { ...result, poolDepthUsd: await readPoolDepthUsd(pool, ca, result.price) };
 }
@@ -3,9 +3,11 @@
 import { FC, useState, useMemo, useCallback, useEffect, useRef } from "react";
 import { PublicKey } from "@solana/web3.js";
 import { useWalletCompat, useConnectionCompat } from "@/hooks/useWalletCompat";
-import { useCreateMarket, MIN_INIT_MARKET_SEED, type CreateMarketParams } from "@/hooks/useCreateMarket";
+import { useCreateMarket, MIN_INIT_MARKET_SEED, launchOraclePriceCapE2bps, type CreateMarketParams } from "@/hooks/useCreateMarket";
 import { useQuickLaunch } from "@/hooks/useQuickLaunch";
 import { type DexPoolResult } from "@/hooks/useDexPoolSearch";
+import { useOracleDepth } from "@/hooks/useOracleDepth";
+import { checkOracleManipulation } from "@/lib/createMarketValidation";
 import { parseHumanAmount, formatHumanAmount } from "@/lib/parseAmount";
 import { SLAB_TIERS, type SlabTierKey } from "@percolator/sdk";
 import { getNetwork } from "@/lib/config";
@@ -197,9 +199,39 @@ export const CreateMarketWizard: FC<{ initialMint?: string }> = ({ initialMint }
     return slabRentSol + tokenAccountRentSol + TX_FEE_ESTIMATE_SOL;
   }, [wizard.slabTier]);
   const hasSufficientSol = solBalance !== null && solBalance >= requiredSol;
+  // DEX oracle: launch only once the pool is known to cost more to move than
+  // the market's max OI can earn. Loading, unreadable or unpriced pools hold it.
+  const isDexOracle = wizard.oracleType === "hyperp_ema" && step2Valid;
+  const oracleDepth = useOracleDepth(isDexOracle && mintValid ? wizard.mintAddress : null, isDexOracle ? wizard.oracleFeed : null);
+  const oracleCheck = useMemo(
+    () =>
+      isDexOracle
+        ? checkOracleManipulation({
+            oraclePoolDepthUsd: oracleDepth.depthUsd,
+            depthLoading: oracleDepth.loading,
+            tokenPriceUsd: wizard.dexPool?.priceUsd || oracleDepth.priceUsd,
+            lpCollateral: wizard.lpCollateral,
+            initialMarginBps: wizard.initialMarginBps,
+            oraclePriceCapE2bps: launchOraclePriceCapE2bps(wizard.oracleType),
+            // The create flow configures no OI cap, so max leverage bounds OI
+            oiCapMultiplierBps: 0,
+          })
+        : null,
+    [
+      isDexOracle,
+      oracleDepth.depthUsd,
+      oracleDepth.loading,
+      oracleDepth.priceUsd,
+      wizard.dexPool?.priceUsd,
+      wizard.lpCollateral,
+      wizard.initialMarginBps,
+      wizard.oracleType,
+    ],
+  );
+
   // On devnet, tokens are auto-airdropped after market creation — skip token balance checks
   const isDevnet = getNetwork() === "devnet";
-  const allValid = step1Valid && step2Valid && step3Valid && (isDevnet || (hasTokens && hasSufficientTokensForSeed)) && hasSufficientSol;
+  const allValid = step1Valid && step2Valid && step3Valid && (oracleCheck === null || oracleCheck.status === "ok") && (isDevnet || (hasTokens && hasSufficientTokensForSeed)) && hasSufficientSol;
 
   // Quick Launch auto-advance: step 1 → step 2 when token is resolved and params ready
   const quickAutoAdvancedRef = useRef(false);
@@ -744,6 +776,7 @@ export const CreateMarketWizard: FC<{ initialMint?: string }> = ({ initialMint }
             hasTokens={hasTokens}
             hasSufficientTokensForSeed={hasSufficientTokensForSeed}
             feeConflict={feeConflict}
+            oracleCheck={oracleCheck}
             onBack={goBack}
             onLaunch={handleLaunch}
             canLaunch={allValid && !!publicKey}
@@ -2,6 +2,7 @@
 
 import { FC, useMemo } from "react";
 import { type SlabTierKey, SLAB_TIERS } from "@percolator/sdk";
+import type { OracleManipulationCheck } from "@/lib/createMarketValidation";
 import { CostEstimate } from "./CostEstimate";
 import Link from "next/link";
 import { getNetwork } from "@/lib/config";
@@ -30,6 +31,8 @@ interface StepReviewProps {
   hasTokens: boolean;
   hasSufficientTokensForSeed: boolean;
   feeConflict: boolean;
+  /** DEX oracle manipulation-cost gate; null when the oracle isn't a DEX pool */
+  oracleCheck?: OracleManipulationCheck | null;
   // Actions
   onBack: () => void;
   onLaunch: () => void;
@@ -68,6 +71,7 @@ export const StepReview: FC<StepReviewProps> = ({
   hasTokens,
   hasSufficientTokensForSeed,
   feeConflict,
+  oracleCheck,
   onBack,
   onLaunch,
   canLaunch,
@@ -89,10 +93,13 @@ export const StepReview: FC<StepReviewProps> = ({
     if (!isDevnet && !hasTokens) return "No Tokens — Mint First";
     if (!isDevnet && !hasSufficientTokensForSeed) return "Insufficient Tokens for Vault Seed (500)";
     if (feeConflict) return "Fix Parameters to Continue";
+    if (oracleCheck?.status === "pending") return "Checking Oracle Pool…";
+    if (oracleCheck?.status === "unknown") return "Oracle Pool Depth Unknown";
+    if (oracleCheck?.status === "blocked") return "Oracle Pool Too Shallow";
     if (!hasSufficientBalance) return "Insufficient SOL";
     if (isDevnet) return "LAUNCH & MINT TOKENS →";
     return "LAUNCH MARKET →";
-  }, [walletConnected, hasTokens, hasSufficientTokensForSeed, feeConflict, hasSufficientBalance, isDevnet]);
+  }, [walletConnected, hasTokens, hasSufficientTokensForSeed, feeConflict, oracleCheck?.status, hasSufficientBalance, isDevnet]);
 
   return (
     <div className="space-y-5">
@@ -139,6 +146,37 @@ export const StepReview: FC<StepReviewProps> = ({
         </div>
       </div>
 
+      {/* Oracle manipulation cost (DEX oracle) */}
+      {oracleCheck && (
+        <div
+          className={`border px-4 py-3 space-y-1 ${
+            oracleCheck.status === "blocked" || oracleCheck.status === "unknown"
+              ? "border-[var(--short)]/30 bg-[var(--short)]/[0.04]"
+              : "border-[var(--border)] bg-[var(--bg)]"
+          }`}
+        >
+          {oracleCheck.risk && (
+            <p className="text-[11px] text-[var(--text)]">
+              <span className={oracleCheck.status === "blocked" ? "text-[var(--short)] font-medium" : "text-[var(--text-muted)]"}>
+                Oracle manipulation risk: {oracleCheck.risk.level.toUpperCase()} ({oracleCheck.risk.score}/100)
+              </span>
+            </p>
+          )}
+          {oracleCheck.risk && oracleCheck.status === "ok" && (
+            <p className="text-[9px] text-[var(--text-dim)]">
+              Moving the pool {(oracleCheck.risk.moveBps / 100).toFixed(2)}% costs ~$
+              {Math.round(oracleCheck.risk.costUsd).toLocaleString()}; max OI would earn ~$
+              {Math.round(oracleCheck.risk.maxProfitUsd).toLocaleString()}.
+            </p>
+          )}
+          {oracleCheck.message && (
+            <p className={oracleCheck.status === "pending" ? "text-[11px] text-[var(--text-dim)]" : "text-[9px] text-[var(--text-dim)]"}>
+              {oracleCheck.message}
+            </p>
+          )}
+        </div>
+      )}
+
       {/* Cost Breakdown */}
       <CostEstimate
         slabTier={slabTier}
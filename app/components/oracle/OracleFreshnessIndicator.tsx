@@ -24,6 +24,7 @@ export const OracleFreshnessIndicator: FC = () => {
     level,
     color,
     ready,
+    oracleRejection,
   } = useOracleFreshness();
 
   const {
@@ -115,8 +116,25 @@ export const OracleFreshnessIndicator: FC = () => {
         </span>
       </button>
 
+      {/* Pyth price outside the market's staleness / confidence limits */}
+      {oracleRejection && (
+        <div
+          className="flex items-center gap-1.5 px-2 py-1 text-[10px]"
+          style={{
+            backgroundColor: "rgba(239,68,68,0.10)",
+            color: "#ef4444",
+            fontFamily: "var(--font-mono)",
+          }}
+        >
+          <span>⚠</span>
+          <span>
+            Oracle price rejected by market limits ({oracleRejection}) — cranks will fail until it updates
+          </span>
+        </div>
+      )}
+
       {/* Stale warning banner */}
-      {level === "stale" && (
+      {level === "stale" && !oracleRejection && (
         <div
           className="flex items-center gap-1.5 px-2 py-1 text-[10px]"
           style={{
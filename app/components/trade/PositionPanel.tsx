@@ -11,6 +11,7 @@ import { AccountKind } from "@percolator/sdk";
 import { formatTokenAmount, formatUsd, formatLiqPrice } from "@/lib/format";
 import { useLivePrice } from "@/hooks/useLivePrice";
 import {
+  computeAccountHealth,
   computeMarkPnl,
   computeLiqPrice,
   computePnlPercent,
@@ -76,24 +77,28 @@ export const PositionPanel: FC<{ slabAddress: string }> = ({ slabAddress }) => {
   // just processed before price arrives), PnL/ROE cannot be computed reliably.
   const hasValidMark = currentPriceE6 > 0n;
 
+  // Same margin report the keeper liquidates against. Without engine state
+  // (mock markets, first render) fall back to the position-only math.
+  const maintenanceBps = params?.maintenanceMarginBps ?? 500n;
+  const health =
+    hasValidMark && engineState && params
+      ? computeAccountHealth(account, engineState, params, currentPriceE6)
+      : null;
+
   // Bug fix: Don't compute P&L with stale/zero price to avoid flash
-  const pnlTokens = hasValidMark ? computeMarkPnl(
-    account.positionSize,
-    account.entryPrice,
-    currentPriceE6,
-  ) : 0n;
+  const pnlTokens = health
+    ? health.unrealizedPnl
+    : hasValidMark
+      ? computeMarkPnl(account.positionSize, account.entryPrice, currentPriceE6)
+      : 0n;
   const pnlUsdRaw =
     priceUsd !== null && hasValidMark ? (Number(pnlTokens) / 10 ** decimals) * priceUsd : null;
   const pnlUsd = pnlUsdRaw !== null && Number.isFinite(pnlUsdRaw) ? pnlUsdRaw : null;
   const roe = hasValidMark ? computePnlPercent(pnlTokens, account.capital) : 0;
 
-  const maintenanceBps = params?.maintenanceMarginBps ?? 500n;
-  const liqPriceE6 = computeLiqPrice(
-    entryPriceE6,
-    account.capital,
-    account.positionSize,
-    maintenanceBps,
-  );
+  const liqPriceE6 = health
+    ? health.liquidationPriceE6
+    : computeLiqPrice(entryPriceE6, account.capital, account.positionSize, maintenanceBps);
 
   // Liq price danger color: amber when mark is within 10% of liq, red within 5%
   const liqPriceColor = (() => {
@@ -116,7 +121,9 @@ export const PositionPanel: FC<{ slabAddress: string }> = ({ slabAddress }) => {
   const pnlBarWidth = Math.min(100, Math.max(0, Math.abs(roe)));
 
   let marginHealthStr = "N/A";
-  if (hasPosition && absPosition > 0n) {
+  if (health?.marginRatioBps != null) {
+    marginHealthStr = `${(Number(health.marginRatioBps) / 100).toFixed(1)}%`;
+  } else if (hasPosition && absPosition > 0n) {
     const healthPct = Number((account.capital * 100n) / absPosition);
     marginHealthStr = `${healthPct.toFixed(1)}%`;
   }
@@ -94,6 +94,17 @@ async function fetchJupiterPriceE6(ca: string): Promise<bigint | null> {
   return null;
 }
 
+/** Per-update oracle price cap set on admin-oracle markets (0.01 bps; 10_000 = 1%). */
+export const ADMIN_ORACLE_PRICE_CAP_E2BPS = 10_000;
+
+/**
+ * Oracle price cap a market launches with. Only admin-oracle markets get one;
+ * Pyth and DEX (Hyperp) markets launch uncapped.
+ */
+export function launchOraclePriceCapE2bps(oracleType: "pyth" | "hyperp_ema" | "admin"): number {
+  return oracleType === "admin" ? ADMIN_ORACLE_PRICE_CAP_E2BPS : 0;
+}
+
 /** Minimum vault seed required by percolator-prog before InitMarket (500_000_000 raw tokens). */
 export const MIN_INIT_MARKET_SEED = 500_000_000n;
 
@@ -131,11 +142,11 @@ export interface CreateMarketParams {
   mainnetCA?: string;
   /** PERC-470: Oracle mode — determines how price is fed to the market */
   oracleMode?: "pyth" | "hyperp" | "admin";
-  /** PERC-470: DEX pool address for hyperp mode (PumpSwap/Raydium/Meteora) */
+  /** PERC-470: DEX pool address for hyperp mode (PumpSwap/Raydium/Meteora/Orca) */
   dexPoolAddress?: string;
-  /** PERC-470: Base vault address for hyperp mode (PumpSwap) */
+  /** PERC-470: Base vault address for hyperp mode (PumpSwap, Raydium CPMM/AMM v4) */
   dexBaseVault?: string;
-  /** PERC-470: Quote vault address for hyperp mode (PumpSwap) */
+  /** PERC-470: Quote vault address for hyperp mode (PumpSwap, Raydium CPMM/AMM v4) */
   dexQuoteVault?: string;
 }
 
@@ -604,7 +615,7 @@ export function useCreateMarket() {
             instructions.push(buildIx({ programId, keys: pushKeys, data: pushData }));
 
             // 3. SetOraclePriceCap — circuit breaker (10_000 = 1% max change per update)
-            const priceCapData = encodeSetOraclePriceCap({ maxChangeE2bps: BigInt(10_000) });
+            const priceCapData = encodeSetOraclePriceCap({ maxChangeE2bps: BigInt(ADMIN_ORACLE_PRICE_CAP_E2BPS) });
             const priceCapKeys = buildAccountMetas(ACCOUNTS_SET_ORACLE_PRICE_CAP, [
               wallet.publicKey, slabPk,
             ]);
@@ -641,7 +652,7 @@ export function useCreateMarket() {
               { pubkey: new PublicKey(params.dexPoolAddress), isSigner: false, isWritable: false },
               { pubkey: WELL_KNOWN.clock, isSigner: false, isWritable: false },
             ];
-            // PumpSwap pools need vault0 + vault1 as remaining accounts
+            // Reserve-priced pools (PumpSwap, Raydium CPMM/AMM v4) need vault0 + vault1 as remaining accounts
             if (params.dexBaseVault) {
               hyperpKeys.push({ pubkey: new PublicKey(params.dexBaseVault), isSigner: false, isWritable: false });
             }
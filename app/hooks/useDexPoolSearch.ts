@@ -4,7 +4,7 @@ import { useEffect, useState, useRef } from "react";
 
 export interface DexPoolResult {
   poolAddress: string;
-  dexId: string;       // "pumpswap" | "raydium" | "meteora"
+  dexId: string;       // "pumpswap" | "raydium" | "meteora" | "orca"
   pairLabel: string;   // e.g. "SOL / USDC"
   liquidityUsd: number;
   priceUsd: number;
@@ -14,7 +14,7 @@ import { SUPPORTED_DEX_IDS } from "@/lib/dex-constants";
 
 /**
  * Search DexScreener for DEX pools containing a given token mint.
- * Filters to supported DEXes (PumpSwap, Raydium, Meteora) and sorts by liquidity.
+ * Filters to supported DEXes (PumpSwap, Raydium, Meteora, Orca) and sorts by liquidity.
  */
 export function useDexPoolSearch(mint: string | null) {
   const [pools, setPools] = useState<DexPoolResult[]>([]);
@@ -0,0 +1,55 @@
+"use client";
+
+import { useEffect, useRef, useState } from "react";
+
+/**
+ * On-chain depth of a DEX oracle pool, in USD (the token's side of the pool),
+ * via /api/oracle/resolve/[ca]?pool=. Feeds the manipulation-cost check in
+ * the create flow.
+ *
+ * `depthUsd` is undefined until loaded and null when the pool can't be read.
+ * `priceUsd` is the token price the route resolved, for pools picked by
+ * address (no search result to take a price from).
+ */
+export function useOracleDepth(mint: string | null, pool: string | null) {
+  const [depthUsd, setDepthUsd] = useState<number | null | undefined>(undefined);
+  const [priceUsd, setPriceUsd] = useState<number | null>(null);
+  const [loading, setLoading] = useState(false);
+  const abortRef = useRef<AbortController | null>(null);
+
+  useEffect(() => {
+    setDepthUsd(undefined);
+    setPriceUsd(null);
+    if (!mint || mint.length < 32 || !pool || pool.length < 32) {
+      setLoading(false);
+      return;
+    }
+
+    abortRef.current?.abort();
+    const controller = new AbortController();
+    abortRef.current = controller;
+
+    setLoading(true);
+
+    (async () => {
+      try {
+        const resp = await fetch(`/api/oracle/resolve/${mint}?pool=${pool}`, { signal: controller.signal });
+        const json = resp.ok ? await resp.json() : null;
+        if (!controller.signal.aborted) {
+          setDepthUsd(typeof json?.poolDepthUsd === "number" ? json.poolDepthUsd : null);
+          setPriceUsd(typeof json?.price === "number" && json.price > 0 ? json.price : null);
+        }
+      } catch {
+        if (!controller.signal.aborted) setDepthUsd(null);
+      } finally {
+        if (!controller.signal.aborted) {
+          setLoading(false);
+        }
+      }
+    })();
+
+    return () => controller.abort();
+  }, [mint, pool]);
+
+  return { depthUsd, priceUsd, loading };
+}
@@ -1,7 +1,9 @@
 "use client";
 
 import { useEffect, useRef, useState, useCallback } from "react";
+import { derivePythPushOraclePDA, parsePythPriceUpdateV2, checkOracleFreshness } from "@percolator/sdk";
 import { useSlabState } from "@/components/providers/SlabProvider";
+import { useConnectionCompat } from "@/hooks/useWalletCompat";
 import { detectOracleMode, type OracleMode } from "@/lib/oraclePrice";
 
 export type FreshnessLevel = "fresh" | "aging" | "stale";
@@ -25,8 +27,18 @@ export interface OracleFreshnessState {
   ready: boolean;
   /** Last update timestamp (ms) */
   lastUpdateMs: number | null;
+  /** Pyth confidence as bps of price (pyth-pinned only) */
+  confBps: number | null;
+  /**
+   * Why the program would reject the current Pyth price at crank time
+   * (stale beyond maxStalenessSlots, or wider than confFilterBps); null if it's usable.
+   */
+  oracleRejection: string | null;
 }
 
+/** How often to re-read the Pyth price account */
+const PYTH_POLL_MS = 10_000;
+
 /** Freshness thresholds in seconds */
 const FRESH_THRESHOLD = 5;
 const AGING_THRESHOLD = 30;
@@ -63,12 +75,19 @@ function getModeLabel(mode: OracleMode): string {
  * Track oracle price freshness for the current market.
  *
  * For admin mode: uses authorityTimestamp (real unix timestamp).
- * For hyperp/pyth modes: tracks when lastEffectivePriceE6 last changed.
+ * For pyth mode: reads the Pyth PriceUpdateV2 account and uses its publish
+ * time, checked against the market's staleness and confidence limits.
+ * For hyperp mode (and pyth, until the account is read): tracks when the
+ * on-chain price last changed.
  */
 export function useOracleFreshness(): OracleFreshnessState {
   const { config } = useSlabState();
+  const { connection } = useConnectionCompat();
   const [elapsedSecs, setElapsedSecs] = useState(0);
   const [lastUpdateMs, setLastUpdateMs] = useState<number | null>(null);
+  const [confBps, setConfBps] = useState<number | null>(null);
+  const [oracleRejection, setOracleRejection] = useState<string | null>(null);
+  const pythReadRef = useRef(false);
   const prevPriceRef = useRef<bigint | null>(null);
   const tickRef = useRef<ReturnType<typeof setInterval>>(undefined);
 
@@ -112,7 +131,10 @@ export function useOracleFreshness(): OracleFreshnessState {
       const currentPrice = currentMode === "hyperp"
         ? config.authorityPriceE6
         : config.lastEffectivePriceE6;
-      if (prevPriceRef.current !== null && currentPrice !== prevPriceRef.current) {
+      if (currentMode === "pyth-pinned" && pythReadRef.current) {
+        // Publish time from the Pyth account is authoritative once read
+        prevPriceRef.current = currentPrice;
+      } else if (prevPriceRef.current !== null && currentPrice !== prevPriceRef.current) {
         setLastUpdateMs(Date.now());
       } else if (prevPriceRef.current === null && currentPrice > 0n) {
         // First load — assume relatively fresh
@@ -122,6 +144,44 @@ export function useOracleFreshness(): OracleFreshnessState {
     }
   }, [config]);
 
+  // Pyth-pinned: read the price account for its publish time and confidence
+  const feedHex = config && mode === "pyth-pinned"
+    ? Array.from(config.indexFeedId.toBytes()).map((b) => b.toString(16).padStart(2, "0")).join("")
+    : null;
+  const maxStalenessSecs = config?.maxStalenessSlots;
+  const confFilterBps = config?.confFilterBps;
+
+  useEffect(() => {
+    pythReadRef.current = false;
+    setConfBps(null);
+    setOracleRejection(null);
+    if (!feedHex) return;
+
+    let cancelled = false;
+    const oracleKey = derivePythPushOraclePDA(feedHex)[0];
+
+    const read = async () => {
+      try {
+        const info = await connection.getAccountInfo(oracleKey);
+        if (cancelled || !info) return;
+        const price = parsePythPriceUpdateV2(new Uint8Array(info.data));
+        const check = checkOracleFreshness(price, { maxStalenessSecs, confFilterBps });
+        pythReadRef.current = true;
+        setLastUpdateMs(Number(price.publishTime) * 1000);
+        setConfBps(check.confBps);
+        setOracleRejection(check.ok ? null : check.reason);
+      } catch {
+        // Keep the price-change fallback
+      }
+    };
+    read();
+    const interval = setInterval(read, PYTH_POLL_MS);
+    return () => {
+      cancelled = true;
+      clearInterval(interval);
+    };
+  }, [connection, feedHex, maxStalenessSecs, confFilterBps]);
+
   // Tick every second to update elapsed time
   useEffect(() => {
     if (lastUpdateMs === null) return;
@@ -148,5 +208,7 @@ export function useOracleFreshness(): OracleFreshnessState {
     publisherTotal: null,
     ready: mode !== null && lastUpdateMs !== null,
     lastUpdateMs,
+    confBps,
+    oracleRejection,
   };
 }
@@ -1,4 +1,10 @@
 import type { ValidationError } from "@/components/create/ValidationSummary";
+import {
+  assessManipulationRisk,
+  marketOiCapMultiplierBps,
+  maxOpenInterestUsd,
+  type ManipulationRisk,
+} from "@percolator/sdk";
 
 /**
  * Minimum LP collateral thresholds per token decimals.
@@ -31,6 +37,89 @@ export interface CreateFormValues {
   decimals: number;
 }
 
+export interface OracleManipulationInput {
+  /** USD value of the token's side of the oracle pool; undefined while loading, null if unreadable */
+  oraclePoolDepthUsd: number | null | undefined;
+  depthLoading?: boolean;
+  /** Token USD price, to value LP collateral for the OI cap */
+  tokenPriceUsd: number | null | undefined;
+  lpCollateral: string;
+  initialMarginBps: number;
+  /** Per-update oracle price cap in 0.01 bps the market launches with; 0 / undefined = none */
+  oraclePriceCapE2bps?: number;
+  /** OI cap multiplier the market launches with; 0 / undefined = none (max leverage bounds OI) */
+  oiCapMultiplierBps?: number;
+}
+
+/**
+ * Manipulation risk of a DEX-priced market: cost of moving the pool by the
+ * initial margin vs what the market's max OI earns from it.
+ * Null when the inputs aren't known yet.
+ */
+export function assessOracleManipulation(values: OracleManipulationInput): ManipulationRisk | null {
+  const { oraclePoolDepthUsd, tokenPriceUsd, lpCollateral, initialMarginBps, oraclePriceCapE2bps } = values;
+  const lpNum = parseFloat(lpCollateral);
+  if (oraclePoolDepthUsd == null || !tokenPriceUsd || !(lpNum > 0) || initialMarginBps <= 0) return null;
+  const oiCapBps = marketOiCapMultiplierBps(values.oiCapMultiplierBps ?? 0, initialMarginBps);
+  return assessManipulationRisk({
+    poolDepthUsd: oraclePoolDepthUsd,
+    initialMarginBps,
+    maxOiUsd: maxOpenInterestUsd(lpNum * tokenPriceUsd, oiCapBps),
+    oraclePriceCapE2bps,
+  });
+}
+
+export interface OracleManipulationCheck {
+  /** A DEX-priced market may launch only on `ok` */
+  status: "pending" | "unknown" | "blocked" | "ok";
+  risk: ManipulationRisk | null;
+  /** Why launch is held, or a warning on a high-risk pass */
+  message: string | null;
+}
+
+/**
+ * Launch gate for a DEX-priced market. Fails closed: while the pool depth is
+ * loading, when it can't be read, or when the token has no USD price, the
+ * manipulation cost is unknown and launch is held.
+ */
+export function checkOracleManipulation(values: OracleManipulationInput): OracleManipulationCheck {
+  if (values.depthLoading || values.oraclePoolDepthUsd === undefined) {
+    return { status: "pending", risk: null, message: "Checking oracle pool depth…" };
+  }
+  if (values.oraclePoolDepthUsd === null) {
+    return {
+      status: "unknown",
+      risk: null,
+      message: "Couldn't read the pool's reserves, so its manipulation cost is unknown. Pick a PumpSwap or Raydium pool.",
+    };
+  }
+  if (!values.tokenPriceUsd) {
+    return {
+      status: "unknown",
+      risk: null,
+      message: "No USD price for this token, so the market's max OI can't be valued against the pool.",
+    };
+  }
+
+  const risk = assessOracleManipulation(values);
+  if (!risk) return { status: "pending", risk: null, message: "Set LP collateral and margin to check the oracle pool." };
+  if (risk.blocked) {
+    return {
+      status: "blocked",
+      risk,
+      message: `Moving this pool ${(risk.moveBps / 100).toFixed(2)}% costs ~$${Math.round(risk.costUsd).toLocaleString()}, but the market's max OI would earn ~$${Math.round(risk.maxProfitUsd).toLocaleString()} from it. Lower leverage or LP collateral, or use a deeper pool.`,
+    };
+  }
+  return {
+    status: "ok",
+    risk,
+    message:
+      risk.level === "high"
+        ? `Pool is only ${risk.safetyMultiple.toFixed(1)}x more expensive to move than the market's max OI can earn. Consider lower leverage.`
+        : null,
+  };
+}
+
 /**
  * Comprehensive validation for the market creation form.
  * Returns all errors/warnings at once so users can fix everything before submitting.
@@ -4,4 +4,4 @@
  */
 
 /** DEX IDs supported for Hyperp EMA oracle mode. */
-export const SUPPORTED_DEX_IDS = new Set(["pumpswap", "raydium", "meteora"]);
+export const SUPPORTED_DEX_IDS = new Set(["pumpswap", "raydium", "meteora", "orca"]);
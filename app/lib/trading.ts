@@ -12,4 +12,6 @@ export {
   computeFundingRateAnnualized,
   computeRequiredMargin,
   computeMaxLeverage,
+  computeAccountHealth,
 } from "@percolator/sdk";
+export type { AccountHealth } from "@percolator/sdk";
@@ -0,0 +1,120 @@
+//! `Account` decoding and the used-index bitmap.
+//!
+//! The first 240 bytes of an account are identical in V0 and V1. V1 appends
+//! `last_partial_liquidation_slot` (u64) at offset 240.
+
+use crate::layout::SlabLayout;
+use crate::read::{i128_at, pubkey_at, u128_at, u64_at, u8_at};
+
+const ACCT_ACCOUNT_ID_OFF: usize = 0;
+const ACCT_CAPITAL_OFF: usize = 8;
+const ACCT_KIND_OFF: usize = 24;
+const ACCT_PNL_OFF: usize = 32;
+const ACCT_RESERVED_PNL_OFF: usize = 48;
+const ACCT_WARMUP_STARTED_OFF: usize = 56;
+const ACCT_WARMUP_SLOPE_OFF: usize = 64;
+const ACCT_POSITION_SIZE_OFF: usize = 80;
+const ACCT_ENTRY_PRICE_OFF: usize = 96;
+const ACCT_FUNDING_INDEX_OFF: usize = 104;
+const ACCT_MATCHER_PROGRAM_OFF: usize = 120;
+const ACCT_MATCHER_CONTEXT_OFF: usize = 152;
+const ACCT_OWNER_OFF: usize = 184;
+const ACCT_FEE_CREDITS_OFF: usize = 216;
+const ACCT_LAST_FEE_SLOT_OFF: usize = 232;
+const ACCT_LAST_PARTIAL_LIQ_SLOT_OFF: usize = 240;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountKind {
+    User = 0,
+    Lp = 1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Account<'a> {
+    pub kind: AccountKind,
+    pub account_id: u64,
+    pub capital: u128,
+    pub pnl: i128,
+    pub reserved_pnl: u64,
+    pub warmup_started_at_slot: u64,
+    pub warmup_slope_per_step: u128,
+    pub position_size: i128,
+    pub entry_price: u64,
+    pub funding_index: i128,
+    pub matcher_program: &'a [u8; 32],
+    pub matcher_context: &'a [u8; 32],
+    pub owner: &'a [u8; 32],
+    pub fee_credits: i128,
+    pub last_fee_slot: u64,
+    /// V1 only; `None` for 240-byte V0 records.
+    pub last_partial_liquidation_slot: Option<u64>,
+}
+
+/// Decode one account record. `rec` must be at least 240 bytes; a 248-byte
+/// V1 record also yields `last_partial_liquidation_slot`.
+pub(crate) fn decode_account(rec: &[u8]) -> Account<'_> {
+    Account {
+        // Any byte other than 1 is treated as User, same as the TS SDK.
+        kind: if u8_at(rec, ACCT_KIND_OFF) == 1 {
+            AccountKind::Lp
+        } else {
+            AccountKind::User
+        },
+        account_id: u64_at(rec, ACCT_ACCOUNT_ID_OFF),
+        capital: u128_at(rec, ACCT_CAPITAL_OFF),
+        pnl: i128_at(rec, ACCT_PNL_OFF),
+        reserved_pnl: u64_at(rec, ACCT_RESERVED_PNL_OFF),
+        warmup_started_at_slot: u64_at(rec, ACCT_WARMUP_STARTED_OFF),
+        warmup_slope_per_step: u128_at(rec, ACCT_WARMUP_SLOPE_OFF),
+        position_size: i128_at(rec, ACCT_POSITION_SIZE_OFF),
+        entry_price: u64_at(rec, ACCT_ENTRY_PRICE_OFF),
+        funding_index: i128_at(rec, ACCT_FUNDING_INDEX_OFF),
+        matcher_program: pubkey_at(rec, ACCT_MATCHER_PROGRAM_OFF),
+        matcher_context: pubkey_at(rec, ACCT_MATCHER_CONTEXT_OFF),
+        owner: pubkey_at(rec, ACCT_OWNER_OFF),
+        fee_credits: i128_at(rec, ACCT_FEE_CREDITS_OFF),
+        last_fee_slot: u64_at(rec, ACCT_LAST_FEE_SLOT_OFF),
+        last_partial_liquidation_slot: (rec.len() >= ACCT_LAST_PARTIAL_LIQ_SLOT_OFF + 8)
+            .then(|| u64_at(rec, ACCT_LAST_PARTIAL_LIQ_SLOT_OFF)),
+    }
+}
+
+/// Iterator over set bits of the used-account bitmap, in ascending order.
+#[derive(Debug, Clone)]
+pub struct UsedIndices<'a> {
+    words: &'a [u8],
+    word: usize,
+    bits: u64,
+}
+
+impl<'a> UsedIndices<'a> {
+    pub(crate) fn new(data: &'a [u8], layout: &SlabLayout) -> Self {
+        let start = layout.engine_off + layout.engine_bitmap_off;
+        let words = &data[start..start + layout.bitmap_words * 8];
+        let bits = if words.is_empty() { 0 } else { u64_at(words, 0) };
+        UsedIndices {
+            words,
+            word: 0,
+            bits,
+        }
+    }
+}
+
+impl Iterator for UsedIndices<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if self.bits != 0 {
+                let bit = self.bits.trailing_zeros() as usize;
+                self.bits &= self.bits - 1;
+                return Some(self.word * 64 + bit);
+            }
+            self.word += 1;
+            if self.word * 8 >= self.words.len() {
+                return None;
+            }
+            self.bits = u64_at(self.words, self.word * 8);
+        }
+    }
+}
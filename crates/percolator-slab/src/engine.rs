@@ -0,0 +1,177 @@
+//! `RiskParams` and `RiskEngine` state decoding.
+
+use crate::error::DecodeError;
+use crate::layout::{SlabLayout, V0_ENGINE_OFF, V0_ENGINE_PARAMS_OFF, V0_PARAMS_SIZE};
+use crate::read::{i128_at, i64_at, u128_at, u16_at, u64_at, u8_at};
+
+/// Basic params present in both layouts.
+const PARAMS_BASE_LEN: usize = 56;
+/// V1 extended params end after `min_liquidation_abs` (u128 at 128).
+const PARAMS_EXTENDED_LEN: usize = 144;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RiskParams {
+    pub warmup_period_slots: u64,
+    pub maintenance_margin_bps: u64,
+    pub initial_margin_bps: u64,
+    pub trading_fee_bps: u64,
+    pub max_accounts: u64,
+    pub new_account_fee: u128,
+    // V1-only — zero on V0 slabs.
+    pub risk_reduction_threshold: u128,
+    pub maintenance_fee_per_slot: u128,
+    pub max_crank_staleness_slots: u64,
+    pub liquidation_fee_bps: u64,
+    pub liquidation_fee_cap: u128,
+    pub liquidation_buffer_bps: u64,
+    pub min_liquidation_abs: u128,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InsuranceFund {
+    pub balance: u128,
+    pub fee_revenue: u128,
+    pub isolated_balance: u128,
+    pub isolation_bps: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EngineState {
+    pub vault: u128,
+    pub insurance_fund: InsuranceFund,
+    pub current_slot: u64,
+    pub funding_index_qpb_e6: i128,
+    pub last_funding_slot: u64,
+    pub funding_rate_bps_per_slot_last: i64,
+    pub last_crank_slot: u64,
+    pub max_crank_staleness_slots: u64,
+    pub total_open_interest: u128,
+    pub long_oi: u128,
+    pub short_oi: u128,
+    pub c_tot: u128,
+    pub pnl_pos_tot: u128,
+    pub liq_cursor: u16,
+    pub gc_cursor: u16,
+    pub last_sweep_start_slot: u64,
+    pub last_sweep_complete_slot: u64,
+    pub crank_cursor: u16,
+    pub sweep_start_idx: u16,
+    pub lifetime_liquidations: u64,
+    pub lifetime_force_closes: u64,
+    pub net_lp_pos: i128,
+    pub lp_sum_abs: u128,
+    pub lp_max_abs: u128,
+    pub lp_max_abs_sweep: u128,
+    pub emergency_oi_mode: bool,
+    pub emergency_start_slot: u64,
+    pub last_breaker_slot: u64,
+    pub num_used_accounts: u16,
+    pub next_account_id: u64,
+    pub mark_price_e6: u64,
+}
+
+/// Parse `RiskParams`. Without a layout the V0 offsets are assumed; V1-only
+/// params default to zero when the layout's params block is too small.
+pub fn parse_params(data: &[u8], layout: Option<&SlabLayout>) -> Result<RiskParams, DecodeError> {
+    let (engine_off, params_off, params_size) = layout.map_or(
+        (V0_ENGINE_OFF, V0_ENGINE_PARAMS_OFF, V0_PARAMS_SIZE),
+        |l| (l.engine_off, l.engine_params_off, l.params_size),
+    );
+    let base = engine_off + params_off;
+    let extended = params_size >= PARAMS_EXTENDED_LEN;
+    let need = base
+        + if extended {
+            PARAMS_EXTENDED_LEN
+        } else {
+            PARAMS_BASE_LEN
+        };
+    if data.len() < need {
+        return Err(DecodeError::TooShort {
+            section: "RiskParams",
+            len: data.len(),
+            need,
+        });
+    }
+
+    let mut p = RiskParams {
+        warmup_period_slots: u64_at(data, base),
+        maintenance_margin_bps: u64_at(data, base + 8),
+        initial_margin_bps: u64_at(data, base + 16),
+        trading_fee_bps: u64_at(data, base + 24),
+        max_accounts: u64_at(data, base + 32),
+        new_account_fee: u128_at(data, base + 40),
+        ..RiskParams::default()
+    };
+
+    if extended {
+        p.risk_reduction_threshold = u128_at(data, base + 56);
+        p.maintenance_fee_per_slot = u128_at(data, base + 72);
+        p.max_crank_staleness_slots = u64_at(data, base + 88);
+        p.liquidation_fee_bps = u64_at(data, base + 96);
+        p.liquidation_fee_cap = u128_at(data, base + 104);
+        p.liquidation_buffer_bps = u64_at(data, base + 120);
+        p.min_liquidation_abs = u128_at(data, base + 128);
+    }
+
+    Ok(p)
+}
+
+/// Parse `RiskEngine` state (everything except the accounts array).
+pub fn parse_engine(data: &[u8], layout: &SlabLayout) -> Result<EngineState, DecodeError> {
+    let base = layout.engine_off;
+    let need = base + layout.engine_next_account_id_off() + 8;
+    if data.len() < need {
+        return Err(DecodeError::TooShort {
+            section: "engine",
+            len: data.len(),
+            need,
+        });
+    }
+
+    let opt_u128 = |off: Option<usize>| off.map_or(0, |o| u128_at(data, base + o));
+    let opt_u64 = |off: Option<usize>| off.map_or(0, |o| u64_at(data, base + o));
+    let ins = base + layout.engine_insurance_off;
+
+    Ok(EngineState {
+        vault: u128_at(data, base),
+        insurance_fund: InsuranceFund {
+            balance: u128_at(data, ins),
+            fee_revenue: u128_at(data, ins + 16),
+            isolated_balance: opt_u128(layout.engine_insurance_isolated_off),
+            isolation_bps: layout
+                .engine_insurance_isolation_bps_off
+                .map_or(0, |o| u16_at(data, base + o)),
+        },
+        current_slot: u64_at(data, base + layout.engine_current_slot_off),
+        funding_index_qpb_e6: i128_at(data, base + layout.engine_funding_index_off),
+        last_funding_slot: u64_at(data, base + layout.engine_last_funding_slot_off),
+        funding_rate_bps_per_slot_last: i64_at(data, base + layout.engine_funding_rate_bps_off),
+        last_crank_slot: u64_at(data, base + layout.engine_last_crank_slot_off),
+        max_crank_staleness_slots: u64_at(data, base + layout.engine_max_crank_staleness_off),
+        total_open_interest: u128_at(data, base + layout.engine_total_oi_off),
+        long_oi: opt_u128(layout.engine_long_oi_off),
+        short_oi: opt_u128(layout.engine_short_oi_off),
+        c_tot: u128_at(data, base + layout.engine_c_tot_off),
+        pnl_pos_tot: u128_at(data, base + layout.engine_pnl_pos_tot_off),
+        liq_cursor: u16_at(data, base + layout.engine_liq_cursor_off),
+        gc_cursor: u16_at(data, base + layout.engine_gc_cursor_off),
+        last_sweep_start_slot: u64_at(data, base + layout.engine_last_sweep_start_off),
+        last_sweep_complete_slot: u64_at(data, base + layout.engine_last_sweep_complete_off),
+        crank_cursor: u16_at(data, base + layout.engine_crank_cursor_off),
+        sweep_start_idx: u16_at(data, base + layout.engine_sweep_start_idx_off),
+        lifetime_liquidations: u64_at(data, base + layout.engine_lifetime_liquidations_off),
+        lifetime_force_closes: u64_at(data, base + layout.engine_lifetime_force_closes_off),
+        net_lp_pos: i128_at(data, base + layout.engine_net_lp_pos_off),
+        lp_sum_abs: u128_at(data, base + layout.engine_lp_sum_abs_off),
+        lp_max_abs: u128_at(data, base + layout.engine_lp_max_abs_off),
+        lp_max_abs_sweep: u128_at(data, base + layout.engine_lp_max_abs_sweep_off),
+        emergency_oi_mode: layout
+            .engine_emergency_oi_mode_off
+            .is_some_and(|o| u8_at(data, base + o) != 0),
+        emergency_start_slot: opt_u64(layout.engine_emergency_start_slot_off),
+        last_breaker_slot: opt_u64(layout.engine_last_breaker_slot_off),
+        num_used_accounts: u16_at(data, base + layout.engine_num_used_off()),
+        next_account_id: u64_at(data, base + layout.engine_next_account_id_off()),
+        mark_price_e6: opt_u64(layout.engine_mark_price_off),
+    })
+}
@@ -0,0 +1,47 @@
+use core::fmt;
+
+/// Errors returned while decoding a slab.
+///
+/// Messages intentionally match the TypeScript SDK (`slab.ts`) so log
+/// scrapers and alerts keep working regardless of which decoder ran.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Buffer ends before the named section.
+    TooShort {
+        section: &'static str,
+        len: usize,
+        need: usize,
+    },
+    /// First 8 bytes are not `PERCOLAT`.
+    InvalidMagic { got: u64 },
+    /// Data length matches no known V0/V1 tier.
+    UnknownLayout { len: usize },
+    /// Account index outside `[0, max_account_index)`.
+    AccountIndexOutOfRange { idx: usize, max: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::TooShort { section, len, need } => {
+                write!(f, "Slab data too short for {section}: {len} < {need}")
+            }
+            DecodeError::InvalidMagic { got } => write!(
+                f,
+                "Invalid slab magic: expected {:x}, got {:x}",
+                crate::MAGIC,
+                got
+            ),
+            DecodeError::UnknownLayout { len } => write!(
+                f,
+                "Unrecognized slab data length: {len}. Cannot determine layout version."
+            ),
+            DecodeError::AccountIndexOutOfRange { idx, max } => {
+                let last = *max as i64 - 1;
+                write!(f, "Account index out of range: {idx} (max: {last})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
@@ -0,0 +1,211 @@
+//! `SlabHeader` and `MarketConfig` decoding.
+
+use crate::error::DecodeError;
+use crate::layout::{SlabLayout, V0_CONFIG_LEN, V0_HEADER_LEN, V0_RESERVED_OFF};
+use crate::read::{i64_at, pubkey_at, u128_at, u16_at, u24_at, u32_at, u64_at, u8_at};
+use crate::MAGIC;
+
+/// Flag bits in header `_padding[0]` at offset 13.
+const FLAG_RESOLVED: u8 = 1 << 0;
+const FLAG_PAUSED: u8 = 1 << 1;
+
+/// Config bytes present in both layouts (collateral mint .. max_pnl_cap).
+const CONFIG_BASE_LEN: usize = 368;
+/// Config bytes up to and including the PERC-622 oracle phase fields.
+const CONFIG_FULL_LEN: usize = 432;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlabHeader<'a> {
+    pub magic: u64,
+    pub version: u32,
+    pub bump: u8,
+    pub flags: u8,
+    pub resolved: bool,
+    pub paused: bool,
+    pub admin: &'a [u8; 32],
+    pub nonce: u64,
+    pub last_thr_update_slot: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketConfig<'a> {
+    pub collateral_mint: &'a [u8; 32],
+    pub vault_pubkey: &'a [u8; 32],
+    pub index_feed_id: &'a [u8; 32],
+    pub max_staleness_slots: u64,
+    pub conf_filter_bps: u16,
+    pub vault_authority_bump: u8,
+    pub invert: u8,
+    pub unit_scale: u32,
+    pub funding_horizon_slots: u64,
+    pub funding_k_bps: u64,
+    pub funding_inv_scale_notional_e6: u128,
+    pub funding_max_premium_bps: i64,
+    pub funding_max_bps_per_slot: i64,
+    pub funding_premium_weight_bps: u64,
+    pub funding_settlement_interval_slots: u64,
+    pub funding_premium_dampening_e6: u64,
+    pub funding_premium_max_bps_per_slot: u64,
+    pub thresh_floor: u128,
+    pub thresh_risk_bps: u64,
+    pub thresh_update_interval_slots: u64,
+    pub thresh_step_bps: u64,
+    pub thresh_alpha_bps: u64,
+    pub thresh_min: u128,
+    pub thresh_max: u128,
+    pub thresh_min_step: u128,
+    pub oracle_authority: &'a [u8; 32],
+    pub authority_price_e6: u64,
+    pub authority_timestamp: i64,
+    pub oracle_price_cap_e2bps: u64,
+    pub last_effective_price_e6: u64,
+    pub oi_cap_multiplier_bps: u64,
+    pub max_pnl_cap: u64,
+    pub adaptive_funding_enabled: bool,
+    pub adaptive_scale_bps: u16,
+    pub adaptive_max_funding_bps: u64,
+    pub market_created_slot: u64,
+    pub oi_ramp_slots: u64,
+    pub resolved_slot: u64,
+    pub insurance_isolation_bps: u16,
+    /// PERC-622: oracle phase (0=Nascent, 1=Growing, 2=Mature), clamped to 2.
+    pub oracle_phase: u8,
+    /// PERC-622: cumulative trade volume in e6 format.
+    pub cumulative_volume_e6: u64,
+    /// PERC-622: slots from market creation to Phase 2 entry (u24).
+    pub phase2_delta_slots: u32,
+}
+
+/// Parse the slab header. The header prefix is layout-independent; only the
+/// `_reserved` offset (nonce, last_thr_update_slot) moves between V0 and V1.
+pub fn parse_header<'a>(
+    data: &'a [u8],
+    layout: Option<&SlabLayout>,
+) -> Result<SlabHeader<'a>, DecodeError> {
+    if data.len() < V0_HEADER_LEN {
+        return Err(DecodeError::TooShort {
+            section: "header",
+            len: data.len(),
+            need: V0_HEADER_LEN,
+        });
+    }
+
+    let magic = u64_at(data, 0);
+    if magic != MAGIC {
+        return Err(DecodeError::InvalidMagic { got: magic });
+    }
+
+    let roff = layout.map_or(V0_RESERVED_OFF, |l| l.reserved_off);
+    if data.len() < roff + 16 {
+        return Err(DecodeError::TooShort {
+            section: "header",
+            len: data.len(),
+            need: roff + 16,
+        });
+    }
+
+    let flags = u8_at(data, 13);
+    Ok(SlabHeader {
+        magic,
+        version: u32_at(data, 8),
+        bump: u8_at(data, 12),
+        flags,
+        resolved: flags & FLAG_RESOLVED != 0,
+        paused: flags & FLAG_PAUSED != 0,
+        admin: pubkey_at(data, 16),
+        nonce: u64_at(data, roff),
+        last_thr_update_slot: u64_at(data, roff + 8),
+    })
+}
+
+/// Parse the market config. Without a layout the V0 offsets are assumed,
+/// matching `parseConfig` in the TS SDK. Fields past the end of a V0 config
+/// default to zero.
+pub fn parse_config<'a>(
+    data: &'a [u8],
+    layout: Option<&SlabLayout>,
+) -> Result<MarketConfig<'a>, DecodeError> {
+    let (base, config_len) = layout.map_or((V0_HEADER_LEN, V0_CONFIG_LEN), |l| {
+        (l.config_offset, l.config_len)
+    });
+
+    let need = base + config_len.min(CONFIG_FULL_LEN);
+    if data.len() < need {
+        return Err(DecodeError::TooShort {
+            section: "config",
+            len: data.len(),
+            need,
+        });
+    }
+
+    let at = |rel: usize| base + rel;
+    let remaining = config_len.saturating_sub(CONFIG_BASE_LEN);
+
+    let mut cfg = MarketConfig {
+        collateral_mint: pubkey_at(data, at(0)),
+        vault_pubkey: pubkey_at(data, at(32)),
+        index_feed_id: pubkey_at(data, at(64)),
+        max_staleness_slots: u64_at(data, at(96)),
+        conf_filter_bps: u16_at(data, at(104)),
+        vault_authority_bump: u8_at(data, at(106)),
+        invert: u8_at(data, at(107)),
+        unit_scale: u32_at(data, at(108)),
+        funding_horizon_slots: u64_at(data, at(112)),
+        funding_k_bps: u64_at(data, at(120)),
+        funding_inv_scale_notional_e6: u128_at(data, at(128)),
+        funding_max_premium_bps: i64_at(data, at(144)),
+        funding_max_bps_per_slot: i64_at(data, at(152)),
+        funding_premium_weight_bps: u64_at(data, at(160)),
+        funding_settlement_interval_slots: u64_at(data, at(168)),
+        funding_premium_dampening_e6: u64_at(data, at(176)),
+        funding_premium_max_bps_per_slot: u64_at(data, at(184)),
+        thresh_floor: u128_at(data, at(192)),
+        thresh_risk_bps: u64_at(data, at(208)),
+        thresh_update_interval_slots: u64_at(data, at(216)),
+        thresh_step_bps: u64_at(data, at(224)),
+        thresh_alpha_bps: u64_at(data, at(232)),
+        thresh_min: u128_at(data, at(240)),
+        thresh_max: u128_at(data, at(256)),
+        thresh_min_step: u128_at(data, at(272)),
+        oracle_authority: pubkey_at(data, at(288)),
+        authority_price_e6: u64_at(data, at(320)),
+        authority_timestamp: i64_at(data, at(328)),
+        oracle_price_cap_e2bps: u64_at(data, at(336)),
+        last_effective_price_e6: u64_at(data, at(344)),
+        oi_cap_multiplier_bps: u64_at(data, at(352)),
+        max_pnl_cap: u64_at(data, at(360)),
+        adaptive_funding_enabled: false,
+        adaptive_scale_bps: 0,
+        adaptive_max_funding_bps: 0,
+        market_created_slot: 0,
+        oi_ramp_slots: 0,
+        resolved_slot: 0,
+        insurance_isolation_bps: 0,
+        oracle_phase: 0,
+        cumulative_volume_e6: 0,
+        phase2_delta_slots: 0,
+    };
+
+    if remaining >= 40 {
+        // Adaptive funding, maturity ramp, auto-unresolve (+ 8 reserved bytes)
+        cfg.adaptive_funding_enabled = u8_at(data, at(368)) != 0;
+        cfg.adaptive_scale_bps = u16_at(data, at(370));
+        cfg.adaptive_max_funding_bps = u64_at(data, at(376));
+        cfg.market_created_slot = u64_at(data, at(384));
+        cfg.oi_ramp_slots = u64_at(data, at(392));
+        cfg.resolved_slot = u64_at(data, at(400));
+    }
+    if remaining >= 42 {
+        cfg.insurance_isolation_bps = u16_at(data, at(416));
+    }
+    if remaining >= 56 {
+        // PERC-622 fields live in _insurance_isolation_padding (starts at 418):
+        // [0..2] mark_oracle_weight, [2] oracle_phase, [3..11] cumulative_volume,
+        // [11..14] phase2_delta (u24)
+        cfg.oracle_phase = u8_at(data, at(420)).min(2);
+        cfg.cumulative_volume_e6 = u64_at(data, at(421));
+        cfg.phase2_delta_slots = u24_at(data, at(429));
+    }
+
+    Ok(cfg)
+}
@@ -0,0 +1,249 @@
+//! Slab layout detection — mirrors `detectSlabLayout` / `buildLayout` in
+//! `packages/core/src/solana/slab.ts`.
+//!
+//! V0 (deployed devnet): HEADER=72, CONFIG=408, ENGINE_OFF=480, ACCOUNT_SIZE=240
+//! V1 (future upgrade):  HEADER=104, CONFIG=536, ENGINE_OFF=640, ACCOUNT_SIZE=248
+//!
+//! All engine field offsets are relative to `engine_off`. Fields that only
+//! exist in V1 are `None` on V0 slabs.
+
+/// Slab tiers compiled into the on-chain program (`MAX_ACCOUNTS`).
+pub const TIERS: [usize; 4] = [64, 256, 1024, 4096];
+
+/// num_used(u16) + pad(6) + next_account_id(u64) + free_head(u16)
+const POST_BITMAP: usize = 18;
+
+pub const V0_HEADER_LEN: usize = 72;
+pub const V0_CONFIG_LEN: usize = 408;
+pub const V0_ENGINE_OFF: usize = 480;
+pub const V0_ACCOUNT_SIZE: usize = 240;
+pub const V0_RESERVED_OFF: usize = 48;
+pub const V0_ENGINE_PARAMS_OFF: usize = 48;
+pub const V0_PARAMS_SIZE: usize = 56;
+pub const V0_ENGINE_BITMAP_OFF: usize = 320;
+
+pub const V1_HEADER_LEN: usize = 104;
+pub const V1_CONFIG_LEN: usize = 536;
+pub const V1_ENGINE_OFF: usize = 640;
+pub const V1_ACCOUNT_SIZE: usize = 248;
+pub const V1_RESERVED_OFF: usize = 80;
+pub const V1_ENGINE_PARAMS_OFF: usize = 72;
+pub const V1_PARAMS_SIZE: usize = 288;
+pub const V1_ENGINE_BITMAP_OFF: usize = 656;
+
+/// Struct layout revision. Not the same as the header `version` field,
+/// which is 1 on every slab deployed so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutVersion {
+    V0,
+    V1,
+}
+
+impl LayoutVersion {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            LayoutVersion::V0 => 0,
+            LayoutVersion::V1 => 1,
+        }
+    }
+}
+
+/// Full slab layout descriptor. Returned by [`detect_slab_layout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlabLayout {
+    pub version: LayoutVersion,
+    pub header_len: usize,
+    pub config_offset: usize,
+    pub config_len: usize,
+    pub reserved_off: usize,
+    pub engine_off: usize,
+    pub account_size: usize,
+    pub max_accounts: usize,
+    pub bitmap_words: usize,
+    /// Absolute offset of the accounts array in the slab.
+    pub accounts_off: usize,
+
+    pub engine_insurance_off: usize,
+    pub engine_params_off: usize,
+    pub params_size: usize,
+    pub engine_current_slot_off: usize,
+    pub engine_funding_index_off: usize,
+    pub engine_last_funding_slot_off: usize,
+    pub engine_funding_rate_bps_off: usize,
+    pub engine_mark_price_off: Option<usize>,
+    pub engine_last_crank_slot_off: usize,
+    pub engine_max_crank_staleness_off: usize,
+    pub engine_total_oi_off: usize,
+    pub engine_long_oi_off: Option<usize>,
+    pub engine_short_oi_off: Option<usize>,
+    pub engine_c_tot_off: usize,
+    pub engine_pnl_pos_tot_off: usize,
+    pub engine_liq_cursor_off: usize,
+    pub engine_gc_cursor_off: usize,
+    pub engine_last_sweep_start_off: usize,
+    pub engine_last_sweep_complete_off: usize,
+    pub engine_crank_cursor_off: usize,
+    pub engine_sweep_start_idx_off: usize,
+    pub engine_lifetime_liquidations_off: usize,
+    pub engine_lifetime_force_closes_off: usize,
+    pub engine_net_lp_pos_off: usize,
+    pub engine_lp_sum_abs_off: usize,
+    pub engine_lp_max_abs_off: usize,
+    pub engine_lp_max_abs_sweep_off: usize,
+    pub engine_emergency_oi_mode_off: Option<usize>,
+    pub engine_emergency_start_slot_off: Option<usize>,
+    pub engine_last_breaker_slot_off: Option<usize>,
+    pub engine_bitmap_off: usize,
+
+    pub engine_insurance_isolated_off: Option<usize>,
+    pub engine_insurance_isolation_bps_off: Option<usize>,
+}
+
+impl SlabLayout {
+    /// Offset (relative to `engine_off`) of `num_used_accounts`, right after the bitmap.
+    pub fn engine_num_used_off(&self) -> usize {
+        self.engine_bitmap_off + self.bitmap_words * 8
+    }
+
+    /// Offset (relative to `engine_off`) of `next_account_id`, 8-byte aligned.
+    pub fn engine_next_account_id_off(&self) -> usize {
+        align8(self.engine_num_used_off() + 2)
+    }
+
+    /// Total slab length implied by this layout.
+    pub fn data_len(&self) -> usize {
+        self.accounts_off + self.max_accounts * self.account_size
+    }
+}
+
+fn align8(n: usize) -> usize {
+    n.div_ceil(8) * 8
+}
+
+fn accounts_off_rel(bitmap_off: usize, max_accounts: usize) -> usize {
+    let bitmap_bytes = max_accounts.div_ceil(64) * 8;
+    align8(bitmap_off + bitmap_bytes + POST_BITMAP + max_accounts * 2)
+}
+
+/// Slab data size for a given layout version and tier.
+pub fn slab_data_size(version: LayoutVersion, max_accounts: usize) -> usize {
+    let (engine_off, bitmap_off, account_size) = match version {
+        LayoutVersion::V0 => (V0_ENGINE_OFF, V0_ENGINE_BITMAP_OFF, V0_ACCOUNT_SIZE),
+        LayoutVersion::V1 => (V1_ENGINE_OFF, V1_ENGINE_BITMAP_OFF, V1_ACCOUNT_SIZE),
+    };
+    engine_off + accounts_off_rel(bitmap_off, max_accounts) + max_accounts * account_size
+}
+
+/// Build the layout descriptor for a version/tier pair.
+pub fn build_layout(version: LayoutVersion, max_accounts: usize) -> SlabLayout {
+    match version {
+        LayoutVersion::V0 => SlabLayout {
+            version,
+            header_len: V0_HEADER_LEN,
+            config_offset: V0_HEADER_LEN,
+            config_len: V0_CONFIG_LEN,
+            reserved_off: V0_RESERVED_OFF,
+            engine_off: V0_ENGINE_OFF,
+            account_size: V0_ACCOUNT_SIZE,
+            max_accounts,
+            bitmap_words: max_accounts.div_ceil(64),
+            accounts_off: V0_ENGINE_OFF + accounts_off_rel(V0_ENGINE_BITMAP_OFF, max_accounts),
+
+            engine_insurance_off: 16,
+            engine_params_off: V0_ENGINE_PARAMS_OFF,
+            params_size: V0_PARAMS_SIZE,
+            engine_current_slot_off: 104,
+            engine_funding_index_off: 112,
+            engine_last_funding_slot_off: 128,
+            engine_funding_rate_bps_off: 136,
+            engine_mark_price_off: None,
+            engine_last_crank_slot_off: 144,
+            engine_max_crank_staleness_off: 152,
+            engine_total_oi_off: 160,
+            engine_long_oi_off: None,
+            engine_short_oi_off: None,
+            engine_c_tot_off: 176,
+            engine_pnl_pos_tot_off: 192,
+            engine_liq_cursor_off: 208,
+            engine_gc_cursor_off: 210,
+            engine_last_sweep_start_off: 216,
+            engine_last_sweep_complete_off: 224,
+            engine_crank_cursor_off: 232,
+            engine_sweep_start_idx_off: 234,
+            engine_lifetime_liquidations_off: 240,
+            engine_lifetime_force_closes_off: 248,
+            engine_net_lp_pos_off: 256,
+            engine_lp_sum_abs_off: 272,
+            engine_lp_max_abs_off: 288,
+            engine_lp_max_abs_sweep_off: 304,
+            engine_emergency_oi_mode_off: None,
+            engine_emergency_start_slot_off: None,
+            engine_last_breaker_slot_off: None,
+            engine_bitmap_off: V0_ENGINE_BITMAP_OFF,
+
+            engine_insurance_isolated_off: None,
+            engine_insurance_isolation_bps_off: None,
+        },
+        LayoutVersion::V1 => SlabLayout {
+            version,
+            header_len: V1_HEADER_LEN,
+            config_offset: V1_HEADER_LEN,
+            config_len: V1_CONFIG_LEN,
+            reserved_off: V1_RESERVED_OFF,
+            engine_off: V1_ENGINE_OFF,
+            account_size: V1_ACCOUNT_SIZE,
+            max_accounts,
+            bitmap_words: max_accounts.div_ceil(64),
+            accounts_off: V1_ENGINE_OFF + accounts_off_rel(V1_ENGINE_BITMAP_OFF, max_accounts),
+
+            engine_insurance_off: 16,
+            engine_params_off: V1_ENGINE_PARAMS_OFF,
+            params_size: V1_PARAMS_SIZE,
+            engine_current_slot_off: 360,
+            engine_funding_index_off: 368,
+            engine_last_funding_slot_off: 384,
+            engine_funding_rate_bps_off: 392,
+            engine_mark_price_off: Some(400),
+            engine_last_crank_slot_off: 424,
+            engine_max_crank_staleness_off: 432,
+            engine_total_oi_off: 440,
+            engine_long_oi_off: Some(456),
+            engine_short_oi_off: Some(472),
+            engine_c_tot_off: 488,
+            engine_pnl_pos_tot_off: 504,
+            engine_liq_cursor_off: 520,
+            engine_gc_cursor_off: 522,
+            engine_last_sweep_start_off: 528,
+            engine_last_sweep_complete_off: 536,
+            engine_crank_cursor_off: 544,
+            engine_sweep_start_idx_off: 546,
+            engine_lifetime_liquidations_off: 552,
+            engine_lifetime_force_closes_off: 560,
+            engine_net_lp_pos_off: 568,
+            engine_lp_sum_abs_off: 584,
+            engine_lp_max_abs_off: 600,
+            engine_lp_max_abs_sweep_off: 616,
+            engine_emergency_oi_mode_off: Some(632),
+            engine_emergency_start_slot_off: Some(640),
+            engine_last_breaker_slot_off: Some(648),
+            engine_bitmap_off: V1_ENGINE_BITMAP_OFF,
+
+            engine_insurance_isolated_off: Some(48),
+            engine_insurance_isolation_bps_off: Some(64),
+        },
+    }
+}
+
+/// Detect slab layout from data length. V0 sizes are checked first
+/// (deployed devnet program), then V1. Returns `None` for unknown sizes.
+///
+/// The header `version` is not consulted: deployed V0 and V1 programs both
+/// write 1. `resolveSlabLayout` in the TS SDK detects the same way, and the
+/// shared golden fixtures (V0 slabs with header version 1) hold both to it.
+pub fn detect_slab_layout(data_len: usize) -> Option<SlabLayout> {
+    [LayoutVersion::V0, LayoutVersion::V1]
+        .into_iter()
+        .flat_map(|v| TIERS.iter().map(move |&n| (v, n)))
+        .find(|&(v, n)| slab_data_size(v, n) == data_len)
+        .map(|(v, n)| build_layout(v, n))
+}
@@ -0,0 +1,37 @@
+//! Zero-copy decoder for Percolator slab accounts.
+//!
+//! Native mirror of `packages/core/src/solana/slab.ts`: same layout
+//! detection, same field offsets, same defaults for fields a layout does not
+//! carry. Golden fixtures in `packages/core/test/fixtures/slab/` are decoded
+//! by both implementations so they cannot drift apart.
+//!
+//! ```no_run
+//! use percolator_slab::Slab;
+//!
+//! # let data: Vec<u8> = Vec::new();
+//! let slab = Slab::new(&data);
+//! let engine = slab.engine()?;
+//! for (idx, account) in slab.accounts()? {
+//!     println!("{idx}: pos={} capital={}", account.position_size, account.capital);
+//! }
+//! # let _ = engine;
+//! # Ok::<(), percolator_slab::DecodeError>(())
+//! ```
+
+mod account;
+mod engine;
+mod error;
+mod header;
+pub mod layout;
+mod read;
+mod slab;
+
+pub use account::{Account, AccountKind, UsedIndices};
+pub use engine::{parse_engine, parse_params, EngineState, InsuranceFund, RiskParams};
+pub use error::DecodeError;
+pub use header::{parse_config, parse_header, MarketConfig, SlabHeader};
+pub use layout::{detect_slab_layout, LayoutVersion, SlabLayout};
+pub use slab::Slab;
+
+/// `PERCOLAT`, stored little-endian on-chain as `TALOCREP`.
+pub const MAGIC: u64 = 0x5045_5243_4f4c_4154;
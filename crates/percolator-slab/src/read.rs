@@ -0,0 +1,50 @@
+//! Little-endian field readers.
+//!
+//! Callers bounds-check the enclosing struct once (see `Slab::require`), so
+//! these index directly and only panic on a decoder bug, never on bad input.
+
+pub(crate) fn u8_at(data: &[u8], off: usize) -> u8 {
+    data[off]
+}
+
+pub(crate) fn u16_at(data: &[u8], off: usize) -> u16 {
+    u16::from_le_bytes(array(data, off))
+}
+
+pub(crate) fn u32_at(data: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes(array(data, off))
+}
+
+pub(crate) fn u64_at(data: &[u8], off: usize) -> u64 {
+    u64::from_le_bytes(array(data, off))
+}
+
+pub(crate) fn i64_at(data: &[u8], off: usize) -> i64 {
+    i64::from_le_bytes(array(data, off))
+}
+
+pub(crate) fn u128_at(data: &[u8], off: usize) -> u128 {
+    u128::from_le_bytes(array(data, off))
+}
+
+pub(crate) fn i128_at(data: &[u8], off: usize) -> i128 {
+    i128::from_le_bytes(array(data, off))
+}
+
+/// u24 little-endian (PERC-622 `phase2_delta_slots`).
+pub(crate) fn u24_at(data: &[u8], off: usize) -> u32 {
+    u32::from(data[off]) | (u32::from(data[off + 1]) << 8) | (u32::from(data[off + 2]) << 16)
+}
+
+/// Borrow a 32-byte pubkey in place — no copy.
+pub(crate) fn pubkey_at(data: &[u8], off: usize) -> &[u8; 32] {
+    data[off..off + 32]
+        .try_into()
+        .expect("slice is exactly 32 bytes")
+}
+
+fn array<const N: usize>(data: &[u8], off: usize) -> [u8; N] {
+    data[off..off + N]
+        .try_into()
+        .expect("slice length matches array length")
+}
@@ -0,0 +1,122 @@
+use crate::account::{decode_account, Account, UsedIndices};
+use crate::engine::{parse_engine, parse_params, EngineState, RiskParams};
+use crate::error::DecodeError;
+use crate::header::{parse_config, parse_header, MarketConfig, SlabHeader};
+use crate::layout::{detect_slab_layout, SlabLayout};
+
+/// Borrowed view over raw slab account data.
+///
+/// Layout detection happens once in [`Slab::new`]; every accessor decodes
+/// straight out of the borrowed buffer, and pubkeys are returned as
+/// `&[u8; 32]` pointing into it.
+#[derive(Debug, Clone, Copy)]
+pub struct Slab<'a> {
+    data: &'a [u8],
+    layout: Option<SlabLayout>,
+}
+
+impl<'a> Slab<'a> {
+    /// Wrap full slab data, detecting the layout from its length.
+    pub fn new(data: &'a [u8]) -> Self {
+        Slab {
+            data,
+            layout: detect_slab_layout(data.len()),
+        }
+    }
+
+    /// Wrap a partial slice (e.g. a `getProgramAccounts` dataSlice) whose
+    /// layout is known from the full account size.
+    pub fn with_layout(data: &'a [u8], layout: SlabLayout) -> Self {
+        Slab {
+            data,
+            layout: Some(layout),
+        }
+    }
+
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn layout(&self) -> Option<&SlabLayout> {
+        self.layout.as_ref()
+    }
+
+    fn require_layout(&self) -> Result<&SlabLayout, DecodeError> {
+        self.layout.as_ref().ok_or(DecodeError::UnknownLayout {
+            len: self.data.len(),
+        })
+    }
+
+    pub fn header(&self) -> Result<SlabHeader<'a>, DecodeError> {
+        parse_header(self.data, self.layout.as_ref())
+    }
+
+    pub fn config(&self) -> Result<MarketConfig<'a>, DecodeError> {
+        parse_config(self.data, self.layout.as_ref())
+    }
+
+    pub fn params(&self) -> Result<RiskParams, DecodeError> {
+        parse_params(self.data, self.layout.as_ref())
+    }
+
+    pub fn engine(&self) -> Result<EngineState, DecodeError> {
+        parse_engine(self.data, self.require_layout()?)
+    }
+
+    /// Used account indices from the engine bitmap, ascending.
+    pub fn used_indices(&self) -> Result<UsedIndices<'a>, DecodeError> {
+        let layout = self.require_layout()?;
+        let need = layout.engine_off + layout.engine_bitmap_off + layout.bitmap_words * 8;
+        if self.data.len() < need {
+            return Err(DecodeError::TooShort {
+                section: "bitmap",
+                len: self.data.len(),
+                need,
+            });
+        }
+        Ok(UsedIndices::new(self.data, layout))
+    }
+
+    pub fn is_account_used(&self, idx: usize) -> bool {
+        let Some(layout) = self.layout.as_ref() else {
+            return false;
+        };
+        if idx >= layout.max_accounts {
+            return false;
+        }
+        let byte = layout.engine_off + layout.engine_bitmap_off + idx / 8;
+        self.data
+            .get(byte)
+            .is_some_and(|b| b & (1 << (idx % 8)) != 0)
+    }
+
+    /// Number of complete account records that fit in the data.
+    pub fn max_account_index(&self) -> usize {
+        self.layout.as_ref().map_or(0, |l| {
+            self.data.len().saturating_sub(l.accounts_off) / l.account_size
+        })
+    }
+
+    pub fn account(&self, idx: usize) -> Result<Account<'a>, DecodeError> {
+        let layout = self.require_layout()?;
+        let max = self.max_account_index();
+        if idx >= max {
+            return Err(DecodeError::AccountIndexOutOfRange { idx, max });
+        }
+        let start = layout.accounts_off + idx * layout.account_size;
+        Ok(decode_account(&self.data[start..start + layout.account_size]))
+    }
+
+    /// Every used account, paired with its index. Bitmap bits past the last
+    /// complete record are skipped, as in `parseAllAccounts`.
+    pub fn accounts(
+        &self,
+    ) -> Result<impl Iterator<Item = (usize, Account<'a>)> + 'a, DecodeError> {
+        let slab = *self;
+        let max = self.max_account_index();
+        Ok(self
+            .used_indices()?
+            .take_while(move |&idx| idx < max)
+            .filter_map(move |idx| slab.account(idx).ok().map(|a| (idx, a))))
+    }
+}
@@ -0,0 +1,346 @@
+//! Golden tests shared with `packages/core/test/slab-golden.test.ts`.
+//!
+//! Each fixture describes a zeroed buffer plus byte patches, and the decoded
+//! values expected from it with every scalar rendered as a decimal string and
+//! every pubkey as hex. Both the TS SDK and this crate must produce the same
+//! strings.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use percolator_slab::{
+    Account, DecodeError, EngineState, MarketConfig, RiskParams, Slab, SlabHeader, SlabLayout,
+};
+use serde_json::Value;
+
+type Fields = BTreeMap<String, String>;
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../packages/core/test/fixtures/slab")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn unhex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn build(fixture: &Value) -> Vec<u8> {
+    let mut data = vec![0u8; fixture["dataLen"].as_u64().unwrap() as usize];
+    for w in fixture["writes"].as_array().unwrap() {
+        let off = w["offset"].as_u64().unwrap() as usize;
+        let bytes = unhex(w["hex"].as_str().unwrap());
+        data[off..off + bytes.len()].copy_from_slice(&bytes);
+    }
+    data
+}
+
+macro_rules! fields {
+    ($($key:literal => $val:expr),* $(,)?) => {{
+        let mut m = Fields::new();
+        $(m.insert($key.to_string(), $val.to_string());)*
+        m
+    }};
+}
+
+fn layout_fields(l: &SlabLayout) -> Fields {
+    fields! {
+        "version" => l.version.as_u8(),
+        "maxAccounts" => l.max_accounts,
+        "accountsOff" => l.accounts_off,
+        "accountSize" => l.account_size,
+    }
+}
+
+fn header_fields(h: &SlabHeader<'_>) -> Fields {
+    fields! {
+        "magic" => h.magic,
+        "version" => h.version,
+        "bump" => h.bump,
+        "flags" => h.flags,
+        "resolved" => h.resolved,
+        "paused" => h.paused,
+        "admin" => hex(h.admin),
+        "nonce" => h.nonce,
+        "lastThrUpdateSlot" => h.last_thr_update_slot,
+    }
+}
+
+fn config_fields(c: &MarketConfig<'_>) -> Fields {
+    fields! {
+        "collateralMint" => hex(c.collateral_mint),
+        "vaultPubkey" => hex(c.vault_pubkey),
+        "indexFeedId" => hex(c.index_feed_id),
+        "maxStalenessSlots" => c.max_staleness_slots,
+        "confFilterBps" => c.conf_filter_bps,
+        "vaultAuthorityBump" => c.vault_authority_bump,
+        "invert" => c.invert,
+        "unitScale" => c.unit_scale,
+        "fundingHorizonSlots" => c.funding_horizon_slots,
+        "fundingKBps" => c.funding_k_bps,
+        "fundingInvScaleNotionalE6" => c.funding_inv_scale_notional_e6,
+        "fundingMaxPremiumBps" => c.funding_max_premium_bps,
+        "fundingMaxBpsPerSlot" => c.funding_max_bps_per_slot,
+        "fundingPremiumWeightBps" => c.funding_premium_weight_bps,
+        "fundingSettlementIntervalSlots" => c.funding_settlement_interval_slots,
+        "fundingPremiumDampeningE6" => c.funding_premium_dampening_e6,
+        "fundingPremiumMaxBpsPerSlot" => c.funding_premium_max_bps_per_slot,
+        "threshFloor" => c.thresh_floor,
+        "threshRiskBps" => c.thresh_risk_bps,
+        "threshUpdateIntervalSlots" => c.thresh_update_interval_slots,
+        "threshStepBps" => c.thresh_step_bps,
+        "threshAlphaBps" => c.thresh_alpha_bps,
+        "threshMin" => c.thresh_min,
+        "threshMax" => c.thresh_max,
+        "threshMinStep" => c.thresh_min_step,
+        "oracleAuthority" => hex(c.oracle_authority),
+        "authorityPriceE6" => c.authority_price_e6,
+        "authorityTimestamp" => c.authority_timestamp,
+        "oraclePriceCapE2bps" => c.oracle_price_cap_e2bps,
+        "lastEffectivePriceE6" => c.last_effective_price_e6,
+        "oiCapMultiplierBps" => c.oi_cap_multiplier_bps,
+        "maxPnlCap" => c.max_pnl_cap,
+        "adaptiveFundingEnabled" => c.adaptive_funding_enabled,
+        "adaptiveScaleBps" => c.adaptive_scale_bps,
+        "adaptiveMaxFundingBps" => c.adaptive_max_funding_bps,
+        "marketCreatedSlot" => c.market_created_slot,
+        "oiRampSlots" => c.oi_ramp_slots,
+        "resolvedSlot" => c.resolved_slot,
+        "insuranceIsolationBps" => c.insurance_isolation_bps,
+        "oraclePhase" => c.oracle_phase,
+        "cumulativeVolumeE6" => c.cumulative_volume_e6,
+        "phase2DeltaSlots" => c.phase2_delta_slots,
+    }
+}
+
+fn params_fields(p: &RiskParams) -> Fields {
+    fields! {
+        "warmupPeriodSlots" => p.warmup_period_slots,
+        "maintenanceMarginBps" => p.maintenance_margin_bps,
+        "initialMarginBps" => p.initial_margin_bps,
+        "tradingFeeBps" => p.trading_fee_bps,
+        "maxAccounts" => p.max_accounts,
+        "newAccountFee" => p.new_account_fee,
+        "riskReductionThreshold" => p.risk_reduction_threshold,
+        "maintenanceFeePerSlot" => p.maintenance_fee_per_slot,
+        "maxCrankStalenessSlots" => p.max_crank_staleness_slots,
+        "liquidationFeeBps" => p.liquidation_fee_bps,
+        "liquidationFeeCap" => p.liquidation_fee_cap,
+        "liquidationBufferBps" => p.liquidation_buffer_bps,
+        "minLiquidationAbs" => p.min_liquidation_abs,
+    }
+}
+
+fn engine_fields(e: &EngineState) -> Fields {
+    fields! {
+        "vault" => e.vault,
+        "insuranceFund.balance" => e.insurance_fund.balance,
+        "insuranceFund.feeRevenue" => e.insurance_fund.fee_revenue,
+        "insuranceFund.isolatedBalance" => e.insurance_fund.isolated_balance,
+        "insuranceFund.isolationBps" => e.insurance_fund.isolation_bps,
+        "currentSlot" => e.current_slot,
+        "fundingIndexQpbE6" => e.funding_index_qpb_e6,
+        "lastFundingSlot" => e.last_funding_slot,
+        "fundingRateBpsPerSlotLast" => e.funding_rate_bps_per_slot_last,
+        "lastCrankSlot" => e.last_crank_slot,
+        "maxCrankStalenessSlots" => e.max_crank_staleness_slots,
+        "totalOpenInterest" => e.total_open_interest,
+        "longOi" => e.long_oi,
+        "shortOi" => e.short_oi,
+        "cTot" => e.c_tot,
+        "pnlPosTot" => e.pnl_pos_tot,
+        "liqCursor" => e.liq_cursor,
+        "gcCursor" => e.gc_cursor,
+        "lastSweepStartSlot" => e.last_sweep_start_slot,
+        "lastSweepCompleteSlot" => e.last_sweep_complete_slot,
+        "crankCursor" => e.crank_cursor,
+        "sweepStartIdx" => e.sweep_start_idx,
+        "lifetimeLiquidations" => e.lifetime_liquidations,
+        "lifetimeForceCloses" => e.lifetime_force_closes,
+        "netLpPos" => e.net_lp_pos,
+        "lpSumAbs" => e.lp_sum_abs,
+        "lpMaxAbs" => e.lp_max_abs,
+        "lpMaxAbsSweep" => e.lp_max_abs_sweep,
+        "emergencyOiMode" => e.emergency_oi_mode,
+        "emergencyStartSlot" => e.emergency_start_slot,
+        "lastBreakerSlot" => e.last_breaker_slot,
+        "numUsedAccounts" => e.num_used_accounts,
+        "nextAccountId" => e.next_account_id,
+        "markPriceE6" => e.mark_price_e6,
+    }
+}
+
+fn account_fields(idx: usize, a: &Account<'_>) -> Fields {
+    let mut f = fields! {
+        "idx" => idx,
+        "kind" => a.kind as u8,
+        "accountId" => a.account_id,
+        "capital" => a.capital,
+        "pnl" => a.pnl,
+        "reservedPnl" => a.reserved_pnl,
+        "warmupStartedAtSlot" => a.warmup_started_at_slot,
+        "warmupSlopePerStep" => a.warmup_slope_per_step,
+        "positionSize" => a.position_size,
+        "entryPrice" => a.entry_price,
+        "fundingIndex" => a.funding_index,
+        "matcherProgram" => hex(a.matcher_program),
+        "matcherContext" => hex(a.matcher_context),
+        "owner" => hex(a.owner),
+        "feeCredits" => a.fee_credits,
+        "lastFeeSlot" => a.last_fee_slot,
+    };
+    if let Some(slot) = a.last_partial_liquidation_slot {
+        f.insert("lastPartialLiquidationSlot".to_string(), slot.to_string());
+    }
+    f
+}
+
+fn expected_fields(v: &Value) -> Fields {
+    v.as_object()
+        .unwrap()
+        .iter()
+        .map(|(k, v)| match v {
+            Value::String(s) => (k.clone(), s.clone()),
+            Value::Number(n) => (k.clone(), n.to_string()),
+            other => panic!("unexpected value for {k}: {other}"),
+        })
+        .collect()
+}
+
+fn check_fixture(name: &str, fixture: &Value) {
+    let data = build(fixture);
+    let slab = Slab::new(&data);
+    let expected = &fixture["expected"];
+
+    match &expected["layout"] {
+        Value::Null => assert!(slab.layout().is_none(), "{name}: layout"),
+        want => assert_eq!(
+            layout_fields(slab.layout().expect("layout")),
+            expected_fields(want),
+            "{name}: layout"
+        ),
+    }
+    if let Some(want) = expected.get("header") {
+        assert_eq!(header_fields(&slab.header().unwrap()), expected_fields(want), "{name}: header");
+    }
+    if let Some(want) = expected.get("config") {
+        assert_eq!(config_fields(&slab.config().unwrap()), expected_fields(want), "{name}: config");
+    }
+    if let Some(want) = expected.get("params") {
+        assert_eq!(params_fields(&slab.params().unwrap()), expected_fields(want), "{name}: params");
+    }
+    if let Some(want) = expected.get("engine") {
+        assert_eq!(engine_fields(&slab.engine().unwrap()), expected_fields(want), "{name}: engine");
+    }
+    if let Some(want) = expected.get("usedIndices") {
+        let got: Vec<String> = slab.used_indices().unwrap().map(|i| i.to_string()).collect();
+        let want: Vec<String> = want
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(got, want, "{name}: usedIndices");
+    }
+    if let Some(want) = expected.get("accounts") {
+        let got: Vec<Fields> = slab
+            .accounts()
+            .unwrap()
+            .map(|(idx, a)| account_fields(idx, &a))
+            .collect();
+        let want: Vec<Fields> = want.as_array().unwrap().iter().map(expected_fields).collect();
+        assert_eq!(got, want, "{name}: accounts");
+    }
+}
+
+#[test]
+fn golden_fixtures_match() {
+    let mut entries: Vec<_> = fs::read_dir(fixtures_dir())
+        .expect("fixtures dir")
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .collect();
+    entries.sort();
+    assert!(!entries.is_empty(), "no slab fixtures found");
+
+    for path in entries {
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let fixture: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        check_fixture(&name, &fixture);
+    }
+}
+
+#[test]
+fn rejects_bad_magic_and_short_data() {
+    let data = vec![0u8; 248_760];
+    assert!(matches!(
+        Slab::new(&data).header(),
+        Err(DecodeError::InvalidMagic { got: 0 })
+    ));
+    assert!(matches!(
+        Slab::new(&[0u8; 10]).header(),
+        Err(DecodeError::TooShort { section: "header", .. })
+    ));
+}
+
+#[test]
+fn unknown_layout_is_reported() {
+    let data = vec![0u8; 12_345];
+    let slab = Slab::new(&data);
+    assert!(slab.layout().is_none());
+    assert_eq!(
+        slab.engine().unwrap_err().to_string(),
+        "Unrecognized slab data length: 12345. Cannot determine layout version."
+    );
+    assert_eq!(slab.max_account_index(), 0);
+    assert!(!slab.is_account_used(0));
+}
+
+#[test]
+fn account_index_bounds() {
+    let data = vec![0u8; 16_320];
+    let slab = Slab::new(&data);
+    assert_eq!(slab.max_account_index(), 64);
+    assert_eq!(
+        slab.account(10_000).unwrap_err().to_string(),
+        "Account index out of range: 10000 (max: 63)"
+    );
+    assert!(!slab.is_account_used(64));
+}
+
+#[test]
+fn tier_sizes_match_ts_sdk() {
+    use percolator_slab::layout::slab_data_size;
+    use percolator_slab::LayoutVersion;
+
+    assert_eq!(slab_data_size(LayoutVersion::V0, 64), 16_320);
+    assert_eq!(slab_data_size(LayoutVersion::V0, 1024), 248_760);
+    assert_eq!(slab_data_size(LayoutVersion::V1, 256), 65_352);
+    for n in [64, 256, 1024, 4096] {
+        for v in [LayoutVersion::V0, LayoutVersion::V1] {
+            let layout = percolator_slab::detect_slab_layout(slab_data_size(v, n)).unwrap();
+            assert_eq!((layout.version, layout.max_accounts), (v, n));
+        }
+    }
+}
+
+#[test]
+fn v1_account_tail_is_decoded() {
+    // V1 records are 248 bytes; the trailing u64 is last_partial_liquidation_slot.
+    let mut data = vec![0u8; 65_352];
+    let slab = Slab::new(&data);
+    let off = slab.layout().unwrap().accounts_off + 240;
+    data[off..off + 8].copy_from_slice(&42u64.to_le_bytes());
+    let slab = Slab::new(&data);
+    assert_eq!(slab.account(0).unwrap().last_partial_liquidation_slot, Some(42));
+
+    let data = vec![0u8; 16_320];
+    let slab = Slab::new(&data);
+    assert_eq!(slab.account(0).unwrap().last_partial_liquidation_slot, None);
+}
@@ -3,22 +3,25 @@ import { Hono } from "hono";
 import { cors } from "hono/cors";
 import { compress } from "hono/compress";
 import { serve } from "@hono/node-server";
-import { createLogger, sendInfoAlert } from "@percolator/shared";
+import { config, createLogger, sendInfoAlert, connectEventBus, createEventTransport, type EventBridge } from "@percolator/shared";
 import { initSentry, sentryMiddleware, flushSentry } from "./middleware/sentry.js";
 
 // Initialize Sentry before anything else
 initSentry();
 import { healthRoutes } from "./routes/health.js";
 import { marketRoutes } from "./routes/markets.js";
+import { accountRoutes } from "./routes/accounts.js";
 import { tradeRoutes } from "./routes/trades.js";
 import { priceRoutes } from "./routes/prices.js";
 import { fundingRoutes } from "./routes/funding.js";
 import { crankStatusRoutes } from "./routes/crank.js";
 import { oracleRouterRoutes } from "./routes/oracle-router.js";
 import { insuranceRoutes } from "./routes/insurance.js";
+import { stakeRoutes } from "./routes/stake.js";
 import { openInterestRoutes } from "./routes/open-interest.js";
 import { statsRoutes } from "./routes/stats.js";
 import { docsRoutes } from "./routes/docs.js";
+import { webhookRoutes } from "./routes/webhooks.js";
 import { setupWebSocket } from "./routes/ws.js";
 import { readRateLimit, writeRateLimit } from "./middleware/rate-limit.js";
 import { ipBlocklist } from "./middleware/ip-blocklist.js";
@@ -65,20 +68,21 @@ app.use("*", cors({
     logger.warn("CORS rejected origin", { origin });
     return null;
   },
-  // Only allow GET + OPTIONS until write endpoints are implemented.
-  // When mutation routes are added, expand this AND apply requireApiKey()
-  // middleware to those routes. See middleware/auth.ts.
+  // Browsers only read. The write endpoints (/webhooks) are called
+  // server-to-server with x-api-key, so they stay out of CORS.
   allowMethods: ["GET", "OPTIONS"],
   allowHeaders: ["Content-Type", "x-api-key"],
 }));
 
-// Default-deny for mutation methods. Until write endpoints are added,
-// reject any POST/PUT/DELETE/PATCH requests that reach the API.
-// When write routes are needed, apply requireApiKey() from middleware/auth.ts
-// to those specific routes and remove this global guard.
+// Default-deny for mutation methods outside WRITE_PREFIXES. Routes under
+// those prefixes apply requireApiKey() from middleware/auth.ts themselves.
+// Add a prefix here only together with routes that do.
+const WRITE_PREFIXES = ["/webhooks"];
 app.use("*", async (c, next) => {
   const method = c.req.method;
-  if (method !== "GET" && method !== "HEAD" && method !== "OPTIONS") {
+  const path = c.req.path;
+  const writable = WRITE_PREFIXES.some((p) => path === p || path.startsWith(`${p}/`));
+  if (method !== "GET" && method !== "HEAD" && method !== "OPTIONS" && !writable) {
     logger.warn("Blocked mutation request (no write endpoints)", {
       method,
       path: c.req.path,
@@ -138,19 +142,23 @@ app.use("/funding/global", cacheMiddleware(60));
 
 // Dynamic route caching (with path parameters) applied in route handlers
 // - /markets/:slab — 10s TTL (handled in route)
+// - /markets/:slab/accounts/:owner/health — 5s TTL (handled in route)
 // - /open-interest/:slab — 15s TTL (handled in route)
 // - /funding/:slab — 30s TTL (handled in route)
 
 app.route("/", healthRoutes());
 app.route("/", marketRoutes());
+app.route("/", accountRoutes());
 app.route("/", tradeRoutes());
 app.route("/", priceRoutes());
 app.route("/", fundingRoutes());
 app.route("/", crankStatusRoutes());
 app.route("/", oracleRouterRoutes());
 app.route("/", insuranceRoutes());
+app.route("/", stakeRoutes());
 app.route("/", openInterestRoutes());
 app.route("/", statsRoutes());
+app.route("/", webhookRoutes());
 app.route("/", docsRoutes());
 
 app.get("/", (c) => c.json({ 
@@ -195,6 +203,27 @@ const server = serve({ fetch: app.fetch, port }, async (info) => {
 
 const wss = setupWebSocket(server as unknown as import("node:http").Server);
 
+// Cross-process eventBus — deliver keeper/indexer events (prices, trades,
+// slab changes) to the WebSocket channels. The API only consumes.
+let eventBridge: EventBridge | null = null;
+connectEventBus(
+  createEventTransport(config.eventBusTransport, {
+    databaseUrl: config.eventBusDatabaseUrl,
+    redisUrl: config.eventBusRedisUrl,
+  }),
+  {
+    publish: false,
+    consumer: config.eventBusConsumer || undefined,
+    replayFrom: config.eventBusReplayFrom || undefined,
+  },
+)
+  .then((bridge) => { eventBridge = bridge; })
+  .catch((err) => {
+    logger.error("Event bus connection failed — serving in-process events only", {
+      error: err instanceof Error ? err.message : String(err),
+    });
+  });
+
 async function shutdown(signal: string): Promise<void> {
   logger.info("Shutdown initiated", { signal });
   
@@ -226,6 +255,8 @@ async function shutdown(signal: string): Promise<void> {
       });
     });
     logger.info("HTTP server closed");
+
+    await eventBridge?.stop();
     
     // Note: Supabase client doesn't need explicit cleanup (connection pooling handled automatically)
     
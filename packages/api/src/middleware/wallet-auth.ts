@@ -0,0 +1,24 @@
+import { createPublicKey, verify } from "node:crypto";
+import { PublicKey } from "@solana/web3.js";
+
+/** DER SPKI header for a raw 32-byte ed25519 public key */
+const ED25519_SPKI_PREFIX = Buffer.from("302a300506032b6570032100", "hex");
+
+/**
+ * Verify a wallet's ed25519 signature (base64, as returned by a wallet's
+ * `signMessage`) over a UTF-8 message. False for a malformed key or signature.
+ */
+export function verifyWalletSignature(owner: string, message: string, signature: string): boolean {
+  try {
+    const key = createPublicKey({
+      key: Buffer.concat([ED25519_SPKI_PREFIX, new PublicKey(owner).toBuffer()]),
+      format: "der",
+      type: "spki",
+    });
+    const sig = Buffer.from(signature, "base64");
+    if (sig.length !== 64) return false;
+    return verify(null, Buffer.from(message, "utf8"), key, sig);
+  } catch {
+    return false;
+  }
+}
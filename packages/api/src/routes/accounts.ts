@@ -0,0 +1,217 @@
+import { Hono } from "hono";
+import { PublicKey } from "@solana/web3.js";
+import { validateSlab } from "../middleware/validateSlab.js";
+import { cacheMiddleware } from "../middleware/cache.js";
+import {
+  fetchSlab,
+  parseConfig,
+  parseEngine,
+  parseParams,
+  parseAllAccounts,
+  computeAccountHealth,
+} from "@percolator/sdk";
+import {
+  getConnection,
+  createLogger,
+  getAccountActivity,
+  getAccountSnapshotsByOwner,
+  getAccountSnapshotsByIndex,
+  sanitizeSlabAddress,
+  sanitizePagination,
+} from "@percolator/shared";
+
+const logger = createLogger("api:accounts");
+
+function parseOwner(raw: string | undefined): PublicKey | null {
+  if (!raw) return null;
+  try {
+    return new PublicKey(raw);
+  } catch {
+    return null;
+  }
+}
+
+/** Parse optional ?from=/&to= ISO bounds; null when either is malformed. */
+function parseTimeRange(from: string | undefined, to: string | undefined): { from?: string; to?: string } | null {
+  const range: { from?: string; to?: string } = {};
+  for (const [key, raw] of [["from", from], ["to", to]] as const) {
+    if (!raw) continue;
+    const d = new Date(raw);
+    if (Number.isNaN(d.getTime())) return null;
+    range[key] = d.toISOString();
+  }
+  return range;
+}
+
+export function accountRoutes(): Hono {
+  const app = new Hono();
+
+  // GET /markets/:slab/accounts/:owner/health — margin report for every account
+  // the wallet holds on this market, priced at the last effective oracle price.
+  app.get("/markets/:slab/accounts/:owner/health", cacheMiddleware(5), validateSlab, async (c) => {
+    const slab = c.req.param("slab");
+    const owner = parseOwner(c.req.param("owner"));
+    if (!owner) return c.json({ error: "Invalid owner address" }, 400);
+
+    let data: Uint8Array;
+    try {
+      data = await fetchSlab(getConnection(), new PublicKey(slab));
+    } catch (err) {
+      const detail = err instanceof Error ? err.message : "Unknown error";
+      logger.error("Account health RPC error", { detail, path: c.req.path });
+      return c.json({ error: "Failed to fetch market account" }, 502);
+    }
+
+    try {
+      const cfg = parseConfig(data);
+      const engine = parseEngine(data);
+      const params = parseParams(data);
+      const priceE6 = cfg.lastEffectivePriceE6 > 0n ? cfg.lastEffectivePriceE6 : cfg.authorityPriceE6;
+
+      const accounts = parseAllAccounts(data)
+        .filter(({ account }) => account.owner.equals(owner))
+        .map(({ idx, account }) => {
+          const h = computeAccountHealth(account, engine, params, priceE6);
+          return {
+            accountIdx: idx,
+            kind: account.kind,
+            capital: account.capital.toString(),
+            positionSize: account.positionSize.toString(),
+            entryPriceE6: account.entryPrice.toString(),
+            equity: h.equity.toString(),
+            unrealizedPnl: h.unrealizedPnl.toString(),
+            pendingFunding: h.pendingFunding.toString(),
+            warmupLockedCapital: h.warmupLockedCapital.toString(),
+            notional: h.notional.toString(),
+            maintenanceMargin: h.maintenanceMargin.toString(),
+            initialMargin: h.initialMargin.toString(),
+            bufferToLiquidation: h.bufferToLiquidation.toString(),
+            marginRatioBps: h.marginRatioBps?.toString() ?? null,
+            liquidationPriceE6: h.liquidationPriceE6.toString(),
+            liquidatable: h.liquidatable,
+          };
+        });
+
+      if (accounts.length === 0) {
+        return c.json({ error: "No account for owner on this market" }, 404);
+      }
+
+      return c.json({
+        slabAddress: slab,
+        owner: owner.toBase58(),
+        priceE6: priceE6.toString(),
+        slot: engine.currentSlot.toString(),
+        accounts,
+      });
+    } catch (err) {
+      const detail = err instanceof Error ? err.message : "Unknown error";
+      logger.error("Account health error", { detail, path: c.req.path });
+      return c.json({ error: "Failed to compute account health" }, 500);
+    }
+  });
+
+  // GET /accounts/:owner/activity — indexed timeline of trades, collateral and
+  // insurance LP movements, and liquidations for a wallet, newest first.
+  // ?slab= narrows to one market; ?before=<ISO time> pages backwards.
+  app.get("/accounts/:owner/activity", cacheMiddleware(10), async (c) => {
+    const owner = parseOwner(c.req.param("owner"));
+    if (!owner) return c.json({ error: "Invalid owner address" }, 400);
+
+    const slabParam = c.req.query("slab");
+    const slab = slabParam ? sanitizeSlabAddress(slabParam) : undefined;
+    if (slab === null) return c.json({ error: "Invalid slab address" }, 400);
+
+    const beforeParam = c.req.query("before");
+    const before = beforeParam ? new Date(beforeParam) : undefined;
+    if (before && Number.isNaN(before.getTime())) {
+      return c.json({ error: "Invalid before timestamp" }, 400);
+    }
+
+    const { limit } = sanitizePagination(c.req.query("limit"), 0);
+    const safeLimit = Math.min(limit, 200);
+
+    try {
+      const activity = await getAccountActivity(owner.toBase58(), {
+        slab,
+        before: before?.toISOString(),
+        limit: safeLimit,
+      });
+      return c.json({
+        owner: owner.toBase58(),
+        activity,
+        nextBefore: activity.length === safeLimit ? activity[activity.length - 1].indexed_at : null,
+      });
+    } catch (err) {
+      logger.error("Account activity error", {
+        error: err instanceof Error ? err.message : err,
+        path: c.req.path,
+      });
+      return c.json({ error: "Failed to fetch account activity" }, 500);
+    }
+  });
+
+  // GET /accounts/:owner/history — stored account snapshots for a wallet,
+  // newest first. A row is written only when the account changed, so gaps
+  // between rows mean "unchanged". ?slab=, ?accountIdx=, ?from=, ?to= filter.
+  app.get("/accounts/:owner/history", cacheMiddleware(30), async (c) => {
+    const owner = parseOwner(c.req.param("owner"));
+    if (!owner) return c.json({ error: "Invalid owner address" }, 400);
+
+    const slabParam = c.req.query("slab");
+    const slab = slabParam ? sanitizeSlabAddress(slabParam) : undefined;
+    if (slab === null) return c.json({ error: "Invalid slab address" }, 400);
+
+    const idxParam = c.req.query("accountIdx");
+    const accountIdx = idxParam !== undefined ? Number(idxParam) : undefined;
+    if (accountIdx !== undefined && (!Number.isInteger(accountIdx) || accountIdx < 0)) {
+      return c.json({ error: "Invalid accountIdx" }, 400);
+    }
+
+    const range = parseTimeRange(c.req.query("from"), c.req.query("to"));
+    if (!range) return c.json({ error: "Invalid from/to timestamp" }, 400);
+
+    const { limit } = sanitizePagination(c.req.query("limit"), 0);
+
+    try {
+      const snapshots = await getAccountSnapshotsByOwner(owner.toBase58(), {
+        slab,
+        accountIdx,
+        ...range,
+        limit: Math.min(limit, 200),
+      });
+      return c.json({ owner: owner.toBase58(), snapshots });
+    } catch (err) {
+      logger.error("Account history error", {
+        error: err instanceof Error ? err.message : err,
+        path: c.req.path,
+      });
+      return c.json({ error: "Failed to fetch account history" }, 500);
+    }
+  });
+
+  // GET /markets/:slab/accounts/:idx/history — snapshots of one account index
+  // across owners (indices are reused after close); used for disputes.
+  app.get("/markets/:slab/accounts/:idx/history", cacheMiddleware(30), validateSlab, async (c) => {
+    const slab = c.req.param("slab");
+    const idx = Number(c.req.param("idx"));
+    if (!Number.isInteger(idx) || idx < 0) return c.json({ error: "Invalid account index" }, 400);
+
+    const range = parseTimeRange(c.req.query("from"), c.req.query("to"));
+    if (!range) return c.json({ error: "Invalid from/to timestamp" }, 400);
+
+    const { limit } = sanitizePagination(c.req.query("limit"), 0);
+
+    try {
+      const snapshots = await getAccountSnapshotsByIndex(slab, idx, { ...range, limit: Math.min(limit, 200) });
+      return c.json({ slabAddress: slab, accountIdx: idx, snapshots });
+    } catch (err) {
+      logger.error("Account index history error", {
+        error: err instanceof Error ? err.message : err,
+        path: c.req.path,
+      });
+      return c.json({ error: "Failed to fetch account history" }, 500);
+    }
+  });
+
+  return app;
+}
@@ -1,20 +1,85 @@
 import { Hono } from "hono";
-import { resolvePrice, type PriceRouterResult } from "@percolator/sdk";
-import { createLogger } from "@percolator/shared";
+import {
+  resolvePrice,
+  fetchDexPoolDepth,
+  poolSideValueUsd,
+  costToMovePriceUsd,
+  assessManipulationRisk,
+  type PriceRouterResult,
+  type PoolDepthSnapshot,
+} from "@percolator/sdk";
+import { getConnection, createLogger } from "@percolator/shared";
 
 const logger = createLogger("api:oracle-router");
 
-// Simple in-memory cache: mint → { result, expiresAt }
-const cache = new Map<string, { result: PriceRouterResult; expiresAt: number }>();
+// Simple in-memory cache: mint → { result, depth, expiresAt }
+const cache = new Map<string, { result: PriceRouterResult; depth: PoolDepthSnapshot | null; expiresAt: number }>();
 const CACHE_TTL_MS = 5 * 60 * 1000; // 5 minutes
 const MAX_CACHE_SIZE = 500;
 
+/** Non-negative finite number from a query param, else undefined. */
+function numberParam(value: string | undefined): number | undefined {
+  if (value === undefined || value === "") return undefined;
+  const n = Number(value);
+  return Number.isFinite(n) && n >= 0 ? n : undefined;
+}
+
+/** On-chain depth of the best DEX source, or null if there is none or it can't be read. */
+async function readBestPoolDepth(result: PriceRouterResult): Promise<PoolDepthSnapshot | null> {
+  const dex = result.bestSource?.type === "dex" ? result.bestSource : result.allSources.find((s) => s.type === "dex");
+  if (!dex) return null;
+  try {
+    return await fetchDexPoolDepth(getConnection(), dex.address);
+  } catch (err) {
+    logger.warn("Pool depth read failed", { pool: dex.address, error: err instanceof Error ? err.message : String(err) });
+    return null;
+  }
+}
+
+/**
+ * Depth and manipulation cost of the oracle pool. With `initialMarginBps` and
+ * `maxOiUsd` (and optionally `oraclePriceCapE2bps`) the proposed market's risk
+ * is assessed too.
+ */
+function oracleDepth(
+  mint: string,
+  result: PriceRouterResult,
+  depth: PoolDepthSnapshot | null,
+  query: Record<string, string | undefined>,
+) {
+  const price = result.bestSource?.price || result.allSources.find((s) => s.price > 0)?.price || 0;
+  const depthUsd = depth ? poolSideValueUsd(depth, mint, price) : null;
+  if (!depth || depthUsd === null) return null;
+
+  const initialMarginBps = numberParam(query.initialMarginBps);
+  const maxOiUsd = numberParam(query.maxOiUsd);
+  return {
+    poolAddress: depth.poolAddress,
+    dexType: depth.dexType,
+    model: depth.model,
+    slot: depth.slot,
+    depthUsd,
+    costToMove1PctUsd: costToMovePriceUsd(depthUsd, 100),
+    risk:
+      initialMarginBps && maxOiUsd !== undefined
+        ? assessManipulationRisk({
+            poolDepthUsd: depthUsd,
+            initialMarginBps,
+            maxOiUsd,
+            oraclePriceCapE2bps: numberParam(query.oraclePriceCapE2bps),
+          })
+        : null,
+  };
+}
+
 export function oracleRouterRoutes(): Hono {
   const app = new Hono();
 
-  // GET /oracle/resolve/:mint — returns ranked oracle sources for a given token
+  // GET /oracle/resolve/:mint — returns ranked oracle sources for a given token,
+  // plus the depth of the best DEX pool (`oracleDepth`)
   app.get("/oracle/resolve/:mint", async (c) => {
     const mint = c.req.param("mint");
+    const query = c.req.query();
 
     // Validate mint format (base58, 32-44 chars)
     if (!mint || mint.length < 32 || mint.length > 44) {
@@ -30,11 +95,12 @@ export function oracleRouterRoutes(): Hono {
     // Check cache
     const cached = cache.get(mint);
     if (cached && now < cached.expiresAt) {
-      return c.json({ ...cached.result, cached: true });
+      return c.json({ ...cached.result, oracleDepth: oracleDepth(mint, cached.result, cached.depth, query), cached: true });
     }
 
     try {
       const result = await resolvePrice(mint);
+      const depth = await readBestPoolDepth(result);
 
       // Cache the result (with max size enforcement)
       if (cache.size >= MAX_CACHE_SIZE) {
@@ -42,9 +108,9 @@ export function oracleRouterRoutes(): Hono {
         const oldestKey = cache.keys().next().value;
         if (oldestKey) cache.delete(oldestKey);
       }
-      cache.set(mint, { result, expiresAt: Date.now() + CACHE_TTL_MS });
+      cache.set(mint, { result, depth, expiresAt: Date.now() + CACHE_TTL_MS });
 
-      return c.json({ ...result, cached: false });
+      return c.json({ ...result, oracleDepth: oracleDepth(mint, result, depth, query), cached: false });
     } catch (err: any) {
       const detail = err instanceof Error ? err.message : String(err);
       logger.error("Oracle resolve error", { detail, path: c.req.path });
@@ -0,0 +1,190 @@
+/**
+ * Stake Pool API Routes
+ *
+ * Serves the percolator-stake pool history recorded by the indexer's
+ * StakePoolService (migration 045):
+ * - Share price, tranche and HWM snapshots, plus insurance flows and HWM breaches
+ * - Trailing APY per tranche
+ */
+import { Hono } from "hono";
+import { PublicKey } from "@solana/web3.js";
+import { cacheMiddleware } from "../middleware/cache.js";
+import { splitStakeTrancheFees } from "@percolator/sdk";
+import {
+  createLogger,
+  getLatestStakePoolSnapshot,
+  getStakePoolEvents,
+  getStakePoolSnapshots,
+  getStakePoolSnapshotSince,
+  sanitizeSlabAddress,
+  sanitizePagination,
+  type StoredStakePoolSnapshot,
+} from "@percolator/shared";
+
+const logger = createLogger("api:stake");
+
+const MS_PER_DAY = 86_400_000;
+const MS_PER_YEAR = 365 * MS_PER_DAY;
+
+function parsePool(raw: string | undefined): string | null {
+  const sanitized = raw ? sanitizeSlabAddress(raw) : null;
+  if (!sanitized) return null;
+  try {
+    return new PublicKey(sanitized).toBase58();
+  } catch {
+    return null;
+  }
+}
+
+/** Parse optional ?from=/&to= ISO bounds; null when either is malformed. */
+function parseTimeRange(from: string | undefined, to: string | undefined): { from?: string; to?: string } | null {
+  const range: { from?: string; to?: string } = {};
+  for (const [key, raw] of [["from", from], ["to", to]] as const) {
+    if (!raw) continue;
+    const d = new Date(raw);
+    if (Number.isNaN(d.getTime())) return null;
+    range[key] = d.toISOString();
+  }
+  return range;
+}
+
+/** Simple annualized growth, 4 decimal places (0.1234 = 12.34%). */
+function annualize(start: number, end: number, elapsedMs: number): number | null {
+  if (start <= 0) return null;
+  const apy = ((end - start) / start) * MS_PER_YEAR / elapsedMs;
+  return Number.isFinite(apy) ? Math.round(apy * 10_000) / 10_000 : null;
+}
+
+function mean(a: string | null, b: string | null): bigint {
+  return (BigInt(a ?? "0") + BigInt(b ?? "0")) / 2n;
+}
+
+export interface StakeApyWindow {
+  from: string;
+  to: string;
+  /** Realized, from LP share price growth */
+  apy: { pool: number | null; senior: number | null; junior: number | null };
+  /** Fees earned in the window split by juniorFeeMultBps, over average tranche value */
+  feeApy: { senior: number | null; junior: number | null };
+  flushed: string;
+  returned: string;
+  feesEarned: string;
+}
+
+/**
+ * Trailing APY between two snapshots of a pool; null with under a day between
+ * them (as InsuranceLPService).
+ */
+export function stakeApyWindow(oldest: StoredStakePoolSnapshot, latest: StoredStakePoolSnapshot): StakeApyWindow | null {
+  const elapsed = Date.parse(latest.created_at) - Date.parse(oldest.created_at);
+  if (!Number.isFinite(elapsed) || elapsed < MS_PER_DAY) return null;
+
+  const price = (s: string | null) => (s === null ? null : Number(s));
+  const growth = (a: string | null, b: string | null) => {
+    const start = price(a);
+    const end = price(b);
+    return start === null || end === null ? null : annualize(start, end, elapsed);
+  };
+
+  const fees = BigInt(latest.total_fees_earned) - BigInt(oldest.total_fees_earned);
+  const seniorValue = mean(oldest.senior_value, latest.senior_value);
+  const juniorValue = latest.tranche_enabled ? mean(oldest.junior_value, latest.junior_value) : 0n;
+  const split = splitStakeTrancheFees(fees, seniorValue, juniorValue, latest.junior_fee_mult_bps);
+  const feeApy = (earned: bigint, value: bigint) =>
+    value > 0n ? annualize(Number(value), Number(value + earned), elapsed) : null;
+
+  return {
+    from: oldest.created_at,
+    to: latest.created_at,
+    apy: {
+      pool: growth(oldest.share_price_e6, latest.share_price_e6),
+      senior: growth(oldest.senior_share_price_e6, latest.senior_share_price_e6),
+      junior: latest.tranche_enabled ? growth(oldest.junior_share_price_e6, latest.junior_share_price_e6) : null,
+    },
+    feeApy: {
+      senior: feeApy(split.senior, seniorValue),
+      junior: latest.tranche_enabled ? feeApy(split.junior, juniorValue) : null,
+    },
+    flushed: (BigInt(latest.total_flushed) - BigInt(oldest.total_flushed)).toString(),
+    returned: (BigInt(latest.total_returned) - BigInt(oldest.total_returned)).toString(),
+    feesEarned: fees.toString(),
+  };
+}
+
+export function stakeRoutes(): Hono {
+  const app = new Hono();
+
+  // GET /stake/:pool/history — snapshots and flush/return/HWM events for a
+  // stake pool, newest first. ?from=, ?to= bound both; ?limit= caps each.
+  app.get("/stake/:pool/history", cacheMiddleware(30), async (c) => {
+    const pool = parsePool(c.req.param("pool"));
+    if (!pool) return c.json({ error: "Invalid pool address" }, 400);
+
+    const range = parseTimeRange(c.req.query("from"), c.req.query("to"));
+    if (!range) return c.json({ error: "Invalid from/to timestamp" }, 400);
+
+    const { limit } = sanitizePagination(c.req.query("limit"), 0);
+    const query = { ...range, limit: Math.min(limit, 200) };
+
+    try {
+      const [snapshots, events] = await Promise.all([
+        getStakePoolSnapshots(pool, query),
+        getStakePoolEvents(pool, query),
+      ]);
+      return c.json({ pool, snapshots, events });
+    } catch (err) {
+      logger.error("Stake pool history error", {
+        error: err instanceof Error ? err.message : err,
+        path: c.req.path,
+      });
+      return c.json({ error: "Failed to fetch stake pool history" }, 500);
+    }
+  });
+
+  // GET /stake/:pool/apy — current share prices and HWM state with trailing
+  // 7d/30d APY per tranche (a window is null until it holds a day of data).
+  app.get("/stake/:pool/apy", cacheMiddleware(60), async (c) => {
+    const pool = parsePool(c.req.param("pool"));
+    if (!pool) return c.json({ error: "Invalid pool address" }, 400);
+
+    try {
+      const latest = await getLatestStakePoolSnapshot(pool);
+      if (!latest) return c.json({ error: "Stake pool not indexed" }, 404);
+
+      const window = async (days: number) => {
+        const oldest = await getStakePoolSnapshotSince(pool, new Date(Date.now() - days * MS_PER_DAY).toISOString());
+        return oldest ? stakeApyWindow(oldest, latest) : null;
+      };
+      const [apy7d, apy30d] = await Promise.all([window(7), window(30)]);
+
+      return c.json({
+        pool,
+        slabAddress: latest.slab_address,
+        asOf: latest.created_at,
+        poolValue: latest.pool_value,
+        trancheEnabled: latest.tranche_enabled,
+        juniorFeeMultBps: latest.junior_fee_mult_bps,
+        sharePriceE6: {
+          pool: latest.share_price_e6,
+          senior: latest.senior_share_price_e6,
+          junior: latest.junior_share_price_e6,
+        },
+        hwm: {
+          enabled: latest.hwm_enabled,
+          floor: latest.hwm_floor,
+          breached: latest.hwm_breached,
+        },
+        apy7d,
+        apy30d,
+      });
+    } catch (err) {
+      logger.error("Stake pool APY error", {
+        error: err instanceof Error ? err.message : err,
+        path: c.req.path,
+      });
+      return c.json({ error: "Failed to compute stake pool APY" }, 500);
+    }
+  });
+
+  return app;
+}
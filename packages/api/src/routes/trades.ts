@@ -4,6 +4,8 @@ import {
   get24hVolume, 
   getGlobalRecentTrades, 
   getPriceHistory, 
+  getCandles,
+  isCandleResolution,
   createLogger,
   sanitizeSlabAddress,
   sanitizePagination,
@@ -12,6 +14,13 @@ import {
 
 const logger = createLogger("api:trades");
 
+/** Accepts ISO-8601 or epoch seconds (what charting libraries send). */
+function parseTimeParam(raw: string | undefined): string | null | undefined {
+  if (raw === undefined) return undefined;
+  const date = /^\d+$/.test(raw) ? new Date(Number(raw) * 1000) : new Date(raw);
+  return Number.isNaN(date.getTime()) ? null : date.toISOString();
+}
+
 export function tradeRoutes(): Hono {
   const app = new Hono();
 
@@ -77,6 +86,56 @@ export function tradeRoutes(): Hono {
     }
   });
 
+  /**
+   * OHLCV candles for a market, oldest first.
+   * ?resolution=1m|5m|15m|1h|4h|1d (default 1h); ?from= / ?to= as ISO or epoch
+   * seconds (`to` exclusive); ?limit= up to 1000. Without `from`, returns the
+   * latest `limit` candles.
+   */
+  app.get("/markets/:slab/candles", async (c) => {
+    const slab = sanitizeSlabAddress(c.req.param("slab"));
+    if (!slab) {
+      return c.json({ error: "Invalid slab address" }, 400);
+    }
+
+    const resolution = c.req.query("resolution") ?? "1h";
+    if (!isCandleResolution(resolution)) {
+      return c.json({ error: "Invalid resolution (use 1m, 5m, 15m, 1h, 4h or 1d)" }, 400);
+    }
+
+    const from = parseTimeParam(c.req.query("from"));
+    const to = parseTimeParam(c.req.query("to"));
+    if (from === null || to === null) {
+      return c.json({ error: "Invalid from/to (use ISO-8601 or epoch seconds)" }, 400);
+    }
+
+    const limitParam = c.req.query("limit");
+    const limit = limitParam === undefined ? 300 : sanitizeNumber(limitParam, 1, 1000);
+    if (limit === null) {
+      return c.json({ error: "Invalid limit (1-1000)" }, 400);
+    }
+
+    try {
+      const candles = await getCandles(slab, resolution, { from, to, limit: Math.floor(limit) });
+      return c.json({
+        slab_address: slab,
+        resolution,
+        candles: candles.map((k) => ({
+          time: Math.floor(Date.parse(k.bucket_start) / 1000),
+          open: Number(k.open),
+          high: Number(k.high),
+          low: Number(k.low),
+          close: Number(k.close),
+          volume: String(k.volume),
+          trade_count: k.trade_count,
+        })),
+      });
+    } catch (err) {
+      logger.error("Error fetching candles", { error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to fetch candles" }, 500);
+    }
+  });
+
   /** Global recent trades across all markets */
   app.get("/trades/recent", async (c) => {
     const { limit } = sanitizePagination(c.req.query("limit"), 0);
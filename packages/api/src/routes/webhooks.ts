@@ -0,0 +1,441 @@
+/**
+ * Outbound Webhook Routes
+ *
+ * Registration and delivery logs for webhook subscriptions. Deliveries are
+ * made by the indexer's WebhookDispatcher (WEBHOOK_DELIVERY_ENABLED=true).
+ * Routes take either the API key (the operator: every subscription) or a
+ * wallet signature over a single-use challenge (that wallet's subscriptions
+ * only, at most WEBHOOK_MAX_PER_OWNER of them).
+ *
+ * - POST   /webhooks/challenge                       — single-use challenge for a wallet to sign
+ * - POST   /webhooks                                 — register; returns the signing secret once
+ * - GET    /webhooks                                 — list subscriptions
+ * - GET    /webhooks/:id                             — one subscription
+ * - PATCH  /webhooks/:id                             — change url, filters, description or active
+ * - DELETE /webhooks/:id                             — remove it and its delivery history
+ * - GET    /webhooks/:id/deliveries                  — delivery log (?status=, ?event=, ?limit=)
+ * - GET    /webhooks/:id/deliveries/:deliveryId      — one delivery with every attempt
+ * - POST   /webhooks/:id/deliveries/:deliveryId/redeliver — re-queue it (e.g. a dead letter)
+ * - GET    /webhooks/:id/dead-letters                — deliveries that exhausted their retries
+ */
+import { Hono, type Context, type MiddlewareHandler } from "hono";
+import { randomBytes } from "node:crypto";
+import { PublicKey } from "@solana/web3.js";
+import {
+  assertPublicHost,
+  consumeWebhookChallenge,
+  countWebhookSubscriptions,
+  createLogger,
+  createWebhookChallenge,
+  createWebhookSubscription,
+  deleteWebhookSubscription,
+  getWebhookAttempts,
+  getWebhookDeadLetters,
+  getWebhookDeliveries,
+  getWebhookDelivery,
+  getWebhookSubscription,
+  listWebhookSubscriptions,
+  redeliverWebhook,
+  sanitizePagination,
+  sanitizeSlabAddress,
+  updateWebhookSubscription,
+  EVENT_DATA_SCHEMAS,
+  PrivateAddressError,
+  type WebhookDeliveryStatus,
+  type WebhookSubscriptionPatch,
+  type WebhookSubscriptionRow,
+} from "@percolator/shared";
+import { requireApiKey } from "../middleware/auth.js";
+import { verifyWalletSignature } from "../middleware/wallet-auth.js";
+
+const logger = createLogger("api:webhooks");
+
+const MAX_URL_LENGTH = 2048;
+const MAX_DESCRIPTION_LENGTH = 200;
+const MAX_FILTER_ENTRIES = 100;
+const DELIVERY_STATUSES: WebhookDeliveryStatus[] = ["pending", "delivering", "delivered", "dead"];
+const UUID_RE = /^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$/i;
+/** How long a wallet has to use a challenge */
+export const WEBHOOK_CHALLENGE_TTL_MS = 5 * 60 * 1000;
+
+/** Set by webhookAuth: the wallet a request acts for, or null for the operator. */
+type WebhookEnv = { Variables: { owner: string | null } };
+
+/** Subscriptions one wallet may hold (WEBHOOK_MAX_PER_OWNER, default 5). */
+function maxWebhooksPerOwner(): number {
+  const n = Number(process.env.WEBHOOK_MAX_PER_OWNER ?? 5);
+  return Number.isInteger(n) && n >= 0 ? n : 5;
+}
+
+/**
+ * Error message for an unacceptable endpoint, or null. Production requires
+ * https and a host whose every DNS address is public: deliveries are POSTed
+ * from inside our network. The dispatcher checks the address again when it
+ * connects, since the name can be re-pointed after registration.
+ */
+export async function validateWebhookUrl(raw: unknown): Promise<string | null> {
+  if (typeof raw !== "string" || raw.length === 0 || raw.length > MAX_URL_LENGTH) {
+    return "url must be a string of at most 2048 characters";
+  }
+  let url: URL;
+  try {
+    url = new URL(raw);
+  } catch {
+    return "url is not a valid URL";
+  }
+  const production = process.env.NODE_ENV === "production";
+  if (url.protocol !== "https:" && !(url.protocol === "http:" && !production)) {
+    return production ? "url must use https" : "url must use http or https";
+  }
+  if (url.username || url.password) return "url must not contain credentials";
+  if (production) {
+    try {
+      await assertPublicHost(url.hostname);
+    } catch (err) {
+      return err instanceof PrivateAddressError ? "url must point to a public host" : "url host does not resolve";
+    }
+  }
+  return null;
+}
+
+/** Canonical base58 wallet address, or null. */
+function parseOwner(raw: unknown): string | null {
+  if (typeof raw !== "string") return null;
+  try {
+    const key = new PublicKey(raw).toBase58();
+    return key === raw ? key : null;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * The message a wallet signs (`signMessage`) to make one webhook request: a
+ * challenge from POST /webhooks/challenge and the method and path it is for.
+ */
+export function webhookAuthMessage(owner: string, challenge: string, method: string, path: string): string {
+  return (
+    `Percolator: manage webhooks\nWallet: ${owner}\n` +
+    `Challenge: ${challenge}\nRequest: ${method.toUpperCase()} ${path}`
+  );
+}
+
+/**
+ * Operator requests carry the API key. Wallet requests carry x-wallet-owner,
+ * x-wallet-challenge and x-wallet-signature (base64, over webhookAuthMessage)
+ * and act only on that wallet's subscriptions; each challenge works once.
+ */
+function webhookAuth(): MiddlewareHandler<WebhookEnv> {
+  const apiKey = requireApiKey();
+  return async (c, next) => {
+    const rawOwner = c.req.header("x-wallet-owner");
+    if (rawOwner === undefined) {
+      c.set("owner", null);
+      return apiKey(c, next);
+    }
+
+    const owner = parseOwner(rawOwner);
+    const challenge = c.req.header("x-wallet-challenge");
+    const signature = c.req.header("x-wallet-signature");
+    if (
+      !owner ||
+      !challenge ||
+      !signature ||
+      !verifyWalletSignature(owner, webhookAuthMessage(owner, challenge, c.req.method, c.req.path), signature)
+    ) {
+      return c.json({ error: "Unauthorized: invalid wallet signature" }, 401);
+    }
+    try {
+      if (!(await consumeWebhookChallenge(owner, challenge))) {
+        return c.json({ error: "Unauthorized: challenge unknown, expired or already used" }, 401);
+      }
+    } catch (err) {
+      logger.error("Webhook challenge check error", { owner, error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to verify challenge" }, 500);
+    }
+    c.set("owner", owner);
+    return next();
+  };
+}
+
+/**
+ * Whether the caller may act on subscription `id`: the operator on any, a
+ * wallet only on its own (others' look nonexistent).
+ */
+async function canAccess(owner: string | null, id: string): Promise<boolean> {
+  if (owner === null) return true;
+  return (await getWebhookSubscription(id))?.owner === owner;
+}
+
+/** Parse an events/slabs filter: undefined → unset, [] → everything. */
+function parseFilter(
+  raw: unknown,
+  name: string,
+  check: (value: string) => string | null,
+): { value?: string[]; error?: string } {
+  if (raw === undefined) return {};
+  if (!Array.isArray(raw) || raw.length > MAX_FILTER_ENTRIES) {
+    return { error: `${name} must be an array of at most ${MAX_FILTER_ENTRIES} entries` };
+  }
+  const values: string[] = [];
+  for (const entry of raw) {
+    const value = typeof entry === "string" ? check(entry) : null;
+    if (value === null) return { error: `${name} contains an invalid entry: ${JSON.stringify(entry)}` };
+    if (!values.includes(value)) values.push(value);
+  }
+  return { value: values };
+}
+
+const parseEvents = (raw: unknown) =>
+  parseFilter(raw, "events", (e) => (Object.hasOwn(EVENT_DATA_SCHEMAS, e) ? e : null));
+const parseSlabs = (raw: unknown) => parseFilter(raw, "slabs", (s) => sanitizeSlabAddress(s));
+
+function parseDescription(raw: unknown): { value?: string | null; error?: string } {
+  if (raw === undefined) return {};
+  if (raw === null) return { value: null };
+  if (typeof raw !== "string" || raw.length > MAX_DESCRIPTION_LENGTH) {
+    return { error: `description must be a string of at most ${MAX_DESCRIPTION_LENGTH} characters` };
+  }
+  return { value: raw };
+}
+
+/** Subscription as returned by the API — never includes the secret. */
+function publicSubscription(row: WebhookSubscriptionRow) {
+  return {
+    id: row.id,
+    url: row.url,
+    events: row.events,
+    slabs: row.slabs,
+    description: row.description,
+    active: row.active,
+    owner: row.owner,
+    createdAt: row.created_at,
+    updatedAt: row.updated_at,
+  };
+}
+
+async function readJson(c: Context): Promise<Record<string, unknown> | null> {
+  try {
+    const body: unknown = await c.req.json();
+    return body && typeof body === "object" && !Array.isArray(body) ? (body as Record<string, unknown>) : null;
+  } catch {
+    return null;
+  }
+}
+
+export function webhookRoutes(): Hono<WebhookEnv> {
+  const app = new Hono<WebhookEnv>();
+  const auth = webhookAuth();
+
+  app.post("/webhooks/challenge", async (c) => {
+    const body = await readJson(c);
+    const owner = parseOwner(body?.owner);
+    if (!owner) return c.json({ error: "owner must be a wallet address" }, 400);
+
+    try {
+      const challenge = randomBytes(16).toString("hex");
+      const expiresAt = new Date(Date.now() + WEBHOOK_CHALLENGE_TTL_MS).toISOString();
+      await createWebhookChallenge(owner, challenge, expiresAt);
+      return c.json({ challenge, expiresAt }, 201);
+    } catch (err) {
+      logger.error("Webhook challenge error", { owner, error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to issue challenge" }, 500);
+    }
+  });
+
+  app.post("/webhooks", auth, async (c) => {
+    const owner = c.get("owner");
+    const body = await readJson(c);
+    if (!body) return c.json({ error: "Body must be a JSON object" }, 400);
+
+    const urlError = await validateWebhookUrl(body.url);
+    if (urlError) return c.json({ error: urlError }, 400);
+    const events = parseEvents(body.events);
+    const slabs = parseSlabs(body.slabs);
+    const description = parseDescription(body.description);
+    const error = events.error ?? slabs.error ?? description.error;
+    if (error) return c.json({ error }, 400);
+
+    try {
+      if (owner !== null) {
+        const limit = maxWebhooksPerOwner();
+        if ((await countWebhookSubscriptions(owner)) >= limit) {
+          return c.json({ error: `Webhook limit reached (${limit} per wallet)` }, 409);
+        }
+      }
+      const secret = `whsec_${randomBytes(32).toString("hex")}`;
+      const row = await createWebhookSubscription({
+        url: body.url as string,
+        secret,
+        events: events.value ?? [],
+        slabs: slabs.value ?? [],
+        description: description.value ?? null,
+        owner,
+      });
+      logger.info("Webhook registered", {
+        id: row.id,
+        owner,
+        events: row.events.length,
+        slabs: row.slabs.length,
+      });
+      // The secret is only ever returned here
+      return c.json({ subscription: publicSubscription(row), secret }, 201);
+    } catch (err) {
+      logger.error("Webhook registration error", { error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to register webhook" }, 500);
+    }
+  });
+
+  app.get("/webhooks", auth, async (c) => {
+    try {
+      const rows = await listWebhookSubscriptions(c.get("owner") ?? undefined);
+      return c.json({ subscriptions: rows.map(publicSubscription) });
+    } catch (err) {
+      logger.error("Webhook list error", { error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to fetch webhooks" }, 500);
+    }
+  });
+
+  app.get("/webhooks/:id", auth, async (c) => {
+    const id = c.req.param("id");
+    if (!UUID_RE.test(id)) return c.json({ error: "Invalid webhook id" }, 400);
+    try {
+      const row = await getWebhookSubscription(id);
+      const owner = c.get("owner");
+      if (!row || (owner !== null && row.owner !== owner)) return c.json({ error: "Webhook not found" }, 404);
+      return c.json({ subscription: publicSubscription(row) });
+    } catch (err) {
+      logger.error("Webhook fetch error", { id, error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to fetch webhook" }, 500);
+    }
+  });
+
+  app.patch("/webhooks/:id", auth, async (c) => {
+    const id = c.req.param("id");
+    if (!UUID_RE.test(id)) return c.json({ error: "Invalid webhook id" }, 400);
+    const body = await readJson(c);
+    if (!body) return c.json({ error: "Body must be a JSON object" }, 400);
+
+    const patch: WebhookSubscriptionPatch = {};
+    if (body.url !== undefined) {
+      const urlError = await validateWebhookUrl(body.url);
+      if (urlError) return c.json({ error: urlError }, 400);
+      patch.url = body.url as string;
+    }
+    if (body.active !== undefined) {
+      if (typeof body.active !== "boolean") return c.json({ error: "active must be a boolean" }, 400);
+      patch.active = body.active;
+    }
+    const events = parseEvents(body.events);
+    const slabs = parseSlabs(body.slabs);
+    const description = parseDescription(body.description);
+    const error = events.error ?? slabs.error ?? description.error;
+    if (error) return c.json({ error }, 400);
+    if (events.value) patch.events = events.value;
+    if (slabs.value) patch.slabs = slabs.value;
+    if (description.value !== undefined) patch.description = description.value;
+    if (Object.keys(patch).length === 0) return c.json({ error: "Nothing to update" }, 400);
+
+    try {
+      if (!(await canAccess(c.get("owner"), id))) return c.json({ error: "Webhook not found" }, 404);
+      const row = await updateWebhookSubscription(id, patch);
+      if (!row) return c.json({ error: "Webhook not found" }, 404);
+      return c.json({ subscription: publicSubscription(row) });
+    } catch (err) {
+      logger.error("Webhook update error", { id, error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to update webhook" }, 500);
+    }
+  });
+
+  app.delete("/webhooks/:id", auth, async (c) => {
+    const id = c.req.param("id");
+    if (!UUID_RE.test(id)) return c.json({ error: "Invalid webhook id" }, 400);
+    try {
+      if (!(await canAccess(c.get("owner"), id)) || !(await deleteWebhookSubscription(id))) {
+        return c.json({ error: "Webhook not found" }, 404);
+      }
+      logger.info("Webhook deleted", { id });
+      return c.json({ deleted: true });
+    } catch (err) {
+      logger.error("Webhook delete error", { id, error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to delete webhook" }, 500);
+    }
+  });
+
+  app.get("/webhooks/:id/deliveries", auth, async (c) => {
+    const id = c.req.param("id");
+    if (!UUID_RE.test(id)) return c.json({ error: "Invalid webhook id" }, 400);
+    const status = c.req.query("status");
+    if (status !== undefined && !DELIVERY_STATUSES.includes(status as WebhookDeliveryStatus)) {
+      return c.json({ error: `status must be one of ${DELIVERY_STATUSES.join(", ")}` }, 400);
+    }
+    const event = c.req.query("event");
+    const { limit } = sanitizePagination(c.req.query("limit"), 0);
+
+    try {
+      if (!(await canAccess(c.get("owner"), id))) return c.json({ error: "Webhook not found" }, 404);
+      const deliveries = await getWebhookDeliveries(id, {
+        status: status as WebhookDeliveryStatus | undefined,
+        event,
+        limit: Math.min(limit, 200),
+      });
+      return c.json({ deliveries });
+    } catch (err) {
+      logger.error("Webhook deliveries error", { id, error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to fetch deliveries" }, 500);
+    }
+  });
+
+  app.get("/webhooks/:id/deliveries/:deliveryId", auth, async (c) => {
+    const id = c.req.param("id");
+    const deliveryId = Number(c.req.param("deliveryId"));
+    if (!UUID_RE.test(id)) return c.json({ error: "Invalid webhook id" }, 400);
+    if (!Number.isSafeInteger(deliveryId) || deliveryId <= 0) return c.json({ error: "Invalid delivery id" }, 400);
+
+    try {
+      if (!(await canAccess(c.get("owner"), id))) return c.json({ error: "Webhook not found" }, 404);
+      const delivery = await getWebhookDelivery(id, deliveryId);
+      if (!delivery) return c.json({ error: "Delivery not found" }, 404);
+      const attempts = await getWebhookAttempts(deliveryId);
+      return c.json({ delivery, attempts });
+    } catch (err) {
+      logger.error("Webhook delivery error", { id, deliveryId, error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to fetch delivery" }, 500);
+    }
+  });
+
+  app.post("/webhooks/:id/deliveries/:deliveryId/redeliver", auth, async (c) => {
+    const id = c.req.param("id");
+    const deliveryId = Number(c.req.param("deliveryId"));
+    if (!UUID_RE.test(id)) return c.json({ error: "Invalid webhook id" }, 400);
+    if (!Number.isSafeInteger(deliveryId) || deliveryId <= 0) return c.json({ error: "Invalid delivery id" }, 400);
+
+    try {
+      if (!(await canAccess(c.get("owner"), id))) return c.json({ error: "Webhook not found" }, 404);
+      if (!(await redeliverWebhook(id, deliveryId))) {
+        return c.json({ error: "Delivery not found or currently being sent" }, 404);
+      }
+      return c.json({ queued: true });
+    } catch (err) {
+      logger.error("Webhook redeliver error", { id, deliveryId, error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to re-queue delivery" }, 500);
+    }
+  });
+
+  app.get("/webhooks/:id/dead-letters", auth, async (c) => {
+    const id = c.req.param("id");
+    if (!UUID_RE.test(id)) return c.json({ error: "Invalid webhook id" }, 400);
+    const { limit } = sanitizePagination(c.req.query("limit"), 0);
+
+    try {
+      if (!(await canAccess(c.get("owner"), id))) return c.json({ error: "Webhook not found" }, 404);
+      const deadLetters = await getWebhookDeadLetters(id, Math.min(limit, 200));
+      return c.json({ deadLetters });
+    } catch (err) {
+      logger.error("Webhook dead letters error", { id, error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to fetch dead letters" }, 500);
+    }
+  });
+
+  return app;
+}
@@ -0,0 +1,302 @@
+import { randomBytes } from "node:crypto";
+import { PublicKey, type Connection } from "@solana/web3.js";
+import {
+  parseConfig,
+  parseEngine,
+  parseParams,
+  parseAllAccounts,
+  computeAccountHealth,
+} from "@percolator/sdk";
+import { createLogger } from "@percolator/shared";
+import { verifyWalletSignature } from "../middleware/wallet-auth.js";
+
+const logger = createLogger("api:ws-accounts");
+
+/** Per-account channel types: `account:<slab>:<owner>` and `health:<slab>:<owner>` */
+export const ACCOUNT_CHANNEL_TYPES = ["account", "health"] as const;
+export type AccountChannelType = (typeof ACCOUNT_CHANNEL_TYPES)[number];
+
+/** How long a signed wallet auth message stays valid (same window as HMAC tokens) */
+export const WALLET_AUTH_MAX_AGE_MS = 5 * 60 * 1000;
+/** Tolerated client clock skew for timestamps in the future */
+const WALLET_AUTH_MAX_SKEW_MS = 60 * 1000;
+
+/**
+ * Distance-to-liquidation thresholds (bps of notional) that trigger a
+ * `liquidation_warning` when an account crosses below them, tightest last.
+ */
+export const WS_LIQUIDATION_WARNING_BPS = parseWarningThresholds(
+  process.env.WS_LIQUIDATION_WARNING_BPS ?? "500,200,100",
+);
+
+export function parseWarningThresholds(raw: string): number[] {
+  return [...new Set(
+    raw.split(",").map((s) => Number(s.trim())).filter((n) => Number.isInteger(n) && n > 0),
+  )].sort((a, b) => b - a);
+}
+
+// ---------------------------------------------------------------------------
+// Wallet-signed authentication
+// ---------------------------------------------------------------------------
+
+/**
+ * A fresh server challenge for wallet auth. Each connection gets one, and a
+ * new one after every successful `auth_wallet`, so a captured signature cannot
+ * be replayed on another connection or twice on the same one.
+ */
+export function newWalletChallenge(): string {
+  return randomBytes(16).toString("hex");
+}
+
+/**
+ * The message a wallet signs (`signMessage`) to stream its own accounts.
+ * Clients send `{ type: "auth_wallet", owner, timestamp, signature }` with the
+ * signature base64-encoded, signing the challenge the server last sent them.
+ */
+export function walletAuthMessage(owner: string, challenge: string, timestamp: number): string {
+  return (
+    `Percolator: stream account updates\nWallet: ${owner}\n` +
+    `Challenge: ${challenge}\nTimestamp: ${timestamp}`
+  );
+}
+
+/** Verify a wallet's ed25519 signature over {@link walletAuthMessage}. */
+export function verifyWalletAuth(
+  owner: string,
+  challenge: string,
+  timestamp: number,
+  signature: string,
+  now: number = Date.now(),
+): boolean {
+  if (!challenge || !Number.isSafeInteger(timestamp)) return false;
+  if (now - timestamp > WALLET_AUTH_MAX_AGE_MS || timestamp - now > WALLET_AUTH_MAX_SKEW_MS) return false;
+  return verifyWalletSignature(owner, walletAuthMessage(owner, challenge, timestamp), signature);
+}
+
+// ---------------------------------------------------------------------------
+// Account snapshots
+// ---------------------------------------------------------------------------
+
+/** One account's position and margin, bigints as decimal strings */
+export interface AccountStreamEntry {
+  accountIdx: number;
+  positionSize: string;
+  entryPriceE6: string;
+  capital: string;
+  unrealizedPnl: string;
+  pendingFunding: string;
+  equity: string;
+  notional: string;
+  maintenanceMargin: string;
+  marginRatioBps: string | null;
+  liquidationPriceE6: string;
+  /** bufferToLiquidation / notional in bps; null when flat or unpriced */
+  distanceToLiquidationBps: string | null;
+  liquidatable: boolean;
+}
+
+export interface OwnerSnapshot {
+  priceE6: string;
+  slot: string;
+  accounts: AccountStreamEntry[];
+}
+
+/**
+ * Decode a slab once and build the snapshot for each requested owner, priced
+ * like `GET /markets/:slab/accounts/:owner/health`. Owners with no account on
+ * the slab get an empty list.
+ */
+export function snapshotOwners(data: Uint8Array, owners: Iterable<string>): Map<string, OwnerSnapshot> {
+  const cfg = parseConfig(data);
+  const engine = parseEngine(data);
+  const params = parseParams(data);
+  const priceE6 = cfg.lastEffectivePriceE6 > 0n ? cfg.lastEffectivePriceE6 : cfg.authorityPriceE6;
+
+  const snapshots = new Map<string, OwnerSnapshot>();
+  for (const owner of owners) {
+    snapshots.set(owner, { priceE6: priceE6.toString(), slot: engine.currentSlot.toString(), accounts: [] });
+  }
+
+  for (const { idx, account } of parseAllAccounts(data)) {
+    const snapshot = snapshots.get(account.owner.toBase58());
+    if (!snapshot) continue;
+    const h = computeAccountHealth(account, engine, params, priceE6);
+    const distance = h.notional > 0n ? (h.bufferToLiquidation * 10_000n) / h.notional : null;
+    snapshot.accounts.push({
+      accountIdx: idx,
+      positionSize: account.positionSize.toString(),
+      entryPriceE6: account.entryPrice.toString(),
+      capital: account.capital.toString(),
+      unrealizedPnl: h.unrealizedPnl.toString(),
+      pendingFunding: h.pendingFunding.toString(),
+      equity: h.equity.toString(),
+      notional: h.notional.toString(),
+      maintenanceMargin: h.maintenanceMargin.toString(),
+      marginRatioBps: h.marginRatioBps?.toString() ?? null,
+      liquidationPriceE6: h.liquidationPriceE6.toString(),
+      distanceToLiquidationBps: distance?.toString() ?? null,
+      liquidatable: h.liquidatable,
+    });
+  }
+  return snapshots;
+}
+
+// ---------------------------------------------------------------------------
+// Liquidation warnings
+// ---------------------------------------------------------------------------
+
+/** Tightest threshold the distance is at or under, or null when above all of them */
+export function warningLevel(distanceBps: string | null, thresholds: number[]): number | null {
+  if (distanceBps === null) return null;
+  const distance = Number(distanceBps);
+  let level: number | null = null;
+  for (const t of thresholds) {
+    if (distance <= t) level = t;
+  }
+  return level;
+}
+
+export interface LiquidationWarning {
+  accountIdx: number;
+  thresholdBps: number;
+  distanceToLiquidationBps: string;
+  liquidatable: boolean;
+}
+
+/**
+ * Remembers each account's warning level so a warning fires once per
+ * threshold crossed on the way down. Recovering above a threshold re-arms it.
+ */
+export class LiquidationWarningTracker {
+  private levels = new Map<string, number>();
+
+  constructor(private readonly thresholds: number[] = WS_LIQUIDATION_WARNING_BPS) {}
+
+  /** Warnings newly crossed by this snapshot of `owner`'s accounts on `slab`. */
+  update(slab: string, owner: string, snapshot: OwnerSnapshot): LiquidationWarning[] {
+    const warnings: LiquidationWarning[] = [];
+    const seen = new Set<string>();
+    for (const a of snapshot.accounts) {
+      const key = `${slab}:${owner}:${a.accountIdx}`;
+      seen.add(key);
+      const level = warningLevel(a.distanceToLiquidationBps, this.thresholds);
+      const prev = this.levels.get(key);
+      if (level === null) {
+        this.levels.delete(key);
+        continue;
+      }
+      this.levels.set(key, level);
+      if (prev === undefined || level < prev) {
+        warnings.push({
+          accountIdx: a.accountIdx,
+          thresholdBps: level,
+          distanceToLiquidationBps: a.distanceToLiquidationBps!,
+          liquidatable: a.liquidatable,
+        });
+      }
+    }
+    // Closed accounts drop out of the snapshot; forget their level
+    const prefix = `${slab}:${owner}:`;
+    for (const key of this.levels.keys()) {
+      if (key.startsWith(prefix) && !seen.has(key)) this.levels.delete(key);
+    }
+    return warnings;
+  }
+
+  /** Forget every account on a slab (no more subscribers). */
+  clearSlab(slab: string): void {
+    for (const key of this.levels.keys()) {
+      if (key.startsWith(`${slab}:`)) this.levels.delete(key);
+    }
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Slab watcher
+// ---------------------------------------------------------------------------
+
+/**
+ * Ref-counted `accountSubscribe` per slab. The first account/health
+ * subscription on a slab opens the RPC subscription and the last one closes it,
+ * so the API holds one subscription per watched slab no matter how many
+ * wallets are streaming.
+ */
+export class SlabAccountWatcher {
+  private subs = new Map<string, { id: number; refs: number }>();
+
+  constructor(
+    private readonly getConnection: () => Connection,
+    private readonly onChange: (slab: string, data: Uint8Array) => void,
+  ) {}
+
+  watch(slab: string): void {
+    const existing = this.subs.get(slab);
+    if (existing) {
+      existing.refs++;
+      return;
+    }
+    const id = this.getConnection().onAccountChange(
+      new PublicKey(slab),
+      (info) => this.onChange(slab, info.data),
+      "confirmed",
+    );
+    this.subs.set(slab, { id, refs: 1 });
+    logger.info("Watching slab for account channels", { slab });
+  }
+
+  /** Returns true when this released the slab's last reference. */
+  unwatch(slab: string): boolean {
+    const sub = this.subs.get(slab);
+    if (!sub) return false;
+    if (--sub.refs > 0) return false;
+    this.subs.delete(slab);
+    this.getConnection().removeAccountChangeListener(sub.id).catch((err) => {
+      logger.warn("Failed to remove slab listener", { slab, error: err instanceof Error ? err.message : err });
+    });
+    logger.info("Stopped watching slab", { slab });
+    return true;
+  }
+
+  get size(): number {
+    return this.subs.size;
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Messages
+// ---------------------------------------------------------------------------
+
+/**
+ * Server → client message for an account channel. `account` carries the
+ * position and PnL picture, `health` the margin view.
+ */
+export function accountChannelMessage(
+  type: AccountChannelType,
+  slab: string,
+  owner: string,
+  snapshot: OwnerSnapshot,
+): string {
+  const accounts = type === "account"
+    ? snapshot.accounts.map((a) => ({
+      accountIdx: a.accountIdx,
+      positionSize: a.positionSize,
+      entryPriceE6: a.entryPriceE6,
+      capital: a.capital,
+      unrealizedPnl: a.unrealizedPnl,
+      pendingFunding: a.pendingFunding,
+      equity: a.equity,
+      marginRatioBps: a.marginRatioBps,
+    }))
+    : snapshot.accounts.map((a) => ({
+      accountIdx: a.accountIdx,
+      positionSize: a.positionSize,
+      equity: a.equity,
+      notional: a.notional,
+      maintenanceMargin: a.maintenanceMargin,
+      marginRatioBps: a.marginRatioBps,
+      liquidationPriceE6: a.liquidationPriceE6,
+      distanceToLiquidationBps: a.distanceToLiquidationBps,
+      liquidatable: a.liquidatable,
+    }));
+  return JSON.stringify({ type, slab, owner, priceE6: snapshot.priceE6, slot: snapshot.slot, accounts });
+}
@@ -2,7 +2,20 @@ import { WebSocketServer, WebSocket } from "ws";
 import type { Server } from "node:http";
 import type { IncomingMessage } from "node:http";
 import { createHmac, timingSafeEqual } from "node:crypto";
-import { eventBus, getSupabase, createLogger, sanitizeSlabAddress } from "@percolator/shared";
+import { PublicKey } from "@solana/web3.js";
+import { fetchSlab } from "@percolator/sdk";
+import { eventBus, getSupabase, getConnection, createLogger, sanitizeSlabAddress } from "@percolator/shared";
+import {
+  ACCOUNT_CHANNEL_TYPES,
+  LiquidationWarningTracker,
+  SlabAccountWatcher,
+  accountChannelMessage,
+  newWalletChallenge,
+  snapshotOwners,
+  verifyWalletAuth,
+  type AccountChannelType,
+  type OwnerSnapshot,
+} from "./ws-accounts.js";
 
 const logger = createLogger("api:ws");
 
@@ -47,6 +60,8 @@ const PONG_TIMEOUT_MS = 10_000; // 10 seconds to respond to ping
 
 // Price update batching configuration
 const PRICE_BATCH_INTERVAL_MS = 500; // Batch price updates every 500ms per slab
+// Account/health channels: decode a changed slab at most once a second
+const ACCOUNT_BATCH_INTERVAL_MS = 1_000;
 
 interface WsClient {
   ws: WebSocket;
@@ -56,6 +71,8 @@ interface WsClient {
   isAlive: boolean; // BH2: Track pong responses
   authenticated: boolean; // Auth status
   authenticatedSlab?: string; // Slab address from auth token (if slab-bound)
+  walletOwners: Set<string>; // Wallets proven via auth_wallet (account/health channels)
+  walletChallenge: string; // Single-use nonce the next auth_wallet must sign
   ip: string; // Client IP address
   authTimeout?: ReturnType<typeof setTimeout>; // Auth timeout timer
 }
@@ -262,16 +279,21 @@ function verifyWsToken(token: string, expectedSlab?: string): { isValid: boolean
 }
 
 /**
- * Extract slab address from channel name (e.g., "price:SOL" -> "SOL")
+ * Extract slab address from channel name (e.g., "price:SOL" -> "SOL",
+ * "account:SOL:OWNER" -> "SOL")
  */
 function extractSlabFromChannel(channel: string): string | null {
   const parts = channel.split(":");
-  if (parts.length === 2) {
+  if (parts.length === 2 || parts.length === 3) {
     return parts[1];
   }
   return null;
 }
 
+function isAccountChannelType(channelType: string): channelType is AccountChannelType {
+  return (ACCOUNT_CHANNEL_TYPES as readonly string[]).includes(channelType);
+}
+
 /**
  * Get all slabs a client is subscribed to
  */
@@ -349,6 +371,112 @@ function flushPriceUpdate(slabAddress: string): void {
   }
 }
 
+// Account/health channels: one RPC account subscription per watched slab,
+// with changes batched per slab like price updates
+const pendingSlabData = new Map<string, Uint8Array>();
+const accountUpdateTimers = new Map<string, ReturnType<typeof setTimeout>>();
+const lastAccountState = new Map<string, string>(); // "slab:owner" -> last streamed accounts
+const warningTracker = new LiquidationWarningTracker();
+const accountWatcher = new SlabAccountWatcher(getConnection, (slabAddress, data) => {
+  pendingSlabData.set(slabAddress, data);
+  if (!accountUpdateTimers.has(slabAddress)) {
+    accountUpdateTimers.set(
+      slabAddress,
+      setTimeout(() => flushAccountUpdate(slabAddress), ACCOUNT_BATCH_INTERVAL_MS),
+    );
+  }
+});
+
+function sendToChannel(slabClients: Set<WsClient>, channel: string, msg: string): void {
+  for (const client of slabClients) {
+    if (
+      client.ws.readyState === WebSocket.OPEN &&
+      client.subscriptions.has(channel)
+    ) {
+      if (client.ws.bufferedAmount > MAX_BUFFER_BYTES) continue;
+      client.ws.send(msg);
+      metrics.messagesSent++;
+      metrics.bytesSent += msg.length;
+    }
+  }
+}
+
+/**
+ * Decode the latest slab data once and stream it to every account/health
+ * subscriber on the slab. Unchanged accounts aren't re-sent; warnings go to
+ * health subscribers when an account crosses a distance-to-liquidation
+ * threshold.
+ */
+function flushAccountUpdate(slabAddress: string): void {
+  const data = pendingSlabData.get(slabAddress);
+  pendingSlabData.delete(slabAddress);
+  accountUpdateTimers.delete(slabAddress);
+
+  const slabClients = connectionsPerSlab.get(slabAddress);
+  if (!data || !slabClients) return;
+
+  const owners = new Set<string>();
+  for (const client of slabClients) {
+    for (const channel of client.subscriptions) {
+      const [channelType, slab, owner] = channel.split(":");
+      if (owner && slab === slabAddress && isAccountChannelType(channelType)) owners.add(owner);
+    }
+  }
+  if (owners.size === 0) return;
+
+  let snapshots: Map<string, OwnerSnapshot>;
+  try {
+    snapshots = snapshotOwners(data, owners);
+  } catch (err) {
+    logger.warn("Failed to decode slab for account channels", {
+      slab: slabAddress,
+      error: err instanceof Error ? err.message : err,
+    });
+    return;
+  }
+
+  for (const [owner, snapshot] of snapshots) {
+    const stateKey = `${slabAddress}:${owner}`;
+    const state = `${snapshot.priceE6}|${JSON.stringify(snapshot.accounts)}`;
+    if (lastAccountState.get(stateKey) !== state) {
+      lastAccountState.set(stateKey, state);
+      for (const channelType of ACCOUNT_CHANNEL_TYPES) {
+        sendToChannel(
+          slabClients,
+          `${channelType}:${slabAddress}:${owner}`,
+          accountChannelMessage(channelType, slabAddress, owner, snapshot),
+        );
+      }
+    }
+
+    for (const warning of warningTracker.update(slabAddress, owner, snapshot)) {
+      sendToChannel(
+        slabClients,
+        `health:${slabAddress}:${owner}`,
+        JSON.stringify({ type: "liquidation_warning", slab: slabAddress, owner, ...warning, timestamp: Date.now() }),
+      );
+    }
+  }
+}
+
+/**
+ * Release the slab watch held by an account/health channel; the last
+ * release stops the RPC subscription and drops the slab's stream state.
+ */
+function releaseAccountChannel(channel: string): void {
+  const [channelType, slabAddress] = channel.split(":");
+  if (!isAccountChannelType(channelType) || !accountWatcher.unwatch(slabAddress)) return;
+
+  const timer = accountUpdateTimers.get(slabAddress);
+  if (timer) clearTimeout(timer);
+  accountUpdateTimers.delete(slabAddress);
+  pendingSlabData.delete(slabAddress);
+  warningTracker.clearSlab(slabAddress);
+  for (const key of lastAccountState.keys()) {
+    if (key.startsWith(`${slabAddress}:`)) lastAccountState.delete(key);
+  }
+}
+
 /**
  * Get WebSocket metrics for /ws/stats endpoint
  */
@@ -366,6 +494,7 @@ export function getWebSocketMetrics(): any {
       maxConnectionsPerSlab: MAX_CONNECTIONS_PER_SLAB,
       maxConnectionsPerIp: MAX_CONNECTIONS_PER_IP,
     },
+    watchedSlabs: accountWatcher.size,
   };
 }
 
@@ -519,6 +648,8 @@ export function setupWebSocket(server: Server): WebSocketServer {
       isAlive: true,
       authenticated,
       authenticatedSlab,
+      walletOwners: new Set(),
+      walletChallenge: newWalletChallenge(),
       ip: clientIp
     };
     clients.add(client);
@@ -576,7 +707,11 @@ export function setupWebSocket(server: Server): WebSocketServer {
       }, PONG_TIMEOUT_MS);
     }, HEARTBEAT_INTERVAL_MS);
 
-    ws.send(JSON.stringify({ type: "connected", message: "Percolator WebSocket connected" }));
+    ws.send(JSON.stringify({
+      type: "connected",
+      message: "Percolator WebSocket connected",
+      walletChallenge: client.walletChallenge,
+    }));
 
     ws.on("message", async (raw) => {
       try {
@@ -597,6 +732,9 @@ export function setupWebSocket(server: Server): WebSocketServer {
           slabAddress?: string; 
           token?: string;
           channels?: string[];
+          owner?: string;
+          timestamp?: number;
+          signature?: string;
         };
         
         // Handle auth message
@@ -634,6 +772,35 @@ export function setupWebSocket(server: Server): WebSocketServer {
           return;
         }
         
+        // Handle wallet auth: a signed message over this connection's current
+        // challenge proves the client controls a wallet and unlocks that
+        // wallet's account/health channels
+        if (msg.type === "auth_wallet") {
+          const owner = typeof msg.owner === "string" ? msg.owner : "";
+          if (
+            typeof msg.timestamp !== "number" ||
+            typeof msg.signature !== "string" ||
+            !verifyWalletAuth(owner, client.walletChallenge, msg.timestamp, msg.signature)
+          ) {
+            logger.warn("Invalid wallet auth signature", { ip: client.ip, owner });
+            recordAuthFailure(client.ip);
+            ws.send(JSON.stringify({ type: "error", message: "Invalid wallet signature" }));
+            return;
+          }
+
+          // The challenge is spent; the next auth_wallet must sign a new one
+          client.walletChallenge = newWalletChallenge();
+          const canonical = new PublicKey(owner).toBase58();
+          client.walletOwners.add(canonical);
+          logger.info("Client authenticated wallet", { ip: client.ip, owner: canonical });
+          ws.send(JSON.stringify({
+            type: "wallet_authenticated",
+            owner: canonical,
+            walletChallenge: client.walletChallenge,
+          }));
+          return;
+        }
+
         // Handle subscribe with channels array
         if (msg.type === "subscribe" && msg.channels && Array.isArray(msg.channels)) {
           const subscribed: string[] = [];
@@ -646,12 +813,26 @@ export function setupWebSocket(server: Server): WebSocketServer {
               continue;
             }
             
-            const [channelType, slabAddress] = channel.split(":");
-            if (!["price", "trades", "funding"].includes(channelType)) {
+            const [channelType, slabAddress, owner] = channel.split(":");
+            const isAccountChannel = isAccountChannelType(channelType);
+            if (!["price", "trades", "funding"].includes(channelType) && !isAccountChannel) {
               errors.push(`Unknown channel type: ${channelType}`);
               continue;
             }
             
+            // account:<slab>:<owner> and health:<slab>:<owner> carry an owner;
+            // the other channel types must not
+            if (isAccountChannel !== (owner !== undefined) || channel.split(":").length > 3) {
+              errors.push(`Invalid channel format: ${channel}`);
+              continue;
+            }
+            
+            // Account channels stream one wallet's positions — only to that wallet
+            if (isAccountChannel && !client.walletOwners.has(owner)) {
+              errors.push(`Wallet ${owner} not authenticated; send auth_wallet first`);
+              continue;
+            }
+            
             // Sanitize slab address
             const sanitized = sanitizeSlabAddress(slabAddress);
             if (!sanitized) {
@@ -670,7 +851,9 @@ export function setupWebSocket(server: Server): WebSocketServer {
               continue;
             }
             
-            const fullChannel = `${channelType}:${sanitized}`;
+            const fullChannel = isAccountChannel
+              ? `${channelType}:${sanitized}:${owner}`
+              : `${channelType}:${sanitized}`;
             
             // Check if already subscribed
             if (client.subscriptions.has(fullChannel)) {
@@ -696,6 +879,16 @@ export function setupWebSocket(server: Server): WebSocketServer {
               continue;
             }
             
+            if (isAccountChannel) {
+              try {
+                accountWatcher.watch(sanitized);
+              } catch (err) {
+                logger.warn("Failed to watch slab", { slab: sanitized, error: err instanceof Error ? err.message : err });
+                errors.push(`Cannot stream accounts for slab ${sanitized}`);
+                continue;
+              }
+            }
+            
             client.subscriptions.add(fullChannel);
             globalSubscriptionCount++;
             addClientToSlab(client, sanitized);
@@ -735,6 +928,27 @@ export function setupWebSocket(server: Server): WebSocketServer {
                 }
               }
             }
+            
+            // Send an initial snapshot for account/health channels, one slab read per slab
+            const accountChannels = new Map<string, string[]>();
+            for (const channel of subscribed) {
+              const [channelType, slab] = channel.split(":");
+              if (!isAccountChannelType(channelType)) continue;
+              accountChannels.set(slab, [...(accountChannels.get(slab) ?? []), channel]);
+            }
+            for (const [slab, channels] of accountChannels) {
+              try {
+                const data = await fetchSlab(getConnection(), new PublicKey(slab));
+                const snapshots = snapshotOwners(data, channels.map((ch) => ch.split(":")[2]));
+                for (const channel of channels) {
+                  const [channelType, , owner] = channel.split(":") as [AccountChannelType, string, string];
+                  if (ws.bufferedAmount > MAX_BUFFER_BYTES) break;
+                  ws.send(accountChannelMessage(channelType, slab, owner, snapshots.get(owner)!));
+                }
+              } catch {
+                // Ignore errors fetching the initial snapshot — updates follow on the next slab change
+              }
+            }
           }
           
           if (errors.length > 0) {
@@ -791,6 +1005,7 @@ export function setupWebSocket(server: Server): WebSocketServer {
             if (client.subscriptions.delete(channel)) {
               globalSubscriptionCount--;
               unsubscribed.push(channel);
+              releaseAccountChannel(channel);
               
               // Extract slab and remove from slab tracking if no more subs for this slab
               const slab = extractSlabFromChannel(channel);
@@ -823,7 +1038,13 @@ export function setupWebSocket(server: Server): WebSocketServer {
               }
             }
             
-            removeClientFromSlab(client, sanitized);
+            // Account/health channels on the slab stay subscribed
+            const stillHasSlab = Array.from(client.subscriptions).some(
+              ch => extractSlabFromChannel(ch) === sanitized
+            );
+            if (!stillHasSlab) {
+              removeClientFromSlab(client, sanitized);
+            }
             ws.send(JSON.stringify({ type: "unsubscribed", slabAddress: sanitized, channels: unsubscribed }));
           }
         }
@@ -863,6 +1084,11 @@ export function setupWebSocket(server: Server): WebSocketServer {
         removeClientFromSlab(client, slab);
       }
       
+      // Release slab watches held by account/health channels
+      for (const channel of client.subscriptions) {
+        releaseAccountChannel(channel);
+      }
+      
       // H2: O(1) removal with Set
       // Decrement global subscription count for all client subscriptions
       globalSubscriptionCount -= client.subscriptions.size;
@@ -0,0 +1,246 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { PublicKey } from "@solana/web3.js";
+import { accountRoutes } from "../../src/routes/accounts.js";
+import { clearCache } from "../../src/middleware/cache.js";
+
+vi.mock("@percolator/shared", () => ({
+  getConnection: vi.fn(),
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+  sanitizeSlabAddress: vi.fn((addr: string) => addr),
+  sanitizePagination: vi.fn((limit?: string) => ({ limit: limit ? Number(limit) : 50, offset: 0 })),
+  getAccountActivity: vi.fn(),
+  getAccountSnapshotsByOwner: vi.fn(),
+  getAccountSnapshotsByIndex: vi.fn(),
+}));
+
+vi.mock("@percolator/sdk", async () => ({
+  computeAccountHealth: (await vi.importActual<typeof import("@percolator/sdk")>("@percolator/sdk")).computeAccountHealth,
+  fetchSlab: vi.fn(),
+  parseConfig: vi.fn(),
+  parseEngine: vi.fn(),
+  parseParams: vi.fn(),
+  parseAllAccounts: vi.fn(),
+}));
+
+const { fetchSlab, parseConfig, parseEngine, parseParams, parseAllAccounts } = await import("@percolator/sdk");
+const { getAccountActivity, getAccountSnapshotsByOwner, getAccountSnapshotsByIndex } = await import("@percolator/shared");
+
+const SLAB = "11111111111111111111111111111111";
+const OWNER = new PublicKey("So11111111111111111111111111111111111111112");
+
+describe("account routes", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    clearCache();
+
+    vi.mocked(fetchSlab).mockResolvedValue(new Uint8Array(100));
+    vi.mocked(parseConfig).mockReturnValue({
+      lastEffectivePriceE6: 1_000_000n,
+      authorityPriceE6: 0n,
+    } as any);
+    vi.mocked(parseEngine).mockReturnValue({
+      currentSlot: 500n,
+      fundingIndexQpbE6: 0n,
+    } as any);
+    vi.mocked(parseParams).mockReturnValue({
+      maintenanceMarginBps: 500n,
+      initialMarginBps: 1000n,
+      warmupPeriodSlots: 0n,
+    } as any);
+  });
+
+  describe("GET /markets/:slab/accounts/:owner/health", () => {
+    it("should return health for the owner's accounts only", async () => {
+      vi.mocked(parseAllAccounts).mockReturnValue([
+        {
+          idx: 3,
+          account: {
+            kind: 0,
+            owner: OWNER,
+            capital: 100_000_000n,
+            pnl: 0n,
+            positionSize: 10_000_000_000n,
+            entryPrice: 1_000_000n,
+            fundingIndex: 0n,
+            warmupStartedAtSlot: 0n,
+          },
+        },
+        {
+          idx: 4,
+          account: {
+            kind: 0,
+            owner: new PublicKey(SLAB),
+            capital: 1n,
+            pnl: 0n,
+            positionSize: 0n,
+            entryPrice: 0n,
+            fundingIndex: 0n,
+            warmupStartedAtSlot: 0n,
+          },
+        },
+      ] as any);
+
+      const app = accountRoutes();
+      const res = await app.request(`/markets/${SLAB}/accounts/${OWNER.toBase58()}/health`);
+
+      expect(res.status).toBe(200);
+      const data = await res.json();
+      expect(data.accounts).toHaveLength(1);
+      expect(data.accounts[0].accountIdx).toBe(3);
+      expect(data.accounts[0].marginRatioBps).toBe("100");
+      expect(data.accounts[0].liquidatable).toBe(true);
+    });
+
+    it("should return 502 when the market can't be fetched and 500 when it can't be parsed", async () => {
+      vi.mocked(fetchSlab).mockRejectedValueOnce(new Error("rpc timeout"));
+      const app = accountRoutes();
+      const url = `/markets/${SLAB}/accounts/${OWNER.toBase58()}/health`;
+
+      expect((await app.request(url)).status).toBe(502);
+
+      clearCache();
+      vi.mocked(parseAllAccounts).mockImplementationOnce(() => {
+        throw new Error("bad layout");
+      });
+      expect((await app.request(url)).status).toBe(500);
+    });
+
+    it("should return 404 when the owner has no account", async () => {
+      vi.mocked(parseAllAccounts).mockReturnValue([]);
+
+      const app = accountRoutes();
+      const res = await app.request(`/markets/${SLAB}/accounts/${OWNER.toBase58()}/health`);
+
+      expect(res.status).toBe(404);
+    });
+
+    it("should return 400 for an invalid owner", async () => {
+      const app = accountRoutes();
+      const res = await app.request(`/markets/${SLAB}/accounts/not-a-key/health`);
+
+      expect(res.status).toBe(400);
+      const data = await res.json();
+      expect(data.error).toBe("Invalid owner address");
+    });
+  });
+
+  describe("GET /accounts/:owner/activity", () => {
+    const entry = {
+      type: "deposit",
+      slab_address: SLAB,
+      tx_signature: "sig",
+      slot: 10,
+      time: "2026-01-01T00:00:00.000Z",
+      indexed_at: "2026-01-01T00:00:05.000Z",
+      account_idx: 3,
+      amount: "1000",
+      details: {},
+    };
+
+    it("should return the timeline and a paging cursor when full", async () => {
+      vi.mocked(getAccountActivity).mockResolvedValue([entry] as any);
+
+      const app = accountRoutes();
+      const res = await app.request(`/accounts/${OWNER.toBase58()}/activity?slab=${SLAB}&limit=1`);
+
+      expect(res.status).toBe(200);
+      const data = await res.json();
+      expect(data.activity).toEqual([entry]);
+      expect(data.nextBefore).toBe(entry.indexed_at);
+      expect(getAccountActivity).toHaveBeenCalledWith(OWNER.toBase58(), { slab: SLAB, before: undefined, limit: 1 });
+    });
+
+    it("should return 400 for an invalid before timestamp", async () => {
+      const app = accountRoutes();
+      const res = await app.request(`/accounts/${OWNER.toBase58()}/activity?before=yesterday`);
+
+      expect(res.status).toBe(400);
+      expect(getAccountActivity).not.toHaveBeenCalled();
+    });
+
+    it("should return 500 when the query fails", async () => {
+      vi.mocked(getAccountActivity).mockRejectedValue(new Error("db down"));
+
+      const app = accountRoutes();
+      const res = await app.request(`/accounts/${OWNER.toBase58()}/activity`);
+
+      expect(res.status).toBe(500);
+    });
+  });
+
+  describe("account history", () => {
+    const snapshot = {
+      id: 1,
+      slab_address: SLAB,
+      account_idx: 3,
+      owner: OWNER.toBase58(),
+      kind: 0,
+      slot: 1010,
+      capital: "1000000",
+      pnl: "-2500",
+      reserved_pnl: "0",
+      position_size: "500",
+      entry_price_e6: "150000000",
+      funding_index: "0",
+      fee_credits: "0",
+      price_e6: "151000000",
+      closed: false,
+      created_at: "2026-01-01T00:00:00.000Z",
+    };
+
+    it("should return snapshots for a wallet with filters applied", async () => {
+      vi.mocked(getAccountSnapshotsByOwner).mockResolvedValue([snapshot] as any);
+
+      const app = accountRoutes();
+      const res = await app.request(
+        `/accounts/${OWNER.toBase58()}/history?slab=${SLAB}&accountIdx=3&from=2026-01-01T00:00:00Z&limit=10`,
+      );
+
+      expect(res.status).toBe(200);
+      const data = await res.json();
+      expect(data.snapshots).toEqual([snapshot]);
+      expect(getAccountSnapshotsByOwner).toHaveBeenCalledWith(OWNER.toBase58(), {
+        slab: SLAB,
+        accountIdx: 3,
+        from: "2026-01-01T00:00:00.000Z",
+        limit: 10,
+      });
+    });
+
+    it("should return 400 for an invalid accountIdx or time range", async () => {
+      const app = accountRoutes();
+      const badIdx = await app.request(`/accounts/${OWNER.toBase58()}/history?accountIdx=-1`);
+      const badTo = await app.request(`/accounts/${OWNER.toBase58()}/history?to=soon`);
+
+      expect(badIdx.status).toBe(400);
+      expect(badTo.status).toBe(400);
+      expect(getAccountSnapshotsByOwner).not.toHaveBeenCalled();
+    });
+
+    it("should return snapshots for an account index", async () => {
+      vi.mocked(getAccountSnapshotsByIndex).mockResolvedValue([snapshot] as any);
+
+      const app = accountRoutes();
+      const res = await app.request(`/markets/${SLAB}/accounts/3/history`);
+
+      expect(res.status).toBe(200);
+      const data = await res.json();
+      expect(data.accountIdx).toBe(3);
+      expect(getAccountSnapshotsByIndex).toHaveBeenCalledWith(SLAB, 3, { limit: 50 });
+    });
+
+    it("should return 500 when the history query fails", async () => {
+      vi.mocked(getAccountSnapshotsByOwner).mockRejectedValue(new Error("db down"));
+
+      const app = accountRoutes();
+      const res = await app.request(`/accounts/${OWNER.toBase58()}/history`);
+
+      expect(res.status).toBe(500);
+    });
+  });
+});
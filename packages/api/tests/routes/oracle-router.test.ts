@@ -0,0 +1,118 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+
+vi.mock("@percolator/sdk", () => ({
+  resolvePrice: vi.fn(),
+  fetchDexPoolDepth: vi.fn(),
+  poolSideValueUsd: vi.fn(),
+  costToMovePriceUsd: vi.fn((depthUsd: number, moveBps: number) => (depthUsd * moveBps) / 20_000),
+  assessManipulationRisk: vi.fn(() => ({ level: "critical", blocked: true })),
+}));
+
+vi.mock("@percolator/shared", () => ({
+  getConnection: vi.fn(() => ({})),
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+}));
+
+const sdk = await import("@percolator/sdk");
+const { oracleRouterRoutes } = await import("../../src/routes/oracle-router.js");
+
+const POOL = "FxfD37s1AZTeWfFQps9Zpebi2dNQ9QSSDtfMKdbsfKrD";
+let mintSeq = 0;
+// Each test uses a fresh mint so the module-level cache doesn't leak between tests
+const nextMint = () => `Mint${String(++mintSeq).padStart(3, "0")}11111111111111111111111111111111`.slice(0, 44);
+
+function routerResult(mint: string, withDex = true) {
+  const dex = { type: "dex", address: POOL, dexId: "pumpswap", liquidity: 400_000, price: 0.5, confidence: 75 };
+  const jup = { type: "jupiter", address: mint, liquidity: 0, price: 0.5, confidence: 40 };
+  return {
+    mint,
+    bestSource: withDex ? dex : jup,
+    allSources: withDex ? [dex, jup] : [jup],
+    resolvedAt: "2025-01-01T00:00:00.000Z",
+  };
+}
+
+const snapshot = {
+  poolAddress: POOL,
+  dexType: "pumpswap",
+  model: "reserves",
+  slot: 123,
+};
+
+describe("GET /oracle/resolve/:mint", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it("reports the depth of the best DEX pool", async () => {
+    const mint = nextMint();
+    vi.mocked(sdk.resolvePrice).mockResolvedValueOnce(routerResult(mint) as any);
+    vi.mocked(sdk.fetchDexPoolDepth).mockResolvedValueOnce(snapshot as any);
+    vi.mocked(sdk.poolSideValueUsd).mockReturnValueOnce(200_000);
+
+    const res = await oracleRouterRoutes().request(`/oracle/resolve/${mint}`);
+
+    expect(res.status).toBe(200);
+    const data = await res.json();
+    expect(sdk.fetchDexPoolDepth).toHaveBeenCalledWith(expect.anything(), POOL);
+    expect(sdk.poolSideValueUsd).toHaveBeenCalledWith(snapshot, mint, 0.5);
+    expect(data.oracleDepth).toEqual({
+      poolAddress: POOL,
+      dexType: "pumpswap",
+      model: "reserves",
+      slot: 123,
+      depthUsd: 200_000,
+      costToMove1PctUsd: 1_000,
+      risk: null,
+    });
+  });
+
+  it("assesses the proposed market when its parameters are given", async () => {
+    const mint = nextMint();
+    vi.mocked(sdk.resolvePrice).mockResolvedValueOnce(routerResult(mint) as any);
+    vi.mocked(sdk.fetchDexPoolDepth).mockResolvedValueOnce(snapshot as any);
+    vi.mocked(sdk.poolSideValueUsd).mockReturnValueOnce(200_000);
+
+    const res = await oracleRouterRoutes().request(
+      `/oracle/resolve/${mint}?initialMarginBps=500&maxOiUsd=1000000&oraclePriceCapE2bps=10000`,
+    );
+
+    const data = await res.json();
+    expect(sdk.assessManipulationRisk).toHaveBeenCalledWith({
+      poolDepthUsd: 200_000,
+      initialMarginBps: 500,
+      maxOiUsd: 1_000_000,
+      oraclePriceCapE2bps: 10_000,
+    });
+    expect(data.oracleDepth.risk).toEqual({ level: "critical", blocked: true });
+  });
+
+  it("returns null depth when there is no DEX source", async () => {
+    const mint = nextMint();
+    vi.mocked(sdk.resolvePrice).mockResolvedValueOnce(routerResult(mint, false) as any);
+
+    const res = await oracleRouterRoutes().request(`/oracle/resolve/${mint}`);
+
+    const data = await res.json();
+    expect(sdk.fetchDexPoolDepth).not.toHaveBeenCalled();
+    expect(data.oracleDepth).toBeNull();
+  });
+
+  it("still resolves when the pool read fails", async () => {
+    const mint = nextMint();
+    vi.mocked(sdk.resolvePrice).mockResolvedValueOnce(routerResult(mint) as any);
+    vi.mocked(sdk.fetchDexPoolDepth).mockRejectedValueOnce(new Error("429"));
+
+    const res = await oracleRouterRoutes().request(`/oracle/resolve/${mint}`);
+
+    expect(res.status).toBe(200);
+    const data = await res.json();
+    expect(data.bestSource.address).toBe(POOL);
+    expect(data.oracleDepth).toBeNull();
+  });
+});
@@ -0,0 +1,162 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { clearCache } from "../../src/middleware/cache.js";
+
+vi.mock("@percolator/shared", () => ({
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+  sanitizeSlabAddress: vi.fn((addr: string) => (addr.length >= 32 ? addr : null)),
+  sanitizePagination: vi.fn((limit: any) => ({ limit: limit ? Number(limit) : 50, offset: 0 })),
+  getLatestStakePoolSnapshot: vi.fn(),
+  getStakePoolSnapshotSince: vi.fn(),
+  getStakePoolSnapshots: vi.fn(),
+  getStakePoolEvents: vi.fn(),
+}));
+
+vi.mock("@percolator/sdk", async () => ({
+  splitStakeTrancheFees: (await vi.importActual<typeof import("@percolator/sdk")>("@percolator/sdk")).splitStakeTrancheFees,
+}));
+
+const shared = await import("@percolator/shared");
+const { stakeRoutes, stakeApyWindow } = await import("../../src/routes/stake.js");
+
+const POOL = "11111111111111111111111111111111";
+const SLAB = "So11111111111111111111111111111111111111112";
+
+function snapshot(overrides: Record<string, unknown> = {}) {
+  return {
+    id: 1,
+    pool_address: POOL,
+    slab_address: SLAB,
+    slot: 100,
+    pool_mode: 1,
+    total_deposited: "2000000",
+    total_withdrawn: "0",
+    total_flushed: "0",
+    total_returned: "0",
+    total_fees_earned: "0",
+    total_lp_supply: "2000000",
+    pool_value: "2000000",
+    share_price_e6: "1000000",
+    tranche_enabled: true,
+    senior_value: "1000000",
+    senior_lp_supply: "1000000",
+    senior_share_price_e6: "1000000",
+    junior_value: "1000000",
+    junior_lp_supply: "1000000",
+    junior_share_price_e6: "1000000",
+    junior_fee_mult_bps: 20_000,
+    hwm_enabled: true,
+    epoch_high_water_tvl: "2000000",
+    hwm_floor_bps: 9_000,
+    hwm_floor: "1800000",
+    hwm_breached: false,
+    created_at: "2026-10-01T00:00:00.000Z",
+    ...overrides,
+  } as any;
+}
+
+// 36.5 days after `snapshot()`: 10% growth annualizes to exactly 100%
+const later = snapshot({
+  id: 2,
+  total_flushed: "50000",
+  total_returned: "20000",
+  total_fees_earned: "300000",
+  pool_value: "2300000",
+  share_price_e6: "1150000",
+  senior_value: "1100000",
+  senior_share_price_e6: "1100000",
+  junior_value: "1200000",
+  junior_share_price_e6: "1200000",
+  created_at: "2026-11-06T12:00:00.000Z",
+});
+
+describe("stake routes", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    clearCache();
+  });
+
+  describe("stakeApyWindow", () => {
+    it("annualizes share price growth per tranche", () => {
+      const w = stakeApyWindow(snapshot(), later)!;
+
+      expect(w.apy).toEqual({ pool: 1.5, senior: 1, junior: 2 });
+      expect(w).toMatchObject({ flushed: "50000", returned: "20000", feesEarned: "300000" });
+    });
+
+    it("splits fee yield by juniorFeeMultBps", () => {
+      // Average values 1.05M senior / 1.1M junior at 2x → junior earns 2.2/3.25 of fees
+      const w = stakeApyWindow(snapshot(), later)!;
+
+      expect(w.feeApy.senior).toBeCloseTo((300_000 * 1.05 / 3.25) / 1_050_000 * 10, 3);
+      expect(w.feeApy.junior).toBeCloseTo((300_000 * 2.2 / 3.25) / 1_100_000 * 10, 3);
+      expect(w.feeApy.junior!).toBeGreaterThan(w.feeApy.senior!);
+    });
+
+    it("has no junior figures without tranches, and nothing under a day", () => {
+      const w = stakeApyWindow(snapshot({ tranche_enabled: false }), { ...later, tranche_enabled: false })!;
+      expect(w.apy.junior).toBeNull();
+      expect(w.feeApy.junior).toBeNull();
+
+      expect(stakeApyWindow(snapshot(), { ...later, created_at: "2026-10-01T12:00:00.000Z" })).toBeNull();
+    });
+  });
+
+  it("GET /stake/:pool/history returns snapshots and events", async () => {
+    vi.mocked(shared.getStakePoolSnapshots).mockResolvedValue([later, snapshot()]);
+    vi.mocked(shared.getStakePoolEvents).mockResolvedValue([{ kind: "flush", amount: "50000" }] as any);
+
+    const res = await stakeRoutes().request(`/stake/${POOL}/history?from=2026-10-01&limit=500`);
+
+    expect(res.status).toBe(200);
+    const data = await res.json();
+    expect(data.snapshots).toHaveLength(2);
+    expect(data.events).toEqual([{ kind: "flush", amount: "50000" }]);
+    const query = { from: "2026-10-01T00:00:00.000Z", limit: 200 };
+    expect(shared.getStakePoolSnapshots).toHaveBeenCalledWith(POOL, query);
+    expect(shared.getStakePoolEvents).toHaveBeenCalledWith(POOL, query);
+  });
+
+  it("rejects bad pool addresses and timestamps", async () => {
+    expect((await stakeRoutes().request("/stake/short/history")).status).toBe(400);
+    expect((await stakeRoutes().request(`/stake/${POOL}/history?to=nope`)).status).toBe(400);
+    expect((await stakeRoutes().request("/stake/short/apy")).status).toBe(400);
+  });
+
+  it("GET /stake/:pool/apy reports share prices, HWM and trailing windows", async () => {
+    vi.mocked(shared.getLatestStakePoolSnapshot).mockResolvedValue(later);
+    vi.mocked(shared.getStakePoolSnapshotSince)
+      .mockResolvedValueOnce(later)
+      .mockResolvedValueOnce(snapshot());
+
+    const res = await stakeRoutes().request(`/stake/${POOL}/apy`);
+
+    expect(res.status).toBe(200);
+    const data = await res.json();
+    expect(data).toMatchObject({
+      pool: POOL,
+      slabAddress: SLAB,
+      juniorFeeMultBps: 20_000,
+      sharePriceE6: { pool: "1150000", senior: "1100000", junior: "1200000" },
+      hwm: { enabled: true, floor: "1800000", breached: false },
+      apy7d: null,
+      apy30d: { apy: { senior: 1, junior: 2 } },
+    });
+  });
+
+  it("returns 404 for a pool that was never indexed", async () => {
+    vi.mocked(shared.getLatestStakePoolSnapshot).mockResolvedValue(null);
+
+    expect((await stakeRoutes().request(`/stake/${POOL}/apy`)).status).toBe(404);
+  });
+
+  it("returns 500 when the query fails", async () => {
+    vi.mocked(shared.getStakePoolSnapshots).mockRejectedValue(new Error("db down"));
+
+    expect((await stakeRoutes().request(`/stake/${POOL}/history`)).status).toBe(500);
+  });
+});
@@ -9,6 +9,8 @@ vi.mock("@percolator/shared", () => ({
   get24hVolume: vi.fn(),
   getGlobalRecentTrades: vi.fn(),
   getPriceHistory: vi.fn(),
+  getCandles: vi.fn(),
+  isCandleResolution: vi.fn((r: string) => ["1m", "5m", "15m", "1h", "4h", "1d"].includes(r)),
   createLogger: vi.fn(() => ({
     info: vi.fn(),
     warn: vi.fn(),
@@ -62,7 +64,7 @@ vi.mock("@percolator/shared", () => ({
   config: { supabaseUrl: "http://test", supabaseKey: "test", rpcUrl: "http://test" },
 }));
 
-const { getRecentTrades, get24hVolume, getGlobalRecentTrades, getPriceHistory } = 
+const { getRecentTrades, get24hVolume, getGlobalRecentTrades, getPriceHistory, getCandles } = 
   await import("@percolator/shared");
 
 describe("trades routes", () => {
@@ -281,6 +283,63 @@ describe("trades routes", () => {
     });
   });
 
+  describe("GET /markets/:slab/candles", () => {
+    const slab = "11111111111111111111111111111111";
+
+    it("should return candles with epoch-second times", async () => {
+      vi.mocked(getCandles).mockResolvedValue([
+        {
+          slab_address: slab,
+          resolution: "5m",
+          bucket_start: "2026-03-01T10:05:00+00:00",
+          open: 100, high: 105, low: 99, close: 104,
+          volume: "12", trade_count: 3,
+        },
+      ] as any);
+
+      const app = tradeRoutes();
+      const res = await app.request(`/markets/${slab}/candles?resolution=5m&from=1772359200&limit=50`);
+
+      expect(res.status).toBe(200);
+      const data = await res.json();
+      expect(data.candles).toEqual([
+        { time: 1772359500, open: 100, high: 105, low: 99, close: 104, volume: "12", trade_count: 3 },
+      ]);
+      expect(getCandles).toHaveBeenCalledWith(slab, "5m", {
+        from: "2026-03-01T10:00:00.000Z",
+        to: undefined,
+        limit: 50,
+      });
+    });
+
+    it("should default to the latest 300 hourly candles", async () => {
+      vi.mocked(getCandles).mockResolvedValue([]);
+
+      const app = tradeRoutes();
+      await app.request(`/markets/${slab}/candles`);
+
+      expect(getCandles).toHaveBeenCalledWith(slab, "1h", { from: undefined, to: undefined, limit: 300 });
+    });
+
+    it("should reject invalid resolution, time and limit", async () => {
+      const app = tradeRoutes();
+
+      expect((await app.request(`/markets/${slab}/candles?resolution=2h`)).status).toBe(400);
+      expect((await app.request(`/markets/${slab}/candles?to=later`)).status).toBe(400);
+      expect((await app.request(`/markets/${slab}/candles?limit=5000`)).status).toBe(400);
+      expect(getCandles).not.toHaveBeenCalled();
+    });
+
+    it("should return 500 when the query fails", async () => {
+      vi.mocked(getCandles).mockRejectedValue(new Error("db down"));
+
+      const app = tradeRoutes();
+      const res = await app.request(`/markets/${slab}/candles`);
+
+      expect(res.status).toBe(500);
+    });
+  });
+
   describe("GET /trades/recent", () => {
     it("should return global recent trades", async () => {
       const mockTrades = [
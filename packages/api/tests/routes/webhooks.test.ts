@@ -0,0 +1,302 @@
+import { describe, it, expect, vi, beforeEach, afterEach } from "vitest";
+import { generateKeyPairSync, sign, type KeyObject } from "node:crypto";
+import { PublicKey } from "@solana/web3.js";
+
+vi.mock("@percolator/shared", () => ({
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+  sanitizeSlabAddress: vi.fn((addr: string) => (addr.length >= 32 ? addr : null)),
+  sanitizePagination: vi.fn((limit: any) => ({ limit: limit ? Number(limit) : 50, offset: 0 })),
+  EVENT_DATA_SCHEMAS: { "market.created": {}, "crank.stale": {}, "position.liquidated": {} },
+  createWebhookSubscription: vi.fn(),
+  countWebhookSubscriptions: vi.fn(),
+  createWebhookChallenge: vi.fn(),
+  consumeWebhookChallenge: vi.fn(),
+  listWebhookSubscriptions: vi.fn(),
+  getWebhookSubscription: vi.fn(),
+  updateWebhookSubscription: vi.fn(),
+  deleteWebhookSubscription: vi.fn(),
+  getWebhookDeliveries: vi.fn(),
+  getWebhookDelivery: vi.fn(),
+  getWebhookAttempts: vi.fn(),
+  getWebhookDeadLetters: vi.fn(),
+  redeliverWebhook: vi.fn(),
+  assertPublicHost: vi.fn(async () => {}),
+  PrivateAddressError: class PrivateAddressError extends Error {},
+}));
+
+const shared = await import("@percolator/shared");
+const { webhookRoutes, validateWebhookUrl, webhookAuthMessage } = await import("../../src/routes/webhooks.js");
+
+const ID = "0b7c9d1e-2f3a-4b5c-8d6e-7f8091a2b3c4";
+const SLAB = "11111111111111111111111111111111";
+
+const row = {
+  id: ID,
+  url: "https://hooks.example.com/percolator",
+  secret: "whsec_stored",
+  events: ["crank.stale"],
+  slabs: [SLAB],
+  description: null,
+  active: true,
+  owner: null as string | null,
+  created_at: "2026-10-01T00:00:00Z",
+  updated_at: "2026-10-01T00:00:00Z",
+};
+
+function wallet() {
+  const { publicKey, privateKey } = generateKeyPairSync("ed25519");
+  // Raw key is the last 32 bytes of the DER SPKI encoding
+  const raw = publicKey.export({ format: "der", type: "spki" }).subarray(-32);
+  return { owner: new PublicKey(raw).toBase58(), privateKey };
+}
+
+/** Wallet auth headers signing `challenge` for one request. */
+function walletHeaders(
+  w: { owner: string; privateKey: KeyObject },
+  method: string,
+  path: string,
+  challenge = "c0ffee",
+): Record<string, string> {
+  const message = webhookAuthMessage(w.owner, challenge, method, path);
+  return {
+    "x-wallet-owner": w.owner,
+    "x-wallet-challenge": challenge,
+    "x-wallet-signature": sign(null, Buffer.from(message), w.privateKey).toString("base64"),
+  };
+}
+
+function send(method: string, path: string, body?: unknown, headers: Record<string, string> = {}) {
+  return webhookRoutes().request(path, {
+    method,
+    headers: { "Content-Type": "application/json", ...headers },
+    body: body === undefined ? undefined : JSON.stringify(body),
+  });
+}
+
+describe("webhook routes", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    delete process.env.API_AUTH_KEY;
+  });
+
+  afterEach(() => {
+    delete process.env.API_AUTH_KEY;
+    vi.unstubAllEnvs();
+  });
+
+  it("requires the API key when one is configured", async () => {
+    process.env.API_AUTH_KEY = "key";
+
+    expect((await send("GET", "/webhooks")).status).toBe(401);
+
+    vi.mocked(shared.listWebhookSubscriptions).mockResolvedValue([row]);
+    const res = await send("GET", "/webhooks", undefined, { "x-api-key": "key" });
+    expect(res.status).toBe(200);
+    expect(shared.listWebhookSubscriptions).toHaveBeenCalledWith(undefined);
+    const data = await res.json();
+    expect(data.subscriptions[0]).toMatchObject({ id: ID, events: ["crank.stale"] });
+    expect(data.subscriptions[0].secret).toBeUndefined();
+  });
+
+  it("registers a subscription and returns its secret once", async () => {
+    vi.mocked(shared.createWebhookSubscription).mockImplementation(async (input) => ({ ...row, ...input }));
+
+    const res = await send("POST", "/webhooks", {
+      url: row.url,
+      events: ["crank.stale", "crank.stale"],
+      slabs: [SLAB],
+    });
+
+    expect(res.status).toBe(201);
+    const data = await res.json();
+    expect(data.secret).toMatch(/^whsec_[0-9a-f]{64}$/);
+    expect(data.subscription.secret).toBeUndefined();
+    expect(shared.createWebhookSubscription).toHaveBeenCalledWith({
+      url: row.url,
+      secret: data.secret,
+      events: ["crank.stale"],
+      slabs: [SLAB],
+      description: null,
+      owner: null,
+    });
+    expect(shared.countWebhookSubscriptions).not.toHaveBeenCalled();
+  });
+
+  it("rejects unknown events, bad slabs and bad URLs", async () => {
+    expect((await send("POST", "/webhooks", { url: row.url, events: ["nope"] })).status).toBe(400);
+    expect((await send("POST", "/webhooks", { url: row.url, slabs: ["short"] })).status).toBe(400);
+    expect((await send("POST", "/webhooks", { url: "ftp://example.com" })).status).toBe(400);
+    expect((await send("POST", "/webhooks", "not an object")).status).toBe(400);
+    expect(shared.createWebhookSubscription).not.toHaveBeenCalled();
+  });
+
+  it("requires public https endpoints in production", async () => {
+    vi.stubEnv("NODE_ENV", "production");
+
+    expect(await validateWebhookUrl("https://hooks.example.com/x")).toBeNull();
+    expect(shared.assertPublicHost).toHaveBeenCalledWith("hooks.example.com");
+    expect(await validateWebhookUrl("http://hooks.example.com/x")).toBe("url must use https");
+    expect(await validateWebhookUrl("https://user:pw@hooks.example.com/x")).toBe("url must not contain credentials");
+
+    // A public-looking name that resolves to a private address
+    vi.mocked(shared.assertPublicHost).mockRejectedValueOnce(new shared.PrivateAddressError("rebind.example.com"));
+    expect(await validateWebhookUrl("https://rebind.example.com/x")).toBe("url must point to a public host");
+    vi.mocked(shared.assertPublicHost).mockRejectedValueOnce(Object.assign(new Error("getaddrinfo ENOTFOUND"), { code: "ENOTFOUND" }));
+    expect(await validateWebhookUrl("https://nowhere.example.com/x")).toBe("url host does not resolve");
+
+    vi.mocked(shared.assertPublicHost).mockRejectedValueOnce(new shared.PrivateAddressError("10.1.2.3"));
+    const res = await send("PATCH", `/webhooks/${ID}`, { url: "https://10.1.2.3/x" });
+    expect(res.status).toBe(400);
+    expect(shared.updateWebhookSubscription).not.toHaveBeenCalled();
+  });
+
+  it("does not resolve hosts outside production", async () => {
+    expect(await validateWebhookUrl("http://localhost:3000/x")).toBeNull();
+    expect(shared.assertPublicHost).not.toHaveBeenCalled();
+  });
+
+  it("updates filters and the active flag", async () => {
+    vi.mocked(shared.updateWebhookSubscription).mockResolvedValue({ ...row, active: false, events: [] });
+
+    const res = await send("PATCH", `/webhooks/${ID}`, { active: false, events: [] });
+
+    expect(res.status).toBe(200);
+    expect(shared.updateWebhookSubscription).toHaveBeenCalledWith(ID, { active: false, events: [] });
+    expect((await send("PATCH", `/webhooks/${ID}`, {})).status).toBe(400);
+    expect((await send("PATCH", "/webhooks/not-a-uuid", { active: false })).status).toBe(400);
+  });
+
+  it("returns 404 for unknown subscriptions", async () => {
+    vi.mocked(shared.getWebhookSubscription).mockResolvedValue(null);
+    vi.mocked(shared.deleteWebhookSubscription).mockResolvedValue(false);
+
+    expect((await send("GET", `/webhooks/${ID}`)).status).toBe(404);
+    expect((await send("DELETE", `/webhooks/${ID}`)).status).toBe(404);
+  });
+
+  it("serves delivery logs with attempts and dead letters", async () => {
+    const delivery = { id: 7, subscription_id: ID, status: "dead", attempts: 10 };
+    vi.mocked(shared.getWebhookDeliveries).mockResolvedValue([delivery] as any);
+    vi.mocked(shared.getWebhookDelivery).mockResolvedValue(delivery as any);
+    vi.mocked(shared.getWebhookAttempts).mockResolvedValue([{ attempt: 1, status_code: 500 }] as any);
+    vi.mocked(shared.getWebhookDeadLetters).mockResolvedValue([{ delivery_id: 7 }] as any);
+
+    const list = await send("GET", `/webhooks/${ID}/deliveries?status=dead&limit=500`);
+    expect(list.status).toBe(200);
+    expect(shared.getWebhookDeliveries).toHaveBeenCalledWith(ID, { status: "dead", event: undefined, limit: 200 });
+    expect((await send("GET", `/webhooks/${ID}/deliveries?status=bogus`)).status).toBe(400);
+
+    const one = await (await send("GET", `/webhooks/${ID}/deliveries/7`)).json();
+    expect(one).toMatchObject({ delivery: { id: 7 }, attempts: [{ attempt: 1, status_code: 500 }] });
+
+    const dead = await (await send("GET", `/webhooks/${ID}/dead-letters`)).json();
+    expect(dead.deadLetters).toEqual([{ delivery_id: 7 }]);
+  });
+
+  it("re-queues a delivery", async () => {
+    vi.mocked(shared.redeliverWebhook).mockResolvedValueOnce(true).mockResolvedValueOnce(false);
+
+    expect((await send("POST", `/webhooks/${ID}/deliveries/7/redeliver`)).status).toBe(200);
+    expect(shared.redeliverWebhook).toHaveBeenCalledWith(ID, 7);
+    expect((await send("POST", `/webhooks/${ID}/deliveries/7/redeliver`)).status).toBe(404);
+    expect((await send("POST", `/webhooks/${ID}/deliveries/abc/redeliver`)).status).toBe(400);
+  });
+
+  describe("wallet owners", () => {
+    const alice = wallet();
+    const bob = wallet();
+
+    beforeEach(() => {
+      // Operator routes stay locked; only the wallet signature gets in
+      process.env.API_AUTH_KEY = "key";
+      vi.mocked(shared.consumeWebhookChallenge).mockResolvedValue(true);
+      vi.mocked(shared.countWebhookSubscriptions).mockResolvedValue(0);
+    });
+
+    it("issues challenges for valid wallet addresses", async () => {
+      const res = await send("POST", "/webhooks/challenge", { owner: alice.owner });
+
+      expect(res.status).toBe(201);
+      const data = await res.json();
+      expect(data.challenge).toMatch(/^[0-9a-f]{32}$/);
+      expect(shared.createWebhookChallenge).toHaveBeenCalledWith(alice.owner, data.challenge, data.expiresAt);
+      expect((await send("POST", "/webhooks/challenge", { owner: "abc" })).status).toBe(400);
+    });
+
+    it("registers a subscription owned by the signing wallet", async () => {
+      vi.mocked(shared.createWebhookSubscription).mockImplementation(async (input) => ({ ...row, ...input }));
+
+      const res = await send("POST", "/webhooks", { url: row.url }, walletHeaders(alice, "POST", "/webhooks"));
+
+      expect(res.status).toBe(201);
+      expect((await res.json()).subscription.owner).toBe(alice.owner);
+      expect(shared.consumeWebhookChallenge).toHaveBeenCalledWith(alice.owner, "c0ffee");
+      expect(shared.createWebhookSubscription).toHaveBeenCalledWith(
+        expect.objectContaining({ url: row.url, owner: alice.owner }),
+      );
+    });
+
+    it("enforces the per-wallet limit", async () => {
+      vi.stubEnv("WEBHOOK_MAX_PER_OWNER", "2");
+      vi.mocked(shared.countWebhookSubscriptions).mockResolvedValue(2);
+
+      const res = await send("POST", "/webhooks", { url: row.url }, walletHeaders(alice, "POST", "/webhooks"));
+
+      expect(res.status).toBe(409);
+      expect((await res.json()).error).toContain("2 per wallet");
+      expect(shared.countWebhookSubscriptions).toHaveBeenCalledWith(alice.owner);
+      expect(shared.createWebhookSubscription).not.toHaveBeenCalled();
+    });
+
+    it("keeps the public-host check for wallet registrations", async () => {
+      vi.stubEnv("NODE_ENV", "production");
+      vi.mocked(shared.assertPublicHost).mockRejectedValueOnce(new shared.PrivateAddressError("10.1.2.3"));
+
+      const headers = walletHeaders(alice, "POST", "/webhooks");
+      const res = await send("POST", "/webhooks", { url: "https://10.1.2.3/x" }, headers);
+
+      expect(res.status).toBe(400);
+      expect(shared.createWebhookSubscription).not.toHaveBeenCalled();
+    });
+
+    it("rejects bad signatures, other requests and spent challenges", async () => {
+      const forged = { ...walletHeaders(bob, "POST", "/webhooks"), "x-wallet-owner": alice.owner };
+      expect((await send("POST", "/webhooks", { url: row.url }, forged)).status).toBe(401);
+      // Signed for listing, replayed to delete
+      const listing = walletHeaders(alice, "GET", "/webhooks");
+      expect((await send("DELETE", `/webhooks/${ID}`, undefined, listing)).status).toBe(401);
+      expect(shared.consumeWebhookChallenge).not.toHaveBeenCalled();
+
+      vi.mocked(shared.consumeWebhookChallenge).mockResolvedValue(false);
+      const res = await send("GET", "/webhooks", undefined, listing);
+      expect(res.status).toBe(401);
+      expect((await res.json()).error).toContain("already used");
+      expect(shared.listWebhookSubscriptions).not.toHaveBeenCalled();
+    });
+
+    it("only shows and changes the wallet's own subscriptions", async () => {
+      vi.mocked(shared.listWebhookSubscriptions).mockResolvedValue([{ ...row, owner: alice.owner }]);
+      vi.mocked(shared.getWebhookSubscription).mockResolvedValue({ ...row, owner: bob.owner });
+
+      expect((await send("GET", "/webhooks", undefined, walletHeaders(alice, "GET", "/webhooks"))).status).toBe(200);
+      expect(shared.listWebhookSubscriptions).toHaveBeenCalledWith(alice.owner);
+
+      const path = `/webhooks/${ID}`;
+      expect((await send("GET", path, undefined, walletHeaders(alice, "GET", path))).status).toBe(404);
+      expect((await send("DELETE", path, undefined, walletHeaders(alice, "DELETE", path))).status).toBe(404);
+      const deliveries = `${path}/deliveries`;
+      expect((await send("GET", deliveries, undefined, walletHeaders(alice, "GET", deliveries))).status).toBe(404);
+      expect(shared.deleteWebhookSubscription).not.toHaveBeenCalled();
+      expect(shared.getWebhookDeliveries).not.toHaveBeenCalled();
+
+      vi.mocked(shared.getWebhookSubscription).mockResolvedValue({ ...row, owner: bob.owner });
+      vi.mocked(shared.deleteWebhookSubscription).mockResolvedValue(true);
+      expect((await send("DELETE", path, undefined, walletHeaders(bob, "DELETE", path))).status).toBe(200);
+    });
+  });
+});
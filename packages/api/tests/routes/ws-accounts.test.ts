@@ -0,0 +1,195 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { generateKeyPairSync, sign } from "node:crypto";
+import { PublicKey } from "@solana/web3.js";
+
+vi.mock("@percolator/shared", () => ({
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+}));
+
+vi.mock("@percolator/sdk", async () => ({
+  computeAccountHealth: (await vi.importActual<typeof import("@percolator/sdk")>("@percolator/sdk")).computeAccountHealth,
+  parseConfig: vi.fn(),
+  parseEngine: vi.fn(),
+  parseParams: vi.fn(),
+  parseAllAccounts: vi.fn(),
+}));
+
+const { parseConfig, parseEngine, parseParams, parseAllAccounts } = await import("@percolator/sdk");
+const {
+  newWalletChallenge,
+  walletAuthMessage,
+  verifyWalletAuth,
+  snapshotOwners,
+  parseWarningThresholds,
+  warningLevel,
+  LiquidationWarningTracker,
+  SlabAccountWatcher,
+  accountChannelMessage,
+} = await import("../../src/routes/ws-accounts.js");
+
+const SLAB = "11111111111111111111111111111111";
+
+function wallet() {
+  const { publicKey, privateKey } = generateKeyPairSync("ed25519");
+  // Raw key is the last 32 bytes of the DER SPKI encoding
+  const raw = publicKey.export({ format: "der", type: "spki" }).subarray(-32);
+  return { owner: new PublicKey(raw).toBase58(), privateKey };
+}
+
+function account(owner: PublicKey, capital: bigint, positionSize: bigint) {
+  return {
+    kind: 0,
+    owner,
+    capital,
+    pnl: 0n,
+    positionSize,
+    entryPrice: 1_000_000n,
+    fundingIndex: 0n,
+    warmupStartedAtSlot: 0n,
+  };
+}
+
+describe("verifyWalletAuth", () => {
+  const now = 1_700_000_000_000;
+  const challenge = newWalletChallenge();
+
+  it("accepts the owner's signature over the auth message", () => {
+    const { owner, privateKey } = wallet();
+    const message = walletAuthMessage(owner, challenge, now);
+    const signature = sign(null, Buffer.from(message), privateKey).toString("base64");
+
+    expect(message).toContain(`Challenge: ${challenge}`);
+    expect(verifyWalletAuth(owner, challenge, now, signature, now + 1_000)).toBe(true);
+  });
+
+  it("rejects another wallet's signature, a stale timestamp and garbage", () => {
+    const { owner, privateKey } = wallet();
+    const other = wallet();
+    const message = walletAuthMessage(owner, challenge, now);
+    const signature = sign(null, Buffer.from(message), privateKey).toString("base64");
+
+    expect(verifyWalletAuth(other.owner, challenge, now, signature, now)).toBe(false);
+    expect(verifyWalletAuth(owner, challenge, now, signature, now + 6 * 60 * 1000)).toBe(false);
+    expect(verifyWalletAuth(owner, challenge, now + 1, signature, now)).toBe(false);
+    expect(verifyWalletAuth(owner, challenge, now, "not-a-signature", now)).toBe(false);
+    expect(verifyWalletAuth("not-a-key", challenge, now, signature, now)).toBe(false);
+  });
+
+  it("rejects a signature over a different or empty challenge", () => {
+    const { owner, privateKey } = wallet();
+    const signature = sign(null, Buffer.from(walletAuthMessage(owner, challenge, now)), privateKey)
+      .toString("base64");
+
+    expect(verifyWalletAuth(owner, newWalletChallenge(), now, signature, now)).toBe(false);
+    expect(verifyWalletAuth(owner, "", now, signature, now)).toBe(false);
+  });
+
+  it("issues a distinct challenge each time", () => {
+    const challenges = new Set(Array.from({ length: 20 }, () => newWalletChallenge()));
+    expect(challenges.size).toBe(20);
+    expect([...challenges][0]).toMatch(/^[0-9a-f]{32}$/);
+  });
+});
+
+describe("snapshotOwners", () => {
+  const owner = new PublicKey("So11111111111111111111111111111111111111112");
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(parseConfig).mockReturnValue({ lastEffectivePriceE6: 1_000_000n, authorityPriceE6: 0n } as any);
+    vi.mocked(parseEngine).mockReturnValue({ currentSlot: 500n, fundingIndexQpbE6: 0n } as any);
+    vi.mocked(parseParams).mockReturnValue({
+      maintenanceMarginBps: 500n,
+      initialMarginBps: 1000n,
+      warmupPeriodSlots: 0n,
+    } as any);
+  });
+
+  it("builds each requested owner's accounts with distance to liquidation", () => {
+    vi.mocked(parseAllAccounts).mockReturnValue([
+      { idx: 3, account: account(owner, 1_000_000_000n, 10_000_000_000n) },
+      { idx: 4, account: account(new PublicKey(SLAB), 1n, 0n) },
+    ] as any);
+
+    const snapshots = snapshotOwners(new Uint8Array(0), [owner.toBase58()]);
+
+    const snap = snapshots.get(owner.toBase58())!;
+    expect(snap).toMatchObject({ priceE6: "1000000", slot: "500" });
+    expect(snap.accounts).toHaveLength(1);
+    // equity 1,000 on 10,000 notional: 10% margin, 5% over maintenance
+    expect(snap.accounts[0]).toMatchObject({
+      accountIdx: 3,
+      marginRatioBps: "1000",
+      distanceToLiquidationBps: "500",
+      liquidatable: false,
+    });
+
+    const msg = JSON.parse(accountChannelMessage("account", SLAB, owner.toBase58(), snap));
+    expect(msg).toMatchObject({ type: "account", slab: SLAB, owner: owner.toBase58() });
+    expect(msg.accounts[0]).toMatchObject({ positionSize: "10000000000", capital: "1000000000", pendingFunding: "0" });
+  });
+});
+
+describe("liquidation warnings", () => {
+  const thresholds = parseWarningThresholds("100, 500,200,abc,200");
+
+  function snapshot(distance: string | null, accountIdx = 1) {
+    return {
+      priceE6: "1",
+      slot: "1",
+      accounts: [{ accountIdx, distanceToLiquidationBps: distance, liquidatable: distance !== null && Number(distance) < 0 } as any],
+    };
+  }
+
+  it("parses thresholds loosest first", () => {
+    expect(thresholds).toEqual([500, 200, 100]);
+    expect(warningLevel("150", thresholds)).toBe(200);
+    expect(warningLevel("600", thresholds)).toBeNull();
+    expect(warningLevel(null, thresholds)).toBeNull();
+  });
+
+  it("warns once per threshold crossed and re-arms after recovery", () => {
+    const tracker = new LiquidationWarningTracker(thresholds);
+
+    expect(tracker.update(SLAB, "O", snapshot("800"))).toEqual([]);
+    expect(tracker.update(SLAB, "O", snapshot("450"))).toEqual([
+      { accountIdx: 1, thresholdBps: 500, distanceToLiquidationBps: "450", liquidatable: false },
+    ]);
+    expect(tracker.update(SLAB, "O", snapshot("400"))).toEqual([]);
+    expect(tracker.update(SLAB, "O", snapshot("-20"))[0]).toMatchObject({ thresholdBps: 100, liquidatable: true });
+    expect(tracker.update(SLAB, "O", snapshot("900"))).toEqual([]);
+    expect(tracker.update(SLAB, "O", snapshot("300"))[0]).toMatchObject({ thresholdBps: 500 });
+
+    tracker.clearSlab(SLAB);
+    expect(tracker.update(SLAB, "O", snapshot("300"))).toHaveLength(1);
+  });
+});
+
+describe("SlabAccountWatcher", () => {
+  it("holds one RPC subscription per slab until the last release", () => {
+    const connection = {
+      onAccountChange: vi.fn(() => 7),
+      removeAccountChangeListener: vi.fn(async () => {}),
+    };
+    const onChange = vi.fn();
+    const watcher = new SlabAccountWatcher(() => connection as any, onChange);
+
+    watcher.watch(SLAB);
+    watcher.watch(SLAB);
+    expect(connection.onAccountChange).toHaveBeenCalledTimes(1);
+
+    const listener = (connection.onAccountChange.mock.calls[0] as any[])[1];
+    listener({ data: Buffer.from([1]) });
+    expect(onChange).toHaveBeenCalledWith(SLAB, Buffer.from([1]));
+
+    expect(watcher.unwatch(SLAB)).toBe(false);
+    expect(watcher.unwatch(SLAB)).toBe(true);
+    expect(connection.removeAccountChangeListener).toHaveBeenCalledWith(7);
+    expect(watcher.size).toBe(0);
+  });
+});
@@ -258,6 +258,59 @@ export const ACCOUNTS_UNPAUSE_MARKET: readonly AccountSpec[] = [
   { name: "slab", signer: false, writable: true },
 ] as const;
 
+/**
+ * AdminForceClose: 4 accounts
+ * Same ordering as LiquidateAtOracle, with the admin in slot 0 as signer.
+ */
+export const ACCOUNTS_ADMIN_FORCE_CLOSE: readonly AccountSpec[] = [
+  { name: "admin", signer: true, writable: true },
+  { name: "slab", signer: false, writable: true },
+  { name: "clock", signer: false, writable: false },
+  { name: "oracle", signer: false, writable: false },
+] as const;
+
+/**
+ * UpdateRiskParams: 2 accounts
+ */
+export const ACCOUNTS_UPDATE_RISK_PARAMS: readonly AccountSpec[] = [
+  { name: "admin", signer: true, writable: true },
+  { name: "slab", signer: false, writable: true },
+] as const;
+
+/**
+ * RenounceAdmin: 2 accounts
+ */
+export const ACCOUNTS_RENOUNCE_ADMIN: readonly AccountSpec[] = [
+  { name: "admin", signer: true, writable: true },
+  { name: "slab", signer: false, writable: true },
+] as const;
+
+/**
+ * SetPythOracle: 2 accounts (PERC-117)
+ */
+export const ACCOUNTS_SET_PYTH_ORACLE: readonly AccountSpec[] = [
+  { name: "admin", signer: true, writable: true },
+  { name: "slab", signer: false, writable: true },
+] as const;
+
+/**
+ * UpdateMarkPrice: 3 accounts + remaining (PERC-118, permissionless)
+ */
+export const ACCOUNTS_UPDATE_MARK_PRICE: readonly AccountSpec[] = [
+  { name: "slab", signer: false, writable: true },
+  { name: "oracle", signer: false, writable: false },
+  { name: "clock", signer: false, writable: false },
+] as const;
+
+/**
+ * UpdateHyperpMark: 3 accounts + remaining pool vaults (PERC-119, permissionless)
+ */
+export const ACCOUNTS_UPDATE_HYPERP_MARK: readonly AccountSpec[] = [
+  { name: "slab", signer: false, writable: true },
+  { name: "dexPool", signer: false, writable: false },
+  { name: "clock", signer: false, writable: false },
+] as const;
+
 // ============================================================================
 // ACCOUNT META BUILDERS
 // ============================================================================
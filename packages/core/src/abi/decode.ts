@@ -0,0 +1,388 @@
+import { PublicKey } from "@solana/web3.js";
+import {
+  IX_TAG,
+  type InitMarketArgs,
+  type InitUserArgs,
+  type InitLPArgs,
+  type DepositCollateralArgs,
+  type WithdrawCollateralArgs,
+  type KeeperCrankArgs,
+  type TradeNoCpiArgs,
+  type LiquidateAtOracleArgs,
+  type CloseAccountArgs,
+  type TopUpInsuranceArgs,
+  type TradeCpiArgs,
+  type TradeCpiV2Args,
+  type SetRiskThresholdArgs,
+  type UpdateAdminArgs,
+  type UpdateConfigArgs,
+  type SetMaintenanceFeeArgs,
+  type SetOracleAuthorityArgs,
+  type PushOraclePriceArgs,
+  type SetOraclePriceCapArgs,
+  type AdminForceCloseArgs,
+  type UpdateRiskParamsArgs,
+  type DepositInsuranceLPArgs,
+  type WithdrawInsuranceLPArgs,
+  type SetPythOracleArgs,
+  type TopUpKeeperFundArgs,
+} from "./instructions.js";
+import {
+  type AccountSpec,
+  ACCOUNTS_INIT_MARKET,
+  ACCOUNTS_INIT_USER,
+  ACCOUNTS_INIT_LP,
+  ACCOUNTS_DEPOSIT_COLLATERAL,
+  ACCOUNTS_WITHDRAW_COLLATERAL,
+  ACCOUNTS_KEEPER_CRANK,
+  ACCOUNTS_TRADE_NOCPI,
+  ACCOUNTS_LIQUIDATE_AT_ORACLE,
+  ACCOUNTS_CLOSE_ACCOUNT,
+  ACCOUNTS_TOPUP_INSURANCE,
+  ACCOUNTS_TRADE_CPI,
+  ACCOUNTS_SET_RISK_THRESHOLD,
+  ACCOUNTS_UPDATE_ADMIN,
+  ACCOUNTS_CLOSE_SLAB,
+  ACCOUNTS_UPDATE_CONFIG,
+  ACCOUNTS_SET_MAINTENANCE_FEE,
+  ACCOUNTS_SET_ORACLE_AUTHORITY,
+  ACCOUNTS_PUSH_ORACLE_PRICE,
+  ACCOUNTS_SET_ORACLE_PRICE_CAP,
+  ACCOUNTS_RESOLVE_MARKET,
+  ACCOUNTS_WITHDRAW_INSURANCE,
+  ACCOUNTS_ADMIN_FORCE_CLOSE,
+  ACCOUNTS_UPDATE_RISK_PARAMS,
+  ACCOUNTS_RENOUNCE_ADMIN,
+  ACCOUNTS_CREATE_INSURANCE_MINT,
+  ACCOUNTS_DEPOSIT_INSURANCE_LP,
+  ACCOUNTS_WITHDRAW_INSURANCE_LP,
+  ACCOUNTS_PAUSE_MARKET,
+  ACCOUNTS_UNPAUSE_MARKET,
+  ACCOUNTS_SET_PYTH_ORACLE,
+  ACCOUNTS_UPDATE_MARK_PRICE,
+  ACCOUNTS_UPDATE_HYPERP_MARK,
+  ACCOUNTS_FUND_MARKET_INSURANCE,
+  ACCOUNTS_SET_INSURANCE_ISOLATION,
+  ACCOUNTS_ADVANCE_ORACLE_PHASE,
+  ACCOUNTS_TOPUP_KEEPER_FUND,
+} from "./accounts.js";
+
+// ============================================================================
+// INSTRUCTION DATA DECODING — inverse of the encode* functions
+// ============================================================================
+
+export type IxName = keyof typeof IX_TAG;
+
+/**
+ * Encoder args as they come back out of the decoder: numeric strings become
+ * bigint and base58 strings become PublicKey. Plain strings (feed IDs) stay.
+ */
+export type Decoded<T> = {
+  [K in keyof T]: [Exclude<T[K], string>] extends [never] ? T[K] : Exclude<T[K], string>;
+};
+
+/** Decoded args per instruction. Tag-only instructions decode to `{}`. */
+export interface IxArgsMap {
+  InitMarket: Decoded<InitMarketArgs>;
+  InitUser: Decoded<InitUserArgs>;
+  InitLP: Decoded<InitLPArgs>;
+  DepositCollateral: Decoded<DepositCollateralArgs>;
+  WithdrawCollateral: Decoded<WithdrawCollateralArgs>;
+  KeeperCrank: KeeperCrankArgs;
+  TradeNoCpi: Decoded<TradeNoCpiArgs>;
+  LiquidateAtOracle: LiquidateAtOracleArgs;
+  CloseAccount: CloseAccountArgs;
+  TopUpInsurance: Decoded<TopUpInsuranceArgs>;
+  TradeCpi: Decoded<TradeCpiArgs>;
+  SetRiskThreshold: Decoded<SetRiskThresholdArgs>;
+  UpdateAdmin: Decoded<UpdateAdminArgs>;
+  CloseSlab: Record<string, never>;
+  UpdateConfig: Decoded<UpdateConfigArgs>;
+  SetMaintenanceFee: Decoded<SetMaintenanceFeeArgs>;
+  SetOracleAuthority: Decoded<SetOracleAuthorityArgs>;
+  PushOraclePrice: Decoded<PushOraclePriceArgs>;
+  SetOraclePriceCap: Decoded<SetOraclePriceCapArgs>;
+  ResolveMarket: Record<string, never>;
+  WithdrawInsurance: Record<string, never>;
+  AdminForceClose: AdminForceCloseArgs;
+  UpdateRiskParams: Decoded<UpdateRiskParamsArgs>;
+  RenounceAdmin: Record<string, never>;
+  CreateInsuranceMint: Record<string, never>;
+  DepositInsuranceLP: Decoded<DepositInsuranceLPArgs>;
+  WithdrawInsuranceLP: Decoded<WithdrawInsuranceLPArgs>;
+  PauseMarket: Record<string, never>;
+  UnpauseMarket: Record<string, never>;
+  SetPythOracle: SetPythOracleArgs;
+  UpdateMarkPrice: Record<string, never>;
+  UpdateHyperpMark: Record<string, never>;
+  TradeCpiV2: Decoded<TradeCpiV2Args>;
+  FundMarketInsurance: { amount: bigint };
+  SetInsuranceIsolation: { bps: number };
+  AdvanceOraclePhase: Record<string, never>;
+  TopUpKeeperFund: Decoded<TopUpKeeperFundArgs>;
+}
+
+export type DecodedIxData =
+  | { [N in keyof IxArgsMap]: { tag: number; name: N; args: IxArgsMap[N] } }[keyof IxArgsMap]
+  | {
+      /** Tag is known on-chain but the SDK has no encoder for it (or it isn't a percolator tag). */
+      tag: number;
+      name: Exclude<IxName, keyof IxArgsMap> | "Unknown";
+      args: null;
+    };
+
+/** Instruction data shorter than its layout, or otherwise malformed. */
+export class InstructionDecodeError extends Error {
+  constructor(
+    readonly tag: number,
+    message: string,
+  ) {
+    super(message);
+    this.name = "InstructionDecodeError";
+  }
+}
+
+const TAG_NAMES: ReadonlyMap<number, IxName> = new Map(
+  (Object.entries(IX_TAG) as [IxName, number][]).map(([name, tag]) => [tag, name]),
+);
+
+/** Instruction name for a tag, or null if the tag isn't assigned. */
+export function ixNameForTag(tag: number): IxName | null {
+  return TAG_NAMES.get(tag) ?? null;
+}
+
+class Reader {
+  private off = 1; // past the tag
+  private readonly dv: DataView;
+
+  constructor(
+    private readonly data: Uint8Array,
+    private readonly tag: number,
+    private readonly name: string,
+  ) {
+    this.dv = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  }
+
+  get remaining(): number {
+    return this.data.length - this.off;
+  }
+
+  private take(n: number): number {
+    if (this.off + n > this.data.length) {
+      throw new InstructionDecodeError(
+        this.tag,
+        `${this.name}: data too short (${this.data.length} bytes, need at least ${this.off + n})`,
+      );
+    }
+    const at = this.off;
+    this.off += n;
+    return at;
+  }
+
+  u8(): number {
+    return this.dv.getUint8(this.take(1));
+  }
+  u16(): number {
+    return this.dv.getUint16(this.take(2), true);
+  }
+  u32(): number {
+    return this.dv.getUint32(this.take(4), true);
+  }
+  u64(): bigint {
+    return this.dv.getBigUint64(this.take(8), true);
+  }
+  i64(): bigint {
+    return this.dv.getBigInt64(this.take(8), true);
+  }
+  u128(): bigint {
+    const at = this.take(16);
+    return this.dv.getBigUint64(at, true) | (this.dv.getBigUint64(at + 8, true) << 64n);
+  }
+  i128(): bigint {
+    const at = this.take(16);
+    return this.dv.getBigUint64(at, true) | (this.dv.getBigInt64(at + 8, true) << 64n);
+  }
+  bytes(n: number): Uint8Array {
+    const at = this.take(n);
+    return this.data.slice(at, at + n);
+  }
+  pubkey(): PublicKey {
+    return new PublicKey(this.bytes(32));
+  }
+}
+
+function hex(bytes: Uint8Array): string {
+  return Array.from(bytes, (b) => b.toString(16).padStart(2, "0")).join("");
+}
+
+type ArgDecoders = { [N in keyof IxArgsMap]: (r: Reader) => IxArgsMap[N] };
+
+const none = (): Record<string, never> => ({});
+
+const ARG_DECODERS: ArgDecoders = {
+  InitMarket: (r) => ({
+    admin: r.pubkey(),
+    collateralMint: r.pubkey(),
+    indexFeedId: hex(r.bytes(32)),
+    maxStalenessSecs: r.u64(),
+    confFilterBps: r.u16(),
+    invert: r.u8(),
+    unitScale: r.u32(),
+    initialMarkPriceE6: r.u64(),
+    warmupPeriodSlots: r.u64(),
+    maintenanceMarginBps: r.u64(),
+    initialMarginBps: r.u64(),
+    tradingFeeBps: r.u64(),
+    maxAccounts: r.u64(),
+    newAccountFee: r.u128(),
+    riskReductionThreshold: r.u128(),
+    maintenanceFeePerSlot: r.u128(),
+    maxCrankStalenessSlots: r.u64(),
+    liquidationFeeBps: r.u64(),
+    liquidationFeeCap: r.u128(),
+    liquidationBufferBps: r.u64(),
+    minLiquidationAbs: r.u128(),
+  }),
+  InitUser: (r) => ({ feePayment: r.u64() }),
+  InitLP: (r) => ({ matcherProgram: r.pubkey(), matcherContext: r.pubkey(), feePayment: r.u64() }),
+  DepositCollateral: (r) => ({ userIdx: r.u16(), amount: r.u64() }),
+  WithdrawCollateral: (r) => ({ userIdx: r.u16(), amount: r.u64() }),
+  KeeperCrank: (r) => ({ callerIdx: r.u16(), allowPanic: r.u8() !== 0 }),
+  TradeNoCpi: (r) => ({ lpIdx: r.u16(), userIdx: r.u16(), size: r.i128() }),
+  LiquidateAtOracle: (r) => ({ targetIdx: r.u16() }),
+  CloseAccount: (r) => ({ userIdx: r.u16() }),
+  TopUpInsurance: (r) => ({ amount: r.u64() }),
+  TradeCpi: (r) => ({ lpIdx: r.u16(), userIdx: r.u16(), size: r.i128() }),
+  SetRiskThreshold: (r) => ({ newThreshold: r.u128() }),
+  UpdateAdmin: (r) => ({ newAdmin: r.pubkey() }),
+  CloseSlab: none,
+  UpdateConfig: (r) => ({
+    fundingHorizonSlots: r.u64(),
+    fundingKBps: r.u64(),
+    fundingInvScaleNotionalE6: r.u128(),
+    fundingMaxPremiumBps: r.i64(),
+    fundingMaxBpsPerSlot: r.i64(),
+    threshFloor: r.u128(),
+    threshRiskBps: r.u64(),
+    threshUpdateIntervalSlots: r.u64(),
+    threshStepBps: r.u64(),
+    threshAlphaBps: r.u64(),
+    threshMin: r.u128(),
+    threshMax: r.u128(),
+    threshMinStep: r.u128(),
+  }),
+  SetMaintenanceFee: (r) => ({ newFee: r.u128() }),
+  SetOracleAuthority: (r) => ({ newAuthority: r.pubkey() }),
+  PushOraclePrice: (r) => ({ priceE6: r.u64(), timestamp: r.i64() }),
+  SetOraclePriceCap: (r) => ({ maxChangeE2bps: r.u64() }),
+  ResolveMarket: none,
+  WithdrawInsurance: none,
+  AdminForceClose: (r) => ({ targetIdx: r.u16() }),
+  UpdateRiskParams: (r) => {
+    const initialMarginBps = r.u64();
+    const maintenanceMarginBps = r.u64();
+    // R2-S13: the fee field is optional; its presence is signalled by length alone.
+    return r.remaining >= 8
+      ? { initialMarginBps, maintenanceMarginBps, tradingFeeBps: r.u64() }
+      : { initialMarginBps, maintenanceMarginBps };
+  },
+  RenounceAdmin: none,
+  CreateInsuranceMint: none,
+  DepositInsuranceLP: (r) => ({ amount: r.u64() }),
+  WithdrawInsuranceLP: (r) => ({ lpAmount: r.u64() }),
+  PauseMarket: none,
+  UnpauseMarket: none,
+  SetPythOracle: (r) => ({ feedId: r.bytes(32), maxStalenessSecs: r.u64(), confFilterBps: r.u16() }),
+  UpdateMarkPrice: none,
+  UpdateHyperpMark: none,
+  TradeCpiV2: (r) => ({ lpIdx: r.u16(), userIdx: r.u16(), size: r.i128(), bump: r.u8() }),
+  FundMarketInsurance: (r) => ({ amount: r.u64() }),
+  SetInsuranceIsolation: (r) => ({ bps: r.u16() }),
+  AdvanceOraclePhase: none,
+  TopUpKeeperFund: (r) => ({ amount: r.u64() }),
+};
+
+/**
+ * Decode percolator instruction data back into its tag, name and args.
+ *
+ * Tags without an SDK encoder decode with `args: null`; unassigned tags are
+ * named "Unknown". Throws InstructionDecodeError if the data is shorter than
+ * the instruction's layout. Trailing bytes are ignored, as on-chain.
+ */
+export function decodeInstructionData(data: Uint8Array): DecodedIxData {
+  if (data.length === 0) throw new InstructionDecodeError(-1, "Empty instruction data");
+  const tag = data[0];
+  const name = TAG_NAMES.get(tag);
+  if (!name) return { tag, name: "Unknown", args: null };
+  if (!(name in ARG_DECODERS)) {
+    return { tag, name: name as Exclude<IxName, keyof IxArgsMap>, args: null };
+  }
+  const decode = ARG_DECODERS[name as keyof IxArgsMap] as (r: Reader) => IxArgsMap[keyof IxArgsMap];
+  return { tag, name, args: decode(new Reader(data, tag, name)) } as DecodedIxData;
+}
+
+// ============================================================================
+// ACCOUNT RESOLUTION — inverse of buildAccountMetas
+// ============================================================================
+
+/** Account ordering for every instruction the SDK knows the accounts of. */
+export const IX_ACCOUNT_SPECS: Readonly<Partial<Record<IxName, readonly AccountSpec[]>>> = {
+  InitMarket: ACCOUNTS_INIT_MARKET,
+  InitUser: ACCOUNTS_INIT_USER,
+  InitLP: ACCOUNTS_INIT_LP,
+  DepositCollateral: ACCOUNTS_DEPOSIT_COLLATERAL,
+  WithdrawCollateral: ACCOUNTS_WITHDRAW_COLLATERAL,
+  KeeperCrank: ACCOUNTS_KEEPER_CRANK,
+  TradeNoCpi: ACCOUNTS_TRADE_NOCPI,
+  LiquidateAtOracle: ACCOUNTS_LIQUIDATE_AT_ORACLE,
+  CloseAccount: ACCOUNTS_CLOSE_ACCOUNT,
+  TopUpInsurance: ACCOUNTS_TOPUP_INSURANCE,
+  TradeCpi: ACCOUNTS_TRADE_CPI,
+  TradeCpiV2: ACCOUNTS_TRADE_CPI,
+  SetRiskThreshold: ACCOUNTS_SET_RISK_THRESHOLD,
+  UpdateAdmin: ACCOUNTS_UPDATE_ADMIN,
+  CloseSlab: ACCOUNTS_CLOSE_SLAB,
+  UpdateConfig: ACCOUNTS_UPDATE_CONFIG,
+  SetMaintenanceFee: ACCOUNTS_SET_MAINTENANCE_FEE,
+  SetOracleAuthority: ACCOUNTS_SET_ORACLE_AUTHORITY,
+  PushOraclePrice: ACCOUNTS_PUSH_ORACLE_PRICE,
+  SetOraclePriceCap: ACCOUNTS_SET_ORACLE_PRICE_CAP,
+  ResolveMarket: ACCOUNTS_RESOLVE_MARKET,
+  WithdrawInsurance: ACCOUNTS_WITHDRAW_INSURANCE,
+  AdminForceClose: ACCOUNTS_ADMIN_FORCE_CLOSE,
+  UpdateRiskParams: ACCOUNTS_UPDATE_RISK_PARAMS,
+  RenounceAdmin: ACCOUNTS_RENOUNCE_ADMIN,
+  CreateInsuranceMint: ACCOUNTS_CREATE_INSURANCE_MINT,
+  DepositInsuranceLP: ACCOUNTS_DEPOSIT_INSURANCE_LP,
+  WithdrawInsuranceLP: ACCOUNTS_WITHDRAW_INSURANCE_LP,
+  PauseMarket: ACCOUNTS_PAUSE_MARKET,
+  UnpauseMarket: ACCOUNTS_UNPAUSE_MARKET,
+  SetPythOracle: ACCOUNTS_SET_PYTH_ORACLE,
+  UpdateMarkPrice: ACCOUNTS_UPDATE_MARK_PRICE,
+  UpdateHyperpMark: ACCOUNTS_UPDATE_HYPERP_MARK,
+  FundMarketInsurance: ACCOUNTS_FUND_MARKET_INSURANCE,
+  SetInsuranceIsolation: ACCOUNTS_SET_INSURANCE_ISOLATION,
+  AdvanceOraclePhase: ACCOUNTS_ADVANCE_ORACLE_PHASE,
+  TopUpKeeperFund: ACCOUNTS_TOPUP_KEEPER_FUND,
+};
+
+export interface ResolvedAccounts {
+  /** Accounts keyed by spec name (e.g. `user`, `slab`, `oracle`) */
+  named: Record<string, PublicKey>;
+  /** Accounts beyond the spec (e.g. PumpSwap vaults), or all of them when there is no spec */
+  remaining: PublicKey[];
+}
+
+/**
+ * Name an instruction's accounts using its ACCOUNTS_* spec.
+ * If fewer keys than the spec are present, only the leading ones are named.
+ */
+export function resolveInstructionAccounts(name: IxName | "Unknown", keys: readonly PublicKey[]): ResolvedAccounts {
+  const spec = name === "Unknown" ? undefined : IX_ACCOUNT_SPECS[name];
+  if (!spec) return { named: {}, remaining: [...keys] };
+  const named: Record<string, PublicKey> = {};
+  spec.forEach((s, i) => {
+    if (i < keys.length) named[s.name] = keys[i];
+  });
+  return { named, remaining: keys.slice(spec.length) };
+}
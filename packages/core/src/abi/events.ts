@@ -0,0 +1,229 @@
+import { PublicKey, type ParsedInnerInstruction, type ParsedTransactionWithMeta } from "@solana/web3.js";
+import {
+  decodeInstructionData,
+  resolveInstructionAccounts,
+  type DecodedIxData,
+} from "./decode.js";
+import { parseErrorFromLogs } from "./errors.js";
+
+// ============================================================================
+// TRANSACTION DECODING — every percolator instruction in a transaction, with
+// its named accounts and the values it logged
+// ============================================================================
+
+export interface InstructionContext {
+  programId: PublicKey;
+  /** Index of the top-level instruction this is, or that CPI'd into percolator */
+  outerIndex: number;
+  /** Position within that instruction's inner instructions; null when top-level */
+  innerIndex: number | null;
+  /** Accounts keyed by ACCOUNTS_* spec name */
+  accounts: Record<string, PublicKey>;
+  /** Accounts past the end of the spec (or all of them, if the spec is unknown) */
+  remainingAccounts: PublicKey[];
+  /** `Program log:` payloads emitted while this instruction was executing */
+  logs: string[];
+  /**
+   * Numeric log lines (`sol_log_64` / `msg!` of integers), one array per line.
+   * Hex (`0x..`) and decimal values are both accepted.
+   */
+  values: bigint[][];
+  /** Bytes from `sol_set_return_data`, if any */
+  returnData: Uint8Array | null;
+}
+
+export type DecodedInstruction = DecodedIxData & InstructionContext;
+
+/** A percolator instruction whose data did not match its layout. */
+export interface UndecodedInstruction {
+  programId: PublicKey;
+  outerIndex: number;
+  innerIndex: number | null;
+  data: Uint8Array;
+  reason: string;
+}
+
+export interface DecodedTransaction {
+  signature: string;
+  slot: number;
+  blockTime: number | null;
+  success: boolean;
+  /** Percolator custom error, when the transaction failed with one */
+  error: ReturnType<typeof parseErrorFromLogs>;
+  /** In execution order: each top-level instruction, then its CPIs */
+  instructions: DecodedInstruction[];
+  undecoded: UndecodedInstruction[];
+  /** True when the runtime truncated the logs, so `logs`/`values` may be incomplete */
+  logsTruncated: boolean;
+}
+
+const BASE58_ALPHABET = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+function decodeBase58(str: string): Uint8Array {
+  let zeros = 0;
+  while (zeros < str.length && str[zeros] === "1") zeros++;
+  const bytes: number[] = [];
+  for (let i = zeros; i < str.length; i++) {
+    let carry = BASE58_ALPHABET.indexOf(str[i]);
+    if (carry < 0) throw new Error(`Invalid base58 character "${str[i]}"`);
+    for (let j = bytes.length - 1; j >= 0; j--) {
+      carry += bytes[j] * 58;
+      bytes[j] = carry & 0xff;
+      carry >>= 8;
+    }
+    while (carry > 0) {
+      bytes.unshift(carry & 0xff);
+      carry >>= 8;
+    }
+  }
+  const out = new Uint8Array(zeros + bytes.length);
+  out.set(bytes, zeros);
+  return out;
+}
+
+const NUMERIC_LOG = /^(?:0x[0-9a-fA-F]+|-?\d+)(?:\s*,\s*(?:0x[0-9a-fA-F]+|-?\d+))*$/;
+
+/** Parse a `Program log:` payload made only of integers, or null if it has anything else. */
+export function parseLogValues(payload: string): bigint[] | null {
+  const trimmed = payload.trim();
+  if (!NUMERIC_LOG.test(trimmed)) return null;
+  return trimmed.split(",").map((v) => BigInt(v.trim()));
+}
+
+interface RawIx {
+  programId: PublicKey;
+  outerIndex: number;
+  innerIndex: number | null;
+  data: string;
+  accounts: PublicKey[];
+}
+
+function ourInstructionsInOrder(tx: ParsedTransactionWithMeta, isOurs: (p: PublicKey) => boolean): RawIx[] {
+  const inner = new Map<number, ParsedInnerInstruction>();
+  for (const group of tx.meta?.innerInstructions ?? []) {
+    inner.set(group.index, group);
+  }
+
+  const out: RawIx[] = [];
+  tx.transaction.message.instructions.forEach((ix, outerIndex) => {
+    if (!("parsed" in ix) && isOurs(ix.programId)) {
+      out.push({ programId: ix.programId, outerIndex, innerIndex: null, data: ix.data, accounts: ix.accounts });
+    }
+    inner.get(outerIndex)?.instructions.forEach((cix, innerIndex) => {
+      if ("parsed" in cix || !isOurs(cix.programId)) return;
+      out.push({ programId: cix.programId, outerIndex, innerIndex, data: cix.data, accounts: cix.accounts });
+    });
+  });
+  return out;
+}
+
+interface InvocationLogs {
+  logs: string[];
+  values: bigint[][];
+  returnData: Uint8Array | null;
+}
+
+/**
+ * Split the log stream into one bucket per percolator invocation, in the
+ * order the runtime entered them. Logs from frames of other programs (e.g. a
+ * matcher CPI'd from inside percolator) are not attributed.
+ */
+function splitLogs(logMessages: string[], ids: ReadonlySet<string>): { buckets: InvocationLogs[]; truncated: boolean } {
+  const buckets: InvocationLogs[] = [];
+  const stack: (InvocationLogs | null)[] = [];
+  let truncated = false;
+
+  for (const line of logMessages) {
+    if (line === "Log truncated") {
+      truncated = true;
+      break;
+    }
+    const invoke = line.match(/^Program (\w+) invoke \[\d+\]$/);
+    if (invoke) {
+      if (ids.has(invoke[1])) {
+        const bucket: InvocationLogs = { logs: [], values: [], returnData: null };
+        buckets.push(bucket);
+        stack.push(bucket);
+      } else {
+        stack.push(null);
+      }
+      continue;
+    }
+    if (/^Program \w+ (success|failed)/.test(line)) {
+      stack.pop();
+      continue;
+    }
+    const current = stack[stack.length - 1];
+    if (!current) continue;
+    if (line.startsWith("Program log: ")) {
+      const payload = line.slice("Program log: ".length);
+      current.logs.push(payload);
+      const values = parseLogValues(payload);
+      if (values) current.values.push(values);
+    } else if (line.startsWith("Program return: ")) {
+      const b64 = line.split(" ")[3];
+      if (b64) current.returnData = Uint8Array.from(Buffer.from(b64, "base64"));
+    }
+  }
+  return { buckets, truncated };
+}
+
+/**
+ * Decode every percolator instruction in a parsed transaction — top-level
+ * and CPI — along with its named accounts and the values it logged.
+ *
+ * `programIds` are the percolator deployments to recognise (the indexer
+ * passes `config.allProgramIds`).
+ */
+export function decodeTransaction(
+  tx: ParsedTransactionWithMeta,
+  programIds: Iterable<PublicKey | string>,
+): DecodedTransaction {
+  const ids = new Set(Array.from(programIds, (p) => (typeof p === "string" ? p : p.toBase58())));
+  const isOurs = (p: PublicKey) => ids.has(p.toBase58());
+
+  const raw = ourInstructionsInOrder(tx, isOurs);
+  const logMessages = tx.meta?.logMessages ?? [];
+  const { buckets, truncated } = splitLogs(logMessages, ids);
+
+  const instructions: DecodedInstruction[] = [];
+  const undecoded: UndecodedInstruction[] = [];
+
+  raw.forEach((ix, i) => {
+    const where = { programId: ix.programId, outerIndex: ix.outerIndex, innerIndex: ix.innerIndex };
+    let data: Uint8Array = new Uint8Array(0);
+    let decoded: DecodedIxData;
+    try {
+      data = decodeBase58(ix.data);
+      decoded = decodeInstructionData(data);
+    } catch (err) {
+      undecoded.push({ ...where, data, reason: err instanceof Error ? err.message : String(err) });
+      return;
+    }
+    const { named, remaining } = resolveInstructionAccounts(decoded.name, ix.accounts);
+    // Failed transactions stop logging at the failing invocation, so later
+    // instructions simply have no bucket.
+    const bucket = buckets[i] ?? { logs: [], values: [], returnData: null };
+    instructions.push({
+      ...decoded,
+      ...where,
+      accounts: named,
+      remainingAccounts: remaining,
+      logs: bucket.logs,
+      values: bucket.values,
+      returnData: bucket.returnData,
+    });
+  });
+
+  const success = !tx.meta?.err;
+  return {
+    signature: tx.transaction.signatures[0] ?? "",
+    slot: tx.slot,
+    blockTime: tx.blockTime ?? null,
+    success,
+    error: success ? null : parseErrorFromLogs(logMessages),
+    instructions,
+    undecoded,
+    logsTruncated: truncated,
+  };
+}
@@ -2,3 +2,5 @@ export * from "./encode.js";
 export * from "./instructions.js";
 export * from "./accounts.js";
 export * from "./errors.js";
+export * from "./decode.js";
+export * from "./events.js";
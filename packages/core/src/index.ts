@@ -4,4 +4,5 @@ export * from "./runtime/index.js";
 export * from "./math/index.js";
 export * from "./validation.js";
 export * from "./oracle/price-router.js";
+export * from "./oracle/manipulation-cost.js";
 export * from "./config/program-ids.js";
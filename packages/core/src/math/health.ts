@@ -0,0 +1,185 @@
+/**
+ * Account health — the single margin report shared by the keeper, the API
+ * and the trade UI.
+ *
+ * Everything is in collateral native units except prices (e6) and ratios (bps).
+ *
+ *   equity = capital + realized pnl + mark pnl − pending funding
+ *
+ * Pending funding is the funding the engine has accrued since the account's
+ * index was last settled. The engine index is quote-per-base (e6), so it is
+ * converted to collateral at the current price; at the price the index moved
+ * at, that is the simulator's `fundingPaid` amount. Positive means the account
+ * owes. The simulator decides liquidations through `computeAccountHealth`.
+ *
+ * The liquidation price is solved from that same equation: it is the price at
+ * which `equity − maintenanceMargin` crosses zero, so `liquidatable` flips
+ * exactly there.
+ */
+
+import type { Account, EngineState, RiskParams } from "../solana/slab.js";
+import { computeMarkPnl, computeRequiredMargin } from "./trading.js";
+import { computeWarmupUnlockedCapital } from "./warmup.js";
+
+export interface AccountHealth {
+  /** capital + pnl + unrealizedPnl − pendingFunding */
+  equity: bigint;
+  /** Mark-to-market PnL of the open position at `priceE6` */
+  unrealizedPnl: bigint;
+  /** Funding accrued but not yet settled into `pnl` (positive = owed) */
+  pendingFunding: bigint;
+  /** Capital not yet released by the market's warmup schedule */
+  warmupLockedCapital: bigint;
+  /** |position| × price / 1e6 — the base for margin requirements */
+  notional: bigint;
+  maintenanceMargin: bigint;
+  initialMargin: bigint;
+  /** equity − maintenanceMargin; liquidatable once this goes negative */
+  bufferToLiquidation: bigint;
+  /** equity / notional in bps; null when flat or unpriced */
+  marginRatioBps: bigint | null;
+  /**
+   * Price at which the account becomes liquidatable: the highest such price
+   * below the current buffer peak for longs, the lowest for shorts. 0n when
+   * flat, max u64 when no price liquidates it.
+   */
+  liquidationPriceE6: bigint;
+  liquidatable: boolean;
+}
+
+function abs(n: bigint): bigint {
+  return n < 0n ? -n : n;
+}
+
+/** |position| × price / 1e6 — the base every margin requirement is taken on. */
+export function computePositionNotional(positionSize: bigint, priceE6: bigint): bigint {
+  return priceE6 > 0n ? (abs(positionSize) * priceE6) / 1_000_000n : 0n;
+}
+
+const MAX_U64 = 18_446_744_073_709_551_615n;
+
+function isqrt(n: bigint): bigint {
+  if (n < 2n) return n;
+  let x = n;
+  let y = (x + 1n) / 2n;
+  while (y < x) {
+    x = y;
+    y = (x + n / x) / 2n;
+  }
+  return x;
+}
+
+/** Equity and maintenance margin at one price — the liquidation equation. */
+function marginAt(account: Account, engine: EngineState, params: RiskParams, priceE6: bigint) {
+  const hasPrice = priceE6 > 0n;
+  const unrealizedPnl = hasPrice
+    ? computeMarkPnl(account.positionSize, account.entryPrice, priceE6)
+    : 0n;
+  const pendingFunding =
+    hasPrice && account.positionSize !== 0n
+      ? (account.positionSize * (engine.fundingIndexQpbE6 - account.fundingIndex)) / priceE6
+      : 0n;
+  const equity = account.capital + account.pnl + unrealizedPnl - pendingFunding;
+  const notional = computePositionNotional(account.positionSize, priceE6);
+  const maintenanceMargin = computeRequiredMargin(notional, params.maintenanceMarginBps);
+  return {
+    unrealizedPnl,
+    pendingFunding,
+    equity,
+    notional,
+    maintenanceMargin,
+    liquidatable: notional > 0n && equity < maintenanceMargin,
+  };
+}
+
+/**
+ * Solve `equity(P) = maintenanceMargin(P)` for P.
+ *
+ * With s the signed position and Q = s × (entry + Δfunding index), the buffer
+ * is `capital + pnl + s − Q/P − maint × |s| × P / 1e10`. For a long with
+ * Q > 0 it rises to a peak at √(Q × 1e10 / (maint × |s|)) and falls after;
+ * otherwise it only falls. The downside crossing is searched below the peak,
+ * the upside crossing above it, each by bisection on `marginAt` itself.
+ */
+function solveLiquidationPrice(account: Account, engine: EngineState, params: RiskParams): bigint {
+  const s = account.positionSize;
+  if (s === 0n) return 0n;
+  const liq = (p: bigint) => marginAt(account, engine, params, p).liquidatable;
+
+  const q = s * (account.entryPrice + engine.fundingIndexQpbE6 - account.fundingIndex);
+  let peak = 1n;
+  if (s > 0n && q > 0n) {
+    const denom = params.maintenanceMarginBps * s;
+    peak = denom > 0n ? isqrt((q * 10_000_000_000n) / denom) : MAX_U64;
+    if (peak > MAX_U64) peak = MAX_U64;
+    if (peak < 1n) peak = 1n;
+  }
+
+  if (peak > 1n && liq(1n)) {
+    // Highest liquidatable price on the way up to the peak
+    if (liq(peak)) return peak;
+    let lo = 1n;
+    let hi = peak;
+    while (hi - lo > 1n) {
+      const mid = (lo + hi) / 2n;
+      if (liq(mid)) lo = mid;
+      else hi = mid;
+    }
+    return lo;
+  }
+
+  // Lowest liquidatable price at or beyond the peak
+  if (liq(peak)) return peak;
+  if (!liq(MAX_U64)) return MAX_U64;
+  let lo = peak;
+  let hi = MAX_U64;
+  while (hi - lo > 1n) {
+    const mid = (lo + hi) / 2n;
+    if (liq(mid)) hi = mid;
+    else lo = mid;
+  }
+  return hi;
+}
+
+/**
+ * Compute the full margin picture for one account at a given price.
+ *
+ * `slot` drives the warmup schedule; it defaults to the engine's current slot.
+ * A zero or negative price yields a report with no mark PnL and no funding,
+ * which callers should treat as "price unavailable".
+ */
+export function computeAccountHealth(
+  account: Account,
+  engine: EngineState,
+  params: RiskParams,
+  priceE6: bigint,
+  slot: bigint = engine.currentSlot,
+): AccountHealth {
+  const { unrealizedPnl, pendingFunding, equity, notional, maintenanceMargin, liquidatable } =
+    marginAt(account, engine, params, priceE6);
+
+  const unlocked = computeWarmupUnlockedCapital(
+    account.capital,
+    slot,
+    account.warmupStartedAtSlot,
+    params.warmupPeriodSlots,
+  );
+  const warmupLockedCapital = account.capital > unlocked ? account.capital - unlocked : 0n;
+
+  const initialMargin = computeRequiredMargin(notional, params.initialMarginBps);
+  const bufferToLiquidation = equity - maintenanceMargin;
+
+  return {
+    equity,
+    unrealizedPnl,
+    pendingFunding,
+    warmupLockedCapital,
+    notional,
+    maintenanceMargin,
+    initialMargin,
+    bufferToLiquidation,
+    marginRatioBps: notional > 0n ? (equity * 10_000n) / notional : null,
+    liquidationPriceE6: solveLiquidationPrice(account, engine, params),
+    liquidatable,
+  };
+}
@@ -1,2 +1,4 @@
 export * from "./trading.js";
 export * from "./warmup.js";
+export * from "./simulator.js";
+export * from "./health.js";
@@ -0,0 +1,428 @@
+/**
+ * Offline risk-engine simulator.
+ *
+ * Applies keeper cranks, funding accrual, trades, deposits/withdrawals and
+ * liquidations to a decoded slab without touching RPC, so keepers, tests and
+ * the UI can preview post-state before sending a transaction.
+ *
+ * The simulator follows the SDK's coin-margined model (see trading.ts):
+ * position sizes and PnL are in collateral native units, prices are e6.
+ * Margin requirements are taken on notional (|position| × price / 1e6) and
+ * liquidation is decided by computeAccountHealth, as in the keeper.
+ * It is deterministic — the same market and ops always produce the same
+ * result — but it is a model of the on-chain engine, not a bit-exact port:
+ * rounding on the program side can differ by a few native units.
+ *
+ * All operations are pure: the input market is never mutated.
+ */
+
+import type { Account, EngineState, MarketConfig, RiskParams } from "../solana/slab.js";
+import { AccountKind, parseAllAccounts, parseConfig, parseEngine, parseParams } from "../solana/slab.js";
+import { getErrorName } from "../abi/errors.js";
+import { computeMarkPnl, computeRequiredMargin, computeTradingFee } from "./trading.js";
+import { computeAccountHealth, computePositionNotional } from "./health.js";
+import { computeWarmupUnlockedCapital } from "./warmup.js";
+
+// Program error codes surfaced by the simulator (see abi/errors.ts)
+const ERR_INSUFFICIENT_BALANCE = 13;
+const ERR_UNDERCOLLATERALIZED = 14;
+const ERR_ACCOUNT_NOT_FOUND = 19;
+const ERR_NOT_LP = 20;
+const ERR_ACCOUNT_KIND_MISMATCH = 23;
+
+/** Decoded market state the simulator operates on. */
+export interface SimMarket {
+  engine: EngineState;
+  params: RiskParams;
+  config: MarketConfig | null;
+  accounts: Map<number, Account>;
+}
+
+export type SimOp =
+  | {
+      type: "crank";
+      slot: bigint;
+      oraclePriceE6: bigint;
+      /** Override the rate applied over this crank interval (defaults to the engine's last rate) */
+      fundingRateBpsPerSlot?: bigint;
+    }
+  | { type: "trade"; slot: bigint; userIdx: number; lpIdx: number; size: bigint; priceE6: bigint }
+  | { type: "deposit"; idx: number; amount: bigint }
+  | { type: "withdraw"; slot: bigint; idx: number; amount: bigint; oraclePriceE6: bigint }
+  | { type: "liquidate"; slot: bigint; idx: number; oraclePriceE6: bigint };
+
+export type SimEvent =
+  | { type: "funding"; slots: bigint; rateBpsPerSlot: bigint; indexDelta: bigint }
+  | { type: "fundingPaid"; idx: number; amount: bigint }
+  | { type: "trade"; userIdx: number; lpIdx: number; size: bigint; priceE6: bigint; fee: bigint }
+  | { type: "deposit"; idx: number; amount: bigint }
+  | { type: "withdraw"; idx: number; amount: bigint }
+  | {
+      type: "liquidation";
+      idx: number;
+      closedSize: bigint;
+      realizedPnl: bigint;
+      fee: bigint;
+      /** Shortfall covered by the insurance fund */
+      insuranceCovered: bigint;
+      /** Shortfall the insurance fund could not cover */
+      badDebt: bigint;
+    }
+  | { type: "liquidationSkipped"; idx: number; equity: bigint; maintenanceRequirement: bigint };
+
+export interface SimResult {
+  market: SimMarket;
+  events: SimEvent[];
+}
+
+/** A simulated op that the program would reject. Codes match PERCOLATOR_ERRORS. */
+export class SimulationError extends Error {
+  readonly code: number;
+
+  constructor(code: number, detail: string) {
+    super(`${getErrorName(code)}: ${detail}`);
+    this.name = "SimulationError";
+    this.code = code;
+  }
+}
+
+/** Build a SimMarket from raw slab account data. */
+export function simMarketFromSlab(data: Uint8Array): SimMarket {
+  const accounts = new Map<number, Account>();
+  for (const { idx, account } of parseAllAccounts(data)) accounts.set(idx, account);
+  return {
+    engine: parseEngine(data),
+    params: parseParams(data),
+    config: parseConfig(data),
+    accounts,
+  };
+}
+
+function cloneMarket(m: SimMarket): SimMarket {
+  const accounts = new Map<number, Account>();
+  for (const [idx, a] of m.accounts) accounts.set(idx, { ...a });
+  return {
+    engine: { ...m.engine, insuranceFund: { ...m.engine.insuranceFund } },
+    params: m.params,
+    config: m.config,
+    accounts,
+  };
+}
+
+function abs(x: bigint): bigint {
+  return x < 0n ? -x : x;
+}
+
+function getAccount(m: SimMarket, idx: number): Account {
+  const acct = m.accounts.get(idx);
+  if (!acct) throw new SimulationError(ERR_ACCOUNT_NOT_FOUND, `account ${idx} is not in use`);
+  return acct;
+}
+
+/** capital + realized PnL + mark-to-market PnL at the given price. */
+export function computeSimEquity(account: Account, priceE6: bigint): bigint {
+  return (
+    account.capital +
+    account.pnl +
+    computeMarkPnl(account.positionSize, account.entryPrice, priceE6)
+  );
+}
+
+/** Maintenance margin requirement for the account's position, on its notional at the given price. */
+export function computeSimMaintenanceRequirement(account: Account, params: RiskParams, priceE6: bigint): bigint {
+  return computeRequiredMargin(computePositionNotional(account.positionSize, priceE6), params.maintenanceMarginBps);
+}
+
+/**
+ * True when the account is below maintenance margin at the given price.
+ * Delegates to computeAccountHealth so the simulator, the keeper and the API
+ * agree on who is liquidatable.
+ */
+export function isSimLiquidatable(account: Account, engine: EngineState, params: RiskParams, priceE6: bigint): boolean {
+  return computeAccountHealth(account, engine, params, priceE6).liquidatable;
+}
+
+/** Equity usable as margin: only warmup-unlocked capital counts. */
+function marginEquity(account: Account, params: RiskParams, slot: bigint, priceE6: bigint): bigint {
+  const unlocked = computeWarmupUnlockedCapital(
+    account.capital,
+    slot,
+    account.warmupStartedAtSlot,
+    params.warmupPeriodSlots,
+  );
+  return unlocked + account.pnl + computeMarkPnl(account.positionSize, account.entryPrice, priceE6);
+}
+
+/**
+ * Apply a signed fill to an account, realizing PnL on the reduced part and
+ * averaging the entry price on the increased part.
+ */
+function applyFill(account: Account, delta: bigint, priceE6: bigint): void {
+  const pos = account.positionSize;
+  if (delta === 0n) return;
+
+  if (pos === 0n || (pos > 0n) === (delta > 0n)) {
+    const oldAbs = abs(pos);
+    const addAbs = abs(delta);
+    account.entryPrice = (oldAbs * account.entryPrice + addAbs * priceE6) / (oldAbs + addAbs);
+    account.positionSize = pos + delta;
+    return;
+  }
+
+  const closed = abs(delta) < abs(pos) ? abs(delta) : abs(pos);
+  account.pnl += computeMarkPnl(pos > 0n ? closed : -closed, account.entryPrice, priceE6);
+  account.positionSize = pos + delta;
+  if (account.positionSize === 0n) {
+    account.entryPrice = 0n;
+  } else if ((account.positionSize > 0n) !== (pos > 0n)) {
+    // Flipped through zero: the remainder opens at the fill price
+    account.entryPrice = priceE6;
+  }
+}
+
+/** Recompute engine aggregates (OI, c_tot, LP exposure) from the accounts. */
+function refreshAggregates(m: SimMarket): void {
+  let totalOi = 0n, longOi = 0n, shortOi = 0n, cTot = 0n, pnlPosTot = 0n;
+  let netLp = 0n, lpSumAbs = 0n, lpMaxAbs = 0n;
+  for (const a of m.accounts.values()) {
+    const p = a.positionSize;
+    totalOi += abs(p);
+    if (p > 0n) longOi += p;
+    else shortOi += -p;
+    cTot += a.capital;
+    if (a.pnl > 0n) pnlPosTot += a.pnl;
+    if (a.kind === AccountKind.LP) {
+      netLp += p;
+      lpSumAbs += abs(p);
+      if (abs(p) > lpMaxAbs) lpMaxAbs = abs(p);
+    }
+  }
+  const e = m.engine;
+  e.totalOpenInterest = totalOi;
+  e.longOi = longOi;
+  e.shortOi = shortOi;
+  e.cTot = cTot;
+  e.pnlPosTot = pnlPosTot;
+  e.netLpPos = netLp;
+  e.lpSumAbs = lpSumAbs;
+  e.lpMaxAbs = lpMaxAbs;
+  e.numUsedAccounts = m.accounts.size;
+}
+
+function advanceSlot(m: SimMarket, slot: bigint): void {
+  if (slot > m.engine.currentSlot) m.engine.currentSlot = slot;
+}
+
+function accrueFunding(
+  m: SimMarket,
+  slot: bigint,
+  priceE6: bigint,
+  rateOverride: bigint | undefined,
+  events: SimEvent[],
+): void {
+  const e = m.engine;
+  const last = e.lastFundingSlot;
+  e.lastFundingSlot = slot > last ? slot : last;
+  if (last === 0n || slot <= last) return;
+
+  let rate = rateOverride ?? e.fundingRateBpsPerSlotLast;
+  const cap = m.config?.fundingMaxBpsPerSlot ?? 0n;
+  if (cap > 0n) {
+    if (rate > cap) rate = cap;
+    if (rate < -cap) rate = -cap;
+  }
+  e.fundingRateBpsPerSlotLast = rate;
+
+  const slots = slot - last;
+  const indexDelta = (priceE6 * rate * slots) / 10_000n;
+  e.fundingIndexQpbE6 += indexDelta;
+  events.push({ type: "funding", slots, rateBpsPerSlot: rate, indexDelta });
+  if (rate === 0n) return;
+
+  // Positive rate: longs pay shorts. Settled eagerly so every touched account
+  // carries the current index afterwards.
+  for (const [idx, a] of m.accounts) {
+    a.fundingIndex = e.fundingIndexQpbE6;
+    if (a.positionSize === 0n) continue;
+    const payment = (a.positionSize * rate * slots) / 10_000n;
+    a.pnl -= payment;
+    events.push({ type: "fundingPaid", idx, amount: payment });
+  }
+}
+
+function liquidate(m: SimMarket, idx: number, priceE6: bigint, events: SimEvent[]): void {
+  const a = getAccount(m, idx);
+  const health = computeAccountHealth(a, m.engine, m.params, priceE6);
+  if (!health.liquidatable) {
+    events.push({
+      type: "liquidationSkipped",
+      idx,
+      equity: health.equity,
+      maintenanceRequirement: health.maintenanceMargin,
+    });
+    return;
+  }
+
+  const closedSize = a.positionSize;
+  const pnlBefore = a.pnl;
+  applyFill(a, -closedSize, priceE6);
+  const realizedPnl = a.pnl - pnlBefore;
+
+  let fee = computeRequiredMargin(abs(closedSize), m.params.liquidationFeeBps);
+  if (m.params.liquidationFeeCap > 0n && fee > m.params.liquidationFeeCap) {
+    fee = m.params.liquidationFeeCap;
+  }
+
+  const ins = m.engine.insuranceFund;
+  let remaining = a.capital + a.pnl;
+  const feePaid = remaining <= 0n ? 0n : remaining < fee ? remaining : fee;
+  remaining -= feePaid;
+  ins.balance += feePaid;
+  ins.feeRevenue += feePaid;
+
+  let insuranceCovered = 0n;
+  let badDebt = 0n;
+  if (remaining < 0n) {
+    const shortfall = -remaining;
+    insuranceCovered = shortfall < ins.balance ? shortfall : ins.balance;
+    ins.balance -= insuranceCovered;
+    badDebt = shortfall - insuranceCovered;
+    remaining = 0n;
+  }
+  a.capital = remaining;
+  a.pnl = 0n;
+
+  m.engine.lifetimeLiquidations += 1n;
+  events.push({ type: "liquidation", idx, closedSize, realizedPnl, fee: feePaid, insuranceCovered, badDebt });
+}
+
+function applyInPlace(m: SimMarket, op: SimOp, events: SimEvent[]): void {
+  switch (op.type) {
+    case "crank": {
+      advanceSlot(m, op.slot);
+      accrueFunding(m, op.slot, op.oraclePriceE6, op.fundingRateBpsPerSlot, events);
+      m.engine.markPriceE6 = op.oraclePriceE6;
+      m.engine.lastSweepStartSlot = op.slot;
+      for (const [idx, a] of [...m.accounts]) {
+        if (isSimLiquidatable(a, m.engine, m.params, op.oraclePriceE6)) liquidate(m, idx, op.oraclePriceE6, events);
+      }
+      m.engine.lastSweepCompleteSlot = op.slot;
+      m.engine.lastCrankSlot = op.slot;
+      break;
+    }
+
+    case "trade": {
+      if (op.size === 0n) break;
+      if (op.userIdx === op.lpIdx) {
+        throw new SimulationError(ERR_ACCOUNT_KIND_MISMATCH, "user and LP must be different accounts");
+      }
+      advanceSlot(m, op.slot);
+      const user = getAccount(m, op.userIdx);
+      const lp = getAccount(m, op.lpIdx);
+      if (lp.kind !== AccountKind.LP) throw new SimulationError(ERR_NOT_LP, `account ${op.lpIdx} is not an LP`);
+      if (user.kind !== AccountKind.User) {
+        throw new SimulationError(ERR_ACCOUNT_KIND_MISMATCH, `account ${op.userIdx} is not a user account`);
+      }
+
+      const fee = computeTradingFee(abs(op.size), m.params.tradingFeeBps);
+      if (user.capital < fee) {
+        throw new SimulationError(ERR_INSUFFICIENT_BALANCE, `fee ${fee} exceeds capital ${user.capital}`);
+      }
+
+      const before = [abs(user.positionSize), abs(lp.positionSize)];
+      user.capital -= fee;
+      applyFill(user, op.size, op.priceE6);
+      applyFill(lp, -op.size, op.priceE6);
+
+      // Only risk-increasing fills must satisfy initial margin
+      for (const [i, a] of [user, lp].entries()) {
+        if (abs(a.positionSize) <= before[i]) continue;
+        const required = computeRequiredMargin(computePositionNotional(a.positionSize, op.priceE6), m.params.initialMarginBps);
+        const equity = marginEquity(a, m.params, op.slot, op.priceE6);
+        if (equity < required) {
+          const idx = i === 0 ? op.userIdx : op.lpIdx;
+          throw new SimulationError(
+            ERR_UNDERCOLLATERALIZED,
+            `account ${idx} equity ${equity} below initial margin ${required}`,
+          );
+        }
+      }
+
+      m.engine.insuranceFund.balance += fee;
+      m.engine.insuranceFund.feeRevenue += fee;
+      events.push({ type: "trade", userIdx: op.userIdx, lpIdx: op.lpIdx, size: op.size, priceE6: op.priceE6, fee });
+      break;
+    }
+
+    case "deposit": {
+      const a = getAccount(m, op.idx);
+      a.capital += op.amount;
+      m.engine.vault += op.amount;
+      events.push({ type: "deposit", idx: op.idx, amount: op.amount });
+      break;
+    }
+
+    case "withdraw": {
+      advanceSlot(m, op.slot);
+      const a = getAccount(m, op.idx);
+      const unlocked = computeWarmupUnlockedCapital(
+        a.capital,
+        op.slot,
+        a.warmupStartedAtSlot,
+        m.params.warmupPeriodSlots,
+      );
+      if (op.amount > unlocked) {
+        throw new SimulationError(
+          ERR_INSUFFICIENT_BALANCE,
+          `withdraw ${op.amount} exceeds available capital ${unlocked}`,
+        );
+      }
+      a.capital -= op.amount;
+      if (a.positionSize !== 0n) {
+        const required = computeRequiredMargin(
+          computePositionNotional(a.positionSize, op.oraclePriceE6),
+          m.params.initialMarginBps,
+        );
+        const equity = marginEquity(a, m.params, op.slot, op.oraclePriceE6);
+        if (equity < required) {
+          throw new SimulationError(
+            ERR_UNDERCOLLATERALIZED,
+            `equity ${equity} after withdraw below initial margin ${required}`,
+          );
+        }
+      }
+      m.engine.vault -= op.amount;
+      events.push({ type: "withdraw", idx: op.idx, amount: op.amount });
+      break;
+    }
+
+    case "liquidate": {
+      advanceSlot(m, op.slot);
+      liquidate(m, op.idx, op.oraclePriceE6, events);
+      break;
+    }
+  }
+}
+
+/**
+ * Apply a single op. Throws SimulationError when the program would reject it;
+ * the input market is left untouched either way.
+ */
+export function applySimOp(market: SimMarket, op: SimOp): SimResult {
+  const next = cloneMarket(market);
+  const events: SimEvent[] = [];
+  applyInPlace(next, op, events);
+  refreshAggregates(next);
+  return { market: next, events };
+}
+
+/** Apply ops in order. Stops at (and throws) the first rejected op. */
+export function simulate(market: SimMarket, ops: SimOp[]): SimResult {
+  const next = cloneMarket(market);
+  const events: SimEvent[] = [];
+  for (const op of ops) {
+    applyInPlace(next, op, events);
+    refreshAggregates(next);
+  }
+  return { market: next, events };
+}
@@ -0,0 +1,210 @@
+/**
+ * Oracle Manipulation Cost — how much capital it takes to move a DEX pool's
+ * price, compared with what a market lets an attacker earn from moving it.
+ *
+ * A market that prices off a pool (Hyperp mode) can be attacked by pushing
+ * the pool and trading the market on the other side. The attack pays when the
+ * PnL it unlocks exceeds the capital burnt moving the pool, so a market is
+ * only safe when its oracle costs more to move than the open interest it
+ * allows can earn from the move.
+ */
+
+import { Connection, PublicKey } from "@solana/web3.js";
+import {
+  detectDexType,
+  parseDexPool,
+  computeDexPoolDepth,
+  dexRequiresVaultData,
+  type DexType,
+  type DexPoolDepth,
+} from "../solana/dex-oracle.js";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+export interface PoolDepthSnapshot extends DexPoolDepth {
+  poolAddress: string;
+  dexType: DexType;
+  baseMint: string;
+  quoteMint: string;
+  baseDecimals: number;
+  quoteDecimals: number;
+  slot: number;
+}
+
+export type ManipulationRiskLevel = "low" | "medium" | "high" | "critical";
+
+export interface ManipulationRiskInput {
+  /** USD value of one side of the pool at spot (both sides are equal in value) */
+  poolDepthUsd: number;
+  /** Market initial margin; 10_000 / initialMarginBps is the max leverage */
+  initialMarginBps: number;
+  /** Largest open interest the market allows, in USD */
+  maxOiUsd: number;
+  /** Per-update oracle circuit breaker in 0.01 bps (1_000_000 = 100%); 0 = none */
+  oraclePriceCapE2bps?: number;
+}
+
+export interface ManipulationRisk {
+  /** Price move the assessment is made at */
+  moveBps: number;
+  /** Capital needed to move the pool by `moveBps` */
+  costUsd: number;
+  /** What the market's max OI earns from a `moveBps` move */
+  maxProfitUsd: number;
+  /** costUsd / maxProfitUsd — below 1 the attack pays */
+  safetyMultiple: number;
+  /** 0 (safe) – 100 (free to manipulate) */
+  score: number;
+  level: ManipulationRiskLevel;
+  /** True when the oracle is cheaper to move than the OI it allows can earn */
+  blocked: boolean;
+}
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/** Safety multiples at or above which a market is rated `medium` / `low` */
+export const MANIPULATION_SAFETY_MEDIUM = 3;
+export const MANIPULATION_SAFETY_LOW = 10;
+
+// ---------------------------------------------------------------------------
+// Math
+// ---------------------------------------------------------------------------
+
+/**
+ * Capital needed to push a constant-product (or in-range concentrated) pool's
+ * price up by `moveBps`, ignoring swap fees.
+ *
+ * With counter-side reserve Q, price P ∝ Q², so moving to P·(1+p) takes the
+ * reserve to Q·√(1+p) — the attacker pays in Q·(√(1+p) − 1). Pushing down
+ * costs more (Q·(1/√(1−p) − 1)), so the cheaper upward push is the bound.
+ * For concentrated pools this assumes the move stays inside the current tick
+ * range; liquidity beyond it may be thinner.
+ */
+export function costToMovePriceUsd(poolDepthUsd: number, moveBps: number): number {
+  if (poolDepthUsd <= 0 || moveBps <= 0) return 0;
+  return poolDepthUsd * (Math.sqrt(1 + moveBps / 10_000) - 1);
+}
+
+/**
+ * OI cap multiplier a market runs with: its configured `oiCapMultiplierBps`,
+ * or with none configured, LP capital × max leverage (the most the LP can
+ * take the other side of at the initial margin).
+ */
+export function marketOiCapMultiplierBps(configuredBps: number, initialMarginBps: number): number {
+  if (configuredBps > 0) return configuredBps;
+  return initialMarginBps > 0 ? (10_000 * 10_000) / initialMarginBps : 0;
+}
+
+/** Max OI for a market: LP capital × OI cap multiplier. */
+export function maxOpenInterestUsd(lpCollateralUsd: number, oiCapMultiplierBps: number): number {
+  return (lpCollateralUsd * oiCapMultiplierBps) / 10_000;
+}
+
+/**
+ * Compare the cost of moving the oracle against what the market lets an
+ * attacker earn from the move.
+ *
+ * The move assessed is the initial margin — enough to wipe out a max-leverage
+ * position on the other side. With an oracle price cap, a single update can
+ * move the mark by no more than the cap, so the move is capped too.
+ */
+export function assessManipulationRisk(input: ManipulationRiskInput): ManipulationRisk {
+  const capBps = (input.oraclePriceCapE2bps ?? 0) / 100;
+  const moveBps = capBps > 0 ? Math.min(input.initialMarginBps, capBps) : input.initialMarginBps;
+
+  const costUsd = costToMovePriceUsd(input.poolDepthUsd, moveBps);
+  const maxProfitUsd = (Math.max(0, input.maxOiUsd) * moveBps) / 10_000;
+  const safetyMultiple = maxProfitUsd > 0 ? costUsd / maxProfitUsd : Infinity;
+
+  let level: ManipulationRiskLevel;
+  if (safetyMultiple < 1) level = "critical";
+  else if (safetyMultiple < MANIPULATION_SAFETY_MEDIUM) level = "high";
+  else if (safetyMultiple < MANIPULATION_SAFETY_LOW) level = "medium";
+  else level = "low";
+
+  return {
+    moveBps,
+    costUsd,
+    maxProfitUsd,
+    safetyMultiple,
+    score: Number.isFinite(safetyMultiple) ? Math.round(100 / (1 + safetyMultiple)) : 0,
+    level,
+    blocked: safetyMultiple < 1,
+  };
+}
+
+// ---------------------------------------------------------------------------
+// On-chain reads
+// ---------------------------------------------------------------------------
+
+/**
+ * Read a pool's depth on-chain: pool, vaults (reserve-priced pools) and both
+ * mints in one `getMultipleAccounts` call, so reserves and decimals come from
+ * the same slot.
+ *
+ * @returns null when the account isn't a supported pool or its depth can't be
+ *          read (Meteora DLMM)
+ */
+export async function fetchDexPoolDepth(
+  connection: Connection,
+  poolAddress: string,
+): Promise<PoolDepthSnapshot | null> {
+  const poolKey = new PublicKey(poolAddress);
+  const poolAccount = await connection.getAccountInfo(poolKey);
+  if (!poolAccount) return null;
+  const dexType = detectDexType(poolAccount.owner);
+  if (!dexType) return null;
+
+  const info = parseDexPool(dexType, poolKey, new Uint8Array(poolAccount.data));
+  const keys = [poolKey, info.baseMint, info.quoteMint];
+  if (dexRequiresVaultData(dexType)) keys.push(info.baseVault!, info.quoteVault!);
+  const { context, value } = await connection.getMultipleAccountsInfoAndContext(keys);
+  if (value.some((a) => !a)) return null;
+  const [pool, baseMint, quoteMint, baseVault, quoteVault] = value.map((a) => new Uint8Array(a!.data));
+  if (baseMint.length <= 44 || quoteMint.length <= 44) return null;
+
+  const depth = computeDexPoolDepth(
+    dexType,
+    pool,
+    baseVault && quoteVault ? { base: baseVault, quote: quoteVault } : undefined,
+  );
+  if (!depth) return null;
+
+  return {
+    ...depth,
+    poolAddress,
+    dexType,
+    baseMint: info.baseMint.toBase58(),
+    quoteMint: info.quoteMint.toBase58(),
+    baseDecimals: baseMint[44],
+    quoteDecimals: quoteMint[44],
+    slot: context.slot,
+  };
+}
+
+/**
+ * USD value of `mint`'s side of the pool, priced at `mintPriceUsd`. At spot
+ * both sides are worth the same, so this is the `poolDepthUsd` for
+ * {@link assessManipulationRisk} without needing a price for the counter token.
+ *
+ * @returns null when `mint` is not in the pool
+ */
+export function poolSideValueUsd(snapshot: PoolDepthSnapshot, mint: string, mintPriceUsd: number): number | null {
+  let atoms: bigint;
+  let decimals: number;
+  if (snapshot.baseMint === mint) {
+    atoms = snapshot.baseReserve;
+    decimals = snapshot.baseDecimals;
+  } else if (snapshot.quoteMint === mint) {
+    atoms = snapshot.quoteReserve;
+    decimals = snapshot.quoteDecimals;
+  } else {
+    return null;
+  }
+  if (atoms <= 0n || !(mintPriceUsd > 0)) return 0;
+  return (Number(atoms) / 10 ** decimals) * mintPriceUsd;
+}
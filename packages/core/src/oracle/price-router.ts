@@ -94,7 +94,7 @@ for (const [feedId, info] of Object.entries(PYTH_SOLANA_FEEDS)) {
 // DexScreener fetcher
 // ---------------------------------------------------------------------------
 
-const SUPPORTED_DEX_IDS = new Set(["pumpswap", "raydium", "meteora"]);
+const SUPPORTED_DEX_IDS = new Set(["pumpswap", "raydium", "meteora", "orca"]);
 
 async function fetchDexSources(mint: string, signal?: AbortSignal): Promise<PriceSource[]> {
   try {
@@ -8,6 +8,10 @@ import {
   Commitment,
   AccountMeta,
   ComputeBudgetProgram,
+  TransactionMessage,
+  VersionedTransaction,
+  type RpcResponseAndContext,
+  type SimulatedTransactionResponse,
 } from "@solana/web3.js";
 import { parseErrorFromLogs } from "../abi/errors.js";
 
@@ -41,13 +45,39 @@ export interface TxResult {
 
 export interface SimulateOrSendParams {
   connection: Connection;
-  ix: TransactionInstruction;
+  /** One instruction, or several sent atomically in one transaction */
+  ix: TransactionInstruction | TransactionInstruction[];
   signers: Keypair[];
   simulate: boolean;
   commitment?: Commitment;
   computeUnitLimit?: number; // Custom compute unit limit (default: 200,000, max: 1,400,000)
 }
 
+function simulationResult(result: RpcResponseAndContext<SimulatedTransactionResponse>): TxResult {
+  const logs = result.value.logs ?? [];
+  let err: string | null = null;
+  let hint: string | undefined;
+
+  if (result.value.err) {
+    const parsed = parseErrorFromLogs(logs);
+    if (parsed) {
+      err = `${parsed.name} (0x${parsed.code.toString(16)})`;
+      hint = parsed.hint;
+    } else {
+      err = JSON.stringify(result.value.err);
+    }
+  }
+
+  return {
+    signature: "(simulated)",
+    slot: result.context.slot,
+    err,
+    hint,
+    logs,
+    unitsConsumed: result.value.unitsConsumed ?? undefined,
+  };
+}
+
 /**
  * Simulate or send a transaction.
  * Returns consistent output for both modes.
@@ -68,36 +98,14 @@ export async function simulateOrSend(
     );
   }
 
-  tx.add(ix);
+  tx.add(...(Array.isArray(ix) ? ix : [ix]));
   const latestBlockhash = await connection.getLatestBlockhash(commitment);
   tx.recentBlockhash = latestBlockhash.blockhash;
   tx.feePayer = signers[0].publicKey;
 
   if (simulate) {
     tx.sign(...signers);
-    const result = await connection.simulateTransaction(tx, signers);
-    const logs = result.value.logs ?? [];
-    let err: string | null = null;
-    let hint: string | undefined;
-
-    if (result.value.err) {
-      const parsed = parseErrorFromLogs(logs);
-      if (parsed) {
-        err = `${parsed.name} (0x${parsed.code.toString(16)})`;
-        hint = parsed.hint;
-      } else {
-        err = JSON.stringify(result.value.err);
-      }
-    }
-
-    return {
-      signature: "(simulated)",
-      slot: result.context.slot,
-      err,
-      hint,
-      logs,
-      unitsConsumed: result.value.unitsConsumed ?? undefined,
-    };
+    return simulationResult(await connection.simulateTransaction(tx, signers));
   }
 
   // Send
@@ -156,6 +164,40 @@ export async function simulateOrSend(
   }
 }
 
+export interface SimulateUnsignedParams {
+  connection: Connection;
+  /** One instruction, or several simulated atomically in one transaction */
+  ix: TransactionInstruction | TransactionInstruction[];
+  /** Fee payer to simulate as; its key is never needed */
+  feePayer: PublicKey;
+  commitment?: Commitment;
+  computeUnitLimit?: number;
+}
+
+/**
+ * Simulate a transaction without signing it (`sigVerify: false`). Every
+ * signer the instructions name, fee payer included, is taken as signed, so
+ * any wallet's transactions can be simulated from its public key alone.
+ */
+export async function simulateUnsigned(params: SimulateUnsignedParams): Promise<TxResult> {
+  const { connection, ix, feePayer, commitment = "confirmed", computeUnitLimit } = params;
+
+  const instructions = Array.isArray(ix) ? [...ix] : [ix];
+  if (computeUnitLimit !== undefined) {
+    instructions.unshift(ComputeBudgetProgram.setComputeUnitLimit({ units: computeUnitLimit }));
+  }
+  const { blockhash } = await connection.getLatestBlockhash(commitment);
+  const message = new TransactionMessage({
+    payerKey: feePayer,
+    recentBlockhash: blockhash,
+    instructions,
+  }).compileToV0Message();
+
+  return simulationResult(
+    await connection.simulateTransaction(new VersionedTransaction(message), { sigVerify: false, commitment }),
+  );
+}
+
 /**
  * Format transaction result for output.
  */
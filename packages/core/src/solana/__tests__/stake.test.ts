@@ -22,6 +22,12 @@ import {
   depositAccounts,
   withdrawAccounts,
   flushToInsuranceAccounts,
+  STAKE_POOL_SIZE,
+  decodeStakePool,
+  stakePoolValue,
+  stakeSharePriceE6,
+  computeStakePoolMetrics,
+  splitStakeTrancheFees,
 } from '../stake.js';
 const slab = Keypair.generate().publicKey;
 const user = Keypair.generate().publicKey;
@@ -311,3 +317,95 @@ describe('Account builders', () => {
     expect(accounts[4].isWritable).toBe(true);
   });
 });
+
+function stakePoolBuffer(f: {
+  deposited?: bigint; lpSupply?: bigint; flushed?: bigint; returned?: bigint; withdrawn?: bigint;
+  feesEarned?: bigint; poolMode?: number;
+  hwmTvl?: bigint; hwmFloorBps?: number;
+  juniorBalance?: bigint; juniorLp?: bigint; juniorFeeMultBps?: number;
+}): Buffer {
+  const buf = Buffer.alloc(STAKE_POOL_SIZE);
+  buf[0] = 1;
+  slab.toBuffer().copy(buf, 8);
+  buf.writeBigUInt64LE(f.deposited ?? 0n, 168);
+  buf.writeBigUInt64LE(f.lpSupply ?? 0n, 176);
+  buf.writeBigUInt64LE(f.flushed ?? 0n, 200);
+  buf.writeBigUInt64LE(f.returned ?? 0n, 208);
+  buf.writeBigUInt64LE(f.withdrawn ?? 0n, 216);
+  buf.writeBigUInt64LE(f.feesEarned ?? 0n, 256);
+  buf[280] = f.poolMode ?? 0;
+  const reserved = 288;
+  if (f.hwmTvl !== undefined) {
+    buf[reserved + 9] = 1;
+    buf.writeBigUInt64LE(f.hwmTvl, reserved + 10);
+    buf.writeUInt16LE(f.hwmFloorBps ?? 0, reserved + 26);
+  }
+  if (f.juniorBalance !== undefined) {
+    buf[reserved + 32] = 1;
+    buf.writeBigUInt64LE(f.juniorBalance, reserved + 33);
+    buf.writeBigUInt64LE(f.juniorLp ?? 0n, reserved + 41);
+    buf.writeUInt16LE(f.juniorFeeMultBps ?? 0, reserved + 49);
+  }
+  return buf;
+}
+
+describe('Pool analytics', () => {
+  it('decodes the fields the analytics read', () => {
+    const pool = decodeStakePool(stakePoolBuffer({
+      deposited: 1_000n, lpSupply: 900n, flushed: 100n, returned: 40n, withdrawn: 50n,
+      hwmTvl: 2_000n, hwmFloorBps: 5_000, juniorBalance: 300n, juniorLp: 250n, juniorFeeMultBps: 20_000,
+    }));
+
+    expect(pool.slab.equals(slab)).toBe(true);
+    expect(pool.totalFlushed).toBe(100n);
+    expect(pool.totalReturned).toBe(40n);
+    expect(pool.epochHighWaterTvl).toBe(2_000n);
+    expect(pool.trancheEnabled).toBe(true);
+    expect(pool.juniorTotalLp).toBe(250n);
+    expect(pool.juniorFeeMultBps).toBe(20_000);
+  });
+
+  it('values the pool net of flows, adding fees only in trading mode', () => {
+    const base = { deposited: 1_000n, withdrawn: 100n, flushed: 200n, returned: 50n, feesEarned: 30n };
+    expect(stakePoolValue(decodeStakePool(stakePoolBuffer(base)))).toBe(750n);
+    expect(stakePoolValue(decodeStakePool(stakePoolBuffer({ ...base, poolMode: 1 })))).toBe(780n);
+    expect(stakePoolValue(decodeStakePool(stakePoolBuffer({ withdrawn: 10n })))).toBe(0n);
+  });
+
+  it('prices LP tokens at 1:1 until any are minted', () => {
+    expect(stakeSharePriceE6(0n, 0n)).toBe(1_000_000n);
+    expect(stakeSharePriceE6(1_050n, 1_000n)).toBe(1_050_000n);
+  });
+
+  it('splits senior and junior tranches out of the pool totals', () => {
+    const m = computeStakePoolMetrics(decodeStakePool(stakePoolBuffer({
+      deposited: 1_200n, lpSupply: 1_000n, juniorBalance: 400n, juniorLp: 250n,
+    })));
+
+    expect(m.sharePriceE6).toBe(1_200_000n);
+    expect(m.junior).toEqual({ value: 400n, lpSupply: 250n, sharePriceE6: 1_600_000n });
+    expect(m.senior).toEqual({ value: 800n, lpSupply: 750n, sharePriceE6: 1_066_666n });
+  });
+
+  it('treats the whole pool as senior without tranches', () => {
+    const m = computeStakePoolMetrics(decodeStakePool(stakePoolBuffer({ deposited: 500n, lpSupply: 500n })));
+    expect(m.junior).toBeNull();
+    expect(m.senior).toEqual({ value: 500n, lpSupply: 500n, sharePriceE6: 1_000_000n });
+    expect(m.hwmFloor).toBeNull();
+    expect(m.hwmBreached).toBe(false);
+  });
+
+  it('flags a pool below its HWM floor', () => {
+    const pool = { deposited: 1_000n, lpSupply: 1_000n, hwmTvl: 2_000n, hwmFloorBps: 5_000 };
+    expect(computeStakePoolMetrics(decodeStakePool(stakePoolBuffer(pool)))).toMatchObject({ hwmFloor: 1_000n, hwmBreached: false });
+    const flushed = computeStakePoolMetrics(decodeStakePool(stakePoolBuffer({ ...pool, flushed: 1n })));
+    expect(flushed.hwmBreached).toBe(true);
+  });
+
+  it('weights junior fees by juniorFeeMultBps', () => {
+    // Equal capital, junior at 2x: junior earns 2/3
+    expect(splitStakeTrancheFees(300n, 1_000n, 1_000n, 20_000)).toEqual({ senior: 100n, junior: 200n });
+    expect(splitStakeTrancheFees(300n, 1_000n, 0n, 20_000)).toEqual({ senior: 300n, junior: 0n });
+    expect(splitStakeTrancheFees(300n, 0n, 0n, 20_000)).toEqual({ senior: 300n, junior: 0n });
+  });
+});
@@ -3,17 +3,26 @@ import {
   PUMPSWAP_PROGRAM_ID,
   RAYDIUM_CLMM_PROGRAM_ID,
   METEORA_DLMM_PROGRAM_ID,
+  ORCA_WHIRLPOOL_PROGRAM_ID,
+  RAYDIUM_CPMM_PROGRAM_ID,
+  RAYDIUM_AMM_V4_PROGRAM_ID,
 } from "./pda.js";
 
-export type DexType = "pumpswap" | "raydium-clmm" | "meteora-dlmm";
+export type DexType =
+  | "pumpswap"
+  | "raydium-clmm"
+  | "meteora-dlmm"
+  | "orca-whirlpool"
+  | "raydium-cpmm"
+  | "raydium-amm-v4";
 
 export interface DexPoolInfo {
   dexType: DexType;
   poolAddress: PublicKey;
   baseMint: PublicKey;
   quoteMint: PublicKey;
-  baseVault?: PublicKey;  // PumpSwap only
-  quoteVault?: PublicKey; // PumpSwap only
+  baseVault?: PublicKey;  // Reserve-priced pools only (see dexRequiresVaultData)
+  quoteVault?: PublicKey; // Reserve-priced pools only (see dexRequiresVaultData)
 }
 
 /**
@@ -26,21 +35,44 @@ export interface DexPoolInfo {
  * - PumpSwap (constant-product AMM)
  * - Raydium CLMM (concentrated liquidity)
  * - Meteora DLMM (discretized liquidity)
+ * - Orca Whirlpool (concentrated liquidity)
+ * - Raydium CPMM (constant-product AMM)
+ * - Raydium AMM v4 (legacy constant-product AMM)
  */
 export function detectDexType(ownerProgramId: PublicKey): DexType | null {
   if (ownerProgramId.equals(PUMPSWAP_PROGRAM_ID)) return "pumpswap";
   if (ownerProgramId.equals(RAYDIUM_CLMM_PROGRAM_ID)) return "raydium-clmm";
   if (ownerProgramId.equals(METEORA_DLMM_PROGRAM_ID)) return "meteora-dlmm";
+  if (ownerProgramId.equals(ORCA_WHIRLPOOL_PROGRAM_ID)) return "orca-whirlpool";
+  if (ownerProgramId.equals(RAYDIUM_CPMM_PROGRAM_ID)) return "raydium-cpmm";
+  if (ownerProgramId.equals(RAYDIUM_AMM_V4_PROGRAM_ID)) return "raydium-amm-v4";
   return null;
 }
 
+/**
+ * Whether {@link computeDexSpotPriceE6} needs the pool's vault token accounts.
+ * True for constant-product pools, which are priced from their reserves.
+ */
+export function dexRequiresVaultData(dexType: DexType): boolean {
+  return dexType === "pumpswap" || dexType === "raydium-cpmm" || dexType === "raydium-amm-v4";
+}
+
+/**
+ * Whether {@link computeDexSpotPriceE6} already adjusts for mint decimals.
+ * Pools that don't store decimals (PumpSwap, Meteora DLMM, Orca Whirlpool)
+ * return a raw atom ratio; multiply by 10^(baseDecimals - quoteDecimals).
+ */
+export function dexSpotPriceIsDecimalAdjusted(dexType: DexType): boolean {
+  return dexType === "raydium-clmm" || dexType === "raydium-cpmm" || dexType === "raydium-amm-v4";
+}
+
 /**
  * Parse a DEX pool account into a {@link DexPoolInfo} struct.
  *
- * @param dexType - The type of DEX (pumpswap, raydium-clmm, or meteora-dlmm)
+ * @param dexType - The type of DEX
  * @param poolAddress - The on-chain address of the pool account
  * @param data - Raw account data bytes
- * @returns Parsed pool info including mints and (for PumpSwap) vault addresses
+ * @returns Parsed pool info including mints and (for reserve-priced pools) vault addresses
  * @throws Error if data is too short for the given DEX type
  */
 export function parseDexPool(
@@ -55,6 +87,12 @@ export function parseDexPool(
       return parseRaydiumClmmPool(poolAddress, data);
     case "meteora-dlmm":
       return parseMeteoraPool(poolAddress, data);
+    case "orca-whirlpool":
+      return parseOrcaWhirlpool(poolAddress, data);
+    case "raydium-cpmm":
+      return parseRaydiumCpmmPool(poolAddress, data);
+    case "raydium-amm-v4":
+      return parseRaydiumAmmV4Pool(poolAddress, data);
   }
 }
 
@@ -67,8 +105,8 @@ export function parseDexPool(
  *
  * @param dexType - The type of DEX
  * @param data - Raw pool account data
- * @param vaultData - For PumpSwap only: base and quote vault account data
- * @returns Price in e6 format (quote per base token)
+ * @param vaultData - For reserve-priced pools (see {@link dexRequiresVaultData}): base and quote vault account data
+ * @returns Price in e6 format (quote per base token; see {@link dexSpotPriceIsDecimalAdjusted})
  * @throws Error if data is too short or computation fails
  */
 export function computeDexSpotPriceE6(
@@ -84,9 +122,91 @@ export function computeDexSpotPriceE6(
       return computeRaydiumClmmPriceE6(data);
     case "meteora-dlmm":
       return computeMeteoraDlmmPriceE6(data);
+    case "orca-whirlpool":
+      return computeOrcaWhirlpoolPriceE6(data);
+    case "raydium-cpmm":
+      if (!vaultData) throw new Error("Raydium CPMM requires vaultData (token_0 and token_1 vault accounts)");
+      return computeRaydiumCpmmPriceE6(data, vaultData);
+    case "raydium-amm-v4":
+      if (!vaultData) throw new Error("Raydium AMM v4 requires vaultData (base and quote vault accounts)");
+      return computeRaydiumAmmV4PriceE6(data, vaultData);
+  }
+}
+
+/**
+ * Reserves backing the current price, in token atoms.
+ *
+ * - `"reserves"`: constant-product pools — swappable vault balances.
+ * - `"active-liquidity"`: concentrated pools — the virtual reserves of the
+ *   active liquidity at the current price (`L / sqrtP` and `L * sqrtP`).
+ *   Valid only until the price crosses out of the current tick range.
+ */
+export interface DexPoolDepth {
+  model: "reserves" | "active-liquidity";
+  baseReserve: bigint;
+  quoteReserve: bigint;
+}
+
+/**
+ * Read the reserves behind a pool's spot price, for estimating how much
+ * capital it takes to move it (see `oracle/manipulation-cost.ts`).
+ *
+ * @param vaultData - Required when {@link dexRequiresVaultData} is true
+ * @returns Depth in token atoms, or null for Meteora DLMM (bin reserves are
+ *          held in separate bin-array accounts)
+ */
+export function computeDexPoolDepth(
+  dexType: DexType,
+  data: Uint8Array,
+  vaultData?: { base: Uint8Array; quote: Uint8Array },
+): DexPoolDepth | null {
+  if (dexRequiresVaultData(dexType) && !vaultData) {
+    throw new Error(`${dexType} depth requires vaultData (base and quote vault accounts)`);
+  }
+  switch (dexType) {
+    case "pumpswap": {
+      const baseReserve = readVaultAmount(vaultData!.base, "PumpSwap base");
+      const quoteReserve = readVaultAmount(vaultData!.quote, "PumpSwap quote");
+      return { model: "reserves", baseReserve, quoteReserve };
+    }
+    case "raydium-cpmm": {
+      const [baseReserve, quoteReserve] = raydiumCpmmReserves(data, vaultData!);
+      return { model: "reserves", baseReserve, quoteReserve };
+    }
+    case "raydium-amm-v4": {
+      const [baseReserve, quoteReserve] = raydiumAmmV4Reserves(data, vaultData!);
+      return { model: "reserves", baseReserve, quoteReserve };
+    }
+    case "raydium-clmm":
+      if (data.length < RAYDIUM_CLMM_MIN_LEN) {
+        throw new Error(`Raydium CLMM data too short: ${data.length} < ${RAYDIUM_CLMM_MIN_LEN}`);
+      }
+      return activeLiquidityDepth(data, 237, 253);
+    case "orca-whirlpool":
+      if (data.length < ORCA_WHIRLPOOL_MIN_LEN) {
+        throw new Error(`Orca Whirlpool data too short: ${data.length} < ${ORCA_WHIRLPOOL_MIN_LEN}`);
+      }
+      return activeLiquidityDepth(data, 49, 65);
+    case "meteora-dlmm":
+      return null;
   }
 }
 
+/** Virtual reserves of a Q64.64 concentrated pool: x = L * 2^64 / sqrtP, y = L * sqrtP / 2^64. */
+function activeLiquidityDepth(data: Uint8Array, liquidityOffset: number, sqrtPriceOffset: number): DexPoolDepth {
+  const dv = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  const liquidity = readU128LE(dv, liquidityOffset);
+  const sqrtPriceX64 = readU128LE(dv, sqrtPriceOffset);
+  if (liquidity === 0n || sqrtPriceX64 === 0n) {
+    return { model: "active-liquidity", baseReserve: 0n, quoteReserve: 0n };
+  }
+  return {
+    model: "active-liquidity",
+    baseReserve: (liquidity << 64n) / sqrtPriceX64,
+    quoteReserve: (liquidity * sqrtPriceX64) >> 64n,
+  };
+}
+
 // ============================================================================
 // PumpSwap
 // ============================================================================
@@ -275,10 +395,194 @@ function computeMeteoraDlmmPriceE6(data: Uint8Array): bigint {
   }
 }
 
+// ============================================================================
+// Orca Whirlpool
+// ============================================================================
+
+const ORCA_WHIRLPOOL_MIN_LEN = 245; // through token_vault_b (213 + 32)
+
+/**
+ * Parse an Orca Whirlpool account.
+ *
+ * Layout (after the 8-byte discriminator): whirlpools_config(32) bump(1)
+ * tick_spacing(2) tick_spacing_seed(2) fee_rate(2) protocol_fee_rate(2)
+ * liquidity(16) sqrt_price(16) tick_current_index(4) protocol_fee_owed_a(8)
+ * protocol_fee_owed_b(8) token_mint_a(32) token_vault_a(32) fee_growth_a(16)
+ * token_mint_b(32) token_vault_b(32) ...
+ *
+ * Vaults are not returned: the price comes from sqrt_price alone.
+ * @internal
+ */
+function parseOrcaWhirlpool(poolAddress: PublicKey, data: Uint8Array): DexPoolInfo {
+  if (data.length < ORCA_WHIRLPOOL_MIN_LEN) {
+    throw new Error(`Orca Whirlpool data too short: ${data.length} < ${ORCA_WHIRLPOOL_MIN_LEN}`);
+  }
+  return {
+    dexType: "orca-whirlpool",
+    poolAddress,
+    baseMint: new PublicKey(data.slice(101, 133)),
+    quoteMint: new PublicKey(data.slice(181, 213)),
+  };
+}
+
+/**
+ * Compute Orca Whirlpool spot price from sqrt_price (Q64.64).
+ *
+ * Formula: `price_e6 = sqrt^2 * 1e6 / 2^128` — token B atoms per token A atom.
+ * The account holds no decimals, so the caller applies 10^(decA - decB).
+ * Scales by 1e6 before shifting, as for Raydium CLMM, so micro-priced pools
+ * don't round to zero.
+ *
+ * @internal
+ */
+function computeOrcaWhirlpoolPriceE6(data: Uint8Array): bigint {
+  if (data.length < ORCA_WHIRLPOOL_MIN_LEN) {
+    throw new Error(`Orca Whirlpool data too short: ${data.length} < ${ORCA_WHIRLPOOL_MIN_LEN}`);
+  }
+  const dv = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  const sqrtPriceX64 = readU128LE(dv, 65);
+  if (sqrtPriceX64 === 0n) return 0n;
+  const term = (sqrtPriceX64 * 1_000_000n) >> 64n;
+  return (term * sqrtPriceX64) >> 64n;
+}
+
+// ============================================================================
+// Raydium CPMM
+// ============================================================================
+
+const RAYDIUM_CPMM_MIN_LEN = 373; // through fund_fees_token_1 (365 + 8)
+
+/**
+ * Parse a Raydium CPMM pool account.
+ *
+ * Layout (after the 8-byte discriminator): amm_config(32) pool_creator(32)
+ * token_0_vault(32) token_1_vault(32) lp_mint(32) token_0_mint(32)
+ * token_1_mint(32) token_0_program(32) token_1_program(32) observation_key(32)
+ * auth_bump(1) status(1) lp_mint_decimals(1) mint_0_decimals(1)
+ * mint_1_decimals(1) lp_supply(8) protocol_fees_token_0(8)
+ * protocol_fees_token_1(8) fund_fees_token_0(8) fund_fees_token_1(8) ...
+ * @internal
+ */
+function parseRaydiumCpmmPool(poolAddress: PublicKey, data: Uint8Array): DexPoolInfo {
+  if (data.length < RAYDIUM_CPMM_MIN_LEN) {
+    throw new Error(`Raydium CPMM pool data too short: ${data.length} < ${RAYDIUM_CPMM_MIN_LEN}`);
+  }
+  return {
+    dexType: "raydium-cpmm",
+    poolAddress,
+    baseMint: new PublicKey(data.slice(168, 200)),
+    quoteMint: new PublicKey(data.slice(200, 232)),
+    baseVault: new PublicKey(data.slice(72, 104)),
+    quoteVault: new PublicKey(data.slice(104, 136)),
+  };
+}
+
+/**
+ * Compute Raydium CPMM price from vault balances net of accrued protocol and
+ * fund fees (which sit in the vaults but are not swappable liquidity),
+ * adjusted by the mint decimals stored in the pool.
+ * @internal
+ */
+function computeRaydiumCpmmPriceE6(
+  data: Uint8Array,
+  vaultData: { base: Uint8Array; quote: Uint8Array },
+): bigint {
+  if (data.length < RAYDIUM_CPMM_MIN_LEN) {
+    throw new Error(`Raydium CPMM data too short: ${data.length} < ${RAYDIUM_CPMM_MIN_LEN}`);
+  }
+  const [reserve0, reserve1] = raydiumCpmmReserves(data, vaultData);
+  return reserveRatioE6(reserve0, reserve1, data[331], data[332]);
+}
+
+/** Swappable CPMM reserves: vault balances minus accrued protocol and fund fees. */
+function raydiumCpmmReserves(data: Uint8Array, vaultData: { base: Uint8Array; quote: Uint8Array }): [bigint, bigint] {
+  const dv = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  const fees0 = readU64LE(dv, 341) + readU64LE(dv, 357);
+  const fees1 = readU64LE(dv, 349) + readU64LE(dv, 365);
+  return [
+    readVaultAmount(vaultData.base, "Raydium CPMM token_0") - fees0,
+    readVaultAmount(vaultData.quote, "Raydium CPMM token_1") - fees1,
+  ];
+}
+
+// ============================================================================
+// Raydium AMM v4
+// ============================================================================
+
+const RAYDIUM_AMM_V4_MIN_LEN = 464; // through quote_mint (432 + 32)
+
+/**
+ * Parse a Raydium AMM v4 (AmmInfo) account.
+ *
+ * AmmInfo starts with 32 u64 fields (no discriminator): base_decimal at 32,
+ * quote_decimal at 40, base_need_take_pnl at 192, quote_need_take_pnl at 200;
+ * then swap accounting, base_vault at 336, quote_vault at 368, base_mint at
+ * 400 and quote_mint at 432.
+ * @internal
+ */
+function parseRaydiumAmmV4Pool(poolAddress: PublicKey, data: Uint8Array): DexPoolInfo {
+  if (data.length < RAYDIUM_AMM_V4_MIN_LEN) {
+    throw new Error(`Raydium AMM v4 pool data too short: ${data.length} < ${RAYDIUM_AMM_V4_MIN_LEN}`);
+  }
+  return {
+    dexType: "raydium-amm-v4",
+    poolAddress,
+    baseMint: new PublicKey(data.slice(400, 432)),
+    quoteMint: new PublicKey(data.slice(432, 464)),
+    baseVault: new PublicKey(data.slice(336, 368)),
+    quoteVault: new PublicKey(data.slice(368, 400)),
+  };
+}
+
+/**
+ * Compute Raydium AMM v4 price from vault balances net of PnL owed to the
+ * protocol (need_take_pnl), adjusted by the decimals stored in AmmInfo.
+ * @internal
+ */
+function computeRaydiumAmmV4PriceE6(
+  data: Uint8Array,
+  vaultData: { base: Uint8Array; quote: Uint8Array },
+): bigint {
+  if (data.length < RAYDIUM_AMM_V4_MIN_LEN) {
+    throw new Error(`Raydium AMM v4 data too short: ${data.length} < ${RAYDIUM_AMM_V4_MIN_LEN}`);
+  }
+  const dv = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  const baseDecimals = Number(readU64LE(dv, 32));
+  const quoteDecimals = Number(readU64LE(dv, 40));
+
+  const [baseReserve, quoteReserve] = raydiumAmmV4Reserves(data, vaultData);
+  return reserveRatioE6(baseReserve, quoteReserve, baseDecimals, quoteDecimals);
+}
+
+/** AMM v4 reserves: vault balances minus PnL owed to the protocol. */
+function raydiumAmmV4Reserves(data: Uint8Array, vaultData: { base: Uint8Array; quote: Uint8Array }): [bigint, bigint] {
+  const dv = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  return [
+    readVaultAmount(vaultData.base, "Raydium AMM v4 base") - readU64LE(dv, 192),
+    readVaultAmount(vaultData.quote, "Raydium AMM v4 quote") - readU64LE(dv, 200),
+  ];
+}
+
 // ============================================================================
 // Helpers
 // ============================================================================
 
+/** SPL token account amount (u64 at offset 64). */
+function readVaultAmount(vault: Uint8Array, label: string): bigint {
+  if (vault.length < SPL_TOKEN_AMOUNT_MIN_LEN) {
+    throw new Error(`${label} vault data too short: ${vault.length} < ${SPL_TOKEN_AMOUNT_MIN_LEN}`);
+  }
+  return readU64LE(new DataView(vault.buffer, vault.byteOffset, vault.byteLength), 64);
+}
+
+/** quote/base reserve ratio in e6, decimal-adjusted. Zero for empty or over-withdrawn reserves. */
+function reserveRatioE6(baseReserve: bigint, quoteReserve: bigint, baseDecimals: number, quoteDecimals: number): bigint {
+  if (baseReserve <= 0n || quoteReserve <= 0n) return 0n;
+  const diff = baseDecimals - quoteDecimals;
+  if (diff >= 0) return (quoteReserve * 1_000_000n * 10n ** BigInt(diff)) / baseReserve;
+  return (quoteReserve * 1_000_000n) / (baseReserve * 10n ** BigInt(-diff));
+}
+
 /** Read a little-endian u64 from a DataView. */
 function readU64LE(dv: DataView, offset: number): bigint {
   const lo = BigInt(dv.getUint32(offset, true));
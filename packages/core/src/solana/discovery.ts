@@ -3,18 +3,19 @@ import {
   parseHeader,
   parseConfig,
   parseParams,
-  detectSlabLayout,
+  parseEngine,
   type SlabHeader,
   type MarketConfig,
   type EngineState,
   type RiskParams,
-  type SlabLayout,
 } from "./slab.js";
-
-/** V1 bitmap offset within engine struct (updated for PERC-120/121/122 struct changes) */
-const ENGINE_BITMAP_OFF = 656; // Updated for PERC-299 (608 + 24 emergency OI fields)
-/** V0 bitmap offset within engine struct (deployed devnet program) */
-const ENGINE_BITMAP_OFF_V0 = 320;
+import {
+  SLAB_LAYOUT_V0,
+  SLAB_LAYOUT_V1,
+  knownSlabSizes,
+  resolveSlabLayout,
+  slabLayoutSize,
+} from "./layouts.js";
 
 /**
  * A discovered Percolator market from on-chain program accounts.
@@ -48,18 +49,18 @@ const MAGIC_BYTES = new Uint8Array([0x54, 0x41, 0x4c, 0x4f, 0x43, 0x52, 0x45, 0x
  *       RiskEngine grew by 32 bytes (PERC-298: long_oi + short_oi) + 24 (PERC-299: emergency OI).
  *       Values below must be verified against BPF build before deployment.
  */
-// V1 sizes (deployed devnet program: HEADER=104, CONFIG=536, ENGINE_OFF=640, ACCOUNT_SIZE=248)
+// V1 sizes (HEADER=104, CONFIG=536, ENGINE_OFF=640, ACCOUNT_SIZE=248), derived from the layout registry
 export const SLAB_TIERS = {
-  small:  { maxAccounts: 256,  dataSize: 65_352,    label: "Small",  description: "256 slots · ~0.45 SOL" },
-  medium: { maxAccounts: 1024, dataSize: 257_448,   label: "Medium", description: "1,024 slots · ~1.79 SOL" },
-  large:  { maxAccounts: 4096, dataSize: 1_025_832, label: "Large",  description: "4,096 slots · ~7.14 SOL" },
+  small:  { maxAccounts: 256,  dataSize: slabLayoutSize(SLAB_LAYOUT_V1, 256),  label: "Small",  description: "256 slots · ~0.45 SOL" },
+  medium: { maxAccounts: 1024, dataSize: slabLayoutSize(SLAB_LAYOUT_V1, 1024), label: "Medium", description: "1,024 slots · ~1.79 SOL" },
+  large:  { maxAccounts: 4096, dataSize: slabLayoutSize(SLAB_LAYOUT_V1, 4096), label: "Large",  description: "4,096 slots · ~7.14 SOL" },
 } as const;
 
 /** @deprecated V0 slab sizes — kept for backward compatibility with old on-chain slabs */
 export const SLAB_TIERS_V0 = {
-  small:  { maxAccounts: 256,  dataSize: 62_808,    label: "Small",  description: "256 slots · ~0.44 SOL" },
-  medium: { maxAccounts: 1024, dataSize: 248_760,   label: "Medium", description: "1,024 slots · ~1.73 SOL" },
-  large:  { maxAccounts: 4096, dataSize: 992_568,   label: "Large",  description: "4,096 slots · ~6.90 SOL" },
+  small:  { maxAccounts: 256,  dataSize: slabLayoutSize(SLAB_LAYOUT_V0, 256),  label: "Small",  description: "256 slots · ~0.44 SOL" },
+  medium: { maxAccounts: 1024, dataSize: slabLayoutSize(SLAB_LAYOUT_V0, 1024), label: "Medium", description: "1,024 slots · ~1.73 SOL" },
+  large:  { maxAccounts: 4096, dataSize: slabLayoutSize(SLAB_LAYOUT_V0, 4096), label: "Large",  description: "4,096 slots · ~6.90 SOL" },
 } as const;
 
 /** @deprecated Alias — use SLAB_TIERS (already V1) */
@@ -82,28 +83,12 @@ export type SlabTierKey = keyof typeof SLAB_TIERS;
  */
 export function slabDataSize(maxAccounts: number): number {
   // V0 layout (deployed devnet): ENGINE_OFF=480, ENGINE_BITMAP_OFF=320, ACCOUNT_SIZE=240
-  const ENGINE_OFF_V0 = 480;
-  const ENGINE_BITMAP_OFF_V0 = 320;
-  const ACCOUNT_SIZE_V0 = 240;
-  const bitmapBytes = Math.ceil(maxAccounts / 64) * 8;
-  const postBitmap = 18;
-  const nextFreeBytes = maxAccounts * 2;
-  const preAccountsLen = ENGINE_BITMAP_OFF_V0 + bitmapBytes + postBitmap + nextFreeBytes;
-  const accountsOff = Math.ceil(preAccountsLen / 8) * 8;
-  return ENGINE_OFF_V0 + accountsOff + maxAccounts * ACCOUNT_SIZE_V0;
+  return slabLayoutSize(SLAB_LAYOUT_V0, maxAccounts);
 }
 
 /** Calculate slab data size for V1 layout (future program upgrade). */
 export function slabDataSizeV1(maxAccounts: number): number {
-  const ENGINE_OFF_V1 = 640;
-  const ENGINE_BITMAP_OFF_V1 = 656;
-  const ACCOUNT_SIZE_V1 = 248;
-  const bitmapBytes = Math.ceil(maxAccounts / 64) * 8;
-  const postBitmap = 18;
-  const nextFreeBytes = maxAccounts * 2;
-  const preAccountsLen = ENGINE_BITMAP_OFF_V1 + bitmapBytes + postBitmap + nextFreeBytes;
-  const accountsOff = Math.ceil(preAccountsLen / 8) * 8;
-  return ENGINE_OFF_V1 + accountsOff + maxAccounts * ACCOUNT_SIZE_V1;
+  return slabLayoutSize(SLAB_LAYOUT_V1, maxAccounts);
 }
 
 /**
@@ -118,172 +103,12 @@ export function validateSlabTierMatch(dataSize: number, programSlabLen: number):
   return dataSize === programSlabLen;
 }
 
-/** All known slab data sizes for discovery (V0 + V1 tiers) */
-const ALL_SLAB_SIZES = [
-  ...Object.values(SLAB_TIERS).map(t => t.dataSize),
-  ...Object.values(SLAB_TIERS_V0).map(t => t.dataSize),
-];
-
 /** Legacy constant for backward compat */
 const SLAB_DATA_SIZE = SLAB_TIERS.large.dataSize;
 
 /** We need header(104) + config(536) + engine up to nextAccountId (~1200). Total ~1840. Use 1940 for margin. */
 const HEADER_SLICE_LENGTH = 1940;
 
-function dv(data: Uint8Array): DataView {
-  return new DataView(data.buffer, data.byteOffset, data.byteLength);
-}
-function readU16LE(data: Uint8Array, off: number): number {
-  return dv(data).getUint16(off, true);
-}
-function readU64LE(data: Uint8Array, off: number): bigint {
-  return dv(data).getBigUint64(off, true);
-}
-function readI64LE(data: Uint8Array, off: number): bigint {
-  return dv(data).getBigInt64(off, true);
-}
-function readU128LE(buf: Uint8Array, offset: number): bigint {
-  const lo = readU64LE(buf, offset);
-  const hi = readU64LE(buf, offset + 8);
-  return (hi << 64n) | lo;
-}
-function readI128LE(buf: Uint8Array, offset: number): bigint {
-  const lo = readU64LE(buf, offset);
-  const hi = readU64LE(buf, offset + 8);
-  const unsigned = (hi << 64n) | lo;
-  const SIGN_BIT = 1n << 127n;
-  if (unsigned >= SIGN_BIT) return unsigned - (1n << 128n);
-  return unsigned;
-}
-
-/**
- * Light engine parser that works with partial slab data (dataSlice, no accounts array).
- * Requires a layout hint (from detectSlabLayout on the actual slab size) to use correct offsets.
- *
- * @param data        — partial slab slice (HEADER_SLICE_LENGTH bytes)
- * @param layout      — SlabLayout from detectSlabLayout(actualDataSize). If null, falls back to V0.
- * @param maxAccounts — tier's max accounts for bitmap offset calculation
- */
-function parseEngineLight(
-  data: Uint8Array,
-  layout: SlabLayout | null,
-  maxAccounts: number = 4096,
-): EngineState {
-  const isV0 = !layout || layout.version === 0;
-  const base = layout ? layout.engineOff : 480; // V0=480, V1=640
-  const bitmapOff = layout ? layout.engineBitmapOff : ENGINE_BITMAP_OFF_V0;
-
-  const minLen = base + bitmapOff;
-  if (data.length < minLen) {
-    throw new Error(`Slab data too short for engine light parse: ${data.length} < ${minLen}`);
-  }
-
-  // Compute tier-dependent offsets for numUsedAccounts and nextAccountId
-  const bitmapWords = Math.ceil(maxAccounts / 64);
-  const numUsedOff = bitmapOff + bitmapWords * 8; // u16 right after bitmap
-  const nextAccountIdOff = Math.ceil((numUsedOff + 2) / 8) * 8; // u64, 8-byte aligned
-
-  const canReadNumUsed = data.length >= base + numUsedOff + 2;
-  const canReadNextId = data.length >= base + nextAccountIdOff + 8;
-
-  if (isV0) {
-    // V0 engine struct (deployed devnet): ENGINE_OFF=480
-    // vault(0,16) + insurance(16,32) + params(48,56) + currentSlot(104,8)
-    // + fundingIndex(112,16) + lastFundingSlot(128,8) + fundingRateBps(136,8)
-    // + lastCrankSlot(144,8) + maxCrankStaleness(152,8) + totalOI(160,16)
-    // + cTot(176,16) + pnlPosTot(192,16) + liqCursor(208,2) + gcCursor(210,2)
-    // + lastSweepStart(216,8) + lastSweepComplete(224,8) + crankCursor(232,2) + sweepStartIdx(234,2)
-    // + lifetimeLiquidations(240,8) + lifetimeForceCloses(248,8)
-    // + netLpPos(256,16) + lpSumAbs(272,16) + lpMaxAbs(288,16) + bitmap(320)
-    return {
-      vault: readU128LE(data, base + 0),
-      insuranceFund: {
-        balance: readU128LE(data, base + 16),
-        feeRevenue: readU128LE(data, base + 32),
-        isolatedBalance: 0n,
-        isolationBps: 0,
-      },
-      currentSlot: readU64LE(data, base + 104),
-      fundingIndexQpbE6: readI128LE(data, base + 112),
-      lastFundingSlot: readU64LE(data, base + 128),
-      fundingRateBpsPerSlotLast: readI64LE(data, base + 136),
-      lastCrankSlot: readU64LE(data, base + 144),
-      maxCrankStalenessSlots: readU64LE(data, base + 152),
-      totalOpenInterest: readU128LE(data, base + 160),
-      longOi: 0n,
-      shortOi: 0n,
-      cTot: readU128LE(data, base + 176),
-      pnlPosTot: readU128LE(data, base + 192),
-      liqCursor: readU16LE(data, base + 208),
-      gcCursor: readU16LE(data, base + 210),
-      lastSweepStartSlot: readU64LE(data, base + 216),
-      lastSweepCompleteSlot: readU64LE(data, base + 224),
-      crankCursor: readU16LE(data, base + 232),
-      sweepStartIdx: readU16LE(data, base + 234),
-      lifetimeLiquidations: readU64LE(data, base + 240),
-      lifetimeForceCloses: readU64LE(data, base + 248),
-      netLpPos: readI128LE(data, base + 256),
-      lpSumAbs: readU128LE(data, base + 272),
-      lpMaxAbs: readU128LE(data, base + 288),
-      lpMaxAbsSweep: 0n,
-      emergencyOiMode: false,
-      emergencyStartSlot: 0n,
-      lastBreakerSlot: 0n,
-      markPriceE6: 0n, // V0 engine has no mark_price field
-      numUsedAccounts: canReadNumUsed ? readU16LE(data, base + numUsedOff) : 0,
-      nextAccountId: canReadNextId ? readU64LE(data, base + nextAccountIdOff) : 0n,
-    };
-  }
-
-  // V1 engine struct (future upgrade / V1 slabs): ENGINE_OFF=640
-  // vault(0) + insurance(16,48) + params(64,288) + currentSlot(352) + fundingIndex(360,16)
-  // + lastFundingSlot(376) + fundingRateBps(384) + markPrice(392) + lastCrankSlot(400)
-  // + maxCrankStaleness(408) + totalOI(416,16) + longOi(432,16) + shortOi(448,16)
-  // + cTot(464,16) + pnlPosTot(480,16) + liqCursor(496,2) + gcCursor(498,2)
-  // + lastSweepStart(504) + lastSweepComplete(512) + crankCursor(520,2) + sweepStartIdx(522,2)
-  // + lifetimeLiquidations(528) + lifetimeForceCloses(536)
-  // + netLpPos(544,16) + lpSumAbs(560,16) + lpMaxAbs(576,16) + lpMaxAbsSweep(592,16)
-  // + emergencyOiMode(608,1+7pad) + emergencyStartSlot(616) + lastBreakerSlot(624) + bitmap(656)
-  return {
-    vault: readU128LE(data, base + 0),
-    insuranceFund: {
-      balance: readU128LE(data, base + 16),
-      feeRevenue: readU128LE(data, base + 32),
-      isolatedBalance: readU128LE(data, base + 48),
-      isolationBps: readU16LE(data, base + 64),
-    },
-    currentSlot: readU64LE(data, base + 352),
-    fundingIndexQpbE6: readI128LE(data, base + 360),
-    lastFundingSlot: readU64LE(data, base + 376),
-    fundingRateBpsPerSlotLast: readI64LE(data, base + 384),
-    lastCrankSlot: readU64LE(data, base + 400),
-    maxCrankStalenessSlots: readU64LE(data, base + 408),
-    totalOpenInterest: readU128LE(data, base + 416),
-    longOi: readU128LE(data, base + 432),
-    shortOi: readU128LE(data, base + 448),
-    cTot: readU128LE(data, base + 464),
-    pnlPosTot: readU128LE(data, base + 480),
-    liqCursor: readU16LE(data, base + 496),
-    gcCursor: readU16LE(data, base + 498),
-    lastSweepStartSlot: readU64LE(data, base + 504),
-    lastSweepCompleteSlot: readU64LE(data, base + 512),
-    crankCursor: readU16LE(data, base + 520),
-    sweepStartIdx: readU16LE(data, base + 522),
-    lifetimeLiquidations: readU64LE(data, base + 528),
-    lifetimeForceCloses: readU64LE(data, base + 536),
-    netLpPos: readI128LE(data, base + 544),
-    lpSumAbs: readU128LE(data, base + 560),
-    lpMaxAbs: readU128LE(data, base + 576),
-    lpMaxAbsSweep: readU128LE(data, base + 592),
-    emergencyOiMode: data[base + 608] !== 0,
-    emergencyStartSlot: readU64LE(data, base + 616),
-    lastBreakerSlot: readU64LE(data, base + 624),
-    markPriceE6: readU64LE(data, base + 392),
-    numUsedAccounts: canReadNumUsed ? readU16LE(data, base + numUsedOff) : 0,
-    nextAccountId: canReadNextId ? readU64LE(data, base + nextAccountIdOff) : 0n,
-  };
-}
-
 /**
  * Discover all Percolator markets owned by the given program.
  * Uses getProgramAccounts with dataSize filter + dataSlice to download only ~1400 bytes per slab.
@@ -292,13 +117,10 @@ export async function discoverMarkets(
   connection: Connection,
   programId: PublicKey,
 ): Promise<DiscoveredMarket[]> {
-  // Query all known slab sizes in parallel — both V0 (deployed devnet) and V1 (upgraded) tiers.
-  // We track the actual dataSize per entry so detectSlabLayout can determine the correct layout,
+  // Query every size in the layout registry in parallel (all versions and tiers).
+  // We track the actual dataSize per entry so the layout can be resolved from it,
   // and pass that layout to all parse functions (avoids wrong-version offsets on partial slices).
-  const ALL_TIERS = [
-    ...Object.values(SLAB_TIERS),
-    ...Object.values(SLAB_TIERS_V0),
-  ];
+  const ALL_TIERS = knownSlabSizes();
   type RawEntry = { pubkey: PublicKey; account: { data: Buffer | Uint8Array }; maxAccounts: number; dataSize: number };
   let rawAccounts: RawEntry[] = [];
   try {
@@ -363,7 +185,7 @@ export async function discoverMarkets(
 
   const markets: DiscoveredMarket[] = [];
 
-  for (const { pubkey, account, maxAccounts, dataSize } of accounts) {
+  for (const { pubkey, account, dataSize } of accounts) {
     const data = new Uint8Array(account.data);
 
     let valid = true;
@@ -375,14 +197,21 @@ export async function discoverMarkets(
     }
     if (!valid) continue;
 
-    // Detect layout from actual slab size — not slice length — so parse functions
-    // get correct V0/V1 offsets even when working on the partial HEADER_SLICE_LENGTH slice.
-    const layout = detectSlabLayout(dataSize);
+    // Resolve layout from actual slab size — not slice length — so parse functions
+    // get correct offsets even when working on the partial HEADER_SLICE_LENGTH slice.
+    const { layout, diagnostic } = resolveSlabLayout(data, dataSize);
+    if (!layout) {
+      console.warn(`[discoverMarkets] Skipping ${pubkey.toBase58()}: ${diagnostic.message}`);
+      continue;
+    }
 
+    // Same offsets as a full parse. (The inline V1 reader this replaced had
+    // params at engine+64 and currentSlot at +352, ignoring the 8-byte wider
+    // insurance fund; the registry's +72/+360 match the program.)
     try {
-      const header = parseHeader(data);
+      const header = parseHeader(data, layout);
       const config = parseConfig(data, layout);
-      const engine = parseEngineLight(data, layout, maxAccounts);
+      const engine = parseEngine(data, layout);
       const params = parseParams(data, layout);
 
       markets.push({ slabAddress: pubkey, programId, header, config, engine, params });
@@ -1,4 +1,5 @@
 export * from "./slab.js";
+export * from "./layouts.js";
 export * from "./pda.js";
 export * from "./ata.js";
 export * from "./discovery.js";
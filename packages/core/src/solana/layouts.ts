@@ -0,0 +1,486 @@
+// =============================================================================
+// Slab Layout Registry
+// =============================================================================
+// Every on-chain slab layout the SDK understands is declared here once. The
+// parsers in slab.ts and discovery.ts never hardcode offsets — they ask the
+// registry for a SlabLayout and read through it.
+//
+// Adding a layout (e.g. V2 after a program upgrade) means declaring one more
+// SlabLayoutSpec and adding it to the registry below (or calling
+// registerSlabLayout at runtime); no parser changes are needed as long as the new layout only moves
+// or appends fields.
+//
+// Layouts are detected by data length alone, as in the Rust crate's
+// detect_slab_layout: every deployed program writes SlabHeader.version = 1
+// whatever its layout, so the header cannot tell them apart. No two
+// registered (layout, tier) pairs may share a size. When RiskParams are
+// available they are used to confirm the match, and anything that cannot be
+// confirmed is reported through a SlabLayoutDiagnostic instead of being
+// parsed with the wrong offsets.
+// =============================================================================
+
+/**
+ * Full slab layout descriptor for one (layout version, maxAccounts) pair.
+ * All engine field offsets are relative to engineOff.
+ */
+export interface SlabLayout {
+  version: number;
+  headerLen: number;
+  configOffset: number;
+  configLen: number;
+  reservedOff: number;          // offset of _reserved in header
+  engineOff: number;
+  accountSize: number;
+  maxAccounts: number;
+  bitmapWords: number;
+  accountsOff: number;          // absolute offset of accounts array in slab
+
+  // Engine field offsets (relative to engineOff)
+  engineInsuranceOff: number;
+  engineParamsOff: number;
+  paramsSize: number;
+  engineCurrentSlotOff: number;
+  engineFundingIndexOff: number;
+  engineLastFundingSlotOff: number;
+  engineFundingRateBpsOff: number;
+  engineMarkPriceOff: number;           // -1 if not present (V0)
+  engineLastCrankSlotOff: number;
+  engineMaxCrankStalenessOff: number;
+  engineTotalOiOff: number;
+  engineLongOiOff: number;              // -1 if not present (V0)
+  engineShortOiOff: number;             // -1 if not present (V0)
+  engineCTotOff: number;
+  enginePnlPosTotOff: number;
+  engineLiqCursorOff: number;
+  engineGcCursorOff: number;
+  engineLastSweepStartOff: number;
+  engineLastSweepCompleteOff: number;
+  engineCrankCursorOff: number;
+  engineSweepStartIdxOff: number;
+  engineLifetimeLiquidationsOff: number;
+  engineLifetimeForceClosesOff: number;
+  engineNetLpPosOff: number;
+  engineLpSumAbsOff: number;
+  engineLpMaxAbsOff: number;
+  engineLpMaxAbsSweepOff: number;
+  engineEmergencyOiModeOff: number;     // -1 if not present (V0)
+  engineEmergencyStartSlotOff: number;  // -1 if not present (V0)
+  engineLastBreakerSlotOff: number;     // -1 if not present (V0)
+  engineBitmapOff: number;              // relative to engineOff
+
+  // Insurance fund layout
+  hasInsuranceIsolation: boolean;
+  engineInsuranceIsolatedOff: number;   // -1 if not present (V0)
+  engineInsuranceIsolationBpsOff: number; // -1 if not present (V0)
+}
+
+/**
+ * Declarative description of one layout version. Tier-dependent values
+ * (bitmap size, accounts offset, total size) are derived by buildSlabLayout.
+ * Optional engine fields are omitted (or -1) when the layout lacks them.
+ */
+export interface SlabLayoutSpec {
+  /** Layout version id (0 = deployed devnet, 1 = PERC-120+ upgrade, ...) */
+  version: number;
+  label: string;
+  /** MAX_ACCOUNTS values the program is compiled for */
+  tiers: readonly number[];
+  headerLen: number;
+  configLen: number;
+  reservedOff: number;
+  accountSize: number;
+  engine: {
+    insuranceOff: number;
+    paramsOff: number;
+    paramsSize: number;
+    currentSlotOff: number;
+    fundingIndexOff: number;
+    lastFundingSlotOff: number;
+    fundingRateBpsOff: number;
+    markPriceOff?: number;
+    lastCrankSlotOff: number;
+    maxCrankStalenessOff: number;
+    totalOiOff: number;
+    longOiOff?: number;
+    shortOiOff?: number;
+    cTotOff: number;
+    pnlPosTotOff: number;
+    liqCursorOff: number;
+    gcCursorOff: number;
+    lastSweepStartOff: number;
+    lastSweepCompleteOff: number;
+    crankCursorOff: number;
+    sweepStartIdxOff: number;
+    lifetimeLiquidationsOff: number;
+    lifetimeForceClosesOff: number;
+    netLpPosOff: number;
+    lpSumAbsOff: number;
+    lpMaxAbsOff: number;
+    lpMaxAbsSweepOff: number;
+    emergencyOiModeOff?: number;
+    emergencyStartSlotOff?: number;
+    lastBreakerSlotOff?: number;
+    bitmapOff: number;
+    insuranceIsolatedOff?: number;
+    insuranceIsolationBpsOff?: number;
+  };
+}
+
+const TIERS = [64, 256, 1024, 4096] as const;
+
+/**
+ * V0 (deployed devnet): HEADER=72, CONFIG=408, ENGINE_OFF=480, ACCOUNT_SIZE=240
+ *   - InsuranceFund: {balance: U128, fee_revenue: U128} (32 bytes)
+ *   - RiskParams: 56 bytes (basic fields only)
+ *   - No mark_price, no long_oi/short_oi, no emergency OI cap fields
+ *   - No partial liquidation field in Account (240 bytes)
+ */
+export const SLAB_LAYOUT_V0: SlabLayoutSpec = {
+  version: 0,
+  label: "V0",
+  tiers: TIERS,
+  headerLen: 72,
+  configLen: 408,
+  reservedOff: 48, // magic(8)+version(4)+bump(1)+pad(3)+admin(32) = 48
+  accountSize: 240,
+  engine: {
+    // vault(16) + insurance{balance(16),fee_revenue(16)}=32 → params at 48
+    // RiskParams: 56 bytes → runtime state at 104
+    insuranceOff: 16,
+    paramsOff: 48,
+    paramsSize: 56,
+    currentSlotOff: 104,
+    fundingIndexOff: 112,
+    lastFundingSlotOff: 128,
+    fundingRateBpsOff: 136,
+    lastCrankSlotOff: 144,
+    maxCrankStalenessOff: 152,
+    totalOiOff: 160,
+    cTotOff: 176,
+    pnlPosTotOff: 192,
+    liqCursorOff: 208,
+    gcCursorOff: 210,
+    lastSweepStartOff: 216,
+    lastSweepCompleteOff: 224,
+    crankCursorOff: 232,
+    sweepStartIdxOff: 234,
+    lifetimeLiquidationsOff: 240,
+    lifetimeForceClosesOff: 248,
+    netLpPosOff: 256,
+    lpSumAbsOff: 272,
+    lpMaxAbsOff: 288,
+    lpMaxAbsSweepOff: 304,
+    bitmapOff: 320,
+  },
+};
+
+/**
+ * V1 (PERC-120/121/122/298/299/300/301/306/328 upgrade):
+ * HEADER=104, CONFIG=536, ENGINE_OFF=640, ACCOUNT_SIZE=248
+ *   - InsuranceFund: expanded with isolation fields (72 bytes)
+ *   - RiskParams: 288 bytes (premium funding, partial liq, dynamic fees)
+ *   - Has mark_price, long_oi/short_oi, emergency fields
+ *   - Account has last_partial_liquidation_slot (248 bytes)
+ */
+export const SLAB_LAYOUT_V1: SlabLayoutSpec = {
+  version: 1,
+  label: "V1",
+  tiers: TIERS,
+  headerLen: 104,
+  configLen: 536,
+  reservedOff: 80,
+  accountSize: 248,
+  engine: {
+    // vault(16) + insurance expanded(56) → params at 72
+    // RiskParams: 288 bytes → runtime state at 360
+    insuranceOff: 16,
+    paramsOff: 72,
+    paramsSize: 288,
+    currentSlotOff: 360,
+    fundingIndexOff: 368,
+    lastFundingSlotOff: 384,
+    fundingRateBpsOff: 392,
+    markPriceOff: 400,
+    lastCrankSlotOff: 424,
+    maxCrankStalenessOff: 432,
+    totalOiOff: 440,
+    longOiOff: 456,
+    shortOiOff: 472,
+    cTotOff: 488,
+    pnlPosTotOff: 504,
+    liqCursorOff: 520,
+    gcCursorOff: 522,
+    lastSweepStartOff: 528,
+    lastSweepCompleteOff: 536,
+    crankCursorOff: 544,
+    sweepStartIdxOff: 546,
+    lifetimeLiquidationsOff: 552,
+    lifetimeForceClosesOff: 560,
+    netLpPosOff: 568,
+    lpSumAbsOff: 584,
+    lpMaxAbsOff: 600,
+    lpMaxAbsSweepOff: 616,
+    emergencyOiModeOff: 632,
+    emergencyStartSlotOff: 640,
+    lastBreakerSlotOff: 648,
+    bitmapOff: 656, // PERC-299: +24 emergency OI fields
+    insuranceIsolatedOff: 48,
+    insuranceIsolationBpsOff: 64,
+  },
+};
+
+/** num_used(u16,2) + pad(6) + next_account_id(u64,8) + free_head(u16,2) */
+const POST_BITMAP_LEN = 18;
+
+function align8(n: number): number {
+  return Math.ceil(n / 8) * 8;
+}
+
+/** Engine offset for a spec: align_up(HEADER_LEN + CONFIG_LEN, 8). */
+export function specEngineOff(spec: SlabLayoutSpec): number {
+  return align8(spec.headerLen + spec.configLen);
+}
+
+/** Accounts array offset relative to engineOff. */
+function accountsOffRel(spec: SlabLayoutSpec, maxAccounts: number): number {
+  const bitmapBytes = Math.ceil(maxAccounts / 64) * 8;
+  const nextFreeBytes = maxAccounts * 2;
+  return align8(spec.engine.bitmapOff + bitmapBytes + POST_BITMAP_LEN + nextFreeBytes);
+}
+
+/** Total slab data size for a layout spec and account count (the program's SLAB_LEN). */
+export function slabLayoutSize(spec: SlabLayoutSpec, maxAccounts: number): number {
+  return specEngineOff(spec) + accountsOffRel(spec, maxAccounts) + maxAccounts * spec.accountSize;
+}
+
+/** Expand a spec into the concrete SlabLayout for one account count. */
+export function buildSlabLayout(spec: SlabLayoutSpec, maxAccounts: number): SlabLayout {
+  const e = spec.engine;
+  const engineOff = specEngineOff(spec);
+  const opt = (off: number | undefined) => off ?? -1;
+  return {
+    version: spec.version,
+    headerLen: spec.headerLen,
+    configOffset: spec.headerLen,
+    configLen: spec.configLen,
+    reservedOff: spec.reservedOff,
+    engineOff,
+    accountSize: spec.accountSize,
+    maxAccounts,
+    bitmapWords: Math.ceil(maxAccounts / 64),
+    accountsOff: engineOff + accountsOffRel(spec, maxAccounts),
+
+    engineInsuranceOff: e.insuranceOff,
+    engineParamsOff: e.paramsOff,
+    paramsSize: e.paramsSize,
+    engineCurrentSlotOff: e.currentSlotOff,
+    engineFundingIndexOff: e.fundingIndexOff,
+    engineLastFundingSlotOff: e.lastFundingSlotOff,
+    engineFundingRateBpsOff: e.fundingRateBpsOff,
+    engineMarkPriceOff: opt(e.markPriceOff),
+    engineLastCrankSlotOff: e.lastCrankSlotOff,
+    engineMaxCrankStalenessOff: e.maxCrankStalenessOff,
+    engineTotalOiOff: e.totalOiOff,
+    engineLongOiOff: opt(e.longOiOff),
+    engineShortOiOff: opt(e.shortOiOff),
+    engineCTotOff: e.cTotOff,
+    enginePnlPosTotOff: e.pnlPosTotOff,
+    engineLiqCursorOff: e.liqCursorOff,
+    engineGcCursorOff: e.gcCursorOff,
+    engineLastSweepStartOff: e.lastSweepStartOff,
+    engineLastSweepCompleteOff: e.lastSweepCompleteOff,
+    engineCrankCursorOff: e.crankCursorOff,
+    engineSweepStartIdxOff: e.sweepStartIdxOff,
+    engineLifetimeLiquidationsOff: e.lifetimeLiquidationsOff,
+    engineLifetimeForceClosesOff: e.lifetimeForceClosesOff,
+    engineNetLpPosOff: e.netLpPosOff,
+    engineLpSumAbsOff: e.lpSumAbsOff,
+    engineLpMaxAbsOff: e.lpMaxAbsOff,
+    engineLpMaxAbsSweepOff: e.lpMaxAbsSweepOff,
+    engineEmergencyOiModeOff: opt(e.emergencyOiModeOff),
+    engineEmergencyStartSlotOff: opt(e.emergencyStartSlotOff),
+    engineLastBreakerSlotOff: opt(e.lastBreakerSlotOff),
+    engineBitmapOff: e.bitmapOff,
+
+    hasInsuranceIsolation: e.insuranceIsolatedOff !== undefined,
+    engineInsuranceIsolatedOff: opt(e.insuranceIsolatedOff),
+    engineInsuranceIsolationBpsOff: opt(e.insuranceIsolationBpsOff),
+  };
+}
+
+// ---- Registry ----
+
+/** One concrete (layout, tier) entry, e.g. for getProgramAccounts dataSize filters. */
+export interface KnownSlabSize {
+  version: number;
+  maxAccounts: number;
+  dataSize: number;
+}
+
+// Order matters: it is the detection order reported in diagnostics. V0 stays
+// first because it is what the deployed devnet program writes.
+const registry: SlabLayoutSpec[] = [SLAB_LAYOUT_V0, SLAB_LAYOUT_V1];
+let sizeIndex = new Map<number, KnownSlabSize>();
+
+function rebuildSizeIndex(): void {
+  const next = new Map<number, KnownSlabSize>();
+  for (const spec of registry) {
+    for (const n of spec.tiers) {
+      const dataSize = slabLayoutSize(spec, n);
+      if (!next.has(dataSize)) next.set(dataSize, { version: spec.version, maxAccounts: n, dataSize });
+    }
+  }
+  sizeIndex = next;
+}
+rebuildSizeIndex();
+
+/**
+ * Register an additional layout version. Throws if the version is already
+ * registered or one of its tier sizes collides with an existing layout (the
+ * two would be indistinguishable by dataSize filters).
+ *
+ * @returns a function that removes the layout again (mainly for tests).
+ */
+export function registerSlabLayout(spec: SlabLayoutSpec): () => void {
+  if (registry.some((s) => s.version === spec.version)) {
+    throw new Error(`Slab layout version ${spec.version} is already registered`);
+  }
+  for (const n of spec.tiers) {
+    const size = slabLayoutSize(spec, n);
+    const existing = sizeIndex.get(size);
+    if (existing) {
+      throw new Error(
+        `Slab layout V${spec.version} (${n} accounts, ${size} bytes) collides with ` +
+          `V${existing.version} (${existing.maxAccounts} accounts)`,
+      );
+    }
+  }
+  registry.push(spec);
+  rebuildSizeIndex();
+  return () => {
+    const idx = registry.indexOf(spec);
+    if (idx >= 0) {
+      registry.splice(idx, 1);
+      rebuildSizeIndex();
+    }
+  };
+}
+
+/** All registered layout specs, in detection order. */
+export function listSlabLayouts(): readonly SlabLayoutSpec[] {
+  return registry;
+}
+
+export function getSlabLayoutSpec(version: number): SlabLayoutSpec | null {
+  return registry.find((s) => s.version === version) ?? null;
+}
+
+/** Every known slab data size across all registered layouts and tiers. */
+export function knownSlabSizes(): KnownSlabSize[] {
+  return [...sizeIndex.values()];
+}
+
+/**
+ * Length-only lookup. Use for partial slices where the real account size is
+ * known (e.g. from a dataSize filter) but the engine bytes are not.
+ */
+export function layoutForDataSize(dataLen: number): SlabLayout | null {
+  const hit = sizeIndex.get(dataLen);
+  if (!hit) return null;
+  return buildSlabLayout(getSlabLayoutSpec(hit.version)!, hit.maxAccounts);
+}
+
+// ---- Resolution with diagnostics ----
+
+export type SlabLayoutDiagnosticCode =
+  | "UNKNOWN_DATA_SIZE"
+  | "MAX_ACCOUNTS_MISMATCH";
+
+/** Why a slab could not be matched to a registered layout. */
+export interface SlabLayoutDiagnostic {
+  code: SlabLayoutDiagnosticCode;
+  dataLen: number;
+  /** SlabHeader.version, if the header was readable (informational only) */
+  headerVersion: number | null;
+  /** RiskParams.max_accounts read through the candidate layout, when it disagreed */
+  maxAccounts: number | null;
+  /** Registered layouts consulted */
+  knownVersions: number[];
+  message: string;
+}
+
+export type SlabLayoutResolution =
+  | { layout: SlabLayout; diagnostic: null }
+  | { layout: null; diagnostic: SlabLayoutDiagnostic };
+
+export class UnknownSlabLayoutError extends Error {
+  readonly diagnostic: SlabLayoutDiagnostic;
+
+  constructor(diagnostic: SlabLayoutDiagnostic) {
+    super(diagnostic.message);
+    this.name = "UnknownSlabLayoutError";
+    this.diagnostic = diagnostic;
+  }
+}
+
+function readU32LE(data: Uint8Array, off: number): number {
+  return new DataView(data.buffer, data.byteOffset, data.byteLength).getUint32(off, true);
+}
+function readU64LE(data: Uint8Array, off: number): bigint {
+  return new DataView(data.buffer, data.byteOffset, data.byteLength).getBigUint64(off, true);
+}
+
+/**
+ * Resolve the layout of a slab from its length, confirming the match against
+ * RiskParams.max_accounts when those bytes are present.
+ *
+ * @param data    - Slab data (full account, or a prefix slice)
+ * @param dataLen - Real account size; defaults to data.length. Pass it when
+ *                  `data` is a dataSlice.
+ */
+export function resolveSlabLayout(data: Uint8Array, dataLen: number = data.length): SlabLayoutResolution {
+  const headerVersion = data.length >= 12 ? readU32LE(data, 8) : null;
+  const knownVersions = registry.map((s) => s.version);
+  const fail = (
+    code: SlabLayoutDiagnosticCode,
+    maxAccounts: number | null,
+    message: string,
+  ): SlabLayoutResolution => ({
+    layout: null,
+    diagnostic: { code, dataLen, headerVersion, maxAccounts, knownVersions, message },
+  });
+
+  const layout = layoutForDataSize(dataLen);
+  if (!layout) {
+    return fail(
+      "UNKNOWN_DATA_SIZE",
+      null,
+      `Unrecognized slab data length: ${dataLen}. Cannot determine layout version ` +
+        `(known layouts: ${knownVersions.map((v) => `V${v}`).join(", ")}).`,
+    );
+  }
+
+  // RiskParams.max_accounts may be configured below the compiled capacity (and
+  // is zero on mocked slabs); only a value above capacity is conclusive.
+  const maxAccountsOff = layout.engineOff + layout.engineParamsOff + 32;
+  if (data.length >= maxAccountsOff + 8) {
+    const onChain = Number(readU64LE(data, maxAccountsOff));
+    if (onChain > layout.maxAccounts) {
+      return fail(
+        "MAX_ACCOUNTS_MISMATCH",
+        onChain,
+        `Slab data length ${dataLen} matches V${layout.version}/${layout.maxAccounts} but ` +
+          `RiskParams.max_accounts is ${onChain}, above that layout's capacity.`,
+      );
+    }
+  }
+
+  return { layout, diagnostic: null };
+}
+
+/** resolveSlabLayout, throwing UnknownSlabLayoutError when unresolved. */
+export function requireSlabLayout(data: Uint8Array, dataLen: number = data.length): SlabLayout {
+  const res = resolveSlabLayout(data, dataLen);
+  if (!res.layout) throw new UnknownSlabLayoutError(res.diagnostic);
+  return res.layout;
+}
@@ -7,10 +7,27 @@
  *
  * Minimum account size: 224 bytes (offset 216 + 8 bytes for i64).
  *
+ * Pyth PriceUpdateV2 (pull oracle receiver / push oracle feed accounts),
+ * Borsh after the 8-byte Anchor discriminator:
+ *   write_authority (32) | verification_level (enum: 0 = Partial{u8}, 1 = Full)
+ *   | feed_id (32) | price i64 | conf u64 | exponent i32 | publish_time i64
+ *   | prev_publish_time i64 | ema_price i64 | ema_conf u64 | posted_slot u64
+ * The enum is variable-length, so the message starts at 42 (Partial) or 41 (Full).
+ *
+ * Switchboard On-Demand PullFeedAccountData (zero-copy, repr(C)):
+ *   offset 2216: last_update_timestamp (i64)
+ *   offset 2264: result.value (i128, 18 decimals)
+ *   offset 2280: result.std_dev (i128, 18 decimals)
+ *   offset 2360: result.num_samples (u8)
+ *   offset 2368: result.slot (u64)
+ *   offset 2392: max_staleness (u32, slots)
+ *
  * These utilities validate oracle data BEFORE parsing to prevent silent
- * propagation of stale or malformed Chainlink data as price.
+ * propagation of stale or malformed data as price.
  */
 
+import { PublicKey } from "@solana/web3.js";
+
 // ---------------------------------------------------------------------------
 // Constants
 // ---------------------------------------------------------------------------
@@ -27,6 +44,28 @@ const CHAINLINK_DECIMALS_OFFSET = 138;
 /** Offset of latest answer in Chainlink aggregator account */
 const CHAINLINK_ANSWER_OFFSET = 216;
 
+/** Anchor discriminator of Pyth `PriceUpdateV2` */
+const PYTH_PRICE_UPDATE_V2_DISCRIMINATOR = new Uint8Array([0x22, 0xf1, 0x23, 0x63, 0x9d, 0x7e, 0xf4, 0xcd]);
+
+/** Full-verification PriceUpdateV2 size; Partial adds one byte (134) */
+const PYTH_PRICE_UPDATE_V2_MIN_SIZE = 133;
+
+/** Anchor discriminator of Switchboard On-Demand `PullFeedAccountData` */
+const SWITCHBOARD_PULL_FEED_DISCRIMINATOR = new Uint8Array([0xc4, 0x1b, 0x6c, 0xc4, 0x0a, 0xd7, 0xdb, 0x28]);
+
+/** Minimum buffer size to read a Switchboard pull feed result (through max_staleness) */
+const SWITCHBOARD_PULL_FEED_MIN_SIZE = 2396;
+
+/** Switchboard On-Demand values are fixed-point with 18 decimals */
+const SWITCHBOARD_DECIMALS = 18;
+
+const SB_LAST_UPDATE_TIMESTAMP_OFFSET = 2216;
+const SB_RESULT_VALUE_OFFSET = 2264;
+const SB_RESULT_STD_DEV_OFFSET = 2280;
+const SB_RESULT_NUM_SAMPLES_OFFSET = 2360;
+const SB_RESULT_SLOT_OFFSET = 2368;
+const SB_MAX_STALENESS_OFFSET = 2392;
+
 // ---------------------------------------------------------------------------
 // Types
 // ---------------------------------------------------------------------------
@@ -34,8 +73,56 @@ const CHAINLINK_ANSWER_OFFSET = 216;
 export interface OraclePrice {
   price: bigint;
   decimals: number;
+  /** Confidence interval (same scale as price) — Pyth conf, Switchboard std dev */
+  conf?: bigint;
+  /** Unix time (seconds) the price was published */
+  publishTime?: bigint;
+  /** Slot the price was posted / resolved at */
+  slot?: bigint;
+}
+
+export type PythVerificationLevel = "partial" | "full";
+
+export interface PythPriceUpdate extends OraclePrice {
+  conf: bigint;
+  publishTime: bigint;
+  slot: bigint;
+  /** Pyth feed ID, hex (64 chars, no 0x) */
+  feedId: string;
+  /** Raw Pyth exponent (price = mantissa × 10^exponent) */
+  exponent: number;
+  verificationLevel: PythVerificationLevel;
+  /** Guardian signatures checked, for partially verified updates */
+  numSignatures?: number;
+  emaPrice: bigint;
+  emaConf: bigint;
+  writeAuthority: PublicKey;
+}
+
+export interface SwitchboardPullFeed extends OraclePrice {
+  conf: bigint;
+  publishTime: bigint;
+  slot: bigint;
+  numSamples: number;
+  /** Feed's own staleness bound, in slots */
+  maxStalenessSlots: number;
+}
+
+export interface OracleFreshnessLimits {
+  /** Max age in seconds against `publishTime` (Pyth pull uses unix time) */
+  maxStalenessSecs?: bigint | number;
+  /** Max age in slots against `slot` */
+  maxStalenessSlots?: bigint | number;
+  /** Max conf / price in bps; 0 = unchecked */
+  confFilterBps?: number;
+  nowUnixSecs?: number;
+  currentSlot?: bigint | number;
 }
 
+export type OracleFreshnessCheck =
+  | { ok: true; ageSecs: number | null; ageSlots: number | null; confBps: number | null }
+  | { ok: false; reason: string; ageSecs: number | null; ageSlots: number | null; confBps: number | null };
+
 // ---------------------------------------------------------------------------
 // Browser-compatible read helpers using DataView
 // ---------------------------------------------------------------------------
@@ -48,6 +135,38 @@ function readBigInt64LE(data: Uint8Array, off: number): bigint {
   return new DataView(data.buffer, data.byteOffset, data.byteLength).getBigInt64(off, true);
 }
 
+function readBigUInt64LE(data: Uint8Array, off: number): bigint {
+  return new DataView(data.buffer, data.byteOffset, data.byteLength).getBigUint64(off, true);
+}
+
+function readInt32LE(data: Uint8Array, off: number): number {
+  return new DataView(data.buffer, data.byteOffset, data.byteLength).getInt32(off, true);
+}
+
+function readUInt32LE(data: Uint8Array, off: number): number {
+  return new DataView(data.buffer, data.byteOffset, data.byteLength).getUint32(off, true);
+}
+
+function readBigInt128LE(data: Uint8Array, off: number): bigint {
+  const lo = readBigUInt64LE(data, off);
+  const hi = readBigInt64LE(data, off + 8);
+  return (hi << 64n) | lo;
+}
+
+function hasPrefix(data: Uint8Array, prefix: Uint8Array): boolean {
+  if (data.length < prefix.length) return false;
+  for (let i = 0; i < prefix.length; i++) {
+    if (data[i] !== prefix[i]) return false;
+  }
+  return true;
+}
+
+function toHex(bytes: Uint8Array): string {
+  let out = "";
+  for (const b of bytes) out += b.toString(16).padStart(2, "0");
+  return out;
+}
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
@@ -102,5 +221,172 @@ export function isValidChainlinkOracle(data: Uint8Array): boolean {
   }
 }
 
+/**
+ * Parse a Pyth `PriceUpdateV2` account (pull oracle receiver, or the push
+ * oracle feed account at {@link derivePythPushOraclePDA}).
+ *
+ * `price`/`conf` are the raw mantissas and `decimals` is `-exponent`; a
+ * positive exponent is folded into the mantissa with `decimals = 0`.
+ *
+ * @throws if the discriminator, verification level or size is wrong, or the
+ *         price is non-positive
+ */
+export function parsePythPriceUpdateV2(data: Uint8Array): PythPriceUpdate {
+  if (data.length < PYTH_PRICE_UPDATE_V2_MIN_SIZE) {
+    throw new Error(
+      `Pyth PriceUpdateV2 data too small: ${data.length} bytes (need at least ${PYTH_PRICE_UPDATE_V2_MIN_SIZE})`
+    );
+  }
+  if (!hasPrefix(data, PYTH_PRICE_UPDATE_V2_DISCRIMINATOR)) {
+    throw new Error("Not a Pyth PriceUpdateV2 account (discriminator mismatch)");
+  }
+
+  const writeAuthority = new PublicKey(data.slice(8, 40));
+  const levelTag = readU8(data, 40);
+  let off: number;
+  let verificationLevel: PythVerificationLevel;
+  let numSignatures: number | undefined;
+  if (levelTag === 0) {
+    verificationLevel = "partial";
+    numSignatures = readU8(data, 41);
+    off = 42;
+  } else if (levelTag === 1) {
+    verificationLevel = "full";
+    off = 41;
+  } else {
+    throw new Error(`Unknown Pyth verification level: ${levelTag}`);
+  }
+  if (data.length < off + 92) {
+    throw new Error(`Pyth PriceUpdateV2 data too small: ${data.length} bytes (need ${off + 92})`);
+  }
+
+  const feedId = toHex(data.slice(off, off + 32));
+  let price = readBigInt64LE(data, off + 32);
+  let conf = readBigUInt64LE(data, off + 40);
+  const exponent = readInt32LE(data, off + 48);
+  const publishTime = readBigInt64LE(data, off + 52);
+  let emaPrice = readBigInt64LE(data, off + 68);
+  let emaConf = readBigUInt64LE(data, off + 76);
+  const slot = readBigUInt64LE(data, off + 84);
+
+  if (price <= 0n) {
+    throw new Error(`Oracle price is non-positive: ${price}`);
+  }
+  if (-exponent > MAX_DECIMALS) {
+    throw new Error(`Oracle decimals out of range: ${-exponent} (max ${MAX_DECIMALS})`);
+  }
+
+  let decimals = -exponent;
+  if (exponent > 0) {
+    const scale = 10n ** BigInt(exponent);
+    price *= scale;
+    conf *= scale;
+    emaPrice *= scale;
+    emaConf *= scale;
+    decimals = 0;
+  }
+
+  return {
+    price,
+    decimals,
+    conf,
+    publishTime,
+    slot,
+    feedId,
+    exponent,
+    verificationLevel,
+    ...(numSignatures !== undefined ? { numSignatures } : {}),
+    emaPrice,
+    emaConf,
+    writeAuthority,
+  };
+}
+
+/**
+ * Parse a Switchboard On-Demand pull feed (`PullFeedAccountData`).
+ * `price` and `conf` (the result's standard deviation) have 18 decimals.
+ *
+ * @throws if the discriminator or size is wrong, the feed has never resolved
+ *         (no samples), or the price is non-positive
+ */
+export function parseSwitchboardPullFeed(data: Uint8Array): SwitchboardPullFeed {
+  if (data.length < SWITCHBOARD_PULL_FEED_MIN_SIZE) {
+    throw new Error(
+      `Switchboard pull feed data too small: ${data.length} bytes (need at least ${SWITCHBOARD_PULL_FEED_MIN_SIZE})`
+    );
+  }
+  if (!hasPrefix(data, SWITCHBOARD_PULL_FEED_DISCRIMINATOR)) {
+    throw new Error("Not a Switchboard PullFeedAccountData account (discriminator mismatch)");
+  }
+
+  const numSamples = readU8(data, SB_RESULT_NUM_SAMPLES_OFFSET);
+  if (numSamples === 0) {
+    throw new Error("Switchboard feed has no resolved result");
+  }
+  const price = readBigInt128LE(data, SB_RESULT_VALUE_OFFSET);
+  if (price <= 0n) {
+    throw new Error(`Oracle price is non-positive: ${price}`);
+  }
+
+  return {
+    price,
+    decimals: SWITCHBOARD_DECIMALS,
+    conf: readBigInt128LE(data, SB_RESULT_STD_DEV_OFFSET),
+    publishTime: readBigInt64LE(data, SB_LAST_UPDATE_TIMESTAMP_OFFSET),
+    slot: readBigUInt64LE(data, SB_RESULT_SLOT_OFFSET),
+    numSamples,
+    maxStalenessSlots: readUInt32LE(data, SB_MAX_STALENESS_OFFSET),
+  };
+}
+
+/**
+ * Parse any supported pull-oracle account by its discriminator: Pyth
+ * PriceUpdateV2 or Switchboard On-Demand. Returns null for other accounts.
+ */
+export function parsePullOraclePrice(data: Uint8Array): PythPriceUpdate | SwitchboardPullFeed | null {
+  if (hasPrefix(data, PYTH_PRICE_UPDATE_V2_DISCRIMINATOR)) return parsePythPriceUpdateV2(data);
+  if (hasPrefix(data, SWITCHBOARD_PULL_FEED_DISCRIMINATOR)) return parseSwitchboardPullFeed(data);
+  return null;
+}
+
+/**
+ * Check an oracle price against a market's staleness and confidence limits
+ * (`maxStalenessSlots` — seconds for Pyth pull — and `confFilterBps` in
+ * MarketConfig), the same checks the program applies when cranking.
+ *
+ * Limits that are unset, or whose field is missing from `price`, are skipped.
+ */
+export function checkOracleFreshness(price: OraclePrice, limits: OracleFreshnessLimits): OracleFreshnessCheck {
+  const nowSecs = limits.nowUnixSecs ?? Math.floor(Date.now() / 1000);
+  const ageSecs = price.publishTime !== undefined ? nowSecs - Number(price.publishTime) : null;
+  const ageSlots =
+    price.slot !== undefined && limits.currentSlot !== undefined
+      ? Number(BigInt(limits.currentSlot) - price.slot)
+      : null;
+  const confBps = price.conf !== undefined && price.price > 0n ? Number((price.conf * 10_000n) / price.price) : null;
+  const info = { ageSecs, ageSlots, confBps };
+
+  const maxSecs = limits.maxStalenessSecs !== undefined ? Number(limits.maxStalenessSecs) : 0;
+  if (maxSecs > 0 && ageSecs !== null && ageSecs > maxSecs) {
+    return { ok: false, reason: `stale: ${ageSecs}s old (max ${maxSecs}s)`, ...info };
+  }
+  const maxSlots = limits.maxStalenessSlots !== undefined ? Number(limits.maxStalenessSlots) : 0;
+  if (maxSlots > 0 && ageSlots !== null && ageSlots > maxSlots) {
+    return { ok: false, reason: `stale: ${ageSlots} slots old (max ${maxSlots})`, ...info };
+  }
+  const maxConf = limits.confFilterBps ?? 0;
+  if (maxConf > 0 && confBps !== null && confBps > maxConf) {
+    return { ok: false, reason: `confidence too wide: ${confBps}bps (max ${maxConf}bps)`, ...info };
+  }
+  return { ok: true, ...info };
+}
+
 // Re-export constants for consumers
-export { CHAINLINK_MIN_SIZE, CHAINLINK_DECIMALS_OFFSET, CHAINLINK_ANSWER_OFFSET, MAX_DECIMALS };
+export {
+  CHAINLINK_MIN_SIZE,
+  CHAINLINK_DECIMALS_OFFSET,
+  CHAINLINK_ANSWER_OFFSET,
+  MAX_DECIMALS,
+  PYTH_PRICE_UPDATE_V2_MIN_SIZE,
+  SWITCHBOARD_PULL_FEED_MIN_SIZE,
+};
@@ -80,6 +80,21 @@ export const METEORA_DLMM_PROGRAM_ID = new PublicKey(
   "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo"
 );
 
+/** Orca Whirlpool (concentrated liquidity) program ID. */
+export const ORCA_WHIRLPOOL_PROGRAM_ID = new PublicKey(
+  "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc"
+);
+
+/** Raydium CPMM (constant product, Token-2022 capable) program ID. */
+export const RAYDIUM_CPMM_PROGRAM_ID = new PublicKey(
+  "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C"
+);
+
+/** Raydium AMM v4 (legacy constant product) program ID. */
+export const RAYDIUM_AMM_V4_PROGRAM_ID = new PublicKey(
+  "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8"
+);
+
 // ---------------------------------------------------------------------------
 // Pyth Push Oracle
 // ---------------------------------------------------------------------------
@@ -105,3 +120,12 @@ export function derivePythPushOraclePDA(feedIdHex: string): [PublicKey, number]
     PYTH_PUSH_ORACLE_PROGRAM_ID,
   );
 }
+
+// ---------------------------------------------------------------------------
+// Switchboard On-Demand
+// ---------------------------------------------------------------------------
+
+/** Switchboard On-Demand program (pull feeds), mainnet and devnet. */
+export const SWITCHBOARD_ON_DEMAND_PROGRAM_ID = new PublicKey(
+  "SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv"
+);
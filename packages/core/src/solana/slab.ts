@@ -1,4 +1,12 @@
 import { Connection, PublicKey } from "@solana/web3.js";
+import {
+  SLAB_LAYOUT_V0,
+  SLAB_LAYOUT_V1,
+  layoutForDataSize,
+  requireSlabLayout,
+  specEngineOff,
+  type SlabLayout,
+} from "./layouts.js";
 
 // =============================================================================
 // Browser-compatible read helpers using DataView
@@ -46,21 +54,10 @@ function readU128LE(buf: Uint8Array, offset: number): bigint {
 // =============================================================================
 // Slab Layout Version Detection
 // =============================================================================
-// The deployed devnet program uses a different struct layout (V0) than the SDK
-// was updated for (V1). V1 includes PERC-120/121/122/298/299/300/301/306/328
-// struct changes that have NOT been deployed to devnet yet.
-//
-// V0 (deployed devnet): HEADER=72, CONFIG=408, ENGINE_OFF=480, ACCOUNT_SIZE=240
-//   - InsuranceFund: {balance: U128, fee_revenue: U128} (32 bytes)
-//   - RiskParams: 56 bytes (basic fields only)
-//   - No mark_price, no long_oi/short_oi, no emergency OI cap fields
-//   - No partial liquidation field in Account (240 bytes)
-//
-// V1 (future upgrade): HEADER=104, CONFIG=536, ENGINE_OFF=640, ACCOUNT_SIZE=248
-//   - InsuranceFund: expanded with isolation fields (72 bytes)
-//   - RiskParams: 288 bytes (premium funding, partial liq, dynamic fees)
-//   - Has mark_price, long_oi/short_oi, emergency fields
-//   - Account has last_partial_liquidation_slot (248 bytes)
+// Layouts are declared in ./layouts.ts. The deployed devnet program uses V0;
+// V1 includes PERC-120/121/122/298/299/300/301/306/328 struct changes that
+// have NOT been deployed to devnet yet. See SLAB_LAYOUT_V0 / SLAB_LAYOUT_V1
+// for the per-version struct notes.
 // =============================================================================
 
 const MAGIC: bigint = 0x504552434f4c4154n; // "PERCOLAT"
@@ -68,243 +65,31 @@ const MAGIC: bigint = 0x504552434f4c4154n; // "PERCOLAT"
 // Flag bits in header._padding[0] at offset 13
 const FLAG_RESOLVED = 1 << 0;
 
-/**
- * Full slab layout descriptor. Returned by detectSlabLayout().
- * All engine field offsets are relative to engineOff.
- */
-export interface SlabLayout {
-  version: 0 | 1;
-  headerLen: number;
-  configOffset: number;
-  configLen: number;
-  reservedOff: number;          // offset of _reserved in header
-  engineOff: number;
-  accountSize: number;
-  maxAccounts: number;
-  bitmapWords: number;
-  accountsOff: number;          // absolute offset of accounts array in slab
-
-  // Engine field offsets (relative to engineOff)
-  engineInsuranceOff: number;
-  engineParamsOff: number;
-  paramsSize: number;
-  engineCurrentSlotOff: number;
-  engineFundingIndexOff: number;
-  engineLastFundingSlotOff: number;
-  engineFundingRateBpsOff: number;
-  engineMarkPriceOff: number;           // -1 if not present (V0)
-  engineLastCrankSlotOff: number;
-  engineMaxCrankStalenessOff: number;
-  engineTotalOiOff: number;
-  engineLongOiOff: number;              // -1 if not present (V0)
-  engineShortOiOff: number;             // -1 if not present (V0)
-  engineCTotOff: number;
-  enginePnlPosTotOff: number;
-  engineLiqCursorOff: number;
-  engineGcCursorOff: number;
-  engineLastSweepStartOff: number;
-  engineLastSweepCompleteOff: number;
-  engineCrankCursorOff: number;
-  engineSweepStartIdxOff: number;
-  engineLifetimeLiquidationsOff: number;
-  engineLifetimeForceClosesOff: number;
-  engineNetLpPosOff: number;
-  engineLpSumAbsOff: number;
-  engineLpMaxAbsOff: number;
-  engineLpMaxAbsSweepOff: number;
-  engineEmergencyOiModeOff: number;     // -1 if not present (V0)
-  engineEmergencyStartSlotOff: number;  // -1 if not present (V0)
-  engineLastBreakerSlotOff: number;     // -1 if not present (V0)
-  engineBitmapOff: number;              // relative to engineOff
-
-  // Insurance fund layout
-  hasInsuranceIsolation: boolean;
-  engineInsuranceIsolatedOff: number;   // -1 if not present (V0)
-  engineInsuranceIsolationBpsOff: number; // -1 if not present (V0)
-}
+// Shortest header of any layout; magic/version/bump/flags/admin sit at the
+// same offsets in all of them.
+const MIN_HEADER_LEN = SLAB_LAYOUT_V0.headerLen;
 
-// ---- V0 layout constants (deployed devnet program) ----
-const V0_HEADER_LEN = 72;
-const V0_CONFIG_LEN = 408;
-const V0_ENGINE_OFF = 480;   // align_up(72 + 408, 8) = 480
-const V0_ACCOUNT_SIZE = 240;
-const V0_RESERVED_OFF = 48;  // magic(8)+version(4)+bump(1)+pad(3)+admin(32) = 48
-
-// V0 engine: vault(16) + insurance{balance(16),fee_revenue(16)}=32 → params at 48
-// V0 RiskParams: 56 bytes → runtime state at 104
-const V0_ENGINE_PARAMS_OFF = 48;
-const V0_PARAMS_SIZE = 56;
-const V0_ENGINE_CURRENT_SLOT_OFF = 104;
-const V0_ENGINE_FUNDING_INDEX_OFF = 112;
-const V0_ENGINE_LAST_FUNDING_SLOT_OFF = 128;
-const V0_ENGINE_FUNDING_RATE_BPS_OFF = 136;
-const V0_ENGINE_LAST_CRANK_SLOT_OFF = 144;
-const V0_ENGINE_MAX_CRANK_STALENESS_OFF = 152;
-const V0_ENGINE_TOTAL_OI_OFF = 160;
-const V0_ENGINE_C_TOT_OFF = 176;
-const V0_ENGINE_PNL_POS_TOT_OFF = 192;
-const V0_ENGINE_LIQ_CURSOR_OFF = 208;
-const V0_ENGINE_GC_CURSOR_OFF = 210;
-const V0_ENGINE_LAST_SWEEP_START_OFF = 216;
-const V0_ENGINE_LAST_SWEEP_COMPLETE_OFF = 224;
-const V0_ENGINE_CRANK_CURSOR_OFF = 232;
-const V0_ENGINE_SWEEP_START_IDX_OFF = 234;
-const V0_ENGINE_LIFETIME_LIQUIDATIONS_OFF = 240;
-const V0_ENGINE_LIFETIME_FORCE_CLOSES_OFF = 248;
-const V0_ENGINE_NET_LP_POS_OFF = 256;
-const V0_ENGINE_LP_SUM_ABS_OFF = 272;
-const V0_ENGINE_LP_MAX_ABS_OFF = 288;
-const V0_ENGINE_LP_MAX_ABS_SWEEP_OFF = 304;
-const V0_ENGINE_BITMAP_OFF = 320;
-
-// ---- V1 layout constants (future program upgrade) ----
-const V1_HEADER_LEN = 104;
-const V1_CONFIG_LEN = 536;
-const V1_ENGINE_OFF = 640;   // align_up(104 + 536, 8) = 640
-const V1_ACCOUNT_SIZE = 248;
-const V1_RESERVED_OFF = 80;
-
-// V1 engine: vault(16) + insurance expanded(56) → params at 72
-// V1 RiskParams: 288 bytes → runtime state at 360
-const V1_ENGINE_PARAMS_OFF = 72;
-const V1_PARAMS_SIZE = 288;
-const V1_ENGINE_CURRENT_SLOT_OFF = 360;
-const V1_ENGINE_FUNDING_INDEX_OFF = 368;
-const V1_ENGINE_LAST_FUNDING_SLOT_OFF = 384;
-const V1_ENGINE_FUNDING_RATE_BPS_OFF = 392;
-const V1_ENGINE_MARK_PRICE_OFF = 400;
-const V1_ENGINE_LAST_CRANK_SLOT_OFF = 424;
-const V1_ENGINE_MAX_CRANK_STALENESS_OFF = 432;
-const V1_ENGINE_TOTAL_OI_OFF = 440;
-const V1_ENGINE_LONG_OI_OFF = 456;
-const V1_ENGINE_SHORT_OI_OFF = 472;
-const V1_ENGINE_C_TOT_OFF = 488;
-const V1_ENGINE_PNL_POS_TOT_OFF = 504;
-const V1_ENGINE_LIQ_CURSOR_OFF = 520;
-const V1_ENGINE_GC_CURSOR_OFF = 522;
-const V1_ENGINE_LAST_SWEEP_START_OFF = 528;
-const V1_ENGINE_LAST_SWEEP_COMPLETE_OFF = 536;
-const V1_ENGINE_CRANK_CURSOR_OFF = 544;
-const V1_ENGINE_SWEEP_START_IDX_OFF = 546;
-const V1_ENGINE_LIFETIME_LIQUIDATIONS_OFF = 552;
-const V1_ENGINE_LIFETIME_FORCE_CLOSES_OFF = 560;
-const V1_ENGINE_NET_LP_POS_OFF = 568;
-const V1_ENGINE_LP_SUM_ABS_OFF = 584;
-const V1_ENGINE_LP_MAX_ABS_OFF = 600;
-const V1_ENGINE_LP_MAX_ABS_SWEEP_OFF = 616;
-const V1_ENGINE_EMERGENCY_OI_MODE_OFF = 632;
-const V1_ENGINE_EMERGENCY_START_SLOT_OFF = 640;
-const V1_ENGINE_LAST_BREAKER_SLOT_OFF = 648;
-const V1_ENGINE_BITMAP_OFF = 656;
+/** Number of whole account slots after the accounts offset. */
+function accountCapacity(layout: SlabLayout, dataLen: number): number {
+  const accountsEnd = dataLen - layout.accountsOff;
+  return accountsEnd <= 0 ? 0 : Math.floor(accountsEnd / layout.accountSize);
+}
 
 // For backward compatibility, export ENGINE_OFF and ENGINE_MARK_PRICE_OFF
 // (used by reinit-slab and other scripts). These refer to V1 layout.
-export const ENGINE_OFF = V1_ENGINE_OFF;
-export const ENGINE_MARK_PRICE_OFF = V1_ENGINE_MARK_PRICE_OFF;
-
-// ---- Known slab sizes per version and tier ----
-
-function computeSlabSize(
-  engineOff: number,
-  bitmapOff: number,
-  accountSize: number,
-  maxAccounts: number,
-): number {
-  const bitmapWords = Math.ceil(maxAccounts / 64);
-  const bitmapBytes = bitmapWords * 8;
-  const postBitmap = 18; // num_used(u16,2) + pad(6) + next_account_id(u64,8) + free_head(u16,2)
-  const nextFreeBytes = maxAccounts * 2;
-  const preAccountsLen = bitmapOff + bitmapBytes + postBitmap + nextFreeBytes;
-  const accountsOff = Math.ceil(preAccountsLen / 8) * 8;
-  return engineOff + accountsOff + maxAccounts * accountSize;
-}
-
-const TIERS = [64, 256, 1024, 4096] as const;
-
-// Pre-compute known slab sizes for fast lookup
-const V0_SIZES = new Map<number, number>();
-const V1_SIZES = new Map<number, number>();
-for (const n of TIERS) {
-  V0_SIZES.set(computeSlabSize(V0_ENGINE_OFF, V0_ENGINE_BITMAP_OFF, V0_ACCOUNT_SIZE, n), n);
-  V1_SIZES.set(computeSlabSize(V1_ENGINE_OFF, V1_ENGINE_BITMAP_OFF, V1_ACCOUNT_SIZE, n), n);
-}
-
-function buildLayout(version: 0 | 1, maxAccounts: number): SlabLayout {
-  const isV0 = version === 0;
-  const engineOff = isV0 ? V0_ENGINE_OFF : V1_ENGINE_OFF;
-  const bitmapOff = isV0 ? V0_ENGINE_BITMAP_OFF : V1_ENGINE_BITMAP_OFF;
-  const accountSize = isV0 ? V0_ACCOUNT_SIZE : V1_ACCOUNT_SIZE;
-  const bitmapWords = Math.ceil(maxAccounts / 64);
-  const bitmapBytes = bitmapWords * 8;
-  const postBitmap = 18;
-  const nextFreeBytes = maxAccounts * 2;
-  const preAccountsLen = bitmapOff + bitmapBytes + postBitmap + nextFreeBytes;
-  const accountsOffRel = Math.ceil(preAccountsLen / 8) * 8;
-
-  return {
-    version,
-    headerLen: isV0 ? V0_HEADER_LEN : V1_HEADER_LEN,
-    configOffset: isV0 ? V0_HEADER_LEN : V1_HEADER_LEN,
-    configLen: isV0 ? V0_CONFIG_LEN : V1_CONFIG_LEN,
-    reservedOff: isV0 ? V0_RESERVED_OFF : V1_RESERVED_OFF,
-    engineOff,
-    accountSize,
-    maxAccounts,
-    bitmapWords,
-    accountsOff: engineOff + accountsOffRel,
-
-    engineInsuranceOff: 16,
-    engineParamsOff: isV0 ? V0_ENGINE_PARAMS_OFF : V1_ENGINE_PARAMS_OFF,
-    paramsSize: isV0 ? V0_PARAMS_SIZE : V1_PARAMS_SIZE,
-    engineCurrentSlotOff: isV0 ? V0_ENGINE_CURRENT_SLOT_OFF : V1_ENGINE_CURRENT_SLOT_OFF,
-    engineFundingIndexOff: isV0 ? V0_ENGINE_FUNDING_INDEX_OFF : V1_ENGINE_FUNDING_INDEX_OFF,
-    engineLastFundingSlotOff: isV0 ? V0_ENGINE_LAST_FUNDING_SLOT_OFF : V1_ENGINE_LAST_FUNDING_SLOT_OFF,
-    engineFundingRateBpsOff: isV0 ? V0_ENGINE_FUNDING_RATE_BPS_OFF : V1_ENGINE_FUNDING_RATE_BPS_OFF,
-    engineMarkPriceOff: isV0 ? -1 : V1_ENGINE_MARK_PRICE_OFF,
-    engineLastCrankSlotOff: isV0 ? V0_ENGINE_LAST_CRANK_SLOT_OFF : V1_ENGINE_LAST_CRANK_SLOT_OFF,
-    engineMaxCrankStalenessOff: isV0 ? V0_ENGINE_MAX_CRANK_STALENESS_OFF : V1_ENGINE_MAX_CRANK_STALENESS_OFF,
-    engineTotalOiOff: isV0 ? V0_ENGINE_TOTAL_OI_OFF : V1_ENGINE_TOTAL_OI_OFF,
-    engineLongOiOff: isV0 ? -1 : V1_ENGINE_LONG_OI_OFF,
-    engineShortOiOff: isV0 ? -1 : V1_ENGINE_SHORT_OI_OFF,
-    engineCTotOff: isV0 ? V0_ENGINE_C_TOT_OFF : V1_ENGINE_C_TOT_OFF,
-    enginePnlPosTotOff: isV0 ? V0_ENGINE_PNL_POS_TOT_OFF : V1_ENGINE_PNL_POS_TOT_OFF,
-    engineLiqCursorOff: isV0 ? V0_ENGINE_LIQ_CURSOR_OFF : V1_ENGINE_LIQ_CURSOR_OFF,
-    engineGcCursorOff: isV0 ? V0_ENGINE_GC_CURSOR_OFF : V1_ENGINE_GC_CURSOR_OFF,
-    engineLastSweepStartOff: isV0 ? V0_ENGINE_LAST_SWEEP_START_OFF : V1_ENGINE_LAST_SWEEP_START_OFF,
-    engineLastSweepCompleteOff: isV0 ? V0_ENGINE_LAST_SWEEP_COMPLETE_OFF : V1_ENGINE_LAST_SWEEP_COMPLETE_OFF,
-    engineCrankCursorOff: isV0 ? V0_ENGINE_CRANK_CURSOR_OFF : V1_ENGINE_CRANK_CURSOR_OFF,
-    engineSweepStartIdxOff: isV0 ? V0_ENGINE_SWEEP_START_IDX_OFF : V1_ENGINE_SWEEP_START_IDX_OFF,
-    engineLifetimeLiquidationsOff: isV0 ? V0_ENGINE_LIFETIME_LIQUIDATIONS_OFF : V1_ENGINE_LIFETIME_LIQUIDATIONS_OFF,
-    engineLifetimeForceClosesOff: isV0 ? V0_ENGINE_LIFETIME_FORCE_CLOSES_OFF : V1_ENGINE_LIFETIME_FORCE_CLOSES_OFF,
-    engineNetLpPosOff: isV0 ? V0_ENGINE_NET_LP_POS_OFF : V1_ENGINE_NET_LP_POS_OFF,
-    engineLpSumAbsOff: isV0 ? V0_ENGINE_LP_SUM_ABS_OFF : V1_ENGINE_LP_SUM_ABS_OFF,
-    engineLpMaxAbsOff: isV0 ? V0_ENGINE_LP_MAX_ABS_OFF : V1_ENGINE_LP_MAX_ABS_OFF,
-    engineLpMaxAbsSweepOff: isV0 ? V0_ENGINE_LP_MAX_ABS_SWEEP_OFF : V1_ENGINE_LP_MAX_ABS_SWEEP_OFF,
-    engineEmergencyOiModeOff: isV0 ? -1 : V1_ENGINE_EMERGENCY_OI_MODE_OFF,
-    engineEmergencyStartSlotOff: isV0 ? -1 : V1_ENGINE_EMERGENCY_START_SLOT_OFF,
-    engineLastBreakerSlotOff: isV0 ? -1 : V1_ENGINE_LAST_BREAKER_SLOT_OFF,
-    engineBitmapOff: isV0 ? V0_ENGINE_BITMAP_OFF : V1_ENGINE_BITMAP_OFF,
-
-    hasInsuranceIsolation: !isV0,
-    engineInsuranceIsolatedOff: isV0 ? -1 : 48,
-    engineInsuranceIsolationBpsOff: isV0 ? -1 : 64,
-  };
-}
+export const ENGINE_OFF = specEngineOff(SLAB_LAYOUT_V1);
+export const ENGINE_MARK_PRICE_OFF = SLAB_LAYOUT_V1.engine.markPriceOff!;
 
 /**
  * Detect slab layout version from data length.
  * Returns a full SlabLayout descriptor or null if unrecognized.
+ *
+ * Length-only: prefer resolveSlabLayout(data) when the bytes are available,
+ * which also checks RiskParams.max_accounts and says why a slab could not be
+ * matched.
  */
 export function detectSlabLayout(dataLen: number): SlabLayout | null {
-  // Check V0 sizes first (deployed devnet program)
-  const v0n = V0_SIZES.get(dataLen);
-  if (v0n !== undefined) return buildLayout(0, v0n);
-
-  // Check V1 sizes (future upgraded program)
-  const v1n = V1_SIZES.get(dataLen);
-  if (v1n !== undefined) return buildLayout(1, v1n);
-
-  return null;
+  return layoutForDataSize(dataLen);
 }
 
 /**
@@ -360,6 +145,7 @@ const ACCT_MATCHER_CONTEXT_OFF = 152;
 const ACCT_OWNER_OFF = 184;
 const ACCT_FEE_CREDITS_OFF = 216;
 const ACCT_LAST_FEE_SLOT_OFF = 232;
+const ACCT_LAST_PARTIAL_LIQ_SLOT_OFF = 240; // V1 only
 
 // =============================================================================
 // Interfaces
@@ -503,6 +289,8 @@ export interface Account {
   owner: PublicKey;
   feeCredits: bigint;
   lastFeeSlot: bigint;
+  /** Slot of the last partial liquidation (V1 accounts only; undefined on V0) */
+  lastPartialLiquidationSlot?: bigint;
 }
 
 // =============================================================================
@@ -546,16 +334,17 @@ export function computeEffectiveOiCapBps(config: MarketConfig, currentSlot: bigi
 // Header helpers
 // =============================================================================
 
+// These and the parsers below throw UnknownSlabLayoutError for slabs no
+// registered layout matches, rather than guessing at offsets.
+
 export function readNonce(data: Uint8Array): bigint {
-  const layout = detectSlabLayout(data.length);
-  const roff = layout ? layout.reservedOff : V0_RESERVED_OFF;
+  const roff = requireSlabLayout(data).reservedOff;
   if (data.length < roff + 8) throw new Error("Slab data too short for nonce");
   return readU64LE(data, roff);
 }
 
 export function readLastThrUpdateSlot(data: Uint8Array): bigint {
-  const layout = detectSlabLayout(data.length);
-  const roff = layout ? layout.reservedOff : V0_RESERVED_OFF;
+  const roff = requireSlabLayout(data).reservedOff;
   if (data.length < roff + 16) throw new Error("Slab data too short for lastThrUpdateSlot");
   return readU64LE(data, roff + 8);
 }
@@ -565,11 +354,16 @@ export function readLastThrUpdateSlot(data: Uint8Array): bigint {
 // =============================================================================
 
 /**
- * Parse slab header (first 72 bytes — layout-independent).
+ * Parse slab header. The fixed fields are layout-independent; the reserved
+ * field (nonce, lastThrUpdateSlot) moves with the layout.
+ *
+ * @param data - Slab data (may be a partial slice; pass layoutHint in that case)
+ * @param layoutHint - Layout resolved from the real account size. If omitted, resolved from
+ *   data; throws UnknownSlabLayoutError when no registered layout matches.
  */
-export function parseHeader(data: Uint8Array): SlabHeader {
-  if (data.length < V0_HEADER_LEN) {
-    throw new Error(`Slab data too short for header: ${data.length} < ${V0_HEADER_LEN}`);
+export function parseHeader(data: Uint8Array, layoutHint?: SlabLayout): SlabHeader {
+  if (data.length < MIN_HEADER_LEN) {
+    throw new Error(`Slab data too short for header: ${data.length} < ${MIN_HEADER_LEN}`);
   }
 
   const magic = readU64LE(data, 0);
@@ -583,8 +377,7 @@ export function parseHeader(data: Uint8Array): SlabHeader {
   const admin = new PublicKey(data.subarray(16, 48));
 
   // Reserved field location depends on layout
-  const layout = detectSlabLayout(data.length);
-  const roff = layout ? layout.reservedOff : V0_RESERVED_OFF;
+  const roff = (layoutHint ?? requireSlabLayout(data)).reservedOff;
   const nonce = readU64LE(data, roff);
   const lastThrUpdateSlot = readU64LE(data, roff + 8);
 
@@ -607,12 +400,13 @@ export function parseHeader(data: Uint8Array): SlabHeader {
  * otherwise defaults are returned.
  *
  * @param data - Slab data (may be a partial slice for discovery; pass layoutHint in that case)
- * @param layoutHint - Pre-detected layout to use; if omitted, detected from data.length.
+ * @param layoutHint - Layout resolved from the real account size. If omitted, resolved from
+ *   data; throws UnknownSlabLayoutError when no registered layout matches.
  */
-export function parseConfig(data: Uint8Array, layoutHint?: SlabLayout | null): MarketConfig {
-  const layout = layoutHint !== undefined ? layoutHint : detectSlabLayout(data.length);
-  const configOff = layout ? layout.configOffset : V0_HEADER_LEN;
-  const configLen = layout ? layout.configLen : V0_CONFIG_LEN;
+export function parseConfig(data: Uint8Array, layoutHint?: SlabLayout): MarketConfig {
+  const layout = layoutHint ?? requireSlabLayout(data);
+  const configOff = layout.configOffset;
+  const configLen = layout.configLen;
 
   const minLen = configOff + Math.min(configLen, 120); // need at least basic fields
   if (data.length < minLen) {
@@ -824,14 +618,13 @@ export function parseConfig(data: Uint8Array, layoutHint?: SlabLayout | null): M
  * not present on-chain, so defaults (0) are returned.
  *
  * @param data - Slab data (may be a partial slice; pass layoutHint in that case)
- * @param layoutHint - Pre-detected layout to use; if omitted, detected from data.length.
+ * @param layoutHint - Layout resolved from the real account size. If omitted, resolved from
+ *   data; throws UnknownSlabLayoutError when no registered layout matches.
  */
-export function parseParams(data: Uint8Array, layoutHint?: SlabLayout | null): RiskParams {
-  const layout = layoutHint !== undefined ? layoutHint : detectSlabLayout(data.length);
-  const engineOff = layout ? layout.engineOff : V0_ENGINE_OFF;
-  const paramsOff = layout ? layout.engineParamsOff : V0_ENGINE_PARAMS_OFF;
-  const paramsSize = layout ? layout.paramsSize : V0_PARAMS_SIZE;
-  const base = engineOff + paramsOff;
+export function parseParams(data: Uint8Array, layoutHint?: SlabLayout): RiskParams {
+  const layout = layoutHint ?? requireSlabLayout(data);
+  const paramsSize = layout.paramsSize;
+  const base = layout.engineOff + layout.engineParamsOff;
 
   if (data.length < base + Math.min(paramsSize, 56)) {
     throw new Error("Slab data too short for RiskParams");
@@ -870,14 +663,24 @@ export function parseParams(data: Uint8Array, layoutHint?: SlabLayout | null): R
 
 /**
  * Parse RiskEngine state (excluding accounts array). Layout-version aware.
+ *
+ * @param data - Slab data (may be a partial slice for discovery; pass layoutHint in that case)
+ * @param layoutHint - Layout resolved from the real account size. If omitted, resolved from
+ *   data; throws UnknownSlabLayoutError when no registered layout matches.
  */
-export function parseEngine(data: Uint8Array): EngineState {
-  const layout = detectSlabLayout(data.length);
-  if (!layout) {
-    throw new Error(`Unrecognized slab data length: ${data.length}. Cannot determine layout version.`);
+export function parseEngine(data: Uint8Array, layoutHint?: SlabLayout): EngineState {
+  const layout = layoutHint ?? requireSlabLayout(data);
+  const base = layout.engineOff;
+
+  const minLen = base + layout.engineBitmapOff;
+  if (data.length < minLen) {
+    throw new Error(`Slab data too short for engine: ${data.length} < ${minLen}`);
   }
 
-  const base = layout.engineOff;
+  // num_used / next_account_id sit after the tier-sized bitmap and may fall
+  // outside a discovery slice.
+  const numUsedOff = layout.engineBitmapOff + layout.bitmapWords * 8;
+  const nextAccountIdOff = Math.ceil((numUsedOff + 2) / 8) * 8;
 
   return {
     vault: readU128LE(data, base),
@@ -930,15 +733,12 @@ export function parseEngine(data: Uint8Array): EngineState {
     markPriceE6: layout.engineMarkPriceOff >= 0
       ? readU64LE(data, base + layout.engineMarkPriceOff)
       : 0n,
-    numUsedAccounts: (() => {
-      const bw = layout.bitmapWords;
-      return readU16LE(data, base + layout.engineBitmapOff + bw * 8);
-    })(),
-    nextAccountId: (() => {
-      const bw = layout.bitmapWords;
-      const numUsedOff = layout.engineBitmapOff + bw * 8;
-      return readU64LE(data, base + Math.ceil((numUsedOff + 2) / 8) * 8);
-    })(),
+    numUsedAccounts: data.length >= base + numUsedOff + 2
+      ? readU16LE(data, base + numUsedOff)
+      : 0,
+    nextAccountId: data.length >= base + nextAccountIdOff + 8
+      ? readU64LE(data, base + nextAccountIdOff)
+      : 0n,
   };
 }
 
@@ -946,8 +746,7 @@ export function parseEngine(data: Uint8Array): EngineState {
  * Read bitmap to get list of used account indices.
  */
 export function parseUsedIndices(data: Uint8Array): number[] {
-  const layout = detectSlabLayout(data.length);
-  if (!layout) throw new Error(`Unrecognized slab data length: ${data.length}`);
+  const layout = requireSlabLayout(data);
 
   const base = layout.engineOff + layout.engineBitmapOff;
   if (data.length < base + layout.bitmapWords * 8) {
@@ -971,8 +770,7 @@ export function parseUsedIndices(data: Uint8Array): number[] {
  * Check if a specific account index is used.
  */
 export function isAccountUsed(data: Uint8Array, idx: number): boolean {
-  const layout = detectSlabLayout(data.length);
-  if (!layout) return false;
+  const layout = requireSlabLayout(data);
   if (!Number.isInteger(idx) || idx < 0 || idx >= layout.maxAccounts) return false;
   const base = layout.engineOff + layout.engineBitmapOff;
   const word = Math.floor(idx / 64);
@@ -983,23 +781,20 @@ export function isAccountUsed(data: Uint8Array, idx: number): boolean {
 
 /**
  * Calculate the maximum valid account index for a given slab size.
+ * Returns 0 for a size no registered layout produces.
  */
 export function maxAccountIndex(dataLen: number): number {
-  const layout = detectSlabLayout(dataLen);
-  if (!layout) return 0;
-  const accountsEnd = dataLen - layout.accountsOff;
-  if (accountsEnd <= 0) return 0;
-  return Math.floor(accountsEnd / layout.accountSize);
+  const layout = layoutForDataSize(dataLen);
+  return layout ? accountCapacity(layout, dataLen) : 0;
 }
 
 /**
  * Parse a single account by index.
  */
 export function parseAccount(data: Uint8Array, idx: number): Account {
-  const layout = detectSlabLayout(data.length);
-  if (!layout) throw new Error(`Unrecognized slab data length: ${data.length}`);
+  const layout = requireSlabLayout(data);
 
-  const maxIdx = maxAccountIndex(data.length);
+  const maxIdx = accountCapacity(layout, data.length);
   if (!Number.isInteger(idx) || idx < 0 || idx >= maxIdx) {
     throw new Error(`Account index out of range: ${idx} (max: ${maxIdx - 1})`);
   }
@@ -1012,7 +807,7 @@ export function parseAccount(data: Uint8Array, idx: number): Account {
   const kindByte = readU8(data, base + ACCT_KIND_OFF);
   const kind = kindByte === 1 ? AccountKind.LP : AccountKind.User;
 
-  return {
+  const account: Account = {
     kind,
     accountId: readU64LE(data, base + ACCT_ACCOUNT_ID_OFF),
     capital: readU128LE(data, base + ACCT_CAPITAL_OFF),
@@ -1029,6 +824,10 @@ export function parseAccount(data: Uint8Array, idx: number): Account {
     feeCredits: readI128LE(data, base + ACCT_FEE_CREDITS_OFF),
     lastFeeSlot: readU64LE(data, base + ACCT_LAST_FEE_SLOT_OFF),
   };
+  if (layout.accountSize > ACCT_LAST_PARTIAL_LIQ_SLOT_OFF) {
+    account.lastPartialLiquidationSlot = readU64LE(data, base + ACCT_LAST_PARTIAL_LIQ_SLOT_OFF);
+  }
+  return account;
 }
 
 /**
@@ -1036,7 +835,7 @@ export function parseAccount(data: Uint8Array, idx: number): Account {
  */
 export function parseAllAccounts(data: Uint8Array): { idx: number; account: Account }[] {
   const indices = parseUsedIndices(data);
-  const maxIdx = maxAccountIndex(data.length);
+  const maxIdx = accountCapacity(requireSlabLayout(data), data.length);
   const validIndices = indices.filter(idx => idx < maxIdx);
   return validIndices.map(idx => ({
     idx,
@@ -1044,3 +843,27 @@ export function parseAllAccounts(data: Uint8Array): { idx: number; account: Acco
   }));
 }
 
+/** Everything decodable from one slab image. */
+export interface SlabState {
+  header: SlabHeader;
+  config: MarketConfig;
+  params: RiskParams;
+  engine: EngineState;
+  accounts: { idx: number; account: Account }[];
+}
+
+/**
+ * Decode a full slab image in one pass, detecting the layout once. Used to
+ * inspect archived slab snapshots as well as live data.
+ */
+export function parseSlabState(data: Uint8Array): SlabState {
+  const layout = requireSlabLayout(data);
+  return {
+    header: parseHeader(data, layout),
+    config: parseConfig(data, layout),
+    params: parseParams(data, layout),
+    engine: parseEngine(data, layout),
+    accounts: parseAllAccounts(data),
+  };
+}
+
@@ -1,6 +1,6 @@
 /**
  * @module stake
- * Percolator Insurance LP Staking program — instruction encoders, PDA derivation, and account specs.
+ * Percolator Insurance LP Staking program — instruction encoders, PDA derivation, account specs, and pool analytics.
  *
  * Program: percolator-stake (dcccrypto/percolator-stake)
  * Deployed devnet: 4mJ8Cas... (TODO: confirm full address from devops)
@@ -380,6 +380,103 @@ export function decodeStakePool(data: Buffer | Uint8Array): StakePoolState {
   };
 }
 
+// ═══════════════════════════════════════════════════════════════
+// Pool Analytics — value, share price, tranches, HWM
+// ═══════════════════════════════════════════════════════════════
+
+/** Share prices carry 6 decimals: 1_000_000 = one collateral unit per LP token. */
+export const STAKE_SHARE_PRICE_SCALE = 1_000_000n;
+
+/**
+ * Collateral backing the pool's LP supply: deposits − withdrawals − flushed to
+ * insurance + returned from insurance, plus accrued fees in trading LP mode
+ * (poolMode 1). Clamped at zero.
+ */
+export function stakePoolValue(pool: StakePoolState): bigint {
+  let value = pool.totalDeposited - pool.totalWithdrawn - pool.totalFlushed + pool.totalReturned;
+  if (pool.poolMode === 1) value += pool.totalFeesEarned;
+  return value > 0n ? value : 0n;
+}
+
+/** Collateral per LP token (e6). 1:1 when nothing is minted. */
+export function stakeSharePriceE6(value: bigint, lpSupply: bigint): bigint {
+  if (lpSupply <= 0n) return STAKE_SHARE_PRICE_SCALE;
+  return (value * STAKE_SHARE_PRICE_SCALE) / lpSupply;
+}
+
+export interface StakeTrancheMetrics {
+  value: bigint;
+  lpSupply: bigint;
+  sharePriceE6: bigint;
+}
+
+export interface StakePoolMetrics {
+  value: bigint;
+  lpSupply: bigint;
+  sharePriceE6: bigint;
+  /** Whole pool when tranches are disabled */
+  senior: StakeTrancheMetrics;
+  /** null when tranches are disabled */
+  junior: StakeTrancheMetrics | null;
+  /** epochHighWaterTvl × hwmFloorBps; null when HWM protection is off */
+  hwmFloor: bigint | null;
+  hwmBreached: boolean;
+}
+
+/**
+ * Split a pool into senior and junior tranches and check its HWM floor.
+ *
+ * Junior balance and LP are part of the pool totals (PERC-303), so senior is
+ * the remainder. Junior is capped at the pool totals in case a flush has taken
+ * the pool below the junior balance (junior absorbs losses first).
+ */
+export function computeStakePoolMetrics(pool: StakePoolState): StakePoolMetrics {
+  const value = stakePoolValue(pool);
+  const lpSupply = pool.totalLpSupply;
+
+  let junior: StakeTrancheMetrics | null = null;
+  let senior: StakeTrancheMetrics = { value, lpSupply, sharePriceE6: stakeSharePriceE6(value, lpSupply) };
+  if (pool.trancheEnabled) {
+    const jValue = pool.juniorBalance < value ? pool.juniorBalance : value;
+    const jLp = pool.juniorTotalLp < lpSupply ? pool.juniorTotalLp : lpSupply;
+    junior = { value: jValue, lpSupply: jLp, sharePriceE6: stakeSharePriceE6(jValue, jLp) };
+    senior = {
+      value: value - jValue,
+      lpSupply: lpSupply - jLp,
+      sharePriceE6: stakeSharePriceE6(value - jValue, lpSupply - jLp),
+    };
+  }
+
+  const hwmFloor = pool.hwmEnabled ? (pool.epochHighWaterTvl * BigInt(pool.hwmFloorBps)) / 10_000n : null;
+  return {
+    value,
+    lpSupply,
+    sharePriceE6: stakeSharePriceE6(value, lpSupply),
+    senior,
+    junior,
+    hwmFloor,
+    hwmBreached: hwmFloor !== null && hwmFloor > 0n && value < hwmFloor,
+  };
+}
+
+/**
+ * Split fees between tranches. Junior capital earns `juniorFeeMultBps / 10_000`
+ * times what the same senior capital earns:
+ *   junior = fees × J·m / (S + J·m), senior = the rest.
+ */
+export function splitStakeTrancheFees(
+  fees: bigint,
+  seniorValue: bigint,
+  juniorValue: bigint,
+  juniorFeeMultBps: number,
+): { senior: bigint; junior: bigint } {
+  const weightedJunior = juniorValue * BigInt(juniorFeeMultBps);
+  const denom = seniorValue * 10_000n + weightedJunior;
+  if (denom === 0n) return { senior: fees, junior: 0n };
+  const junior = (fees * weightedJunior) / denom;
+  return { senior: fees - junior, junior };
+}
+
 // ═══════════════════════════════════════════════════════════════
 // Account Specs (for building TransactionInstructions)
 // ═══════════════════════════════════════════════════════════════
@@ -0,0 +1,225 @@
+import { describe, it, expect } from "vitest";
+import { PublicKey, type ParsedTransactionWithMeta } from "@solana/web3.js";
+import {
+  encodeInitMarket, encodeInitUser, encodeDepositCollateral, encodeKeeperCrank,
+  encodeTradeNoCpi, encodeTradeCpiV2, encodeLiquidateAtOracle, encodeUpdateConfig,
+  encodePushOraclePrice, encodeUpdateRiskParams, encodeSetPythOracle, encodeCloseSlab,
+  encodeSetInsuranceIsolation, IX_TAG,
+} from "../src/abi/instructions.js";
+import {
+  decodeInstructionData, resolveInstructionAccounts, InstructionDecodeError, ixNameForTag,
+} from "../src/abi/decode.js";
+import { decodeTransaction, parseLogValues } from "../src/abi/events.js";
+
+const ALPHABET = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+function toBase58(bytes: Uint8Array): string {
+  let n = 0n;
+  for (const b of bytes) n = (n << 8n) | BigInt(b);
+  let out = "";
+  while (n > 0n) {
+    out = ALPHABET[Number(n % 58n)] + out;
+    n /= 58n;
+  }
+  for (const b of bytes) {
+    if (b !== 0) break;
+    out = "1" + out;
+  }
+  return out;
+}
+
+describe("decodeInstructionData", () => {
+  it("inverts the trade encoders, including negative sizes", () => {
+    const d = decodeInstructionData(encodeTradeNoCpi({ lpIdx: 0, userIdx: 7, size: "-1500000" }));
+    expect(d.name).toBe("TradeNoCpi");
+    if (d.name !== "TradeNoCpi") throw new Error("unreachable");
+    expect(d.args).toEqual({ lpIdx: 0, userIdx: 7, size: -1_500_000n });
+
+    const v2 = decodeInstructionData(encodeTradeCpiV2({ lpIdx: 1, userIdx: 2, size: 10n ** 30n, bump: 254 }));
+    expect(v2).toEqual({ tag: IX_TAG.TradeCpiV2, name: "TradeCpiV2", args: { lpIdx: 1, userIdx: 2, size: 10n ** 30n, bump: 254 } });
+  });
+
+  it("round-trips InitMarket", () => {
+    const admin = PublicKey.unique();
+    const mint = PublicKey.unique();
+    const args = {
+      admin, collateralMint: mint, indexFeedId: "ab".repeat(32),
+      maxStalenessSecs: 60n, confFilterBps: 200, invert: 1, unitScale: 1000,
+      initialMarkPriceE6: 1_000_000n, warmupPeriodSlots: 100n, maintenanceMarginBps: 500n,
+      initialMarginBps: 1000n, tradingFeeBps: 10n, maxAccounts: 256n, newAccountFee: 1_000_000n,
+      riskReductionThreshold: 0n, maintenanceFeePerSlot: 0n, maxCrankStalenessSlots: 200n,
+      liquidationFeeBps: 100n, liquidationFeeCap: 1_000_000_000n, liquidationBufferBps: 50n,
+      minLiquidationAbs: 1n << 100n,
+    };
+    const d = decodeInstructionData(encodeInitMarket(args));
+    if (d.name !== "InitMarket") throw new Error(`got ${d.name}`);
+    expect(d.args.admin.equals(admin)).toBe(true);
+    expect(d.args.collateralMint.equals(mint)).toBe(true);
+    expect({ ...d.args, admin, collateralMint: mint }).toEqual(args);
+  });
+
+  it("round-trips UpdateConfig with signed fields", () => {
+    const args = {
+      fundingHorizonSlots: 500n, fundingKBps: 100n, fundingInvScaleNotionalE6: 10n ** 12n,
+      fundingMaxPremiumBps: -500n, fundingMaxBpsPerSlot: -5n, threshFloor: 0n, threshRiskBps: 50n,
+      threshUpdateIntervalSlots: 10n, threshStepBps: 1n, threshAlphaBps: 1000n, threshMin: 0n,
+      threshMax: 10n ** 18n, threshMinStep: 1n,
+    };
+    expect(decodeInstructionData(encodeUpdateConfig(args)).args).toEqual(args);
+  });
+
+  it("handles the optional UpdateRiskParams fee field", () => {
+    expect(decodeInstructionData(encodeUpdateRiskParams({ initialMarginBps: "1000", maintenanceMarginBps: "500" })).args)
+      .toEqual({ initialMarginBps: 1000n, maintenanceMarginBps: 500n });
+    expect(decodeInstructionData(encodeUpdateRiskParams({ initialMarginBps: 1000n, maintenanceMarginBps: 500n, tradingFeeBps: 25n })).args)
+      .toEqual({ initialMarginBps: 1000n, maintenanceMarginBps: 500n, tradingFeeBps: 25n });
+  });
+
+  it("decodes the remaining simple layouts", () => {
+    expect(decodeInstructionData(encodeInitUser({ feePayment: "1000000" })).args).toEqual({ feePayment: 1_000_000n });
+    expect(decodeInstructionData(encodeDepositCollateral({ userIdx: 3, amount: 5n })).args).toEqual({ userIdx: 3, amount: 5n });
+    expect(decodeInstructionData(encodeKeeperCrank({ callerIdx: 65535, allowPanic: true })).args).toEqual({ callerIdx: 65535, allowPanic: true });
+    expect(decodeInstructionData(encodeLiquidateAtOracle({ targetIdx: 9 })).args).toEqual({ targetIdx: 9 });
+    expect(decodeInstructionData(encodePushOraclePrice({ priceE6: 42n, timestamp: -1n })).args).toEqual({ priceE6: 42n, timestamp: -1n });
+    expect(decodeInstructionData(encodeSetInsuranceIsolation({ bps: 2500 })).args).toEqual({ bps: 2500 });
+    expect(decodeInstructionData(encodeCloseSlab())).toEqual({ tag: IX_TAG.CloseSlab, name: "CloseSlab", args: {} });
+
+    const feedId = new Uint8Array(32).fill(7);
+    expect(decodeInstructionData(encodeSetPythOracle({ feedId, maxStalenessSecs: 30n, confFilterBps: 50 })).args)
+      .toEqual({ feedId, maxStalenessSecs: 30n, confFilterBps: 50 });
+  });
+
+  it("names tags without an encoder and flags unassigned tags", () => {
+    expect(decodeInstructionData(new Uint8Array([IX_TAG.ChallengeSettlement, 1, 2]))).toEqual({
+      tag: 43, name: "ChallengeSettlement", args: null,
+    });
+    expect(decodeInstructionData(new Uint8Array([200]))).toEqual({ tag: 200, name: "Unknown", args: null });
+    expect(ixNameForTag(47)).toBeNull();
+  });
+
+  it("rejects truncated data", () => {
+    const data = encodeDepositCollateral({ userIdx: 1, amount: 1n }).slice(0, 6);
+    expect(() => decodeInstructionData(data)).toThrow(InstructionDecodeError);
+    expect(() => decodeInstructionData(new Uint8Array(0))).toThrow(InstructionDecodeError);
+  });
+});
+
+describe("resolveInstructionAccounts", () => {
+  it("names accounts by spec and keeps extras", () => {
+    const keys = Array.from({ length: 5 }, () => PublicKey.unique());
+    const { named, remaining } = resolveInstructionAccounts("UpdateHyperpMark", keys);
+    expect(named.slab.equals(keys[0])).toBe(true);
+    expect(named.dexPool.equals(keys[1])).toBe(true);
+    expect(remaining).toEqual(keys.slice(3));
+  });
+
+  it("leaves accounts positional for instructions without a spec", () => {
+    const keys = [PublicKey.unique()];
+    expect(resolveInstructionAccounts("Unknown", keys)).toEqual({ named: {}, remaining: keys });
+  });
+});
+
+describe("parseLogValues", () => {
+  it("accepts hex and decimal integer lists only", () => {
+    expect(parseLogValues("0x1, 0x2, 0x0, 0xf4240, 0x0")).toEqual([1n, 2n, 0n, 1_000_000n, 0n]);
+    expect(parseLogValues("12, -3")).toEqual([12n, -3n]);
+    expect(parseLogValues("Instruction: Trade")).toBeNull();
+  });
+});
+
+describe("decodeTransaction", () => {
+  const PROGRAM = PublicKey.unique();
+  const MATCHER = PublicKey.unique();
+  const user = PublicKey.unique();
+  const slab = PublicKey.unique();
+  const oracle = PublicKey.unique();
+  const lpPda = PublicKey.unique();
+
+  function tx(overrides: { err?: unknown; logs: string[] }): ParsedTransactionWithMeta {
+    const computeBudget = { programId: new PublicKey("ComputeBudget111111111111111111111111111111"), accounts: [], data: "3" };
+    const crank = {
+      programId: PROGRAM,
+      accounts: [user, slab, PublicKey.unique(), oracle],
+      data: toBase58(encodeKeeperCrank({ callerIdx: 65535, allowPanic: false })),
+    };
+    const viaMatcher = { programId: MATCHER, accounts: [user, slab], data: "1" };
+    const innerTrade = {
+      programId: PROGRAM,
+      accounts: [user, PublicKey.unique(), slab, oracle, MATCHER, PublicKey.unique(), lpPda],
+      data: toBase58(encodeTradeCpiV2({ lpIdx: 0, userIdx: 4, size: 2_000_000n, bump: 255 })),
+    };
+    return {
+      slot: 1234,
+      blockTime: 1_700_000_000,
+      transaction: {
+        signatures: ["sig1"],
+        message: { accountKeys: [], recentBlockhash: "", instructions: [computeBudget, crank, viaMatcher] },
+      },
+      meta: {
+        err: overrides.err ?? null,
+        fee: 5000,
+        preBalances: [],
+        postBalances: [],
+        logMessages: overrides.logs,
+        innerInstructions: [{ index: 2, instructions: [innerTrade] }],
+      },
+    } as unknown as ParsedTransactionWithMeta;
+  }
+
+  it("decodes top-level and CPI instructions with their own logs", () => {
+    const decoded = decodeTransaction(
+      tx({
+        logs: [
+          "Program ComputeBudget111111111111111111111111111111 invoke [1]",
+          "Program ComputeBudget111111111111111111111111111111 success",
+          `Program ${PROGRAM.toBase58()} invoke [1]`,
+          "Program log: Instruction: KeeperCrank",
+          "Program log: 0x5, 0x0, 0x0, 0x0, 0x0",
+          `Program ${PROGRAM.toBase58()} success`,
+          `Program ${MATCHER.toBase58()} invoke [1]`,
+          "Program log: 0x63, 0x63",
+          `Program ${PROGRAM.toBase58()} invoke [2]`,
+          "Program log: 0x1e8480, 0xf4240, 0x0, 0x0, 0x0",
+          `Program ${PROGRAM.toBase58()} success`,
+          `Program ${MATCHER.toBase58()} success`,
+        ],
+      }),
+      [PROGRAM.toBase58()],
+    );
+
+    expect(decoded.success).toBe(true);
+    expect(decoded.signature).toBe("sig1");
+    expect(decoded.instructions.map((i) => i.name)).toEqual(["KeeperCrank", "TradeCpiV2"]);
+
+    const [crank, trade] = decoded.instructions;
+    expect(crank.outerIndex).toBe(1);
+    expect(crank.innerIndex).toBeNull();
+    expect(crank.values).toEqual([[5n, 0n, 0n, 0n, 0n]]);
+    expect(crank.accounts.oracle.equals(oracle)).toBe(true);
+
+    expect(trade.outerIndex).toBe(2);
+    expect(trade.innerIndex).toBe(0);
+    // The matcher's own numeric log must not leak into the percolator frame
+    expect(trade.values).toEqual([[2_000_000n, 1_000_000n, 0n, 0n, 0n]]);
+    expect(trade.accounts.lpPda.equals(lpPda)).toBe(true);
+    if (trade.name !== "TradeCpiV2") throw new Error("unreachable");
+    expect(trade.args.size).toBe(2_000_000n);
+  });
+
+  it("reports the program error of a failed transaction", () => {
+    const decoded = decodeTransaction(
+      tx({
+        err: { InstructionError: [1, { Custom: 14 }] },
+        logs: [
+          `Program ${PROGRAM.toBase58()} invoke [1]`,
+          `Program ${PROGRAM.toBase58()} failed: custom program error: 0xe`,
+        ],
+      }),
+      [PROGRAM],
+    );
+    expect(decoded.success).toBe(false);
+    expect(decoded.error?.name).toBe("EngineUndercollateralized");
+    // The trade never ran, so it has no logs, but it still decodes
+    expect(decoded.instructions[1].logs).toEqual([]);
+  });
+});
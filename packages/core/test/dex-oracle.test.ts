@@ -1,5 +1,8 @@
 /**
  * DEX Oracle tests — PumpSwap, Raydium CLMM, Meteora DLMM
+ *
+ * Orca Whirlpool and Raydium CPMM/AMM v4 are covered by captured-account
+ * fixtures in dex-pools.test.ts.
  */
 import { PublicKey } from "@solana/web3.js";
 import {
@@ -11,6 +14,9 @@ import {
   PUMPSWAP_PROGRAM_ID,
   RAYDIUM_CLMM_PROGRAM_ID,
   METEORA_DLMM_PROGRAM_ID,
+  ORCA_WHIRLPOOL_PROGRAM_ID,
+  RAYDIUM_CPMM_PROGRAM_ID,
+  RAYDIUM_AMM_V4_PROGRAM_ID,
 } from "../src/solana/pda.js";
 
 function assert(cond: boolean, msg: string): void {
@@ -67,6 +73,9 @@ console.log("--- detectDexType ---");
 assert(detectDexType(PUMPSWAP_PROGRAM_ID) === "pumpswap", "detect pumpswap");
 assert(detectDexType(RAYDIUM_CLMM_PROGRAM_ID) === "raydium-clmm", "detect raydium-clmm");
 assert(detectDexType(METEORA_DLMM_PROGRAM_ID) === "meteora-dlmm", "detect meteora-dlmm");
+assert(detectDexType(ORCA_WHIRLPOOL_PROGRAM_ID) === "orca-whirlpool", "detect orca-whirlpool");
+assert(detectDexType(RAYDIUM_CPMM_PROGRAM_ID) === "raydium-cpmm", "detect raydium-cpmm");
+assert(detectDexType(RAYDIUM_AMM_V4_PROGRAM_ID) === "raydium-amm-v4", "detect raydium-amm-v4");
 assert(detectDexType(PublicKey.default) === null, "detect unknown returns null");
 assert(detectDexType(new PublicKey("11111111111111111111111111111111")) === null, "detect system program returns null");
 
@@ -0,0 +1,155 @@
+import { describe, it, expect } from "vitest";
+import { readdirSync, readFileSync } from "node:fs";
+import { join } from "node:path";
+import { fileURLToPath } from "node:url";
+import { PublicKey } from "@solana/web3.js";
+import {
+  detectDexType,
+  parseDexPool,
+  computeDexSpotPriceE6,
+  computeDexPoolDepth,
+  dexRequiresVaultData,
+  dexSpotPriceIsDecimalAdjusted,
+  type DexType,
+} from "../src/solana/dex-oracle.js";
+
+/**
+ * Pool account fixtures for the DEX readers. Each fixture is the pool account
+ * (and, for reserve-priced pools, its two vault token accounts) as sparse
+ * writes over a zeroed buffer. Refresh from mainnet with
+ * `npx tsx scripts/capture-dex-fixture.ts <POOL>`, which writes the full
+ * account at offset 0.
+ */
+const FIXTURES_DIR = join(fileURLToPath(new URL(".", import.meta.url)), "fixtures/dex");
+
+interface AccountImage {
+  dataLen: number;
+  writes: { offset: number; hex: string }[];
+}
+
+interface Fixture extends AccountImage {
+  description: string;
+  dexType: DexType;
+  address: string;
+  owner: string;
+  vaults?: { base: AccountImage; quote: AccountImage };
+  expected: {
+    baseMint: string;
+    quoteMint: string;
+    baseVault?: string;
+    quoteVault?: string;
+    priceE6: string;
+  };
+}
+
+function build(image: AccountImage): Uint8Array {
+  const data = new Uint8Array(image.dataLen);
+  for (const w of image.writes) {
+    data.set(Buffer.from(w.hex, "hex"), w.offset);
+  }
+  return data;
+}
+
+const fixtures = readdirSync(FIXTURES_DIR)
+  .filter((f) => f.endsWith(".json"))
+  .sort()
+  .map((f) => [f, JSON.parse(readFileSync(join(FIXTURES_DIR, f), "utf8")) as Fixture] as const);
+
+describe("DEX pool fixtures", () => {
+  it("covers every newly supported pool type", () => {
+    const types = fixtures.map(([, f]) => f.dexType);
+    for (const t of ["orca-whirlpool", "raydium-cpmm", "raydium-amm-v4"] as const) expect(types).toContain(t);
+  });
+
+  for (const [file, f] of fixtures) {
+    describe(file, () => {
+      const data = build(f);
+      const address = new PublicKey(f.address);
+      const vaultData = f.vaults ? { base: build(f.vaults.base), quote: build(f.vaults.quote) } : undefined;
+
+      it("is detected from its owner program", () => {
+        expect(detectDexType(new PublicKey(f.owner))).toBe(f.dexType);
+      });
+
+      it("parses mints and vaults", () => {
+        const info = parseDexPool(f.dexType, address, data);
+        expect(info.dexType).toBe(f.dexType);
+        expect(info.poolAddress.equals(address)).toBe(true);
+        expect(info.baseMint.toBase58()).toBe(f.expected.baseMint);
+        expect(info.quoteMint.toBase58()).toBe(f.expected.quoteMint);
+        expect(info.baseVault?.toBase58()).toBe(f.expected.baseVault);
+        expect(info.quoteVault?.toBase58()).toBe(f.expected.quoteVault);
+      });
+
+      it("computes the spot price", () => {
+        expect(dexRequiresVaultData(f.dexType)).toBe(vaultData !== undefined);
+        expect(computeDexSpotPriceE6(f.dexType, data, vaultData).toString()).toBe(f.expected.priceE6);
+      });
+
+      it("rejects truncated pool data", () => {
+        expect(() => parseDexPool(f.dexType, address, data.slice(0, 100))).toThrow(/too short/);
+        expect(() => computeDexSpotPriceE6(f.dexType, data.slice(0, 100), vaultData)).toThrow(/too short/);
+      });
+    });
+  }
+});
+
+describe("new pool types", () => {
+  const load = (name: string) => fixtures.find(([file]) => file === name)![1];
+
+  it("requires vault data for reserve-priced pools", () => {
+    const f = load("raydium-cpmm-sol-usdc.json");
+    expect(() => computeDexSpotPriceE6("raydium-cpmm", build(f))).toThrow(/requires vaultData/);
+    const g = load("raydium-amm-v4-sol-usdc.json");
+    expect(() => computeDexSpotPriceE6("raydium-amm-v4", build(g))).toThrow(/requires vaultData/);
+  });
+
+  it("prices Orca as a raw atom ratio and Raydium pools decimal-adjusted", () => {
+    expect(dexSpotPriceIsDecimalAdjusted("orca-whirlpool")).toBe(false);
+    expect(dexSpotPriceIsDecimalAdjusted("raydium-cpmm")).toBe(true);
+    expect(dexSpotPriceIsDecimalAdjusted("raydium-amm-v4")).toBe(true);
+
+    // SOL (9 decimals) / USDC (6): ~0.15 USDC atoms per lamport → ~$150
+    const f = load("orca-whirlpool-sol-usdc.json");
+    const raw = computeDexSpotPriceE6("orca-whirlpool", build(f));
+    expect(Number(raw * 1_000n) / 1e6).toBeCloseTo(150, 2);
+  });
+
+  it("excludes CPMM accrued fees from reserves", () => {
+    const f = load("raydium-cpmm-sol-usdc.json");
+    const data = build(f);
+    const vaults = { base: build(f.vaults!.base), quote: build(f.vaults!.quote) };
+    // Clearing the fee counters leaves the fees counted as liquidity, moving the price
+    data.fill(0, 341, 373);
+    expect(computeDexSpotPriceE6("raydium-cpmm", data, vaults).toString()).not.toBe(f.expected.priceE6);
+  });
+
+  it("reads swappable reserves net of fees and owed PnL", () => {
+    const f = load("raydium-cpmm-sol-usdc.json");
+    expect(computeDexPoolDepth("raydium-cpmm", build(f), { base: build(f.vaults!.base), quote: build(f.vaults!.quote) })).toEqual({
+      model: "reserves",
+      baseReserve: 1_000_000_000_000n,
+      quoteReserve: 150_000_000_000n,
+    });
+    const g = load("raydium-amm-v4-sol-usdc.json");
+    const amm = computeDexPoolDepth("raydium-amm-v4", build(g), { base: build(g.vaults!.base), quote: build(g.vaults!.quote) });
+    expect(amm?.baseReserve).toBe(40_000_000_000_000n);
+    expect(amm?.quoteReserve).toBe(6_200_000_000_000n);
+  });
+
+  it("reads Orca active liquidity as virtual reserves at the current price", () => {
+    const depth = computeDexPoolDepth("orca-whirlpool", build(load("orca-whirlpool-sol-usdc.json")))!;
+    expect(depth.model).toBe("active-liquidity");
+    // Both sides are worth the same at spot: ~131.7k SOL against ~$19.75M
+    const solValue = (Number(depth.baseReserve) / 1e9) * 150;
+    const usdcValue = Number(depth.quoteReserve) / 1e6;
+    expect(usdcValue).toBeGreaterThan(19_000_000);
+    expect(solValue / usdcValue).toBeCloseTo(1, 3);
+  });
+
+  it("returns zero for an empty Orca pool", () => {
+    const data = build(load("orca-whirlpool-sol-usdc.json"));
+    data.fill(0, 65, 81);
+    expect(computeDexSpotPriceE6("orca-whirlpool", data)).toBe(0n);
+  });
+});
@@ -1,6 +1,8 @@
-import { describe, it, expect } from "vitest";
+import { describe, it, expect, vi } from "vitest";
+import { PublicKey, type Connection } from "@solana/web3.js";
 import {
   SLAB_TIERS,
+  discoverMarkets,
   slabDataSize,
   type SlabTierKey,
 } from "../src/solana/discovery.js";
@@ -95,3 +97,37 @@ describe("slabDataSize", () => {
     expect(slabDataSize(256)).toBe(62808);
   });
 });
+
+// ============================================================================
+// discoverMarkets
+// ============================================================================
+
+describe("discoverMarkets", () => {
+  it("reads V1 slices at the parser's offsets (params at engine+72, currentSlot at engine+360)", async () => {
+    // Header + config + engine prefix of a small V1 slab, as returned by the dataSlice
+    const slice = new Uint8Array(1940);
+    const dv = new DataView(slice.buffer);
+    dv.setBigUint64(0, 0x504552434f4c4154n, true);
+    dv.setUint32(8, 1, true);
+    dv.setBigUint64(80, 7n, true); // nonce (V1 reserved field)
+    const engine = 640;
+    dv.setBigUint64(engine + 72 + 8, 500n, true); // maintenanceMarginBps
+    dv.setBigUint64(engine + 360, 123_456n, true); // currentSlot
+    dv.setBigUint64(engine + 400, 150_000_000n, true); // markPrice
+
+    const slab = PublicKey.unique();
+    const connection = {
+      getProgramAccounts: vi.fn(async (_program: PublicKey, opts: { filters: { dataSize?: number }[] }) =>
+        opts.filters[0].dataSize === SLAB_TIERS.small.dataSize ? [{ pubkey: slab, account: { data: slice } }] : [],
+      ),
+    } as unknown as Connection;
+
+    const markets = await discoverMarkets(connection, PublicKey.unique());
+
+    expect(markets).toHaveLength(1);
+    expect(markets[0].header.nonce).toBe(7n);
+    expect(markets[0].params.maintenanceMarginBps).toBe(500n);
+    expect(markets[0].engine.currentSlot).toBe(123_456n);
+    expect(markets[0].engine.markPriceE6).toBe(150_000_000n);
+  });
+});
@@ -0,0 +1,173 @@
+import { describe, it, expect } from "vitest";
+import { PublicKey } from "@solana/web3.js";
+import { computeAccountHealth } from "../src/math/health.js";
+import { AccountKind, type Account, type EngineState, type RiskParams } from "../src/solana/slab.js";
+
+const P = (usd: number) => BigInt(usd) * 1_000_000n;
+
+function account(extra: Partial<Account> = {}): Account {
+  return {
+    kind: AccountKind.User,
+    accountId: 1n,
+    capital: 100_000_000n,
+    pnl: 0n,
+    reservedPnl: 0n,
+    warmupStartedAtSlot: 0n,
+    warmupSlopePerStep: 0n,
+    positionSize: 0n,
+    entryPrice: 0n,
+    fundingIndex: 0n,
+    matcherProgram: PublicKey.default,
+    matcherContext: PublicKey.default,
+    owner: PublicKey.default,
+    feeCredits: 0n,
+    lastFeeSlot: 0n,
+    ...extra,
+  };
+}
+
+const params: RiskParams = {
+  warmupPeriodSlots: 0n,
+  maintenanceMarginBps: 500n,
+  initialMarginBps: 1000n,
+  tradingFeeBps: 10n,
+  maxAccounts: 64n,
+  newAccountFee: 0n,
+  riskReductionThreshold: 0n,
+  maintenanceFeePerSlot: 0n,
+  maxCrankStalenessSlots: 0n,
+  liquidationFeeBps: 100n,
+  liquidationFeeCap: 0n,
+  liquidationBufferBps: 0n,
+  minLiquidationAbs: 0n,
+};
+
+function engine(overrides: Partial<EngineState> = {}): EngineState {
+  return {
+    vault: 0n,
+    insuranceFund: { balance: 0n, feeRevenue: 0n, isolatedBalance: 0n, isolationBps: 0 },
+    currentSlot: 1_000n,
+    fundingIndexQpbE6: 0n,
+    lastFundingSlot: 0n,
+    fundingRateBpsPerSlotLast: 0n,
+    lastCrankSlot: 0n,
+    maxCrankStalenessSlots: 0n,
+    totalOpenInterest: 0n,
+    longOi: 0n,
+    shortOi: 0n,
+    cTot: 0n,
+    pnlPosTot: 0n,
+    liqCursor: 0,
+    gcCursor: 0,
+    lastSweepStartSlot: 0n,
+    lastSweepCompleteSlot: 0n,
+    crankCursor: 0,
+    sweepStartIdx: 0,
+    lifetimeLiquidations: 0n,
+    lifetimeForceCloses: 0n,
+    netLpPos: 0n,
+    lpSumAbs: 0n,
+    lpMaxAbs: 0n,
+    lpMaxAbsSweep: 0n,
+    emergencyOiMode: false,
+    emergencyStartSlot: 0n,
+    lastBreakerSlot: 0n,
+    numUsedAccounts: 1,
+    nextAccountId: 2n,
+    markPriceE6: 0n,
+    ...overrides,
+  };
+}
+
+describe("computeAccountHealth", () => {
+  it("reports a flat account as its capital with no requirements", () => {
+    const h = computeAccountHealth(account(), engine(), params, P(1));
+    expect(h.equity).toBe(100_000_000n);
+    expect(h.notional).toBe(0n);
+    expect(h.maintenanceMargin).toBe(0n);
+    expect(h.marginRatioBps).toBeNull();
+    expect(h.liquidationPriceE6).toBe(0n);
+    expect(h.liquidatable).toBe(false);
+  });
+
+  it("includes mark PnL and realized pnl in equity", () => {
+    const a = account({ positionSize: 500_000_000n, entryPrice: P(1), pnl: -5_000_000n });
+    const h = computeAccountHealth(a, engine(), params, 1_100_000n);
+    // (1.1 - 1.0) * 500 / 1.1 ≈ 45.45 tokens
+    expect(h.unrealizedPnl).toBe(45_454_545n);
+    expect(h.equity).toBe(100_000_000n - 5_000_000n + 45_454_545n);
+    expect(h.notional).toBe(550_000_000n);
+    expect(h.maintenanceMargin).toBe(27_500_000n);
+    expect(h.initialMargin).toBe(55_000_000n);
+    expect(h.bufferToLiquidation).toBe(h.equity - 27_500_000n);
+    expect(h.liquidatable).toBe(false);
+  });
+
+  it("flags an undercollateralized position", () => {
+    // Same scenario the keeper's liquidation scan is tested against:
+    // 100 tokens backing 10,000 at $1 is a 1% margin ratio.
+    const a = account({ positionSize: 10_000_000_000n, entryPrice: P(1) });
+    const h = computeAccountHealth(a, engine(), params, P(1));
+    expect(h.marginRatioBps).toBe(100n);
+    expect(h.bufferToLiquidation).toBeLessThan(0n);
+    expect(h.liquidatable).toBe(true);
+  });
+
+  it("deducts unsettled funding from equity", () => {
+    // Longs owe: engine index moved 0.01 quote per base since the account settled
+    const a = account({ positionSize: 1_000_000_000n, entryPrice: P(1), fundingIndex: 0n });
+    const h = computeAccountHealth(a, engine({ fundingIndexQpbE6: 10_000n }), params, P(1));
+    expect(h.pendingFunding).toBe(10_000_000n);
+    expect(h.equity).toBe(90_000_000n);
+
+    const short = account({ positionSize: -1_000_000_000n, entryPrice: P(1) });
+    const hs = computeAccountHealth(short, engine({ fundingIndexQpbE6: 10_000n }), params, P(1));
+    expect(hs.pendingFunding).toBe(-10_000_000n);
+    expect(hs.equity).toBe(110_000_000n);
+  });
+
+  it("reports capital still locked by warmup", () => {
+    const a = account({ warmupStartedAtSlot: 900n });
+    const h = computeAccountHealth(a, engine(), { ...params, warmupPeriodSlots: 400n }, P(1));
+    // 100 of 400 slots elapsed → 75% still locked
+    expect(h.warmupLockedCapital).toBe(75_000_000n);
+    const later = computeAccountHealth(a, engine(), { ...params, warmupPeriodSlots: 400n }, P(1), 1_300n);
+    expect(later.warmupLockedCapital).toBe(0n);
+  });
+
+  it("moves the liquidation price with realized losses", () => {
+    const a = account({ positionSize: 1_000_000_000n, entryPrice: P(1) });
+    const base = computeAccountHealth(a, engine(), params, P(1));
+    const worse = computeAccountHealth({ ...a, pnl: -50_000_000n }, engine(), params, P(1));
+    expect(base.liquidationPriceE6).toBe(950_124n);
+    expect(worse.liquidationPriceE6).toBeGreaterThan(base.liquidationPriceE6);
+  });
+
+  it("puts the liquidation price exactly where liquidatable flips", () => {
+    const cases: [string, Account, EngineState][] = [
+      ["long", account({ positionSize: 1_000_000_000n, entryPrice: P(1) }), engine()],
+      ["short", account({ positionSize: -1_000_000_000n, entryPrice: P(1) }), engine()],
+      ["long with losses and funding owed", account({ positionSize: 2_000_000_000n, entryPrice: P(1), pnl: -20_000_000n }), engine({ fundingIndexQpbE6: 5_000n })],
+      ["short with funding owed", account({ positionSize: -2_000_000_000n, entryPrice: P(1), fundingIndex: 5_000n }), engine()],
+    ];
+    for (const [name, a, e] of cases) {
+      const liq = computeAccountHealth(a, e, params, P(1)).liquidationPriceE6;
+      const past = a.positionSize > 0n ? liq + 1n : liq - 1n;
+      const at = computeAccountHealth(a, e, params, liq);
+      const next = computeAccountHealth(a, e, params, past);
+      // One tick either side of the zero crossing of the buffer
+      expect(at.liquidatable, name).toBe(true);
+      expect(at.bufferToLiquidation, name).toBeLessThan(0n);
+      expect(next.liquidatable, name).toBe(false);
+      expect(next.bufferToLiquidation, name).toBeGreaterThanOrEqual(0n);
+    }
+  });
+
+  it("treats a missing price as unpriced rather than liquidatable", () => {
+    const a = account({ positionSize: 10_000_000_000n, entryPrice: P(1) });
+    const h = computeAccountHealth(a, engine(), params, 0n);
+    expect(h.unrealizedPnl).toBe(0n);
+    expect(h.marginRatioBps).toBeNull();
+    expect(h.liquidatable).toBe(false);
+  });
+});
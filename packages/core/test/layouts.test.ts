@@ -0,0 +1,169 @@
+import { describe, it, expect } from "vitest";
+import {
+  SLAB_LAYOUT_V1,
+  buildSlabLayout,
+  getSlabLayoutSpec,
+  knownSlabSizes,
+  registerSlabLayout,
+  requireSlabLayout,
+  resolveSlabLayout,
+  slabLayoutSize,
+  UnknownSlabLayoutError,
+  type SlabLayoutSpec,
+} from "../src/solana/layouts.js";
+import {
+  detectSlabLayout,
+  isAccountUsed,
+  maxAccountIndex,
+  parseAccount,
+  parseConfig,
+  parseEngine,
+  parseHeader,
+  parseParams,
+  readNonce,
+} from "../src/solana/slab.js";
+import { SLAB_TIERS, SLAB_TIERS_V0 } from "../src/solana/discovery.js";
+
+const MAGIC_LE = [0x54, 0x41, 0x4c, 0x4f, 0x43, 0x52, 0x45, 0x50];
+
+function mockSlab(size: number, headerVersion: number): Uint8Array {
+  const buf = new Uint8Array(size);
+  buf.set(MAGIC_LE, 0);
+  new DataView(buf.buffer).setUint32(8, headerVersion, true);
+  return buf;
+}
+
+/** Hypothetical V2: V1 with a 32-byte larger config and a new engine field before the bitmap. */
+const V2: SlabLayoutSpec = {
+  ...SLAB_LAYOUT_V1,
+  version: 2,
+  label: "V2",
+  configLen: SLAB_LAYOUT_V1.configLen + 32,
+  engine: { ...SLAB_LAYOUT_V1.engine, bitmapOff: 672 },
+};
+
+describe("layout registry", () => {
+  it("matches the published tier sizes", () => {
+    expect(SLAB_TIERS.small.dataSize).toBe(65_352);
+    expect(SLAB_TIERS.medium.dataSize).toBe(257_448);
+    expect(SLAB_TIERS.large.dataSize).toBe(1_025_832);
+    expect(SLAB_TIERS_V0.small.dataSize).toBe(62_808);
+    expect(SLAB_TIERS_V0.medium.dataSize).toBe(248_760);
+    expect(SLAB_TIERS_V0.large.dataSize).toBe(992_568);
+  });
+
+  it("lists every version × tier size for discovery", () => {
+    const sizes = knownSlabSizes();
+    expect(sizes).toHaveLength(8);
+    expect(sizes).toContainEqual({ version: 0, maxAccounts: 64, dataSize: 16_320 });
+    expect(sizes).toContainEqual({ version: 1, maxAccounts: 4096, dataSize: 1_025_832 });
+  });
+
+  it("builds the same layout detectSlabLayout returns", () => {
+    expect(detectSlabLayout(65_352)).toEqual(buildSlabLayout(SLAB_LAYOUT_V1, 256));
+    expect(getSlabLayoutSpec(7)).toBeNull();
+  });
+});
+
+describe("resolveSlabLayout", () => {
+  it("resolves a known size", () => {
+    const res = resolveSlabLayout(mockSlab(65_352, 1));
+    expect(res.diagnostic).toBeNull();
+    expect(res.layout?.version).toBe(1);
+    expect(res.layout?.maxAccounts).toBe(256);
+  });
+
+  it("reports an unknown data size", () => {
+    const res = resolveSlabLayout(mockSlab(12_345, 1));
+    expect(res.layout).toBeNull();
+    expect(res.diagnostic?.code).toBe("UNKNOWN_DATA_SIZE");
+    expect(res.diagnostic?.message).toContain("Unrecognized slab data length: 12345");
+    expect(res.diagnostic?.knownVersions).toEqual([0, 1]);
+  });
+
+  it("detects by length alone, whatever the header version", () => {
+    // Deployed V0 programs write header version 1, same as V1.
+    expect(resolveSlabLayout(mockSlab(16_320, 1)).layout?.version).toBe(0);
+    expect(resolveSlabLayout(mockSlab(65_352, 9)).layout?.version).toBe(1);
+    expect(resolveSlabLayout(mockSlab(12_345, 9)).diagnostic?.headerVersion).toBe(9);
+  });
+
+  it("rejects max_accounts above the tier capacity", () => {
+    const data = mockSlab(65_352, 1);
+    const layout = detectSlabLayout(data.length)!;
+    new DataView(data.buffer).setBigUint64(layout.engineOff + layout.engineParamsOff + 32, 1024n, true);
+    const res = resolveSlabLayout(data);
+    expect(res.diagnostic?.code).toBe("MAX_ACCOUNTS_MISMATCH");
+    expect(res.diagnostic?.maxAccounts).toBe(1024);
+  });
+
+  it("accepts max_accounts configured below capacity", () => {
+    const data = mockSlab(65_352, 1);
+    const layout = detectSlabLayout(data.length)!;
+    new DataView(data.buffer).setBigUint64(layout.engineOff + layout.engineParamsOff + 32, 100n, true);
+    expect(resolveSlabLayout(data).layout?.maxAccounts).toBe(256);
+  });
+
+  it("uses the real account size for partial slices", () => {
+    const slice = mockSlab(1940, 1);
+    expect(resolveSlabLayout(slice).diagnostic?.code).toBe("UNKNOWN_DATA_SIZE");
+    expect(resolveSlabLayout(slice, 1_025_832).layout?.maxAccounts).toBe(4096);
+  });
+
+  it("throws UnknownSlabLayoutError from parsers", () => {
+    const data = mockSlab(12_345, 1);
+    expect(() => requireSlabLayout(data)).toThrow(UnknownSlabLayoutError);
+    expect(() => parseEngine(data)).toThrow("Unrecognized slab data length");
+    // No silent V0 fallback for the header, config or params either
+    for (const parse of [parseHeader, parseConfig, parseParams, readNonce, (d: Uint8Array) => isAccountUsed(d, 0)]) {
+      expect(() => parse(data)).toThrow(UnknownSlabLayoutError);
+    }
+  });
+
+  it("keeps maxAccountIndex at 0 for unknown sizes", () => {
+    expect(maxAccountIndex(12_345)).toBe(0);
+    expect(maxAccountIndex(0)).toBe(0);
+    expect(maxAccountIndex(16_320)).toBe(64);
+  });
+
+  it("re-resolves the layout on every read", () => {
+    const data = mockSlab(16_320, 1);
+    expect(parseAccount(data, 0).capital).toBe(0n);
+    // A max_accounts no V0 small slab can hold now fails resolution
+    const layout = detectSlabLayout(data.length)!;
+    new DataView(data.buffer).setBigUint64(layout.engineOff + layout.engineParamsOff + 32, 1024n, true);
+    expect(() => parseAccount(data, 1)).toThrow(UnknownSlabLayoutError);
+  });
+});
+
+describe("registerSlabLayout", () => {
+  it("adds a new layout without parser changes", () => {
+    const unregister = registerSlabLayout(V2);
+    try {
+      const size = slabLayoutSize(V2, 256);
+      const data = mockSlab(size, 2);
+      const layout = detectSlabLayout(size)!;
+      expect(layout.version).toBe(2);
+      expect(layout.engineOff).toBe(672);
+
+      const dv = new DataView(data.buffer);
+      dv.setBigUint64(layout.engineOff + layout.engineCurrentSlotOff, 123n, true);
+      dv.setBigUint64(layout.engineOff + layout.engineMarkPriceOff, 150_000_000n, true);
+      const engine = parseEngine(data);
+      expect(engine.currentSlot).toBe(123n);
+      expect(engine.markPriceE6).toBe(150_000_000n);
+    } finally {
+      unregister();
+    }
+    expect(getSlabLayoutSpec(2)).toBeNull();
+  });
+
+  it("rejects duplicate versions", () => {
+    expect(() => registerSlabLayout({ ...V2, version: 1 })).toThrow("already registered");
+  });
+
+  it("rejects layouts whose sizes collide with a registered tier", () => {
+    expect(() => registerSlabLayout({ ...SLAB_LAYOUT_V1, version: 3 })).toThrow("collides");
+    expect(knownSlabSizes()).toHaveLength(8);
+  });
+});
@@ -0,0 +1,141 @@
+import { describe, it, expect, vi } from "vitest";
+import { PublicKey } from "@solana/web3.js";
+import {
+  assessManipulationRisk,
+  costToMovePriceUsd,
+  fetchDexPoolDepth,
+  maxOpenInterestUsd,
+  marketOiCapMultiplierBps,
+  poolSideValueUsd,
+} from "../src/oracle/manipulation-cost.js";
+import { PUMPSWAP_PROGRAM_ID, METEORA_DLMM_PROGRAM_ID } from "../src/solana/pda.js";
+
+const WSOL = "So11111111111111111111111111111111111111112";
+const USDC = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const POOL = "FxfD37s1AZTeWfFQps9Zpebi2dNQ9QSSDtfMKdbsfKrD";
+
+describe("costToMovePriceUsd", () => {
+  it("is Q·(√(1+p) − 1) for a constant-product pool", () => {
+    // $1M a side, +21% needs the counter reserve to grow by 10%
+    expect(costToMovePriceUsd(1_000_000, 2_100)).toBeCloseTo(100_000, 6);
+  });
+
+  it("is zero for an empty pool or no move", () => {
+    expect(costToMovePriceUsd(0, 500)).toBe(0);
+    expect(costToMovePriceUsd(1_000_000, 0)).toBe(0);
+  });
+});
+
+describe("assessManipulationRisk", () => {
+  it("blocks a market whose OI can earn more than moving the pool costs", () => {
+    // $100k pool, 20x leverage (5% move), $1M max OI: ~$2.5k to move, $50k to earn
+    const risk = assessManipulationRisk({ poolDepthUsd: 100_000, initialMarginBps: 500, maxOiUsd: 1_000_000 });
+
+    expect(risk.moveBps).toBe(500);
+    expect(risk.maxProfitUsd).toBeCloseTo(50_000);
+    expect(risk.safetyMultiple).toBeLessThan(1);
+    expect(risk.level).toBe("critical");
+    expect(risk.blocked).toBe(true);
+    expect(risk.score).toBeGreaterThan(90);
+  });
+
+  it("rates deep pools against small OI as low risk", () => {
+    const risk = assessManipulationRisk({ poolDepthUsd: 50_000_000, initialMarginBps: 1_000, maxOiUsd: 100_000 });
+
+    expect(risk.level).toBe("low");
+    expect(risk.blocked).toBe(false);
+    expect(risk.score).toBeLessThan(5);
+  });
+
+  it("assesses the move at the oracle price cap when it is tighter than the margin", () => {
+    // 1% cap (10_000 e2bps) against a 10% initial margin
+    const risk = assessManipulationRisk({
+      poolDepthUsd: 1_000_000,
+      initialMarginBps: 1_000,
+      maxOiUsd: 100_000,
+      oraclePriceCapE2bps: 10_000,
+    });
+
+    expect(risk.moveBps).toBe(100);
+    expect(risk.maxProfitUsd).toBeCloseTo(1_000);
+  });
+
+  it("treats a market with no OI as unexploitable", () => {
+    const risk = assessManipulationRisk({ poolDepthUsd: 1_000, initialMarginBps: 500, maxOiUsd: 0 });
+
+    expect(risk.safetyMultiple).toBe(Infinity);
+    expect(risk.score).toBe(0);
+    expect(risk.blocked).toBe(false);
+  });
+});
+
+describe("maxOpenInterestUsd", () => {
+  it("scales LP capital by the market's OI cap", () => {
+    expect(maxOpenInterestUsd(25_000, 20_000)).toBe(50_000);
+    // No configured cap: bounded by max leverage (1000 bps IM → 10x)
+    expect(maxOpenInterestUsd(25_000, marketOiCapMultiplierBps(0, 1000))).toBe(250_000);
+    expect(marketOiCapMultiplierBps(0, 500)).toBe(200_000);
+    expect(marketOiCapMultiplierBps(30_000, 500)).toBe(30_000);
+  });
+});
+
+describe("fetchDexPoolDepth", () => {
+  function pumpswapPool(): Buffer {
+    const data = Buffer.alloc(300);
+    new PublicKey(WSOL).toBuffer().copy(data, 35);
+    new PublicKey(USDC).toBuffer().copy(data, 67);
+    new PublicKey(POOL).toBuffer().copy(data, 131);
+    new PublicKey(POOL).toBuffer().copy(data, 163);
+    return data;
+  }
+  const mint = (decimals: number) => {
+    const data = Buffer.alloc(82);
+    data[44] = decimals;
+    return { data };
+  };
+  const vault = (amount: bigint) => {
+    const data = Buffer.alloc(165);
+    data.writeBigUInt64LE(amount, 64);
+    return { data };
+  };
+
+  it("reads reserves and decimals at one slot", async () => {
+    const connection = {
+      getAccountInfo: vi.fn().mockResolvedValue({ owner: PUMPSWAP_PROGRAM_ID, data: pumpswapPool() }),
+      getMultipleAccountsInfoAndContext: vi.fn().mockResolvedValue({
+        context: { slot: 42 },
+        value: [{ data: pumpswapPool() }, mint(9), mint(6), vault(2_000_000_000_000n), vault(300_000_000_000n)],
+      }),
+    };
+
+    const snapshot = await fetchDexPoolDepth(connection as any, POOL);
+
+    expect(snapshot).toMatchObject({
+      dexType: "pumpswap",
+      model: "reserves",
+      baseMint: WSOL,
+      quoteMint: USDC,
+      baseReserve: 2_000_000_000_000n,
+      quoteReserve: 300_000_000_000n,
+      baseDecimals: 9,
+      quoteDecimals: 6,
+      slot: 42,
+    });
+    // 2,000 SOL at $150 — same value as the 300k USDC side
+    expect(poolSideValueUsd(snapshot!, WSOL, 150)).toBeCloseTo(300_000);
+    expect(poolSideValueUsd(snapshot!, "NotInPool111111111111111111111111111111111", 1)).toBeNull();
+  });
+
+  it("returns null for pools without readable depth", async () => {
+    const data = Buffer.alloc(200);
+    const connection = {
+      getAccountInfo: vi.fn().mockResolvedValue({ owner: METEORA_DLMM_PROGRAM_ID, data }),
+      getMultipleAccountsInfoAndContext: vi.fn().mockResolvedValue({
+        context: { slot: 1 },
+        value: [{ data }, mint(6), mint(6)],
+      }),
+    };
+
+    expect(await fetchDexPoolDepth(connection as any, POOL)).toBeNull();
+  });
+});
@@ -2,7 +2,8 @@
  * Oracle parsing/validation tests
  *
  * Tests for parseChainlinkPrice() which extracts price data from
- * Chainlink aggregator account buffers with proper validation.
+ * Chainlink aggregator account buffers with proper validation, and for the
+ * Pyth PriceUpdateV2 / Switchboard On-Demand pull-oracle decoders.
  *
  * Ported from Toly's percolator-cli (aeyakovenko/percolator-cli, Feb 16 2026)
  * with adaptations for browser-compatible Uint8Array API.
@@ -11,9 +12,15 @@
 import {
   parseChainlinkPrice,
   isValidChainlinkOracle,
+  parsePythPriceUpdateV2,
+  parseSwitchboardPullFeed,
+  parsePullOraclePrice,
+  checkOracleFreshness,
   CHAINLINK_MIN_SIZE,
   CHAINLINK_DECIMALS_OFFSET,
   CHAINLINK_ANSWER_OFFSET,
+  PYTH_PRICE_UPDATE_V2_MIN_SIZE,
+  SWITCHBOARD_PULL_FEED_MIN_SIZE,
 } from "../src/solana/oracle.js";
 
 function assert(cond: boolean, msg: string): void {
@@ -164,6 +171,204 @@ console.log("\nTesting isValidChainlinkOracle...\n");
   console.log("✓ isValidChainlinkOracle works correctly");
 }
 
+// --- Pyth PriceUpdateV2 ---
+
+console.log("\nTesting parsePythPriceUpdateV2...\n");
+
+const PYTH_DISC = [0x22, 0xf1, 0x23, 0x63, 0x9d, 0x7e, 0xf4, 0xcd];
+const SOL_USD_FEED = "ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+
+function buildPythBuffer(opts: {
+  price: bigint;
+  conf?: bigint;
+  exponent?: number;
+  publishTime?: bigint;
+  postedSlot?: bigint;
+  partialSignatures?: number;
+}): Uint8Array {
+  const partial = opts.partialSignatures !== undefined;
+  const msg = partial ? 42 : 41;
+  const buf = new Uint8Array(msg + 92);
+  const dv = new DataView(buf.buffer);
+  buf.set(PYTH_DISC, 0);
+  buf.fill(7, 8, 40); // write authority
+  buf[40] = partial ? 0 : 1;
+  if (partial) buf[41] = opts.partialSignatures!;
+  buf.set(Buffer.from(SOL_USD_FEED, "hex"), msg);
+  dv.setBigInt64(msg + 32, opts.price, true);
+  dv.setBigUint64(msg + 40, opts.conf ?? 0n, true);
+  dv.setInt32(msg + 48, opts.exponent ?? -8, true);
+  dv.setBigInt64(msg + 52, opts.publishTime ?? 1_700_000_000n, true);
+  dv.setBigInt64(msg + 60, (opts.publishTime ?? 1_700_000_000n) - 1n, true);
+  dv.setBigInt64(msg + 68, opts.price, true); // ema price
+  dv.setBigUint64(msg + 76, opts.conf ?? 0n, true); // ema conf
+  dv.setBigUint64(msg + 84, opts.postedSlot ?? 250_000_000n, true);
+  return buf;
+}
+
+// Fully verified update: $150.12345678 ± $0.075
+{
+  const data = buildPythBuffer({ price: 15012345678n, conf: 7_500_000n, publishTime: 1_700_000_123n });
+  assert(data.length === PYTH_PRICE_UPDATE_V2_MIN_SIZE, "full update is 133 bytes");
+  const p = parsePythPriceUpdateV2(data);
+  assert(p.price === 15012345678n, `price: ${p.price}`);
+  assert(p.decimals === 8, `decimals: ${p.decimals}`);
+  assert(p.exponent === -8, `exponent: ${p.exponent}`);
+  assert(p.conf === 7_500_000n, `conf: ${p.conf}`);
+  assert(p.publishTime === 1_700_000_123n, `publishTime: ${p.publishTime}`);
+  assert(p.slot === 250_000_000n, `slot: ${p.slot}`);
+  assert(p.feedId === SOL_USD_FEED, `feedId: ${p.feedId}`);
+  assert(p.verificationLevel === "full", "verification level full");
+  assert(p.numSignatures === undefined, "no signature count when full");
+  assert(p.emaPrice === 15012345678n, "ema price");
+  console.log("✓ parses fully verified update");
+}
+
+// Partially verified update shifts the message by one byte
+{
+  const data = buildPythBuffer({ price: 15012345678n, conf: 1n, partialSignatures: 5 });
+  assert(data.length === 134, "partial update is 134 bytes");
+  const p = parsePythPriceUpdateV2(data);
+  assert(p.verificationLevel === "partial", "verification level partial");
+  assert(p.numSignatures === 5, `numSignatures: ${p.numSignatures}`);
+  assert(p.price === 15012345678n, `price: ${p.price}`);
+  assert(p.feedId === SOL_USD_FEED, "feedId after partial tag");
+  console.log("✓ parses partially verified update");
+}
+
+// Positive exponent is folded into the mantissa
+{
+  const p = parsePythPriceUpdateV2(buildPythBuffer({ price: 3n, conf: 1n, exponent: 2 }));
+  assert(p.price === 300n && p.conf === 100n && p.decimals === 0, "positive exponent scaled");
+  console.log("✓ handles positive exponent");
+}
+
+{
+  assertThrows(() => parsePythPriceUpdateV2(new Uint8Array(100)), "too small", "rejects short buffer");
+  const wrongDisc = buildPythBuffer({ price: 1n });
+  wrongDisc[0] ^= 0xff;
+  assertThrows(() => parsePythPriceUpdateV2(wrongDisc), "discriminator", "rejects wrong discriminator");
+  const badLevel = buildPythBuffer({ price: 1n });
+  badLevel[40] = 2;
+  assertThrows(() => parsePythPriceUpdateV2(badLevel), "verification level", "rejects unknown level");
+  assertThrows(
+    () => parsePythPriceUpdateV2(buildPythBuffer({ price: 1n, partialSignatures: 3 }).slice(0, 133)),
+    "too small",
+    "rejects truncated partial update"
+  );
+  assertThrows(() => parsePythPriceUpdateV2(buildPythBuffer({ price: 0n })), "non-positive", "rejects zero price");
+  assertThrows(
+    () => parsePythPriceUpdateV2(buildPythBuffer({ price: 1n, exponent: -19 })),
+    "decimals",
+    "rejects exponent below -18"
+  );
+  console.log("✓ rejects malformed Pyth updates");
+}
+
+// --- Switchboard On-Demand ---
+
+console.log("\nTesting parseSwitchboardPullFeed...\n");
+
+const SB_DISC = [0xc4, 0x1b, 0x6c, 0xc4, 0x0a, 0xd7, 0xdb, 0x28];
+
+function writeI128(dv: DataView, off: number, v: bigint): void {
+  dv.setBigUint64(off, BigInt.asUintN(64, v), true);
+  dv.setBigInt64(off + 8, BigInt.asIntN(64, v >> 64n), true);
+}
+
+function buildSwitchboardBuffer(opts: {
+  value: bigint;
+  stdDev?: bigint;
+  numSamples?: number;
+  slot?: bigint;
+  lastUpdate?: bigint;
+  maxStaleness?: number;
+}): Uint8Array {
+  const buf = new Uint8Array(3208);
+  const dv = new DataView(buf.buffer);
+  buf.set(SB_DISC, 0);
+  dv.setBigInt64(2216, opts.lastUpdate ?? 1_700_000_000n, true);
+  writeI128(dv, 2264, opts.value);
+  writeI128(dv, 2280, opts.stdDev ?? 0n);
+  buf[2360] = opts.numSamples ?? 3;
+  dv.setBigUint64(2368, opts.slot ?? 250_000_000n, true);
+  dv.setUint32(2392, opts.maxStaleness ?? 250, true);
+  return buf;
+}
+
+// $150.5 ± $0.3 at 18 decimals — exceeds i64, exercises the i128 read
+{
+  const value = 150_500_000_000_000_000_000n;
+  const p = parseSwitchboardPullFeed(
+    buildSwitchboardBuffer({ value, stdDev: 300_000_000_000_000_000n, lastUpdate: 1_700_000_050n })
+  );
+  assert(p.price === value, `price: ${p.price}`);
+  assert(p.decimals === 18, `decimals: ${p.decimals}`);
+  assert(p.conf === 300_000_000_000_000_000n, `conf: ${p.conf}`);
+  assert(p.publishTime === 1_700_000_050n, `publishTime: ${p.publishTime}`);
+  assert(p.slot === 250_000_000n, `slot: ${p.slot}`);
+  assert(p.numSamples === 3, `numSamples: ${p.numSamples}`);
+  assert(p.maxStalenessSlots === 250, `maxStalenessSlots: ${p.maxStalenessSlots}`);
+  console.log("✓ parses pull feed result");
+}
+
+{
+  assertThrows(() => parseSwitchboardPullFeed(new Uint8Array(2000)), "too small", "rejects short buffer");
+  const wrongDisc = buildSwitchboardBuffer({ value: 1n });
+  wrongDisc[7] = 0;
+  assertThrows(() => parseSwitchboardPullFeed(wrongDisc), "discriminator", "rejects wrong discriminator");
+  assertThrows(
+    () => parseSwitchboardPullFeed(buildSwitchboardBuffer({ value: 1n, numSamples: 0 })),
+    "no resolved result",
+    "rejects unresolved feed"
+  );
+  assertThrows(
+    () => parseSwitchboardPullFeed(buildSwitchboardBuffer({ value: -5n })),
+    "non-positive",
+    "rejects negative price"
+  );
+  console.log("✓ rejects malformed Switchboard feeds");
+}
+
+// --- parsePullOraclePrice ---
+
+{
+  assert(parsePullOraclePrice(buildPythBuffer({ price: 1n }))?.decimals === 8, "dispatches Pyth");
+  assert(parsePullOraclePrice(buildSwitchboardBuffer({ value: 1n }))?.decimals === 18, "dispatches Switchboard");
+  assert(parsePullOraclePrice(new Uint8Array(224)) === null, "unknown account returns null");
+  console.log("✓ parsePullOraclePrice dispatches on discriminator");
+}
+
+// --- checkOracleFreshness ---
+
+console.log("\nTesting checkOracleFreshness...\n");
+
+{
+  // $150 ± $0.15 = 10 bps, published 30s ago
+  const p = parsePythPriceUpdateV2(
+    buildPythBuffer({ price: 15_000_000_000n, conf: 15_000_000n, publishTime: 1_700_000_000n })
+  );
+  const fresh = checkOracleFreshness(p, { maxStalenessSecs: 60, confFilterBps: 50, nowUnixSecs: 1_700_000_030 });
+  assert(fresh.ok, "fresh price within conf passes");
+  assert(fresh.ageSecs === 30 && fresh.confBps === 10, "reports age and conf");
+
+  const stale = checkOracleFreshness(p, { maxStalenessSecs: 20, nowUnixSecs: 1_700_000_030 });
+  assert(!stale.ok && stale.reason.includes("stale"), "stale by seconds");
+
+  const wide = checkOracleFreshness(p, { confFilterBps: 5, nowUnixSecs: 1_700_000_030 });
+  assert(!wide.ok && wide.reason.includes("confidence"), "conf wider than filter");
+
+  const slotStale = checkOracleFreshness(p, { maxStalenessSlots: 100, currentSlot: 250_000_200n });
+  assert(!slotStale.ok && slotStale.ageSlots === 200, "stale by slots");
+
+  const unchecked = checkOracleFreshness(p, { maxStalenessSecs: 0, confFilterBps: 0, nowUnixSecs: 1_800_000_000 });
+  assert(unchecked.ok, "zero limits are unchecked");
+
+  const chainlink = checkOracleFreshness(parseChainlinkPrice(buildChainlinkBuffer(8, 100n)), { confFilterBps: 1 });
+  assert(chainlink.ok && chainlink.confBps === null, "missing fields are skipped");
+  console.log("✓ checkOracleFreshness applies staleness and confidence limits");
+}
+
 // --- Constants ---
 
 console.log("\nTesting exported constants...\n");
@@ -172,6 +377,8 @@ console.log("\nTesting exported constants...\n");
   assert(CHAINLINK_MIN_SIZE === 224, "CHAINLINK_MIN_SIZE = 224");
   assert(CHAINLINK_DECIMALS_OFFSET === 138, "CHAINLINK_DECIMALS_OFFSET = 138");
   assert(CHAINLINK_ANSWER_OFFSET === 216, "CHAINLINK_ANSWER_OFFSET = 216");
+  assert(PYTH_PRICE_UPDATE_V2_MIN_SIZE === 133, "PYTH_PRICE_UPDATE_V2_MIN_SIZE = 133");
+  assert(SWITCHBOARD_PULL_FEED_MIN_SIZE === 2396, "SWITCHBOARD_PULL_FEED_MIN_SIZE = 2396");
   console.log("✓ exported constants correct");
 }
 
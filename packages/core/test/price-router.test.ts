@@ -416,4 +416,26 @@ describe("resolvePrice", () => {
     expect(dexIds).toContain("pumpswap");
     expect(dexIds).toContain("meteora");
   });
+
+  it("accepts orca pools", async () => {
+    const mint = "OrcaDexIdTestMint11111111111111111111111111111";
+    mockApis({
+      dexPairs: [
+        {
+          chainId: "solana",
+          dexId: "orca",
+          pairAddress: "whirl1",
+          liquidity: { usd: 5_000 },
+          priceUsd: "0.03",
+          baseToken: { symbol: "X" },
+          quoteToken: { symbol: "USDC" },
+        },
+      ],
+    });
+
+    const result = await resolvePrice(mint);
+    const dex = result.allSources.filter((s) => s.type === "dex");
+    expect(dex.map((s) => s.dexId)).toEqual(["orca"]);
+    expect(dex[0].address).toBe("whirl1");
+  });
 });
@@ -0,0 +1,248 @@
+import { describe, it, expect } from "vitest";
+import { PublicKey } from "@solana/web3.js";
+import { computeAccountHealth } from "../src/math/health.js";
+import {
+  applySimOp,
+  simulate,
+  isSimLiquidatable,
+  SimulationError,
+  type SimMarket,
+} from "../src/math/simulator.js";
+import { AccountKind, type Account, type EngineState, type RiskParams } from "../src/solana/slab.js";
+
+const P = (usd: number) => BigInt(Math.round(usd * 1_000_000));
+
+function account(kind: AccountKind, capital: bigint, extra: Partial<Account> = {}): Account {
+  return {
+    kind,
+    accountId: 0n,
+    capital,
+    pnl: 0n,
+    reservedPnl: 0n,
+    warmupStartedAtSlot: 0n,
+    warmupSlopePerStep: 0n,
+    positionSize: 0n,
+    entryPrice: 0n,
+    fundingIndex: 0n,
+    matcherProgram: PublicKey.default,
+    matcherContext: PublicKey.default,
+    owner: PublicKey.default,
+    feeCredits: 0n,
+    lastFeeSlot: 0n,
+    ...extra,
+  };
+}
+
+const params: RiskParams = {
+  warmupPeriodSlots: 0n,
+  maintenanceMarginBps: 500n,
+  initialMarginBps: 1000n,
+  tradingFeeBps: 10n,
+  maxAccounts: 64n,
+  newAccountFee: 0n,
+  riskReductionThreshold: 0n,
+  maintenanceFeePerSlot: 0n,
+  maxCrankStalenessSlots: 0n,
+  liquidationFeeBps: 100n,
+  liquidationFeeCap: 0n,
+  liquidationBufferBps: 0n,
+  minLiquidationAbs: 0n,
+};
+
+function market(overrides: Partial<RiskParams> = {}, user: Partial<Account> = {}): SimMarket {
+  const engine: EngineState = {
+    vault: 11_000_000_000n,
+    insuranceFund: { balance: 0n, feeRevenue: 0n, isolatedBalance: 0n, isolationBps: 0 },
+    currentSlot: 100n,
+    fundingIndexQpbE6: 0n,
+    lastFundingSlot: 100n,
+    fundingRateBpsPerSlotLast: 0n,
+    lastCrankSlot: 100n,
+    maxCrankStalenessSlots: 0n,
+    totalOpenInterest: 0n,
+    longOi: 0n,
+    shortOi: 0n,
+    cTot: 11_000_000_000n,
+    pnlPosTot: 0n,
+    liqCursor: 0,
+    gcCursor: 0,
+    lastSweepStartSlot: 0n,
+    lastSweepCompleteSlot: 0n,
+    crankCursor: 0,
+    sweepStartIdx: 0,
+    lifetimeLiquidations: 0n,
+    lifetimeForceCloses: 0n,
+    netLpPos: 0n,
+    lpSumAbs: 0n,
+    lpMaxAbs: 0n,
+    lpMaxAbsSweep: 0n,
+    emergencyOiMode: false,
+    emergencyStartSlot: 0n,
+    lastBreakerSlot: 0n,
+    numUsedAccounts: 2,
+    nextAccountId: 2n,
+    markPriceE6: 0n,
+  };
+  return {
+    engine,
+    params: { ...params, ...overrides },
+    config: null,
+    accounts: new Map([
+      [0, account(AccountKind.LP, 10_000_000_000n)],
+      [1, account(AccountKind.User, 1_000_000_000n, user)],
+    ]),
+  };
+}
+
+const openLong = { type: "trade", slot: 100n, userIdx: 1, lpIdx: 0, size: 5_000_000_000n, priceE6: P(1) } as const;
+
+describe("simulator trades", () => {
+  it("opens a position, charges the fee and updates OI", () => {
+    const before = market();
+    const { market: m, events } = applySimOp(before, openLong);
+
+    const user = m.accounts.get(1)!;
+    const lp = m.accounts.get(0)!;
+    expect(user.capital).toBe(995_000_000n);
+    expect(user.positionSize).toBe(5_000_000_000n);
+    expect(user.entryPrice).toBe(P(1));
+    expect(lp.positionSize).toBe(-5_000_000_000n);
+    expect(m.engine.insuranceFund.feeRevenue).toBe(5_000_000n);
+    expect(m.engine.longOi).toBe(5_000_000_000n);
+    expect(m.engine.shortOi).toBe(5_000_000_000n);
+    expect(m.engine.netLpPos).toBe(-5_000_000_000n);
+    expect(events).toEqual([
+      { type: "trade", userIdx: 1, lpIdx: 0, size: 5_000_000_000n, priceE6: P(1), fee: 5_000_000n },
+    ]);
+
+    // Input untouched
+    expect(before.accounts.get(1)!.positionSize).toBe(0n);
+  });
+
+  it("realizes PnL when reducing and averages entry when adding", () => {
+    const { market: m } = simulate(market(), [
+      openLong,
+      { ...openLong, size: 5_000_000_000n, priceE6: P(1.1) },
+      { ...openLong, size: -5_000_000_000n, priceE6: P(1.2) },
+    ]);
+    const user = m.accounts.get(1)!;
+    expect(user.positionSize).toBe(5_000_000_000n);
+    expect(user.entryPrice).toBe(P(1.05));
+    // (1.20 - 1.05) * 5e9 / 1.20
+    expect(user.pnl).toBe(625_000_000n);
+  });
+
+  it("rejects risk-increasing trades below initial margin", () => {
+    const op = { ...openLong, size: 20_000_000_000n };
+    expect(() => applySimOp(market(), op)).toThrow(SimulationError);
+    try {
+      applySimOp(market(), op);
+    } catch (e) {
+      expect((e as SimulationError).code).toBe(14);
+      expect((e as Error).message).toContain("EngineUndercollateralized");
+    }
+  });
+
+  it("requires the counterparty to be an LP", () => {
+    expect(() => applySimOp(market(), { ...openLong, userIdx: 0, lpIdx: 1 })).toThrow("EngineNotAnLPAccount");
+  });
+});
+
+describe("simulator crank", () => {
+  it("accrues funding from longs to shorts", () => {
+    const { market: m, events } = simulate(market(), [
+      openLong,
+      { type: "crank", slot: 110n, oraclePriceE6: P(1), fundingRateBpsPerSlot: 1n },
+    ]);
+    expect(m.accounts.get(1)!.pnl).toBe(-5_000_000n);
+    expect(m.accounts.get(0)!.pnl).toBe(5_000_000n);
+    expect(m.engine.fundingIndexQpbE6).toBe(1_000n);
+    expect(m.engine.lastCrankSlot).toBe(110n);
+    expect(events).toContainEqual({ type: "funding", slots: 10n, rateBpsPerSlot: 1n, indexDelta: 1_000n });
+  });
+
+  it("liquidates underwater accounts and draws on insurance for the shortfall", () => {
+    const { market: m, events } = simulate(market(), [
+      openLong,
+      { type: "crank", slot: 110n, oraclePriceE6: P(0.9), fundingRateBpsPerSlot: 1n },
+      { type: "crank", slot: 111n, oraclePriceE6: P(0.82), fundingRateBpsPerSlot: 0n },
+    ]);
+    const user = m.accounts.get(1)!;
+    expect(user.positionSize).toBe(0n);
+    expect(user.capital).toBe(0n);
+    expect(m.engine.lifetimeLiquidations).toBe(1n);
+    // equity = 995e6 - 5e6 - 1_097_560_975 → shortfall 107_560_975, insurance holds the 5e6 trade fee
+    expect(events).toContainEqual({
+      type: "liquidation",
+      idx: 1,
+      closedSize: 5_000_000_000n,
+      realizedPnl: -1_097_560_975n,
+      fee: 0n,
+      insuranceCovered: 5_000_000n,
+      badDebt: 102_560_975n,
+    });
+    expect(m.engine.insuranceFund.balance).toBe(0n);
+  });
+
+  it("leaves healthy accounts alone", () => {
+    const { market: m } = applySimOp(market(), openLong);
+    expect(isSimLiquidatable(m.accounts.get(1)!, m.engine, m.params, P(0.9))).toBe(false);
+    const res = applySimOp(m, { type: "liquidate", slot: 101n, idx: 1, oraclePriceE6: P(0.9) });
+    expect(res.events[0].type).toBe("liquidationSkipped");
+    expect(res.market.accounts.get(1)!.positionSize).toBe(5_000_000_000n);
+  });
+});
+
+describe("simulator deposits and withdrawals", () => {
+  it("moves capital and the vault", () => {
+    const { market: m } = simulate(market(), [
+      { type: "deposit", idx: 1, amount: 500_000_000n },
+      { type: "withdraw", slot: 100n, idx: 1, amount: 200_000_000n, oraclePriceE6: P(1) },
+    ]);
+    expect(m.accounts.get(1)!.capital).toBe(1_300_000_000n);
+    expect(m.engine.vault).toBe(11_300_000_000n);
+  });
+
+  it("limits withdrawals to warmup-unlocked capital", () => {
+    const m = market({ warmupPeriodSlots: 100n }, { warmupStartedAtSlot: 100n });
+    const op = { type: "withdraw", slot: 150n, idx: 1, oraclePriceE6: P(1) } as const;
+    expect(applySimOp(m, { ...op, amount: 500_000_000n }).market.accounts.get(1)!.capital).toBe(500_000_000n);
+    expect(() => applySimOp(m, { ...op, amount: 500_000_001n })).toThrow("EngineInsufficientBalance");
+  });
+
+  it("rejects withdrawals that break initial margin", () => {
+    const { market: m } = applySimOp(market(), openLong);
+    expect(() =>
+      applySimOp(m, { type: "withdraw", slot: 100n, idx: 1, amount: 600_000_000n, oraclePriceE6: P(1) }),
+    ).toThrow("EngineUndercollateralized");
+  });
+});
+
+describe("simulator vs account health", () => {
+  it("sizes margin on notional, not raw position", () => {
+    // 5e9 at $100 is 5e11 notional: 1e9 capital is far below 5% maintenance
+    const m = market({}, { positionSize: 5_000_000_000n, entryPrice: 100_000_000n });
+    expect(isSimLiquidatable(m.accounts.get(1)!, m.engine, m.params, 100_000_000n)).toBe(true);
+  });
+
+  it("agrees with computeAccountHealth on liquidatability across prices", () => {
+    const cases: Partial<Account>[] = [
+      { positionSize: 5_000_000_000n, entryPrice: P(1) },
+      { positionSize: -5_000_000_000n, entryPrice: P(1) },
+      { positionSize: 20_000_000n, entryPrice: 50_000_000n, pnl: -200_000_000n },
+      { positionSize: -8_000_000n, entryPrice: 100_000_000n, fundingIndex: -5_000n },
+    ];
+    const prices = [P(0.5), P(0.82), P(1), P(1.5), 45_000_000n, 100_000_000n, 180_000_000n];
+    for (const user of cases) {
+      const m = market({}, user);
+      const a = m.accounts.get(1)!;
+      for (const price of prices) {
+        const health = computeAccountHealth(a, m.engine, m.params, price);
+        expect(isSimLiquidatable(a, m.engine, m.params, price)).toBe(health.liquidatable);
+
+        const res = applySimOp(m, { type: "liquidate", slot: 101n, idx: 1, oraclePriceE6: price });
+        expect(res.events[0].type).toBe(health.liquidatable ? "liquidation" : "liquidationSkipped");
+      }
+    }
+  });
+});
@@ -0,0 +1,127 @@
+import { describe, it, expect } from "vitest";
+import { readdirSync, readFileSync } from "node:fs";
+import { join } from "node:path";
+import { fileURLToPath } from "node:url";
+import { PublicKey } from "@solana/web3.js";
+import {
+  detectSlabLayout, parseHeader, parseConfig, parseParams, parseEngine,
+  parseUsedIndices, parseAllAccounts, parseSlabState,
+} from "../src/solana/slab.js";
+import { resolveSlabLayout } from "../src/solana/layouts.js";
+
+/**
+ * Golden fixtures shared with the Rust decoder (crates/percolator-slab/tests/golden.rs).
+ * Every scalar is compared as a decimal string and every pubkey as hex, so the
+ * same JSON can be asserted from both sides.
+ */
+const FIXTURES_DIR = join(fileURLToPath(new URL(".", import.meta.url)), "fixtures/slab");
+
+interface Fixture {
+  description: string;
+  dataLen: number;
+  writes: { offset: number; hex: string }[];
+  expected: Record<string, any>;
+}
+
+function build(f: Fixture): Uint8Array {
+  const data = new Uint8Array(f.dataLen);
+  for (const w of f.writes) {
+    data.set(Buffer.from(w.hex, "hex"), w.offset);
+  }
+  return data;
+}
+
+function str(v: unknown): string {
+  if (v instanceof PublicKey) return Buffer.from(v.toBytes()).toString("hex");
+  return String(v);
+}
+
+/** Flatten one level of nesting (insuranceFund.balance) and stringify. */
+function flatten(obj: Record<string, unknown>): Record<string, string> {
+  const out: Record<string, string> = {};
+  for (const [k, v] of Object.entries(obj)) {
+    if (v && typeof v === "object" && !(v instanceof PublicKey)) {
+      for (const [k2, v2] of Object.entries(v)) out[`${k}.${k2}`] = str(v2);
+    } else {
+      out[k] = str(v);
+    }
+  }
+  return out;
+}
+
+const fixtures = readdirSync(FIXTURES_DIR)
+  .filter((f) => f.endsWith(".json"))
+  .sort()
+  .map((f) => [f, JSON.parse(readFileSync(join(FIXTURES_DIR, f), "utf8")) as Fixture] as const);
+
+describe("slab golden fixtures", () => {
+  it("finds fixtures", () => {
+    expect(fixtures.length).toBeGreaterThan(0);
+  });
+
+  for (const [name, fixture] of fixtures) {
+    describe(name, () => {
+      const data = build(fixture);
+      const { expected } = fixture;
+
+      it("detects layout", () => {
+        const layout = detectSlabLayout(data.length);
+        if (expected.layout === null) {
+          expect(layout).toBeNull();
+        } else {
+          expect(layout).not.toBeNull();
+          expect({
+            version: str(layout!.version),
+            maxAccounts: str(layout!.maxAccounts),
+            accountsOff: str(layout!.accountsOff),
+            accountSize: str(layout!.accountSize),
+          }).toEqual(expected.layout);
+          // Length-only, like the Rust decoder: the V0 fixtures carry header
+          // version 1, as deployed V0 slabs do.
+          expect(resolveSlabLayout(data).layout).toEqual(layout);
+        }
+      });
+
+      if (expected.header) {
+        it("parses header", () => {
+          expect(flatten({ ...parseHeader(data) })).toEqual(expected.header);
+        });
+      }
+      if (expected.config) {
+        it("parses config", () => {
+          expect(flatten({ ...parseConfig(data) })).toEqual(expected.config);
+        });
+      }
+      if (expected.params) {
+        it("parses params", () => {
+          expect(flatten({ ...parseParams(data) })).toEqual(expected.params);
+        });
+      }
+      if (expected.engine) {
+        it("parses engine", () => {
+          expect(flatten({ ...parseEngine(data) })).toEqual(expected.engine);
+        });
+      }
+      if (expected.usedIndices) {
+        it("parses used indices", () => {
+          expect(parseUsedIndices(data).map(String)).toEqual(expected.usedIndices);
+        });
+      }
+      if (expected.accounts) {
+        it("parses accounts", () => {
+          const got = parseAllAccounts(data).map(({ idx, account }) =>
+            flatten({ idx, ...account }),
+          );
+          expect(got).toEqual(expected.accounts);
+        });
+      }
+      if (expected.engine && expected.accounts) {
+        it("parses full state in one pass", () => {
+          const state = parseSlabState(data);
+          expect(flatten({ ...state.engine })).toEqual(expected.engine);
+          expect(state.accounts.map(({ idx, account }) => flatten({ idx, ...account }))).toEqual(expected.accounts);
+        });
+      }
+    });
+  }
+});
@@ -22,8 +22,9 @@ console.log("Testing slab parsing...\n");
 // V0 layout (deployed devnet): HEADER_LEN=72, CONFIG_LEN=408, ENGINE_OFF=480
 //   RESERVED_OFF = 48 (nonce at 48, lastThrUpdateSlot at 56)
 //   Config starts at offset 72
+// Sized as the V0 64-account tier: parsers refuse sizes no layout produces.
 function createMockSlab(): Buffer {
-  const buf = Buffer.alloc(480);  // HEADER_LEN(72) + CONFIG_LEN(408) = 480 minimum
+  const buf = Buffer.alloc(16_320);
 
   // Header (72 bytes)
   // magic: "PERCOLAT" = 0x504552434f4c4154
@@ -152,6 +153,25 @@ function createMockSlab(): Buffer {
   console.log("✓ parseHeader rejects short buffer");
 }
 
+// Test error on a size no layout produces
+{
+  const slab = Buffer.alloc(480);
+  createMockSlab().copy(slab, 0, 0, 480);
+
+  let threw = false;
+  try {
+    parseConfig(slab);
+  } catch (e) {
+    threw = true;
+    assert(
+      (e as Error).name === "UnknownSlabLayoutError",
+      "unknown size throws UnknownSlabLayoutError"
+    );
+  }
+  assert(threw, "parseConfig throws on an unknown slab size");
+  console.log("✓ parseConfig rejects unknown slab size");
+}
+
 console.log("\n✅ All basic slab tests passed!");
 
 // =============================================================================
@@ -7,10 +7,17 @@ export default defineConfig({
       "test/instructions.test.ts",
       "test/pda.test.ts",
       "test/slab-parser.test.ts",
+      "test/slab-golden.test.ts",
+      "test/layouts.test.ts",
+      "test/simulator.test.ts",
+      "test/health.test.ts",
       "test/accounts.test.ts",
       "test/errors.test.ts",
+      "test/decode.test.ts",
       "test/discovery.test.ts",
       "test/price-router.test.ts",
+      "test/dex-pools.test.ts",
+      "test/manipulation-cost.test.ts",
       "src/solana/__tests__/stake.test.ts",
       "src/solana/__tests__/stake-cpi.test.ts",
     ],
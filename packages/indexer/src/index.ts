@@ -1,13 +1,18 @@
 import "dotenv/config";
 import { Hono } from "hono";
 import { serve } from "@hono/node-server";
-import { config, createLogger, initSentry, captureException, getSupabase, getConnection, sendCriticalAlert, sendInfoAlert } from "@percolator/shared";
+import { config, createLogger, initSentry, captureException, getSupabase, getConnection, sendCriticalAlert, sendInfoAlert, SlabArchive, SlabWatcher, connectEventBus, createEventTransport, type EventBridge } from "@percolator/shared";
 import { MarketDiscovery } from "./services/MarketDiscovery.js";
 import { StatsCollector } from "./services/StatsCollector.js";
 import { TradeIndexerPolling } from "./services/TradeIndexer.js";
 import { InsuranceLPService } from "./services/InsuranceLPService.js";
+import { StakePoolService } from "./services/StakePoolService.js";
 import { HeliusWebhookManager } from "./services/HeliusWebhookManager.js";
+import { SlabArchiver } from "./services/SlabArchiver.js";
+import { CandleBuilder } from "./services/CandleBuilder.js";
+import { WebhookDispatcher } from "./services/WebhookDispatcher.js";
 import { webhookRoutes } from "./routes/webhook.js";
+import { archiveRoutes } from "./routes/archive.js";
 
 // Initialize Sentry first
 initSentry("indexer");
@@ -20,7 +25,28 @@ const discovery = new MarketDiscovery();
 const statsCollector = new StatsCollector(discovery);
 const tradeIndexer = new TradeIndexerPolling();
 const insuranceService = new InsuranceLPService(discovery);
+// Share price / tranche / HWM history of every percolator-stake pool
+const stakePoolService = new StakePoolService();
 const webhookManager = new HeliusWebhookManager();
+const candleBuilder = new CandleBuilder();
+// Raw slab history for point-in-time debugging; disabled when SLAB_ARCHIVE_DIR is ""
+const slabArchive = config.slabArchiveDir ? new SlabArchive(config.slabArchiveDir) : null;
+const slabArchiver = slabArchive ? new SlabArchiver(discovery, slabArchive) : null;
+// Push-based slab diffs (position/oi/insurance/config/pause events) on the eventBus
+const slabWatcher = config.slabWatcherEnabled
+  ? new SlabWatcher(getConnection, { programIds: config.allProgramIds, staleAfterMs: config.slabWatcherStaleMs })
+  : null;
+// Outbound webhooks registered through the API
+const webhookDispatcher = config.webhookDeliveryEnabled
+  ? new WebhookDispatcher({
+      maxAttempts: config.webhookMaxAttempts,
+      timeoutMs: config.webhookTimeoutMs,
+      allowPrivateHosts: process.env.NODE_ENV !== "production",
+    })
+  : null;
+// Cross-process eventBus — publishes trades and slab diffs, consumes keeper
+// crank events (TradeIndexer scans after each crank)
+let eventBridge: EventBridge | null = null;
 
 const app = new Hono();
 
@@ -59,10 +85,11 @@ app.get("/health", async (c) => {
   
   const statusCode = status === "down" ? 503 : 200;
   
-  return c.json({ status, checks, service: "indexer" }, statusCode);
+  return c.json({ status, checks, service: "indexer", slabWatcher: slabWatcher?.getStatus() ?? null, eventBus: eventBridge?.getStatus() ?? null, webhooks: webhookDispatcher?.getStatus() ?? null, stakePools: stakePoolService.getStatus() }, statusCode);
 });
 
 app.route("/", webhookRoutes());
+if (slabArchive) app.route("/", archiveRoutes(slabArchive));
 
 const port = Number(process.env.INDEXER_PORT ?? 3002);
 
@@ -87,10 +114,30 @@ setInterval(async () => {
 }, 30_000); // Check every 30s
 
 async function start() {
+  try {
+    eventBridge = await connectEventBus(
+      createEventTransport(config.eventBusTransport, {
+        databaseUrl: config.eventBusDatabaseUrl,
+        redisUrl: config.eventBusRedisUrl,
+      }),
+      {
+        consumer: config.eventBusConsumer || undefined,
+        replayFrom: config.eventBusReplayFrom || undefined,
+      },
+    );
+  } catch (err) {
+    logger.error("Event bus connection failed — running with in-process events only", { error: err });
+  }
+  // Before anything that publishes, so no event is missed
+  await webhookDispatcher?.start();
   await discovery.start();
   statsCollector.start();
   tradeIndexer.start();
   insuranceService.start();
+  stakePoolService.start();
+  slabArchiver?.start();
+  await slabWatcher?.start();
+  candleBuilder.start();
   await webhookManager.start();
   
   serve({ fetch: app.fetch, port }, (info) => {
@@ -130,9 +177,27 @@ async function shutdown(signal: string): Promise<void> {
     
     logger.info("Stopping insurance LP service");
     insuranceService.stop();
+
+    logger.info("Stopping stake pool service");
+    stakePoolService.stop();
+    
+    logger.info("Stopping candle builder");
+    candleBuilder.stop();
+    
+    logger.info("Stopping slab archiver");
+    slabArchiver?.stop();
+    
+    logger.info("Stopping slab watcher");
+    await slabWatcher?.stop();
     
     logger.info("Stopping webhook manager");
     webhookManager.stop();
+
+    logger.info("Stopping webhook dispatcher");
+    webhookDispatcher?.stop();
+
+    logger.info("Stopping event bridge");
+    await eventBridge?.stop();
     
     // Note: Solana connection and Supabase client don't need explicit cleanup
     
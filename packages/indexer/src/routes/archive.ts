@@ -0,0 +1,95 @@
+import { Hono } from "hono";
+import { parseSlabState } from "@percolator/sdk";
+import { SlabArchive, createLogger } from "@percolator/shared";
+
+const logger = createLogger("indexer:archive");
+
+const BASE58_PUBKEY = /^[1-9A-HJ-NP-Za-km-z]{32,44}$/;
+
+/** JSON with bigints as decimal strings (PublicKey serializes itself as base58). */
+function stringify(value: unknown): string {
+  return JSON.stringify(value, (_k, v) => (typeof v === "bigint" ? v.toString() : v));
+}
+
+function parseSlot(raw: string | undefined): number | null {
+  if (raw === undefined) return Number.MAX_SAFE_INTEGER; // latest
+  const slot = Number(raw);
+  return Number.isSafeInteger(slot) && slot >= 0 ? slot : null;
+}
+
+/**
+ * Point-in-time reads from the local slab archive (see SlabArchiver).
+ *
+ *   GET /archive/:slab/entries          archived slots and content hashes
+ *   GET /archive/:slab/state?slot=N     decoded config/params/engine/accounts as of slot N
+ *   GET /archive/:slab/raw?slot=N       raw slab bytes as of slot N (for dump scripts)
+ *
+ * `slot` defaults to the newest image. The response's `archivedSlot` is the
+ * slot the image was actually read at (the last change at or before `slot`).
+ */
+export function archiveRoutes(archive: SlabArchive): Hono {
+  const app = new Hono();
+
+  app.use("/archive/:slab/*", async (c, next) => {
+    if (!BASE58_PUBKEY.test(c.req.param("slab"))) return c.json({ error: "Invalid slab address" }, 400);
+    await next();
+  });
+
+  app.get("/archive/:slab/entries", async (c) => {
+    try {
+      const entries = await archive.entries(c.req.param("slab"));
+      return c.json({ slabAddress: c.req.param("slab"), entries });
+    } catch (err) {
+      logger.error("Archive entries error", { error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to read archive" }, 500);
+    }
+  });
+
+  app.get("/archive/:slab/state", async (c) => {
+    const slab = c.req.param("slab");
+    const slot = parseSlot(c.req.query("slot"));
+    if (slot === null) return c.json({ error: "Invalid slot" }, 400);
+
+    try {
+      const found = await archive.getAt(slab, slot);
+      if (!found) return c.json({ error: "No archived image at or before slot" }, 404);
+
+      const state = parseSlabState(found.data);
+      return c.body(
+        stringify({
+          slabAddress: slab,
+          archivedSlot: found.entry.slot,
+          archivedAt: found.entry.at,
+          hash: found.entry.hash,
+          ...state,
+        }),
+        200,
+        { "Content-Type": "application/json" },
+      );
+    } catch (err) {
+      logger.error("Archive state error", { slab, slot, error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to decode archived slab" }, 500);
+    }
+  });
+
+  app.get("/archive/:slab/raw", async (c) => {
+    const slab = c.req.param("slab");
+    const slot = parseSlot(c.req.query("slot"));
+    if (slot === null) return c.json({ error: "Invalid slot" }, 400);
+
+    try {
+      const found = await archive.getAt(slab, slot);
+      if (!found) return c.json({ error: "No archived image at or before slot" }, 404);
+      return c.body(found.data, 200, {
+        "Content-Type": "application/octet-stream",
+        "X-Archived-Slot": String(found.entry.slot),
+        "X-Content-Sha256": found.entry.hash,
+      });
+    } catch (err) {
+      logger.error("Archive raw error", { slab, slot, error: err instanceof Error ? err.message : err });
+      return c.json({ error: "Failed to read archive" }, 500);
+    }
+  });
+
+  return app;
+}
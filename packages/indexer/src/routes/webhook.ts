@@ -1,16 +1,19 @@
 import { Hono } from "hono";
-import { IX_TAG, detectSlabLayout } from "@percolator/sdk";
+import { PublicKey, type ParsedTransactionWithMeta } from "@solana/web3.js";
+import { IX_TAG, detectSlabLayout, decodeTransaction } from "@percolator/sdk";
 import { config, insertTrade, eventBus, decodeBase58, readU128LE, parseTradeSize, createLogger } from "@percolator/shared";
+import { recordInstructions } from "../services/InstructionIndexer.js";
 
 const logger = createLogger("indexer:webhook");
 
-const TRADE_TAGS = new Set<number>([IX_TAG.TradeNoCpi, IX_TAG.TradeCpi]);
+const TRADE_TAGS = new Set<number>([IX_TAG.TradeNoCpi, IX_TAG.TradeCpi, IX_TAG.TradeCpiV2]);
 const PROGRAM_IDS = new Set(config.allProgramIds);
 const BASE58_PUBKEY = /^[1-9A-HJ-NP-Za-km-z]{32,44}$/;
 
 /**
  * Helius Enhanced Transaction webhook receiver.
- * Parses trade instructions from enhanced tx data and stores them.
+ * Parses trade instructions from enhanced tx data and stores them, and records
+ * every other percolator instruction via InstructionIndexer.
  */
 export function webhookRoutes(): Hono {
   const app = new Hono();
@@ -49,8 +52,16 @@ export function webhookRoutes(): Hono {
 
 async function processTransactions(transactions: any[]): Promise<void> {
   let indexed = 0;
+  let recorded = 0;
 
   for (const tx of transactions) {
+    try {
+      const parsed = toParsedTransaction(tx);
+      if (parsed) recorded += await recordInstructions(decodeTransaction(parsed, PROGRAM_IDS));
+    } catch (err) {
+      logger.warn("Failed to record instructions", { error: err instanceof Error ? err.message : err });
+    }
+
     try {
       const trades = extractTradesFromEnhancedTx(tx);
       for (const trade of trades) {
@@ -78,6 +89,61 @@ async function processTransactions(transactions: any[]): Promise<void> {
   if (indexed > 0) {
     logger.info("Trades indexed", { count: indexed });
   }
+  if (recorded > 0) {
+    logger.info("Instructions recorded", { count: recorded });
+  }
+}
+
+/**
+ * Reshape a Helius enhanced transaction into the RPC `jsonParsed` form that
+ * `decodeTransaction` reads. Helius nests CPIs under each instruction
+ * (`instructions[i].innerInstructions`); RPC-style top-level
+ * `innerInstructions: [{ index, instructions }]` is accepted as well.
+ * Returns null if the payload has no signature or contains a malformed key.
+ */
+function toParsedTransaction(tx: any): ParsedTransactionWithMeta | null {
+  const signature = tx.signature ?? "";
+  if (!signature) return null;
+
+  const toIx = (ix: any) => ({
+    programId: new PublicKey(ix.programId),
+    accounts: ((ix.accounts ?? []) as string[]).map((a) => new PublicKey(a)),
+    data: ix.data ?? "",
+  });
+
+  try {
+    const outer: any[] = tx.instructions ?? [];
+    const inner = new Map<number, any[]>();
+    outer.forEach((ix, index) => {
+      if (Array.isArray(ix.innerInstructions) && ix.innerInstructions.length > 0) {
+        inner.set(index, ix.innerInstructions);
+      }
+    });
+    for (const group of tx.innerInstructions ?? []) {
+      if (typeof group.index === "number" && !inner.has(group.index)) {
+        inner.set(group.index, group.instructions ?? []);
+      }
+    }
+
+    return {
+      slot: tx.slot ?? 0,
+      blockTime: tx.timestamp ?? null,
+      transaction: {
+        signatures: [signature],
+        message: { accountKeys: [], recentBlockhash: "", instructions: outer.map(toIx) },
+      },
+      meta: {
+        err: tx.transactionError ?? null,
+        fee: tx.fee ?? 0,
+        preBalances: [],
+        postBalances: [],
+        logMessages: tx.logs ?? tx.logMessages ?? [],
+        innerInstructions: [...inner].map(([index, ixs]) => ({ index, instructions: ixs.map(toIx) })),
+      },
+    } as ParsedTransactionWithMeta;
+  } catch {
+    return null;
+  }
 }
 
 interface TradeData {
@@ -88,12 +154,15 @@ interface TradeData {
   price: number;
   fee: number;
   tx_signature: string;
+  block_time: string | null;
 }
 
 function extractTradesFromEnhancedTx(tx: any): TradeData[] {
   const trades: TradeData[] = [];
   const signature = tx.signature ?? "";
   if (!signature) return trades;
+  // Helius enhanced txs carry the block time as epoch seconds in `timestamp`
+  const blockTime = typeof tx.timestamp === "number" ? new Date(tx.timestamp * 1000).toISOString() : null;
 
   const instructions = tx.instructions ?? [];
 
@@ -135,6 +204,7 @@ function extractTradesFromEnhancedTx(tx: any): TradeData[] {
       price,
       fee,
       tx_signature: signature,
+      block_time: blockTime,
     });
   }
 
@@ -176,6 +246,7 @@ function extractTradesFromEnhancedTx(tx: any): TradeData[] {
         price,
         fee,
         tx_signature: signature,
+        block_time: blockTime,
       });
     }
   }
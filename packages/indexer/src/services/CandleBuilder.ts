@@ -0,0 +1,237 @@
+/**
+ * CandleBuilder — Maintains OHLCV candles from indexed trades and oracle prices.
+ *
+ * Each tick scans trades and oracle_prices rows *indexed* since the last tick
+ * (by created_at, then id) and marks the 1m bucket each one *belongs to* (by block or
+ * push time) as dirty. A trade indexed hours late therefore still lands in
+ * the right bucket. Dirty 1m buckets are rebuilt from raw points, then every
+ * enclosing 5m/15m/1h/4h/1d bucket is rolled up from the stored 1m candles.
+ *
+ * Trades with an unknown price (0) count toward volume but not OHLC. A bucket
+ * left with no priced point has its candle deleted, at every resolution.
+ */
+import {
+  CANDLE_RESOLUTION_SECONDS,
+  createLogger,
+  deleteCandles,
+  getCandles,
+  getOraclePricesInRange,
+  getOraclePricesIndexedSince,
+  getTradesInRange,
+  getTradesIndexedSince,
+  upsertCandles,
+  type CandleResolution,
+  type CandleRow,
+  type IndexCursor,
+  type IndexedPointRef,
+} from "@percolator/shared";
+
+const logger = createLogger("indexer:candles");
+
+const BUILD_INTERVAL_MS = 15_000;
+/** Rows fetched per scan page, and max pages per tick (bounds catch-up work) */
+const PAGE_SIZE = 1000;
+const MAX_PAGES_PER_TICK = 10;
+/** On startup, re-scan rows indexed in this window so restarts leave no gaps */
+const STARTUP_LOOKBACK_MS = 24 * 60 * 60 * 1000;
+
+const ROLLUP_RESOLUTIONS: CandleResolution[] = ["5m", "15m", "1h", "4h", "1d"];
+
+export interface CandlePoint {
+  /** Event time, epoch ms */
+  time: number;
+  /** USD price; 0 when unknown */
+  price: number;
+  /** Trade size (signed or not) for trades; undefined for oracle prices */
+  size?: bigint;
+}
+
+export function bucketStartMs(timeMs: number, resolution: CandleResolution): number {
+  const len = CANDLE_RESOLUTION_SECONDS[resolution] * 1000;
+  return Math.floor(timeMs / len) * len;
+}
+
+function toBigInt(v: string | number | bigint): bigint {
+  try {
+    return BigInt(v);
+  } catch {
+    return BigInt(Math.trunc(Number(v)));
+  }
+}
+
+/** Build one candle from raw points, or null if none of them carries a price. */
+export function buildCandle(
+  slabAddress: string,
+  resolution: CandleResolution,
+  bucketStart: number,
+  points: CandlePoint[],
+): CandleRow | null {
+  const sorted = [...points].sort((a, b) => a.time - b.time);
+  const priced = sorted.filter((p) => p.price > 0);
+  if (priced.length === 0) return null;
+
+  let volume = 0n;
+  let tradeCount = 0;
+  for (const p of sorted) {
+    if (p.size === undefined) continue;
+    volume += p.size < 0n ? -p.size : p.size;
+    tradeCount++;
+  }
+
+  return {
+    slab_address: slabAddress,
+    resolution,
+    bucket_start: new Date(bucketStart).toISOString(),
+    open: priced[0].price,
+    high: Math.max(...priced.map((p) => p.price)),
+    low: Math.min(...priced.map((p) => p.price)),
+    close: priced[priced.length - 1].price,
+    volume: volume.toString(),
+    trade_count: tradeCount,
+  };
+}
+
+/** Combine finer candles into one coarser candle, or null if there are none. */
+export function rollupCandles(
+  slabAddress: string,
+  resolution: CandleResolution,
+  bucketStart: number,
+  children: CandleRow[],
+): CandleRow | null {
+  if (children.length === 0) return null;
+  const sorted = [...children].sort((a, b) => Date.parse(a.bucket_start) - Date.parse(b.bucket_start));
+  return {
+    slab_address: slabAddress,
+    resolution,
+    bucket_start: new Date(bucketStart).toISOString(),
+    open: Number(sorted[0].open),
+    high: Math.max(...sorted.map((c) => Number(c.high))),
+    low: Math.min(...sorted.map((c) => Number(c.low))),
+    close: Number(sorted[sorted.length - 1].close),
+    volume: sorted.reduce((sum, c) => sum + toBigInt(c.volume), 0n).toString(),
+    trade_count: sorted.reduce((sum, c) => sum + Number(c.trade_count), 0),
+  };
+}
+
+export class CandleBuilder {
+  private timer: ReturnType<typeof setInterval> | null = null;
+  private _running = false;
+  private _building = false;
+  private tradeCursor: IndexCursor;
+  private oracleCursor: IndexCursor;
+  /** slab → dirty 1m bucket starts (ms); kept until rebuilt successfully */
+  private pending = new Map<string, Set<number>>();
+
+  constructor(now: number = Date.now()) {
+    const createdAt = new Date(now - STARTUP_LOOKBACK_MS).toISOString();
+    this.tradeCursor = { createdAt, id: null };
+    this.oracleCursor = { createdAt, id: null };
+  }
+
+  start(): void {
+    if (this._running) return;
+    this._running = true;
+    this.timer = setInterval(() => this.tick(), BUILD_INTERVAL_MS);
+    logger.info("CandleBuilder started", { intervalMs: BUILD_INTERVAL_MS });
+  }
+
+  stop(): void {
+    this._running = false;
+    if (this.timer) {
+      clearInterval(this.timer);
+      this.timer = null;
+    }
+    logger.info("CandleBuilder stopped");
+  }
+
+  /** Scan for new points and rebuild affected buckets. Returns 1m buckets rebuilt. */
+  async tick(): Promise<number> {
+    if (this._building) return 0;
+    this._building = true;
+    try {
+      await this.scan(this.tradeCursor, getTradesIndexedSince);
+      await this.scan(this.oracleCursor, getOraclePricesIndexedSince);
+
+      let rebuilt = 0;
+      for (const [slab, buckets] of this.pending) {
+        try {
+          await this.rebuild(slab, buckets);
+          rebuilt += buckets.size;
+          this.pending.delete(slab);
+        } catch (err) {
+          logger.warn("Candle rebuild failed", { slab, buckets: buckets.size, error: err instanceof Error ? err.message : err });
+        }
+      }
+      return rebuilt;
+    } catch (err) {
+      logger.error("Candle tick failed", { error: err instanceof Error ? err.message : err });
+      return 0;
+    } finally {
+      this._building = false;
+    }
+  }
+
+  private async scan(
+    cursor: IndexCursor,
+    fetch: (cursor: IndexCursor, limit: number) => Promise<IndexedPointRef[]>,
+  ): Promise<void> {
+    for (let page = 0; page < MAX_PAGES_PER_TICK; page++) {
+      const rows = await fetch({ ...cursor }, PAGE_SIZE);
+      for (const row of rows) {
+        this.markDirty(row.slab_address, Date.parse(row.time));
+        cursor.createdAt = row.created_at;
+        cursor.id = row.id;
+      }
+      if (rows.length < PAGE_SIZE) return;
+    }
+  }
+
+  private markDirty(slab: string, timeMs: number): void {
+    if (!Number.isFinite(timeMs)) return;
+    let set = this.pending.get(slab);
+    if (!set) this.pending.set(slab, (set = new Set()));
+    set.add(bucketStartMs(timeMs, "1m"));
+  }
+
+  private async rebuild(slab: string, minuteBuckets: Set<number>): Promise<void> {
+    const minuteLen = CANDLE_RESOLUTION_SECONDS["1m"] * 1000;
+    const rows: CandleRow[] = [];
+    const emptied: string[] = [];
+    for (const start of minuteBuckets) {
+      const end = start + minuteLen;
+      const [trades, prices] = await Promise.all([
+        getTradesInRange(slab, new Date(start).toISOString(), new Date(end).toISOString()),
+        getOraclePricesInRange(slab, start / 1000, end / 1000),
+      ]);
+      const points: CandlePoint[] = [
+        ...trades.map((t) => ({ time: Date.parse(t.time), price: t.price, size: toBigInt(t.size) })),
+        ...prices.map((p) => ({ time: Number(p.timestamp) * 1000, price: Number(p.price_e6) / 1_000_000 })),
+      ];
+      const candle = buildCandle(slab, "1m", start, points);
+      if (candle) rows.push(candle);
+      else emptied.push(new Date(start).toISOString());
+    }
+    await upsertCandles(rows);
+    await deleteCandles(slab, "1m", emptied);
+
+    // Roll each coarser resolution up from the (now current) 1m candles
+    for (const resolution of ROLLUP_RESOLUTIONS) {
+      const len = CANDLE_RESOLUTION_SECONDS[resolution] * 1000;
+      const parents = new Set([...minuteBuckets].map((b) => bucketStartMs(b, resolution)));
+      const rollups: CandleRow[] = [];
+      const emptiedParents: string[] = [];
+      for (const start of parents) {
+        const children = await getCandles(slab, "1m", {
+          from: new Date(start).toISOString(),
+          to: new Date(start + len).toISOString(),
+          limit: len / minuteLen,
+        });
+        const candle = rollupCandles(slab, resolution, start, children);
+        if (candle) rollups.push(candle);
+        else emptiedParents.push(new Date(start).toISOString());
+      }
+      await upsertCandles(rollups);
+      await deleteCandles(slab, resolution, emptiedParents);
+    }
+  }
+}
@@ -0,0 +1,195 @@
+import { PublicKey } from "@solana/web3.js";
+import { IX_ACCOUNT_SPECS, type DecodedInstruction, type DecodedTransaction } from "@percolator/sdk";
+import {
+  insertInstructionEvents,
+  insertCollateralEvents,
+  insertLiquidationEvents,
+  insertInsuranceMovements,
+  insertMarketAdminEvents,
+  createLogger,
+  type InstructionEventRow,
+  type CollateralEventRow,
+  type LiquidationEventRow,
+  type InsuranceMovementRow,
+  type MarketAdminEventRow,
+} from "@percolator/shared";
+
+const logger = createLogger("indexer:instructions");
+
+/** Admin / oracle-authority instructions recorded in market_admin_events */
+const ADMIN_INSTRUCTIONS = new Set<string>([
+  "InitMarket",
+  "SetRiskThreshold",
+  "UpdateAdmin",
+  "AcceptAdmin",
+  "RenounceAdmin",
+  "CloseSlab",
+  "UpdateConfig",
+  "SetMaintenanceFee",
+  "SetOracleAuthority",
+  "SetOraclePriceCap",
+  "SetPythOracle",
+  "UpdateRiskParams",
+  "ResolveMarket",
+  "UnresolveMarket",
+  "ResolveDispute",
+  "PauseMarket",
+  "UnpauseMarket",
+  "CreateInsuranceMint",
+  "SetInsuranceWithdrawPolicy",
+  "SetInsuranceIsolation",
+]);
+
+export interface ActivityRows {
+  instructions: InstructionEventRow[];
+  collateral: CollateralEventRow[];
+  liquidations: LiquidationEventRow[];
+  insurance: InsuranceMovementRow[];
+  admin: MarketAdminEventRow[];
+}
+
+/** Convert decoded args to JSON: bigints as decimal strings, pubkeys as base58, bytes as hex. */
+function toJsonArgs(args: object | null): Record<string, unknown> | null {
+  if (!args) return null;
+  const out: Record<string, unknown> = {};
+  for (const [key, value] of Object.entries(args)) {
+    if (typeof value === "bigint") out[key] = value.toString();
+    else if (value instanceof PublicKey) out[key] = value.toBase58();
+    else if (value instanceof Uint8Array) out[key] = Buffer.from(value).toString("hex");
+    else out[key] = value;
+  }
+  return out;
+}
+
+/** The instruction's first signer according to its account spec. */
+function signerOf(ix: DecodedInstruction): string | null {
+  const spec = ix.name === "Unknown" ? undefined : IX_ACCOUNT_SPECS[ix.name];
+  const signer = spec?.find((s) => s.signer);
+  return signer ? (ix.accounts[signer.name]?.toBase58() ?? null) : null;
+}
+
+/**
+ * Map every percolator instruction of a successful transaction to its
+ * instruction_events row and, where it has one, its typed table row.
+ * Trades are not included here — TradeIndexer/webhook write them to `trades`
+ * with price and fee attached.
+ */
+export function buildActivityRows(tx: DecodedTransaction): ActivityRows {
+  const rows: ActivityRows = { instructions: [], collateral: [], liquidations: [], insurance: [], admin: [] };
+  if (!tx.success) return rows;
+
+  const blockTime = tx.blockTime != null ? new Date(tx.blockTime * 1000).toISOString() : null;
+
+  for (const ix of tx.instructions) {
+    const key = {
+      tx_signature: tx.signature,
+      ix_index: ix.outerIndex,
+      inner_index: ix.innerIndex ?? -1,
+      slot: tx.slot,
+      block_time: blockTime,
+    };
+    const slab = ix.accounts.slab?.toBase58() ?? null;
+    const signer = signerOf(ix);
+
+    rows.instructions.push({
+      ...key,
+      slab_address: slab,
+      tag: ix.tag,
+      name: ix.name,
+      signer,
+      args: toJsonArgs(ix.args),
+    });
+
+    if (!slab) continue;
+    const base = { ...key, slab_address: slab };
+
+    switch (ix.name) {
+      case "InitUser":
+      case "InitLP":
+        if (signer) {
+          rows.collateral.push({
+            ...base,
+            event_type: ix.name === "InitUser" ? "init_user" : "init_lp",
+            owner: signer,
+            account_idx: null,
+            amount: ix.args.feePayment.toString(),
+          });
+        }
+        break;
+      case "DepositCollateral":
+      case "WithdrawCollateral":
+        if (signer) {
+          rows.collateral.push({
+            ...base,
+            event_type: ix.name === "DepositCollateral" ? "deposit" : "withdraw",
+            owner: signer,
+            account_idx: ix.args.userIdx,
+            amount: ix.args.amount.toString(),
+          });
+        }
+        break;
+      case "CloseAccount":
+        if (signer) {
+          rows.collateral.push({ ...base, event_type: "close_account", owner: signer, account_idx: ix.args.userIdx, amount: null });
+        }
+        break;
+      case "LiquidateAtOracle":
+      case "AdminForceClose":
+        rows.liquidations.push({
+          ...base,
+          event_type: ix.name === "LiquidateAtOracle" ? "liquidation" : "force_close",
+          // LiquidateAtOracle is permissionless; its first account is unused,
+          // so the fee payer isn't visible here
+          caller: signer,
+          target_idx: ix.args.targetIdx,
+        });
+        break;
+      case "TopUpInsurance":
+        rows.insurance.push({ ...base, event_type: "top_up", actor: signer, amount: ix.args.amount.toString() });
+        break;
+      case "DepositInsuranceLP":
+        rows.insurance.push({ ...base, event_type: "lp_deposit", actor: signer, amount: ix.args.amount.toString() });
+        break;
+      case "WithdrawInsuranceLP":
+        rows.insurance.push({ ...base, event_type: "lp_withdraw", actor: signer, amount: ix.args.lpAmount.toString() });
+        break;
+      case "FundMarketInsurance":
+        rows.insurance.push({ ...base, event_type: "fund_market", actor: signer, amount: ix.args.amount.toString() });
+        break;
+      case "WithdrawInsurance":
+        rows.insurance.push({ ...base, event_type: "admin_withdraw", actor: signer, amount: null });
+        break;
+      default:
+        if (ADMIN_INSTRUCTIONS.has(ix.name)) {
+          rows.admin.push({ ...base, name: ix.name, authority: signer, args: toJsonArgs(ix.args) });
+        }
+    }
+  }
+  return rows;
+}
+
+/**
+ * Persist every percolator instruction in a decoded transaction.
+ * Idempotent: rows already indexed (by either the poller or the webhook) are skipped.
+ * Returns the number of instructions recorded.
+ */
+export async function recordInstructions(tx: DecodedTransaction): Promise<number> {
+  if (tx.undecoded.length > 0) {
+    logger.warn("Undecodable percolator instructions", {
+      signature: tx.signature.slice(0, 12),
+      reasons: tx.undecoded.map((u) => u.reason),
+    });
+  }
+
+  const rows = buildActivityRows(tx);
+  if (rows.instructions.length === 0) return 0;
+
+  await insertInstructionEvents(rows.instructions);
+  await Promise.all([
+    insertCollateralEvents(rows.collateral),
+    insertLiquidationEvents(rows.liquidations),
+    insertInsuranceMovements(rows.insurance),
+    insertMarketAdminEvents(rows.admin),
+  ]);
+  return rows.instructions.length;
+}
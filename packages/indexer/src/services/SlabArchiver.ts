@@ -0,0 +1,81 @@
+/**
+ * SlabArchiver — Periodically stores raw slab images in the local SlabArchive.
+ *
+ * Each cycle batch-reads every known slab together with the RPC context slot
+ * and hands the bytes to SlabArchive, which only records images whose content
+ * changed. The archive backs point-in-time reads (`/archive/:slab/state`) for
+ * debugging cranks, liquidations and admin actions after the fact.
+ */
+import { PublicKey } from "@solana/web3.js";
+import { config, createLogger, getConnection, withRetry, SlabArchive } from "@percolator/shared";
+import type { MarketProvider } from "./StatsCollector.js";
+
+const logger = createLogger("indexer:slab-archiver");
+
+/** Slabs per getMultipleAccountsInfo call (RPC limit is 100) */
+const BATCH_SIZE = 20;
+
+export class SlabArchiver {
+  private timer: ReturnType<typeof setInterval> | null = null;
+  private _running = false;
+  private _archiving = false;
+
+  constructor(
+    private readonly marketProvider: MarketProvider,
+    private readonly archive: SlabArchive = new SlabArchive(config.slabArchiveDir),
+    private readonly intervalMs: number = config.slabArchiveIntervalMs,
+  ) {}
+
+  start(): void {
+    if (this._running) return;
+    this._running = true;
+    this.timer = setInterval(() => this.archiveAll(), this.intervalMs);
+    logger.info("SlabArchiver started", { intervalMs: this.intervalMs, dir: config.slabArchiveDir });
+  }
+
+  stop(): void {
+    this._running = false;
+    if (this.timer) {
+      clearInterval(this.timer);
+      this.timer = null;
+    }
+    logger.info("SlabArchiver stopped");
+  }
+
+  /** One archive pass over all known markets. Returns the number of new images stored. */
+  async archiveAll(): Promise<number> {
+    if (this._archiving) return 0;
+    this._archiving = true;
+
+    let written = 0;
+    try {
+      const slabs = Array.from(this.marketProvider.getMarkets().keys());
+      const connection = getConnection();
+
+      for (let i = 0; i < slabs.length; i += BATCH_SIZE) {
+        const batch = slabs.slice(i, i + BATCH_SIZE);
+        try {
+          const { context, value } = await withRetry(
+            () => connection.getMultipleAccountsInfoAndContext(batch.map((s) => new PublicKey(s))),
+            { maxRetries: 3, baseDelayMs: 1000, label: `slabArchive(batch ${i / BATCH_SIZE + 1})` },
+          );
+          for (const [j, info] of value.entries()) {
+            if (!info?.data) continue;
+            const res = await this.archive.put(batch[j], context.slot, new Uint8Array(info.data));
+            if (res.written) written++;
+          }
+        } catch (err) {
+          logger.warn("Slab archive batch failed", {
+            batch: i / BATCH_SIZE + 1,
+            error: err instanceof Error ? err.message : err,
+          });
+        }
+      }
+
+      if (written > 0) logger.debug("Slab images archived", { written, slabs: slabs.length });
+    } finally {
+      this._archiving = false;
+    }
+    return written;
+  }
+}
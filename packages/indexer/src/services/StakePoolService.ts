@@ -0,0 +1,196 @@
+/**
+ * StakePoolService — Snapshots every percolator-stake pool.
+ *
+ * InsuranceLPService only follows the legacy insurance LP mint; this reads
+ * the stake program's StakePool accounts directly. Each poll stores every
+ * pool's value, LP share price (overall, senior and junior tranche) and HWM
+ * state in stake_pool_snapshots.
+ *
+ * Growth in total_flushed / total_returned since the previous poll is stored
+ * as flush / return events (flows to and from the market's insurance fund),
+ * and the pool value crossing its HWM floor as hwm_breach / hwm_recovered.
+ * A breach also raises a warning alert.
+ *
+ * Trailing APY is derived from the snapshots by the API (/stake/:pool/apy).
+ */
+import { PublicKey, type Connection } from "@solana/web3.js";
+import {
+  STAKE_POOL_SIZE,
+  STAKE_PROGRAM_ID,
+  computeStakePoolMetrics,
+  decodeStakePool,
+  type StakePoolState,
+} from "@percolator/sdk";
+import {
+  config,
+  createLogger,
+  getConnection,
+  getLatestStakePoolSnapshot,
+  insertStakePoolEvents,
+  insertStakePoolSnapshots,
+  sendWarningAlert,
+  type StakePoolEventRow,
+  type StakePoolSnapshotRow,
+} from "@percolator/shared";
+
+const logger = createLogger("indexer:stake-pools");
+
+const POLL_INTERVAL_MS = 120_000;
+
+type PreviousSnapshot = Pick<StakePoolSnapshotRow, "total_flushed" | "total_returned" | "hwm_breached">;
+
+export function stakePoolSnapshotRow(poolAddress: string, pool: StakePoolState, slot: number): StakePoolSnapshotRow {
+  const m = computeStakePoolMetrics(pool);
+  return {
+    pool_address: poolAddress,
+    slab_address: pool.slab.toBase58(),
+    slot,
+    pool_mode: pool.poolMode,
+    total_deposited: pool.totalDeposited.toString(),
+    total_withdrawn: pool.totalWithdrawn.toString(),
+    total_flushed: pool.totalFlushed.toString(),
+    total_returned: pool.totalReturned.toString(),
+    total_fees_earned: pool.totalFeesEarned.toString(),
+    total_lp_supply: m.lpSupply.toString(),
+    pool_value: m.value.toString(),
+    share_price_e6: m.sharePriceE6.toString(),
+    tranche_enabled: pool.trancheEnabled,
+    senior_value: m.senior.value.toString(),
+    senior_lp_supply: m.senior.lpSupply.toString(),
+    senior_share_price_e6: m.senior.sharePriceE6.toString(),
+    junior_value: m.junior?.value.toString() ?? null,
+    junior_lp_supply: m.junior?.lpSupply.toString() ?? null,
+    junior_share_price_e6: m.junior?.sharePriceE6.toString() ?? null,
+    junior_fee_mult_bps: pool.juniorFeeMultBps,
+    hwm_enabled: pool.hwmEnabled,
+    epoch_high_water_tvl: pool.epochHighWaterTvl.toString(),
+    hwm_floor_bps: pool.hwmFloorBps,
+    hwm_floor: m.hwmFloor?.toString() ?? null,
+    hwm_breached: m.hwmBreached,
+  };
+}
+
+/**
+ * Flows and HWM transitions between two snapshots of one pool. Nothing on the
+ * first snapshot: its cumulative totals predate indexing.
+ */
+export function stakePoolEvents(prev: PreviousSnapshot | null, row: StakePoolSnapshotRow): StakePoolEventRow[] {
+  if (!prev) return [];
+  const base = {
+    pool_address: row.pool_address,
+    slab_address: row.slab_address,
+    slot: row.slot,
+    pool_value: row.pool_value,
+    hwm_floor: row.hwm_floor,
+  };
+  const events: StakePoolEventRow[] = [];
+
+  const flushed = BigInt(row.total_flushed) - BigInt(prev.total_flushed);
+  if (flushed > 0n) events.push({ ...base, kind: "flush", amount: flushed.toString() });
+  const returned = BigInt(row.total_returned) - BigInt(prev.total_returned);
+  if (returned > 0n) events.push({ ...base, kind: "return", amount: returned.toString() });
+
+  if (row.hwm_breached !== prev.hwm_breached) {
+    events.push({ ...base, kind: row.hwm_breached ? "hwm_breach" : "hwm_recovered", amount: row.pool_value });
+  }
+  return events;
+}
+
+export class StakePoolService {
+  private timer: ReturnType<typeof setInterval> | null = null;
+  private _polling = false;
+  private readonly programId: PublicKey;
+  /** Last stored snapshot per pool, loaded from the DB on first sight */
+  private readonly previous = new Map<string, PreviousSnapshot>();
+  private stats = { pools: 0, breached: 0, lastPollAt: null as string | null };
+
+  constructor(
+    programId?: PublicKey,
+    private readonly connectionFn: () => Connection = getConnection,
+  ) {
+    this.programId = programId ?? (config.stakeProgramId ? new PublicKey(config.stakeProgramId) : STAKE_PROGRAM_ID);
+  }
+
+  start(): void {
+    if (this.timer) return;
+    if (!config.supabaseUrl || !config.supabaseKey) {
+      logger.warn("SUPABASE_URL/KEY not set, service disabled");
+      return;
+    }
+    this.poll().catch((e) => logger.error("Failed to run initial poll", { error: e }));
+    this.timer = setInterval(() => {
+      this.poll().catch((e) => logger.error("Failed to poll stake pools", { error: e }));
+    }, POLL_INTERVAL_MS);
+    logger.info("StakePoolService started", { programId: this.programId.toBase58(), intervalMs: POLL_INTERVAL_MS });
+  }
+
+  stop(): void {
+    if (this.timer) {
+      clearInterval(this.timer);
+      this.timer = null;
+    }
+  }
+
+  getStatus() {
+    return { programId: this.programId.toBase58(), ...this.stats };
+  }
+
+  /** Snapshot every initialized pool once. Returns the number stored. */
+  async poll(): Promise<number> {
+    if (this._polling) return 0;
+    this._polling = true;
+    try {
+      const { context, value: accounts } = await this.connectionFn().getProgramAccounts(this.programId, {
+        filters: [{ dataSize: STAKE_POOL_SIZE }],
+        withContext: true,
+      });
+
+      const rows: StakePoolSnapshotRow[] = [];
+      for (const { pubkey, account } of accounts) {
+        try {
+          const pool = decodeStakePool(account.data);
+          if (!pool.isInitialized) continue;
+          rows.push(stakePoolSnapshotRow(pubkey.toBase58(), pool, context.slot));
+        } catch (err) {
+          logger.warn("Failed to decode stake pool", { pool: pubkey.toBase58(), error: err instanceof Error ? err.message : err });
+        }
+      }
+
+      const events: StakePoolEventRow[] = [];
+      for (const row of rows) {
+        events.push(...stakePoolEvents(await this.previousSnapshot(row.pool_address), row));
+      }
+
+      await insertStakePoolSnapshots(rows);
+      await insertStakePoolEvents(events);
+      for (const row of rows) this.previous.set(row.pool_address, row);
+
+      for (const event of events.filter((e) => e.kind === "hwm_breach")) {
+        logger.warn("Stake pool below HWM floor", { pool: event.pool_address, value: event.pool_value, floor: event.hwm_floor });
+        await sendWarningAlert("Stake pool below HWM floor", [
+          { name: "Pool", value: event.pool_address },
+          { name: "Slab", value: event.slab_address },
+          { name: "Value", value: event.pool_value, inline: true },
+          { name: "Floor", value: event.hwm_floor ?? "-", inline: true },
+        ]);
+      }
+
+      this.stats = {
+        pools: rows.length,
+        breached: rows.filter((r) => r.hwm_breached).length,
+        lastPollAt: new Date().toISOString(),
+      };
+      return rows.length;
+    } finally {
+      this._polling = false;
+    }
+  }
+
+  private async previousSnapshot(poolAddress: string): Promise<PreviousSnapshot | null> {
+    const cached = this.previous.get(poolAddress);
+    if (cached) return cached;
+    const stored = await getLatestStakePoolSnapshot(poolAddress);
+    if (stored) this.previous.set(poolAddress, stored);
+    return stored;
+  }
+}
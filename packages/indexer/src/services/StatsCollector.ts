@@ -4,6 +4,7 @@
  * Runs after each crank cycle to read on-chain slab data and persist:
  * - Market stats (OI, vault, accounts, insurance, prices)
  * - Oracle prices (for price chart history)
+ * - Per-account snapshots (only accounts whose state changed since the last cycle)
  *
  * This closes two architecture gaps:
  * 1. market_stats table was never populated
@@ -15,6 +16,7 @@ import {
   parseConfig,
   parseParams,
   parseAllAccounts,
+  type Account,
   type EngineState,
   type MarketConfig,
   type RiskParams,
@@ -24,6 +26,8 @@ import {
   getConnection,
   upsertMarketStats, 
   insertOraclePrice, 
+  insertAccountSnapshots,
+  getLatestOpenAccountSnapshots,
   get24hVolume,
   getMarkets,
   insertMarket,
@@ -32,6 +36,7 @@ import {
   createLogger,
   captureException,
   addBreadcrumb,
+  type AccountSnapshotRow,
 } from "@percolator/shared";
 
 const logger = createLogger("indexer:stats-collector");
@@ -47,6 +52,69 @@ const COLLECT_INTERVAL_MS = 120_000;
 /** How often to log oracle prices to DB (every 60s per market to avoid bloat) */
 const ORACLE_LOG_INTERVAL_MS = 60_000;
 
+/** Fields whose change produces a new account snapshot (slot and price alone don't) */
+function snapshotKey(row: AccountSnapshotRow): string {
+  return [
+    row.owner, row.kind, row.capital, row.pnl, row.reserved_pnl, row.position_size,
+    row.entry_price_e6, row.funding_index, row.fee_credits,
+  ].join("|");
+}
+
+/**
+ * Compare freshly parsed accounts with the last stored snapshot of each index.
+ * Returns rows for new or changed accounts, plus a `closed` row for every
+ * index that was open last time and is gone now, and the state to keep for
+ * the next cycle.
+ */
+export function diffAccountSnapshots(
+  previous: ReadonlyMap<number, AccountSnapshotRow>,
+  accounts: { idx: number; account: Account }[],
+  ctx: { slabAddress: string; slot: number; priceE6: bigint },
+): { rows: AccountSnapshotRow[]; next: Map<number, AccountSnapshotRow> } {
+  const rows: AccountSnapshotRow[] = [];
+  const next = new Map<number, AccountSnapshotRow>();
+  const priceE6 = ctx.priceE6 > 0n ? ctx.priceE6.toString() : null;
+
+  for (const { idx, account } of accounts) {
+    const row: AccountSnapshotRow = {
+      slab_address: ctx.slabAddress,
+      account_idx: idx,
+      owner: account.owner.toBase58(),
+      kind: account.kind,
+      slot: ctx.slot,
+      capital: account.capital.toString(),
+      pnl: account.pnl.toString(),
+      reserved_pnl: account.reservedPnl.toString(),
+      position_size: account.positionSize.toString(),
+      entry_price_e6: account.entryPrice.toString(),
+      funding_index: account.fundingIndex.toString(),
+      fee_credits: account.feeCredits.toString(),
+      price_e6: priceE6,
+      closed: false,
+    };
+    const prev = previous.get(idx);
+    if (!prev || snapshotKey(prev) !== snapshotKey(row)) rows.push(row);
+    next.set(idx, prev && snapshotKey(prev) === snapshotKey(row) ? prev : row);
+  }
+
+  for (const [idx, prev] of previous) {
+    if (next.has(idx)) continue;
+    rows.push({
+      ...prev,
+      slot: ctx.slot,
+      capital: "0",
+      pnl: "0",
+      reserved_pnl: "0",
+      position_size: "0",
+      fee_credits: "0",
+      price_e6: priceE6,
+      closed: true,
+    });
+  }
+
+  return { rows, next };
+}
+
 export class StatsCollector {
   private timer: ReturnType<typeof setInterval> | null = null;
   private _running = false;
@@ -56,6 +124,12 @@ export class StatsCollector {
   private lastOiHistoryTime = new Map<string, number>();
   private lastInsHistoryTime = new Map<string, number>();
   private lastFundingHistoryTime = new Map<string, number>();
+  /**
+   * Last stored snapshot per slab → account index. Seeded from the database
+   * the first time a slab is seen, so a restart only stores accounts that
+   * changed while the indexer was down.
+   */
+  private lastAccountSnapshots = new Map<string, Map<number, AccountSnapshotRow>>();
 
   constructor(
     private readonly marketProvider: MarketProvider,
@@ -257,8 +331,9 @@ export class StatsCollector {
             // Calculate open interest (separate long/short)
             let oiLong = 0n;
             let oiShort = 0n;
+            let accounts: { idx: number; account: Account }[] | null = null;
             try {
-              const accounts = parseAllAccounts(data);
+              accounts = parseAllAccounts(data);
               for (const { account } of accounts) {
                 if (account.positionSize > 0n) {
                   oiLong += account.positionSize;
@@ -389,6 +464,26 @@ export class StatsCollector {
               updated_at: new Date().toISOString(),
             });
 
+            // Store changed account snapshots. Skipped when account parsing
+            // failed — an empty parse must not be read as "every account closed".
+            if (accounts) {
+              try {
+                const previous =
+                  this.lastAccountSnapshots.get(slabAddress) ??
+                  (await getLatestOpenAccountSnapshots(slabAddress));
+                const { rows, next } = diffAccountSnapshots(
+                  previous,
+                  accounts,
+                  { slabAddress, slot: safePgBigint(engine.currentSlot), priceE6 },
+                );
+                await insertAccountSnapshots(rows);
+                this.lastAccountSnapshots.set(slabAddress, next);
+              } catch (snapErr) {
+                // Non-fatal; state isn't advanced, so the same diff is retried next cycle
+                logger.warn("Account snapshot log failed", { slabAddress, error: snapErr instanceof Error ? snapErr.message : snapErr });
+              }
+            }
+
             // Log oracle price to DB (rate-limited per market)
             if (priceE6 > 0n) {
               const lastLog = this.lastOracleLogTime.get(slabAddress) ?? 0;
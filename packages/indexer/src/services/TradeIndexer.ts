@@ -1,11 +1,15 @@
 import { Connection, PublicKey, type ParsedTransactionWithMeta } from "@solana/web3.js";
-import { IX_TAG, detectSlabLayout } from "@percolator/sdk";
+import { IX_TAG, detectSlabLayout, decodeTransaction } from "@percolator/sdk";
+import { recordInstructions } from "./InstructionIndexer.js";
 import { config, getConnection, insertTrade, tradeExistsBySignature, getMarkets, eventBus, decodeBase58, readU128LE, parseTradeSize, withRetry, createLogger, captureException, addBreadcrumb } from "@percolator/shared";
 
 const logger = createLogger("indexer:trade-indexer");
 
-/** Trade instruction tags we want to index */
-const TRADE_TAGS = new Set<number>([IX_TAG.TradeNoCpi, IX_TAG.TradeCpi]);
+/**
+ * Trade instruction tags written to `trades`. Every other instruction is
+ * recorded by InstructionIndexer into instruction_events and the typed tables.
+ */
+const TRADE_TAGS = new Set<number>([IX_TAG.TradeNoCpi, IX_TAG.TradeCpi, IX_TAG.TradeCpiV2]);
 
 /** How many recent signatures to fetch per slab per cycle */
 const MAX_SIGNATURES = 50;
@@ -18,6 +22,8 @@ const BACKFILL_SIGNATURES = 100;
 
 /**
  * TradeIndexerPolling — backup/backfill trade indexer using on-chain polling.
+ * Besides trades, every percolator instruction in the fetched transactions is
+ * recorded via InstructionIndexer (deposits, liquidations, admin updates, ...).
  *
  * Primary indexing is now webhook-driven (see HeliusWebhookManager + webhook routes).
  * This poller runs on startup for backfill, then every 5 minutes as a catchall.
@@ -240,6 +246,16 @@ export class TradeIndexerPolling {
   ): Promise<boolean> {
     if (!tx.meta || tx.meta.err) return false;
 
+    // Non-fatal: a failed activity write must not block trade indexing
+    try {
+      await recordInstructions(decodeTransaction(tx, programIds));
+    } catch (err) {
+      logger.warn("Failed to record instructions", {
+        signature: signature.slice(0, 12),
+        error: err instanceof Error ? err.message : err,
+      });
+    }
+
     const message = tx.transaction.message;
 
     for (const ix of message.instructions) {
@@ -309,6 +325,7 @@ export class TradeIndexerPolling {
         price,
         fee,
         tx_signature: signature,
+        block_time: tx.blockTime ? new Date(tx.blockTime * 1000).toISOString() : null,
       });
 
       eventBus.publish("trade.executed", slabAddress, { signature, trader, side, size: sizeValue.toString() });
@@ -0,0 +1,352 @@
+/**
+ * WebhookDispatcher — Delivers eventBus events to registered outbound webhooks.
+ *
+ * Every event (local or arriving over EVENT_BUS_TRANSPORT) is matched against
+ * the active subscriptions' event and slab filters and queued as one
+ * webhook_deliveries row per match. The queue key is derived from the event,
+ * so a replica seeing the same event doesn't queue it twice.
+ *
+ * A worker loop claims due deliveries and POSTs them, HMAC-signed with the
+ * subscription's secret. Anything but a 2xx is retried with exponential
+ * backoff; after `maxAttempts` the delivery is dead-lettered. Every attempt
+ * is logged in webhook_delivery_attempts.
+ *
+ * An event whose rows can't be written (database down) is held in memory and
+ * retried at the start of every tick, ahead of new events.
+ *
+ * Unless `allowPrivateHosts` is set, deliveries connect through `publicLookup`:
+ * an endpoint whose name now resolves to a private address fails the attempt.
+ *
+ * Subscriptions are cached and re-read every 30s, so one registered through
+ * the API starts receiving events within that window.
+ */
+import { createHash, createHmac } from "node:crypto";
+import { request as httpRequest } from "node:http";
+import { request as httpsRequest } from "node:https";
+import {
+  claimDueWebhookDeliveries,
+  createLogger,
+  enqueueWebhookDeliveries,
+  eventBus,
+  getActiveWebhookSubscriptions,
+  insertWebhookAttempt,
+  insertWebhookDeadLetter,
+  isPrivateHostname,
+  PrivateAddressError,
+  publicLookup,
+  releaseStaleWebhookDeliveries,
+  updateWebhookDelivery,
+  type EventPayload,
+  type WebhookDeliveryRow,
+  type WebhookSubscriptionRow,
+} from "@percolator/shared";
+
+const logger = createLogger("indexer:webhooks");
+
+const TICK_INTERVAL_MS = 2_000;
+const SUBSCRIPTION_REFRESH_MS = 30_000;
+/** Deliveries claimed (and sent concurrently) per tick */
+const BATCH_SIZE = 50;
+const RETRY_BASE_MS = 10_000;
+const RETRY_MAX_MS = 60 * 60 * 1000;
+/** A delivery still "delivering" after this was claimed by a dispatcher that died */
+const STALE_CLAIM_MS = 5 * 60 * 1000;
+/** Events held for re-enqueue while the database is unavailable; the oldest go first past this */
+const MAX_UNQUEUED_EVENTS = 10_000;
+
+export const WEBHOOK_SIGNATURE_HEADER = "X-Percolator-Signature";
+
+export interface WebhookDispatcherOptions {
+  maxAttempts: number;
+  timeoutMs: number;
+  /** Skip the public-address check (local development receivers) */
+  allowPrivateHosts?: boolean;
+}
+
+type Fetch = (url: string, init: RequestInit) => Promise<Response>;
+type Bus = Pick<typeof eventBus, "subscribe">;
+
+/** Deterministic per event: the same event seen by two processes gets the same key. */
+export function webhookEventKey(payload: EventPayload): string {
+  return createHash("sha256")
+    .update(`${payload.event}|${payload.slabAddress}|${payload.timestamp}|${JSON.stringify(payload.data)}`)
+    .digest("hex");
+}
+
+export function matchesSubscription(sub: Pick<WebhookSubscriptionRow, "events" | "slabs">, payload: EventPayload): boolean {
+  return (sub.events.length === 0 || sub.events.includes(payload.event))
+    && (sub.slabs.length === 0 || sub.slabs.includes(payload.slabAddress));
+}
+
+/**
+ * `sha256=<hex>` HMAC over `${timestamp}.${body}`. Receivers recompute it with
+ * their secret and reject stale timestamps to stop replays.
+ */
+export function signWebhook(secret: string, timestampSec: number, body: string): string {
+  return `sha256=${createHmac("sha256", secret).update(`${timestampSec}.${body}`).digest("hex")}`;
+}
+
+/**
+ * POST through node:http(s) with `publicLookup`, so the socket connects to the
+ * address that passed the check rather than whatever the name resolves to
+ * next. Redirects are never followed; the body is discarded.
+ */
+export function publicFetch(url: string, init: RequestInit): Promise<Response> {
+  const target = new URL(url);
+  if (isPrivateHostname(target.hostname)) return Promise.reject(new PrivateAddressError(target.hostname));
+  const request = target.protocol === "https:" ? httpsRequest : httpRequest;
+  return new Promise((resolve, reject) => {
+    const req = request(
+      target,
+      {
+        method: init.method,
+        headers: init.headers as Record<string, string>,
+        lookup: publicLookup,
+        signal: init.signal ?? undefined,
+      },
+      (res) => {
+        res.resume();
+        resolve(new Response(null, { status: res.statusCode ?? 500 }));
+      },
+    );
+    req.on("error", reject);
+    req.end(init.body as string | undefined);
+  });
+}
+
+/** Backoff before retry number `attempts` (1-based): 10s, 20s, 40s, ... capped at 1h. */
+export function webhookRetryDelayMs(attempts: number): number {
+  return Math.min(RETRY_BASE_MS * 2 ** Math.max(attempts - 1, 0), RETRY_MAX_MS);
+}
+
+export class WebhookDispatcher {
+  private subscriptions: WebhookSubscriptionRow[] = [];
+  private unsubscribe: (() => void) | null = null;
+  private tickTimer: ReturnType<typeof setInterval> | null = null;
+  private refreshTimer: ReturnType<typeof setInterval> | null = null;
+  private _sending = false;
+  /** Events whose deliveries couldn't be written yet, oldest first */
+  private unqueued: EventPayload[] = [];
+  private stats = { enqueued: 0, delivered: 0, failedAttempts: 0, deadLettered: 0, droppedEvents: 0 };
+
+  constructor(
+    private readonly options: WebhookDispatcherOptions,
+    private readonly fetchFn: Fetch = options.allowPrivateHosts ? (url, init) => fetch(url, init) : publicFetch,
+    private readonly bus: Bus = eventBus,
+  ) {}
+
+  async start(): Promise<void> {
+    if (this.unsubscribe) return;
+    await this.refreshSubscriptions();
+    this.unsubscribe = this.bus.subscribe("*", (payload) => {
+      void this.enqueue(payload);
+    });
+    this.refreshTimer = setInterval(() => void this.refreshSubscriptions(), SUBSCRIPTION_REFRESH_MS);
+    this.tickTimer = setInterval(() => void this.tick(), TICK_INTERVAL_MS);
+    logger.info("WebhookDispatcher started", { subscriptions: this.subscriptions.length, ...this.options });
+  }
+
+  stop(): void {
+    this.unsubscribe?.();
+    this.unsubscribe = null;
+    if (this.tickTimer) clearInterval(this.tickTimer);
+    if (this.refreshTimer) clearInterval(this.refreshTimer);
+    this.tickTimer = null;
+    this.refreshTimer = null;
+    logger.info("WebhookDispatcher stopped");
+  }
+
+  getStatus() {
+    return { subscriptions: this.subscriptions.length, unqueuedEvents: this.unqueued.length, ...this.stats };
+  }
+
+  async refreshSubscriptions(): Promise<void> {
+    try {
+      this.subscriptions = await getActiveWebhookSubscriptions();
+    } catch (err) {
+      logger.warn("Webhook subscription refresh failed", { error: err instanceof Error ? err.message : err });
+    }
+  }
+
+  /**
+   * Queue one delivery per matching subscription. If the write fails the
+   * event is held and retried by `tick`; returns the rows written now.
+   */
+  async enqueue(payload: EventPayload): Promise<number> {
+    if (this.unqueued.length === 0) {
+      try {
+        return await this.writeDeliveries(payload);
+      } catch (err) {
+        logger.error("Webhook enqueue failed; holding event for retry", { event: payload.event, slab: payload.slabAddress, error: err instanceof Error ? err.message : err });
+      }
+    }
+    // Behind earlier failures: keep arrival order
+    this.unqueued.push(payload);
+    if (this.unqueued.length > MAX_UNQUEUED_EVENTS) {
+      const dropped = this.unqueued.shift()!;
+      this.stats.droppedEvents++;
+      logger.error("Webhook event dropped: retry buffer full", { event: dropped.event, slab: dropped.slabAddress, buffered: MAX_UNQUEUED_EVENTS });
+    }
+    return 0;
+  }
+
+  /** Write held events in order, stopping at the first that still fails. */
+  async flushUnqueued(): Promise<number> {
+    let flushed = 0;
+    while (this.unqueued.length > 0) {
+      try {
+        await this.writeDeliveries(this.unqueued[0]);
+      } catch (err) {
+        logger.warn("Webhook enqueue retry failed", { buffered: this.unqueued.length, error: err instanceof Error ? err.message : err });
+        break;
+      }
+      this.unqueued.shift();
+      flushed++;
+    }
+    return flushed;
+  }
+
+  private async writeDeliveries(payload: EventPayload): Promise<number> {
+    const matching = this.subscriptions.filter((sub) => matchesSubscription(sub, payload));
+    if (matching.length === 0) return 0;
+    const eventKey = webhookEventKey(payload);
+    const body = {
+      id: eventKey,
+      event: payload.event,
+      slabAddress: payload.slabAddress,
+      timestamp: payload.timestamp,
+      data: payload.data,
+    };
+    await enqueueWebhookDeliveries(matching.map((sub) => ({
+      subscription_id: sub.id,
+      event_key: eventKey,
+      event: payload.event,
+      slab_address: payload.slabAddress,
+      payload: body,
+    })));
+    this.stats.enqueued += matching.length;
+    return matching.length;
+  }
+
+  /** Send every due delivery once. Returns the number attempted. */
+  async tick(): Promise<number> {
+    if (this._sending) return 0;
+    this._sending = true;
+    try {
+      await this.flushUnqueued();
+      await releaseStaleWebhookDeliveries(new Date(Date.now() - STALE_CLAIM_MS).toISOString());
+      const due = await claimDueWebhookDeliveries(BATCH_SIZE);
+      await Promise.all(due.map((delivery) => this.deliver(delivery)));
+      return due.length;
+    } catch (err) {
+      logger.error("Webhook tick failed", { error: err instanceof Error ? err.message : err });
+      return 0;
+    } finally {
+      this._sending = false;
+    }
+  }
+
+  private async deliver(delivery: WebhookDeliveryRow): Promise<void> {
+    const attempt = delivery.attempts + 1;
+    const sub = this.subscriptions.find((s) => s.id === delivery.subscription_id);
+    if (!sub) {
+      // Disabled (or deleted) after the event was queued
+      await this.deadLetter(delivery, attempt - 1, null, "subscription disabled");
+      return;
+    }
+
+    const body = JSON.stringify(delivery.payload);
+    const timestampSec = Math.floor(Date.now() / 1000);
+    const started = Date.now();
+    let statusCode: number | null = null;
+    let error: string | null = null;
+    try {
+      const res = await this.fetchFn(sub.url, {
+        method: "POST",
+        headers: {
+          "Content-Type": "application/json",
+          "User-Agent": "Percolator-Webhooks/1",
+          "X-Percolator-Event": delivery.event,
+          "X-Percolator-Delivery": String(delivery.id),
+          "X-Percolator-Timestamp": String(timestampSec),
+          [WEBHOOK_SIGNATURE_HEADER]: signWebhook(sub.secret, timestampSec, body),
+        },
+        body,
+        // A redirect could point the signed payload anywhere; treat it as a failure
+        redirect: "manual",
+        signal: AbortSignal.timeout(this.options.timeoutMs),
+      });
+      statusCode = res.status;
+      if (!res.ok) error = `HTTP ${res.status}`;
+    } catch (err) {
+      error = err instanceof Error ? err.message : String(err);
+    }
+    const durationMs = Date.now() - started;
+
+    try {
+      await insertWebhookAttempt({
+        delivery_id: delivery.id,
+        subscription_id: delivery.subscription_id,
+        attempt,
+        status_code: statusCode,
+        error,
+        duration_ms: durationMs,
+      });
+
+      if (!error) {
+        await updateWebhookDelivery(delivery.id, {
+          status: "delivered",
+          attempts: attempt,
+          last_status_code: statusCode,
+          last_error: null,
+          delivered_at: new Date().toISOString(),
+        });
+        this.stats.delivered++;
+        return;
+      }
+
+      this.stats.failedAttempts++;
+      if (attempt >= this.options.maxAttempts) {
+        await this.deadLetter(delivery, attempt, statusCode, error);
+        return;
+      }
+      await updateWebhookDelivery(delivery.id, {
+        status: "pending",
+        attempts: attempt,
+        next_attempt_at: new Date(Date.now() + webhookRetryDelayMs(attempt)).toISOString(),
+        last_status_code: statusCode,
+        last_error: error,
+      });
+    } catch (err) {
+      // Left "delivering"; released back to the queue after STALE_CLAIM_MS
+      logger.error("Webhook delivery bookkeeping failed", { delivery: delivery.id, error: err instanceof Error ? err.message : err });
+    }
+  }
+
+  private async deadLetter(delivery: WebhookDeliveryRow, attempts: number, statusCode: number | null, error: string): Promise<void> {
+    await updateWebhookDelivery(delivery.id, {
+      status: "dead",
+      attempts,
+      last_status_code: statusCode,
+      last_error: error,
+    });
+    await insertWebhookDeadLetter({
+      delivery_id: delivery.id,
+      subscription_id: delivery.subscription_id,
+      event: delivery.event,
+      slab_address: delivery.slab_address,
+      payload: delivery.payload,
+      attempts,
+      last_status_code: statusCode,
+      last_error: error,
+    });
+    this.stats.deadLettered++;
+    logger.warn("Webhook delivery dead-lettered", {
+      delivery: delivery.id,
+      subscription: delivery.subscription_id,
+      event: delivery.event,
+      attempts,
+      error,
+    });
+  }
+}
@@ -0,0 +1,76 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { PublicKey } from '@solana/web3.js';
+
+vi.mock('@percolator/sdk', () => ({
+  parseSlabState: vi.fn(),
+}));
+
+vi.mock('@percolator/shared', () => ({
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+  SlabArchive: vi.fn(),
+}));
+
+import * as sdk from '@percolator/sdk';
+import { archiveRoutes } from '../../src/routes/archive.js';
+
+const SLAB = 'FxfD37s1AZTeWfFQps9Zpebi2dNQ9QSSDtfMKdbsfKrD';
+const entry = { slot: 500, hash: 'ab'.repeat(32), at: '2026-01-01T00:00:00.000Z' };
+
+describe('archive routes', () => {
+  const archive = { entries: vi.fn(), getAt: vi.fn() };
+  const app = archiveRoutes(archive as any);
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('returns decoded state as of a slot with bigints as strings', async () => {
+    archive.getAt.mockResolvedValue({ entry, data: new Uint8Array(8) });
+    vi.mocked(sdk.parseSlabState).mockReturnValue({
+      header: { admin: new PublicKey(SLAB) },
+      config: {},
+      params: {},
+      engine: { vault: 123n },
+      accounts: [],
+    } as any);
+
+    const res = await app.request(`/archive/${SLAB}/state?slot=600`);
+
+    expect(res.status).toBe(200);
+    const body = await res.json();
+    expect(archive.getAt).toHaveBeenCalledWith(SLAB, 600);
+    expect(body.archivedSlot).toBe(500);
+    expect(body.engine.vault).toBe('123');
+    expect(body.header.admin).toBe(SLAB);
+  });
+
+  it('returns 404 when nothing was archived before the slot', async () => {
+    archive.getAt.mockResolvedValue(null);
+
+    const res = await app.request(`/archive/${SLAB}/state?slot=1`);
+
+    expect(res.status).toBe(404);
+  });
+
+  it('rejects invalid slabs and slots', async () => {
+    expect((await app.request('/archive/not..valid/state')).status).toBe(400);
+    expect((await app.request(`/archive/${SLAB}/state?slot=-5`)).status).toBe(400);
+    expect(archive.getAt).not.toHaveBeenCalled();
+  });
+
+  it('serves raw bytes with the archived slot header', async () => {
+    archive.getAt.mockResolvedValue({ entry, data: new Uint8Array([9, 8, 7]) });
+
+    const res = await app.request(`/archive/${SLAB}/raw`);
+
+    expect(res.status).toBe(200);
+    expect(res.headers.get('X-Archived-Slot')).toBe('500');
+    expect(new Uint8Array(await res.arrayBuffer())).toEqual(new Uint8Array([9, 8, 7]));
+    expect(archive.getAt).toHaveBeenCalledWith(SLAB, Number.MAX_SAFE_INTEGER);
+  });
+});
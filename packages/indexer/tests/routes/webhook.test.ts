@@ -2,6 +2,8 @@ import { describe, it, expect, vi, beforeEach } from 'vitest';
 
 vi.mock('@percolator/sdk', () => ({
   IX_TAG: { TradeNoCpi: 10, TradeCpi: 11 },
+  IX_ACCOUNT_SPECS: {},
+  decodeTransaction: vi.fn(() => ({ success: true, instructions: [], undecoded: [] })),
   // detectSlabLayout: returns a V1-style layout so engineOff + engineMarkPriceOff = 1040,
   // matching the mock slab buffers built in tests below.
   detectSlabLayout: vi.fn((dataLen: number) => {
@@ -36,6 +38,11 @@ vi.mock('@percolator/shared', () => ({
     side: 'long' as const,
   })),
   readU128LE: vi.fn(() => 0n),
+  insertInstructionEvents: vi.fn(),
+  insertCollateralEvents: vi.fn(),
+  insertLiquidationEvents: vi.fn(),
+  insertInsuranceMovements: vi.fn(),
+  insertMarketAdminEvents: vi.fn(),
 }));
 
 import * as shared from '@percolator/shared';
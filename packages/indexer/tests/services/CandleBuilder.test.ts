@@ -0,0 +1,158 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+
+vi.mock('@percolator/shared', () => ({
+  CANDLE_RESOLUTION_SECONDS: { '1m': 60, '5m': 300, '15m': 900, '1h': 3600, '4h': 14400, '1d': 86400 },
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+  getCandles: vi.fn(async () => []),
+  getOraclePricesInRange: vi.fn(async () => []),
+  getOraclePricesIndexedSince: vi.fn(async () => []),
+  getTradesInRange: vi.fn(async () => []),
+  getTradesIndexedSince: vi.fn(async () => []),
+  upsertCandles: vi.fn(),
+  deleteCandles: vi.fn(),
+}));
+
+import * as shared from '@percolator/shared';
+import { CandleBuilder, bucketStartMs, buildCandle, rollupCandles } from '../../src/services/CandleBuilder.js';
+
+const SLAB = 'FxfD37s1AZTeWfFQps9Zpebi2dNQ9QSSDtfMKdbsfKrD';
+const T0 = Date.parse('2026-03-01T10:00:00.000Z');
+
+describe('CandleBuilder', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  describe('buildCandle', () => {
+    it('takes OHLC from priced points in time order and volume from trades', () => {
+      const candle = buildCandle(SLAB, '1m', T0, [
+        { time: T0 + 30_000, price: 105, size: -4n },
+        { time: T0 + 1_000, price: 100 },
+        { time: T0 + 50_000, price: 0, size: 6n }, // unknown price: volume only
+        { time: T0 + 40_000, price: 97 },
+      ]);
+
+      expect(candle).toEqual({
+        slab_address: SLAB,
+        resolution: '1m',
+        bucket_start: '2026-03-01T10:00:00.000Z',
+        open: 100,
+        high: 105,
+        low: 97,
+        close: 97,
+        volume: '10',
+        trade_count: 2,
+      });
+    });
+
+    it('returns null without a priced point', () => {
+      expect(buildCandle(SLAB, '1m', T0, [{ time: T0, price: 0, size: 1n }])).toBeNull();
+    });
+  });
+
+  it('rolls finer candles up into a coarser bucket', () => {
+    const child = (minute: number, o: number, h: number, l: number, c: number, volume: string) => ({
+      slab_address: SLAB,
+      resolution: '1m' as const,
+      bucket_start: new Date(T0 + minute * 60_000).toISOString(),
+      open: o, high: h, low: l, close: c, volume, trade_count: 1,
+    });
+
+    const candle = rollupCandles(SLAB, '5m', T0, [
+      child(3, 110, 112, 108, 109, '5'),
+      child(0, 100, 104, 99, 103, '7'),
+    ]);
+
+    expect(candle).toMatchObject({ open: 100, high: 112, low: 99, close: 109, volume: '12', trade_count: 2 });
+  });
+
+  it('aligns buckets to UTC boundaries', () => {
+    expect(bucketStartMs(Date.parse('2026-03-01T10:07:31Z'), '5m')).toBe(Date.parse('2026-03-01T10:05:00Z'));
+    expect(bucketStartMs(Date.parse('2026-03-01T10:07:31Z'), '4h')).toBe(Date.parse('2026-03-01T08:00:00Z'));
+    expect(bucketStartMs(Date.parse('2026-03-01T10:07:31Z'), '1d')).toBe(Date.parse('2026-03-01T00:00:00Z'));
+  });
+
+  it('rebuilds the bucket a late trade belongs to, not the one it was indexed in', async () => {
+    vi.mocked(shared.getTradesIndexedSince).mockResolvedValueOnce([
+      { id: 't1', slab_address: SLAB, created_at: '2026-03-01T12:00:00.000Z', time: '2026-03-01T10:02:15.000Z' },
+    ]);
+    vi.mocked(shared.getTradesInRange).mockResolvedValueOnce([
+      { price: 101, size: '3', time: '2026-03-01T10:02:15.000Z' },
+    ]);
+
+    const builder = new CandleBuilder(T0);
+    expect(await builder.tick()).toBe(1);
+
+    expect(shared.getTradesInRange).toHaveBeenCalledWith(SLAB, '2026-03-01T10:02:00.000Z', '2026-03-01T10:03:00.000Z');
+    expect(shared.upsertCandles).toHaveBeenCalledWith([
+      expect.objectContaining({ resolution: '1m', bucket_start: '2026-03-01T10:02:00.000Z', close: 101 }),
+    ]);
+    // One rollup query per coarser resolution
+    expect(shared.getCandles).toHaveBeenCalledTimes(5);
+    expect(shared.getCandles).toHaveBeenCalledWith(SLAB, '1m', {
+      from: '2026-03-01T08:00:00.000Z',
+      to: '2026-03-01T12:00:00.000Z',
+      limit: 240,
+    });
+  });
+
+  it('resumes after the last row seen, by created_at then id', async () => {
+    const row = { id: 'o1', slab_address: SLAB, created_at: '2026-03-01T12:00:00.000Z', time: '2026-03-01T11:59:00.000Z' };
+    vi.mocked(shared.getOraclePricesIndexedSince).mockResolvedValueOnce([row]);
+    vi.mocked(shared.getOraclePricesInRange).mockResolvedValueOnce([{ price_e6: '100000000', timestamp: 1772366340 }]);
+
+    const builder = new CandleBuilder(T0);
+    expect(await builder.tick()).toBe(1);
+    expect(shared.getOraclePricesIndexedSince).toHaveBeenCalledWith({ createdAt: '2026-02-28T10:00:00.000Z', id: null }, 1000);
+    expect(await builder.tick()).toBe(0);
+    expect(shared.getOraclePricesIndexedSince).toHaveBeenLastCalledWith({ createdAt: '2026-03-01T12:00:00.000Z', id: 'o1' }, 1000);
+  });
+
+  it('pages past a full page of rows sharing one created_at', async () => {
+    const createdAt = '2026-03-01T12:00:00.000Z';
+    const page = Array.from({ length: 1000 }, (_, i) => ({
+      id: `t${String(i).padStart(4, '0')}`,
+      slab_address: SLAB,
+      created_at: createdAt,
+      time: '2026-03-01T10:02:15.000Z',
+    }));
+    vi.mocked(shared.getTradesIndexedSince).mockResolvedValueOnce(page);
+
+    const builder = new CandleBuilder(T0);
+    await builder.tick();
+
+    expect(shared.getTradesIndexedSince).toHaveBeenCalledTimes(2);
+    expect(shared.getTradesIndexedSince).toHaveBeenLastCalledWith({ createdAt, id: 't0999' }, 1000);
+  });
+
+  it('deletes candles whose bucket no longer has a priced point', async () => {
+    vi.mocked(shared.getTradesIndexedSince).mockResolvedValueOnce([
+      { id: 't1', slab_address: SLAB, created_at: '2026-03-01T12:00:00.000Z', time: '2026-03-01T10:02:15.000Z' },
+    ]);
+    // Re-indexed without a price: volume only
+    vi.mocked(shared.getTradesInRange).mockResolvedValueOnce([{ price: 0, size: '3', time: '2026-03-01T10:02:15.000Z' }]);
+
+    const builder = new CandleBuilder(T0);
+    await builder.tick();
+
+    expect(shared.deleteCandles).toHaveBeenCalledWith(SLAB, '1m', ['2026-03-01T10:02:00.000Z']);
+    expect(shared.deleteCandles).toHaveBeenCalledWith(SLAB, '1h', ['2026-03-01T10:00:00.000Z']);
+    expect(shared.upsertCandles).not.toHaveBeenCalledWith([expect.anything()]);
+  });
+
+  it('keeps dirty buckets when a rebuild fails', async () => {
+    vi.mocked(shared.getTradesIndexedSince).mockResolvedValueOnce([
+      { id: 't1', slab_address: SLAB, created_at: '2026-03-01T12:00:00.000Z', time: '2026-03-01T10:02:15.000Z' },
+    ]);
+    vi.mocked(shared.getTradesInRange).mockRejectedValueOnce(new Error('timeout'));
+
+    const builder = new CandleBuilder(T0);
+    expect(await builder.tick()).toBe(0);
+    expect(await builder.tick()).toBe(1);
+  });
+});
@@ -0,0 +1,135 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { PublicKey, type ParsedTransactionWithMeta } from '@solana/web3.js';
+import {
+  decodeTransaction,
+  encodeDepositCollateral,
+  encodeLiquidateAtOracle,
+  encodePauseMarket,
+  encodeWithdrawInsuranceLP,
+  encodeKeeperCrank,
+} from '@percolator/sdk';
+
+vi.mock('@percolator/shared', () => ({
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+  insertInstructionEvents: vi.fn(),
+  insertCollateralEvents: vi.fn(),
+  insertLiquidationEvents: vi.fn(),
+  insertInsuranceMovements: vi.fn(),
+  insertMarketAdminEvents: vi.fn(),
+}));
+
+import * as shared from '@percolator/shared';
+import { buildActivityRows, recordInstructions } from '../../src/services/InstructionIndexer.js';
+
+const PROGRAM = new PublicKey('FxfD37s1AZTeWfFQps9Zpebi2dNQ9QSSDtfMKdbsfKrD');
+const USER = new PublicKey('So11111111111111111111111111111111111111112');
+const SLAB = new PublicKey('EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v');
+const ALPHABET = '123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz';
+
+function toBase58(bytes: Uint8Array): string {
+  let n = 0n;
+  for (const b of bytes) n = (n << 8n) | BigInt(b);
+  let out = '';
+  while (n > 0n) {
+    out = ALPHABET[Number(n % 58n)] + out;
+    n /= 58n;
+  }
+  for (const b of bytes) {
+    if (b !== 0) break;
+    out = '1' + out;
+  }
+  return out;
+}
+
+function ix(data: Uint8Array, accounts: PublicKey[]) {
+  return { programId: PROGRAM, accounts, data: toBase58(data) };
+}
+
+function makeTx(instructions: ReturnType<typeof ix>[], err: unknown = null): ParsedTransactionWithMeta {
+  return {
+    slot: 900,
+    blockTime: 1_700_000_000,
+    transaction: {
+      signatures: ['sigA'],
+      message: { accountKeys: [], recentBlockhash: '', instructions },
+    },
+    meta: { err, fee: 5000, preBalances: [], postBalances: [], logMessages: [], innerInstructions: [] },
+  } as unknown as ParsedTransactionWithMeta;
+}
+
+const other = () => PublicKey.unique();
+
+describe('InstructionIndexer', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('maps each instruction to instruction_events and its typed table', () => {
+    const decoded = decodeTransaction(
+      makeTx([
+        ix(encodeDepositCollateral({ userIdx: 4, amount: 2_500_000n }), [USER, SLAB, other(), other(), other(), other()]),
+        ix(encodeLiquidateAtOracle({ targetIdx: 9 }), [other(), SLAB, other(), other()]),
+        ix(encodeWithdrawInsuranceLP({ lpAmount: 77n }), [USER, SLAB, other(), other(), other(), other(), other(), other()]),
+        ix(encodePauseMarket(), [USER, SLAB]),
+        ix(encodeKeeperCrank({ callerIdx: 65535, allowPanic: false }), [USER, SLAB, other(), other()]),
+      ]),
+      [PROGRAM],
+    );
+
+    const rows = buildActivityRows(decoded);
+
+    expect(rows.instructions.map((r) => r.name)).toEqual([
+      'DepositCollateral', 'LiquidateAtOracle', 'WithdrawInsuranceLP', 'PauseMarket', 'KeeperCrank',
+    ]);
+    expect(rows.instructions[0]).toMatchObject({
+      tx_signature: 'sigA',
+      ix_index: 0,
+      inner_index: -1,
+      slot: 900,
+      block_time: '2023-11-14T22:13:20.000Z',
+      slab_address: SLAB.toBase58(),
+      signer: USER.toBase58(),
+      args: { userIdx: 4, amount: '2500000' },
+    });
+
+    expect(rows.collateral).toEqual([
+      expect.objectContaining({ event_type: 'deposit', owner: USER.toBase58(), account_idx: 4, amount: '2500000' }),
+    ]);
+    expect(rows.liquidations).toEqual([
+      expect.objectContaining({ event_type: 'liquidation', target_idx: 9, caller: null, ix_index: 1 }),
+    ]);
+    expect(rows.insurance).toEqual([
+      expect.objectContaining({ event_type: 'lp_withdraw', actor: USER.toBase58(), amount: '77' }),
+    ]);
+    expect(rows.admin).toEqual([
+      expect.objectContaining({ name: 'PauseMarket', authority: USER.toBase58() }),
+    ]);
+  });
+
+  it('records nothing for failed transactions', async () => {
+    const decoded = decodeTransaction(
+      makeTx([ix(encodeDepositCollateral({ userIdx: 1, amount: 1n }), [USER, SLAB])], { InstructionError: [0, { Custom: 1 }] }),
+      [PROGRAM],
+    );
+
+    expect(await recordInstructions(decoded)).toBe(0);
+    expect(shared.insertInstructionEvents).not.toHaveBeenCalled();
+  });
+
+  it('writes every table when recording', async () => {
+    const decoded = decodeTransaction(
+      makeTx([ix(encodeDepositCollateral({ userIdx: 1, amount: 1n }), [USER, SLAB])]),
+      [PROGRAM],
+    );
+
+    expect(await recordInstructions(decoded)).toBe(1);
+    expect(shared.insertInstructionEvents).toHaveBeenCalledWith([expect.objectContaining({ name: 'DepositCollateral' })]);
+    expect(shared.insertCollateralEvents).toHaveBeenCalledWith([expect.objectContaining({ event_type: 'deposit' })]);
+    expect(shared.insertLiquidationEvents).toHaveBeenCalledWith([]);
+  });
+});
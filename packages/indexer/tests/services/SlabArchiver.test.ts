@@ -0,0 +1,64 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+
+const mockGetMultipleAccountsInfoAndContext = vi.fn();
+
+vi.mock('@percolator/shared', () => ({
+  config: { slabArchiveDir: '/tmp/unused', slabArchiveIntervalMs: 60_000 },
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+  getConnection: vi.fn(() => ({
+    getMultipleAccountsInfoAndContext: mockGetMultipleAccountsInfoAndContext,
+  })),
+  withRetry: vi.fn(async (fn: any) => fn()),
+  SlabArchive: vi.fn(),
+}));
+
+import { SlabArchiver } from '../../src/services/SlabArchiver.js';
+
+const SLAB1 = 'FxfD37s1AZTeWfFQps9Zpebi2dNQ9QSSDtfMKdbsfKrD';
+const SLAB2 = 'FwfBKZXbYr4vTK23bMFkbgKq3npJ3MSDxEaKmq9Aj4Qn';
+
+describe('SlabArchiver', () => {
+  const put = vi.fn();
+  const archive = { put } as any;
+  const provider = { getMarkets: vi.fn(() => new Map<string, any>([[SLAB1, {}], [SLAB2, {}]])) };
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('archives every slab at the RPC context slot', async () => {
+    mockGetMultipleAccountsInfoAndContext.mockResolvedValue({
+      context: { slot: 4242 },
+      value: [{ data: Buffer.from([1, 2, 3]) }, null],
+    });
+    put.mockResolvedValue({ hash: 'h', written: true });
+
+    const written = await new SlabArchiver(provider, archive).archiveAll();
+
+    expect(written).toBe(1);
+    expect(put).toHaveBeenCalledTimes(1);
+    expect(put).toHaveBeenCalledWith(SLAB1, 4242, new Uint8Array([1, 2, 3]));
+  });
+
+  it('counts only images the archive actually stored', async () => {
+    mockGetMultipleAccountsInfoAndContext.mockResolvedValue({
+      context: { slot: 10 },
+      value: [{ data: Buffer.from([1]) }, { data: Buffer.from([2]) }],
+    });
+    put.mockResolvedValueOnce({ hash: 'a', written: false }).mockResolvedValueOnce({ hash: 'b', written: true });
+
+    expect(await new SlabArchiver(provider, archive).archiveAll()).toBe(1);
+  });
+
+  it('survives a failed RPC batch', async () => {
+    mockGetMultipleAccountsInfoAndContext.mockRejectedValue(new Error('429'));
+
+    await expect(new SlabArchiver(provider, archive).archiveAll()).resolves.toBe(0);
+    expect(put).not.toHaveBeenCalled();
+  });
+});
@@ -0,0 +1,169 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { PublicKey } from '@solana/web3.js';
+
+vi.mock('@percolator/sdk', () => ({
+  STAKE_POOL_SIZE: 352,
+  STAKE_PROGRAM_ID: new PublicKey('6aJb1F9CDCVWCNYFwj8aQsVb696YnW6J1FznteHq4Q6k'),
+  decodeStakePool: vi.fn(),
+  computeStakePoolMetrics: vi.fn(),
+}));
+
+vi.mock('@percolator/shared', () => ({
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+  config: { supabaseUrl: 'http://test', supabaseKey: 'key', stakeProgramId: '' },
+  getConnection: vi.fn(),
+  getLatestStakePoolSnapshot: vi.fn(async () => null),
+  insertStakePoolSnapshots: vi.fn(),
+  insertStakePoolEvents: vi.fn(),
+  sendWarningAlert: vi.fn(),
+}));
+
+import * as core from '@percolator/sdk';
+import * as shared from '@percolator/shared';
+import { StakePoolService, stakePoolEvents } from '../../src/services/StakePoolService.js';
+
+const POOL = 'FwfBKZXbYr4vTK23bMFkbgKq3npJ3MSDxEaKmq9Aj4Qn';
+const SLAB = 'FxfD37s1AZTeWfFQps9Zpebi2dNQ9QSSDtfMKdbsfKrD';
+
+function poolState(overrides: Record<string, unknown> = {}) {
+  return {
+    isInitialized: true,
+    slab: new PublicKey(SLAB),
+    poolMode: 0,
+    totalDeposited: 1_000n,
+    totalWithdrawn: 0n,
+    totalFlushed: 100n,
+    totalReturned: 20n,
+    totalFeesEarned: 0n,
+    totalLpSupply: 1_000n,
+    trancheEnabled: true,
+    juniorBalance: 300n,
+    juniorTotalLp: 250n,
+    juniorFeeMultBps: 20_000,
+    hwmEnabled: true,
+    epochHighWaterTvl: 1_000n,
+    hwmFloorBps: 9_500,
+    ...overrides,
+  } as any;
+}
+
+function metrics(overrides: Record<string, unknown> = {}) {
+  return {
+    value: 920n,
+    lpSupply: 1_000n,
+    sharePriceE6: 920_000n,
+    senior: { value: 620n, lpSupply: 750n, sharePriceE6: 826_666n },
+    junior: { value: 300n, lpSupply: 250n, sharePriceE6: 1_200_000n },
+    hwmFloor: 950n,
+    hwmBreached: true,
+    ...overrides,
+  } as any;
+}
+
+function connectionWith(accounts: { pubkey: PublicKey; account: { data: Buffer } }[], slot = 500) {
+  return {
+    getProgramAccounts: vi.fn(async () => ({ context: { slot }, value: accounts })),
+  } as any;
+}
+
+const previous = { total_flushed: '100', total_returned: '20', hwm_breached: false };
+const row = {
+  pool_address: POOL,
+  slab_address: SLAB,
+  slot: 500,
+  total_flushed: '150',
+  total_returned: '20',
+  pool_value: '870',
+  hwm_floor: '950',
+  hwm_breached: true,
+} as any;
+
+describe('StakePoolService', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  describe('stakePoolEvents', () => {
+    it('records nothing for a pool seen for the first time', () => {
+      expect(stakePoolEvents(null, row)).toEqual([]);
+    });
+
+    it('records flush/return deltas and HWM transitions', () => {
+      expect(stakePoolEvents(previous, { ...row, total_returned: '35' })).toEqual([
+        expect.objectContaining({ kind: 'flush', amount: '50', pool_value: '870', slot: 500 }),
+        expect.objectContaining({ kind: 'return', amount: '15' }),
+        expect.objectContaining({ kind: 'hwm_breach', amount: '870', hwm_floor: '950' }),
+      ]);
+      expect(stakePoolEvents({ ...previous, hwm_breached: true }, { ...row, total_flushed: '100', hwm_breached: false })).toEqual([
+        expect.objectContaining({ kind: 'hwm_recovered' }),
+      ]);
+      expect(stakePoolEvents(previous, { ...row, total_flushed: '100', hwm_breached: false })).toEqual([]);
+    });
+  });
+
+  it('snapshots every initialized pool at the RPC context slot', async () => {
+    const uninitialized = new PublicKey(SLAB);
+    vi.mocked(core.decodeStakePool).mockImplementation((data: any) =>
+      data[0] === 1 ? poolState() : poolState({ isInitialized: false }),
+    );
+    vi.mocked(core.computeStakePoolMetrics).mockReturnValue(metrics({ hwmBreached: false }));
+    const connection = connectionWith([
+      { pubkey: new PublicKey(POOL), account: { data: Buffer.from([1]) } },
+      { pubkey: uninitialized, account: { data: Buffer.from([0]) } },
+    ]);
+    const service = new StakePoolService(undefined, () => connection);
+
+    expect(await service.poll()).toBe(1);
+
+    expect(connection.getProgramAccounts.mock.calls[0][1]).toMatchObject({
+      filters: [{ dataSize: 352 }],
+      withContext: true,
+    });
+    const rows = vi.mocked(shared.insertStakePoolSnapshots).mock.calls[0][0];
+    expect(rows).toEqual([
+      expect.objectContaining({
+        pool_address: POOL,
+        slab_address: SLAB,
+        slot: 500,
+        total_flushed: '100',
+        pool_value: '920',
+        share_price_e6: '920000',
+        senior_share_price_e6: '826666',
+        junior_value: '300',
+        junior_share_price_e6: '1200000',
+        junior_fee_mult_bps: 20_000,
+        hwm_floor: '950',
+        hwm_breached: false,
+      }),
+    ]);
+    expect(shared.insertStakePoolEvents).toHaveBeenCalledWith([]);
+    expect(service.getStatus()).toMatchObject({ pools: 1, breached: 0 });
+  });
+
+  it('diffs against the stored snapshot after a restart and alerts on a breach', async () => {
+    vi.mocked(shared.getLatestStakePoolSnapshot).mockResolvedValue(previous as any);
+    vi.mocked(core.decodeStakePool).mockReturnValue(poolState({ totalFlushed: 180n }));
+    vi.mocked(core.computeStakePoolMetrics).mockReturnValue(metrics());
+    const connection = connectionWith([{ pubkey: new PublicKey(POOL), account: { data: Buffer.from([1]) } }]);
+    const service = new StakePoolService(undefined, () => connection);
+
+    await service.poll();
+
+    expect(shared.getLatestStakePoolSnapshot).toHaveBeenCalledWith(POOL);
+    expect(vi.mocked(shared.insertStakePoolEvents).mock.calls[0][0]).toEqual([
+      expect.objectContaining({ kind: 'flush', amount: '80' }),
+      expect.objectContaining({ kind: 'hwm_breach', pool_value: '920' }),
+    ]);
+    expect(shared.sendWarningAlert).toHaveBeenCalledWith('Stake pool below HWM floor', expect.any(Array));
+
+    // The next poll diffs against the in-memory snapshot
+    await service.poll();
+    expect(shared.getLatestStakePoolSnapshot).toHaveBeenCalledTimes(1);
+    expect(shared.insertStakePoolEvents).toHaveBeenLastCalledWith([]);
+  });
+});
@@ -27,6 +27,8 @@ vi.mock('@percolator/shared', () => ({
   })),
   upsertMarketStats: vi.fn(),
   insertOraclePrice: vi.fn(),
+  insertAccountSnapshots: vi.fn(),
+  getLatestOpenAccountSnapshots: vi.fn(async () => new Map()),
   get24hVolume: vi.fn(async () => ({ volume: '1000000', tradeCount: 5 })),
   getMarkets: vi.fn(async () => []),
   insertMarket: vi.fn(),
@@ -40,7 +42,7 @@ vi.mock('@percolator/shared', () => ({
   captureException: vi.fn(),
 }));
 
-import { StatsCollector } from '../../src/services/StatsCollector.js';
+import { StatsCollector, diffAccountSnapshots } from '../../src/services/StatsCollector.js';
 import type { MarketProvider } from '../../src/services/StatsCollector.js';
 import * as core from '@percolator/sdk';
 import * as shared from '@percolator/shared';
@@ -62,6 +64,7 @@ function makeEngineState(overrides: Record<string, any> = {}) {
     lifetimeForceCloses: 2n,
     cTot: 1_000_000n,
     pnlPosTot: 500_000n,
+    currentSlot: 1010n,
     lastCrankSlot: 1000n,
     maxCrankStalenessSlots: 100n,
     fundingIndexQpbE6: 0n,
@@ -364,4 +367,156 @@ describe('StatsCollector', () => {
       );
     });
   });
+
+  describe('account snapshots', () => {
+    const OWNER = new PublicKey('So11111111111111111111111111111111111111112');
+
+    function makeAccount(overrides: Record<string, any> = {}) {
+      return {
+        kind: 0,
+        owner: OWNER,
+        capital: 1_000_000n,
+        pnl: 0n,
+        reservedPnl: 0n,
+        positionSize: 500n,
+        entryPrice: 150_000_000n,
+        fundingIndex: 0n,
+        feeCredits: 0n,
+        ...overrides,
+      } as any;
+    }
+
+    const ctx = { slabAddress: SLAB1, slot: 1010, priceE6: 151_000_000n };
+
+    it('stores every account on the first pass and nothing when unchanged', () => {
+      const first = diffAccountSnapshots(new Map(), [{ idx: 3, account: makeAccount() }], ctx);
+      expect(first.rows).toEqual([
+        expect.objectContaining({
+          account_idx: 3,
+          owner: OWNER.toBase58(),
+          capital: '1000000',
+          position_size: '500',
+          price_e6: '151000000',
+          closed: false,
+        }),
+      ]);
+
+      const second = diffAccountSnapshots(
+        first.next,
+        [{ idx: 3, account: makeAccount() }],
+        { ...ctx, slot: 1020, priceE6: 152_000_000n },
+      );
+      expect(second.rows).toEqual([]);
+    });
+
+    it('stores changed accounts and marks missing ones closed', () => {
+      const { next } = diffAccountSnapshots(
+        new Map(),
+        [{ idx: 1, account: makeAccount() }, { idx: 2, account: makeAccount() }],
+        ctx,
+      );
+
+      const { rows, next: after } = diffAccountSnapshots(
+        next,
+        [{ idx: 1, account: makeAccount({ pnl: -2500n }) }],
+        { ...ctx, slot: 1020 },
+      );
+
+      expect(rows).toEqual([
+        expect.objectContaining({ account_idx: 1, pnl: '-2500', slot: 1020, closed: false }),
+        expect.objectContaining({ account_idx: 2, capital: '0', position_size: '0', slot: 1020, closed: true }),
+      ]);
+      expect([...after.keys()]).toEqual([1]);
+    });
+
+    it('writes only changed accounts from the collect loop', async () => {
+      const markets = new Map([[SLAB1, makeMockMarket(SLAB1)]]);
+      vi.mocked(mockMarketProvider.getMarkets).mockReturnValue(markets);
+      mockGetMultipleAccountsInfo.mockResolvedValue([{ data: new Uint8Array(2048) }]);
+      vi.mocked(core.parseEngine).mockReturnValue(makeEngineState());
+      vi.mocked(core.parseConfig).mockReturnValue(makeConfig());
+      vi.mocked(core.parseParams).mockReturnValue(makeParams());
+      vi.mocked(core.parseAllAccounts).mockReturnValue([{ idx: 0, account: makeAccount() }] as any);
+
+      statsCollector.start();
+      await vi.advanceTimersByTimeAsync(10_500);
+
+      expect(shared.insertAccountSnapshots).toHaveBeenCalledWith([
+        expect.objectContaining({ slab_address: SLAB1, account_idx: 0, slot: 1010 }),
+      ]);
+    });
+
+    it('seeds change detection from the latest stored snapshots after a restart', async () => {
+      const markets = new Map([[SLAB1, makeMockMarket(SLAB1)]]);
+      vi.mocked(mockMarketProvider.getMarkets).mockReturnValue(markets);
+      mockGetMultipleAccountsInfo.mockResolvedValue([{ data: new Uint8Array(2048) }]);
+      vi.mocked(core.parseEngine).mockReturnValue(makeEngineState());
+      vi.mocked(core.parseConfig).mockReturnValue(makeConfig());
+      vi.mocked(core.parseParams).mockReturnValue(makeParams());
+      vi.mocked(core.parseAllAccounts).mockReturnValue([
+        { idx: 0, account: makeAccount() },
+        { idx: 1, account: makeAccount({ capital: 2_000_000n }) },
+      ] as any);
+      // Stored before the restart: idx 0 unchanged, idx 1 older state, idx 2 since closed
+      const stored = diffAccountSnapshots(
+        new Map(),
+        [0, 1, 2].map((idx) => ({ idx, account: makeAccount() })),
+        { ...ctx, slot: 900 },
+      ).next;
+      vi.mocked(shared.getLatestOpenAccountSnapshots).mockResolvedValueOnce(stored);
+
+      statsCollector.start();
+      await vi.advanceTimersByTimeAsync(10_500);
+
+      expect(shared.getLatestOpenAccountSnapshots).toHaveBeenCalledWith(SLAB1);
+      expect(shared.insertAccountSnapshots).toHaveBeenCalledWith([
+        expect.objectContaining({ account_idx: 1, capital: '2000000', closed: false }),
+        expect.objectContaining({ account_idx: 2, closed: true }),
+      ]);
+
+      // Later cycles diff against memory, not the database
+      await vi.advanceTimersByTimeAsync(120_000);
+      expect(shared.getLatestOpenAccountSnapshots).toHaveBeenCalledTimes(1);
+      expect(shared.insertAccountSnapshots).toHaveBeenLastCalledWith([]);
+    });
+
+    it('retries the snapshot diff when seeding from the database fails', async () => {
+      const markets = new Map([[SLAB1, makeMockMarket(SLAB1)]]);
+      vi.mocked(mockMarketProvider.getMarkets).mockReturnValue(markets);
+      mockGetMultipleAccountsInfo.mockResolvedValue([{ data: new Uint8Array(2048) }]);
+      vi.mocked(core.parseEngine).mockReturnValue(makeEngineState());
+      vi.mocked(core.parseConfig).mockReturnValue(makeConfig());
+      vi.mocked(core.parseParams).mockReturnValue(makeParams());
+      vi.mocked(core.parseAllAccounts).mockReturnValue([{ idx: 0, account: makeAccount() }] as any);
+      vi.mocked(shared.getLatestOpenAccountSnapshots).mockRejectedValueOnce(new Error('db down'));
+
+      statsCollector.start();
+      await vi.advanceTimersByTimeAsync(10_500);
+      expect(shared.insertAccountSnapshots).not.toHaveBeenCalled();
+
+      await vi.advanceTimersByTimeAsync(120_000);
+      expect(shared.getLatestOpenAccountSnapshots).toHaveBeenCalledTimes(2);
+      expect(shared.insertAccountSnapshots).toHaveBeenCalledWith([
+        expect.objectContaining({ account_idx: 0, closed: false }),
+      ]);
+    });
+
+    it('skips snapshots when account parsing fails', async () => {
+      const markets = new Map([[SLAB1, makeMockMarket(SLAB1)]]);
+      vi.mocked(mockMarketProvider.getMarkets).mockReturnValue(markets);
+      mockGetMultipleAccountsInfo.mockResolvedValue([{ data: new Uint8Array(2048) }]);
+      vi.mocked(core.parseEngine).mockReturnValue(makeEngineState());
+      vi.mocked(core.parseConfig).mockReturnValue(makeConfig());
+      vi.mocked(core.parseParams).mockReturnValue(makeParams());
+      vi.mocked(core.parseAllAccounts).mockImplementation(() => {
+        throw new Error('bad layout');
+      });
+
+      statsCollector.start();
+      await vi.advanceTimersByTimeAsync(10_500);
+
+      expect(shared.upsertMarketStats).toHaveBeenCalled();
+      expect(shared.insertAccountSnapshots).not.toHaveBeenCalled();
+    });
+  });
 });
@@ -6,6 +6,8 @@ const mockGetParsedTransaction = vi.fn();
 
 vi.mock('@percolator/sdk', () => ({
   IX_TAG: { TradeNoCpi: 10, TradeCpi: 11 },
+  IX_ACCOUNT_SPECS: {},
+  decodeTransaction: vi.fn(() => ({ success: true, instructions: [], undecoded: [] })),
 }));
 
 vi.mock('@percolator/shared', () => ({
@@ -55,6 +57,11 @@ vi.mock('@percolator/shared', () => ({
   withRetry: vi.fn(async (fn: any) => fn()),
   addBreadcrumb: vi.fn(),
   captureException: vi.fn(),
+  insertInstructionEvents: vi.fn(),
+  insertCollateralEvents: vi.fn(),
+  insertLiquidationEvents: vi.fn(),
+  insertInsuranceMovements: vi.fn(),
+  insertMarketAdminEvents: vi.fn(),
 }));
 
 import { TradeIndexerPolling } from '../../src/services/TradeIndexer.js';
@@ -0,0 +1,211 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { createHmac } from 'node:crypto';
+
+vi.mock('@percolator/shared', () => ({
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+  eventBus: { subscribe: vi.fn(() => () => {}) },
+  getActiveWebhookSubscriptions: vi.fn(async () => []),
+  enqueueWebhookDeliveries: vi.fn(),
+  claimDueWebhookDeliveries: vi.fn(async () => []),
+  releaseStaleWebhookDeliveries: vi.fn(),
+  insertWebhookAttempt: vi.fn(),
+  insertWebhookDeadLetter: vi.fn(),
+  updateWebhookDelivery: vi.fn(),
+  isPrivateHostname: vi.fn((host: string) => host === '127.0.0.1'),
+  PrivateAddressError: class PrivateAddressError extends Error {},
+  publicLookup: vi.fn(),
+}));
+
+import * as shared from '@percolator/shared';
+import {
+  WebhookDispatcher,
+  matchesSubscription,
+  publicFetch,
+  signWebhook,
+  webhookEventKey,
+  webhookRetryDelayMs,
+} from '../../src/services/WebhookDispatcher.js';
+
+const SLAB = 'FxfD37s1AZTeWfFQps9Zpebi2dNQ9QSSDtfMKdbsfKrD';
+const OTHER_SLAB = 'FwfBKZXbYr4vTK23bMFkbgKq3npJ3MSDxEaKmq9Aj4Qn';
+
+const payload = {
+  event: 'crank.stale' as const,
+  slabAddress: SLAB,
+  timestamp: 1_760_000_000_000,
+  data: { reason: 'oracle stale' },
+};
+
+function subscription(overrides: Record<string, unknown> = {}) {
+  return {
+    id: 'sub-1',
+    url: 'https://hooks.example.com/x',
+    secret: 'whsec_test',
+    events: [],
+    slabs: [],
+    description: null,
+    active: true,
+    created_at: '',
+    updated_at: '',
+    ...overrides,
+  } as any;
+}
+
+function delivery(attempts = 0) {
+  return {
+    id: 42,
+    subscription_id: 'sub-1',
+    event_key: 'k',
+    event: 'crank.stale',
+    slab_address: SLAB,
+    payload: { id: 'k', ...payload },
+    status: 'delivering',
+    attempts,
+  } as any;
+}
+
+describe('WebhookDispatcher', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  describe('helpers', () => {
+    it('filters by event and slab; empty filters match everything', () => {
+      expect(matchesSubscription({ events: [], slabs: [] }, payload)).toBe(true);
+      expect(matchesSubscription({ events: ['crank.stale'], slabs: [SLAB] }, payload)).toBe(true);
+      expect(matchesSubscription({ events: ['market.created'], slabs: [] }, payload)).toBe(false);
+      expect(matchesSubscription({ events: [], slabs: [OTHER_SLAB] }, payload)).toBe(false);
+    });
+
+    it('keys an event the same however it arrives', () => {
+      const bridged = JSON.parse(JSON.stringify(payload));
+      expect(webhookEventKey(bridged)).toBe(webhookEventKey(payload));
+      expect(webhookEventKey({ ...payload, timestamp: payload.timestamp + 1 })).not.toBe(webhookEventKey(payload));
+    });
+
+    it('signs timestamp.body with the subscription secret', () => {
+      const expected = createHmac('sha256', 'whsec_test').update('1700.{"a":1}').digest('hex');
+      expect(signWebhook('whsec_test', 1700, '{"a":1}')).toBe(`sha256=${expected}`);
+    });
+
+    it('backs off exponentially up to an hour', () => {
+      expect([1, 2, 3, 4].map(webhookRetryDelayMs)).toEqual([10_000, 20_000, 40_000, 80_000]);
+      expect(webhookRetryDelayMs(20)).toBe(3_600_000);
+    });
+  });
+
+  it('queues one delivery per matching subscription', async () => {
+    vi.mocked(shared.getActiveWebhookSubscriptions).mockResolvedValue([
+      subscription({ id: 'all' }),
+      subscription({ id: 'stale-only', events: ['crank.stale'] }),
+      subscription({ id: 'other-slab', slabs: [OTHER_SLAB] }),
+    ]);
+    const dispatcher = new WebhookDispatcher({ maxAttempts: 3, timeoutMs: 1000 }, vi.fn());
+    await dispatcher.refreshSubscriptions();
+
+    expect(await dispatcher.enqueue(payload)).toBe(2);
+
+    const rows = vi.mocked(shared.enqueueWebhookDeliveries).mock.calls[0][0];
+    expect(rows.map((r) => r.subscription_id)).toEqual(['all', 'stale-only']);
+    expect(rows[0]).toMatchObject({
+      event: 'crank.stale',
+      slab_address: SLAB,
+      event_key: webhookEventKey(payload),
+      payload: { id: webhookEventKey(payload), event: 'crank.stale', data: { reason: 'oracle stale' } },
+    });
+  });
+
+  it('holds events it could not queue and retries them in order before sending', async () => {
+    vi.mocked(shared.getActiveWebhookSubscriptions).mockResolvedValue([subscription()]);
+    const dispatcher = new WebhookDispatcher({ maxAttempts: 3, timeoutMs: 1000 }, vi.fn());
+    await dispatcher.refreshSubscriptions();
+    const later = { ...payload, timestamp: payload.timestamp + 1 };
+
+    vi.mocked(shared.enqueueWebhookDeliveries).mockRejectedValueOnce(new Error('db down'));
+    expect(await dispatcher.enqueue(payload)).toBe(0);
+    // Queued behind the failed event, not written ahead of it
+    expect(await dispatcher.enqueue(later)).toBe(0);
+    expect(shared.enqueueWebhookDeliveries).toHaveBeenCalledTimes(1);
+    expect(dispatcher.getStatus()).toMatchObject({ unqueuedEvents: 2 });
+
+    vi.mocked(shared.enqueueWebhookDeliveries).mockRejectedValueOnce(new Error('db down'));
+    await dispatcher.tick();
+    expect(dispatcher.getStatus()).toMatchObject({ unqueuedEvents: 2 });
+
+    await dispatcher.tick();
+    const keys = vi.mocked(shared.enqueueWebhookDeliveries).mock.calls.slice(2).map(([rows]) => rows[0].event_key);
+    expect(keys).toEqual([webhookEventKey(payload), webhookEventKey(later)]);
+    expect(dispatcher.getStatus()).toMatchObject({ unqueuedEvents: 0, enqueued: 2 });
+  });
+
+  it('refuses literal private addresses without connecting', async () => {
+    await expect(publicFetch('http://127.0.0.1:8080/x', { method: 'POST', body: '{}' })).rejects.toBeInstanceOf(
+      shared.PrivateAddressError,
+    );
+  });
+
+  it('POSTs a signed body and marks it delivered on 2xx', async () => {
+    vi.mocked(shared.getActiveWebhookSubscriptions).mockResolvedValue([subscription()]);
+    vi.mocked(shared.claimDueWebhookDeliveries).mockResolvedValue([delivery()]);
+    const fetchFn = vi.fn(async () => new Response(null, { status: 204 }));
+    const dispatcher = new WebhookDispatcher({ maxAttempts: 3, timeoutMs: 1000 }, fetchFn);
+    await dispatcher.refreshSubscriptions();
+
+    expect(await dispatcher.tick()).toBe(1);
+
+    const [url, init] = fetchFn.mock.calls[0] as unknown as [string, RequestInit];
+    const headers = init.headers as Record<string, string>;
+    expect(url).toBe('https://hooks.example.com/x');
+    expect(init.redirect).toBe('manual');
+    expect(headers['X-Percolator-Signature']).toBe(
+      signWebhook('whsec_test', Number(headers['X-Percolator-Timestamp']), init.body as string),
+    );
+    expect(shared.insertWebhookAttempt).toHaveBeenCalledWith(
+      expect.objectContaining({ delivery_id: 42, attempt: 1, status_code: 204, error: null }),
+    );
+    expect(shared.updateWebhookDelivery).toHaveBeenCalledWith(42, expect.objectContaining({ status: 'delivered', attempts: 1 }));
+    expect(dispatcher.getStatus()).toMatchObject({ delivered: 1 });
+  });
+
+  it('reschedules failures with backoff, then dead-letters the last attempt', async () => {
+    vi.mocked(shared.getActiveWebhookSubscriptions).mockResolvedValue([subscription()]);
+    const fetchFn = vi.fn(async () => new Response('nope', { status: 500 }));
+    const dispatcher = new WebhookDispatcher({ maxAttempts: 3, timeoutMs: 1000 }, fetchFn);
+    await dispatcher.refreshSubscriptions();
+
+    vi.mocked(shared.claimDueWebhookDeliveries).mockResolvedValueOnce([delivery(1)]);
+    const before = Date.now();
+    await dispatcher.tick();
+    const retry = vi.mocked(shared.updateWebhookDelivery).mock.calls[0][1];
+    expect(retry).toMatchObject({ status: 'pending', attempts: 2, last_status_code: 500, last_error: 'HTTP 500' });
+    expect(Date.parse(retry.next_attempt_at!) - before).toBeGreaterThanOrEqual(20_000);
+    expect(shared.insertWebhookDeadLetter).not.toHaveBeenCalled();
+
+    fetchFn.mockRejectedValueOnce(new Error('connect ECONNREFUSED'));
+    vi.mocked(shared.claimDueWebhookDeliveries).mockResolvedValueOnce([delivery(2)]);
+    await dispatcher.tick();
+    expect(shared.updateWebhookDelivery).toHaveBeenLastCalledWith(42, expect.objectContaining({ status: 'dead', attempts: 3 }));
+    expect(shared.insertWebhookDeadLetter).toHaveBeenCalledWith(
+      expect.objectContaining({ delivery_id: 42, attempts: 3, last_status_code: null, last_error: 'connect ECONNREFUSED' }),
+    );
+    expect(dispatcher.getStatus()).toMatchObject({ failedAttempts: 2, deadLettered: 1 });
+  });
+
+  it('dead-letters deliveries of a disabled subscription without sending', async () => {
+    vi.mocked(shared.claimDueWebhookDeliveries).mockResolvedValue([delivery(1)]);
+    const fetchFn = vi.fn();
+    const dispatcher = new WebhookDispatcher({ maxAttempts: 3, timeoutMs: 1000 }, fetchFn);
+
+    await dispatcher.tick();
+
+    expect(fetchFn).not.toHaveBeenCalled();
+    expect(shared.insertWebhookDeadLetter).toHaveBeenCalledWith(
+      expect.objectContaining({ attempts: 1, last_error: 'subscription disabled' }),
+    );
+  });
+});
@@ -8,4 +8,15 @@ export function validateKeeperEnvGuards(env: NodeJS.ProcessEnv = process.env): v
       "Set SUPABASE_KEY to the anon key for keeper runtime."
     );
   }
+
+  // A shadow keeper simulates as the live wallets it shadows and never loads a key
+  if (env.KEEPER_SHADOW_MODE === "true") {
+    if (!env.KEEPER_SHADOW_LIVE_WALLETS?.split(",").some((w) => w.trim())) {
+      throw new Error(
+        "Keeper misconfiguration: KEEPER_SHADOW_LIVE_WALLETS must list the live keeper wallets in shadow mode."
+      );
+    }
+  } else if (!env.CRANK_KEYPAIR) {
+    throw new Error("CRANK_KEYPAIR must be set for keeper service");
+  }
 }
@@ -1,9 +1,15 @@
 import "dotenv/config";
 import http from "node:http";
-import { config, createLogger, initSentry, captureException, sendInfoAlert, createServiceMonitors } from "@percolator/shared";
+import { PublicKey } from "@solana/web3.js";
+import { config, createLogger, initSentry, captureException, sendInfoAlert, createServiceMonitors, connectEventBus, createEventTransport, type EventBridge } from "@percolator/shared";
 import { OracleService } from "./services/oracle.js";
+import { CollateralValuer, PythSource, WSOL_MINT } from "./services/oracle-sources.js";
+import { PoolTwapEngine } from "./services/pool-twap.js";
 import { CrankService } from "./services/crank.js";
 import { LiquidationService } from "./services/liquidation.js";
+import { KeeperCoordinator, InMemoryCoordinationBackend, type CoordinationBackend } from "./services/coordination.js";
+import { PostgresCoordinationBackend } from "./services/coordination-pg.js";
+import { ShadowRecorder, ShadowReporter } from "./services/shadow.js";
 import { validateKeeperEnvGuards } from "./env-guards.js";
 
 // Monitoring — alerts to Discord on threshold breaches
@@ -14,17 +20,75 @@ initSentry("keeper");
 
 const logger = createLogger("keeper");
 
-if (!process.env.CRANK_KEYPAIR) {
-  throw new Error("CRANK_KEYPAIR must be set for keeper service");
-}
-
 validateKeeperEnvGuards();
 
 logger.info("Keeper service starting");
 
-const oracleService = new OracleService();
+// TWAP mode: push the on-chain pool TWAP instead of the multi-source median
+let twapEngine: PoolTwapEngine | null = null;
+if (config.oraclePriceMode === "twap") {
+  const pyth = new PythSource();
+  twapEngine = new PoolTwapEngine(
+    {
+      windowSlots: config.twapWindowSlots,
+      minSamples: config.twapMinSamples,
+      minDepthUsdE6: BigInt(Math.round(config.twapMinDepthUsd * 1_000_000)),
+    },
+    config.twapSampleIntervalMs,
+    () => pyth.quote(WSOL_MINT),
+  );
+}
+
+const oracleService = new OracleService(twapEngine ? { twap: twapEngine } : {});
 const crankService = new CrankService(oracleService);
 const liquidationService = new LiquidationService(oracleService);
+// Crank scheduler ranks markets using the liquidation scanner's fresher engine state
+crankService.setRiskSource((slabAddress) => liquidationService.getMarketRisk(slabAddress));
+// Liquidation planner weighs fees (in collateral) against tx cost (in lamports)
+const collateralValuer = new CollateralValuer();
+liquidationService.setCollateralValuer((market) =>
+  collateralValuer.lamportsPerUnit(
+    market.config.collateralMint.toBase58(),
+    oracleService.getCurrentPrice(market.slabAddress.toBase58())?.priceE6,
+  ),
+);
+
+// Shadow mode — every transaction is simulated and diffed against the live keepers
+let shadowReporter: ShadowReporter | null = null;
+if (config.keeperShadowMode) {
+  // Simulations are unsigned, paid for by the first live wallet
+  const liveWallets = config.keeperShadowLiveWallets.map((w) => new PublicKey(w));
+  const recorder = new ShadowRecorder(config.allProgramIds, liveWallets[0]!);
+  oracleService.setShadow(recorder);
+  crankService.setShadow(recorder);
+  liquidationService.setShadow(recorder);
+  shadowReporter = new ShadowReporter(recorder, liveWallets, config.allProgramIds, config.keeperShadowReportIntervalMs);
+  logger.warn("SHADOW MODE — transactions are simulated, never sent", {
+    liveWallets: liveWallets.map((w) => w.toBase58()),
+    reportIntervalMs: config.keeperShadowReportIntervalMs,
+  });
+}
+
+// Multi-keeper coordination — replicas shard markets and elect oracle leaders.
+// A shadow keeper never joins the live group: it would take markets from real keepers.
+let coordinationBackend: CoordinationBackend;
+if (config.keeperShadowMode) {
+  coordinationBackend = new InMemoryCoordinationBackend();
+} else if (config.keeperCoordination === "postgres" && config.keeperDatabaseUrl) {
+  coordinationBackend = new PostgresCoordinationBackend(config.keeperDatabaseUrl);
+} else {
+  if (config.keeperCoordination === "postgres") {
+    logger.warn("KEEPER_DATABASE_URL not set — running as a single keeper; do not start replicas");
+  }
+  coordinationBackend = new InMemoryCoordinationBackend();
+}
+const coordinator = new KeeperCoordinator(coordinationBackend, { refreshMs: config.keeperCoordinationRefreshMs });
+crankService.setCoordinator(coordinator);
+
+// Cross-process eventBus — keeper events (crank, price, liquidation, ADL) reach
+// the api and indexer. Publish-only: nothing here listens to other services.
+// A shadow keeper stays in-process so simulated outcomes never look live.
+let eventBridge: EventBridge | null = null;
 
 // Health state tracking
 let lastSuccessfulCrankTime = 0;
@@ -85,6 +149,28 @@ const healthServer = http.createServer((req, res) => {
     return;
   }
 
+  // GET /adl — deleveraging plans for markets whose insurance fund can't cover
+  // bad debt (or in emergency OI mode). Executed by an admin with
+  // scripts/execute-adl-plan.ts until native ADL ships.
+  if (req.url === "/adl" && req.method === "GET") {
+    const plans = Object.fromEntries(liquidationService.getAdlPlans());
+    res.writeHead(200, { "Content-Type": "application/json" });
+    res.end(JSON.stringify({ markets: plans }, (_key, val) => typeof val === "bigint" ? val.toString() : val));
+    return;
+  }
+
+  // GET /shadow — last shadow-vs-live diff report (shadow mode only)
+  if (req.url === "/shadow" && req.method === "GET") {
+    if (!shadowReporter) {
+      res.writeHead(404, { "Content-Type": "application/json" });
+      res.end(JSON.stringify({ message: "Not running in shadow mode" }));
+      return;
+    }
+    res.writeHead(200, { "Content-Type": "application/json" });
+    res.end(JSON.stringify({ report: shadowReporter.getLastReport() }));
+    return;
+  }
+
   if (req.url === "/health" && req.method === "GET") {
     const markets = crankService.getMarkets();
     const marketsTracked = markets.size;
@@ -124,6 +210,7 @@ const healthServer = http.createServer((req, res) => {
       status = "down";
     }
     
+    const plan = crankService.getLastPlan();
     const healthData = {
       status,
       lastCrankTime: mostRecentCrank,
@@ -131,6 +218,22 @@ const healthServer = http.createServer((req, res) => {
       marketsTracked,
       timeSinceLastCrankMs: timeSinceLastCrank === Infinity ? null : timeSinceLastCrank,
       timeSinceLastOracleMs: timeSinceLastOracle === Infinity ? null : timeSinceLastOracle,
+      coordination: coordinator.getStatus(),
+      eventBus: eventBridge?.getStatus() ?? null,
+      marketsOwned: crankService.getOwnedMarkets().size,
+      adlMarkets: [...liquidationService.getAdlPlans().keys()],
+      shadow: shadowReporter && {
+        lastReportAt: shadowReporter.getLastReport()?.windowEnd ?? null,
+        divergedMarkets: shadowReporter.getLastReport()?.divergedMarkets ?? null,
+      },
+      crankSchedule: plan && {
+        selected: plan.selected.length,
+        deferred: plan.deferred.length,
+        deferredUrgent: plan.deferred.filter((p) => p.urgent).length,
+        notDue: plan.notDue.length,
+        computeUnits: plan.computeUnits,
+        feeLamports: plan.feeLamports,
+      },
       monitors: {
         rpc: monitors.rpc.getStatus(),
         scan: monitors.scan.getStatus(),
@@ -152,16 +255,41 @@ healthServer.listen(healthPort, () => {
 });
 
 async function start() {
+  if (!config.keeperShadowMode) {
+    try {
+      eventBridge = await connectEventBus(
+        createEventTransport(config.eventBusTransport, {
+          databaseUrl: config.eventBusDatabaseUrl,
+          redisUrl: config.eventBusRedisUrl,
+        }),
+        { consume: false },
+      );
+    } catch (err) {
+      // Events are informational for other services; keep cranking without them
+      logger.error("Event bus connection failed — events stay in-process", {
+        error: err instanceof Error ? err.message : String(err),
+      });
+    }
+  }
+  await coordinator.start();
+  logger.info("Joined keeper group", coordinator.getStatus());
   const markets = await crankService.discover();
   logger.info("Markets discovered", { count: markets.length });
   crankService.start();
   logger.info("Crank service started");
-  liquidationService.start(() => crankService.getMarkets());
+  twapEngine?.start();
+  // Only scan this keeper's shard — peers liquidate theirs
+  liquidationService.start(() => crankService.getOwnedMarkets());
   logger.info("Liquidation scanner started");
+  if (shadowReporter) {
+    shadowReporter.start();
+    logger.info("Shadow reporter started");
+  }
   
   // Send startup alert
   await sendInfoAlert("Keeper service started", [
     { name: "Markets Tracked", value: markets.length.toString(), inline: true },
+    { name: "Mode", value: shadowReporter ? "shadow" : "live", inline: true },
     { name: "Health Endpoint", value: `http://localhost:${healthPort}/health`, inline: true },
   ]);
 }
@@ -196,6 +324,19 @@ async function shutdown(signal: string): Promise<void> {
     // Stop liquidation service (clears timers)
     logger.info("Stopping liquidation service");
     liquidationService.stop();
+    shadowReporter?.stop();
+
+    if (twapEngine) {
+      logger.info("Stopping pool TWAP sampler");
+      twapEngine.stop();
+    }
+
+    // Leave the group so peers take over our markets on their next refresh
+    logger.info("Leaving keeper group");
+    await coordinator.stop();
+
+    // Flush events still queued for other services
+    await eventBridge?.stop();
     
     // Note: Solana connection doesn't need explicit cleanup
     // Oracle service has no persistent state to clean up
@@ -0,0 +1,258 @@
+/**
+ * ADL planner — what to deleverage when the insurance fund can no longer
+ * cover a market's bad debt.
+ *
+ *   - Trigger: accounts past bankruptcy (equity < 0) owe more than the
+ *     insurance fund holds, or the engine has entered `emergencyOiMode`
+ *   - Side: the winners opposite the bankrupt positions — their unrealized
+ *     PnL is the liability the fund can't pay. In emergency OI mode with no
+ *     shortfall, the dominant OI side is reduced instead
+ *   - Rank: profitable positions on that side by PnL × leverage, so the most
+ *     levered winners are closed first (the usual exchange ADL queue)
+ *   - Size: take ranked positions until their PnL covers the shortfall, or
+ *     their size covers the OI imbalance
+ *
+ * Native ADL (PERC-305) is not on-chain. Until it ships, a plan is executed
+ * by an admin through `AdminForceClose`, one instruction per position — see
+ * {@link ADMIN_FORCE_CLOSE_EXECUTOR}. That closes at the oracle price: it
+ * stops the liability growing but, unlike native ADL, doesn't haircut the
+ * closed PnL.
+ */
+
+import { SYSVAR_CLOCK_PUBKEY, type PublicKey, type TransactionInstruction } from "@solana/web3.js";
+import {
+  buildAccountMetas,
+  buildIx,
+  encodeAdminForceClose,
+  ACCOUNTS_ADMIN_FORCE_CLOSE,
+  type EngineState,
+} from "@percolator/sdk";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+export type AdlSide = "long" | "short";
+
+export type AdlTrigger =
+  | "insurance-shortfall" // bad debt exceeds the insurance fund
+  | "emergency-oi";       // engine.emergencyOiMode is set
+
+/** An open user position, as scanned at the current price */
+export interface AdlPosition {
+  accountIdx: number;
+  owner: string;
+  positionSize: bigint;
+  /** capital + pnl + mark pnl − funding (collateral units) */
+  equity: bigint;
+  unrealizedPnl: bigint;
+  notional: bigint;
+}
+
+export interface AdlAssessment {
+  slabAddress: string;
+  /** Σ −equity over bankrupt accounts (collateral units) */
+  badDebt: bigint;
+  /** Insurance balance, including the market's isolated share */
+  insuranceAvailable: bigint;
+  /** badDebt − insuranceAvailable, floored at 0 */
+  shortfall: bigint;
+  emergencyOiMode: boolean;
+  longOi: bigint;
+  shortOi: bigint;
+  triggers: AdlTrigger[];
+}
+
+export interface AdlCandidate extends AdlPosition {
+  side: AdlSide;
+  /** notional / equity, in bps */
+  leverageBps: bigint;
+  /** unrealizedPnl × leverageBps / 1e4 — the ranking key */
+  score: bigint;
+}
+
+export interface AdlPlan {
+  assessment: AdlAssessment;
+  /** Side being deleveraged; null when nothing triggered */
+  side: AdlSide | null;
+  /** What the selection has to cover: shortfall (collateral) or OI imbalance (position units) */
+  target: bigint;
+  targetKind: "pnl" | "oi" | null;
+  /** Highest score first */
+  selected: AdlCandidate[];
+  /** Σ PnL (or |size| for OI) of the selection */
+  covered: bigint;
+  /** Whether every eligible position together still falls short of the target */
+  exhausted: boolean;
+}
+
+// ---------------------------------------------------------------------------
+// Assessment
+// ---------------------------------------------------------------------------
+
+function abs(n: bigint): bigint {
+  return n < 0n ? -n : n;
+}
+
+function sideOf(positionSize: bigint): AdlSide {
+  return positionSize > 0n ? "long" : "short";
+}
+
+/** Compare a market's bad debt to its insurance fund and check the OI breaker. */
+export function assessAdl(
+  slabAddress: string,
+  engine: Pick<EngineState, "insuranceFund" | "emergencyOiMode" | "longOi" | "shortOi">,
+  positions: AdlPosition[],
+): AdlAssessment {
+  let badDebt = 0n;
+  for (const p of positions) {
+    if (p.equity < 0n) badDebt += -p.equity;
+  }
+  const insuranceAvailable = engine.insuranceFund.balance + engine.insuranceFund.isolatedBalance;
+  const shortfall = badDebt > insuranceAvailable ? badDebt - insuranceAvailable : 0n;
+
+  const triggers: AdlTrigger[] = [];
+  if (shortfall > 0n) triggers.push("insurance-shortfall");
+  if (engine.emergencyOiMode) triggers.push("emergency-oi");
+
+  return {
+    slabAddress,
+    badDebt,
+    insuranceAvailable,
+    shortfall,
+    emergencyOiMode: engine.emergencyOiMode,
+    longOi: engine.longOi,
+    shortOi: engine.shortOi,
+    triggers,
+  };
+}
+
+// ---------------------------------------------------------------------------
+// Ranking
+// ---------------------------------------------------------------------------
+
+/**
+ * Profitable positions on `side`, highest PnL × leverage first. Ties go to
+ * the larger PnL, then the lower account index so the order is stable.
+ */
+export function rankAdlCandidates(positions: AdlPosition[], side: AdlSide): AdlCandidate[] {
+  const ranked: AdlCandidate[] = [];
+  for (const p of positions) {
+    if (p.positionSize === 0n || sideOf(p.positionSize) !== side) continue;
+    if (p.unrealizedPnl <= 0n || p.equity <= 0n) continue;
+    const leverageBps = (p.notional * 10_000n) / p.equity;
+    ranked.push({ ...p, side, leverageBps, score: (p.unrealizedPnl * leverageBps) / 10_000n });
+  }
+  return ranked.sort((a, b) =>
+    a.score !== b.score ? (a.score > b.score ? -1 : 1)
+      : a.unrealizedPnl !== b.unrealizedPnl ? (a.unrealizedPnl > b.unrealizedPnl ? -1 : 1)
+        : a.accountIdx - b.accountIdx,
+  );
+}
+
+// ---------------------------------------------------------------------------
+// Planning
+// ---------------------------------------------------------------------------
+
+/**
+ * Plan a market's deleveraging. With a shortfall, the side opposite the bulk
+ * of the bad debt is deleveraged until the selected PnL covers it; in
+ * emergency OI mode alone, the heavier side is cut until it matches the
+ * other. Returns a plan with no selection when nothing triggered.
+ */
+export function planAdl(
+  slabAddress: string,
+  engine: Pick<EngineState, "insuranceFund" | "emergencyOiMode" | "longOi" | "shortOi">,
+  positions: AdlPosition[],
+): AdlPlan {
+  const assessment = assessAdl(slabAddress, engine, positions);
+  const empty: AdlPlan = {
+    assessment, side: null, target: 0n, targetKind: null, selected: [], covered: 0n, exhausted: false,
+  };
+
+  let side: AdlSide;
+  let target: bigint;
+  let targetKind: "pnl" | "oi";
+  if (assessment.shortfall > 0n) {
+    // Bankrupt longs are owed to profitable shorts, and vice versa
+    let longDebt = 0n;
+    let shortDebt = 0n;
+    for (const p of positions) {
+      if (p.equity >= 0n) continue;
+      if (p.positionSize > 0n) longDebt += -p.equity;
+      else shortDebt += -p.equity;
+    }
+    side = longDebt >= shortDebt ? "short" : "long";
+    target = assessment.shortfall;
+    targetKind = "pnl";
+  } else if (assessment.emergencyOiMode && engine.longOi !== engine.shortOi) {
+    side = engine.longOi > engine.shortOi ? "long" : "short";
+    target = abs(engine.longOi - engine.shortOi);
+    targetKind = "oi";
+  } else {
+    return empty;
+  }
+
+  const selected: AdlCandidate[] = [];
+  let covered = 0n;
+  for (const c of rankAdlCandidates(positions, side)) {
+    if (covered >= target) break;
+    selected.push(c);
+    covered += targetKind === "pnl" ? c.unrealizedPnl : abs(c.positionSize);
+  }
+
+  return { assessment, side, target, targetKind, selected, covered, exhausted: covered < target };
+}
+
+// ---------------------------------------------------------------------------
+// Execution
+// ---------------------------------------------------------------------------
+
+export interface AdlInstructionContext {
+  programId: PublicKey;
+  slab: PublicKey;
+  /** Same oracle account the market's crank and liquidations use */
+  oracle: PublicKey;
+  /** Signing authority for the executor — the market admin for AdminForceClose */
+  authority: PublicKey;
+}
+
+/** How one selected position is deleveraged on-chain. */
+export interface AdlExecutor {
+  name: string;
+  /** Who must sign: force-closes need the market admin */
+  authority: "admin" | "keeper";
+  instruction(ctx: AdlInstructionContext, accountIdx: number): TransactionInstruction;
+}
+
+/**
+ * Interim executor: AdminForceClose (tag 21) closes the position at the
+ * oracle price, skipping margin checks. Admin-signed.
+ */
+export const ADMIN_FORCE_CLOSE_EXECUTOR: AdlExecutor = {
+  name: "admin-force-close",
+  authority: "admin",
+  instruction(ctx, accountIdx) {
+    const keys = buildAccountMetas(ACCOUNTS_ADMIN_FORCE_CLOSE, [
+      ctx.authority, ctx.slab, SYSVAR_CLOCK_PUBKEY, ctx.oracle,
+    ]);
+    return buildIx({ programId: ctx.programId, keys, data: encodeAdminForceClose({ targetIdx: accountIdx }) });
+  },
+};
+
+/**
+ * Executor used for plans. When PERC-305 ships, add its encoder and account
+ * spec to the SDK under the newly assigned tag (≥47 — tag 43 is
+ * ChallengeSettlement, and `ACCOUNTS_EXECUTE_ADL` stays deprecated), add a
+ * keeper-signed executor here, and point this at it.
+ */
+export const ADL_EXECUTOR: AdlExecutor = ADMIN_FORCE_CLOSE_EXECUTOR;
+
+/** One instruction per selected position, in ranked order. */
+export function buildAdlInstructions(
+  plan: AdlPlan,
+  ctx: AdlInstructionContext,
+  executor: AdlExecutor = ADL_EXECUTOR,
+): TransactionInstruction[] {
+  return plan.selected.map((c) => executor.instruction(ctx, c.accountIdx));
+}
@@ -0,0 +1,123 @@
+/**
+ * Postgres coordination backend — session-level advisory locks.
+ *
+ * Each keeper holds one dedicated connection. Membership is an advisory lock
+ * on (MEMBER namespace, member ID) and the group is read back from
+ * `pg_locks`; oracle leadership is a lock on (ORACLE namespace, hash of the
+ * market). Postgres drops a session's locks the moment the session ends, so
+ * a crashed keeper's markets fail over on the peers' next refresh.
+ *
+ * KEEPER_DATABASE_URL must be a direct/session connection — advisory locks
+ * don't survive a transaction-mode pooler (Supabase port 6543).
+ */
+
+import { createHash, randomInt } from "node:crypto";
+import pg from "pg";
+import { createLogger } from "@percolator/shared";
+import type { CoordinationBackend } from "./coordination.js";
+
+const logger = createLogger("keeper:coordination-pg");
+
+/** Advisory lock namespaces (first int4 key). Arbitrary, but fixed across replicas. */
+export const PG_LOCK_NAMESPACE_MEMBER = 0x5045_5201; // "PER" + 1
+export const PG_LOCK_NAMESPACE_ORACLE = 0x5045_5202;
+
+/** Non-negative int4 key for a resource name */
+export function pgLockKey(resource: string): number {
+  return createHash("sha1").update(resource).digest().readUInt32BE(0) & 0x7fff_ffff;
+}
+
+export class PostgresCoordinationBackend implements CoordinationBackend {
+  private client: pg.Client | null = null;
+  private memberKey: number | null = null;
+
+  constructor(private readonly connectionString: string) {}
+
+  private async connect(): Promise<pg.Client> {
+    if (this.client) return this.client;
+    const client = new pg.Client({
+      connectionString: this.connectionString,
+      application_name: "percolator-keeper",
+      keepAlive: true,
+      // Fail fast so a hung database fences this keeper instead of stalling it
+      statement_timeout: 5_000,
+      connectionTimeoutMillis: 5_000,
+    });
+    client.on("error", (err) => {
+      logger.error("Coordination connection lost", { error: err.message });
+      // Locks died with the session; the next call reconnects and re-joins
+      if (this.client === client) {
+        this.client = null;
+        this.memberKey = null;
+      }
+    });
+    await client.connect();
+    this.client = client;
+    return client;
+  }
+
+  async join(): Promise<string> {
+    const client = await this.connect();
+    // Random member key; retry on the (unlikely) collision with a live peer
+    for (let attempt = 0; attempt < 5; attempt++) {
+      const key = randomInt(0, 0x7fff_ffff);
+      const { rows } = await client.query<{ ok: boolean }>(
+        "SELECT pg_try_advisory_lock($1, $2) AS ok",
+        [PG_LOCK_NAMESPACE_MEMBER, key],
+      );
+      if (rows[0]?.ok) {
+        this.memberKey = key;
+        return String(key);
+      }
+    }
+    throw new Error("Could not claim a keeper member key");
+  }
+
+  async members(): Promise<string[]> {
+    if (!this.client || this.memberKey === null) throw new Error("Not joined");
+    // Two-key advisory locks appear with classid = key1, objid = key2, objsubid = 2
+    const { rows } = await this.client.query<{ objid: string }>(
+      `SELECT objid::text AS objid FROM pg_locks
+        WHERE locktype = 'advisory' AND granted AND objsubid = 2 AND classid = $1::int8::oid`,
+      [PG_LOCK_NAMESPACE_MEMBER],
+    );
+    return rows.map((r) => r.objid);
+  }
+
+  async tryAcquire(resource: string): Promise<boolean> {
+    if (!this.client || this.memberKey === null) return false;
+    // pg_try_advisory_lock is re-entrant per session; only take it once
+    const key = pgLockKey(resource);
+    const held = await this.client.query<{ held: boolean }>(
+      `SELECT EXISTS (
+         SELECT 1 FROM pg_locks
+          WHERE locktype = 'advisory' AND granted AND objsubid = 2 AND pid = pg_backend_pid()
+            AND classid = $1::int8::oid AND objid = $2::int8::oid
+       ) AS held`,
+      [PG_LOCK_NAMESPACE_ORACLE, key],
+    );
+    if (held.rows[0]?.held) return true;
+    const { rows } = await this.client.query<{ ok: boolean }>(
+      "SELECT pg_try_advisory_lock($1, $2) AS ok",
+      [PG_LOCK_NAMESPACE_ORACLE, key],
+    );
+    return rows[0]?.ok === true;
+  }
+
+  async release(resource: string): Promise<void> {
+    if (!this.client) return;
+    await this.client.query("SELECT pg_advisory_unlock($1, $2)", [PG_LOCK_NAMESPACE_ORACLE, pgLockKey(resource)]);
+  }
+
+  async leave(): Promise<void> {
+    const client = this.client;
+    this.client = null;
+    this.memberKey = null;
+    if (!client) return;
+    try {
+      await client.query("SELECT pg_advisory_unlock_all()");
+    } finally {
+      await client.end().catch(() => {});
+    }
+  }
+}
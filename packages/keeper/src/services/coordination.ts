@@ -0,0 +1,290 @@
+/**
+ * Multi-keeper coordination — lets several keeper replicas share the markets
+ * without double-sending.
+ *
+ *   - Sharding: every live keeper is placed on a consistent-hash ring; a
+ *     market is cranked and liquidated only by the keeper that owns its
+ *     point on the ring. A keeper joining or leaving moves ~1/N of markets.
+ *   - Oracle leadership: before pushing a price, the owner takes a per-market
+ *     lock. Ring views can briefly disagree while membership changes; the
+ *     lock guarantees a single pusher even then.
+ *   - Failover: membership is re-read every `refreshMs`. A dead keeper's
+ *     locks vanish with its backend session, so its markets are picked up on
+ *     the next refresh. A keeper that can't reach the backend owns nothing
+ *     until it can.
+ *
+ * Coordination goes through a {@link CoordinationBackend}: Postgres advisory
+ * locks in production (coordination-pg.ts), or the in-memory backend below
+ * for a single instance and tests.
+ */
+
+import { createHash, randomUUID } from "node:crypto";
+import { createLogger } from "@percolator/shared";
+
+const logger = createLogger("keeper:coordination");
+
+// ---------------------------------------------------------------------------
+// Backend
+// ---------------------------------------------------------------------------
+
+export interface CoordinationBackend {
+  /** Register this process as a live member; returns its member ID on the ring. */
+  join(): Promise<string>;
+  /** Member IDs of every live keeper, including this one. */
+  members(): Promise<string[]>;
+  /** Take an exclusive lock, held until released or this member dies. Re-entrant. */
+  tryAcquire(resource: string): Promise<boolean>;
+  release(resource: string): Promise<void>;
+  /** Leave the group, dropping membership and every lock held. */
+  leave(): Promise<void>;
+}
+
+/** Shared state for in-memory backends — one hub stands in for the database. */
+export class InMemoryCoordinationHub {
+  readonly members = new Set<string>();
+  /** resource → holder member ID */
+  readonly locks = new Map<string, string>();
+}
+
+/**
+ * In-memory backend. A single instance with its own hub is a solo keeper
+ * that owns every market; several backends on one hub simulate replicas.
+ */
+export class InMemoryCoordinationBackend implements CoordinationBackend {
+  private memberId: string | null = null;
+
+  constructor(
+    private readonly hub: InMemoryCoordinationHub = new InMemoryCoordinationHub(),
+    private readonly preferredId?: string,
+  ) {}
+
+  async join(): Promise<string> {
+    const id = this.preferredId ?? randomUUID();
+    this.hub.members.add(id);
+    this.memberId = id;
+    return id;
+  }
+
+  async members(): Promise<string[]> {
+    return [...this.hub.members];
+  }
+
+  async tryAcquire(resource: string): Promise<boolean> {
+    if (!this.memberId) return false;
+    const holder = this.hub.locks.get(resource);
+    if (holder && holder !== this.memberId) return false;
+    this.hub.locks.set(resource, this.memberId);
+    return true;
+  }
+
+  async release(resource: string): Promise<void> {
+    if (this.memberId && this.hub.locks.get(resource) === this.memberId) {
+      this.hub.locks.delete(resource);
+    }
+  }
+
+  async leave(): Promise<void> {
+    if (!this.memberId) return;
+    this.hub.members.delete(this.memberId);
+    for (const [resource, holder] of this.hub.locks) {
+      if (holder === this.memberId) this.hub.locks.delete(resource);
+    }
+    this.memberId = null;
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Consistent hashing
+// ---------------------------------------------------------------------------
+
+/** Virtual nodes per member — smooths the share each keeper gets */
+export const DEFAULT_VIRTUAL_NODES = 64;
+
+function hash32(key: string): number {
+  return createHash("sha1").update(key).digest().readUInt32BE(0);
+}
+
+/** Consistent-hash ring over member IDs with virtual nodes. */
+export class HashRing {
+  private readonly points: { hash: number; member: string }[];
+
+  constructor(members: Iterable<string>, virtualNodes = DEFAULT_VIRTUAL_NODES) {
+    this.points = [];
+    for (const member of members) {
+      for (let v = 0; v < virtualNodes; v++) {
+        this.points.push({ hash: hash32(`${member}#${v}`), member });
+      }
+    }
+    this.points.sort((a, b) => a.hash - b.hash || (a.member < b.member ? -1 : 1));
+  }
+
+  /** Member owning `key`: the first point clockwise from the key's hash. */
+  owner(key: string): string | null {
+    if (this.points.length === 0) return null;
+    const h = hash32(key);
+    let lo = 0;
+    let hi = this.points.length;
+    while (lo < hi) {
+      const mid = (lo + hi) >> 1;
+      if (this.points[mid]!.hash < h) lo = mid + 1;
+      else hi = mid;
+    }
+    return this.points[lo % this.points.length]!.member;
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Coordinator
+// ---------------------------------------------------------------------------
+
+export interface CoordinatorOptions {
+  /** How often membership is re-read; bounds failover time */
+  refreshMs?: number;
+  virtualNodes?: number;
+}
+
+export interface CoordinatorStatus {
+  memberId: string | null;
+  healthy: boolean;
+  members: number;
+  oracleLeaderships: number;
+  lastRefresh: number;
+}
+
+/** Lock resource name for a market's oracle pushes */
+export function oracleLeaderResource(slabAddress: string): string {
+  return `oracle:${slabAddress}`;
+}
+
+export class KeeperCoordinator {
+  private readonly refreshMs: number;
+  private readonly virtualNodes: number;
+  private memberId: string | null = null;
+  private memberIds: string[] = [];
+  private ring: HashRing | null = null;
+  private healthy = false;
+  private lastRefresh = 0;
+  private readonly oracleLeaderOf = new Set<string>();
+  private timer: ReturnType<typeof setInterval> | null = null;
+  private refreshing = false;
+
+  constructor(private readonly backend: CoordinationBackend, opts: CoordinatorOptions = {}) {
+    this.refreshMs = opts.refreshMs ?? 2_000;
+    this.virtualNodes = opts.virtualNodes ?? DEFAULT_VIRTUAL_NODES;
+  }
+
+  async start(): Promise<void> {
+    if (this.timer) return;
+    await this.refresh();
+    this.timer = setInterval(() => {
+      this.refresh().catch(() => { /* logged in refresh */ });
+    }, this.refreshMs);
+  }
+
+  async stop(): Promise<void> {
+    if (this.timer) {
+      clearInterval(this.timer);
+      this.timer = null;
+    }
+    this.healthy = false;
+    this.oracleLeaderOf.clear();
+    this.memberId = null;
+    try {
+      await this.backend.leave();
+    } catch (err) {
+      logger.warn("Leaving coordination group failed", { error: err instanceof Error ? err.message : String(err) });
+    }
+  }
+
+  /**
+   * Re-read membership and rebuild the ring. Joins (or re-joins after a lost
+   * session) first; on any backend error the keeper fences itself off —
+   * owning nothing — until a refresh succeeds.
+   */
+  async refresh(): Promise<void> {
+    if (this.refreshing) return;
+    this.refreshing = true;
+    try {
+      if (!this.memberId) {
+        this.memberId = await this.backend.join();
+        logger.info("Joined keeper group", { memberId: this.memberId });
+      }
+      const members = (await this.backend.members()).sort();
+      if (!members.includes(this.memberId)) {
+        throw new Error("membership lost");
+      }
+
+      if (members.join(",") !== this.memberIds.join(",")) {
+        logger.info("Keeper group changed — rebalancing markets", {
+          memberId: this.memberId,
+          members: members.length,
+          previous: this.memberIds.length,
+        });
+        this.ring = new HashRing(members, this.virtualNodes);
+        this.memberIds = members;
+      }
+      this.healthy = true;
+      this.lastRefresh = Date.now();
+
+      // Hand oracle leadership back for markets that moved to a peer
+      for (const slab of [...this.oracleLeaderOf]) {
+        if (!this.owns(slab)) {
+          await this.backend.release(oracleLeaderResource(slab));
+          this.oracleLeaderOf.delete(slab);
+        }
+      }
+    } catch (err) {
+      if (this.healthy) {
+        logger.error("Coordination backend unavailable — pausing all markets", {
+          memberId: this.memberId,
+          error: err instanceof Error ? err.message : String(err),
+        });
+      }
+      this.healthy = false;
+      this.oracleLeaderOf.clear();
+      this.memberIds = [];
+      this.ring = null;
+      // Drop the session so the next refresh re-joins cleanly
+      this.memberId = null;
+      await this.backend.leave().catch(() => {});
+    } finally {
+      this.refreshing = false;
+    }
+  }
+
+  /** Whether this keeper should crank and liquidate `slabAddress`. */
+  owns(slabAddress: string): boolean {
+    return this.healthy && this.ring !== null && this.ring.owner(slabAddress) === this.memberId;
+  }
+
+  /**
+   * Take (or confirm) oracle-push leadership for a market this keeper owns.
+   * Returns false when the market belongs to a peer or a peer still holds
+   * the lock from before a rebalance.
+   */
+  async acquireOracleLeadership(slabAddress: string): Promise<boolean> {
+    if (!this.owns(slabAddress)) return false;
+    if (this.oracleLeaderOf.has(slabAddress)) return true;
+    try {
+      const ok = await this.backend.tryAcquire(oracleLeaderResource(slabAddress));
+      if (ok) this.oracleLeaderOf.add(slabAddress);
+      return ok;
+    } catch (err) {
+      logger.warn("Oracle leadership check failed", {
+        slabAddress,
+        error: err instanceof Error ? err.message : String(err),
+      });
+      return false;
+    }
+  }
+
+  getStatus(): CoordinatorStatus {
+    return {
+      memberId: this.memberId,
+      healthy: this.healthy,
+      members: this.memberIds.length,
+      oracleLeaderships: this.oracleLeaderOf.size,
+      lastRefresh: this.lastRefresh,
+    };
+  }
+}
@@ -0,0 +1,318 @@
+/**
+ * Crank scheduler — ranks markets by how urgently they need a crank and fits
+ * the most urgent ones into a per-cycle compute-unit and fee budget.
+ *
+ * Urgency comes from on-chain state rather than wall-clock intervals:
+ *   - staleness: slots since `lastCrankSlot` against `maxCrankStalenessSlots`
+ *     (past it, the program rejects trades and liquidations on the market)
+ *   - liquidation risk: margin headroom of the market's worst account, as
+ *     last seen by the liquidation scanner
+ *   - open interest, relative to the largest market in the cycle
+ *   - a funding settlement or oracle phase advance that has come due
+ *
+ * Markets with none of these are cranked at the old fixed intervals, and only
+ * after every urgent market has been funded from the budget.
+ */
+
+import { checkPhaseTransition, type DiscoveredMarket, type EngineState } from "@percolator/sdk";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/** Fresher market state fed back by the liquidation scanner */
+export interface MarketRiskSnapshot {
+  engine: EngineState;
+  /** Smallest (equity − maintenance margin) / notional across open positions, in bps */
+  worstMarginHeadroomBps: number | null;
+  observedAt: number;
+}
+
+export interface CrankCandidate {
+  slabAddress: string;
+  market: DiscoveredMarket;
+  lastCrankTime: number;
+  /** Slot of our last confirmed crank — newer than `engine.lastCrankSlot` between scans */
+  lastCrankSlot?: bigint;
+  isActive: boolean;
+  /** Whether the crank tx also pushes the oracle price (admin-oracle markets we own) */
+  pushesPrice: boolean;
+  risk?: MarketRiskSnapshot;
+}
+
+export interface CrankPriority {
+  slabAddress: string;
+  score: number;
+  /** slots since last crank / maxCrankStalenessSlots; 0 when the market sets no limit */
+  stalenessRatio: number;
+  fundingDue: boolean;
+  phaseAdvanceDue: boolean;
+  /** Needs a crank this cycle regardless of the fixed intervals */
+  urgent: boolean;
+  /** Fixed interval has elapsed (the pre-scheduler due rule) */
+  intervalDue: boolean;
+  computeUnits: number;
+  feeLamports: number;
+  reasons: string[];
+}
+
+export interface CrankBudget {
+  maxComputeUnits: number;
+  maxFeeLamports: number;
+}
+
+export interface CrankPlanOptions {
+  currentSlot: bigint;
+  budget: CrankBudget;
+  priorityFeeMicroLamports: number;
+  intervalMs: number;
+  inactiveIntervalMs: number;
+  now?: number;
+}
+
+export interface CrankPlan {
+  /** Markets to crank this cycle, most urgent first */
+  selected: CrankPriority[];
+  /** Due (urgent or interval-elapsed) but left out by the budget */
+  deferred: CrankPriority[];
+  /** Neither urgent nor interval-due */
+  notDue: CrankPriority[];
+  computeUnits: number;
+  feeLamports: number;
+}
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/** CU for a KeeperCrank (+ optional AdvanceOraclePhase), and for the bundled oracle push */
+export const CRANK_COMPUTE_UNITS = 200_000;
+export const PRICE_PUSH_COMPUTE_UNITS = 30_000;
+export const PHASE_ADVANCE_COMPUTE_UNITS = 5_000;
+
+/** Base fee per signature */
+export const SIGNATURE_FEE_LAMPORTS = 5_000;
+
+/** Staleness ratio at which a market is cranked ahead of its interval */
+export const URGENT_STALENESS_RATIO = 0.5;
+
+/** Worst-account headroom below which a market is cranked ahead of its interval */
+export const URGENT_MARGIN_HEADROOM_BPS = 500;
+
+/** Risk snapshots older than this are ignored */
+const RISK_SNAPSHOT_MAX_AGE_MS = 120_000;
+
+const WEIGHT_STALENESS = 1_000;
+const WEIGHT_STALE_BONUS = 1_000;
+const WEIGHT_LIQUIDATION = 800;
+const WEIGHT_OPEN_INTEREST = 200;
+const WEIGHT_FUNDING = 150;
+const WEIGHT_PHASE = 100;
+const WEIGHT_WAIT = 50;
+/** Markets demoted after repeated failures keep a quarter of their score */
+const INACTIVE_SCORE_FACTOR = 0.25;
+
+// ---------------------------------------------------------------------------
+// Priority queue
+// ---------------------------------------------------------------------------
+
+/** Binary max-heap keyed by a numeric priority. */
+export class PriorityQueue<T> {
+  private heap: { item: T; priority: number }[] = [];
+
+  get size(): number {
+    return this.heap.length;
+  }
+
+  push(item: T, priority: number): void {
+    this.heap.push({ item, priority });
+    let i = this.heap.length - 1;
+    while (i > 0) {
+      const parent = (i - 1) >> 1;
+      if (this.heap[parent]!.priority >= this.heap[i]!.priority) break;
+      [this.heap[parent], this.heap[i]] = [this.heap[i]!, this.heap[parent]!];
+      i = parent;
+    }
+  }
+
+  pop(): T | undefined {
+    if (this.heap.length === 0) return undefined;
+    const top = this.heap[0]!;
+    const last = this.heap.pop()!;
+    if (this.heap.length > 0) {
+      this.heap[0] = last;
+      let i = 0;
+      for (;;) {
+        const l = 2 * i + 1;
+        const r = l + 1;
+        let max = i;
+        if (l < this.heap.length && this.heap[l]!.priority > this.heap[max]!.priority) max = l;
+        if (r < this.heap.length && this.heap[r]!.priority > this.heap[max]!.priority) max = r;
+        if (max === i) break;
+        [this.heap[max], this.heap[i]] = [this.heap[i]!, this.heap[max]!];
+        i = max;
+      }
+    }
+    return top.item;
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Scoring
+// ---------------------------------------------------------------------------
+
+/** Estimated compute units and fee (base + priority) for one market's crank tx. */
+export function estimateCrankCost(
+  pushesPrice: boolean,
+  phaseAdvanceDue: boolean,
+  priorityFeeMicroLamports: number,
+): { computeUnits: number; feeLamports: number } {
+  const computeUnits = CRANK_COMPUTE_UNITS
+    + (pushesPrice ? PRICE_PUSH_COMPUTE_UNITS : 0)
+    + (phaseAdvanceDue ? PHASE_ADVANCE_COMPUTE_UNITS : 0);
+  const feeLamports = SIGNATURE_FEE_LAMPORTS + Math.ceil((computeUnits * priorityFeeMicroLamports) / 1_000_000);
+  return { computeUnits, feeLamports };
+}
+
+/**
+ * Score one market's crank urgency. `maxOpenInterest` is the largest OI among
+ * this cycle's candidates, so OI only ranks markets against each other.
+ */
+export function scoreCrankCandidate(
+  candidate: CrankCandidate,
+  opts: CrankPlanOptions,
+  maxOpenInterest: bigint,
+): CrankPriority {
+  const now = opts.now ?? Date.now();
+  const { config } = candidate.market;
+  const risk = candidate.risk && now - candidate.risk.observedAt <= RISK_SNAPSHOT_MAX_AGE_MS
+    ? candidate.risk
+    : undefined;
+  const engine = risk?.engine ?? candidate.market.engine;
+  const reasons: string[] = [];
+  let score = 0;
+
+  // Staleness
+  let lastCrankSlot = engine.lastCrankSlot;
+  if (candidate.lastCrankSlot !== undefined && candidate.lastCrankSlot > lastCrankSlot) {
+    lastCrankSlot = candidate.lastCrankSlot;
+  }
+  let stalenessRatio = 0;
+  if (engine.maxCrankStalenessSlots > 0n) {
+    const elapsed = opts.currentSlot > lastCrankSlot ? opts.currentSlot - lastCrankSlot : 0n;
+    stalenessRatio = Number(elapsed) / Number(engine.maxCrankStalenessSlots);
+    score += WEIGHT_STALENESS * Math.min(stalenessRatio, 2);
+    if (stalenessRatio >= 1) {
+      score += WEIGHT_STALE_BONUS;
+      reasons.push("stale");
+    } else if (stalenessRatio >= URGENT_STALENESS_RATIO) {
+      reasons.push("staling");
+    }
+  }
+
+  // Liquidation risk
+  const headroom = risk?.worstMarginHeadroomBps ?? null;
+  if (headroom !== null) {
+    score += WEIGHT_LIQUIDATION * Math.max(0, Math.min(1, 1 - headroom / (2 * URGENT_MARGIN_HEADROOM_BPS)));
+    if (headroom < URGENT_MARGIN_HEADROOM_BPS) reasons.push(headroom <= 0 ? "liquidatable" : "near-liquidation");
+  }
+
+  // Open interest
+  if (maxOpenInterest > 0n && engine.totalOpenInterest > 0n) {
+    score += WEIGHT_OPEN_INTEREST * (Number((engine.totalOpenInterest * 10_000n) / maxOpenInterest) / 10_000);
+  }
+
+  // Funding settlement
+  const fundingDue = config.fundingSettlementIntervalSlots > 0n
+    && engine.totalOpenInterest > 0n
+    && opts.currentSlot - engine.lastFundingSlot >= config.fundingSettlementIntervalSlots;
+  if (fundingDue) {
+    score += WEIGHT_FUNDING;
+    reasons.push("funding-due");
+  }
+
+  // Oracle phase advance (slabs predating PERC-622 have no creation slot)
+  const [, phaseAdvanceDue] = config.marketCreatedSlot > 0n
+    ? checkPhaseTransition(
+      opts.currentSlot,
+      config.marketCreatedSlot,
+      config.oraclePhase,
+      config.cumulativeVolumeE6,
+      config.phase2DeltaSlots,
+      false,
+    )
+    : [config.oraclePhase, false];
+  if (phaseAdvanceDue) {
+    score += WEIGHT_PHASE;
+    reasons.push("phase-advance-due");
+  }
+
+  // Time waited, so idle markets still rotate through
+  const interval = candidate.isActive ? opts.intervalMs : opts.inactiveIntervalMs;
+  const waited = now - candidate.lastCrankTime;
+  score += WEIGHT_WAIT * Math.min(1, waited / interval);
+
+  if (!candidate.isActive) score *= INACTIVE_SCORE_FACTOR;
+
+  const urgent = stalenessRatio >= URGENT_STALENESS_RATIO
+    || (headroom !== null && headroom < URGENT_MARGIN_HEADROOM_BPS && engine.totalOpenInterest > 0n)
+    || fundingDue
+    || phaseAdvanceDue;
+
+  return {
+    slabAddress: candidate.slabAddress,
+    score,
+    stalenessRatio,
+    fundingDue,
+    phaseAdvanceDue,
+    urgent,
+    intervalDue: waited >= interval,
+    ...estimateCrankCost(candidate.pushesPrice, phaseAdvanceDue, opts.priorityFeeMicroLamports),
+    reasons,
+  };
+}
+
+// ---------------------------------------------------------------------------
+// Planning
+// ---------------------------------------------------------------------------
+
+/**
+ * Pick this cycle's cranks: every due market goes into a priority queue by
+ * score and is popped until the CU or fee budget runs out. A market that
+ * doesn't fit is deferred but cheaper ones behind it may still be taken. The
+ * top market is always taken, so a budget smaller than one crank can't stall
+ * the keeper.
+ */
+export function planCrankCycle(candidates: CrankCandidate[], opts: CrankPlanOptions): CrankPlan {
+  let maxOpenInterest = 0n;
+  for (const c of candidates) {
+    const oi = (c.risk?.engine ?? c.market.engine).totalOpenInterest;
+    if (oi > maxOpenInterest) maxOpenInterest = oi;
+  }
+
+  const queue = new PriorityQueue<CrankPriority>();
+  const notDue: CrankPriority[] = [];
+  for (const c of candidates) {
+    const p = scoreCrankCandidate(c, opts, maxOpenInterest);
+    if (p.urgent || p.intervalDue) queue.push(p, p.score);
+    else notDue.push(p);
+  }
+
+  const selected: CrankPriority[] = [];
+  const deferred: CrankPriority[] = [];
+  let computeUnits = 0;
+  let feeLamports = 0;
+  for (let p = queue.pop(); p; p = queue.pop()) {
+    const fits = computeUnits + p.computeUnits <= opts.budget.maxComputeUnits
+      && feeLamports + p.feeLamports <= opts.budget.maxFeeLamports;
+    if (fits || selected.length === 0) {
+      selected.push(p);
+      computeUnits += p.computeUnits;
+      feeLamports += p.feeLamports;
+    } else {
+      deferred.push(p);
+    }
+  }
+
+  return { selected, deferred, notDue, computeUnits, feeLamports };
+}
@@ -1,13 +1,17 @@
-import { PublicKey, SYSVAR_CLOCK_PUBKEY } from "@solana/web3.js";
+import { PublicKey, SYSVAR_CLOCK_PUBKEY, type Connection } from "@solana/web3.js";
 import {
   discoverMarkets,
   encodeKeeperCrank,
   encodePushOraclePrice,
+  encodeAdvanceOraclePhase,
   buildAccountMetas,
   buildIx,
   derivePythPushOraclePDA,
+  parsePythPriceUpdateV2,
+  checkOracleFreshness,
   ACCOUNTS_KEEPER_CRANK,
   ACCOUNTS_PUSH_ORACLE_PRICE,
+  ACCOUNTS_ADVANCE_ORACLE_PHASE,
   fetchSlab,
   parseHeader,
   parseConfig,
@@ -15,8 +19,11 @@ import {
   parseParams,
   type DiscoveredMarket,
 } from "@percolator/sdk";
-import { config, getConnection, getFallbackConnection, loadKeypair, sendWithRetry, sendWithRetryKeeper, rateLimitedCall, eventBus, createLogger, sendCriticalAlert } from "@percolator/shared";
+import { config, getConnection, getFallbackConnection, loadKeypair, sendWithRetry, sendWithRetryKeeper, getRecentPriorityFees, rateLimitedCall, eventBus, createLogger, sendCriticalAlert } from "@percolator/shared";
 import { OracleService } from "./oracle.js";
+import { planCrankCycle, type CrankPlan, type MarketRiskSnapshot } from "./crank-scheduler.js";
+import type { KeeperCoordinator } from "./coordination.js";
+import type { ShadowRecorder } from "./shadow.js";
 
 const logger = createLogger("keeper:crank");
 
@@ -42,6 +49,8 @@ interface MarketCrankState {
    * This field stores the original mainnet CA so Jupiter/DexScreener lookups use the right address.
    */
   mainnetCA?: string;
+  /** Cycle slot of our last successful crank (approximate landing slot) */
+  lastCrankSlot?: bigint;
 }
 
 /** Process items in batches with delay between batches.
@@ -87,6 +96,14 @@ export class CrankService {
   private readonly discoveryIntervalMs: number;
   private readonly oracleService: OracleService;
   private lastCycleResult = { success: 0, failed: 0, skipped: 0 };
+  private lastPlan: CrankPlan | null = null;
+  private currentSlot: bigint | null = null;
+  /** Fresher engine state + worst-account headroom, from the liquidation scanner */
+  private riskSource: ((slabAddress: string) => MarketRiskSnapshot | undefined) | null = null;
+  /** Multi-keeper sharding; without one this keeper owns every market */
+  private coordinator: KeeperCoordinator | null = null;
+  /** Shadow mode: simulate instead of sending */
+  private shadow: ShadowRecorder | null = null;
   private lastDiscoveryTime = 0;
   // BC1: Signature replay protection
   private recentSignatures = new Map<string, number>(); // signature -> timestamp
@@ -105,6 +122,36 @@ export class CrankService {
     return this._isRunning;
   }
 
+  /** Share markets with peer keepers: only owned markets are cranked. */
+  setCoordinator(coordinator: KeeperCoordinator): void {
+    this.coordinator = coordinator;
+  }
+
+  /** Shadow mode: crank transactions are simulated and recorded, never sent. */
+  setShadow(recorder: ShadowRecorder): void {
+    this.shadow = recorder;
+  }
+
+  /** Whether this keeper's shard includes the market. */
+  owns(slabAddress: string): boolean {
+    return this.coordinator ? this.coordinator.owns(slabAddress) : true;
+  }
+
+  /** Tracked markets in this keeper's shard (for the liquidation scanner). */
+  getOwnedMarkets(): Map<string, MarketCrankState> {
+    if (!this.coordinator) return this.markets;
+    const owned = new Map<string, MarketCrankState>();
+    for (const [key, state] of this.markets) {
+      if (this.owns(key)) owned.set(key, state);
+    }
+    return owned;
+  }
+
+  /** Feed the scheduler per-market risk (see LiquidationService.getMarketRisk). */
+  setRiskSource(source: (slabAddress: string) => MarketRiskSnapshot | undefined): void {
+    this.riskSource = source;
+  }
+
   async discover(): Promise<DiscoveredMarket[]> {
     const programIds = config.allProgramIds;
     logger.info("Discovering markets", { programCount: programIds.length });
@@ -192,13 +239,46 @@ export class CrankService {
     return !market.config.oracleAuthority.equals(PublicKey.default);
   }
 
-  /** Check if a market is due for cranking based on activity */
-  private isDue(state: MarketCrankState): boolean {
-    const interval = state.isActive ? this.intervalMs : this.inactiveIntervalMs;
-    return Date.now() - state.lastCrankTime >= interval;
+  /**
+   * Pre-check a Pyth-pinned market's PriceUpdateV2 against its
+   * `maxStalenessSlots` (seconds for Pyth pull) and `confFilterBps`.
+   * Returns the rejection reason, or null when the crank should go ahead —
+   * including when the account can't be read or parsed, which is left for
+   * the program to reject.
+   */
+  private async checkPythOracle(
+    connection: Connection,
+    market: DiscoveredMarket,
+    oracleKey: PublicKey,
+  ): Promise<string | null> {
+    try {
+      const info = await connection.getAccountInfo(oracleKey);
+      if (!info) return null;
+      const price = parsePythPriceUpdateV2(new Uint8Array(info.data));
+      const check = checkOracleFreshness(price, {
+        maxStalenessSecs: market.config.maxStalenessSlots,
+        confFilterBps: market.config.confFilterBps,
+      });
+      return check.ok ? null : check.reason;
+    } catch (err) {
+      logger.debug("Pyth pre-check unavailable", {
+        slabAddress: market.slabAddress.toBase58(),
+        error: err instanceof Error ? err.message : String(err),
+      });
+      return null;
+    }
+  }
+
+  /** Whether this keeper pushes the oracle price in the market's crank tx */
+  private pushesPrice(market: DiscoveredMarket, keeper: PublicKey): boolean {
+    return this.isAdminOracle(market) && keeper.equals(market.config.oracleAuthority);
   }
 
-  async crankMarket(slabAddress: string): Promise<boolean> {
+  /**
+   * @param opts.advanceOraclePhase - bundle a permissionless AdvanceOraclePhase
+   *        (the scheduler sets this when a phase transition is due)
+   */
+  async crankMarket(slabAddress: string, opts: { advanceOraclePhase?: boolean } = {}): Promise<boolean> {
     const state = this.markets.get(slabAddress);
     if (!state) {
       logger.warn("Market not found", { slabAddress });
@@ -209,15 +289,19 @@ export class CrankService {
 
     try {
       const connection = getConnection();
-      const keypair = loadKeypair(process.env.CRANK_KEYPAIR!);
+      // A shadow keeper builds as the live wallet it shadows and holds no key
+      const keypair = this.shadow ? null : loadKeypair(process.env.CRANK_KEYPAIR!);
+      const keeper = this.shadow?.payer ?? keypair!.publicKey;
       const programId = market.programId;
 
       // PERC-204: Build all instructions into a single transaction bundle
       const instructions = [];
 
       // PERC-204: Bundle oracle price push with crank tx (eliminates separate oracle tx round-trip)
-      // Only push if we are the oracle authority for this market
-      if (this.isAdminOracle(market) && keypair.publicKey.equals(market.config.oracleAuthority)) {
+      // Only push if we are the oracle authority for this market — and, with
+      // peer keepers, the market's elected oracle leader
+      const oracleLeader = !this.coordinator || await this.coordinator.acquireOracleLeadership(slabAddress);
+      if (this.pushesPrice(market, keeper) && oracleLeader) {
         try {
           // PERC-465: Use mainnetCA if available (devnet mirror mint markets), else collateralMint.
           // Fresh devnet mints have no DEX liquidity so Jupiter/DexScreener lookups fail for them.
@@ -229,9 +313,15 @@ export class CrankService {
               timestamp: BigInt(Math.floor(Date.now() / 1000)),
             });
             const pushKeys = buildAccountMetas(ACCOUNTS_PUSH_ORACLE_PRICE, [
-              keypair.publicKey, market.slabAddress,
+              keeper, market.slabAddress,
             ]);
             instructions.push(buildIx({ programId, keys: pushKeys, data: pushData }));
+            logger.debug("Bundling oracle price", {
+              slabAddress,
+              priceE6: priceEntry.priceE6.toString(),
+              source: priceEntry.source,
+              provenance: priceEntry.provenance?.sources,
+            });
           }
         } catch (priceErr) {
           // Non-fatal: price fetch failed, crank will still run with existing on-chain price
@@ -249,18 +339,34 @@ export class CrankService {
         const feedHex = Array.from(market.config.indexFeedId.toBytes())
           .map(b => b.toString(16).padStart(2, "0")).join("");
         oracleKey = derivePythPushOraclePDA(feedHex)[0];
+
+        // Don't burn a crank tx on a Pyth price the program will reject anyway
+        const rejection = await this.checkPythOracle(connection, market, oracleKey);
+        if (rejection) {
+          logger.warn("Crank skipped — Pyth price rejected by market limits", { slabAddress, reason: rejection });
+          eventBus.publish("crank.stale", slabAddress, { oracle: oracleKey.toBase58(), reason: rejection });
+          return false;
+        }
       }
 
       const crankKeys = buildAccountMetas(ACCOUNTS_KEEPER_CRANK, [
-        keypair.publicKey,
+        keeper,
         market.slabAddress,
         SYSVAR_CLOCK_PUBKEY,
         oracleKey,
       ]);
       instructions.push(buildIx({ programId, keys: crankKeys, data: crankData }));
 
+      // PERC-622: phase advance rides along with the crank once it's due
+      if (opts.advanceOraclePhase) {
+        const phaseKeys = buildAccountMetas(ACCOUNTS_ADVANCE_ORACLE_PHASE, [market.slabAddress]);
+        instructions.push(buildIx({ programId, keys: phaseKeys, data: encodeAdvanceOraclePhase() }));
+      }
+
       // PERC-204: Use keeper-optimized send (skipPreflight + multi-RPC + tight CU)
-      const sig = await sendWithRetryKeeper(connection, instructions, [keypair]);
+      const sig = this.shadow
+        ? await this.shadow.simulate(connection, instructions)
+        : await sendWithRetryKeeper(connection, instructions, [keypair!]);
 
       // BC1: Track signature to prevent replay attacks
       const now = Date.now();
@@ -273,6 +379,7 @@ export class CrankService {
       }
 
       state.lastCrankTime = Date.now();
+      if (this.currentSlot !== null) state.lastCrankSlot = this.currentSlot;
       state.successCount++;
       state.consecutiveFailures = 0;
       state.isActive = true;
@@ -331,70 +438,100 @@ export class CrankService {
     }
   }
 
+  /**
+   * Crank the markets the scheduler ranks most urgent, within the per-cycle
+   * compute-unit and fee budget (see crank-scheduler.ts).
+   */
   async crankAll(): Promise<{ success: number; failed: number; skipped: number }> {
-  let success = 0;
-  let failed = 0;
+    let success = 0;
+    let failed = 0;
+    let skippedPermanent = 0;
+    let skippedFailures = 0;
+    let skippedPeer = 0;
 
-  // NEW: split skipped into categories
-  let skippedPermanent = 0;
-  let skippedFailures = 0;
-  let skippedNotDue = 0;
+    const MAX_CONSECUTIVE_FAILURES = 10;
 
-  const MAX_CONSECUTIVE_FAILURES = 10;
-
-  const toCrank: string[] = [];
-
-  for (const [slabAddress, state] of this.markets) {
-    if (state.permanentlySkipped) {
-      skippedPermanent++;
-      continue;
-    }
-    if (state.consecutiveFailures > MAX_CONSECUTIVE_FAILURES) {
-      skippedFailures++;
-      continue;
-    }
-    if (!this.isDue(state)) {
-      skippedNotDue++;
-      continue;
+    const connection = getConnection();
+    let keeper: PublicKey | null = null;
+    try {
+      keeper = this.shadow?.payer ?? loadKeypair(process.env.CRANK_KEYPAIR!).publicKey;
+    } catch { /* surfaced per market by crankMarket */ }
+
+    // One slot + fee read per cycle; without a slot the plan falls back to intervals only
+    const [slotResult, fees] = await Promise.all([
+      connection.getSlot("confirmed").then(BigInt).catch(() => null),
+      getRecentPriorityFees(connection),
+    ]);
+    this.currentSlot = slotResult;
+
+    const candidates = [];
+    for (const [slabAddress, state] of this.markets) {
+      if (!this.owns(slabAddress)) {
+        skippedPeer++;
+        continue;
+      }
+      if (state.permanentlySkipped) {
+        skippedPermanent++;
+        continue;
+      }
+      if (state.consecutiveFailures > MAX_CONSECUTIVE_FAILURES) {
+        skippedFailures++;
+        continue;
+      }
+      candidates.push({
+        slabAddress,
+        market: state.market,
+        lastCrankTime: state.lastCrankTime,
+        lastCrankSlot: state.lastCrankSlot,
+        isActive: state.isActive,
+        pushesPrice: keeper !== null && this.pushesPrice(state.market, keeper),
+        risk: this.riskSource?.(slabAddress),
+      });
     }
-    toCrank.push(slabAddress);
-  }
 
-  // NEW: meaningful accounting check
-  const skipped = skippedPermanent + skippedFailures + skippedNotDue;
-  const total = this.markets.size;
-  const accounted = toCrank.length + skipped;
-
-  if (accounted !== total) {
-    logger.warn("Crank accounting mismatch", {
-      totalMarkets: total,
-      toCrank: toCrank.length,
-      skipped,
-      skippedPermanent,
-      skippedFailures,
-      skippedNotDue,
+    const plan = planCrankCycle(candidates, {
+      currentSlot: slotResult ?? 0n,
+      budget: { maxComputeUnits: config.crankCuBudget, maxFeeLamports: config.crankFeeBudgetLamports },
+      priorityFeeMicroLamports: fees.priorityFeeMicroLamports,
+      intervalMs: this.intervalMs,
+      inactiveIntervalMs: this.inactiveIntervalMs,
     });
-  }
+    this.lastPlan = plan;
+
+    const skipped = skippedPeer + skippedPermanent + skippedFailures + plan.notDue.length + plan.deferred.length;
+    if (plan.deferred.length > 0) {
+      logger.warn("Crank budget exhausted — deferring markets", {
+        selected: plan.selected.length,
+        deferred: plan.deferred.length,
+        deferredUrgent: plan.deferred.filter((p) => p.urgent).length,
+        computeUnits: plan.computeUnits,
+        feeLamports: plan.feeLamports,
+      });
+    }
+    for (const p of plan.selected) {
+      if (p.reasons.length > 0) {
+        logger.debug("Urgent crank", { slabAddress: p.slabAddress, score: Math.round(p.score), reasons: p.reasons });
+      }
+    }
 
-    // PERC-204: Full parallel fan-out — all market cranks are independent transactions,
-    // submit them all simultaneously instead of in sequential batches.
-    // Each market gets its own transaction with independent nonce/blockhash.
-    // The Solana network de-dupes by signature, so parallel submission is safe.
-    const PARALLEL_CONCURRENCY = 10; // Cap concurrency to avoid rate limit storms
+    // PERC-204: market cranks are independent transactions, fanned out in
+    // parallel batches in priority order (most urgent go out first).
+    const concurrency = config.crankConcurrency;
+    const phaseDue = new Set(plan.selected.filter((p) => p.phaseAdvanceDue).map((p) => p.slabAddress));
+    const toCrank = plan.selected.map((p) => p.slabAddress);
 
-    // Process in parallel batches (larger batches, no delay between)
-    const batchResult = await processBatched(toCrank, PARALLEL_CONCURRENCY, 500, async (slabAddress) => {
-      const ok = await this.crankMarket(slabAddress);
+    const batchResult = await processBatched(toCrank, concurrency, 500, async (slabAddress) => {
+      const ok = await this.crankMarket(slabAddress, { advanceOraclePhase: phaseDue.has(slabAddress) });
       if (ok) success++;
       else failed++;
     });
 
     // BM7: Log detailed error summary if any failed
     if (batchResult.failed > 0) {
-      logger.error("Parallel crank batch completed with errors", { 
+      logger.error("Parallel crank batch completed with errors", {
         failedCount: batchResult.failed,
         successCount: success,
-        parallelism: PARALLEL_CONCURRENCY,
+        parallelism: concurrency,
       });
       for (const [slab, error] of batchResult.errors) {
         logger.error("Batch error detail", { slabAddress: slab, error: error.message });
@@ -466,6 +603,11 @@ export class CrankService {
 
       logger.info("Hot-registered new market", { slabAddress, programId: programId.toBase58() });
 
+      if (!this.owns(slabAddress)) {
+        // A peer owns it and will pick it up on its next discovery
+        return { success: true, message: "Market registered; cranked by a peer keeper" };
+      }
+
       // Trigger immediate oracle push + crank so price is live within seconds
       await this.crankMarket(slabAddress);
 
@@ -538,6 +680,11 @@ export class CrankService {
     return this.lastCycleResult;
   }
 
+  /** Last cycle's scheduling decisions: selected, deferred by budget, not due */
+  getLastPlan(): CrankPlan | null {
+    return this.lastPlan;
+  }
+
   getMarkets(): Map<string, MarketCrankState> {
     return this.markets;
   }
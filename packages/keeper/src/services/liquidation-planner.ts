@@ -0,0 +1,243 @@
+/**
+ * Liquidation planner — decides which liquidations are worth a transaction,
+ * in what order, and how many share one.
+ *
+ *   - Outcome: predicts whether the engine will partially or fully close a
+ *     position under the V1 rules (`liquidationBufferBps`, `minLiquidationAbs`,
+ *     `last_partial_liquidation_slot`), and so how much notional is closed
+ *   - Value: the fee the liquidation earns — `liquidationFeeBps` of the closed
+ *     notional, capped by `liquidationFeeCap` and by what equity is left —
+ *     against the compute and priority fee it adds to a transaction. Only
+ *     with the opt-in profit gate are batches that don't pay held back
+ *   - Order: a market's candidates are ranked by value at risk (how far equity
+ *     has fallen below maintenance margin), so accounts nearest bad debt go first
+ *   - Batching: a market's liquidations share one oracle push + crank and are
+ *     packed several to a transaction while the compute budget allows
+ *
+ * Every liquidatable account is sent by default. Underwater accounts
+ * (equity ≤ 0) are sent even with the profit gate on: every slot they stay
+ * open grows the bad debt the insurance fund absorbs.
+ */
+
+import type { AccountHealth, RiskParams } from "@percolator/sdk";
+import { CRANK_COMPUTE_UNITS, PRICE_PUSH_COMPUTE_UNITS, SIGNATURE_FEE_LAMPORTS } from "./crank-scheduler.js";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+export type LiquidationOutcome = "partial" | "full";
+
+/** Why the engine is expected to close the whole position */
+export type FullCloseReason =
+  | "underwater"        // equity ≤ 0
+  | "no-buffer"         // liquidationBufferBps = 0 (V0 params): partials disabled
+  | "partial-this-slot" // already partially liquidated this slot
+  | "buffer-unreachable" // no partial close restores maintenance + buffer
+  | "below-min";        // the remainder would fall under minLiquidationAbs
+
+export interface LiquidationPrediction {
+  outcome: LiquidationOutcome;
+  /** Notional the engine is expected to close (collateral units) */
+  closeNotional: bigint;
+  /** Fee on the closed notional, after the cap and limited to remaining equity */
+  expectedFee: bigint;
+  feeCapped: boolean;
+  fullReason?: FullCloseReason;
+}
+
+export type LiquidationRiskParams = Pick<
+  RiskParams,
+  "maintenanceMarginBps" | "liquidationFeeBps" | "liquidationFeeCap" | "liquidationBufferBps" | "minLiquidationAbs"
+>;
+
+/** What the planner needs from a scanned candidate */
+export interface PlannableLiquidation {
+  accountIdx: number;
+  /** maintenance margin − equity (collateral units); larger is closer to bad debt */
+  valueAtRisk: bigint;
+  prediction: LiquidationPrediction;
+}
+
+export interface MarketLiquidations<T extends PlannableLiquidation> {
+  slabAddress: string;
+  candidates: T[];
+  /** Whether the liquidation tx also pushes the oracle price */
+  pushesPrice: boolean;
+  /** Lamports per collateral base unit; null when the collateral can't be valued */
+  lamportsPerCollateralUnit: number | null;
+}
+
+export interface LiquidationPlanOptions {
+  priorityFeeMicroLamports: number;
+  /** Liquidations per transaction, before the compute limit */
+  maxPerTx: number;
+  /** Hold back batches whose expected fees don't cover their cost (off by default) */
+  profitGate?: boolean;
+}
+
+export interface LiquidationBatch<T extends PlannableLiquidation> {
+  slabAddress: string;
+  /** Highest value at risk first */
+  candidates: T[];
+  computeUnits: number;
+  costLamports: number;
+  /** Expected fees in lamports; null when the collateral couldn't be valued */
+  expectedFeeLamports: number | null;
+  /** Holds an underwater account — sent whatever it costs */
+  underwater: boolean;
+}
+
+export interface LiquidationPlan<T extends PlannableLiquidation> {
+  /** Batches to send: underwater ones first, then in market order */
+  batches: LiquidationBatch<T>[];
+  /** Batches held back by the profit gate; retried next scan */
+  unprofitable: LiquidationBatch<T>[];
+}
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/** Estimated CU per LiquidateAtOracle instruction */
+export const LIQUIDATE_COMPUTE_UNITS = 80_000;
+
+/** Solana per-transaction compute limit */
+export const MAX_TX_COMPUTE_UNITS = 1_400_000;
+
+// ---------------------------------------------------------------------------
+// Prediction
+// ---------------------------------------------------------------------------
+
+function ceilDiv(a: bigint, b: bigint): bigint {
+  return (a + b - 1n) / b;
+}
+
+function liquidationFee(
+  closeNotional: bigint,
+  equity: bigint,
+  params: LiquidationRiskParams,
+): { expectedFee: bigint; feeCapped: boolean } {
+  let fee = (closeNotional * params.liquidationFeeBps) / 10_000n;
+  const feeCapped = params.liquidationFeeCap > 0n && fee > params.liquidationFeeCap;
+  if (feeCapped) fee = params.liquidationFeeCap;
+  // The fee comes out of what equity is left, like the rest of the close
+  const available = equity > 0n ? equity : 0n;
+  return { expectedFee: fee < available ? fee : available, feeCapped };
+}
+
+/**
+ * Predict how the engine will liquidate an account. A partial close takes
+ * just enough notional to bring equity back to maintenance + buffer:
+ *
+ *   E − c·f ≥ (N − c)·(m + b)   ⇒   c ≥ (N·(m + b) − E) / (m + b − f)
+ *
+ * (all in bps; the fee cap is ignored here, which only over-estimates c).
+ * The position is closed in full instead when the account is underwater,
+ * partials are off, it was already partially liquidated this slot, or the
+ * remainder would be below `minLiquidationAbs`.
+ */
+export function predictLiquidation(
+  health: Pick<AccountHealth, "equity" | "notional">,
+  params: LiquidationRiskParams,
+  currentSlot: bigint,
+  lastPartialLiquidationSlot?: bigint,
+): LiquidationPrediction {
+  const { equity, notional } = health;
+  const full = (fullReason: FullCloseReason): LiquidationPrediction => ({
+    outcome: "full",
+    closeNotional: notional,
+    ...liquidationFee(notional, equity, params),
+    fullReason,
+  });
+
+  if (equity <= 0n) return full("underwater");
+  if (params.liquidationBufferBps === 0n) return full("no-buffer");
+  if (lastPartialLiquidationSlot !== undefined && lastPartialLiquidationSlot > 0n
+    && lastPartialLiquidationSlot >= currentSlot) {
+    return full("partial-this-slot");
+  }
+
+  const targetBps = params.maintenanceMarginBps + params.liquidationBufferBps;
+  if (targetBps <= params.liquidationFeeBps) return full("buffer-unreachable");
+  const deficit = notional * targetBps - equity * 10_000n;
+  const closeNotional = deficit > 0n ? ceilDiv(deficit, targetBps - params.liquidationFeeBps) : 1n;
+  if (closeNotional >= notional) return full("buffer-unreachable");
+  if (notional - closeNotional < params.minLiquidationAbs) return full("below-min");
+
+  return { outcome: "partial", closeNotional, ...liquidationFee(closeNotional, equity, params) };
+}
+
+// ---------------------------------------------------------------------------
+// Planning
+// ---------------------------------------------------------------------------
+
+/** Estimated compute units and fee (base + priority) for one liquidation tx. */
+export function estimateLiquidationCost(
+  liquidations: number,
+  pushesPrice: boolean,
+  priorityFeeMicroLamports: number,
+): { computeUnits: number; costLamports: number } {
+  const computeUnits = CRANK_COMPUTE_UNITS
+    + (pushesPrice ? PRICE_PUSH_COMPUTE_UNITS : 0)
+    + liquidations * LIQUIDATE_COMPUTE_UNITS;
+  const costLamports = SIGNATURE_FEE_LAMPORTS + Math.ceil((computeUnits * priorityFeeMicroLamports) / 1_000_000);
+  return { computeUnits, costLamports };
+}
+
+function byValueAtRisk(a: PlannableLiquidation, b: PlannableLiquidation): number {
+  return a.valueAtRisk === b.valueAtRisk ? 0 : a.valueAtRisk > b.valueAtRisk ? -1 : 1;
+}
+
+/**
+ * Plan one scan's liquidations: rank each market's candidates by value at
+ * risk and pack them into transactions up to `maxPerTx` and the compute limit.
+ * With `profitGate`, batches whose expected fees don't cover their cost are
+ * held back; a batch holding an underwater account, or on collateral that
+ * can't be valued, never is.
+ */
+export function planLiquidations<T extends PlannableLiquidation>(
+  markets: MarketLiquidations<T>[],
+  opts: LiquidationPlanOptions,
+): LiquidationPlan<T> {
+  const overhead = estimateLiquidationCost(0, false, 0).computeUnits;
+  const perTx = Math.max(1, Math.min(
+    opts.maxPerTx,
+    Math.floor((MAX_TX_COMPUTE_UNITS - overhead - PRICE_PUSH_COMPUTE_UNITS) / LIQUIDATE_COMPUTE_UNITS),
+  ));
+
+  const batches: LiquidationBatch<T>[] = [];
+  const unprofitable: LiquidationBatch<T>[] = [];
+  for (const market of markets) {
+    const ranked = [...market.candidates].sort(byValueAtRisk);
+    for (let i = 0; i < ranked.length; i += perTx) {
+      const candidates = ranked.slice(i, i + perTx);
+      const { computeUnits, costLamports } = estimateLiquidationCost(
+        candidates.length,
+        market.pushesPrice,
+        opts.priorityFeeMicroLamports,
+      );
+      const fees = candidates.reduce((sum, c) => sum + c.prediction.expectedFee, 0n);
+      const expectedFeeLamports = market.lamportsPerCollateralUnit === null
+        ? null
+        : Number(fees) * market.lamportsPerCollateralUnit;
+      const batch: LiquidationBatch<T> = {
+        slabAddress: market.slabAddress,
+        candidates,
+        computeUnits,
+        costLamports,
+        expectedFeeLamports,
+        underwater: candidates.some((c) => c.prediction.fullReason === "underwater"),
+      };
+      if (opts.profitGate && !batch.underwater && expectedFeeLamports !== null && expectedFeeLamports < costLamports) {
+        unprofitable.push(batch);
+      } else {
+        batches.push(batch);
+      }
+    }
+  }
+
+  // Stable: underwater batches first, otherwise keep market order
+  batches.sort((a, b) => Number(b.underwater) - Number(a.underwater));
+  return { batches, unprofitable };
+}
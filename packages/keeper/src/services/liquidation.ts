@@ -16,13 +16,38 @@ import {
   ACCOUNTS_KEEPER_CRANK,
   ACCOUNTS_PUSH_ORACLE_PRICE,
   derivePythPushOraclePDA,
+  computeAccountHealth,
   type DiscoveredMarket,
 } from "@percolator/sdk";
-import { config, getConnection, loadKeypair, sendWithRetry, sendWithRetryKeeper, pollSignatureStatus, getRecentPriorityFees, checkTransactionSize, eventBus, createLogger, sendWarningAlert, acquireToken, getFallbackConnection, backoffMs } from "@percolator/shared";
+import { config, getConnection, loadKeypair, sendWithRetry, sendWithRetryKeeper, pollSignatureStatus, getRecentPriorityFees, checkTransactionSize, eventBus, createLogger, sendWarningAlert, sendCriticalAlert, acquireToken, getFallbackConnection, backoffMs } from "@percolator/shared";
 import { OracleService } from "./oracle.js";
+import type { MarketRiskSnapshot } from "./crank-scheduler.js";
+import { planLiquidations, predictLiquidation, type LiquidationPrediction, type MarketLiquidations } from "./liquidation-planner.js";
+import { planAdl, type AdlPlan, type AdlPosition } from "./adl-planner.js";
+import type { ShadowRecorder } from "./shadow.js";
 
 const logger = createLogger("keeper:liquidation");
 
+/** Consecutive scans an account may be held back by the profit gate before an alert */
+const HELD_BACK_ALERT_SCANS = 3;
+
+/**
+ * Oracle account passed to crank, liquidate and force-close: the Pyth push
+ * PDA for the market's feed, or the slab itself for admin/Hyperp oracles.
+ */
+export function marketOracleAccount(market: DiscoveredMarket): PublicKey {
+  const feedHex = Array.from(market.config.indexFeedId.toBytes()).map(b => b.toString(16).padStart(2, "0")).join("");
+  return feedHex === "0".repeat(64) ? market.slabAddress : derivePythPushOraclePDA(feedHex)[0];
+}
+
+/** A market's latest ADL plan, with what's needed to build its instructions */
+export interface MarketAdlPlan {
+  plan: AdlPlan;
+  programId: string;
+  oracle: string;
+  observedAt: number;
+}
+
 /**
  * Rate-limited fetchSlab with automatic fallback to secondary RPC.
  * Retries up to 3 times with exponential backoff on rate-limit (429) or
@@ -59,10 +84,6 @@ async function fetchSlabWithRetry(
   throw lastErr;
 }
 
-// BL2: Extract magic numbers to named constants
-const PRICE_E6_DIVISOR = 1_000_000n; // Price precision divisor (6 decimals)
-const BPS_MULTIPLIER = 10_000n; // Basis points multiplier (100% = 10000 bps)
-
 /**
  * Oracle mode for a market.
  * - 'pyth-pinned': oracle_authority == [0;32] && index_feed_id != [0;32]
@@ -130,6 +151,10 @@ interface LiquidationCandidate {
   pnl: bigint;
   marginRatio: number;  // as percentage
   maintenanceMarginBps: bigint;
+  /** maintenance margin − equity: how far the account is past liquidation */
+  valueAtRisk: bigint;
+  /** Expected partial/full close and fee */
+  prediction: LiquidationPrediction;
 }
 
 export class LiquidationService {
@@ -150,16 +175,39 @@ export class LiquidationService {
   // PERC-484: Track markets that permanently fail with InvalidSlabLen (0x4).
   // These are test/corrupt markets with wrong slab size — skip them indefinitely.
   private readonly permanentlySkipped = new Set<string>();
+  /** Per-market engine state + worst margin headroom from the last scan, for the crank scheduler */
+  private readonly marketRisk = new Map<string, MarketRiskSnapshot>();
+  /** Lamports per collateral base unit; without a valuer, the profit gate never holds back */
+  private collateralValuer: (market: DiscoveredMarket) => Promise<number | null> = async () => null;
+  /** Slot read at the start of the current cycle, for partial-liquidation predictions */
+  private currentSlot: bigint | null = null;
+  /** Per-market deleveraging plan from the last scan; only triggered markets are kept */
+  private readonly adlPlans = new Map<string, MarketAdlPlan>();
+  /** Shadow mode: simulate instead of sending */
+  private shadow: ShadowRecorder | null = null;
+  /** Consecutive scans each liquidatable account (slab:idx) was held back by the profit gate */
+  private readonly heldBackScans = new Map<string, number>();
 
   constructor(oracleService: OracleService, intervalMs = 60_000) {
     this.oracleService = oracleService;
     this.intervalMs = intervalMs;
   }
 
+  /** Value collateral in lamports so expected fees can be weighed against tx cost. */
+  setCollateralValuer(valuer: (market: DiscoveredMarket) => Promise<number | null>): void {
+    this.collateralValuer = valuer;
+  }
+
+  /** Shadow mode: liquidation transactions are simulated and recorded, never sent. */
+  setShadow(recorder: ShadowRecorder): void {
+    this.shadow = recorder;
+  }
+
   /**
    * Scan a single market for undercollateralized accounts.
+   * `currentSlot` defaults to the slab's last engine slot.
    */
-  async scanMarket(market: DiscoveredMarket): Promise<LiquidationCandidate[]> {
+  async scanMarket(market: DiscoveredMarket, currentSlot?: bigint): Promise<LiquidationCandidate[]> {
     const slabAddress = market.slabAddress.toBase58();
 
     try {
@@ -215,6 +263,8 @@ export class LiquidationService {
       // Use bitmap to find actually-used account indices (not sequential iteration)
       // The bitmap can be sparse — e.g., accounts at indices 0, 5, 100
       const usedIndices = parseUsedIndices(data);
+      let worstMarginHeadroomBps: number | null = null;
+      const positions: AdlPosition[] = [];
 
       for (const i of usedIndices) {
         try {
@@ -224,67 +274,55 @@ export class LiquidationService {
           if (account.kind !== 0) continue;  // 0 = User
           if (account.positionSize === 0n) continue;  // No position
 
-          // Calculate margin health using mark-to-market PnL (not stale on-chain pnl)
-          // On-chain pnl is only updated during cranks; between cranks it can be stale
-          const notional = absBI(account.positionSize) * price / PRICE_E6_DIVISOR;
-          if (notional === 0n) continue;
-
-          // Compute mark PnL from live price instead of stale on-chain pnl
-          const entryPrice = account.entryPrice;
-          let markPnl = 0n;
-          if (entryPrice > 0n && price > 0n) {
-            const diff = account.positionSize > 0n
-              ? price - entryPrice    // long: profit when price goes up
-              : entryPrice - price;   // short: profit when price goes down
-            
-            const entryPrice = account.entryPrice;
-            let markPnl = 0n;
-            if (entryPrice > 0n && price > 0n) {
-               const diff = account.positionSize > 0n
-                 ? price - entryPrice
-                 : entryPrice - price;
-
-             // BigInt does not overflow—compute exactly.
-             markPnl = (diff * absBI(account.positionSize)) / price;
-            }
-          const equity = account.capital + markPnl;
-
-          // H4: If equity <= 0, definitely liquidatable (skip ratio calc)
-          if (equity <= 0n) {
-            candidates.push({
-              slabAddress,
-              accountIdx: i,
-              owner: account.owner.toBase58(),
-              positionSize: account.positionSize,
-              capital: account.capital,
-              pnl: markPnl,
-              marginRatio: equity <= 0n ? 0 : -1,
-              maintenanceMarginBps,
-            });
-            continue;
+          // Margin health from the live price — shared with the API and trade UI
+          // so every surface agrees on who is liquidatable.
+          const health = computeAccountHealth(account, engine, params, price);
+          if (health.notional === 0n) continue;
+          positions.push({
+            accountIdx: i,
+            owner: account.owner.toBase58(),
+            positionSize: account.positionSize,
+            equity: health.equity,
+            unrealizedPnl: health.unrealizedPnl,
+            notional: health.notional,
+          });
+          const headroomBps = Number((health.bufferToLiquidation * 10_000n) / health.notional);
+          if (worstMarginHeadroomBps === null || headroomBps < worstMarginHeadroomBps) {
+            worstMarginHeadroomBps = headroomBps;
           }
+          if (!health.liquidatable) continue;
 
-          const marginRatioBps = equity * BPS_MULTIPLIER / notional;
-
-          // If margin ratio < maintenance margin, this account is liquidatable
-          if (marginRatioBps < maintenanceMarginBps) {
-            candidates.push({
-              slabAddress,
-              accountIdx: i,
-              owner: account.owner.toBase58(),
-              positionSize: account.positionSize,
-              capital: account.capital,
-              pnl: markPnl,
-              marginRatio: Number(marginRatioBps) / 100,
-              maintenanceMarginBps,
-            });
-          }
+          candidates.push({
+            slabAddress,
+            accountIdx: i,
+            owner: account.owner.toBase58(),
+            positionSize: account.positionSize,
+            capital: account.capital,
+            pnl: health.unrealizedPnl,
+            // H4: non-positive equity reports a 0% ratio
+            marginRatio: health.equity <= 0n ? 0 : Number(health.marginRatioBps!) / 100,
+            maintenanceMarginBps,
+            valueAtRisk: -health.bufferToLiquidation,
+            prediction: predictLiquidation(
+              health,
+              params,
+              currentSlot ?? engine.currentSlot,
+              account.lastPartialLiquidationSlot,
+            ),
+          });
         } catch {
           // Skip accounts that fail to parse
           continue;
         }
       }
 
+      this.marketRisk.set(slabAddress, { engine, worstMarginHeadroomBps, observedAt: Date.now() });
+      // ADL bookkeeping must never hold up the liquidations themselves
+      try {
+        this.updateAdlPlan(market, planAdl(slabAddress, engine, positions));
+      } catch (err) {
+        logger.warn("ADL assessment failed", { slabAddress, error: err instanceof Error ? err.message : String(err) });
+      }
       return candidates;
     } catch (err) {
       logger.error("Market scan failed", {
@@ -304,26 +342,40 @@ export class LiquidationService {
     market: DiscoveredMarket,
     accountIdx: number,
   ): Promise<string | null> {
+    const [landed] = await this.liquidateBatch(market, [accountIdx]);
+    return landed?.signature ?? null;
+  }
+
+  /**
+   * Liquidate several accounts of one market in a single transaction:
+   * oracle push (if we're the authority) + crank, then one LiquidateAtOracle
+   * per account still liquidatable on a fresh read. If a multi-account
+   * transaction fails, each account is retried on its own so one bad target
+   * can't block the rest. Returns the transactions that landed.
+   */
+  async liquidateBatch(
+    market: DiscoveredMarket,
+    accountIdxs: number[],
+  ): Promise<{ signature: string; accountIdxs: number[] }[]> {
     const slabAddress = market.slabAddress;
     const isAdminOracle = !market.config.oracleAuthority.equals(PublicKey.default);
 
     try {
       const connection = getConnection();
-      const keypair = loadKeypair(process.env.CRANK_KEYPAIR!);
+      // A shadow keeper builds as the live wallet it shadows and holds no key
+      const keypair = this.shadow ? null : loadKeypair(process.env.CRANK_KEYPAIR!);
+      const keeper = this.shadow?.payer ?? keypair!.publicKey;
       const programId = market.programId;
 
-      // Build multi-instruction tx: push price → crank → liquidate
+      // Build multi-instruction tx: push price → crank → liquidate × N
       const instructions = [];
 
       // Determine oracle account for crank/liquidate
-      const feedIdBytes = market.config.indexFeedId.toBytes();
-      const feedHex = Array.from(feedIdBytes).map(b => b.toString(16).padStart(2, "0")).join("");
-      const isAllZeros = feedHex === "0".repeat(64);
-      const oracleAccount = isAllZeros ? slabAddress : derivePythPushOraclePDA(feedHex)[0];
+      const oracleAccount = marketOracleAccount(market);
 
       // 1. Push oracle price only if crank wallet IS the oracle authority
       // (user-owned oracle markets skip the push — user pushes manually)
-      if (isAdminOracle && market.config.oracleAuthority.equals(keypair.publicKey)) {
+      if (isAdminOracle && market.config.oracleAuthority.equals(keeper)) {
         const mint = market.config.collateralMint.toBase58();
         const priceEntry = await this.oracleService.fetchPrice(mint, slabAddress.toBase58());
         if (priceEntry) {
@@ -332,7 +384,7 @@ export class LiquidationService {
             timestamp: BigInt(Math.floor(Date.now() / 1000)),
           });
           const pushKeys = buildAccountMetas(ACCOUNTS_PUSH_ORACLE_PRICE, [
-            keypair.publicKey, slabAddress,
+            keeper, slabAddress,
           ]);
           instructions.push(buildIx({ programId, keys: pushKeys, data: pushData }));
         }
@@ -341,18 +393,12 @@ export class LiquidationService {
       // 2. Crank (make sure engine state is fresh)
       const crankData = encodeKeeperCrank({ callerIdx: 65535, allowPanic: false });
       const crankKeys = buildAccountMetas(ACCOUNTS_KEEPER_CRANK, [
-        keypair.publicKey, slabAddress, SYSVAR_CLOCK_PUBKEY, oracleAccount,
+        keeper, slabAddress, SYSVAR_CLOCK_PUBKEY, oracleAccount,
       ]);
       instructions.push(buildIx({ programId, keys: crankKeys, data: crankData }));
 
-      // 3. Liquidate
-      const liqData = encodeLiquidateAtOracle({ targetIdx: accountIdx });
-      const liqKeys = buildAccountMetas(ACCOUNTS_LIQUIDATE_AT_ORACLE, [
-        keypair.publicKey, slabAddress, SYSVAR_CLOCK_PUBKEY, oracleAccount,
-      ]);
-      instructions.push(buildIx({ programId, keys: liqKeys, data: liqData }));
-
-      // Bug 3: Re-read slab data and verify account before submitting
+      // Bug 3: Re-read slab data and verify each account before submitting
+      const targets: { accountIdx: number; prediction: LiquidationPrediction | null }[] = [];
       {
         const freshData = await fetchSlabWithRetry(slabAddress);
         const freshEngine = parseEngine(freshData);
@@ -361,54 +407,87 @@ export class LiquidationService {
 
         // Use bitmap to verify account is still active (not sequential numUsedAccounts)
         const freshUsed = parseUsedIndices(freshData);
-        if (!freshUsed.includes(accountIdx)) {
-          logger.warn("Race condition: account not in bitmap", { accountIndex: accountIdx, slabAddress: slabAddress.toBase58() });
-          return null;
-        }
-
-        const freshAccount = parseAccount(freshData, accountIdx);
-        // Owner is verified implicitly — the account at this index is what we'll liquidate
-
-        // Verify still undercollateralized
-        if (freshAccount.kind !== 0 || freshAccount.positionSize === 0n) {
-          logger.warn("Race condition: account no longer active", { accountIndex: accountIdx, slabAddress: slabAddress.toBase58() });
-          return null;
-        }
 
         // Use the same price source as scanMarket via shared helpers
         // (fixes bug where admin-oracle staleness fallback was missing here)
         const freshMode = detectOracleMode(freshCfg);
         const { price: freshPrice } = resolveMarketPrice(freshCfg, freshMode);
-        if (freshPrice > 0n) {
-          const notional = absBI(freshAccount.positionSize) * freshPrice / PRICE_E6_DIVISOR;
-          if (notional > 0n) {
-            // Use mark-to-market PnL for re-verification
-            const freshEntry = freshAccount.entryPrice;
-            let freshMarkPnl = 0n;
-            if (freshEntry > 0n && freshPrice > 0n) {
-              const diff = freshAccount.positionSize > 0n
-                ? freshPrice - freshEntry
-                : freshEntry - freshPrice;
-              freshMarkPnl = (diff * absBI(freshAccount.positionSize)) / freshPrice;
+
+        for (const accountIdx of accountIdxs) {
+          if (!freshUsed.includes(accountIdx)) {
+            logger.warn("Race condition: account not in bitmap", { accountIndex: accountIdx, slabAddress: slabAddress.toBase58() });
+            continue;
+          }
+
+          const freshAccount = parseAccount(freshData, accountIdx);
+          // Owner is verified implicitly — the account at this index is what we'll liquidate
+
+          // Verify still undercollateralized
+          if (freshAccount.kind !== 0 || freshAccount.positionSize === 0n) {
+            logger.warn("Race condition: account no longer active", { accountIndex: accountIdx, slabAddress: slabAddress.toBase58() });
+            continue;
+          }
+
+          let prediction: LiquidationPrediction | null = null;
+          if (freshPrice > 0n) {
+            const health = computeAccountHealth(freshAccount, freshEngine, freshParams, freshPrice);
+            if (health.notional > 0n && !health.liquidatable) {
+              logger.warn("Race condition: account no longer undercollateralized", {
+                accountIndex: accountIdx,
+                slabAddress: slabAddress.toBase58(),
+                marginRatioBps: Number(health.marginRatioBps),
+              });
+              continue;
             }
-            const equity = freshAccount.capital + freshMarkPnl;
-            if (equity > 0n) {
-              const marginRatioBps = equity * BPS_MULTIPLIER / notional;
-              if (marginRatioBps >= freshParams.maintenanceMarginBps) {
-                logger.warn("Race condition: account no longer undercollateralized", { accountIndex: accountIdx, slabAddress: slabAddress.toBase58(), marginRatioBps: Number(marginRatioBps) });
-                return null;
-              }
+            if (health.notional > 0n) {
+              prediction = predictLiquidation(
+                health,
+                freshParams,
+                this.currentSlot ?? freshEngine.currentSlot,
+                freshAccount.lastPartialLiquidationSlot,
+              );
             }
           }
+          targets.push({ accountIdx, prediction });
         }
       }
+      if (targets.length === 0) return [];
+
+      // 3. Liquidate
+      for (const { accountIdx } of targets) {
+        const liqData = encodeLiquidateAtOracle({ targetIdx: accountIdx });
+        const liqKeys = buildAccountMetas(ACCOUNTS_LIQUIDATE_AT_ORACLE, [
+          keeper, slabAddress, SYSVAR_CLOCK_PUBKEY, oracleAccount,
+        ]);
+        instructions.push(buildIx({ programId, keys: liqKeys, data: liqData }));
+      }
 
       // PERC-204: Use keeper-optimized send (skipPreflight + multi-RPC + tight CU)
       // Replaces manual tx building with sendWithRetryKeeper for:
       //   - skipPreflight=true (saves ~20-50ms)
       //   - Multi-RPC parallel broadcast (+20-40% landing rate)
       //   - Simulation-based tight CU limit (better queue position)
-      const sig = await sendWithRetryKeeper(connection, instructions, [keypair], 3);
+      let sig: string;
+      try {
+        sig = this.shadow
+          ? await this.shadow.simulate(connection, instructions)
+          : await sendWithRetryKeeper(connection, instructions, [keypair!], 3);
+      } catch (err) {
+        const errMsg = err instanceof Error ? err.message : String(err);
+        if (targets.length > 1 && !errMsg.includes("custom program error: 0x4")) {
+          logger.warn("Batched liquidation failed — retrying accounts individually", {
+            slabAddress: slabAddress.toBase58(),
+            accounts: targets.length,
+            error: errMsg,
+          });
+          const landed = [];
+          for (const { accountIdx } of targets) {
+            landed.push(...await this.liquidateBatch(market, [accountIdx]));
+          }
+          return landed;
+        }
+        throw err;
+      }
 
       // BC1: Track signature to prevent replay attacks
       const now = Date.now();
@@ -420,21 +499,31 @@ export class LiquidationService {
         }
       }
 
-      this.liquidationCount++;
-      eventBus.publish("liquidation.success", slabAddress.toBase58(), {
-        accountIdx,
-        signature: sig,
-      });
-      logger.info("Account liquidated", { accountIndex: accountIdx, slabAddress: slabAddress.toBase58(), signature: sig });
+      this.liquidationCount += targets.length;
+      for (const { accountIdx, prediction } of targets) {
+        eventBus.publish("liquidation.success", slabAddress.toBase58(), {
+          accountIdx,
+          signature: sig,
+          ...(prediction && {
+            outcome: prediction.outcome,
+            closeNotional: prediction.closeNotional.toString(),
+            expectedFee: prediction.expectedFee.toString(),
+          }),
+        });
+      }
+      const accountList = targets.map((t) => t.accountIdx).join(", ");
+      logger.info("Accounts liquidated", { accountIndexes: accountList, slabAddress: slabAddress.toBase58(), signature: sig });
       
-      // Send Discord alert for liquidation execution
-      await sendWarningAlert("Liquidation executed", [
-        { name: "Market", value: slabAddress.toBase58().slice(0, 8), inline: true },
-        { name: "Account Index", value: accountIdx.toString(), inline: true },
-        { name: "Signature", value: sig.slice(0, 12), inline: true },
-      ]);
+      // Send Discord alert for liquidation execution (a shadow keeper executes nothing)
+      if (!this.shadow) {
+        await sendWarningAlert("Liquidation executed", [
+          { name: "Market", value: slabAddress.toBase58().slice(0, 8), inline: true },
+          { name: targets.length > 1 ? "Account Indexes" : "Account Index", value: accountList, inline: true },
+          { name: "Signature", value: sig.slice(0, 12), inline: true },
+        ]);
+      }
       
-      return sig;
+      return [{ signature: sig, accountIdxs: targets.map((t) => t.accountIdx) }];
     } catch (err) {
       const errMsg = err instanceof Error ? err.message : String(err);
 
@@ -448,30 +537,97 @@ export class LiquidationService {
           "Fix: run `npx tsx scripts/reinit-slab.ts --slab <ADDRESS>` to recreate with correct size.",
           {
             slabAddress: slabAddress.toBase58(),
-            accountIdx,
+            accountIdxs,
             programId: market.programId.toBase58(),
           },
         );
-        return null;
+        return [];
       }
 
       logger.error("Liquidation failed", {
         error: errMsg,
         stack: err instanceof Error ? err.stack : undefined,
         slabAddress: slabAddress.toBase58(),
-        accountIdx,
+        accountIdxs,
         market: slabAddress.toBase58(),
         programId: market.programId.toBase58(),
       });
       
-      eventBus.publish("liquidation.failure", slabAddress.toBase58(), {
-        accountIdx,
-        error: errMsg,
-      });
-      return null;
+      for (const accountIdx of accountIdxs) {
+        eventBus.publish("liquidation.failure", slabAddress.toBase58(), {
+          accountIdx,
+          error: errMsg,
+        });
+      }
+      return [];
     }
   }
 
+  /** Latest scan's risk snapshot for a market, if it has been scanned. */
+  getMarketRisk(slabAddress: string): MarketRiskSnapshot | undefined {
+    return this.marketRisk.get(slabAddress);
+  }
+
+  /**
+   * Keep the market's ADL plan while it's triggered, alerting when it first
+   * triggers and when it clears. Plans are executed by an admin (see
+   * scripts/execute-adl-plan.ts) until native ADL ships.
+   */
+  private updateAdlPlan(market: DiscoveredMarket, plan: AdlPlan): void {
+    const slabAddress = market.slabAddress.toBase58();
+    const { assessment } = plan;
+    const previous = this.adlPlans.get(slabAddress);
+
+    if (assessment.triggers.length === 0) {
+      if (previous) {
+        this.adlPlans.delete(slabAddress);
+        logger.info("ADL condition cleared", { slabAddress });
+      }
+      return;
+    }
+
+    this.adlPlans.set(slabAddress, {
+      plan,
+      programId: market.programId.toBase58(),
+      oracle: marketOracleAccount(market).toBase58(),
+      observedAt: Date.now(),
+    });
+    if (previous) return;
+
+    logger.error("ADL triggered — insurance fund cannot cover bad debt", {
+      slabAddress,
+      triggers: assessment.triggers,
+      badDebt: assessment.badDebt.toString(),
+      insuranceAvailable: assessment.insuranceAvailable.toString(),
+      shortfall: assessment.shortfall.toString(),
+      side: plan.side,
+      selected: plan.selected.map((c) => c.accountIdx),
+      exhausted: plan.exhausted,
+    });
+    eventBus.publish("adl.triggered", slabAddress, {
+      triggers: assessment.triggers,
+      shortfall: assessment.shortfall.toString(),
+      side: plan.side,
+      accounts: plan.selected.length,
+    });
+    if (this.shadow) return; // the live keepers raise the alert
+    sendCriticalAlert("ADL plan ready — admin action required", [
+      { name: "Market", value: slabAddress.slice(0, 8), inline: true },
+      { name: "Trigger", value: assessment.triggers.join(", "), inline: true },
+      { name: "Shortfall", value: assessment.shortfall.toString(), inline: true },
+      { name: "Deleverage", value: `${plan.selected.length} ${plan.side ?? "-"} position(s)`, inline: true },
+    ]).catch(() => {});
+  }
+
+  getAdlPlan(slabAddress: string): MarketAdlPlan | undefined {
+    return this.adlPlans.get(slabAddress);
+  }
+
+  /** Markets currently needing deleveraging */
+  getAdlPlans(): Map<string, MarketAdlPlan> {
+    return this.adlPlans;
+  }
+
   /**
    * Scan all markets and liquidate any undercollateralized accounts.
    */
@@ -479,10 +635,22 @@ export class LiquidationService {
     scanned: number;
     candidates: number;
     liquidated: number;
+    unprofitable: number;
   }> {
     let scanned = 0;
     let candidateCount = 0;
     let liquidated = 0;
+    let unprofitable = 0;
+    const heldBack = new Set<string>();
+
+    // One slot + priority fee read per cycle, and only once there is something to liquidate
+    this.currentSlot = null;
+    const connection = getConnection();
+    let priorityFeeMicroLamports: number | null = null;
+    let keeper: PublicKey | null = null;
+    try {
+      keeper = this.shadow?.payer ?? loadKeypair(process.env.CRANK_KEYPAIR!).publicKey;
+    } catch { /* surfaced per batch by liquidateBatch */ }
 
     // Process markets in batches to avoid RPC rate-limit bursts.
     // Batch size of 10 keeps us well within Helius free-tier (100 req/10s).
@@ -505,6 +673,8 @@ export class LiquidationService {
         filteredBatch.map((state) => this.scanMarket(state.market)),
       );
 
+      const found: MarketLiquidations<LiquidationCandidate>[] = [];
+      const marketByAddress = new Map<string, DiscoveredMarket>();
       for (let j = 0; j < batchResults.length; j++) {
         scanned++;
         const result = batchResults[j]!;
@@ -514,11 +684,53 @@ export class LiquidationService {
         }
         const candidates = result.value;
         candidateCount += candidates.length;
+        if (candidates.length === 0) continue;
+
+        const market = filteredBatch[j]!.market;
+        const isAdminOracle = !market.config.oracleAuthority.equals(PublicKey.default);
+        marketByAddress.set(market.slabAddress.toBase58(), market);
+        found.push({
+          slabAddress: market.slabAddress.toBase58(),
+          candidates,
+          pushesPrice: isAdminOracle && keeper !== null && market.config.oracleAuthority.equals(keeper),
+          lamportsPerCollateralUnit: await this.collateralValuer(market).catch(() => null),
+        });
+      }
+
+      if (found.length > 0) {
+        if (priorityFeeMicroLamports === null) {
+          const [slot, fees] = await Promise.all([
+            connection.getSlot("confirmed").then(BigInt).catch(() => null),
+            getRecentPriorityFees(connection),
+          ]);
+          this.currentSlot = slot;
+          priorityFeeMicroLamports = fees.priorityFeeMicroLamports;
+        }
+        const plan = planLiquidations(found, {
+          priorityFeeMicroLamports,
+          maxPerTx: config.liquidationMaxPerTx,
+          profitGate: config.liquidationProfitGate,
+        });
+        for (const skipped of plan.unprofitable) {
+          unprofitable += skipped.candidates.length;
+          logger.warn("Liquidation held back: fees below cost", {
+            slabAddress: skipped.slabAddress,
+            accountIdxs: skipped.candidates.map((c) => c.accountIdx),
+            expectedFeeLamports: skipped.expectedFeeLamports,
+            costLamports: skipped.costLamports,
+          });
+          for (const c of skipped.candidates) {
+            const key = `${skipped.slabAddress}:${c.accountIdx}`;
+            heldBack.add(key);
+            this.noteHeldBack(key, skipped.slabAddress, c.accountIdx);
+          }
+        }
 
-        // Liquidations are sequential (each is a transaction)
-        for (const candidate of candidates) {
-          const sig = await this.liquidate(filteredBatch[j]!.market, candidate.accountIdx);
-          if (sig) liquidated++;
+        // Liquidations are sequential (each batch is a transaction)
+        for (const batch of plan.batches) {
+          const market = marketByAddress.get(batch.slabAddress)!;
+          const landed = await this.liquidateBatch(market, batch.candidates.map((c) => c.accountIdx));
+          for (const tx of landed) liquidated += tx.accountIdxs.length;
         }
       }
 
@@ -528,9 +740,26 @@ export class LiquidationService {
       }
     }
 
+    // An account's streak ends once it is sent or no longer liquidatable
+    for (const key of this.heldBackScans.keys()) {
+      if (!heldBack.has(key)) this.heldBackScans.delete(key);
+    }
+
     this.scanCount++;
     this.lastScanTime = Date.now();
-    return { scanned, candidates: candidateCount, liquidated };
+    return { scanned, candidates: candidateCount, liquidated, unprofitable };
+  }
+
+  /** Count a profit-gate hold-back; alert once the account stays held back across scans. */
+  private noteHeldBack(key: string, slabAddress: string, accountIdx: number): void {
+    const scans = (this.heldBackScans.get(key) ?? 0) + 1;
+    this.heldBackScans.set(key, scans);
+    if (scans !== HELD_BACK_ALERT_SCANS || this.shadow) return;
+    sendWarningAlert("Liquidatable account held back by profit gate", [
+      { name: "Market", value: slabAddress.slice(0, 8), inline: true },
+      { name: "Account", value: String(accountIdx), inline: true },
+      { name: "Scans", value: String(scans), inline: true },
+    ]).catch(() => {});
   }
 
   start(getMarkets: () => Map<string, { market: DiscoveredMarket }>): void {
@@ -549,7 +778,8 @@ export class LiquidationService {
           logger.info("Liquidation scan complete", { 
             scanned: result.scanned, 
             candidates: result.candidates, 
-            liquidated: result.liquidated 
+            liquidated: result.liquidated,
+            unprofitable: result.unprofitable,
           });
         }
       } catch (err) {
@@ -593,7 +823,3 @@ export class LiquidationService {
     };
   }
 }
-
-function absBI(n: bigint): bigint {
-  return n < 0n ? -n : n;
-}
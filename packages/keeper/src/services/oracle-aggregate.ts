@@ -0,0 +1,153 @@
+/**
+ * Median-of-N price aggregation for OracleService.
+ *
+ * Each source quote carries a nominal weight (how much we trust the source in
+ * general) and a confidence in [0, 1] (how much we trust this particular read,
+ * e.g. pool depth or Pyth's confidence interval). Their product is the quote's
+ * effective weight.
+ *
+ * Policy:
+ *   1. Take the weighted median of all quotes.
+ *   2. Reject quotes more than `maxDeviationBps` away from it as outliers.
+ *   3. Require a quorum among the survivors: at least `minAgreeing` sources
+ *      (capped at the number that responded) holding a strict majority of the
+ *      responding effective weight. Otherwise no price is produced.
+ *   4. The result is the weighted median of the survivors.
+ */
+
+export interface SourceQuote {
+  source: string;
+  priceE6: bigint;
+  /** 0..1 — quality of this particular read */
+  confidence: number;
+  /** Source-specific context (pool address, liquidity, Pyth conf, ...) */
+  detail?: Record<string, unknown>;
+}
+
+export interface ProvenanceEntry {
+  source: string;
+  status: "used" | "outlier" | "unavailable";
+  priceE6?: string;
+  confidence?: number;
+  weight: number;
+  detail?: Record<string, unknown>;
+}
+
+/** Attached to every aggregated price so a push can be traced back to its inputs. */
+export interface PriceProvenance {
+  method: "weighted-median";
+  priceE6: string;
+  /** Share of the configured source weight that agreed on the price (0..1) */
+  confidence: number;
+  sources: ProvenanceEntry[];
+  aggregatedAt: number;
+}
+
+export interface AggregationPolicy {
+  maxDeviationBps: number;
+  minAgreeing: number;
+}
+
+export const DEFAULT_AGGREGATION_POLICY: AggregationPolicy = {
+  maxDeviationBps: 1_000, // 10%, same tolerance as the old two-source cross-check
+  minAgreeing: 2,
+};
+
+export type AggregationResult =
+  | { ok: true; priceE6: bigint; provenance: PriceProvenance }
+  | { ok: false; reason: string; provenance: PriceProvenance };
+
+interface Weighted {
+  quote: SourceQuote;
+  weight: number;
+}
+
+/**
+ * Lower weighted median: the first price (ascending) at which cumulative
+ * weight reaches half the total. Ties between two equal halves resolve to the
+ * lower price's side, which keeps the result on an actual quote.
+ */
+export function weightedMedian(items: { priceE6: bigint; weight: number }[]): bigint {
+  if (items.length === 0) throw new Error("weightedMedian of empty set");
+  const sorted = [...items].sort((a, b) => (a.priceE6 < b.priceE6 ? -1 : a.priceE6 > b.priceE6 ? 1 : 0));
+  const total = sorted.reduce((s, i) => s + i.weight, 0);
+  let cumulative = 0;
+  for (const item of sorted) {
+    cumulative += item.weight;
+    if (cumulative >= total / 2) return item.priceE6;
+  }
+  return sorted[sorted.length - 1].priceE6;
+}
+
+function deviationBps(price: bigint, reference: bigint): number {
+  if (reference <= 0n) return Infinity;
+  const diff = price > reference ? price - reference : reference - price;
+  return Number((diff * 10_000n) / reference);
+}
+
+/**
+ * @param quotes  quote (or null when the source had nothing) per configured source
+ * @param weights nominal weight per source name
+ */
+export function aggregatePrices(
+  quotes: { source: string; quote: SourceQuote | null }[],
+  weights: Record<string, number>,
+  policy: AggregationPolicy = DEFAULT_AGGREGATION_POLICY,
+  now: number = Date.now(),
+): AggregationResult {
+  const configuredWeight = quotes.reduce((s, q) => s + (weights[q.source] ?? 0), 0);
+  const responding: Weighted[] = [];
+  const bySource = new Map<string, Weighted>();
+  for (const { source, quote } of quotes) {
+    if (!quote || quote.priceE6 <= 0n) continue;
+    const confidence = Math.min(1, Math.max(0, quote.confidence));
+    const weight = (weights[source] ?? 0) * confidence;
+    if (weight <= 0) continue;
+    const w = { quote: { ...quote, source, confidence }, weight };
+    responding.push(w);
+    bySource.set(source, w);
+  }
+
+  const provenance = (used: Set<Weighted>, priceE6: bigint | null, confidence: number): PriceProvenance => ({
+    method: "weighted-median",
+    priceE6: (priceE6 ?? 0n).toString(),
+    confidence,
+    aggregatedAt: now,
+    sources: quotes.map(({ source }) => {
+      const r = bySource.get(source);
+      if (!r) return { source, status: "unavailable" as const, weight: 0 };
+      return {
+        source,
+        status: used.has(r) ? ("used" as const) : ("outlier" as const),
+        priceE6: r.quote.priceE6.toString(),
+        confidence: r.quote.confidence,
+        weight: r.weight,
+        ...(r.quote.detail ? { detail: r.quote.detail } : {}),
+      };
+    }),
+  });
+
+  if (responding.length === 0) {
+    return { ok: false, reason: "no source returned a price", provenance: provenance(new Set(), null, 0) };
+  }
+
+  const median = weightedMedian(responding.map((r) => ({ priceE6: r.quote.priceE6, weight: r.weight })));
+  const survivors = responding.filter((r) => deviationBps(r.quote.priceE6, median) <= policy.maxDeviationBps);
+  const used = new Set(survivors);
+
+  const respondingWeight = responding.reduce((s, r) => s + r.weight, 0);
+  const survivingWeight = survivors.reduce((s, r) => s + r.weight, 0);
+  const needed = Math.min(policy.minAgreeing, responding.length);
+
+  if (survivors.length < needed || survivingWeight * 2 <= respondingWeight) {
+    return {
+      ok: false,
+      reason: `no quorum: ${survivors.length}/${responding.length} sources within ${policy.maxDeviationBps}bps of median`,
+      provenance: provenance(used, null, 0),
+    };
+  }
+
+  const priceE6 = weightedMedian(survivors.map((r) => ({ priceE6: r.quote.priceE6, weight: r.weight })));
+  const confidence = configuredWeight > 0 ? Math.min(1, survivingWeight / configuredWeight) : 0;
+  return { ok: true, priceE6, provenance: provenance(used, priceE6, confidence) };
+}
@@ -0,0 +1,315 @@
+import { PublicKey } from "@solana/web3.js";
+import {
+  detectDexType,
+  parseDexPool,
+  computeDexSpotPriceE6,
+  dexRequiresVaultData,
+  dexSpotPriceIsDecimalAdjusted,
+  PYTH_SOLANA_FEEDS,
+} from "@percolator/sdk";
+import { getConnection, createLogger } from "@percolator/shared";
+import type { SourceQuote } from "./oracle-aggregate.js";
+
+const logger = createLogger("keeper:oracle-sources");
+
+/** A price source OracleService can aggregate over. */
+export interface OracleSource {
+  readonly name: string;
+  /** Nominal weight in the median, before per-quote confidence */
+  readonly weight: number;
+  /** Quote `mint` in USD (e6), or null if this source has nothing for it */
+  quote(mint: string): Promise<SourceQuote | null>;
+}
+
+const API_TIMEOUT_MS = 10_000;
+
+export const WSOL_MINT = "So11111111111111111111111111111111111111112";
+export const USD_STABLE_MINTS = new Set([
+  "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", // USDC
+  "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", // USDT
+]);
+
+/** Counter tokens a pool price can be converted to USD from: stables at $1, wSOL via Pyth. */
+export function isUsdConvertibleMint(mint: string): boolean {
+  return mint === WSOL_MINT || USD_STABLE_MINTS.has(mint);
+}
+
+/** SPL mint layout: decimals at byte 44. */
+export function readMintDecimals(data: Uint8Array): number | null {
+  return data.length > 44 ? data[44] : null;
+}
+
+/** SPL token account layout: amount (u64 LE) at byte 64. */
+export function readTokenAmount(data: Uint8Array): bigint | null {
+  if (data.length < 72) return null;
+  return new DataView(data.buffer, data.byteOffset, data.byteLength).getBigUint64(64, true);
+}
+
+/** Atom ratio (quote atoms per base atom, e6) → quote tokens per base token, e6. */
+export function adjustRatioForDecimalsE6(ratioE6: bigint, baseDecimals: number, quoteDecimals: number): bigint {
+  const diff = baseDecimals - quoteDecimals;
+  return diff >= 0 ? ratioE6 * 10n ** BigInt(diff) : ratioE6 / 10n ** BigInt(-diff);
+}
+
+/** Quote-per-base price (e6) → price of the tracked mint in counter-token units (e6). */
+export function orientPriceE6(quotePerBaseE6: bigint, mintIsBase: boolean): bigint {
+  if (quotePerBaseE6 <= 0n) return 0n;
+  return mintIsBase ? quotePerBaseE6 : 1_000_000_000_000n / quotePerBaseE6;
+}
+
+/** Confidence from USD pool depth — same tiers as the SDK price router. */
+export function liquidityConfidence(liquidityUsd: number): number {
+  if (liquidityUsd > 1_000_000) return 0.9;
+  if (liquidityUsd > 100_000) return 0.75;
+  if (liquidityUsd > 10_000) return 0.6;
+  if (liquidityUsd > 1_000) return 0.45;
+  return 0.3;
+}
+
+// ---------------------------------------------------------------------------
+// Pyth (Hermes)
+// ---------------------------------------------------------------------------
+
+const PYTH_MAX_AGE_S = 60;
+/** A confidence interval this wide (bps of price) scores zero */
+const PYTH_MAX_CONF_BPS = 200;
+const PYTH_CACHE_TTL_MS = 5_000;
+
+const MINT_TO_PYTH_FEED = new Map<string, string>();
+for (const [feedId, info] of Object.entries(PYTH_SOLANA_FEEDS)) {
+  MINT_TO_PYTH_FEED.set(info.mint, feedId);
+}
+
+interface HermesResponse {
+  parsed?: Array<{
+    id: string;
+    price: { price: string; conf: string; expo: number; publish_time: number };
+  }>;
+}
+
+function scaleToE6(mantissa: bigint, expo: number): bigint {
+  const shift = 6 + expo;
+  return shift >= 0 ? mantissa * 10n ** BigInt(shift) : mantissa / 10n ** BigInt(-shift);
+}
+
+/** Pyth price via Hermes, for mints with a known feed. Stale updates are ignored. */
+export class PythSource implements OracleSource {
+  readonly name = "pyth";
+  readonly weight = 1.0;
+  private cache = new Map<string, { quote: SourceQuote | null; fetchedAt: number }>();
+
+  async quote(mint: string): Promise<SourceQuote | null> {
+    const feedId = MINT_TO_PYTH_FEED.get(mint);
+    if (!feedId) return null;
+
+    const now = Date.now();
+    const cached = this.cache.get(feedId);
+    if (cached && now - cached.fetchedAt < PYTH_CACHE_TTL_MS) return cached.quote;
+
+    const quote = await this.fetchFeed(feedId);
+    this.cache.set(feedId, { quote, fetchedAt: now });
+    return quote;
+  }
+
+  private async fetchFeed(feedId: string): Promise<SourceQuote | null> {
+    try {
+      const controller = new AbortController();
+      const timeoutId = setTimeout(() => controller.abort(), API_TIMEOUT_MS);
+      const res = await fetch(`https://hermes.pyth.network/v2/updates/price/latest?ids[]=${feedId}`, {
+        signal: controller.signal,
+      });
+      clearTimeout(timeoutId);
+
+      const json = (await res.json()) as HermesResponse;
+      const update = json.parsed?.find((p) => p.id.replace(/^0x/, "") === feedId);
+      if (!update) return null;
+
+      const ageS = Math.floor(Date.now() / 1000) - update.price.publish_time;
+      if (ageS > PYTH_MAX_AGE_S) {
+        logger.debug("Pyth update stale", { feedId, ageS });
+        return null;
+      }
+
+      const priceE6 = scaleToE6(BigInt(update.price.price), update.price.expo);
+      if (priceE6 <= 0n) return null;
+      const confE6 = scaleToE6(BigInt(update.price.conf), update.price.expo);
+      const confBps = Number((confE6 * 10_000n) / priceE6);
+
+      return {
+        source: this.name,
+        priceE6,
+        confidence: 1 - Math.min(1, confBps / PYTH_MAX_CONF_BPS),
+        detail: { feedId, confBps, publishTime: update.price.publish_time },
+      };
+    } catch {
+      return null;
+    }
+  }
+}
+
+// ---------------------------------------------------------------------------
+// On-chain DEX pools
+// ---------------------------------------------------------------------------
+
+export interface DexPoolCandidate {
+  address: string;
+  dexId: string;
+  liquidityUsd: number;
+}
+
+/** DexScreener dexIds whose pools the on-chain reader may be able to decode */
+export const POOL_DEX_IDS = new Set(["pumpswap", "raydium", "meteora", "orca"]);
+
+const POOL_CONFIDENCE = 0.7;
+const MAX_POOLS_TRIED = 3;
+
+/**
+ * Spot price read straight from a pool account via RPC. Pool addresses are
+ * learned from DexScreener (see `setPools`) and kept for the life of the
+ * process, so this source keeps quoting when the HTTP aggregators are down.
+ *
+ * Quotes in USDC/USDT count at $1; wSOL-quoted pools are converted with
+ * `solUsd`. Pools quoted in anything else are skipped.
+ */
+export class DexPoolSource implements OracleSource {
+  readonly name = "dex-pool";
+  readonly weight = 1.0;
+  private pools = new Map<string, DexPoolCandidate[]>();
+  private mintDecimals = new Map<string, number>();
+
+  constructor(private readonly solUsd: () => Promise<SourceQuote | null>) {}
+
+  /** Replace the known pools for `mint` (deepest first). Ignored when empty. */
+  setPools(mint: string, pools: DexPoolCandidate[]): void {
+    if (pools.length === 0) return;
+    this.pools.set(mint, [...pools].sort((a, b) => b.liquidityUsd - a.liquidityUsd).slice(0, MAX_POOLS_TRIED));
+  }
+
+  async quote(mint: string): Promise<SourceQuote | null> {
+    const pools = this.pools.get(mint);
+    if (!pools) return null;
+
+    for (const pool of pools) {
+      try {
+        const quote = await this.quotePool(mint, pool);
+        if (quote) return quote;
+      } catch (err) {
+        logger.debug("Pool read failed", { mint, pool: pool.address, error: err instanceof Error ? err.message : err });
+      }
+    }
+    return null;
+  }
+
+  private async quotePool(mint: string, pool: DexPoolCandidate): Promise<SourceQuote | null> {
+    const connection = getConnection();
+    const poolKey = new PublicKey(pool.address);
+    const poolAccount = await connection.getAccountInfo(poolKey);
+    if (!poolAccount) return null;
+
+    const dexType = detectDexType(poolAccount.owner);
+    if (!dexType) return null;
+    const data = new Uint8Array(poolAccount.data);
+    const info = parseDexPool(dexType, poolKey, data);
+    const baseMint = info.baseMint.toBase58();
+    const quoteMint = info.quoteMint.toBase58();
+
+    const isBase = baseMint === mint;
+    if (!isBase && quoteMint !== mint) return null;
+    const counterMint = isBase ? quoteMint : baseMint;
+    if (!isUsdConvertibleMint(counterMint)) return null;
+
+    // One round trip for vaults (reserve-priced pools) and any mint decimals not yet cached
+    const needVaults = dexRequiresVaultData(dexType);
+    const decimalAdjusted = dexSpotPriceIsDecimalAdjusted(dexType);
+    const extra: PublicKey[] = [];
+    if (needVaults) extra.push(info.baseVault!, info.quoteVault!);
+    const needDecimals = !decimalAdjusted
+      ? [baseMint, quoteMint].filter((m) => !this.mintDecimals.has(m))
+      : [];
+    extra.push(...needDecimals.map((m) => new PublicKey(m)));
+    const extraAccounts = extra.length > 0 ? await connection.getMultipleAccountsInfo(extra) : [];
+
+    let offset = 0;
+    let vaultData: { base: Uint8Array; quote: Uint8Array } | undefined;
+    if (needVaults) {
+      const [base, quote] = extraAccounts;
+      if (!base || !quote) return null;
+      vaultData = { base: new Uint8Array(base.data), quote: new Uint8Array(quote.data) };
+      offset = 2;
+    }
+    needDecimals.forEach((m, i) => {
+      const decimals = extraAccounts[offset + i] ? readMintDecimals(extraAccounts[offset + i]!.data) : null;
+      if (decimals !== null) this.mintDecimals.set(m, decimals);
+    });
+
+    // Quote-per-base, e6. Raydium pools are decimal-adjusted already; the others are atom ratios.
+    let ratioE6 = computeDexSpotPriceE6(dexType, data, vaultData);
+    if (!decimalAdjusted) {
+      const baseDec = this.mintDecimals.get(baseMint);
+      const quoteDec = this.mintDecimals.get(quoteMint);
+      if (baseDec === undefined || quoteDec === undefined) return null;
+      ratioE6 = adjustRatioForDecimalsE6(ratioE6, baseDec, quoteDec);
+    }
+
+    const inCounterE6 = orientPriceE6(ratioE6, isBase);
+    if (inCounterE6 <= 0n) return null;
+    let counterUsdE6 = 1_000_000n;
+    if (counterMint === WSOL_MINT) {
+      const sol = await this.solUsd();
+      if (!sol) return null;
+      counterUsdE6 = sol.priceE6;
+    }
+    const priceE6 = (inCounterE6 * counterUsdE6) / 1_000_000n;
+    if (priceE6 <= 0n) return null;
+
+    return {
+      source: this.name,
+      priceE6,
+      confidence: POOL_CONFIDENCE,
+      detail: { pool: pool.address, dexType, liquidityUsd: pool.liquidityUsd },
+    };
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Collateral valuation
+// ---------------------------------------------------------------------------
+
+/**
+ * Lamports per base unit of a collateral mint, for weighing fees earned in
+ * collateral against SOL transaction costs. wSOL is 1:1; stables and other
+ * mints (given their USD price) convert through SOL/USD. Null when a price
+ * or the mint's decimals are unavailable.
+ */
+export class CollateralValuer {
+  private readonly mintDecimals = new Map<string, number>();
+
+  constructor(private readonly sol: OracleSource = new PythSource()) {}
+
+  async lamportsPerUnit(mint: string, mintUsdE6?: bigint | null): Promise<number | null> {
+    if (mint === WSOL_MINT) return 1;
+    const usdE6 = USD_STABLE_MINTS.has(mint) ? 1_000_000n : mintUsdE6 ?? null;
+    if (usdE6 === null || usdE6 <= 0n) return null;
+
+    const decimals = await this.decimals(mint);
+    if (decimals === null) return null;
+    const sol = await this.sol.quote(WSOL_MINT).catch(() => null);
+    if (!sol || sol.priceE6 <= 0n) return null;
+
+    // (USD per token / 10^decimals) / (USD per SOL / 1e9)
+    return (Number(usdE6) * 1e9) / (Number(sol.priceE6) * 10 ** decimals);
+  }
+
+  private async decimals(mint: string): Promise<number | null> {
+    const cached = this.mintDecimals.get(mint);
+    if (cached !== undefined) return cached;
+    try {
+      const info = await getConnection().getAccountInfo(new PublicKey(mint));
+      const decimals = info ? readMintDecimals(info.data) : null;
+      if (decimals !== null) this.mintDecimals.set(mint, decimals);
+      return decimals;
+    } catch {
+      return null;
+    }
+  }
+}
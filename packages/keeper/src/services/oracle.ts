@@ -7,13 +7,34 @@ import {
   type MarketConfig,
 } from "@percolator/sdk";
 import { config, getConnection, loadKeypair, sendWithRetry, eventBus, createLogger } from "@percolator/shared";
+import {
+  aggregatePrices,
+  DEFAULT_AGGREGATION_POLICY,
+  type AggregationPolicy,
+  type PriceProvenance,
+  type SourceQuote,
+} from "./oracle-aggregate.js";
+import {
+  DexPoolSource,
+  PythSource,
+  POOL_DEX_IDS,
+  WSOL_MINT,
+  liquidityConfidence,
+  type OracleSource,
+} from "./oracle-sources.js";
+import type { PoolTwapEngine } from "./pool-twap.js";
+import type { ShadowRecorder } from "./shadow.js";
 
 const logger = createLogger("keeper:oracle");
 
-interface PriceEntry {
+export interface PriceEntry {
   priceE6: bigint;
+  /** Single source name, "median" when several agreed, or "cached" / "on-chain" */
   source: string;
   timestamp: number;
+  /** Share of configured source weight behind the price (0..1) */
+  confidence?: number;
+  provenance?: PriceProvenance;
 }
 
 // BL2: Extract magic numbers to named constants
@@ -21,15 +42,22 @@ const API_TIMEOUT_MS = 10_000; // 10 second timeout for external API calls
 const PRICE_E6_MULTIPLIER = 1_000_000; // Price precision (6 decimals)
 const CACHED_PRICE_MAX_AGE_MS = 60_000; // Reject cached prices older than 60s
 
-// Cross-source validation: reject if DexScreener and Jupiter diverge by more than this %
-const MAX_CROSS_SOURCE_DEVIATION_PCT = 10;
+// Jupiter is an aggregate of the same pools DexScreener lists; trust it a little less
+const JUPITER_WEIGHT = 0.8;
+const JUPITER_CONFIDENCE = 0.5;
 
 // DexScreener rate limit: cache responses for 10s to avoid hitting limits
 const dexScreenerCache = new Map<string, { data: DexScreenerResponse; fetchedAt: number }>();
 const DEX_SCREENER_CACHE_TTL_MS = 10_000;
 
 interface DexScreenerResponse {
-  pairs?: Array<{ priceUsd?: string; liquidity?: { usd?: number } }>;
+  pairs?: Array<{
+    chainId?: string;
+    dexId?: string;
+    pairAddress?: string;
+    priceUsd?: string;
+    liquidity?: { usd?: number };
+  }>;
 }
 
 function sortPairsByLiquidity(pairs: DexScreenerResponse["pairs"]): DexScreenerResponse["pairs"] {
@@ -50,6 +78,36 @@ export class OracleService {
   private readonly maxTrackedMarkets = 500;
   // BM2: Deduplicate concurrent requests for the same mint
   private inFlightRequests = new Map<string, Promise<bigint | null>>();
+  private readonly pyth = new PythSource();
+  private readonly dexPools = new DexPoolSource(() => this.pyth.quote(WSOL_MINT));
+  private readonly sources: OracleSource[];
+  private readonly policy: AggregationPolicy;
+  private readonly twap: PoolTwapEngine | null;
+  /** Shadow mode: simulate instead of sending */
+  private shadow: ShadowRecorder | null = null;
+
+  /**
+   * @param options.sources replaces the default set (DexScreener, Jupiter,
+   *   on-chain DEX pools, Pyth)
+   * @param options.twap TWAP mode: price only from this engine's pool TWAP.
+   *   When it refuses (too few samples, thin pool) nothing is pushed — no
+   *   cached or on-chain fallback.
+   */
+  constructor(options: { sources?: OracleSource[]; policy?: AggregationPolicy; twap?: PoolTwapEngine } = {}) {
+    this.twap = options.twap ?? null;
+    this.sources = options.sources ?? (this.twap ? [this.twap] : [
+      { name: "dexscreener", weight: 1.0, quote: (mint) => this.quoteDexScreener(mint) },
+      { name: "jupiter", weight: JUPITER_WEIGHT, quote: (mint) => this.quoteJupiter(mint) },
+      this.dexPools,
+      this.pyth,
+    ]);
+    this.policy = options.policy ?? DEFAULT_AGGREGATION_POLICY;
+  }
+
+  /** Shadow mode: price pushes are simulated and recorded, never sent. */
+  setShadow(recorder: ShadowRecorder): void {
+    this.shadow = recorder;
+  }
 
   /** Fetch price from DexScreener (with rate-limit cache) */
   async fetchDexScreenerPrice(mint: string): Promise<bigint | null> {
@@ -150,49 +208,65 @@ export class OracleService {
   }
 
   /**
-   * Fetch price with cross-source validation and fallback.
+   * DexScreener quote, scored by the depth of its top pair. Also refreshes the
+   * pools the on-chain source and TWAP engine read (used from the next fetch on).
+   */
+  private async quoteDexScreener(mint: string): Promise<SourceQuote | null> {
+    const priceE6 = await this.fetchDexScreenerPrice(mint);
+    const pairs = sortPairsByLiquidity(dexScreenerCache.get(mint)?.data.pairs) ?? [];
+    const pools = pairs
+      .filter((p) => p.chainId === "solana" && p.pairAddress && POOL_DEX_IDS.has((p.dexId ?? "").toLowerCase()))
+      .map((p) => ({ address: p.pairAddress!, dexId: p.dexId!.toLowerCase(), liquidityUsd: p.liquidity?.usd ?? 0 }));
+    this.dexPools.setPools(mint, pools);
+    this.twap?.track(mint, pools.map((p) => p.address));
+    if (priceE6 === null) return null;
+    const liquidityUsd = pairs[0]?.liquidity?.usd ?? 0;
+    return {
+      source: "dexscreener",
+      priceE6,
+      confidence: liquidityConfidence(liquidityUsd),
+      detail: { pair: pairs[0]?.pairAddress, liquidityUsd },
+    };
+  }
+
+  private async quoteJupiter(mint: string): Promise<SourceQuote | null> {
+    const priceE6 = await this.fetchJupiterPrice(mint);
+    return priceE6 === null ? null : { source: "jupiter", priceE6, confidence: JUPITER_CONFIDENCE };
+  }
+
+  /**
+   * Fetch price as a weighted median across all sources, with fallback.
    *
    * Strategy:
-   *   1. Fetch DexScreener and Jupiter in parallel
-   *   2. If both respond, cross-validate (reject if divergence > CROSS_SOURCE_MAX_DEVIATION_PCT)
-   *   3. Use the higher-confidence source (DexScreener preferred, Jupiter fallback)
-   *   4. If both fail, use cached price (reject if stale >60s)
+   *   1. Query every source in parallel; a failing source is just "unavailable"
+   *   2. Aggregate (see oracle-aggregate.ts): reject outliers >10% from the
+   *      weighted median and require a quorum of the sources that responded
+   *   3. If the responding sources disagree, refuse to price
+   *   4. If no source responds, use cached price (reject if stale >60s; never in TWAP mode)
    *   5. Historical deviation check (reject if >30% change from last known price)
    */
   async fetchPrice(mint: string, slabAddress: string): Promise<PriceEntry | null> {
-    // Fetch both sources in parallel for cross-validation
-    const [dexPrice, jupPrice] = await Promise.all([
-      this.fetchDexScreenerPrice(mint),
-      this.fetchJupiterPrice(mint),
-    ]);
-
-    // Cross-source validation: if both sources respond, check agreement
-    if (dexPrice !== null && jupPrice !== null && dexPrice > 0n && jupPrice > 0n) {
-      const larger = dexPrice > jupPrice ? dexPrice : jupPrice;
-      const smaller = dexPrice > jupPrice ? jupPrice : dexPrice;
-      const divergencePct = Number((larger - smaller) * 100n / smaller);
-
-      if (divergencePct > MAX_CROSS_SOURCE_DEVIATION_PCT) {
-        logger.warn("Cross-source divergence detected", {
+    // TWAP mode only asks DexScreener which pools exist; the price never comes from it
+    if (this.twap && !this.twap.isTracking(mint)) await this.quoteDexScreener(mint);
+
+    const quotes = await Promise.all(
+      this.sources.map(async (s) => ({ source: s.name, quote: await s.quote(mint).catch(() => null) })),
+    );
+    const weights = Object.fromEntries(this.sources.map((s) => [s.name, s.weight]));
+    const result = aggregatePrices(quotes, weights, this.policy);
+
+    if (!result.ok) {
+      // Sources answered but couldn't agree — never fall back to a cached price here
+      if (result.provenance.sources.some((s) => s.status !== "unavailable")) {
+        logger.warn("Oracle sources disagree", {
           mint,
-          divergencePct,
-          maxAllowed: MAX_CROSS_SOURCE_DEVIATION_PCT,
-          dexPrice: dexPrice.toString(),
-          jupPrice: jupPrice.toString()
+          reason: result.reason,
+          sources: result.provenance.sources,
         });
         return null;
       }
-    }
+      if (this.twap) return null;
 
-    // Select best available price (DexScreener preferred)
-    let priceE6: bigint | null = dexPrice;
-    let source = "dexscreener";
-    if (priceE6 === null) {
-      priceE6 = jupPrice;
-      source = "jupiter";
-    }
-
-    if (priceE6 === null) {
       const history = this.priceHistory.get(slabAddress);
       if (history && history.length > 0) {
         const last = history[history.length - 1];
@@ -210,6 +284,10 @@ export class OracleService {
       return null;
     }
 
+    const { priceE6, provenance } = result;
+    const used = provenance.sources.filter((s) => s.status === "used");
+    const source = used.length === 1 ? used[0].source : "median";
+
     // R2-S4: Historical deviation check — reject if >30% change from last known price
     const history = this.priceHistory.get(slabAddress);
     if (history && history.length > 0) {
@@ -232,7 +310,13 @@ export class OracleService {
       }
     }
 
-    const entry: PriceEntry = { priceE6, source, timestamp: Date.now() };
+    const entry: PriceEntry = {
+      priceE6,
+      source,
+      timestamp: Date.now(),
+      confidence: provenance.confidence,
+      provenance,
+    };
     this.recordPrice(slabAddress, entry);
     return entry;
   }
@@ -277,6 +361,10 @@ export class OracleService {
 
     // Fallback for devnet test tokens with no external price source:
     // use the last on-chain authority price, or default to 1.0
+    if (!priceEntry && this.twap) {
+      logger.debug("No publishable TWAP, skipping push", { mint, slabAddress });
+      return false;
+    }
     if (!priceEntry) {
       const onChainPrice = marketConfig.authorityPriceE6;
       if (onChainPrice > 0n) {
@@ -290,16 +378,18 @@ export class OracleService {
 
     try {
       const connection = getConnection();
-      const keypair = loadKeypair(process.env.CRANK_KEYPAIR!);
+      // A shadow keeper builds as the live wallet it shadows and holds no key
+      const keypair = this.shadow ? null : loadKeypair(process.env.CRANK_KEYPAIR!);
+      const keeper = this.shadow?.payer ?? keypair!.publicKey;
       const slabPubkey = new PublicKey(slabAddress);
       const programId = marketProgramId ?? new PublicKey(config.programId);
 
       // BC4: Validate that crank keypair is the oracle authority
-      if (!keypair.publicKey.equals(marketConfig.oracleAuthority)) {
+      if (!keeper.equals(marketConfig.oracleAuthority)) {
         // Skip silently for markets we don't control — only log once per market
         if (!this._nonAuthorityLogged.has(slabAddress)) {
           this._nonAuthorityLogged.add(slabAddress);
-          logger.debug("Not oracle authority, skipping", { slabAddress, ourAuthority: keypair.publicKey.toBase58().slice(0, 8), theirAuthority: marketConfig.oracleAuthority.toBase58().slice(0, 8) });
+          logger.debug("Not oracle authority, skipping", { slabAddress, ourAuthority: keeper.toBase58().slice(0, 8), theirAuthority: marketConfig.oracleAuthority.toBase58().slice(0, 8) });
         }
         return false;
       }
@@ -310,19 +400,27 @@ export class OracleService {
       });
 
       const keys = buildAccountMetas(ACCOUNTS_PUSH_ORACLE_PRICE, [
-        keypair.publicKey,
+        keeper,
         slabPubkey,
       ]);
 
       const ix = buildIx({ programId, keys, data });
       logger.debug("Pushing oracle price", { slabAddress, priceE6: priceEntry.priceE6.toString(), programId: programId.toBase58() });
-      const sig = await sendWithRetry(connection, ix, [keypair]);
-      logger.info("Oracle price pushed", { signature: sig });
+      const sig = this.shadow
+        ? await this.shadow.simulate(connection, [ix])
+        : await sendWithRetry(connection, ix, [keypair!]);
+      logger.info("Oracle price pushed", {
+        signature: sig,
+        source: priceEntry.source,
+        confidence: priceEntry.confidence,
+        provenance: priceEntry.provenance?.sources,
+      });
 
       this.lastPushTime.set(slabAddress, now);
       eventBus.publish("price.updated", slabAddress, {
         priceE6: priceEntry.priceE6.toString(),
         source: priceEntry.source,
+        ...(priceEntry.provenance ? { confidence: priceEntry.confidence, provenance: priceEntry.provenance } : {}),
       });
       return true;
     } catch (err) {
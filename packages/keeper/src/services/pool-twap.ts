@@ -0,0 +1,377 @@
+/**
+ * Pool TWAP — rolling time- and volume-weighted averages of on-chain DEX spot prices.
+ *
+ * A single pool snapshot is trivially moved within one block, so pushing spot
+ * is unsafe. This engine samples each tracked pool (pool + both vaults, read
+ * atomically at one slot) on a fixed cadence and keeps a window of samples:
+ *
+ *   TWAP — each sample's price held until the next sample's slot (the last one
+ *          until the current slot), weighted by slots held.
+ *   VWAP — each sample's price weighted by the base-reserve change since the
+ *          previous sample (swap volume proxy). Null when nothing traded.
+ *
+ * A price is only published when the window holds at least `minSamples`
+ * samples and the thinnest counter-side reserve seen in the window is worth at
+ * least `minDepthUsdE6`. A manipulator therefore has to hold the pool off-price
+ * for most of the window, against real depth, to move the result.
+ *
+ * Supported pools: PumpSwap, Raydium CLMM/CPMM/AMM v4 and Orca Whirlpool
+ * quoted in USDC/USDT or wSOL. Meteora DLMM exposes no reserves we can read
+ * and is never sampled.
+ */
+import { PublicKey } from "@solana/web3.js";
+import {
+  detectDexType,
+  parseDexPool,
+  computeDexSpotPriceE6,
+  dexRequiresVaultData,
+  dexSpotPriceIsDecimalAdjusted,
+  type DexType,
+} from "@percolator/sdk";
+import { getConnection, createLogger } from "@percolator/shared";
+import type { SourceQuote } from "./oracle-aggregate.js";
+import {
+  adjustRatioForDecimalsE6,
+  isUsdConvertibleMint,
+  orientPriceE6,
+  readMintDecimals,
+  readTokenAmount,
+  WSOL_MINT,
+  type OracleSource,
+} from "./oracle-sources.js";
+
+const logger = createLogger("keeper:pool-twap");
+
+/**
+ * Vault offsets for concentrated-liquidity pools, whose price doesn't need the
+ * vaults (so the SDK doesn't return them) but whose depth gate does.
+ * Raydium CLMM: token_vault_0 / token_vault_1 follow the two mints.
+ * Orca Whirlpool: token_vault_a / token_vault_b each follow their mint.
+ */
+const CONCENTRATED_VAULT_OFFSETS: Partial<Record<DexType, [number, number]>> = {
+  "raydium-clmm": [137, 169],
+  "orca-whirlpool": [133, 213],
+};
+/** Pools per getMultipleAccountsInfo call (3 accounts each, RPC max is 100) */
+const POOLS_PER_BATCH = 30;
+/** Refuse to publish if a pool hasn't been sampled for this many intervals */
+const STALE_AFTER_INTERVALS = 5;
+
+export interface PoolSample {
+  slot: number;
+  /** Tracked mint priced in the counter token, e6 */
+  priceE6: bigint;
+  /** Counter-token reserve, e6 tokens */
+  counterReserveE6: bigint;
+  /** Tracked-mint reserve in atoms (volume proxy) */
+  mintReserve: bigint;
+}
+
+export interface TwapGates {
+  windowSlots: number;
+  minSamples: number;
+  minDepthUsdE6: bigint;
+}
+
+export type TwapResult =
+  | {
+      ok: true;
+      /** All in counter-token units, e6 */
+      twapE6: bigint;
+      vwapE6: bigint | null;
+      minCounterReserveE6: bigint;
+      samples: number;
+      spanSlots: number;
+    }
+  | { ok: false; reason: string; samples: number };
+
+/**
+ * TWAP/VWAP over the samples inside `[currentSlot - windowSlots, currentSlot]`.
+ * Gates on sample count only; depth is checked by the caller once the counter
+ * token's USD value is known.
+ */
+export function computeTwap(samples: PoolSample[], currentSlot: number, gates: Pick<TwapGates, "windowSlots" | "minSamples">): TwapResult {
+  const from = currentSlot - gates.windowSlots;
+  const window = samples.filter((s) => s.slot >= from && s.slot <= currentSlot).sort((a, b) => a.slot - b.slot);
+  if (window.length < gates.minSamples) {
+    return { ok: false, reason: `insufficient samples: ${window.length}/${gates.minSamples}`, samples: window.length };
+  }
+
+  let weighted = 0n;
+  let totalSlots = 0n;
+  let volumeWeighted = 0n;
+  let totalVolume = 0n;
+  let minReserve = window[0].counterReserveE6;
+
+  for (let i = 0; i < window.length; i++) {
+    const s = window[i];
+    const until = i + 1 < window.length ? window[i + 1].slot : currentSlot;
+    const held = BigInt(until - s.slot);
+    weighted += s.priceE6 * held;
+    totalSlots += held;
+
+    if (i > 0) {
+      const d = s.mintReserve - window[i - 1].mintReserve;
+      const volume = d < 0n ? -d : d;
+      volumeWeighted += s.priceE6 * volume;
+      totalVolume += volume;
+    }
+    if (s.counterReserveE6 < minReserve) minReserve = s.counterReserveE6;
+  }
+
+  // Every sample at the current slot: nothing to weight by, use the plain mean
+  const twapE6 = totalSlots > 0n
+    ? weighted / totalSlots
+    : window.reduce((sum, s) => sum + s.priceE6, 0n) / BigInt(window.length);
+
+  return {
+    ok: true,
+    twapE6,
+    vwapE6: totalVolume > 0n ? volumeWeighted / totalVolume : null,
+    minCounterReserveE6: minReserve,
+    samples: window.length,
+    spanSlots: currentSlot - window[0].slot,
+  };
+}
+
+interface ResolvedPool {
+  address: PublicKey;
+  dexType: DexType;
+  mintIsBase: boolean;
+  counterMint: string;
+  baseVault: PublicKey;
+  quoteVault: PublicKey;
+  baseDecimals: number;
+  quoteDecimals: number;
+}
+
+interface TrackedMint {
+  candidates: string[];
+  pool: ResolvedPool | null;
+  samples: PoolSample[];
+  /** Wall-clock time of the last recorded sample */
+  sampledAt: number;
+}
+
+export type TwapQuote =
+  | ({ ok: true; priceE6: bigint; pool: string; dexType: DexType; depthUsdE6: bigint } & Omit<Extract<TwapResult, { ok: true }>, "ok">)
+  | { ok: false; reason: string };
+
+export class PoolTwapEngine implements OracleSource {
+  readonly name = "pool-twap";
+  readonly weight = 1.0;
+  private tracked = new Map<string, TrackedMint>();
+  /** mint → best candidate that had no samplable pool; not retried until the candidates change */
+  private unsupported = new Map<string, string>();
+  private timer: ReturnType<typeof setInterval> | null = null;
+  private _sampling = false;
+  private lastSlot = 0;
+
+  constructor(
+    private readonly gates: TwapGates,
+    private readonly sampleIntervalMs: number,
+    private readonly solUsd: () => Promise<SourceQuote | null>,
+  ) {}
+
+  start(): void {
+    if (this.timer) return;
+    this.timer = setInterval(() => this.sampleAll(), this.sampleIntervalMs);
+    logger.info("Pool TWAP sampler started", { intervalMs: this.sampleIntervalMs, ...this.gates, minDepthUsdE6: this.gates.minDepthUsdE6.toString() });
+  }
+
+  stop(): void {
+    if (this.timer) {
+      clearInterval(this.timer);
+      this.timer = null;
+    }
+    logger.info("Pool TWAP sampler stopped");
+  }
+
+  /** True once `mint` has been handed candidates (even if none were samplable). */
+  isTracking(mint: string): boolean {
+    return this.tracked.has(mint) || this.unsupported.has(mint);
+  }
+
+  /**
+   * Track `mint` via the first readable pool among `candidates` (deepest
+   * first). Re-tracking with a different best pool restarts the window.
+   */
+  track(mint: string, candidates: string[]): void {
+    if (candidates.length === 0) return;
+    const existing = this.tracked.get(mint);
+    if (existing && existing.candidates[0] === candidates[0]) return;
+    if (this.unsupported.get(mint) === candidates[0]) return;
+    this.unsupported.delete(mint);
+    this.tracked.set(mint, { candidates, pool: null, samples: [], sampledAt: 0 });
+  }
+
+  /** Take one sample of every tracked pool. Returns samples recorded. */
+  async sampleAll(): Promise<number> {
+    if (this._sampling) return 0;
+    this._sampling = true;
+    try {
+      for (const [mint, t] of this.tracked) {
+        if (!t.pool) await this.resolve(mint, t);
+      }
+      const ready = [...this.tracked.values()].filter((t): t is TrackedMint & { pool: ResolvedPool } => t.pool !== null);
+
+      let recorded = 0;
+      for (let i = 0; i < ready.length; i += POOLS_PER_BATCH) {
+        recorded += await this.sampleBatch(ready.slice(i, i + POOLS_PER_BATCH));
+      }
+      return recorded;
+    } catch (err) {
+      logger.warn("TWAP sampling failed", { error: err instanceof Error ? err.message : err });
+      return 0;
+    } finally {
+      this._sampling = false;
+    }
+  }
+
+  private async sampleBatch(batch: (TrackedMint & { pool: ResolvedPool })[]): Promise<number> {
+    const keys = batch.flatMap((t) => [t.pool.address, t.pool.baseVault, t.pool.quoteVault]);
+    const { context, value } = await getConnection().getMultipleAccountsInfoAndContext(keys);
+    const slot = context.slot;
+    if (slot > this.lastSlot) this.lastSlot = slot;
+
+    let recorded = 0;
+    batch.forEach((t, i) => {
+      const [pool, baseVault, quoteVault] = value.slice(i * 3, i * 3 + 3);
+      if (!pool || !baseVault || !quoteVault) return;
+      const last = t.samples[t.samples.length - 1];
+      if (last && last.slot >= slot) return;
+
+      const sample = this.toSample(t.pool, slot, pool.data, baseVault.data, quoteVault.data);
+      if (!sample) return;
+      t.samples.push(sample);
+      t.sampledAt = Date.now();
+      const cutoff = slot - this.gates.windowSlots;
+      while (t.samples.length > 0 && t.samples[0].slot < cutoff) t.samples.shift();
+      recorded++;
+    });
+    return recorded;
+  }
+
+  private toSample(p: ResolvedPool, slot: number, poolData: Uint8Array, baseVaultData: Uint8Array, quoteVaultData: Uint8Array): PoolSample | null {
+    const baseAmount = readTokenAmount(baseVaultData);
+    const quoteAmount = readTokenAmount(quoteVaultData);
+    if (baseAmount === null || quoteAmount === null) return null;
+
+    let ratioE6 = computeDexSpotPriceE6(
+      p.dexType,
+      new Uint8Array(poolData),
+      dexRequiresVaultData(p.dexType) ? { base: new Uint8Array(baseVaultData), quote: new Uint8Array(quoteVaultData) } : undefined,
+    );
+    if (!dexSpotPriceIsDecimalAdjusted(p.dexType)) ratioE6 = adjustRatioForDecimalsE6(ratioE6, p.baseDecimals, p.quoteDecimals);
+    const priceE6 = orientPriceE6(ratioE6, p.mintIsBase);
+    if (priceE6 <= 0n) return null;
+
+    const [counterAmount, counterDecimals] = p.mintIsBase ? [quoteAmount, p.quoteDecimals] : [baseAmount, p.baseDecimals];
+    return {
+      slot,
+      priceE6,
+      counterReserveE6: (counterAmount * 1_000_000n) / 10n ** BigInt(counterDecimals),
+      mintReserve: p.mintIsBase ? baseAmount : quoteAmount,
+    };
+  }
+
+  /** Find the first candidate pool we can sample; drop the mint if none. */
+  private async resolve(mint: string, t: TrackedMint): Promise<void> {
+    const connection = getConnection();
+    for (const candidate of t.candidates) {
+      try {
+        const address = new PublicKey(candidate);
+        const account = await connection.getAccountInfo(address);
+        if (!account) continue;
+        const dexType = detectDexType(account.owner);
+        if (!dexType) continue;
+        const vaultOffsets = CONCENTRATED_VAULT_OFFSETS[dexType];
+        if (!dexRequiresVaultData(dexType) && !vaultOffsets) continue;
+
+        const data = new Uint8Array(account.data);
+        const info = parseDexPool(dexType, address, data);
+        const baseMint = info.baseMint.toBase58();
+        const quoteMint = info.quoteMint.toBase58();
+        const mintIsBase = baseMint === mint;
+        if (!mintIsBase && quoteMint !== mint) continue;
+        const counterMint = mintIsBase ? quoteMint : baseMint;
+        if (!isUsdConvertibleMint(counterMint)) continue;
+
+        const baseVault = info.baseVault ?? new PublicKey(data.slice(vaultOffsets![0], vaultOffsets![0] + 32));
+        const quoteVault = info.quoteVault ?? new PublicKey(data.slice(vaultOffsets![1], vaultOffsets![1] + 32));
+        const mints = await connection.getMultipleAccountsInfo([info.baseMint, info.quoteMint]);
+        const baseDecimals = mints[0] ? readMintDecimals(mints[0].data) : null;
+        const quoteDecimals = mints[1] ? readMintDecimals(mints[1].data) : null;
+        if (baseDecimals === null || quoteDecimals === null) continue;
+
+        t.pool = { address, dexType, mintIsBase, counterMint, baseVault, quoteVault, baseDecimals, quoteDecimals };
+        logger.info("TWAP tracking pool", { mint, pool: candidate, dexType });
+        return;
+      } catch (err) {
+        logger.debug("TWAP pool candidate rejected", { mint, pool: candidate, error: err instanceof Error ? err.message : err });
+      }
+    }
+    logger.warn("No TWAP-capable pool for mint", { mint, candidates: t.candidates.length });
+    this.tracked.delete(mint);
+    this.unsupported.set(mint, t.candidates[0]);
+  }
+
+  /** Current TWAP for `mint` in USD, or the reason it can't be published. */
+  async getPrice(mint: string): Promise<TwapQuote> {
+    const t = this.tracked.get(mint);
+    if (!t?.pool) return { ok: false, reason: "no sampled pool" };
+    if (Date.now() - t.sampledAt > STALE_AFTER_INTERVALS * this.sampleIntervalMs) {
+      return { ok: false, reason: "samples stale" };
+    }
+
+    const result = computeTwap(t.samples, this.lastSlot, this.gates);
+    if (!result.ok) return { ok: false, reason: result.reason };
+
+    let counterUsdE6 = 1_000_000n;
+    if (t.pool.counterMint === WSOL_MINT) {
+      const sol = await this.solUsd();
+      if (!sol) return { ok: false, reason: "no SOL/USD price" };
+      counterUsdE6 = sol.priceE6;
+    }
+    const depthUsdE6 = (result.minCounterReserveE6 * counterUsdE6) / 1_000_000n;
+    if (depthUsdE6 < this.gates.minDepthUsdE6) {
+      return { ok: false, reason: `insufficient depth: $${depthUsdE6 / 1_000_000n} < $${this.gates.minDepthUsdE6 / 1_000_000n}` };
+    }
+
+    const usd = (v: bigint) => (v * counterUsdE6) / 1_000_000n;
+    return {
+      ok: true,
+      priceE6: usd(result.twapE6),
+      pool: t.pool.address.toBase58(),
+      dexType: t.pool.dexType,
+      depthUsdE6,
+      twapE6: usd(result.twapE6),
+      vwapE6: result.vwapE6 === null ? null : usd(result.vwapE6),
+      minCounterReserveE6: result.minCounterReserveE6,
+      samples: result.samples,
+      spanSlots: result.spanSlots,
+    };
+  }
+
+  async quote(mint: string): Promise<SourceQuote | null> {
+    const twap = await this.getPrice(mint);
+    if (!twap.ok) {
+      logger.info("TWAP not publishable", { mint, reason: twap.reason });
+      return null;
+    }
+    return {
+      source: this.name,
+      priceE6: twap.priceE6,
+      confidence: 1,
+      detail: {
+        pool: twap.pool,
+        dexType: twap.dexType,
+        twapE6: twap.twapE6.toString(),
+        vwapE6: twap.vwapE6?.toString() ?? null,
+        samples: twap.samples,
+        spanSlots: twap.spanSlots,
+        depthUsdE6: twap.depthUsdE6.toString(),
+      },
+    };
+  }
+}
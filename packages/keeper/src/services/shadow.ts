@@ -0,0 +1,430 @@
+/**
+ * Shadow mode — trial a keeper build against production markets without
+ * sending anything.
+ *
+ * With KEEPER_SHADOW_MODE=true the keeper discovers markets, fetches prices
+ * and schedules cranks and liquidations exactly as usual, but each
+ * transaction it would send is simulated instead: unsigned, as the first
+ * live keeper wallet (`simulateUnsigned`), so the shadow holds no key. Every
+ * simulation is logged with its compute units and decoded program error and
+ * recorded as keeper actions.
+ *
+ * Every report interval the recorded actions are diffed against what the
+ * live keeper wallets actually landed on-chain over the same window: cranks
+ * and oracle pushes per market, and which accounts were liquidated.
+ */
+
+import type { Connection, PublicKey, TransactionInstruction } from "@solana/web3.js";
+import { IX_TAG, simulateUnsigned } from "@percolator/sdk";
+import { acquireToken, createLogger, getConnection } from "@percolator/shared";
+import { MAX_TX_COMPUTE_UNITS } from "./liquidation-planner.js";
+
+const logger = createLogger("keeper:shadow");
+
+// ---------------------------------------------------------------------------
+// Actions
+// ---------------------------------------------------------------------------
+
+export type KeeperActionKind = "crank" | "liquidation" | "oracle-push";
+
+/** One keeper instruction, simulated by the shadow or landed by a live keeper */
+export interface KeeperAction {
+  kind: KeeperActionKind;
+  slabAddress: string;
+  /** Target account, for liquidations */
+  accountIdx?: number;
+  /** Whether the transaction carrying it succeeded */
+  ok: boolean;
+  unitsConsumed?: number;
+  /** Decoded program error for failed transactions */
+  error?: string;
+  /** ms — simulation time, or block time for live transactions */
+  at: number;
+}
+
+/** One simulated transaction */
+export interface SimulationRecord {
+  ok: boolean;
+  unitsConsumed?: number;
+  error?: string;
+  at: number;
+}
+
+/** What the shadow recorded over a report window */
+export interface ShadowWindow {
+  actions: KeeperAction[];
+  simulations: SimulationRecord[];
+}
+
+/** Signature returned to services for a simulated transaction */
+export const SIMULATED_SIGNATURE = "(simulated)";
+
+const KIND_BY_TAG = new Map<number, KeeperActionKind>([
+  [IX_TAG.KeeperCrank, "crank"],
+  [IX_TAG.LiquidateAtOracle, "liquidation"],
+  [IX_TAG.PushOraclePrice, "oracle-push"],
+]);
+
+/**
+ * Decode a keeper instruction (crank, liquidate, oracle push) from its
+ * program, accounts and data. The slab is the second account of all three.
+ * Returns null for anything else, including compute-budget instructions.
+ */
+export function decodeKeeperInstruction(
+  programIds: ReadonlySet<string>,
+  programId: PublicKey,
+  accounts: PublicKey[],
+  data: Uint8Array,
+): Pick<KeeperAction, "kind" | "slabAddress" | "accountIdx"> | null {
+  if (!programIds.has(programId.toBase58()) || data.length === 0) return null;
+  const kind = KIND_BY_TAG.get(data[0]!);
+  const slab = accounts[1];
+  if (!kind || !slab) return null;
+  if (kind === "liquidation") {
+    if (data.length < 3) return null;
+    return { kind, slabAddress: slab.toBase58(), accountIdx: data[1]! | (data[2]! << 8) };
+  }
+  return { kind, slabAddress: slab.toBase58() };
+}
+
+// ---------------------------------------------------------------------------
+// Recorder
+// ---------------------------------------------------------------------------
+
+/** The log line naming the failing program instruction, if any */
+function programFailure(logs: string[]): string | undefined {
+  return logs.find((l) => l.includes(" failed: "));
+}
+
+/**
+ * Stands in for the send path in shadow mode. Services build instructions
+ * as `payer` — the live keeper wallet being shadowed — and hand over what
+ * they would have sent; it simulates them, records the
+ * decoded actions and returns {@link SIMULATED_SIGNATURE} — or throws like a
+ * failed send, so the service's normal failure handling runs.
+ */
+export class ShadowRecorder {
+  private readonly programIds: ReadonlySet<string>;
+  private actions: KeeperAction[] = [];
+  private simulations: SimulationRecord[] = [];
+
+  constructor(programIds: string[], readonly payer: PublicKey) {
+    this.programIds = new Set(programIds);
+  }
+
+  async simulate(connection: Connection, instructions: TransactionInstruction[]): Promise<string> {
+    await acquireToken();
+    const result = await simulateUnsigned({
+      connection,
+      ix: instructions,
+      feePayer: this.payer,
+      computeUnitLimit: MAX_TX_COMPUTE_UNITS,
+    });
+    const at = Date.now();
+    this.simulations.push({
+      ok: result.err === null,
+      unitsConsumed: result.unitsConsumed,
+      error: result.err ?? undefined,
+      at,
+    });
+
+    const decoded = instructions
+      .map((ix) => decodeKeeperInstruction(this.programIds, ix.programId, ix.keys.map((k) => k.pubkey), ix.data))
+      .filter((a) => a !== null);
+    for (const action of decoded) {
+      this.actions.push({
+        ...action,
+        ok: result.err === null,
+        unitsConsumed: result.unitsConsumed,
+        error: result.err ?? undefined,
+        at,
+      });
+    }
+
+    const context = {
+      actions: decoded.map((a) => (a.accountIdx === undefined ? a.kind : `${a.kind}#${a.accountIdx}`)),
+      slabAddress: decoded[0]?.slabAddress,
+      slot: result.slot,
+      unitsConsumed: result.unitsConsumed,
+    };
+    if (result.err) {
+      const failure = programFailure(result.logs);
+      logger.warn("Shadow simulation failed", { ...context, error: result.err, hint: result.hint, failure });
+      throw new Error(`Simulation failed: ${result.err}${failure ? ` — ${failure}` : ""}`);
+    }
+    logger.info("Shadow simulation", context);
+    return SIMULATED_SIGNATURE;
+  }
+
+  /** Take what was recorded before `until` (ms), keeping later records. */
+  drain(until: number): ShadowWindow {
+    const window = {
+      actions: this.actions.filter((a) => a.at < until),
+      simulations: this.simulations.filter((r) => r.at < until),
+    };
+    this.actions = this.actions.filter((a) => a.at >= until);
+    this.simulations = this.simulations.filter((r) => r.at >= until);
+    return window;
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Live activity
+// ---------------------------------------------------------------------------
+
+const SIGNATURE_PAGE = 1_000;
+const TX_FETCH_CHUNK = 100;
+
+/**
+ * Keeper actions the given wallets landed (or had fail) on-chain in
+ * [since, until), by block time.
+ */
+export async function fetchLiveActions(
+  connection: Connection,
+  wallets: PublicKey[],
+  programIds: ReadonlySet<string>,
+  since: number,
+  until: number,
+): Promise<KeeperAction[]> {
+  const signatures: string[] = [];
+  for (const wallet of wallets) {
+    let before: string | undefined;
+    for (;;) {
+      await acquireToken();
+      const page = await connection.getSignaturesForAddress(wallet, { before, limit: SIGNATURE_PAGE }, "confirmed");
+      for (const s of page) {
+        const at = (s.blockTime ?? 0) * 1000;
+        if (at >= since && at < until) signatures.push(s.signature);
+      }
+      const last = page[page.length - 1];
+      if (page.length < SIGNATURE_PAGE || !last || (last.blockTime ?? 0) * 1000 < since) break;
+      before = last.signature;
+    }
+  }
+
+  const actions: KeeperAction[] = [];
+  for (let i = 0; i < signatures.length; i += TX_FETCH_CHUNK) {
+    await acquireToken();
+    const txs = await connection.getTransactions(signatures.slice(i, i + TX_FETCH_CHUNK), {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    for (const tx of txs) {
+      if (!tx) continue;
+      const message = tx.transaction.message;
+      const keys = message.getAccountKeys({ accountKeysFromLookups: tx.meta?.loadedAddresses });
+      for (const ix of message.compiledInstructions) {
+        const programId = keys.get(ix.programIdIndex);
+        if (!programId) continue;
+        const accounts = ix.accountKeyIndexes.map((k) => keys.get(k)).filter((k) => k !== undefined);
+        const action = decodeKeeperInstruction(programIds, programId, accounts, ix.data);
+        if (!action) continue;
+        actions.push({
+          ...action,
+          ok: !tx.meta?.err,
+          unitsConsumed: tx.meta?.computeUnitsConsumed,
+          error: tx.meta?.err ? JSON.stringify(tx.meta.err) : undefined,
+          at: (tx.blockTime ?? 0) * 1000,
+        });
+      }
+    }
+  }
+  return actions;
+}
+
+// ---------------------------------------------------------------------------
+// Diff
+// ---------------------------------------------------------------------------
+
+export interface KeeperActivity {
+  /** Successful cranks / pushes */
+  cranks: number;
+  pushes: number;
+  /** Accounts successfully liquidated, ascending */
+  liquidations: number[];
+  /** Actions whose transaction failed */
+  failed: number;
+}
+
+export interface MarketShadowDiff {
+  slabAddress: string;
+  shadow: KeeperActivity;
+  live: KeeperActivity;
+  /** Liquidated live that the shadow never attempted */
+  missedLiquidations: number[];
+  /** The shadow would have liquidated, but no live keeper did */
+  extraLiquidations: number[];
+  /** Liquidations differ, or crank counts differ by more than one (window edges) */
+  diverged: boolean;
+}
+
+export interface ShadowReport {
+  windowStart: number;
+  windowEnd: number;
+  simulations: number;
+  simulationFailures: number;
+  /** Mean compute units of successful simulations */
+  avgSimulatedUnits: number | null;
+  /** Simulation failures by decoded error */
+  errors: Record<string, number>;
+  liveActions: number;
+  divergedMarkets: number;
+  /** Diverged markets first */
+  markets: MarketShadowDiff[];
+}
+
+function emptyActivity(): KeeperActivity {
+  return { cranks: 0, pushes: 0, liquidations: [], failed: 0 };
+}
+
+function tally(actions: KeeperAction[]): Map<string, { activity: KeeperActivity; attempted: Set<number> }> {
+  const bySlab = new Map<string, { activity: KeeperActivity; attempted: Set<number> }>();
+  for (const a of actions) {
+    let entry = bySlab.get(a.slabAddress);
+    if (!entry) {
+      entry = { activity: emptyActivity(), attempted: new Set() };
+      bySlab.set(a.slabAddress, entry);
+    }
+    if (a.kind === "liquidation" && a.accountIdx !== undefined) entry.attempted.add(a.accountIdx);
+    if (!a.ok) {
+      entry.activity.failed++;
+    } else if (a.kind === "crank") {
+      entry.activity.cranks++;
+    } else if (a.kind === "oracle-push") {
+      entry.activity.pushes++;
+    } else if (a.accountIdx !== undefined && !entry.activity.liquidations.includes(a.accountIdx)) {
+      entry.activity.liquidations.push(a.accountIdx);
+    }
+  }
+  for (const { activity } of bySlab.values()) activity.liquidations.sort((x, y) => x - y);
+  return bySlab;
+}
+
+/** Compare what the shadow would have done with what the live keepers did. */
+export function diffShadowActions(
+  shadow: ShadowWindow,
+  live: KeeperAction[],
+  windowStart: number,
+  windowEnd: number,
+): ShadowReport {
+  const shadowBySlab = tally(shadow.actions);
+  const liveBySlab = tally(live);
+
+  const markets: MarketShadowDiff[] = [];
+  for (const slabAddress of new Set([...shadowBySlab.keys(), ...liveBySlab.keys()])) {
+    const s = shadowBySlab.get(slabAddress);
+    const l = liveBySlab.get(slabAddress);
+    const shadowActivity = s?.activity ?? emptyActivity();
+    const liveActivity = l?.activity ?? emptyActivity();
+    const missedLiquidations = liveActivity.liquidations.filter((idx) => !s?.attempted.has(idx));
+    const extraLiquidations = shadowActivity.liquidations.filter((idx) => !liveActivity.liquidations.includes(idx));
+    markets.push({
+      slabAddress,
+      shadow: shadowActivity,
+      live: liveActivity,
+      missedLiquidations,
+      extraLiquidations,
+      diverged: missedLiquidations.length > 0
+        || extraLiquidations.length > 0
+        || Math.abs(shadowActivity.cranks - liveActivity.cranks) > 1,
+    });
+  }
+  markets.sort((a, b) => Number(b.diverged) - Number(a.diverged) || (a.slabAddress < b.slabAddress ? -1 : 1));
+
+  const errors: Record<string, number> = {};
+  let failures = 0;
+  let units = 0;
+  let unitsCount = 0;
+  for (const r of shadow.simulations) {
+    if (!r.ok) {
+      failures++;
+      const key = r.error ?? "unknown";
+      errors[key] = (errors[key] ?? 0) + 1;
+    } else if (r.unitsConsumed !== undefined) {
+      units += r.unitsConsumed;
+      unitsCount++;
+    }
+  }
+
+  return {
+    windowStart,
+    windowEnd,
+    simulations: shadow.simulations.length,
+    simulationFailures: failures,
+    avgSimulatedUnits: unitsCount > 0 ? Math.round(units / unitsCount) : null,
+    errors,
+    liveActions: live.length,
+    divergedMarkets: markets.filter((m) => m.diverged).length,
+    markets,
+  };
+}
+
+// ---------------------------------------------------------------------------
+// Reporter
+// ---------------------------------------------------------------------------
+
+export class ShadowReporter {
+  private timer: ReturnType<typeof setInterval> | null = null;
+  private windowStart = Date.now();
+  private lastReport: ShadowReport | null = null;
+  private readonly programIds: ReadonlySet<string>;
+
+  constructor(
+    private readonly recorder: ShadowRecorder,
+    /** Wallets of the live keepers being compared against */
+    private readonly liveWallets: PublicKey[],
+    programIds: string[],
+    private readonly intervalMs: number,
+  ) {
+    this.programIds = new Set(programIds);
+  }
+
+  start(): void {
+    if (this.timer) return;
+    this.windowStart = Date.now();
+    this.timer = setInterval(() => {
+      this.report().catch((err) => {
+        logger.error("Shadow report failed", { error: err instanceof Error ? err.message : String(err) });
+      });
+    }, this.intervalMs);
+  }
+
+  stop(): void {
+    if (this.timer) {
+      clearInterval(this.timer);
+      this.timer = null;
+    }
+  }
+
+  /** Close the current window and diff it. A failed live fetch keeps the window open. */
+  async report(): Promise<ShadowReport> {
+    const windowEnd = Date.now();
+    const live = await fetchLiveActions(getConnection(), this.liveWallets, this.programIds, this.windowStart, windowEnd);
+    const report = diffShadowActions(this.recorder.drain(windowEnd), live, this.windowStart, windowEnd);
+    this.windowStart = windowEnd;
+    this.lastReport = report;
+
+    const summary = {
+      windowMs: windowEnd - report.windowStart,
+      simulations: report.simulations,
+      simulationFailures: report.simulationFailures,
+      avgSimulatedUnits: report.avgSimulatedUnits,
+      liveActions: report.liveActions,
+      divergedMarkets: report.divergedMarkets,
+      errors: report.errors,
+    };
+    if (report.divergedMarkets > 0) {
+      logger.warn("Shadow report: diverged from live keepers", {
+        ...summary,
+        markets: report.markets.filter((m) => m.diverged),
+      });
+    } else {
+      logger.info("Shadow report: matches live keepers", summary);
+    }
+    return report;
+  }
+
+  getLastReport(): ShadowReport | null {
+    return this.lastReport;
+  }
+}
@@ -17,6 +17,7 @@ describe("validateKeeperEnvGuards", () => {
     const env = {
       SUPABASE_KEY: "anon-key",
       SUPABASE_SERVICE_ROLE_KEY: "service-role-key",
+      CRANK_KEYPAIR: "keypair",
     } as NodeJS.ProcessEnv;
 
     expect(() => validateKeeperEnvGuards(env)).not.toThrow();
@@ -25,8 +26,20 @@ describe("validateKeeperEnvGuards", () => {
   it("does not throw when one key is missing", () => {
     const env = {
       SUPABASE_KEY: "anon-key",
+      CRANK_KEYPAIR: "keypair",
     } as NodeJS.ProcessEnv;
 
     expect(() => validateKeeperEnvGuards(env)).not.toThrow();
   });
+
+  it("requires CRANK_KEYPAIR outside shadow mode", () => {
+    expect(() => validateKeeperEnvGuards({} as NodeJS.ProcessEnv)).toThrow("CRANK_KEYPAIR must be set");
+  });
+
+  it("requires live wallets, and no keypair, in shadow mode", () => {
+    expect(() => validateKeeperEnvGuards({ KEEPER_SHADOW_MODE: "true", KEEPER_SHADOW_LIVE_WALLETS: " , " } as NodeJS.ProcessEnv))
+      .toThrow("KEEPER_SHADOW_LIVE_WALLETS must list the live keeper wallets");
+    expect(() => validateKeeperEnvGuards({ KEEPER_SHADOW_MODE: "true", KEEPER_SHADOW_LIVE_WALLETS: "Live1111" } as NodeJS.ProcessEnv))
+      .not.toThrow();
+  });
 });
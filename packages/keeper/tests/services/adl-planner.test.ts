@@ -0,0 +1,106 @@
+import { describe, it, expect, vi } from 'vitest';
+
+vi.mock('@percolator/sdk', () => ({
+  ACCOUNTS_ADMIN_FORCE_CLOSE: [{ name: 'admin' }, { name: 'slab' }, { name: 'clock' }, { name: 'oracle' }],
+  encodeAdminForceClose: vi.fn(({ targetIdx }: { targetIdx: number }) => Uint8Array.of(21, targetIdx & 0xff, targetIdx >> 8)),
+  buildAccountMetas: vi.fn((_spec: unknown, keys: unknown[]) => keys),
+  buildIx: vi.fn((ix: unknown) => ix),
+}));
+
+import {
+  assessAdl,
+  rankAdlCandidates,
+  planAdl,
+  buildAdlInstructions,
+  ADL_EXECUTOR,
+  type AdlPosition,
+} from '../../src/services/adl-planner.js';
+
+function engine(balance: bigint, opts: { emergencyOiMode?: boolean; longOi?: bigint; shortOi?: bigint } = {}) {
+  return {
+    insuranceFund: { balance, feeRevenue: 0n, isolatedBalance: 0n, isolationBps: 0 },
+    emergencyOiMode: opts.emergencyOiMode ?? false,
+    longOi: opts.longOi ?? 0n,
+    shortOi: opts.shortOi ?? 0n,
+  };
+}
+
+function position(accountIdx: number, positionSize: bigint, equity: bigint, unrealizedPnl: bigint, notional: bigint): AdlPosition {
+  return { accountIdx, owner: `Owner${accountIdx}`, positionSize, equity, unrealizedPnl, notional };
+}
+
+// Two bankrupt longs owing 700 in total, three profitable shorts and an underwater short
+const POSITIONS = [
+  position(1, 1_000n, -500n, -900n, 10_000n),
+  position(2, 500n, -200n, -400n, 5_000n),
+  position(3, -1_000n, 1_000n, 400n, 10_000n), // 10x → score 4,000
+  position(4, -2_000n, 500n, 300n, 10_000n),   // 20x → score 6,000
+  position(5, -100n, 100n, 50n, 1_000n),       // 10x → score 500
+  position(6, -300n, 200n, -20n, 3_000n),      // losing short: never deleveraged
+];
+
+describe('assessAdl', () => {
+  it('compares bad debt to the insurance fund, isolated share included', () => {
+    const a = assessAdl('M', { ...engine(300n), insuranceFund: { balance: 300n, feeRevenue: 0n, isolatedBalance: 100n, isolationBps: 0 } }, POSITIONS);
+
+    expect(a).toMatchObject({ badDebt: 700n, insuranceAvailable: 400n, shortfall: 300n, triggers: ['insurance-shortfall'] });
+    expect(assessAdl('M', engine(1_000n), POSITIONS)).toMatchObject({ shortfall: 0n, triggers: [] });
+  });
+});
+
+describe('rankAdlCandidates', () => {
+  it('ranks profitable positions on one side by PnL × leverage', () => {
+    const ranked = rankAdlCandidates(POSITIONS, 'short');
+
+    expect(ranked.map((c) => c.accountIdx)).toEqual([4, 3, 5]);
+    expect(ranked[0]).toMatchObject({ side: 'short', leverageBps: 200_000n, score: 6_000n });
+  });
+});
+
+describe('planAdl', () => {
+  it('deleverages the winners opposite the bad debt until their PnL covers the shortfall', () => {
+    const plan = planAdl('M', engine(100n), POSITIONS);
+
+    // Shortfall 600: #4 (300) then #3 (400) covers it; #5 is left alone
+    expect(plan).toMatchObject({ side: 'short', target: 600n, targetKind: 'pnl', covered: 700n, exhausted: false });
+    expect(plan.selected.map((c) => c.accountIdx)).toEqual([4, 3]);
+  });
+
+  it('flags a plan that can\'t cover the shortfall', () => {
+    const plan = planAdl('M', engine(0n), POSITIONS.filter((p) => p.accountIdx !== 3));
+
+    expect(plan.selected.map((c) => c.accountIdx)).toEqual([4, 5]);
+    expect(plan.exhausted).toBe(true);
+  });
+
+  it('cuts the heavier OI side in emergency OI mode', () => {
+    const longs = [position(7, 800n, 400n, 200n, 8_000n), position(8, 300n, 100n, 90n, 3_000n)];
+    const plan = planAdl('M', engine(10_000n, { emergencyOiMode: true, longOi: 1_500n, shortOi: 1_000n }), longs);
+
+    expect(plan).toMatchObject({ side: 'long', targetKind: 'oi', target: 500n, covered: 800n });
+    expect(plan.assessment.triggers).toEqual(['emergency-oi']);
+    expect(plan.selected.map((c) => c.accountIdx)).toEqual([7]);
+  });
+
+  it('plans nothing when the fund covers the bad debt', () => {
+    expect(planAdl('M', engine(1_000n), POSITIONS)).toMatchObject({ side: null, selected: [] });
+  });
+});
+
+describe('buildAdlInstructions', () => {
+  it('force-closes each selected position as the admin, in ranked order', () => {
+    const plan = planAdl('M', engine(100n), POSITIONS);
+    const ctx = { programId: 'Program' as any, slab: 'Slab' as any, oracle: 'Oracle' as any, authority: 'Admin' as any };
+
+    const ixs = buildAdlInstructions(plan, ctx) as any[];
+
+    expect(ADL_EXECUTOR.authority).toBe('admin');
+    expect(ixs).toHaveLength(2);
+    expect(ixs[0].programId).toBe('Program');
+    expect(ixs[0].keys[0]).toBe('Admin');
+    expect(ixs[0].keys[1]).toBe('Slab');
+    expect(ixs[0].keys[3]).toBe('Oracle');
+    expect(Array.from(ixs[0].data)).toEqual([21, 4, 0]);
+    expect(Array.from(ixs[1].data)).toEqual([21, 3, 0]);
+  });
+});
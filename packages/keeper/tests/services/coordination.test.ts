@@ -0,0 +1,147 @@
+import { describe, it, expect, vi, beforeEach, afterEach } from 'vitest';
+
+vi.mock('@percolator/shared', () => ({
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+}));
+
+import {
+  HashRing,
+  InMemoryCoordinationBackend,
+  InMemoryCoordinationHub,
+  KeeperCoordinator,
+  oracleLeaderResource,
+} from '../../src/services/coordination.js';
+
+const MARKETS = Array.from({ length: 200 }, (_, i) => `Market${i.toString().padStart(4, '0')}`);
+
+function ownersOf(keepers: KeeperCoordinator[]): Map<string, number[]> {
+  const owners = new Map<string, number[]>();
+  for (const m of MARKETS) {
+    owners.set(m, keepers.flatMap((k, i) => (k.owns(m) ? [i] : [])));
+  }
+  return owners;
+}
+
+describe('HashRing', () => {
+  it('moves only the joining member\'s share of keys', () => {
+    const before = new HashRing(['a', 'b', 'c']);
+    const after = new HashRing(['a', 'b', 'c', 'd']);
+
+    let moved = 0;
+    for (const m of MARKETS) {
+      const prev = before.owner(m);
+      const next = after.owner(m);
+      if (prev !== next) {
+        moved++;
+        expect(next).toBe('d');
+      }
+    }
+    expect(moved).toBeGreaterThan(0);
+    expect(moved).toBeLessThan(MARKETS.length / 2);
+  });
+
+  it('has no owner when empty', () => {
+    expect(new HashRing([]).owner('Market0000')).toBeNull();
+  });
+});
+
+describe('KeeperCoordinator', () => {
+  let hub: InMemoryCoordinationHub;
+  let keepers: KeeperCoordinator[];
+
+  beforeEach(async () => {
+    hub = new InMemoryCoordinationHub();
+    keepers = ['k1', 'k2', 'k3'].map(
+      (id) => new KeeperCoordinator(new InMemoryCoordinationBackend(hub, id)),
+    );
+    for (const k of keepers) await k.refresh();
+    for (const k of keepers) await k.refresh(); // every keeper sees the full group
+  });
+
+  afterEach(async () => {
+    for (const k of keepers) await k.stop();
+  });
+
+  it('assigns every market to exactly one keeper', () => {
+    const counts = [0, 0, 0];
+    for (const [, owners] of ownersOf(keepers)) {
+      expect(owners).toHaveLength(1);
+      counts[owners[0]!]!++;
+    }
+    for (const c of counts) expect(c).toBeGreaterThan(MARKETS.length / 10);
+  });
+
+  it('fails a dead keeper\'s markets over on the next refresh', async () => {
+    const orphaned = MARKETS.filter((m) => keepers[2]!.owns(m));
+    hub.members.delete('k3'); // session expired without a clean leave
+
+    await keepers[0]!.refresh();
+    await keepers[1]!.refresh();
+
+    for (const m of orphaned) {
+      expect(keepers[0]!.owns(m) || keepers[1]!.owns(m)).toBe(true);
+    }
+    expect(keepers[0]!.getStatus().members).toBe(2);
+  });
+
+  it('grants oracle leadership to one keeper and releases it when the market moves', async () => {
+    const market = MARKETS.find((m) => keepers[0]!.owns(m))!;
+
+    expect(await keepers[0]!.acquireOracleLeadership(market)).toBe(true);
+    expect(await keepers[1]!.acquireOracleLeadership(market)).toBe(false);
+    expect(hub.locks.get(oracleLeaderResource(market))).toBe('k1');
+
+    // k1 dies: its lock goes with it and a peer takes over the pushes
+    await keepers[0]!.stop();
+    expect(hub.locks.has(oracleLeaderResource(market))).toBe(false);
+
+    await keepers[1]!.refresh();
+    await keepers[2]!.refresh();
+    const owner = keepers.slice(1).find((k) => k.owns(market))!;
+    expect(await owner.acquireOracleLeadership(market)).toBe(true);
+  });
+
+  it('hands leadership back when a joining keeper takes the market over', async () => {
+    for (const m of MARKETS) {
+      const owner = keepers.find((k) => k.owns(m))!;
+      await owner.acquireOracleLeadership(m);
+    }
+    expect(hub.locks.size).toBe(MARKETS.length);
+
+    const k4 = new KeeperCoordinator(new InMemoryCoordinationBackend(hub, 'k4'));
+    keepers.push(k4);
+    await k4.refresh();
+    for (const k of keepers.slice(0, 3)) await k.refresh();
+
+    const moved = MARKETS.filter((m) => k4.owns(m));
+    expect(moved.length).toBeGreaterThan(0);
+    for (const m of moved) {
+      expect(await k4.acquireOracleLeadership(m)).toBe(true);
+    }
+  });
+
+  it('owns nothing while the backend is unreachable, then re-joins', async () => {
+    const backend = new InMemoryCoordinationBackend(hub, 'k5');
+    const k5 = new KeeperCoordinator(backend);
+    keepers.push(k5);
+    await k5.refresh();
+    expect(MARKETS.some((m) => k5.owns(m))).toBe(true);
+
+    const members = vi.spyOn(backend, 'members').mockRejectedValueOnce(new Error('connection reset'));
+    await k5.refresh();
+
+    expect(k5.getStatus().healthy).toBe(false);
+    expect(MARKETS.some((m) => k5.owns(m))).toBe(false);
+    expect(hub.members.has('k5')).toBe(false);
+
+    await k5.refresh();
+    expect(members).toHaveBeenCalledTimes(2);
+    expect(k5.getStatus().healthy).toBe(true);
+    expect(MARKETS.some((m) => k5.owns(m))).toBe(true);
+  });
+});
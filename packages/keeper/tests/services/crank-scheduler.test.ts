@@ -0,0 +1,182 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+
+vi.mock('@percolator/sdk', () => ({
+  checkPhaseTransition: vi.fn(() => [0, false]),
+}));
+
+import * as core from '@percolator/sdk';
+import {
+  PriorityQueue,
+  planCrankCycle,
+  estimateCrankCost,
+  CRANK_COMPUTE_UNITS,
+  PRICE_PUSH_COMPUTE_UNITS,
+  type CrankCandidate,
+  type CrankPlanOptions,
+} from '../../src/services/crank-scheduler.js';
+
+const NOW = 1_700_000_000_000;
+const SLOT = 300_000_000n;
+
+function candidate(
+  slabAddress: string,
+  opts: {
+    slotsSinceCrank?: bigint;
+    maxStaleness?: bigint;
+    oi?: bigint;
+    msSinceCrank?: number;
+    headroomBps?: number | null;
+    fundingInterval?: bigint;
+    slotsSinceFunding?: bigint;
+    marketCreatedSlot?: bigint;
+    isActive?: boolean;
+    pushesPrice?: boolean;
+  } = {},
+): CrankCandidate {
+  const engine = {
+    lastCrankSlot: SLOT - (opts.slotsSinceCrank ?? 0n),
+    maxCrankStalenessSlots: opts.maxStaleness ?? 150n,
+    totalOpenInterest: opts.oi ?? 0n,
+    lastFundingSlot: SLOT - (opts.slotsSinceFunding ?? 0n),
+  };
+  return {
+    slabAddress,
+    market: {
+      engine,
+      config: {
+        fundingSettlementIntervalSlots: opts.fundingInterval ?? 0n,
+        marketCreatedSlot: opts.marketCreatedSlot ?? 0n,
+        oraclePhase: 0,
+        cumulativeVolumeE6: 0n,
+        phase2DeltaSlots: 0,
+      },
+    } as any,
+    lastCrankTime: NOW - (opts.msSinceCrank ?? 0),
+    isActive: opts.isActive ?? true,
+    pushesPrice: opts.pushesPrice ?? false,
+    risk: opts.headroomBps === undefined
+      ? undefined
+      : { engine: engine as any, worstMarginHeadroomBps: opts.headroomBps, observedAt: NOW },
+  };
+}
+
+const baseOpts: CrankPlanOptions = {
+  currentSlot: SLOT,
+  budget: { maxComputeUnits: 10_000_000, maxFeeLamports: 10_000_000 },
+  priorityFeeMicroLamports: 10_000,
+  intervalMs: 30_000,
+  inactiveIntervalMs: 120_000,
+  now: NOW,
+};
+
+describe('PriorityQueue', () => {
+  it('pops items highest priority first', () => {
+    const q = new PriorityQueue<string>();
+    for (const [item, p] of [['c', 3], ['a', 10], ['e', 1], ['b', 7], ['d', 2]] as const) q.push(item, p);
+
+    const out: string[] = [];
+    for (let x = q.pop(); x !== undefined; x = q.pop()) out.push(x);
+
+    expect(out).toEqual(['a', 'b', 'c', 'd', 'e']);
+    expect(q.size).toBe(0);
+  });
+});
+
+describe('planCrankCycle', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('ranks a market past its staleness limit ahead of idle interval-due markets', () => {
+    const plan = planCrankCycle([
+      candidate('idle', { msSinceCrank: 60_000 }),
+      candidate('stale', { slotsSinceCrank: 200n }),
+    ], baseOpts);
+
+    expect(plan.selected.map((p) => p.slabAddress)).toEqual(['stale', 'idle']);
+    expect(plan.selected[0]!.urgent).toBe(true);
+    expect(plan.selected[0]!.reasons).toContain('stale');
+  });
+
+  it('cranks urgent markets before their interval and leaves quiet ones alone', () => {
+    const plan = planCrankCycle([
+      candidate('quiet', { msSinceCrank: 5_000 }),
+      candidate('staling', { slotsSinceCrank: 90n, msSinceCrank: 5_000 }),
+    ], baseOpts);
+
+    expect(plan.selected.map((p) => p.slabAddress)).toEqual(['staling']);
+    expect(plan.notDue.map((p) => p.slabAddress)).toEqual(['quiet']);
+  });
+
+  it('keeps high-risk markets within the budget and defers idle ones', () => {
+    const cost = estimateCrankCost(false, false, baseOpts.priorityFeeMicroLamports);
+    const plan = planCrankCycle([
+      candidate('idle-1', { msSinceCrank: 60_000 }),
+      candidate('idle-2', { msSinceCrank: 60_000 }),
+      candidate('near-liq', { headroomBps: 50, oi: 1_000_000n, msSinceCrank: 1_000 }),
+    ], { ...baseOpts, budget: { maxComputeUnits: cost.computeUnits, maxFeeLamports: 10_000_000 } });
+
+    expect(plan.selected.map((p) => p.slabAddress)).toEqual(['near-liq']);
+    expect(plan.selected[0]!.reasons).toContain('near-liquidation');
+    expect(plan.deferred.map((p) => p.slabAddress).sort()).toEqual(['idle-1', 'idle-2']);
+    expect(plan.computeUnits).toBe(cost.computeUnits);
+  });
+
+  it('enforces the fee budget but always takes the top market', () => {
+    const plan = planCrankCycle([
+      candidate('a', { slotsSinceCrank: 300n }),
+      candidate('b', { slotsSinceCrank: 200n }),
+    ], { ...baseOpts, budget: { maxComputeUnits: 10_000_000, maxFeeLamports: 1 } });
+
+    expect(plan.selected.map((p) => p.slabAddress)).toEqual(['a']);
+    expect(plan.deferred.map((p) => p.slabAddress)).toEqual(['b']);
+  });
+
+  it('ranks by open interest among otherwise equal markets', () => {
+    const plan = planCrankCycle([
+      candidate('small', { oi: 1_000n, msSinceCrank: 60_000 }),
+      candidate('large', { oi: 9_000n, msSinceCrank: 60_000 }),
+    ], baseOpts);
+
+    expect(plan.selected.map((p) => p.slabAddress)).toEqual(['large', 'small']);
+  });
+
+  it('flags a due funding settlement and phase advance', () => {
+    vi.mocked(core.checkPhaseTransition).mockReturnValueOnce([1, true]);
+    const plan = planCrankCycle([
+      candidate('funding', { oi: 1n, fundingInterval: 100n, slotsSinceFunding: 150n, marketCreatedSlot: 1n }),
+    ], baseOpts);
+
+    const [p] = plan.selected;
+    expect(p!.fundingDue).toBe(true);
+    expect(p!.phaseAdvanceDue).toBe(true);
+    expect(p!.reasons).toEqual(['funding-due', 'phase-advance-due']);
+  });
+
+  it('ignores risk snapshots that are too old', () => {
+    const c = candidate('old-risk', { headroomBps: -100, oi: 1n, msSinceCrank: 1_000 });
+    c.risk!.observedAt = NOW - 10 * 60_000;
+
+    const plan = planCrankCycle([c], baseOpts);
+
+    expect(plan.notDue.map((p) => p.slabAddress)).toEqual(['old-risk']);
+  });
+
+  it('uses our own last crank slot when it is newer than the scanned engine', () => {
+    const c = candidate('recent', { slotsSinceCrank: 200n, msSinceCrank: 1_000 });
+    c.lastCrankSlot = SLOT - 10n;
+
+    const plan = planCrankCycle([c], baseOpts);
+
+    expect(plan.notDue[0]!.stalenessRatio).toBeCloseTo(10 / 150);
+  });
+});
+
+describe('estimateCrankCost', () => {
+  it('adds the bundled price push and the priority fee', () => {
+    expect(estimateCrankCost(true, false, 1_000_000)).toEqual({
+      computeUnits: CRANK_COMPUTE_UNITS + PRICE_PUSH_COMPUTE_UNITS,
+      feeLamports: 5_000 + CRANK_COMPUTE_UNITS + PRICE_PUSH_COMPUTE_UNITS,
+    });
+  });
+});
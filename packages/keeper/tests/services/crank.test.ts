@@ -17,11 +17,16 @@ vi.mock('@percolator/sdk', () => ({
   discoverMarkets: vi.fn(),
   encodeKeeperCrank: vi.fn(() => Buffer.from([1, 2, 3])),
   encodePushOraclePrice: vi.fn(() => Buffer.from([4, 5, 6])),
+  encodeAdvanceOraclePhase: vi.fn(() => Buffer.from([56])),
+  checkPhaseTransition: vi.fn(() => [0, false]),
   buildAccountMetas: vi.fn(() => []),
   buildIx: vi.fn(() => ({})),
   derivePythPushOraclePDA: vi.fn(() => [{ toBase58: () => '11111111111111111111111111111111' }, 0]),
+  parsePythPriceUpdateV2: vi.fn(),
+  checkOracleFreshness: vi.fn(() => ({ ok: true })),
   ACCOUNTS_KEEPER_CRANK: {},
   ACCOUNTS_PUSH_ORACLE_PRICE: {},
+  ACCOUNTS_ADVANCE_ORACLE_PHASE: {},
 }));
 
 vi.mock('@percolator/shared', () => ({
@@ -31,6 +36,9 @@ vi.mock('@percolator/shared', () => ({
     discoveryIntervalMs: 300000,
     allProgramIds: ['11111111111111111111111111111111', 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA'],
     crankKeypair: 'mock-keypair-path',
+    crankCuBudget: 4_000_000,
+    crankFeeBudgetLamports: 2_000_000,
+    crankConcurrency: 10,
   },
   createLogger: vi.fn(() => ({
     info: vi.fn(),
@@ -40,6 +48,7 @@ vi.mock('@percolator/shared', () => ({
   })),
   getConnection: vi.fn(() => ({
     getAccountInfo: vi.fn(),
+    getSlot: vi.fn(async () => 300_000_000),
   })),
   getFallbackConnection: vi.fn(() => ({
     getProgramAccounts: vi.fn(),
@@ -50,6 +59,7 @@ vi.mock('@percolator/shared', () => ({
   })),
   sendWithRetry: vi.fn(async () => 'mock-signature-' + Date.now()),
   sendWithRetryKeeper: vi.fn(async () => 'mock-keeper-sig-' + Date.now()),
+  getRecentPriorityFees: vi.fn(async () => ({ priorityFeeMicroLamports: 10_000, computeUnitLimit: 400_000 })),
   rateLimitedCall: vi.fn((fn) => fn()),
   sendCriticalAlert: vi.fn(),
   eventBus: {
@@ -204,6 +214,47 @@ describe('CrankService', () => {
       expect(state?.isActive).toBe(true);
     });
 
+    it('should skip the crank when the Pyth price is stale', async () => {
+      const slabAddress = 'Market4P1111111111111111111111111111111';
+      const mockMarket = {
+        slabAddress: { toBase58: () => slabAddress },
+        programId: { toBase58: () => '11111111111111111111111111111111' },
+        config: {
+          collateralMint: { toBase58: () => 'Mint4P11111111111111111111111111111111' },
+          oracleAuthority: { toBase58: () => '11111111111111111111111111111111', equals: () => true },
+          indexFeedId: { toBytes: () => new Uint8Array(32).fill(1) },
+          maxStalenessSlots: 60n,
+          confFilterBps: 50,
+        },
+        params: { maintenanceMarginBps: 500n },
+        header: { admin: { toBase58: () => 'Admin4P111111111111111111111111111111' } },
+      };
+
+      vi.mocked(core.discoverMarkets).mockResolvedValue([mockMarket] as any);
+      await crankService.discover();
+
+      vi.mocked(shared.getConnection).mockReturnValueOnce({
+        getAccountInfo: vi.fn().mockResolvedValue({ data: Buffer.alloc(133) }),
+      } as any);
+      vi.mocked(core.parsePythPriceUpdateV2).mockReturnValueOnce({ price: 1n, decimals: 8 } as any);
+      vi.mocked(core.checkOracleFreshness).mockReturnValueOnce({ ok: false, reason: 'stale: 90s old (max 60s)' } as any);
+
+      const result = await crankService.crankMarket(slabAddress);
+
+      expect(result).toBe(false);
+      expect(core.checkOracleFreshness).toHaveBeenCalledWith(
+        { price: 1n, decimals: 8 },
+        { maxStalenessSecs: 60n, confFilterBps: 50 },
+      );
+      expect(shared.sendWithRetryKeeper).not.toHaveBeenCalled();
+      expect(shared.eventBus.publish).toHaveBeenCalledWith(
+        'crank.stale',
+        slabAddress,
+        expect.objectContaining({ reason: 'stale: 90s old (max 60s)' }),
+      );
+      expect(crankService.getMarkets().get(slabAddress)?.consecutiveFailures).toBe(0);
+    });
+
     it('should increment failure count on crank failure', async () => {
       const slabAddress = 'Market511111111111111111111111111111111';
       const mockMarket = {
@@ -444,6 +495,70 @@ describe('CrankService', () => {
     });
   });
 
+  describe('crankAll', () => {
+    function scheduledMarket(slabAddress: string, slotsSinceCrank: bigint) {
+      return {
+        slabAddress: { toBase58: () => slabAddress },
+        programId: { toBase58: () => '11111111111111111111111111111111' },
+        config: {
+          collateralMint: { toBase58: () => 'MintSched111111111111111111111111111111' },
+          oracleAuthority: { toBase58: () => '11111111111111111111111111111111', equals: () => true },
+          indexFeedId: { toBytes: () => new Uint8Array(32).fill(1) },
+        },
+        engine: {
+          lastCrankSlot: 300_000_000n - slotsSinceCrank,
+          maxCrankStalenessSlots: 150n,
+          totalOpenInterest: 0n,
+          lastFundingSlot: 0n,
+        },
+        params: { maintenanceMarginBps: 500n },
+        header: { admin: { toBase58: () => 'AdminSched11111111111111111111111111111' } },
+      };
+    }
+
+    it('cranks the stalest market first and defers the rest past the CU budget', async () => {
+      vi.mocked(core.discoverMarkets).mockResolvedValue([
+        scheduledMarket('MarketFresh1111111111111111111111111111', 0n),
+        scheduledMarket('MarketStale1111111111111111111111111111', 400n),
+      ] as any);
+      await crankService.discover();
+
+      vi.mocked(shared.sendWithRetryKeeper).mockResolvedValue('scheduled-sig');
+      const cuBudget = shared.config.crankCuBudget;
+      (shared.config as any).crankCuBudget = 200_000; // room for one crank
+      try {
+        const result = await crankService.crankAll();
+
+        expect(result).toEqual({ success: 1, failed: 0, skipped: 1 });
+        expect(shared.sendWithRetryKeeper).toHaveBeenCalledTimes(1);
+        expect(crankService.getMarkets().get('MarketStale1111111111111111111111111111')?.successCount).toBe(1);
+        expect(crankService.getMarkets().get('MarketStale1111111111111111111111111111')?.lastCrankSlot).toBe(300_000_000n);
+        expect(crankService.getLastPlan()?.deferred.map((p) => p.slabAddress)).toEqual(['MarketFresh1111111111111111111111111111']);
+      } finally {
+        (shared.config as any).crankCuBudget = cuBudget;
+      }
+    });
+
+    it('only cranks markets this keeper owns when coordinated with peers', async () => {
+      vi.mocked(core.discoverMarkets).mockResolvedValue([
+        scheduledMarket('MarketMine1111111111111111111111111111', 400n),
+        scheduledMarket('MarketPeer1111111111111111111111111111', 400n),
+      ] as any);
+      await crankService.discover();
+      crankService.setCoordinator({
+        owns: (slab: string) => slab === 'MarketMine1111111111111111111111111111',
+        acquireOracleLeadership: vi.fn(async () => true),
+      } as any);
+
+      vi.mocked(shared.sendWithRetryKeeper).mockResolvedValue('owned-sig');
+      const result = await crankService.crankAll();
+
+      expect(result).toEqual({ success: 1, failed: 0, skipped: 1 });
+      expect(crankService.getMarkets().get('MarketPeer1111111111111111111111111111')?.successCount).toBe(0);
+      expect([...crankService.getOwnedMarkets().keys()]).toEqual(['MarketMine1111111111111111111111111111']);
+    });
+  });
+
   describe('start and stop', () => {
     it('should start timer and perform initial discovery', async () => {
       vi.mocked(core.discoverMarkets).mockResolvedValue([]);
@@ -0,0 +1,120 @@
+import { describe, it, expect, vi } from 'vitest';
+
+vi.mock('@percolator/sdk', () => ({
+  checkPhaseTransition: vi.fn(() => [0, false]),
+}));
+
+import {
+  predictLiquidation,
+  planLiquidations,
+  estimateLiquidationCost,
+  type LiquidationRiskParams,
+  type MarketLiquidations,
+  type PlannableLiquidation,
+} from '../../src/services/liquidation-planner.js';
+
+const SLOT = 300_000_000n;
+
+// 5% maintenance + 1% buffer, 1% fee
+const params: LiquidationRiskParams = {
+  maintenanceMarginBps: 500n,
+  liquidationBufferBps: 100n,
+  liquidationFeeBps: 100n,
+  liquidationFeeCap: 0n,
+  minLiquidationAbs: 0n,
+};
+
+describe('predictLiquidation', () => {
+  it('closes just enough notional to restore maintenance + buffer', () => {
+    // 4% equity on 10,000 notional: close c so that 400 − 0.01c = 0.06 (10,000 − c)
+    const p = predictLiquidation({ equity: 400n, notional: 10_000n }, params, SLOT);
+
+    expect(p.outcome).toBe('partial');
+    expect(p.closeNotional).toBe(4_000n);
+    expect(p.expectedFee).toBe(40n);
+    // Remaining equity exactly meets the target ratio on the remaining notional
+    expect(400n - p.expectedFee).toBe(((10_000n - p.closeNotional) * 600n) / 10_000n);
+  });
+
+  it('caps the fee at liquidationFeeCap', () => {
+    const p = predictLiquidation({ equity: 400n, notional: 10_000n }, { ...params, liquidationFeeCap: 25n }, SLOT);
+
+    expect(p.expectedFee).toBe(25n);
+    expect(p.feeCapped).toBe(true);
+  });
+
+  it('closes in full when the remainder would be below minLiquidationAbs', () => {
+    const p = predictLiquidation({ equity: 400n, notional: 10_000n }, { ...params, minLiquidationAbs: 7_000n }, SLOT);
+
+    expect(p).toMatchObject({ outcome: 'full', fullReason: 'below-min', closeNotional: 10_000n, expectedFee: 100n });
+  });
+
+  it('closes in full when already partially liquidated this slot', () => {
+    const p = predictLiquidation({ equity: 400n, notional: 10_000n }, params, SLOT, SLOT);
+
+    expect(p).toMatchObject({ outcome: 'full', fullReason: 'partial-this-slot' });
+    expect(predictLiquidation({ equity: 400n, notional: 10_000n }, params, SLOT, SLOT - 1n).outcome).toBe('partial');
+  });
+
+  it('closes in full without a buffer and earns nothing when underwater', () => {
+    expect(predictLiquidation({ equity: 400n, notional: 10_000n }, { ...params, liquidationBufferBps: 0n }, SLOT))
+      .toMatchObject({ outcome: 'full', fullReason: 'no-buffer' });
+    expect(predictLiquidation({ equity: -50n, notional: 10_000n }, params, SLOT))
+      .toMatchObject({ outcome: 'full', fullReason: 'underwater', expectedFee: 0n });
+  });
+});
+
+describe('planLiquidations', () => {
+  function candidate(accountIdx: number, valueAtRisk: bigint, expectedFee: bigint, underwater = false): PlannableLiquidation {
+    return {
+      accountIdx,
+      valueAtRisk,
+      prediction: {
+        outcome: underwater ? 'full' : 'partial',
+        closeNotional: 0n,
+        expectedFee,
+        feeCapped: false,
+        fullReason: underwater ? 'underwater' : undefined,
+      },
+    };
+  }
+
+  function market(
+    slabAddress: string,
+    candidates: PlannableLiquidation[],
+    lamportsPerCollateralUnit: number | null = 1,
+  ): MarketLiquidations<PlannableLiquidation> {
+    return { slabAddress, candidates, pushesPrice: false, lamportsPerCollateralUnit };
+  }
+
+  const opts = { priorityFeeMicroLamports: 10_000, maxPerTx: 2 };
+
+  it('packs candidates by value at risk, several per transaction', () => {
+    const plan = planLiquidations([
+      market('A', [candidate(1, 100n, 50_000n), candidate(2, 300n, 50_000n), candidate(3, 200n, 50_000n)]),
+    ], opts);
+
+    expect(plan.batches.map((b) => b.candidates.map((c) => c.accountIdx))).toEqual([[2, 3], [1]]);
+    expect(plan.batches[0]!).toMatchObject(estimateLiquidationCost(2, false, opts.priorityFeeMicroLamports));
+    expect(plan.unprofitable).toHaveLength(0);
+  });
+
+  it('sends batches whose fees do not cover the cost unless the profit gate is on', () => {
+    const plan = planLiquidations([market('cheap', [candidate(1, 10n, 10n)])], opts);
+
+    expect(plan.batches.map((b) => b.slabAddress)).toEqual(['cheap']);
+    expect(plan.unprofitable).toHaveLength(0);
+  });
+
+  it('with the profit gate, holds back unprofitable batches but never underwater ones', () => {
+    const plan = planLiquidations([
+      market('cheap', [candidate(1, 10n, 10n)]),
+      market('underwater', [candidate(2, 5_000n, 0n, true)]),
+      market('unvalued', [candidate(3, 10n, 10n)], null),
+    ], { ...opts, profitGate: true });
+
+    expect(plan.unprofitable.map((b) => b.slabAddress)).toEqual(['cheap']);
+    expect(plan.batches.map((b) => b.slabAddress)).toEqual(['underwater', 'unvalued']);
+    expect(plan.batches[1]!.expectedFeeLamports).toBeNull();
+  });
+});
@@ -38,8 +38,9 @@ vi.mock('@solana/web3.js', async () => {
   };
 });
 
-// Mock external dependencies
-vi.mock('@percolator/sdk', () => ({
+// Mock external dependencies — health math stays real so scans exercise it
+vi.mock('@percolator/sdk', async () => ({
+  computeAccountHealth: (await vi.importActual<typeof import('@percolator/sdk')>('@percolator/sdk')).computeAccountHealth,
   fetchSlab: vi.fn(),
   parseConfig: vi.fn(),
   parseEngine: vi.fn(),
@@ -62,6 +63,7 @@ vi.mock('@percolator/sdk', () => ({
 vi.mock('@percolator/shared', () => ({
   config: {
     crankKeypair: 'mock-keypair-path',
+    liquidationMaxPerTx: 6,
   },
   createLogger: vi.fn(() => ({
     info: vi.fn(),
@@ -69,9 +71,11 @@ vi.mock('@percolator/shared', () => ({
     error: vi.fn(),
     debug: vi.fn(),
   })),
-  sendWarningAlert: vi.fn(),
+  sendWarningAlert: vi.fn(async () => {}),
+  sendCriticalAlert: vi.fn(async () => {}),
   getConnection: vi.fn(() => ({
     getAccountInfo: vi.fn(),
+    getSlot: vi.fn(async () => 300_000_000),
     getLatestBlockhash: vi.fn(async () => ({
       blockhash: 'mock-blockhash',
       lastValidBlockHeight: 1000000,
@@ -143,6 +147,14 @@ function mockZeroKey() {
   };
 }
 
+// V1 liquidation params: 1% fee, no buffer (full closes)
+const LIQ_PARAMS = {
+  liquidationFeeBps: 100n,
+  liquidationFeeCap: 0n,
+  liquidationBufferBps: 0n,
+  minLiquidationAbs: 0n,
+};
+
 function mockNonZeroKey(base58 = 'NonZero1111111111111111111111111111111111') {
   return {
     toBase58: () => base58,
@@ -207,12 +219,20 @@ describe('LiquidationService', () => {
       vi.mocked(core.fetchSlab).mockResolvedValue(mockSlabData);
       vi.mocked(core.parseEngine).mockReturnValue({
         totalOpenInterest: 100_000_000n,
+        fundingIndexQpbE6: 0n,
+        currentSlot: 0n,
         numUsedAccounts: 1,
         vault: 1000_000n,
-        insuranceFund: { balance: 500_000n, feeRevenue: 0n },
+        insuranceFund: { balance: 500_000n, feeRevenue: 0n, isolatedBalance: 0n, isolationBps: 0 },
+        emergencyOiMode: true,
+        longOi: 100_000_000n,
+        shortOi: 0n,
       } as any);
       vi.mocked(core.parseParams).mockReturnValue({
         maintenanceMarginBps: 500n,
+        initialMarginBps: 1000n,
+        warmupPeriodSlots: 0n,
+        ...LIQ_PARAMS,
       } as any);
       vi.mocked(core.parseConfig).mockReturnValue({
         oracleAuthority: mockNonZeroKey(),
@@ -232,6 +252,9 @@ describe('LiquidationService', () => {
         positionSize: 10_000_000_000n, // 10,000 units (6 decimals)
         capital: 100_000_000n, // 100 USDC
         entryPrice: 1_000_000n,
+        pnl: 0n,
+        fundingIndex: 0n,
+        warmupStartedAtSlot: 0n,
       } as any);
 
       const candidates = await liquidationService.scanMarket(mockMarket as any);
@@ -239,6 +262,16 @@ describe('LiquidationService', () => {
       expect(candidates).toHaveLength(1);
       expect(candidates[0].accountIdx).toBe(0);
       expect(candidates[0].marginRatio).toBeLessThan(5); // Below 5%
+
+      // Worst headroom is fed to the crank scheduler: 1% equity − 5% maintenance
+      const risk = liquidationService.getMarketRisk('Market111111111111111111111111111111111');
+      expect(risk?.worstMarginHeadroomBps).toBe(-400);
+      expect(risk?.engine.totalOpenInterest).toBe(100_000_000n);
+
+      // The scan also assesses ADL: the OI breaker is set, so a plan is kept
+      const adl = liquidationService.getAdlPlan('Market111111111111111111111111111111111');
+      expect(adl?.plan.assessment.triggers).toEqual(['emergency-oi']);
+      expect(adl?.plan.side).toBe('long');
     });
 
     it('should find undercollateralized accounts in Pyth-pinned oracle mode', async () => {
@@ -259,12 +292,20 @@ describe('LiquidationService', () => {
       vi.mocked(core.fetchSlab).mockResolvedValue(mockSlabData);
       vi.mocked(core.parseEngine).mockReturnValue({
         totalOpenInterest: 100_000_000n,
+        fundingIndexQpbE6: 0n,
+        currentSlot: 0n,
         numUsedAccounts: 1,
         vault: 1000_000n,
-        insuranceFund: { balance: 500_000n, feeRevenue: 0n },
+        insuranceFund: { balance: 500_000n, feeRevenue: 0n, isolatedBalance: 0n, isolationBps: 0 },
+        emergencyOiMode: false,
+        longOi: 100_000_000n,
+        shortOi: 0n,
       } as any);
       vi.mocked(core.parseParams).mockReturnValue({
         maintenanceMarginBps: 500n,
+        initialMarginBps: 1000n,
+        warmupPeriodSlots: 0n,
+        ...LIQ_PARAMS,
       } as any);
       // Pyth-pinned: oracleAuthority = zero, indexFeedId = non-zero
       vi.mocked(core.parseConfig).mockReturnValue({
@@ -284,6 +325,9 @@ describe('LiquidationService', () => {
         positionSize: 10_000_000_000n,
         capital: 100_000_000n,
         entryPrice: 1_000_000n,
+        pnl: 0n,
+        fundingIndex: 0n,
+        warmupStartedAtSlot: 0n,
       } as any);
 
       const candidates = await liquidationService.scanMarket(mockMarket as any);
@@ -291,6 +335,8 @@ describe('LiquidationService', () => {
       expect(candidates).toHaveLength(1);
       expect(candidates[0].accountIdx).toBe(0);
       expect(candidates[0].marginRatio).toBeLessThan(5); // Below 5% maintenance
+      // Solvent and no OI breaker: assessed, nothing to deleverage
+      expect(liquidationService.getAdlPlan('MarketPyth1111111111111111111111111111')).toBeUndefined();
     });
 
     it('should use staleness fallback for admin oracle in scanMarket', async () => {
@@ -311,9 +357,14 @@ describe('LiquidationService', () => {
       vi.mocked(core.fetchSlab).mockResolvedValue(mockSlabData);
       vi.mocked(core.parseEngine).mockReturnValue({
         totalOpenInterest: 100_000_000n,
+        fundingIndexQpbE6: 0n,
+        currentSlot: 0n,
       } as any);
       vi.mocked(core.parseParams).mockReturnValue({
         maintenanceMarginBps: 500n,
+        initialMarginBps: 1000n,
+        warmupPeriodSlots: 0n,
+        ...LIQ_PARAMS,
       } as any);
       // Admin oracle with stale authority but valid lastEffectivePriceE6
       vi.mocked(core.parseConfig).mockReturnValue({
@@ -333,6 +384,9 @@ describe('LiquidationService', () => {
         positionSize: 10_000_000_000n,
         capital: 100_000_000n,
         entryPrice: 1_000_000n,
+        pnl: 0n,
+        fundingIndex: 0n,
+        warmupStartedAtSlot: 0n,
       } as any);
 
       const candidates = await liquidationService.scanMarket(mockMarket as any);
@@ -362,9 +416,14 @@ describe('LiquidationService', () => {
       vi.mocked(core.fetchSlab).mockResolvedValue(mockSlabData);
       vi.mocked(core.parseEngine).mockReturnValue({
         totalOpenInterest: 100_000_000n,
+        fundingIndexQpbE6: 0n,
+        currentSlot: 0n,
       } as any);
       vi.mocked(core.parseParams).mockReturnValue({
         maintenanceMarginBps: 500n,
+        initialMarginBps: 1000n,
+        warmupPeriodSlots: 0n,
+        ...LIQ_PARAMS,
       } as any);
       vi.mocked(core.parseConfig).mockReturnValue({
         oracleAuthority: mockNonZeroKey(),
@@ -379,6 +438,56 @@ describe('LiquidationService', () => {
 
       expect(candidates).toHaveLength(0); // Skipped due to stale price and no fallback
     });
+
+    it('plans ADL against profitable shorts when bad debt outruns the insurance fund', async () => {
+      const mockMarket = {
+        slabAddress: { toBase58: () => 'MarketAdl11111111111111111111111111111' },
+        programId: { toBase58: () => 'Program11111111111111111111111111111111' },
+        config: { indexFeedId: { toBytes: () => new Uint8Array(32) } },
+      };
+
+      vi.mocked(core.fetchSlab).mockResolvedValue(new Uint8Array(1024));
+      vi.mocked(core.parseEngine).mockReturnValue({
+        totalOpenInterest: 15_000_000_000n,
+        fundingIndexQpbE6: 0n,
+        currentSlot: 0n,
+        insuranceFund: { balance: 50_000_000n, feeRevenue: 0n, isolatedBalance: 0n, isolationBps: 0 },
+        emergencyOiMode: false,
+        longOi: 10_000_000_000n,
+        shortOi: 5_000_000_000n,
+      } as any);
+      vi.mocked(core.parseParams).mockReturnValue({
+        maintenanceMarginBps: 500n,
+        initialMarginBps: 1000n,
+        warmupPeriodSlots: 0n,
+        ...LIQ_PARAMS,
+      } as any);
+      vi.mocked(core.parseConfig).mockReturnValue({
+        oracleAuthority: mockNonZeroKey(),
+        indexFeedId: mockZeroKey(), // Hyperp mode
+        authorityPriceE6: 1_000_000n,
+        lastEffectivePriceE6: 1_000_000n,
+        authorityTimestamp: BigInt(Math.floor(Date.now() / 1000)),
+      } as any);
+      vi.mocked(core.detectLayout).mockReturnValue({ accountsOffset: 0 } as any);
+      vi.mocked(core.parseUsedIndices).mockReturnValue([0, 1]);
+      const base = { kind: 0, pnl: 0n, fundingIndex: 0n, warmupStartedAtSlot: 0n };
+      vi.mocked(core.parseAccount)
+        // Long, 200 USDC past bankruptcy
+        .mockReturnValueOnce({ ...base, owner: { toBase58: () => 'Long' }, positionSize: 10_000_000_000n, capital: 100_000_000n, pnl: -300_000_000n, entryPrice: 1_000_000n } as any)
+        // Short opened at $1.20, now in profit
+        .mockReturnValueOnce({ ...base, owner: { toBase58: () => 'Short' }, positionSize: -5_000_000_000n, capital: 500_000_000n, entryPrice: 1_200_000n } as any);
+
+      await liquidationService.scanMarket(mockMarket as any);
+
+      const adl = liquidationService.getAdlPlan('MarketAdl11111111111111111111111111111');
+      expect(adl?.plan.assessment).toMatchObject({ badDebt: 200_000_000n, shortfall: 150_000_000n });
+      expect(adl?.plan.side).toBe('short');
+      expect(adl?.plan.selected.map((c) => c.accountIdx)).toEqual([1]);
+      expect(adl?.oracle).toBe('MarketAdl11111111111111111111111111111');
+      expect(shared.sendCriticalAlert).toHaveBeenCalledTimes(1);
+      expect(shared.eventBus.publish).toHaveBeenCalledWith('adl.triggered', 'MarketAdl11111111111111111111111111111', expect.objectContaining({ side: 'short' }));
+    });
   });
 
   describe('liquidate', () => {
@@ -398,8 +507,8 @@ describe('LiquidationService', () => {
       const mockSlabData = new Uint8Array(1024);
 
       vi.mocked(core.fetchSlab).mockResolvedValue(mockSlabData);
-      vi.mocked(core.parseEngine).mockReturnValue({} as any);
-      vi.mocked(core.parseParams).mockReturnValue({ maintenanceMarginBps: 500n } as any);
+      vi.mocked(core.parseEngine).mockReturnValue({ fundingIndexQpbE6: 0n, currentSlot: 0n } as any);
+      vi.mocked(core.parseParams).mockReturnValue({ maintenanceMarginBps: 500n, initialMarginBps: 1000n, warmupPeriodSlots: 0n, ...LIQ_PARAMS } as any);
       vi.mocked(core.parseConfig).mockReturnValue({
         oracleAuthority: mockNonZeroKey(),
         indexFeedId: mockZeroKey(), // Hyperp mode
@@ -414,6 +523,9 @@ describe('LiquidationService', () => {
         positionSize: 10_000_000_000n,
         capital: 1_000_000n,
         entryPrice: 1_000_000n,
+        pnl: 0n,
+        fundingIndex: 0n,
+        warmupStartedAtSlot: 0n,
       } as any);
 
       const signature = await liquidationService.liquidate(mockMarket as any, 0);
@@ -440,8 +552,8 @@ describe('LiquidationService', () => {
       };
 
       vi.mocked(core.fetchSlab).mockResolvedValue(new Uint8Array(1024));
-      vi.mocked(core.parseEngine).mockReturnValue({} as any);
-      vi.mocked(core.parseParams).mockReturnValue({ maintenanceMarginBps: 500n } as any);
+      vi.mocked(core.parseEngine).mockReturnValue({ fundingIndexQpbE6: 0n, currentSlot: 0n } as any);
+      vi.mocked(core.parseParams).mockReturnValue({ maintenanceMarginBps: 500n, initialMarginBps: 1000n, warmupPeriodSlots: 0n, ...LIQ_PARAMS } as any);
       vi.mocked(core.parseConfig).mockReturnValue({
         oracleAuthority: mockNonZeroKey(),
         indexFeedId: mockZeroKey(), // Hyperp mode
@@ -456,6 +568,9 @@ describe('LiquidationService', () => {
         positionSize: 10_000_000_000n,
         capital: 1_000_000n,
         entryPrice: 1_000_000n,
+        pnl: 0n,
+        fundingIndex: 0n,
+        warmupStartedAtSlot: 0n,
       } as any);
 
       const statusBefore = liquidationService.getStatus();
@@ -467,6 +582,110 @@ describe('LiquidationService', () => {
     });
   });
 
+  describe('scanAndLiquidateAll', () => {
+    function setupMarket(slabAddress: string, usedIndices: number[]) {
+      vi.mocked(core.fetchSlab).mockResolvedValue(new Uint8Array(1024));
+      vi.mocked(core.parseEngine).mockReturnValue({ totalOpenInterest: 1n, fundingIndexQpbE6: 0n, currentSlot: 0n } as any);
+      vi.mocked(core.parseParams).mockReturnValue({ maintenanceMarginBps: 500n, initialMarginBps: 1000n, warmupPeriodSlots: 0n, ...LIQ_PARAMS } as any);
+      vi.mocked(core.parseConfig).mockReturnValue({
+        oracleAuthority: mockNonZeroKey(),
+        indexFeedId: mockZeroKey(), // Hyperp mode
+        authorityPriceE6: 1_000_000n,
+        lastEffectivePriceE6: 1_000_000n,
+        authorityTimestamp: BigInt(Math.floor(Date.now() / 1000)),
+      } as any);
+      vi.mocked(core.detectLayout).mockReturnValue({ accountsOffset: 0 } as any);
+      vi.mocked(core.parseUsedIndices).mockReturnValue(usedIndices);
+      // 1% equity on 10,000 notional — well past 5% maintenance
+      vi.mocked(core.parseAccount).mockReturnValue({
+        kind: 0,
+        owner: { toBase58: () => 'User5111111111111111111111111111111111111' },
+        positionSize: 10_000_000_000n,
+        capital: 100_000_000n,
+        entryPrice: 1_000_000n,
+        pnl: 0n,
+        fundingIndex: 0n,
+        warmupStartedAtSlot: 0n,
+      } as any);
+      const market = {
+        slabAddress: { toBase58: () => slabAddress },
+        programId: { toBase58: () => 'Program11111111111111111111111111111111' },
+        config: {
+          collateralMint: { toBase58: () => 'So11111111111111111111111111111111111111112' },
+          oracleAuthority: mockNonZeroKey(),
+          indexFeedId: mockZeroKey(),
+        },
+        params: { maintenanceMarginBps: 500n },
+        header: { admin: { toBase58: () => 'Admin111111111111111111111111111111111' } },
+      };
+      return new Map([[slabAddress, { market: market as any }]]);
+    }
+
+    it('liquidates a market\'s accounts in one batched transaction', async () => {
+      const markets = setupMarket('MarketBatch1111111111111111111111111111', [0, 1]);
+      vi.mocked(shared.sendWithRetryKeeper).mockResolvedValue('batch-signature');
+
+      const result = await liquidationService.scanAndLiquidateAll(markets);
+
+      expect(result).toEqual({ scanned: 1, candidates: 2, liquidated: 2, unprofitable: 0 });
+      expect(shared.sendWithRetryKeeper).toHaveBeenCalledTimes(1);
+      expect(core.encodeLiquidateAtOracle).toHaveBeenNthCalledWith(1, { targetIdx: 0 });
+      expect(core.encodeLiquidateAtOracle).toHaveBeenNthCalledWith(2, { targetIdx: 1 });
+      expect(shared.eventBus.publish).toHaveBeenCalledWith(
+        'liquidation.success',
+        'MarketBatch1111111111111111111111111111',
+        expect.objectContaining({ accountIdx: 1, signature: 'batch-signature', outcome: 'full' }),
+      );
+    });
+
+    it('sends liquidations whose fee does not cover the transaction cost by default', async () => {
+      const markets = setupMarket('MarketCheap1111111111111111111111111111', [0]);
+      // Collateral worth a billionth of a lamport per unit: the 1% fee is dust next to the tx cost
+      liquidationService.setCollateralValuer(async () => 1e-9);
+      vi.mocked(shared.sendWithRetryKeeper).mockResolvedValue('cheap-signature');
+
+      const result = await liquidationService.scanAndLiquidateAll(markets);
+
+      expect(result).toMatchObject({ candidates: 1, liquidated: 1, unprofitable: 0 });
+      expect(shared.sendWithRetryKeeper).toHaveBeenCalledTimes(1);
+    });
+
+    describe('with the profit gate on', () => {
+      beforeEach(() => {
+        (shared.config as any).liquidationProfitGate = true;
+      });
+      afterEach(() => {
+        (shared.config as any).liquidationProfitGate = false;
+      });
+
+      it('holds back liquidations whose fee does not cover the transaction cost', async () => {
+        const markets = setupMarket('MarketCheap1111111111111111111111111111', [0]);
+        liquidationService.setCollateralValuer(async () => 1e-9);
+
+        const result = await liquidationService.scanAndLiquidateAll(markets);
+
+        expect(result).toMatchObject({ candidates: 1, liquidated: 0, unprofitable: 1 });
+        expect(shared.sendWithRetryKeeper).not.toHaveBeenCalled();
+      });
+
+      it('alerts once an account stays held back across scans', async () => {
+        const markets = setupMarket('MarketCheap1111111111111111111111111111', [0]);
+        liquidationService.setCollateralValuer(async () => 1e-9);
+        const heldBackAlerts = () => vi.mocked(shared.sendWarningAlert).mock.calls
+          .filter(([title]) => title === 'Liquidatable account held back by profit gate');
+
+        await liquidationService.scanAndLiquidateAll(markets);
+        await liquidationService.scanAndLiquidateAll(markets);
+        expect(heldBackAlerts()).toHaveLength(0);
+
+        await liquidationService.scanAndLiquidateAll(markets);
+        await liquidationService.scanAndLiquidateAll(markets);
+        expect(heldBackAlerts()).toHaveLength(1);
+        expect(heldBackAlerts()[0]![1]).toContainEqual(expect.objectContaining({ name: 'Account', value: '0' }));
+      });
+    });
+  });
+
   describe('start and stop', () => {
     it('should start and stop timer', () => {
       const markets = new Map();
@@ -498,8 +717,8 @@ describe('LiquidationService', () => {
         new Error('Transaction simulation failed: custom program error: 0x4'),
       );
       vi.mocked(core.fetchSlab).mockResolvedValue(new Uint8Array(1024));
-      vi.mocked(core.parseEngine).mockReturnValue({} as any);
-      vi.mocked(core.parseParams).mockReturnValue({ maintenanceMarginBps: 500n } as any);
+      vi.mocked(core.parseEngine).mockReturnValue({ fundingIndexQpbE6: 0n, currentSlot: 0n } as any);
+      vi.mocked(core.parseParams).mockReturnValue({ maintenanceMarginBps: 500n, initialMarginBps: 1000n, warmupPeriodSlots: 0n, ...LIQ_PARAMS } as any);
       vi.mocked(core.parseConfig).mockReturnValue({
         oracleAuthority: mockNonZeroKey(),
         indexFeedId: mockZeroKey(),
@@ -514,6 +733,9 @@ describe('LiquidationService', () => {
         positionSize: 10_000_000_000n,
         capital: 1_000_000n,
         entryPrice: 1_000_000n,
+        pnl: 0n,
+        fundingIndex: 0n,
+        warmupStartedAtSlot: 0n,
       } as any);
 
       const result = await liquidationService.liquidate(mockMarket as any, 1);
@@ -547,8 +769,8 @@ describe('LiquidationService', () => {
         new Error('custom program error: 0x4'),
       );
       vi.mocked(core.fetchSlab).mockResolvedValue(new Uint8Array(1024));
-      vi.mocked(core.parseEngine).mockReturnValue({} as any);
-      vi.mocked(core.parseParams).mockReturnValue({ maintenanceMarginBps: 500n } as any);
+      vi.mocked(core.parseEngine).mockReturnValue({ fundingIndexQpbE6: 0n, currentSlot: 0n } as any);
+      vi.mocked(core.parseParams).mockReturnValue({ maintenanceMarginBps: 500n, initialMarginBps: 1000n, warmupPeriodSlots: 0n, ...LIQ_PARAMS } as any);
       vi.mocked(core.parseConfig).mockReturnValue({
         oracleAuthority: mockNonZeroKey(),
         indexFeedId: mockZeroKey(),
@@ -563,6 +785,9 @@ describe('LiquidationService', () => {
         positionSize: 10_000_000_000n,
         capital: 1_000_000n,
         entryPrice: 1_000_000n,
+        pnl: 0n,
+        fundingIndex: 0n,
+        warmupStartedAtSlot: 0n,
       } as any);
 
       // First liquidation attempt → 0x4 → marked as permanently skipped
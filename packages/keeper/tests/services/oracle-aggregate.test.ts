@@ -0,0 +1,89 @@
+import { describe, it, expect } from 'vitest';
+import { aggregatePrices, weightedMedian } from '../../src/services/oracle-aggregate.js';
+
+const q = (source: string, priceE6: bigint | null, confidence = 1) => ({
+  source,
+  quote: priceE6 === null ? null : { source, priceE6, confidence },
+});
+
+describe('weightedMedian', () => {
+  it('returns the price where cumulative weight reaches half', () => {
+    expect(weightedMedian([
+      { priceE6: 300n, weight: 1 },
+      { priceE6: 100n, weight: 1 },
+      { priceE6: 200n, weight: 1 },
+    ])).toBe(200n);
+  });
+
+  it('is pulled toward heavier quotes', () => {
+    expect(weightedMedian([
+      { priceE6: 100n, weight: 0.2 },
+      { priceE6: 200n, weight: 0.2 },
+      { priceE6: 300n, weight: 0.9 },
+    ])).toBe(300n);
+  });
+});
+
+describe('aggregatePrices', () => {
+  const weights = { dex: 1, jup: 0.8, pool: 1, pyth: 1 };
+
+  it('rejects outliers and reports them in provenance', () => {
+    const result = aggregatePrices(
+      [q('dex', 1_000_000n), q('jup', 1_010_000n), q('pool', 990_000n), q('pyth', 2_000_000n)],
+      weights,
+      undefined,
+      1_700_000_000_000,
+    );
+
+    expect(result.ok).toBe(true);
+    if (!result.ok) return;
+    expect(result.priceE6).toBe(1_000_000n);
+    expect(result.provenance.aggregatedAt).toBe(1_700_000_000_000);
+    expect(result.provenance.sources.map((s) => [s.source, s.status])).toEqual([
+      ['dex', 'used'],
+      ['jup', 'used'],
+      ['pool', 'used'],
+      ['pyth', 'outlier'],
+    ]);
+    expect(result.provenance.confidence).toBeCloseTo(2.8 / 3.8);
+  });
+
+  it('scales each quote by its confidence', () => {
+    const result = aggregatePrices([q('dex', 1_000_000n, 0.3), q('pool', 1_050_000n, 0.9)], weights);
+
+    expect(result.ok && result.priceE6).toBe(1_050_000n);
+    expect(result.provenance.sources[0]).toMatchObject({ status: 'used', weight: 0.3 });
+  });
+
+  it('refuses to price two sources that disagree', () => {
+    const result = aggregatePrices([q('dex', 1_000_000n), q('jup', 1_500_000n)], weights);
+
+    expect(result.ok).toBe(false);
+    if (result.ok) return;
+    expect(result.reason).toMatch(/no quorum/);
+  });
+
+  it('requires agreeing sources to hold a majority of the weight', () => {
+    // Two low-confidence quotes agree; the heavy quote sits alone
+    const result = aggregatePrices(
+      [q('dex', 1_000_000n, 0.3), q('jup', 1_000_000n, 0.3), q('pyth', 2_000_000n, 1)],
+      weights,
+    );
+
+    expect(result.ok).toBe(false);
+  });
+
+  it('accepts a single responding source', () => {
+    const result = aggregatePrices([q('dex', null), q('jup', null), q('pool', 1_234_000n, 0.7)], weights);
+
+    expect(result.ok && result.priceE6).toBe(1_234_000n);
+    expect(result.provenance.sources.map((s) => s.status)).toEqual(['unavailable', 'unavailable', 'used']);
+  });
+
+  it('fails when nothing responds', () => {
+    const result = aggregatePrices([q('dex', null), q('jup', 0n)], weights);
+
+    expect(result.ok).toBe(false);
+    expect(result.provenance.sources.every((s) => s.status === 'unavailable')).toBe(true);
+  });
+});
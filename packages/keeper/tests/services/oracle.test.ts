@@ -10,6 +10,12 @@ vi.mock('@percolator/sdk', () => ({
   buildAccountMetas: vi.fn(() => []),
   buildIx: vi.fn(() => ({})),
   ACCOUNTS_PUSH_ORACLE_PRICE: {},
+  PYTH_SOLANA_FEEDS: {},
+  detectDexType: vi.fn(() => null),
+  parseDexPool: vi.fn(),
+  computeDexSpotPriceE6: vi.fn(),
+  dexRequiresVaultData: vi.fn(() => false),
+  dexSpotPriceIsDecimalAdjusted: vi.fn(() => true),
 }));
 
 vi.mock('@percolator/shared', () => ({
@@ -38,6 +44,7 @@ vi.mock('@percolator/shared', () => ({
 
 import { OracleService } from '../../src/services/oracle.js';
 import * as shared from '@percolator/shared';
+import * as core from '@percolator/sdk';
 
 describe('OracleService', () => {
   let oracleService: OracleService;
@@ -489,6 +496,118 @@ describe('OracleService', () => {
     });
   });
 
+  describe('median aggregation', () => {
+    const stub = (name: string, price: bigint | null, confidence = 1) => ({
+      name,
+      weight: 1,
+      quote: vi.fn(async () => (price === null ? null : { source: name, priceE6: price, confidence })),
+    });
+
+    it('should drop an outlier source and price on the agreeing majority', async () => {
+      const service = new OracleService({
+        sources: [stub('a', 1_000_000n), stub('b', 1_020_000n), stub('c', 5_000_000n)],
+      });
+
+      const entry = await service.fetchPrice('MINT_MEDIAN', 'SLAB_MEDIAN');
+
+      expect(entry?.priceE6).toBe(1_000_000n);
+      expect(entry?.source).toBe('median');
+      expect(entry?.provenance?.sources.map((s) => s.status)).toEqual(['used', 'used', 'outlier']);
+      expect(entry?.confidence).toBeCloseTo(2 / 3);
+    });
+
+    it('should keep pricing when one source is down', async () => {
+      const failing = { name: 'http', weight: 1, quote: vi.fn(async () => { throw new Error('503'); }) };
+      const service = new OracleService({ sources: [failing, stub('pool', 2_000_000n, 0.7)] });
+
+      const entry = await service.fetchPrice('MINT_OUTAGE', 'SLAB_OUTAGE');
+
+      expect(entry?.priceE6).toBe(2_000_000n);
+      expect(entry?.source).toBe('pool');
+      expect(entry?.provenance?.sources[0]).toEqual({ source: 'http', status: 'unavailable', weight: 0 });
+    });
+
+    it('should attach provenance to the price.updated event', async () => {
+      const service = new OracleService({ sources: [stub('a', 1_000_000n), stub('b', 1_010_000n)] });
+      const mockMarketConfig: any = {
+        collateralMint: new PublicKey('So11111111111111111111111111111111111111112'),
+        oracleAuthority: new PublicKey('11111111111111111111111111111111'),
+        authorityPriceE6: 0n,
+      };
+      const slab = '7cVrFyTHjDvZ4tkSeBJyXy6YYnPKEMk5AxbGbGzJKJhC';
+
+      expect(await service.pushPrice(slab, mockMarketConfig)).toBe(true);
+
+      expect(shared.eventBus.publish).toHaveBeenCalledWith('price.updated', slab, expect.objectContaining({
+        priceE6: '1000000',
+        source: 'median',
+        provenance: expect.objectContaining({
+          method: 'weighted-median',
+          sources: [
+            expect.objectContaining({ source: 'a', status: 'used', priceE6: '1000000' }),
+            expect.objectContaining({ source: 'b', status: 'used', priceE6: '1010000' }),
+          ],
+        }),
+      }));
+    });
+  });
+
+  describe('TWAP mode', () => {
+    const twapEngine = (price: bigint | null) => ({
+      name: 'pool-twap',
+      weight: 1,
+      isTracking: vi.fn(() => true),
+      track: vi.fn(),
+      quote: vi.fn(async () => (price === null ? null : { source: 'pool-twap', priceE6: price, confidence: 1 })),
+    });
+
+    it('should push the TWAP as the oracle price', async () => {
+      const service = new OracleService({ twap: twapEngine(1_234_000n) as any });
+
+      const entry = await service.fetchPrice('MINT_TWAP', 'SLAB_TWAP');
+
+      expect(entry?.priceE6).toBe(1_234_000n);
+      expect(entry?.source).toBe('pool-twap');
+      expect(fetch).not.toHaveBeenCalled();
+    });
+
+    it('should not fall back to an on-chain price when the TWAP refuses', async () => {
+      const service = new OracleService({ twap: twapEngine(null) as any });
+      const mockMarketConfig: any = {
+        collateralMint: new PublicKey('So11111111111111111111111111111111111111112'),
+        oracleAuthority: new PublicKey('11111111111111111111111111111111'),
+        authorityPriceE6: 1_000_000n,
+      };
+
+      const pushed = await service.pushPrice('Ar1ZLiRjVdpmMBhqbFu9QzTqoPuCDuR4zbqKtk8ozPNu', mockMarketConfig);
+
+      expect(pushed).toBe(false);
+      expect(shared.sendWithRetry).not.toHaveBeenCalled();
+    });
+  });
+
+  describe('shadow mode', () => {
+    it('should simulate the push as the live wallet without loading a keypair', async () => {
+      const live = new PublicKey('So11111111111111111111111111111111111111112');
+      const recorder = { payer: live, simulate: vi.fn(async () => '(simulated)') };
+      const quote = (name: string) => ({ name, weight: 1, quote: vi.fn(async () => ({ source: name, priceE6: 1_000_000n, confidence: 1 })) });
+      const service = new OracleService({ sources: [quote('a'), quote('b')] });
+      service.setShadow(recorder as any);
+      const mockMarketConfig: any = {
+        collateralMint: new PublicKey('So11111111111111111111111111111111111111112'),
+        oracleAuthority: live,
+        authorityPriceE6: 0n,
+      };
+
+      expect(await service.pushPrice('7cVrFyTHjDvZ4tkSeBJyXy6YYnPKEMk5AxbGbGzJKJhC', mockMarketConfig)).toBe(true);
+
+      expect(shared.loadKeypair).not.toHaveBeenCalled();
+      expect(shared.sendWithRetry).not.toHaveBeenCalled();
+      expect(recorder.simulate).toHaveBeenCalledTimes(1);
+      expect(core.buildAccountMetas).toHaveBeenCalledWith(expect.anything(), [live, expect.any(PublicKey)]);
+    });
+  });
+
   describe('getCurrentPrice', () => {
     it('should return latest price from history', async () => {
       const mockResponse = {
@@ -0,0 +1,162 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { PublicKey } from '@solana/web3.js';
+
+const POOL = 'FxfD37s1AZTeWfFQps9Zpebi2dNQ9QSSDtfMKdbsfKrD';
+const MINT = 'FwfBKZXbYr4vTK23bMFkbgKq3npJ3MSDxEaKmq9Aj4Qn';
+const USDC = 'EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v';
+const BASE_VAULT = 'g9msRSV3sJmmE3r5Twn9HuBsxzuuRGTjKCVTKudm9in';
+const QUOTE_VAULT = 'SysvarC1ock11111111111111111111111111111111';
+
+vi.mock('@percolator/sdk', () => ({
+  PYTH_SOLANA_FEEDS: {},
+  detectDexType: vi.fn(() => 'pumpswap'),
+  parseDexPool: vi.fn((_type: string, poolAddress: PublicKey) => ({
+    dexType: 'pumpswap',
+    poolAddress,
+    baseMint: new PublicKey(MINT),
+    quoteMint: new PublicKey(USDC),
+    baseVault: new PublicKey(BASE_VAULT),
+    quoteVault: new PublicKey(QUOTE_VAULT),
+  })),
+  // Same as the real PumpSwap path: quote atoms * 1e6 / base atoms
+  computeDexSpotPriceE6: vi.fn((_type: string, _data: Uint8Array, vaults: { base: Uint8Array; quote: Uint8Array }) => {
+    const amount = (d: Uint8Array) => new DataView(d.buffer, d.byteOffset, d.byteLength).getBigUint64(64, true);
+    return (amount(vaults.quote) * 1_000_000n) / amount(vaults.base);
+  }),
+  dexRequiresVaultData: vi.fn((type: string) => type === 'pumpswap'),
+  dexSpotPriceIsDecimalAdjusted: vi.fn(() => false),
+}));
+
+const connection = {
+  getAccountInfo: vi.fn(),
+  getMultipleAccountsInfo: vi.fn(),
+  getMultipleAccountsInfoAndContext: vi.fn(),
+};
+
+vi.mock('@percolator/shared', () => ({
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+  getConnection: vi.fn(() => connection),
+}));
+
+import { PoolTwapEngine, computeTwap, type PoolSample } from '../../src/services/pool-twap.js';
+
+function tokenAccount(amount: bigint): { data: Buffer } {
+  const data = Buffer.alloc(165);
+  data.writeBigUInt64LE(amount, 64);
+  return { data };
+}
+
+function mintAccount(decimals: number): { data: Buffer } {
+  const data = Buffer.alloc(82);
+  data[44] = decimals;
+  return { data };
+}
+
+const sample = (slot: number, priceE6: bigint, mintReserve = 0n, counterReserveE6 = 50_000_000_000n): PoolSample => ({
+  slot,
+  priceE6,
+  counterReserveE6,
+  mintReserve,
+});
+
+describe('computeTwap', () => {
+  it('weights each sample by the slots it was the latest price', () => {
+    // 1.00 held for 90 slots, then a 1-slot spike to 5.00 held for 10
+    const result = computeTwap([sample(100, 1_000_000n), sample(190, 5_000_000n)], 200, { windowSlots: 150, minSamples: 2 });
+
+    expect(result.ok && result.twapE6).toBe(1_400_000n);
+  });
+
+  it('volume-weights by reserve changes between samples', () => {
+    const result = computeTwap(
+      [sample(10, 1_000_000n, 1_000n), sample(20, 2_000_000n, 1_300n), sample(30, 4_000_000n, 1_400n)],
+      30,
+      { windowSlots: 100, minSamples: 3 },
+    );
+
+    expect(result.ok).toBe(true);
+    if (!result.ok) return;
+    // (2.00 * 300 + 4.00 * 100) / 400
+    expect(result.vwapE6).toBe(2_500_000n);
+    expect(result.spanSlots).toBe(20);
+  });
+
+  it('has no VWAP when reserves never moved', () => {
+    const result = computeTwap([sample(10, 1_000_000n, 5n), sample(20, 1_000_000n, 5n)], 20, { windowSlots: 100, minSamples: 2 });
+
+    expect(result.ok && result.vwapE6).toBeNull();
+  });
+
+  it('refuses with too few samples inside the window', () => {
+    const result = computeTwap([sample(10, 1_000_000n), sample(150, 1_000_000n)], 200, { windowSlots: 100, minSamples: 2 });
+
+    expect(result).toEqual({ ok: false, reason: 'insufficient samples: 1/2', samples: 1 });
+  });
+});
+
+describe('PoolTwapEngine', () => {
+  let slot: number;
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    slot = 1_000;
+    connection.getAccountInfo.mockResolvedValue({ owner: new PublicKey(POOL), data: Buffer.alloc(200) });
+    connection.getMultipleAccountsInfo.mockResolvedValue([mintAccount(6), mintAccount(6)]);
+  });
+
+  function poolState(baseAtoms: bigint, quoteAtoms: bigint) {
+    connection.getMultipleAccountsInfoAndContext.mockImplementationOnce(async () => ({
+      context: { slot: (slot += 10) },
+      value: [{ data: Buffer.alloc(200) }, tokenAccount(baseAtoms), tokenAccount(quoteAtoms)],
+    }));
+  }
+
+  it('publishes the USD TWAP once enough samples are in the window', async () => {
+    const engine = new PoolTwapEngine({ windowSlots: 100, minSamples: 3, minDepthUsdE6: 10_000_000_000n }, 1_000, async () => null);
+    engine.track(MINT, [POOL]);
+
+    // 1M tokens against 50k USDC → $0.05
+    poolState(1_000_000_000_000n, 50_000_000_000n);
+    await engine.sampleAll();
+    poolState(1_000_000_000_000n, 50_000_000_000n);
+    await engine.sampleAll();
+    expect(await engine.getPrice(MINT)).toEqual({ ok: false, reason: 'insufficient samples: 2/3' });
+
+    poolState(1_000_000_000_000n, 50_000_000_000n);
+    await engine.sampleAll();
+    const quote = await engine.quote(MINT);
+
+    expect(quote?.priceE6).toBe(50_000n);
+    expect(quote?.detail).toMatchObject({ pool: POOL, samples: 3, depthUsdE6: '50000000000' });
+  });
+
+  it('refuses when the pool is thinner than the depth gate', async () => {
+    const engine = new PoolTwapEngine({ windowSlots: 100, minSamples: 1, minDepthUsdE6: 10_000_000_000n }, 1_000, async () => null);
+    engine.track(MINT, [POOL]);
+
+    // Only $5k of USDC in the pool
+    poolState(100_000_000_000n, 5_000_000_000n);
+    await engine.sampleAll();
+
+    const result = await engine.getPrice(MINT);
+    expect(result.ok).toBe(false);
+    expect(!result.ok && result.reason).toMatch(/insufficient depth/);
+    expect(await engine.quote(MINT)).toBeNull();
+  });
+
+  it('does not record two samples at the same slot', async () => {
+    const engine = new PoolTwapEngine({ windowSlots: 100, minSamples: 2, minDepthUsdE6: 0n }, 1_000, async () => null);
+    engine.track(MINT, [POOL]);
+
+    poolState(1_000_000_000_000n, 50_000_000_000n);
+    expect(await engine.sampleAll()).toBe(1);
+    slot -= 10; // RPC returns the same slot again
+    poolState(1_000_000_000_000n, 50_000_000_000n);
+    expect(await engine.sampleAll()).toBe(0);
+  });
+});
@@ -0,0 +1,154 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { Keypair, PublicKey, TransactionInstruction } from '@solana/web3.js';
+
+vi.mock('@percolator/sdk', () => ({
+  checkPhaseTransition: vi.fn(() => [0, false]),
+  IX_TAG: { KeeperCrank: 5, LiquidateAtOracle: 7, PushOraclePrice: 17 },
+  simulateUnsigned: vi.fn(),
+}));
+
+vi.mock('@percolator/shared', () => ({
+  acquireToken: vi.fn(async () => {}),
+  getConnection: vi.fn(),
+  createLogger: vi.fn(() => ({
+    info: vi.fn(),
+    warn: vi.fn(),
+    error: vi.fn(),
+    debug: vi.fn(),
+  })),
+}));
+
+import * as core from '@percolator/sdk';
+import {
+  ShadowRecorder,
+  SIMULATED_SIGNATURE,
+  decodeKeeperInstruction,
+  diffShadowActions,
+  type KeeperAction,
+} from '../../src/services/shadow.js';
+
+const PROGRAM = Keypair.generate().publicKey;
+const SLAB = Keypair.generate().publicKey;
+const KEEPER = Keypair.generate().publicKey;
+
+function ix(data: number[], programId = PROGRAM): TransactionInstruction {
+  return new TransactionInstruction({
+    programId,
+    keys: [KEEPER, SLAB, PublicKey.default].map((pubkey) => ({ pubkey, isSigner: false, isWritable: false })),
+    data: Buffer.from(data),
+  });
+}
+
+function action(kind: KeeperAction['kind'], slabAddress: string, accountIdx?: number, ok = true): KeeperAction {
+  return { kind, slabAddress, accountIdx, ok, at: 1_000 };
+}
+
+describe('decodeKeeperInstruction', () => {
+  const programs = new Set([PROGRAM.toBase58()]);
+
+  it('decodes cranks, pushes and liquidation targets from the slab program', () => {
+    const accounts = [KEEPER, SLAB];
+
+    expect(decodeKeeperInstruction(programs, PROGRAM, accounts, Uint8Array.of(5, 0xff, 0xff, 0)))
+      .toEqual({ kind: 'crank', slabAddress: SLAB.toBase58() });
+    expect(decodeKeeperInstruction(programs, PROGRAM, accounts, Uint8Array.of(7, 0x2c, 0x01)))
+      .toEqual({ kind: 'liquidation', slabAddress: SLAB.toBase58(), accountIdx: 300 });
+    expect(decodeKeeperInstruction(programs, PROGRAM, accounts, Uint8Array.of(17)))
+      .toMatchObject({ kind: 'oracle-push' });
+  });
+
+  it('ignores other programs and other instructions', () => {
+    const accounts = [KEEPER, SLAB];
+
+    expect(decodeKeeperInstruction(programs, PublicKey.default, accounts, Uint8Array.of(5))).toBeNull();
+    expect(decodeKeeperInstruction(programs, PROGRAM, accounts, Uint8Array.of(6))).toBeNull();
+  });
+});
+
+describe('ShadowRecorder', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it('simulates instead of sending and records the decoded actions', async () => {
+    vi.mocked(core.simulateUnsigned).mockResolvedValueOnce({
+      signature: '(simulated)', slot: 1, err: null, logs: [], unitsConsumed: 120_000,
+    });
+    const recorder = new ShadowRecorder([PROGRAM.toBase58()], KEEPER);
+
+    const sig = await recorder.simulate({} as any, [ix([5, 0xff, 0xff, 0]), ix([7, 3, 0])]);
+
+    expect(sig).toBe(SIMULATED_SIGNATURE);
+    expect(vi.mocked(core.simulateUnsigned).mock.calls[0]![0]).toMatchObject({ feePayer: KEEPER });
+    const { actions, simulations } = recorder.drain(Date.now() + 1);
+    expect(actions.map((a) => [a.kind, a.accountIdx])).toEqual([['crank', undefined], ['liquidation', 3]]);
+    expect(simulations).toEqual([expect.objectContaining({ ok: true, unitsConsumed: 120_000 })]);
+    expect(recorder.drain(Date.now() + 1).actions).toHaveLength(0);
+  });
+
+  it('throws like a failed send, keeping the program failure for 0x4 detection', async () => {
+    vi.mocked(core.simulateUnsigned).mockResolvedValueOnce({
+      signature: '(simulated)',
+      slot: 1,
+      err: 'InvalidSlabLen (0x4)',
+      logs: [`Program ${PROGRAM.toBase58()} failed: custom program error: 0x4`],
+    });
+    const recorder = new ShadowRecorder([PROGRAM.toBase58()], KEEPER);
+
+    await expect(recorder.simulate({} as any, [ix([5, 0xff, 0xff, 0])]))
+      .rejects.toThrow(/InvalidSlabLen \(0x4\).*custom program error: 0x4/);
+    expect(recorder.drain(Date.now() + 1).actions[0]).toMatchObject({ ok: false, error: 'InvalidSlabLen (0x4)' });
+  });
+});
+
+describe('diffShadowActions', () => {
+  it('reports missed and extra liquidations per market', () => {
+    const shadow = {
+      actions: [
+        action('crank', 'A'), action('liquidation', 'A', 1), action('liquidation', 'A', 4),
+        action('crank', 'B'), action('crank', 'B'),
+      ],
+      simulations: [
+        { ok: true, unitsConsumed: 100_000, at: 1_000 },
+        { ok: true, unitsConsumed: 300_000, at: 1_000 },
+        { ok: false, error: 'OracleStale (0xd)', at: 1_000 },
+      ],
+    };
+    const live = [
+      action('crank', 'A'), action('liquidation', 'A', 1), action('liquidation', 'A', 2),
+      action('crank', 'B'), action('crank', 'B'), action('oracle-push', 'B'),
+    ];
+
+    const report = diffShadowActions(shadow, live, 0, 2_000);
+
+    expect(report).toMatchObject({
+      simulations: 3,
+      simulationFailures: 1,
+      avgSimulatedUnits: 200_000,
+      errors: { 'OracleStale (0xd)': 1 },
+      liveActions: 6,
+      divergedMarkets: 1,
+    });
+    expect(report.markets[0]).toMatchObject({
+      slabAddress: 'A',
+      missedLiquidations: [2],
+      extraLiquidations: [4],
+      diverged: true,
+    });
+    expect(report.markets[1]).toMatchObject({ slabAddress: 'B', diverged: false });
+    expect(report.markets[1]!.live.pushes).toBe(1);
+  });
+
+  it('tolerates a one-crank difference at the window edge', () => {
+    const report = diffShadowActions(
+      { actions: [action('crank', 'A'), action('crank', 'A')], simulations: [] },
+      [action('crank', 'A')],
+      0,
+      2_000,
+    );
+
+    expect(report.divergedMarkets).toBe(0);
+    expect(diffShadowActions({ actions: [], simulations: [] }, [action('crank', 'A'), action('crank', 'A')], 0, 1).divergedMarkets)
+      .toBe(1);
+  });
+});
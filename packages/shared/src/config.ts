@@ -54,4 +54,51 @@ export const config = {
   webhookSecret: env.HELIUS_WEBHOOK_SECRET ?? "",
   /** Public URL for webhook registration (e.g. Railway URL) */
   webhookUrl: env.WEBHOOK_URL ?? "",
+  /** Local directory for the indexer's raw slab archive; set to "" to disable archiving */
+  slabArchiveDir: env.SLAB_ARCHIVE_DIR ?? "./data/slab-archive",
+  slabArchiveIntervalMs: env.SLAB_ARCHIVE_INTERVAL_MS ?? 60_000,
+  /** Stream slab changes over RPC websocket subscriptions into the eventBus (indexer) */
+  slabWatcherEnabled: env.SLAB_WATCHER_ENABLED === "true",
+  /** Re-subscribe and gap-fill when no slab notification arrives for this long */
+  slabWatcherStaleMs: env.SLAB_WATCHER_STALE_MS ?? 60_000,
+  /** Cross-process eventBus transport; "memory" keeps events in-process */
+  eventBusTransport: env.EVENT_BUS_TRANSPORT ?? "memory",
+  /** Session-mode Postgres URL for the postgres transport (LISTEN needs a direct connection) */
+  eventBusDatabaseUrl: env.EVENT_BUS_DATABASE_URL ?? "",
+  eventBusRedisUrl: env.EVENT_BUS_REDIS_URL ?? "",
+  /** Name under which this service saves its read offset; unique per replica. Unset: start at the newest event */
+  eventBusConsumer: env.EVENT_BUS_CONSUMER ?? "",
+  /** Replay after this offset ("earliest" for everything held) on startup */
+  eventBusReplayFrom: env.EVENT_BUS_REPLAY_FROM ?? "",
+  /** Deliver eventBus events to registered outbound webhooks (indexer) */
+  webhookDeliveryEnabled: env.WEBHOOK_DELIVERY_ENABLED === "true",
+  /** Attempts per delivery before it is dead-lettered */
+  webhookMaxAttempts: env.WEBHOOK_MAX_ATTEMPTS ?? 10,
+  webhookTimeoutMs: env.WEBHOOK_TIMEOUT_MS ?? 10_000,
+  /** percolator-stake program whose pools the indexer snapshots; unset: the SDK's STAKE_PROGRAM_ID */
+  stakeProgramId: env.STAKE_PROGRAM_ID ?? "",
+  /** Keeper oracle pushes: "median" of all sources, or "twap" of on-chain pool samples only */
+  oraclePriceMode: env.ORACLE_PRICE_MODE ?? "median",
+  /** TWAP window (~400ms slots; 300 ≈ 2 min) and publish gates */
+  twapWindowSlots: env.TWAP_WINDOW_SLOTS ?? 300,
+  twapMinSamples: env.TWAP_MIN_SAMPLES ?? 30,
+  twapMinDepthUsd: env.TWAP_MIN_DEPTH_USD ?? 10_000,
+  twapSampleIntervalMs: env.TWAP_SAMPLE_INTERVAL_MS ?? 2_000,
+  /** Crank scheduler budget per cycle: compute units, fees (lamports) and parallel sends */
+  crankCuBudget: env.CRANK_CU_BUDGET ?? 4_000_000,
+  crankFeeBudgetLamports: env.CRANK_FEE_BUDGET_LAMPORTS ?? 2_000_000,
+  crankConcurrency: env.CRANK_CONCURRENCY ?? 10,
+  /** Multi-keeper coordination backend; postgres needs a session-mode KEEPER_DATABASE_URL */
+  keeperCoordination: env.KEEPER_COORDINATION ?? "postgres",
+  keeperDatabaseUrl: env.KEEPER_DATABASE_URL ?? "",
+  keeperCoordinationRefreshMs: env.KEEPER_COORDINATION_REFRESH_MS ?? 2_000,
+  /** Liquidations packed into one transaction */
+  liquidationMaxPerTx: env.LIQUIDATION_MAX_PER_TX ?? 6,
+  /** Hold back liquidations whose expected fee doesn't cover the tx cost (underwater ones are always sent) */
+  liquidationProfitGate: env.LIQUIDATION_PROFIT_GATE === "true",
+  /** Keeper shadow mode: simulate every transaction, never send */
+  keeperShadowMode: env.KEEPER_SHADOW_MODE === "true",
+  /** Live keeper wallets the shadow is compared against; the first pays for its simulations */
+  keeperShadowLiveWallets: (env.KEEPER_SHADOW_LIVE_WALLETS ?? "").split(",").map((w) => w.trim()).filter(Boolean),
+  keeperShadowReportIntervalMs: env.KEEPER_SHADOW_REPORT_INTERVAL_MS ?? 600_000,
 } as const;
@@ -0,0 +1,297 @@
+import { getSupabase } from "./client.js";
+import type { TradeRow } from "./queries.js";
+
+// Instruction history written by the indexer (migrations 039/040).
+// Every row is keyed by (tx_signature, ix_index, inner_index) so the poller
+// and the Helius webhook can both index the same transaction safely.
+
+interface InstructionKey {
+  tx_signature: string;
+  ix_index: number;
+  /** -1 for a top-level instruction */
+  inner_index: number;
+  slot: number;
+  block_time: string | null;
+}
+
+export interface InstructionEventRow extends InstructionKey {
+  slab_address: string | null;
+  tag: number;
+  name: string;
+  signer: string | null;
+  args: Record<string, unknown> | null;
+}
+
+export interface CollateralEventRow extends InstructionKey {
+  slab_address: string;
+  event_type: "init_user" | "init_lp" | "deposit" | "withdraw" | "close_account";
+  owner: string;
+  account_idx: number | null;
+  amount: string | null;
+}
+
+export interface LiquidationEventRow extends InstructionKey {
+  slab_address: string;
+  event_type: "liquidation" | "force_close";
+  caller: string | null;
+  target_idx: number;
+}
+
+export interface InsuranceMovementRow extends InstructionKey {
+  slab_address: string;
+  event_type: "top_up" | "lp_deposit" | "lp_withdraw" | "fund_market" | "admin_withdraw";
+  actor: string | null;
+  amount: string | null;
+}
+
+export interface MarketAdminEventRow extends InstructionKey {
+  slab_address: string;
+  name: string;
+  authority: string | null;
+  args: Record<string, unknown> | null;
+}
+
+const INSTRUCTION_KEY = "tx_signature,ix_index,inner_index";
+
+async function insertIgnoringDuplicates(table: string, rows: object[]): Promise<void> {
+  if (rows.length === 0) return;
+  const { error } = await getSupabase()
+    .from(table)
+    .upsert(rows, { onConflict: INSTRUCTION_KEY, ignoreDuplicates: true });
+  if (error) throw error;
+}
+
+export async function insertInstructionEvents(rows: InstructionEventRow[]): Promise<void> {
+  await insertIgnoringDuplicates("instruction_events", rows);
+}
+
+export async function insertCollateralEvents(rows: CollateralEventRow[]): Promise<void> {
+  await insertIgnoringDuplicates("collateral_events", rows);
+}
+
+export async function insertLiquidationEvents(rows: LiquidationEventRow[]): Promise<void> {
+  await insertIgnoringDuplicates("liquidation_events", rows);
+}
+
+export async function insertInsuranceMovements(rows: InsuranceMovementRow[]): Promise<void> {
+  await insertIgnoringDuplicates("insurance_movements", rows);
+}
+
+export async function insertMarketAdminEvents(rows: MarketAdminEventRow[]): Promise<void> {
+  await insertIgnoringDuplicates("market_admin_events", rows);
+}
+
+export interface AccountActivityEntry {
+  type:
+    | "trade"
+    | CollateralEventRow["event_type"]
+    | LiquidationEventRow["event_type"]
+    | InsuranceMovementRow["event_type"];
+  slab_address: string;
+  tx_signature: string | null;
+  slot: number | null;
+  /** Block time when known, otherwise when the row was indexed */
+  time: string;
+  /** When the row was indexed; the timeline is ordered and paged on this */
+  indexed_at: string;
+  account_idx: number | null;
+  amount: string | null;
+  details: Record<string, unknown>;
+}
+
+export interface AccountActivityQuery {
+  slab?: string;
+  /** ISO timestamp; only entries indexed strictly earlier are returned (for paging) */
+  before?: string;
+  limit: number;
+}
+
+type Stored<T> = T & { created_at: string };
+
+/** Keeps each liquidation query's filter well inside URL length limits */
+const LIQUIDATION_CLAUSES_PER_QUERY = 50;
+const RANGE_PAGE_SIZE = 1000;
+
+/**
+ * Merged activity timeline for a wallet, newest first: trades, collateral
+ * movements, insurance LP movements, and liquidations/force-closes of any
+ * account index the wallet has used.
+ *
+ * Every source is ordered and paged on `created_at`, which all rows have
+ * (block_time is null for some), so `before` cuts each source at the same
+ * point and the merged page has no gaps.
+ *
+ * Liquidation instructions only carry the target index, so they are matched
+ * to the wallet through its collateral_events rows. Indices are reused after
+ * CloseAccount, so the query asks for each (slab, index, slot range) the
+ * wallet held rather than every index on every slab.
+ */
+export async function getAccountActivity(
+  owner: string,
+  query: AccountActivityQuery,
+): Promise<AccountActivityEntry[]> {
+  const db = getSupabase();
+  const { slab, before, limit } = query;
+
+  let trades = db.from("trades").select("*").eq("trader", owner);
+  let collateral = db.from("collateral_events").select("*").eq("owner", owner);
+  let insurance = db.from("insurance_movements").select("*").eq("actor", owner);
+  if (slab) {
+    trades = trades.eq("slab_address", slab);
+    collateral = collateral.eq("slab_address", slab);
+    insurance = insurance.eq("slab_address", slab);
+  }
+  if (before) {
+    trades = trades.lt("created_at", before);
+    collateral = collateral.lt("created_at", before);
+    insurance = insurance.lt("created_at", before);
+  }
+
+  const [tradeRes, collateralRes, insuranceRes, ranges] = await Promise.all([
+    trades.order("created_at", { ascending: false }).limit(limit),
+    collateral.order("created_at", { ascending: false }).limit(limit),
+    insurance.order("created_at", { ascending: false }).limit(limit),
+    getAccountIndexRanges(owner, slab),
+  ]);
+  if (tradeRes.error) throw tradeRes.error;
+  if (collateralRes.error) throw collateralRes.error;
+  if (insuranceRes.error) throw insuranceRes.error;
+
+  const entries: AccountActivityEntry[] = [];
+
+  for (const t of (tradeRes.data ?? []) as (TradeRow & { block_time?: string | null })[]) {
+    entries.push({
+      type: "trade",
+      slab_address: t.slab_address,
+      tx_signature: t.tx_signature,
+      slot: null,
+      time: t.block_time ?? t.created_at,
+      indexed_at: t.created_at,
+      account_idx: null,
+      amount: String(t.size),
+      details: { side: t.side, size: String(t.size), price: t.price, fee: t.fee },
+    });
+  }
+  for (const r of (collateralRes.data ?? []) as Stored<CollateralEventRow>[]) {
+    entries.push({
+      type: r.event_type,
+      slab_address: r.slab_address,
+      tx_signature: r.tx_signature,
+      slot: r.slot,
+      time: r.block_time ?? r.created_at,
+      indexed_at: r.created_at,
+      account_idx: r.account_idx,
+      amount: r.amount,
+      details: {},
+    });
+  }
+  for (const r of (insuranceRes.data ?? []) as Stored<InsuranceMovementRow>[]) {
+    entries.push({
+      type: r.event_type,
+      slab_address: r.slab_address,
+      tx_signature: r.tx_signature,
+      slot: r.slot,
+      time: r.block_time ?? r.created_at,
+      indexed_at: r.created_at,
+      account_idx: null,
+      amount: r.amount,
+      details: {},
+    });
+  }
+
+  // One clause per held range, so other holders of a reused index never use up the limit
+  const clauses: string[] = [];
+  for (const [key, held] of ranges) {
+    const [slabAddress, idx] = key.split(":");
+    for (const range of held) {
+      const to = range.to === Number.MAX_SAFE_INTEGER ? "" : `,slot.lte.${range.to}`;
+      clauses.push(
+        `and(slab_address.eq.${slabAddress},target_idx.eq.${idx},slot.gte.${range.from}${to})`,
+      );
+    }
+  }
+  const liqPages = [];
+  for (let i = 0; i < clauses.length; i += LIQUIDATION_CLAUSES_PER_QUERY) {
+    let liqs = db
+      .from("liquidation_events")
+      .select("*")
+      .or(clauses.slice(i, i + LIQUIDATION_CLAUSES_PER_QUERY).join(","));
+    if (before) liqs = liqs.lt("created_at", before);
+    liqPages.push(liqs.order("created_at", { ascending: false }).limit(limit));
+  }
+  for (const liqRes of await Promise.all(liqPages)) {
+    if (liqRes.error) throw liqRes.error;
+    for (const r of (liqRes.data ?? []) as Stored<LiquidationEventRow>[]) {
+      entries.push({
+        type: r.event_type,
+        slab_address: r.slab_address,
+        tx_signature: r.tx_signature,
+        slot: r.slot,
+        time: r.block_time ?? r.created_at,
+        indexed_at: r.created_at,
+        account_idx: r.target_idx,
+        amount: null,
+        details: { caller: r.caller },
+      });
+    }
+  }
+
+  entries.sort((a, b) => (a.indexed_at < b.indexed_at ? 1 : a.indexed_at > b.indexed_at ? -1 : 0));
+  return entries.slice(0, limit);
+}
+
+interface SlotRange {
+  from: number;
+  to: number;
+}
+
+/**
+ * `slab:idx` → every slot range during which `owner` held that account index.
+ * The wallet can close an index and open it again later, with another wallet
+ * holding it in between, so each open starts a new range. Only the events
+ * that open or close an index are read, every page of them.
+ */
+async function getAccountIndexRanges(
+  owner: string,
+  slab?: string,
+): Promise<Map<string, SlotRange[]>> {
+  type OwnershipRow = Pick<
+    CollateralEventRow,
+    "slab_address" | "account_idx" | "event_type" | "slot"
+  >;
+  const rows: OwnershipRow[] = [];
+  for (let from = 0; ; from += RANGE_PAGE_SIZE) {
+    let q = getSupabase()
+      .from("collateral_events")
+      .select("slab_address, account_idx, event_type, slot")
+      .eq("owner", owner)
+      .in("event_type", ["init_user", "init_lp", "close_account"])
+      .not("account_idx", "is", null);
+    if (slab) q = q.eq("slab_address", slab);
+    const { data, error } = await q
+      .order("slot", { ascending: true })
+      .order("tx_signature", { ascending: true })
+      .order("ix_index", { ascending: true })
+      .order("inner_index", { ascending: true })
+      .range(from, from + RANGE_PAGE_SIZE - 1);
+    if (error) throw error;
+    rows.push(...((data ?? []) as OwnershipRow[]));
+    if ((data ?? []).length < RANGE_PAGE_SIZE) break;
+  }
+
+  const ranges = new Map<string, SlotRange[]>();
+  for (const row of rows) {
+    const key = `${row.slab_address}:${row.account_idx}`;
+    const held = ranges.get(key) ?? [];
+    const last = held[held.length - 1];
+    const open = last && last.to === Number.MAX_SAFE_INTEGER ? last : null;
+    if (row.event_type === "close_account") {
+      // A liquidation can land in the same slot as the close that follows it
+      if (open) open.to = row.slot;
+    } else if (!open) {
+      held.push({ from: row.slot, to: Number.MAX_SAFE_INTEGER });
+    }
+    ranges.set(key, held);
+  }
+  return ranges;
+}
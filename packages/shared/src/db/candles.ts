@@ -0,0 +1,184 @@
+import { getSupabase } from "./client.js";
+
+// OHLCV candles built by the indexer's CandleBuilder (migration 042).
+
+export type CandleResolution = "1m" | "5m" | "15m" | "1h" | "4h" | "1d";
+
+export const CANDLE_RESOLUTION_SECONDS: Record<CandleResolution, number> = {
+  "1m": 60,
+  "5m": 300,
+  "15m": 900,
+  "1h": 3_600,
+  "4h": 14_400,
+  "1d": 86_400,
+};
+
+export function isCandleResolution(value: string): value is CandleResolution {
+  return Object.prototype.hasOwnProperty.call(CANDLE_RESOLUTION_SECONDS, value);
+}
+
+export interface CandleRow {
+  slab_address: string;
+  resolution: CandleResolution;
+  /** ISO start of the bucket (UTC-aligned) */
+  bucket_start: string;
+  open: number;
+  high: number;
+  low: number;
+  close: number;
+  /** Sum of |trade size| in native units */
+  volume: string;
+  trade_count: number;
+}
+
+export async function upsertCandles(rows: CandleRow[]): Promise<void> {
+  if (rows.length === 0) return;
+  const now = new Date().toISOString();
+  const { error } = await getSupabase()
+    .from("candles")
+    .upsert(rows.map((r) => ({ ...r, updated_at: now })), { onConflict: "slab_address,resolution,bucket_start" });
+  if (error) throw error;
+}
+
+/** Remove candles for buckets that no longer hold a priced point. */
+export async function deleteCandles(slabAddress: string, resolution: CandleResolution, bucketStarts: string[]): Promise<void> {
+  if (bucketStarts.length === 0) return;
+  const { error } = await getSupabase()
+    .from("candles")
+    .delete()
+    .eq("slab_address", slabAddress)
+    .eq("resolution", resolution)
+    .in("bucket_start", bucketStarts);
+  if (error) throw error;
+}
+
+export interface CandleQuery {
+  /** ISO bounds on bucket_start: `from` inclusive, `to` exclusive */
+  from?: string;
+  to?: string;
+  limit: number;
+}
+
+/**
+ * Candles in ascending time order. Without `from`, returns the most recent
+ * `limit` buckets (before `to` if given).
+ */
+export async function getCandles(slabAddress: string, resolution: CandleResolution, query: CandleQuery): Promise<CandleRow[]> {
+  let q = getSupabase()
+    .from("candles")
+    .select("slab_address, resolution, bucket_start, open, high, low, close, volume, trade_count")
+    .eq("slab_address", slabAddress)
+    .eq("resolution", resolution);
+  if (query.from) q = q.gte("bucket_start", query.from);
+  if (query.to) q = q.lt("bucket_start", query.to);
+
+  const newestFirst = !query.from;
+  const { data, error } = await q.order("bucket_start", { ascending: !newestFirst }).limit(query.limit);
+  if (error) throw error;
+  const rows = (data ?? []) as CandleRow[];
+  return newestFirst ? rows.reverse() : rows;
+}
+
+/** Row reference used by CandleBuilder to find newly indexed points. */
+export interface IndexedPointRef {
+  id: string;
+  slab_address: string;
+  created_at: string;
+  /** Event time (ISO) — block time for trades, push time for oracle prices */
+  time: string;
+}
+
+/**
+ * Scan position over (created_at, id). With `id` null the scan starts at
+ * `createdAt` inclusive; otherwise it resumes strictly after that row, so a
+ * page of rows sharing one created_at (a batch insert) can't stall it.
+ */
+export interface IndexCursor {
+  createdAt: string;
+  id: string | null;
+}
+
+function cursorFilter(cursor: IndexCursor): string {
+  return cursor.id === null
+    ? `created_at.gte."${cursor.createdAt}"`
+    : `created_at.gt."${cursor.createdAt}",and(created_at.eq."${cursor.createdAt}",id.gt.${cursor.id})`;
+}
+
+/** Trades indexed after `cursor`, in (created_at, id) order. */
+export async function getTradesIndexedSince(cursor: IndexCursor, limit: number): Promise<IndexedPointRef[]> {
+  const { data, error } = await getSupabase()
+    .from("trades")
+    .select("id, slab_address, block_time, created_at")
+    .or(cursorFilter(cursor))
+    .order("created_at", { ascending: true })
+    .order("id", { ascending: true })
+    .limit(limit);
+  if (error) throw error;
+  return (data ?? []).map((r: { id: string; slab_address: string; block_time: string | null; created_at: string }) => ({
+    id: r.id,
+    slab_address: r.slab_address,
+    created_at: r.created_at,
+    time: r.block_time ?? r.created_at,
+  }));
+}
+
+/** Oracle prices inserted after `cursor`, in (created_at, id) order. */
+export async function getOraclePricesIndexedSince(cursor: IndexCursor, limit: number): Promise<IndexedPointRef[]> {
+  const { data, error } = await getSupabase()
+    .from("oracle_prices")
+    .select("id, slab_address, timestamp, created_at")
+    .or(cursorFilter(cursor))
+    .order("created_at", { ascending: true })
+    .order("id", { ascending: true })
+    .limit(limit);
+  if (error) throw error;
+  return (data ?? []).map((r: { id: string; slab_address: string; timestamp: number; created_at: string }) => ({
+    id: r.id,
+    slab_address: r.slab_address,
+    created_at: r.created_at,
+    time: new Date(Number(r.timestamp) * 1000).toISOString(),
+  }));
+}
+
+/**
+ * Trades executed in [from, to) — by block_time, or created_at for rows
+ * indexed before block_time was recorded.
+ */
+export async function getTradesInRange(
+  slabAddress: string,
+  from: string,
+  to: string,
+): Promise<{ price: number; size: string | number; time: string }[]> {
+  const { data, error } = await getSupabase()
+    .from("trades")
+    .select("price, size, block_time, created_at")
+    .eq("slab_address", slabAddress)
+    .or(
+      `and(block_time.gte."${from}",block_time.lt."${to}"),` +
+      `and(block_time.is.null,created_at.gte."${from}",created_at.lt."${to}")`,
+    )
+    .limit(10_000);
+  if (error) throw error;
+  return (data ?? []).map((r: { price: number | string; size: string | number; block_time: string | null; created_at: string }) => ({
+    price: Number(r.price),
+    size: r.size,
+    time: r.block_time ?? r.created_at,
+  }));
+}
+
+/** Oracle prices pushed in [fromSec, toSec) epoch seconds. */
+export async function getOraclePricesInRange(
+  slabAddress: string,
+  fromSec: number,
+  toSec: number,
+): Promise<{ price_e6: string; timestamp: number }[]> {
+  const { data, error } = await getSupabase()
+    .from("oracle_prices")
+    .select("price_e6, timestamp")
+    .eq("slab_address", slabAddress)
+    .gte("timestamp", fromSec)
+    .lt("timestamp", toSec)
+    .limit(10_000);
+  if (error) throw error;
+  return (data ?? []) as { price_e6: string; timestamp: number }[];
+}
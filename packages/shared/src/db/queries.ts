@@ -63,6 +63,8 @@ export interface TradeRow {
   price: number;
   fee: number;
   tx_signature: string | null;
+  /** On-chain block time (042); null for rows indexed before it was recorded */
+  block_time?: string | null;
   created_at: string;
 }
 
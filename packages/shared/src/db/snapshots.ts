@@ -0,0 +1,100 @@
+import { getSupabase } from "./client.js";
+
+// Per-account state history written by StatsCollector (migration 041).
+
+export interface AccountSnapshotRow {
+  slab_address: string;
+  account_idx: number;
+  owner: string;
+  kind: number;
+  slot: number;
+  capital: string;
+  pnl: string;
+  reserved_pnl: string;
+  position_size: string;
+  entry_price_e6: string;
+  funding_index: string;
+  fee_credits: string;
+  price_e6: string | null;
+  closed: boolean;
+}
+
+export type StoredAccountSnapshot = AccountSnapshotRow & { id: number; created_at: string };
+
+export async function insertAccountSnapshots(rows: AccountSnapshotRow[]): Promise<void> {
+  if (rows.length === 0) return;
+  const { error } = await getSupabase().from("account_snapshots").insert(rows);
+  if (error) throw error;
+}
+
+/** Columns of account_snapshots_latest, NUMERICs cast to text so they compare exactly. */
+const LATEST_SNAPSHOT_COLUMNS =
+  "slab_address,account_idx,owner,kind,slot,capital::text,pnl::text,reserved_pnl::text," +
+  "position_size::text,entry_price_e6::text,funding_index::text,fee_credits::text,price_e6::text,closed";
+const LATEST_SNAPSHOT_PAGE_SIZE = 1000;
+
+/**
+ * Newest stored snapshot of every account index still open on a slab, keyed
+ * by index (migration 046). Seeds StatsCollector's change detection after a
+ * restart.
+ */
+export async function getLatestOpenAccountSnapshots(
+  slabAddress: string,
+): Promise<Map<number, AccountSnapshotRow>> {
+  const latest = new Map<number, AccountSnapshotRow>();
+  for (let from = 0; ; from += LATEST_SNAPSHOT_PAGE_SIZE) {
+    const { data, error } = await getSupabase()
+      .from("account_snapshots_latest")
+      .select(LATEST_SNAPSHOT_COLUMNS)
+      .eq("slab_address", slabAddress)
+      .eq("closed", false)
+      .order("account_idx", { ascending: true })
+      .range(from, from + LATEST_SNAPSHOT_PAGE_SIZE - 1);
+    if (error) throw error;
+    const rows = (data ?? []) as unknown as AccountSnapshotRow[];
+    for (const row of rows) latest.set(row.account_idx, row);
+    if (rows.length < LATEST_SNAPSHOT_PAGE_SIZE) return latest;
+  }
+}
+
+export interface AccountSnapshotQuery {
+  slab?: string;
+  accountIdx?: number;
+  /** ISO timestamps bounding created_at (inclusive) */
+  from?: string;
+  to?: string;
+  limit: number;
+}
+
+/** Snapshots for a wallet, newest first. */
+export async function getAccountSnapshotsByOwner(owner: string, query: AccountSnapshotQuery): Promise<StoredAccountSnapshot[]> {
+  let q = getSupabase().from("account_snapshots").select("*").eq("owner", owner);
+  if (query.slab) q = q.eq("slab_address", query.slab);
+  if (query.accountIdx != null) q = q.eq("account_idx", query.accountIdx);
+  if (query.from) q = q.gte("created_at", query.from);
+  if (query.to) q = q.lte("created_at", query.to);
+  const { data, error } = await q.order("created_at", { ascending: false }).limit(query.limit);
+  if (error) throw error;
+  return (data ?? []) as StoredAccountSnapshot[];
+}
+
+/**
+ * Snapshots of one slab account index regardless of owner, newest first.
+ * Indices are reused after close, so this can span several owners.
+ */
+export async function getAccountSnapshotsByIndex(
+  slabAddress: string,
+  accountIdx: number,
+  query: Omit<AccountSnapshotQuery, "slab" | "accountIdx">,
+): Promise<StoredAccountSnapshot[]> {
+  let q = getSupabase()
+    .from("account_snapshots")
+    .select("*")
+    .eq("slab_address", slabAddress)
+    .eq("account_idx", accountIdx);
+  if (query.from) q = q.gte("created_at", query.from);
+  if (query.to) q = q.lte("created_at", query.to);
+  const { data, error } = await q.order("created_at", { ascending: false }).limit(query.limit);
+  if (error) throw error;
+  return (data ?? []) as StoredAccountSnapshot[];
+}
@@ -0,0 +1,114 @@
+import { getSupabase } from "./client.js";
+
+// Stake pool history written by the indexer's StakePoolService (migration 045).
+
+export interface StakePoolSnapshotRow {
+  pool_address: string;
+  slab_address: string;
+  slot: number;
+  pool_mode: number;
+  total_deposited: string;
+  total_withdrawn: string;
+  total_flushed: string;
+  total_returned: string;
+  total_fees_earned: string;
+  total_lp_supply: string;
+  pool_value: string;
+  share_price_e6: string;
+  tranche_enabled: boolean;
+  senior_value: string;
+  senior_lp_supply: string;
+  senior_share_price_e6: string;
+  junior_value: string | null;
+  junior_lp_supply: string | null;
+  junior_share_price_e6: string | null;
+  junior_fee_mult_bps: number;
+  hwm_enabled: boolean;
+  epoch_high_water_tvl: string;
+  hwm_floor_bps: number;
+  hwm_floor: string | null;
+  hwm_breached: boolean;
+}
+
+export type StoredStakePoolSnapshot = StakePoolSnapshotRow & { id: number; created_at: string };
+
+export type StakePoolEventKind = "flush" | "return" | "hwm_breach" | "hwm_recovered";
+
+export interface StakePoolEventRow {
+  pool_address: string;
+  slab_address: string;
+  slot: number;
+  kind: StakePoolEventKind;
+  /** Flow amount for flush/return; pool value for HWM events */
+  amount: string;
+  pool_value: string;
+  hwm_floor: string | null;
+}
+
+export type StoredStakePoolEvent = StakePoolEventRow & { id: number; created_at: string };
+
+export interface StakePoolHistoryQuery {
+  /** ISO timestamps bounding created_at (inclusive) */
+  from?: string;
+  to?: string;
+  limit: number;
+}
+
+export async function insertStakePoolSnapshots(rows: StakePoolSnapshotRow[]): Promise<void> {
+  if (rows.length === 0) return;
+  const { error } = await getSupabase().from("stake_pool_snapshots").insert(rows);
+  if (error) throw error;
+}
+
+export async function insertStakePoolEvents(rows: StakePoolEventRow[]): Promise<void> {
+  if (rows.length === 0) return;
+  const { error } = await getSupabase().from("stake_pool_events").insert(rows);
+  if (error) throw error;
+}
+
+/** A pool's most recent snapshot; the indexer diffs its first poll after a restart against it. */
+export async function getLatestStakePoolSnapshot(poolAddress: string): Promise<StoredStakePoolSnapshot | null> {
+  const { data, error } = await getSupabase()
+    .from("stake_pool_snapshots")
+    .select("*")
+    .eq("pool_address", poolAddress)
+    .order("created_at", { ascending: false })
+    .limit(1)
+    .maybeSingle();
+  if (error) throw error;
+  return (data as StoredStakePoolSnapshot | null) ?? null;
+}
+
+/** Oldest snapshot taken at or after `since`; the start of a trailing window. */
+export async function getStakePoolSnapshotSince(poolAddress: string, since: string): Promise<StoredStakePoolSnapshot | null> {
+  const { data, error } = await getSupabase()
+    .from("stake_pool_snapshots")
+    .select("*")
+    .eq("pool_address", poolAddress)
+    .gte("created_at", since)
+    .order("created_at", { ascending: true })
+    .limit(1)
+    .maybeSingle();
+  if (error) throw error;
+  return (data as StoredStakePoolSnapshot | null) ?? null;
+}
+
+/** A pool's snapshots, newest first. */
+export async function getStakePoolSnapshots(poolAddress: string, query: StakePoolHistoryQuery): Promise<StoredStakePoolSnapshot[]> {
+  let q = getSupabase().from("stake_pool_snapshots").select("*").eq("pool_address", poolAddress);
+  if (query.from) q = q.gte("created_at", query.from);
+  if (query.to) q = q.lte("created_at", query.to);
+  const { data, error } = await q.order("created_at", { ascending: false }).limit(query.limit);
+  if (error) throw error;
+  return (data ?? []) as StoredStakePoolSnapshot[];
+}
+
+/** A pool's flush/return/HWM events, newest first. */
+export async function getStakePoolEvents(poolAddress: string, query: StakePoolHistoryQuery): Promise<StoredStakePoolEvent[]> {
+  let q = getSupabase().from("stake_pool_events").select("*").eq("pool_address", poolAddress);
+  if (query.from) q = q.gte("created_at", query.from);
+  if (query.to) q = q.lte("created_at", query.to);
+  const { data, error } = await q.order("created_at", { ascending: false }).limit(query.limit);
+  if (error) throw error;
+  return (data ?? []) as StoredStakePoolEvent[];
+}
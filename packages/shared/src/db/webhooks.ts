@@ -0,0 +1,314 @@
+import { getSupabase } from "./client.js";
+
+// Outbound webhook subscriptions and delivery state (migration 044).
+
+export interface WebhookSubscriptionRow {
+  id: string;
+  url: string;
+  secret: string;
+  /** Event names to deliver; empty = all */
+  events: string[];
+  /** Slab addresses to deliver for; empty = all */
+  slabs: string[];
+  description: string | null;
+  active: boolean;
+  /** Wallet that registered it (migration 047); null = operator */
+  owner: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export type WebhookSubscriptionInput = Pick<WebhookSubscriptionRow, "url" | "secret" | "events" | "slabs" | "description" | "owner">;
+export type WebhookSubscriptionPatch = Partial<Pick<WebhookSubscriptionRow, "url" | "events" | "slabs" | "description" | "active">>;
+
+export type WebhookDeliveryStatus = "pending" | "delivering" | "delivered" | "dead";
+
+export interface WebhookDeliveryRow {
+  id: number;
+  subscription_id: string;
+  event_key: string;
+  event: string;
+  slab_address: string;
+  payload: Record<string, unknown>;
+  status: WebhookDeliveryStatus;
+  attempts: number;
+  next_attempt_at: string;
+  last_status_code: number | null;
+  last_error: string | null;
+  delivered_at: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export type NewWebhookDelivery = Pick<WebhookDeliveryRow, "subscription_id" | "event_key" | "event" | "slab_address" | "payload">;
+
+export interface WebhookAttemptRow {
+  delivery_id: number;
+  subscription_id: string;
+  attempt: number;
+  status_code: number | null;
+  error: string | null;
+  duration_ms: number;
+}
+
+export type StoredWebhookAttempt = WebhookAttemptRow & { id: number; created_at: string };
+
+export interface WebhookDeadLetterRow {
+  id: number;
+  delivery_id: number;
+  subscription_id: string;
+  event: string;
+  slab_address: string;
+  payload: Record<string, unknown>;
+  attempts: number;
+  last_status_code: number | null;
+  last_error: string | null;
+  created_at: string;
+}
+
+// ---------------------------------------------------------------------------
+// Subscriptions
+// ---------------------------------------------------------------------------
+
+export async function createWebhookSubscription(input: WebhookSubscriptionInput): Promise<WebhookSubscriptionRow> {
+  const { data, error } = await getSupabase().from("webhook_subscriptions").insert(input).select("*").single();
+  if (error) throw error;
+  return data as WebhookSubscriptionRow;
+}
+
+/** Every subscription, or only one wallet's when `owner` is given. */
+export async function listWebhookSubscriptions(owner?: string): Promise<WebhookSubscriptionRow[]> {
+  let q = getSupabase().from("webhook_subscriptions").select("*");
+  if (owner !== undefined) q = q.eq("owner", owner);
+  const { data, error } = await q.order("created_at", { ascending: true });
+  if (error) throw error;
+  return (data ?? []) as WebhookSubscriptionRow[];
+}
+
+export async function countWebhookSubscriptions(owner: string): Promise<number> {
+  const { count, error } = await getSupabase()
+    .from("webhook_subscriptions")
+    .select("id", { count: "exact", head: true })
+    .eq("owner", owner);
+  if (error) throw error;
+  return count ?? 0;
+}
+
+export async function getActiveWebhookSubscriptions(): Promise<WebhookSubscriptionRow[]> {
+  const { data, error } = await getSupabase().from("webhook_subscriptions").select("*").eq("active", true);
+  if (error) throw error;
+  return (data ?? []) as WebhookSubscriptionRow[];
+}
+
+export async function getWebhookSubscription(id: string): Promise<WebhookSubscriptionRow | null> {
+  const { data, error } = await getSupabase().from("webhook_subscriptions").select("*").eq("id", id).maybeSingle();
+  if (error) throw error;
+  return (data as WebhookSubscriptionRow | null) ?? null;
+}
+
+/** Returns the updated row, or null if there is no such subscription. */
+export async function updateWebhookSubscription(id: string, patch: WebhookSubscriptionPatch): Promise<WebhookSubscriptionRow | null> {
+  const { data, error } = await getSupabase()
+    .from("webhook_subscriptions")
+    .update({ ...patch, updated_at: new Date().toISOString() })
+    .eq("id", id)
+    .select("*")
+    .maybeSingle();
+  if (error) throw error;
+  return (data as WebhookSubscriptionRow | null) ?? null;
+}
+
+/** Deletes the subscription and (by cascade) its deliveries. Returns false if it didn't exist. */
+export async function deleteWebhookSubscription(id: string): Promise<boolean> {
+  const { data, error } = await getSupabase().from("webhook_subscriptions").delete().eq("id", id).select("id");
+  if (error) throw error;
+  return (data ?? []).length > 0;
+}
+
+// ---------------------------------------------------------------------------
+// Owner challenges
+// ---------------------------------------------------------------------------
+
+/** Store a challenge for `owner`, purging expired ones first. */
+export async function createWebhookChallenge(owner: string, challenge: string, expiresAt: string): Promise<void> {
+  const now = new Date().toISOString();
+  const purged = await getSupabase().from("webhook_owner_challenges").delete().lt("expires_at", now);
+  if (purged.error) throw purged.error;
+  const { error } = await getSupabase()
+    .from("webhook_owner_challenges")
+    .insert({ challenge, owner, expires_at: expiresAt });
+  if (error) throw error;
+}
+
+/**
+ * Mark a challenge used. Returns false if it is unknown, another wallet's,
+ * expired or already used; concurrent calls consume it at most once.
+ */
+export async function consumeWebhookChallenge(owner: string, challenge: string): Promise<boolean> {
+  const now = new Date().toISOString();
+  const { data, error } = await getSupabase()
+    .from("webhook_owner_challenges")
+    .update({ used_at: now })
+    .eq("challenge", challenge)
+    .eq("owner", owner)
+    .is("used_at", null)
+    .gt("expires_at", now)
+    .select("challenge");
+  if (error) throw error;
+  return (data ?? []).length > 0;
+}
+
+// ---------------------------------------------------------------------------
+// Delivery queue
+// ---------------------------------------------------------------------------
+
+/** Queue deliveries; one already queued for the same subscription and event is skipped. */
+export async function enqueueWebhookDeliveries(rows: NewWebhookDelivery[]): Promise<void> {
+  if (rows.length === 0) return;
+  const { error } = await getSupabase()
+    .from("webhook_deliveries")
+    .upsert(rows, { onConflict: "subscription_id,event_key", ignoreDuplicates: true });
+  if (error) throw error;
+}
+
+/**
+ * Claim up to `limit` due pending deliveries, oldest first. A row is only
+ * claimed if it is still pending, so concurrent dispatchers never both send it.
+ */
+export async function claimDueWebhookDeliveries(limit: number): Promise<WebhookDeliveryRow[]> {
+  const now = new Date().toISOString();
+  const { data, error } = await getSupabase()
+    .from("webhook_deliveries")
+    .select("id")
+    .eq("status", "pending")
+    .lte("next_attempt_at", now)
+    .order("next_attempt_at", { ascending: true })
+    .limit(limit);
+  if (error) throw error;
+
+  const claimed: WebhookDeliveryRow[] = [];
+  for (const { id } of (data ?? []) as { id: number }[]) {
+    const { data: row, error: claimError } = await getSupabase()
+      .from("webhook_deliveries")
+      .update({ status: "delivering", updated_at: now })
+      .eq("id", id)
+      .eq("status", "pending")
+      .select("*")
+      .maybeSingle();
+    if (claimError) throw claimError;
+    if (row) claimed.push(row as WebhookDeliveryRow);
+  }
+  return claimed;
+}
+
+/** Put deliveries left "delivering" by a dispatcher that died mid-send back in the queue. */
+export async function releaseStaleWebhookDeliveries(olderThan: string): Promise<void> {
+  const { error } = await getSupabase()
+    .from("webhook_deliveries")
+    .update({ status: "pending", updated_at: new Date().toISOString() })
+    .eq("status", "delivering")
+    .lt("updated_at", olderThan);
+  if (error) throw error;
+}
+
+export async function insertWebhookAttempt(row: WebhookAttemptRow): Promise<void> {
+  const { error } = await getSupabase().from("webhook_delivery_attempts").insert(row);
+  if (error) throw error;
+}
+
+export async function updateWebhookDelivery(
+  id: number,
+  patch: Partial<Pick<WebhookDeliveryRow, "status" | "attempts" | "next_attempt_at" | "last_status_code" | "last_error" | "delivered_at">>,
+): Promise<void> {
+  const { error } = await getSupabase()
+    .from("webhook_deliveries")
+    .update({ ...patch, updated_at: new Date().toISOString() })
+    .eq("id", id);
+  if (error) throw error;
+}
+
+export async function insertWebhookDeadLetter(row: Omit<WebhookDeadLetterRow, "id" | "created_at">): Promise<void> {
+  const { error } = await getSupabase()
+    .from("webhook_dead_letters")
+    .upsert(row, { onConflict: "delivery_id", ignoreDuplicates: true });
+  if (error) throw error;
+}
+
+/**
+ * Re-queue a delivery (typically a dead letter) for an immediate fresh round
+ * of attempts. Returns false if the subscription has no such delivery.
+ */
+export async function redeliverWebhook(subscriptionId: string, deliveryId: number): Promise<boolean> {
+  const { data, error } = await getSupabase()
+    .from("webhook_deliveries")
+    .update({
+      status: "pending",
+      attempts: 0,
+      next_attempt_at: new Date().toISOString(),
+      updated_at: new Date().toISOString(),
+    })
+    .eq("id", deliveryId)
+    .eq("subscription_id", subscriptionId)
+    .neq("status", "delivering")
+    .select("id");
+  if (error) throw error;
+  if ((data ?? []).length === 0) return false;
+
+  const { error: dlError } = await getSupabase().from("webhook_dead_letters").delete().eq("delivery_id", deliveryId);
+  if (dlError) throw dlError;
+  return true;
+}
+
+// ---------------------------------------------------------------------------
+// Delivery logs
+// ---------------------------------------------------------------------------
+
+export interface WebhookDeliveryQuery {
+  status?: WebhookDeliveryStatus;
+  event?: string;
+  limit: number;
+}
+
+/** A subscription's deliveries, newest first. */
+export async function getWebhookDeliveries(subscriptionId: string, query: WebhookDeliveryQuery): Promise<WebhookDeliveryRow[]> {
+  let q = getSupabase().from("webhook_deliveries").select("*").eq("subscription_id", subscriptionId);
+  if (query.status) q = q.eq("status", query.status);
+  if (query.event) q = q.eq("event", query.event);
+  const { data, error } = await q.order("created_at", { ascending: false }).limit(query.limit);
+  if (error) throw error;
+  return (data ?? []) as WebhookDeliveryRow[];
+}
+
+export async function getWebhookDelivery(subscriptionId: string, deliveryId: number): Promise<WebhookDeliveryRow | null> {
+  const { data, error } = await getSupabase()
+    .from("webhook_deliveries")
+    .select("*")
+    .eq("id", deliveryId)
+    .eq("subscription_id", subscriptionId)
+    .maybeSingle();
+  if (error) throw error;
+  return (data as WebhookDeliveryRow | null) ?? null;
+}
+
+/** Every attempt at one delivery, in order. */
+export async function getWebhookAttempts(deliveryId: number): Promise<StoredWebhookAttempt[]> {
+  const { data, error } = await getSupabase()
+    .from("webhook_delivery_attempts")
+    .select("*")
+    .eq("delivery_id", deliveryId)
+    .order("attempt", { ascending: true });
+  if (error) throw error;
+  return (data ?? []) as StoredWebhookAttempt[];
+}
+
+/** A subscription's dead letters, newest first. */
+export async function getWebhookDeadLetters(subscriptionId: string, limit: number): Promise<WebhookDeadLetterRow[]> {
+  const { data, error } = await getSupabase()
+    .from("webhook_dead_letters")
+    .select("*")
+    .eq("subscription_id", subscriptionId)
+    .order("created_at", { ascending: false })
+    .limit(limit);
+  if (error) throw error;
+  return (data ?? []) as WebhookDeadLetterRow[];
+}
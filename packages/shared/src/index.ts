@@ -3,12 +3,23 @@ export * from "./validation.js";
 export * from "./logger.js";
 export * from "./db/client.js";
 export * from "./db/queries.js";
+export * from "./db/activity.js";
+export * from "./db/snapshots.js";
+export * from "./db/candles.js";
+export * from "./db/webhooks.js";
+export * from "./db/stakePools.js";
 export * from "./utils/solana.js";
 export * from "./utils/rpc-client.js";
 export * from "./utils/binary.js";
 export * from "./services/events.js";
+export * from "./services/slabArchive.js";
+export * from "./services/slabWatcher.js";
+export * from "./services/eventTransport.js";
+export * from "./services/eventTransportPg.js";
+export * from "./services/eventTransportRedis.js";
 export * from "./retry.js";
 export * from "./sentry.js";
 export * from "./sanitize.js";
+export * from "./publicAddress.js";
 export * from "./alerts.js";
 export * from "./monitor.js";
@@ -0,0 +1,99 @@
+/**
+ * publicAddress.ts — Keep outbound requests to user-supplied URLs off our network
+ *
+ * Checking the hostname is not enough: a public name can resolve to loopback
+ * or the metadata service, and can resolve differently between the check and
+ * the connection. `publicLookup` is a drop-in `dns.lookup` that fails when any
+ * resolved address is private, so handing it to the request pins the
+ * connection to an address that was checked.
+ */
+import { lookup as dnsLookup, promises as dns } from "node:dns";
+import { isIP, type LookupFunction } from "node:net";
+
+export class PrivateAddressError extends Error {
+  constructor(hostname: string, address?: string) {
+    super(address ? `${hostname} resolves to private address ${address}` : `${hostname} is not a public host`);
+    this.name = "PrivateAddressError";
+  }
+}
+
+function isPrivateIPv4(ip: string): boolean {
+  const [a, b] = ip.split(".").map(Number);
+  return a === 0 || a === 10 || a === 127
+    || (a === 169 && b === 254) // link-local, cloud metadata
+    || (a === 172 && b >= 16 && b <= 31)
+    || (a === 192 && b === 168)
+    || (a === 100 && b >= 64 && b <= 127) // CGNAT
+    || a >= 224; // multicast, reserved, broadcast
+}
+
+/** Eight 16-bit groups of an IPv6 address, or null if it doesn't parse. */
+function ipv6Groups(ip: string): number[] | null {
+  let s = ip.toLowerCase().replace(/%.*$/, "");
+  const dotted = s.match(/(\d+)\.(\d+)\.(\d+)\.(\d+)$/);
+  if (dotted) {
+    const [a, b, c, d] = dotted.slice(1).map(Number);
+    s = `${s.slice(0, dotted.index)}${((a << 8) | b).toString(16)}:${((c << 8) | d).toString(16)}`;
+  }
+  const halves = s.split("::");
+  if (halves.length > 2) return null;
+  const head = halves[0] ? halves[0].split(":") : [];
+  const tail = halves.length === 2 && halves[1] ? halves[1].split(":") : [];
+  const fill = halves.length === 2 ? 8 - head.length - tail.length : 0;
+  if (fill < 0) return null;
+  const groups = [...head, ...Array<string>(fill).fill("0"), ...tail].map((g) => parseInt(g, 16));
+  return groups.length === 8 && groups.every((g) => g >= 0 && g <= 0xffff) ? groups : null;
+}
+
+/**
+ * True for loopback, private, link-local, CGNAT, multicast and unspecified
+ * addresses, including IPv4 embedded in IPv6 (mapped, compatible, NAT64).
+ */
+export function isPrivateAddress(ip: string): boolean {
+  const version = isIP(ip);
+  if (version === 4) return isPrivateIPv4(ip);
+  if (version !== 6) return false;
+  const g = ipv6Groups(ip);
+  if (!g) return true;
+  const embedded = () => `${g[6] >> 8}.${g[6] & 0xff}.${g[7] >> 8}.${g[7] & 0xff}`;
+  if (g.slice(0, 5).every((x) => x === 0) && (g[5] === 0 || g[5] === 0xffff)) {
+    // ::, ::1, ::a.b.c.d and ::ffff:a.b.c.d
+    return g[5] === 0 && g[6] === 0 ? true : isPrivateIPv4(embedded());
+  }
+  if (g[0] === 0x64 && g[1] === 0xff9b && g.slice(2, 6).every((x) => x === 0)) return isPrivateIPv4(embedded());
+  return (g[0] & 0xfe00) === 0xfc00 // unique local
+    || (g[0] & 0xffc0) === 0xfe80 // link-local
+    || (g[0] & 0xff00) === 0xff00; // multicast
+}
+
+/** Private by name or by literal address, without resolving. */
+export function isPrivateHostname(hostname: string): boolean {
+  const host = hostname.replace(/^\[|\]$/g, "").toLowerCase();
+  return host === "localhost" || host.endsWith(".localhost") || host.endsWith(".internal")
+    || host.endsWith(".local") || isPrivateAddress(host);
+}
+
+/**
+ * `dns.lookup` for `http.request({ lookup })` and friends: resolves every
+ * address and fails with PrivateAddressError if any of them is private.
+ * Literal IPs never reach a lookup, so check those with isPrivateHostname.
+ */
+export const publicLookup: LookupFunction = (hostname, options, callback) => {
+  dnsLookup(hostname, { ...options, all: true }, (err, addresses) => {
+    if (err) return callback(err, "");
+    const blocked = addresses.find((a) => isPrivateAddress(a.address));
+    if (blocked || addresses.length === 0) return callback(new PrivateAddressError(hostname, blocked?.address), "");
+    if (options.all) return callback(null, addresses);
+    callback(null, addresses[0].address, addresses[0].family);
+  });
+};
+
+/** Resolve a hostname and throw PrivateAddressError unless every address is public. */
+export async function assertPublicHost(hostname: string): Promise<void> {
+  const host = hostname.replace(/^\[|\]$/g, "");
+  if (isPrivateHostname(host)) throw new PrivateAddressError(host);
+  if (isIP(host)) return;
+  const addresses = await dns.lookup(host, { all: true });
+  const blocked = addresses.find((a) => isPrivateAddress(a.address));
+  if (blocked || addresses.length === 0) throw new PrivateAddressError(host, blocked?.address);
+}
@@ -0,0 +1,431 @@
+import { randomUUID } from "node:crypto";
+import { hostname } from "node:os";
+import { z } from "zod";
+import { createLogger } from "../logger.js";
+import { eventBus, type EventPayload, type ServerEvent } from "./events.js";
+import { PostgresEventTransport } from "./eventTransportPg.js";
+import { RedisStreamEventTransport } from "./eventTransportRedis.js";
+
+/**
+ * Cross-process delivery for the event bus.
+ *
+ * `eventBus` stays an in-process EventEmitter; an EventBridge hangs off it
+ * and carries events between services through an append-only log:
+ *
+ *   - publish: every local publish is validated against its event schema and
+ *     appended to the transport. Failed appends stay in an outbox and are
+ *     retried with backoff, in order
+ *   - consume: a read loop delivers other processes' events to local
+ *     listeners, oldest first. The read offset only advances (and, with a
+ *     consumer name, is saved) after delivery, so a crash re-delivers rather
+ *     than drops — at-least-once. Event IDs seen recently are skipped
+ *   - replay: start reading after any offset, or from the earliest event the
+ *     transport still holds
+ *
+ * Transports: Postgres (table + LISTEN/NOTIFY) and Redis streams.
+ */
+
+const logger = createLogger("event-bridge");
+
+// ---------------------------------------------------------------------------
+// Envelope and schemas
+// ---------------------------------------------------------------------------
+
+/** An event as carried between processes */
+export interface EventEnvelope extends EventPayload {
+  /** Unique per publish; duplicates from retried appends share it */
+  id: string;
+  /** Publishing process (see EventBridgeOptions.origin) */
+  origin: string;
+}
+
+/** One entry read back from a transport; the envelope is validated by the bridge */
+export interface StoredEvent {
+  offset: string;
+  envelope: unknown;
+}
+
+const decimal = z.string().regex(/^-?\d+$/);
+const numeric = z.union([z.string(), z.number()]);
+const anyData = z.record(z.string(), z.unknown());
+
+const slabEventBase = {
+  slot: z.number().int().nonnegative(),
+  previousSlot: z.number().int().nonnegative(),
+  source: z.enum(["subscription", "gap-fill"]),
+};
+
+const positionSnapshot = z.object({
+  owner: z.string(),
+  kind: z.number().int(),
+  positionSize: decimal,
+  entryPrice: decimal,
+  capital: decimal,
+  pnl: decimal,
+}).nullable();
+
+/** Data schema per event. Publishers may add fields; required ones must be present. */
+export const EVENT_DATA_SCHEMAS: Record<ServerEvent, z.ZodType> = {
+  "market.creating": anyData,
+  "market.created": anyData,
+  "market.updated": anyData,
+  "price.updated": z.looseObject({ priceE6: numeric, source: z.string().optional() }),
+  "crank.success": z.looseObject({ signature: z.string() }),
+  "crank.failure": z.looseObject({ error: z.string() }),
+  "crank.stale": z.looseObject({ reason: z.string() }),
+  "trade.executed": z.looseObject({ signature: z.string(), trader: z.string(), side: z.string(), size: numeric }),
+  "position.liquidated": anyData,
+  "liquidation.success": z.looseObject({ accountIdx: z.number().int().nonnegative(), signature: z.string() }),
+  "liquidation.failure": z.looseObject({ accountIdx: z.number().int().nonnegative(), error: z.string() }),
+  "adl.triggered": z.looseObject({
+    triggers: z.array(z.string()),
+    shortfall: decimal,
+    side: z.enum(["long", "short"]).nullable(),
+    accounts: z.number().int().nonnegative(),
+  }),
+  "price.engine.degraded": anyData,
+  "price.engine.recovered": anyData,
+  "position.changed": z.looseObject({
+    ...slabEventBase,
+    accountIdx: z.number().int().nonnegative(),
+    before: positionSnapshot,
+    after: positionSnapshot,
+  }),
+  "oi.changed": z.looseObject({
+    ...slabEventBase,
+    totalOpenInterest: decimal,
+    longOi: decimal,
+    shortOi: decimal,
+    previous: z.object({ totalOpenInterest: decimal, longOi: decimal, shortOi: decimal }),
+  }),
+  "insurance.changed": z.looseObject({
+    ...slabEventBase,
+    balance: decimal,
+    feeRevenue: decimal,
+    isolatedBalance: decimal,
+    previous: z.object({ balance: decimal, feeRevenue: decimal, isolatedBalance: decimal }),
+  }),
+  "config.changed": z.looseObject({
+    ...slabEventBase,
+    changes: z.array(z.object({ field: z.string(), before: z.string(), after: z.string() })),
+  }),
+  "market.paused": z.looseObject({ ...slabEventBase, paused: z.boolean() }),
+};
+
+const envelopeSchema = z.object({
+  id: z.string().min(1),
+  origin: z.string().min(1),
+  event: z.enum(Object.keys(EVENT_DATA_SCHEMAS) as [ServerEvent, ...ServerEvent[]]),
+  slabAddress: z.string(),
+  timestamp: z.number(),
+  data: anyData,
+});
+
+/** Validate an envelope and its event's data. Returns the error message, or null when valid. */
+export function validateEnvelope(value: unknown): string | null {
+  const envelope = envelopeSchema.safeParse(value);
+  if (!envelope.success) return envelope.error.issues.map((i) => `${i.path.join(".")}: ${i.message}`).join("; ");
+  const data = EVENT_DATA_SCHEMAS[envelope.data.event].safeParse(envelope.data.data);
+  if (!data.success) {
+    return `${envelope.data.event}: ${data.error.issues.map((i) => `data.${i.path.join(".")}: ${i.message}`).join("; ")}`;
+  }
+  return null;
+}
+
+/** Transports store JSON; a bigint or cycle in the data would fail every append. */
+function serializationError(envelope: EventEnvelope): string | null {
+  try {
+    JSON.stringify(envelope);
+    return null;
+  } catch (err) {
+    return err instanceof Error ? err.message : String(err);
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Transport
+// ---------------------------------------------------------------------------
+
+export interface EventTransport {
+  readonly name: string;
+  /** Durably append one event; resolves with its offset. */
+  append(envelope: EventEnvelope): Promise<string>;
+  /**
+   * Up to `limit` events after `offset` (null = from the earliest held),
+   * oldest first. When none are ready, waits up to `waitMs` for new ones.
+   */
+  read(offset: string | null, limit: number, waitMs: number): Promise<StoredEvent[]>;
+  /** Offset of the newest event, or null when the log is empty */
+  latestOffset(): Promise<string | null>;
+  loadOffset(consumer: string): Promise<string | null>;
+  saveOffset(consumer: string, offset: string): Promise<void>;
+  close(): Promise<void>;
+}
+
+export type EventTransportKind = "memory" | "postgres" | "redis";
+
+/** Build the configured transport; null for "memory" (in-process only). */
+export function createEventTransport(
+  kind: EventTransportKind,
+  urls: { databaseUrl?: string; redisUrl?: string },
+): EventTransport | null {
+  switch (kind) {
+    case "memory":
+      return null;
+    case "postgres":
+      if (!urls.databaseUrl) throw new Error("EVENT_BUS_TRANSPORT=postgres needs EVENT_BUS_DATABASE_URL");
+      return new PostgresEventTransport(urls.databaseUrl);
+    case "redis":
+      if (!urls.redisUrl) throw new Error("EVENT_BUS_TRANSPORT=redis needs EVENT_BUS_REDIS_URL");
+      return new RedisStreamEventTransport(urls.redisUrl);
+  }
+}
+
+// ---------------------------------------------------------------------------
+// Bridge
+// ---------------------------------------------------------------------------
+
+/** Outbox cap; past it the oldest unsent events are dropped (logged) */
+const MAX_OUTBOX = 10_000;
+/** Recently delivered event IDs kept for duplicate suppression */
+const DEDUPE_WINDOW = 10_000;
+const RETRY_BASE_MS = 1_000;
+const RETRY_MAX_MS = 30_000;
+
+export interface EventBridgeOptions {
+  /** Identifies this process; its own events are not delivered back to it */
+  origin?: string;
+  /** Append local publishes to the transport. A shadow keeper sets this false */
+  publish?: boolean;
+  /** Deliver other processes' events to local listeners */
+  consume?: boolean;
+  /** Save the read offset under this name and resume from it after a restart */
+  consumer?: string;
+  /** Replay from here: an offset to read after, or "earliest". Overrides the saved offset */
+  replayFrom?: string;
+  batchSize?: number;
+  /** Longest a read waits for new events before polling again */
+  waitMs?: number;
+}
+
+export interface EventBridgeStatus {
+  transport: string;
+  origin: string;
+  publishing: boolean;
+  consuming: boolean;
+  offset: string | null;
+  appended: number;
+  outbox: number;
+  appendFailures: number;
+  delivered: number;
+  duplicates: number;
+  invalid: number;
+}
+
+type LocalBus = Pick<typeof eventBus, "deliver" | "setForwarder">;
+
+export class EventBridge {
+  readonly origin: string;
+  private outbox: EventEnvelope[] = [];
+  private draining = false;
+  private retryTimer: ReturnType<typeof setTimeout> | null = null;
+  private retryDelayMs = RETRY_BASE_MS;
+  private recentIds = new Set<string>();
+  private offset: string | null = null;
+  private running = false;
+  private loop: Promise<void> | null = null;
+  private stats = { appended: 0, appendFailures: 0, delivered: 0, duplicates: 0, invalid: 0 };
+
+  constructor(
+    private readonly transport: EventTransport,
+    private readonly options: EventBridgeOptions = {},
+    private readonly bus: LocalBus = eventBus,
+  ) {
+    this.origin = options.origin ?? `${hostname()}:${process.pid}:${randomUUID().slice(0, 8)}`;
+  }
+
+  private get publishing(): boolean {
+    return this.options.publish ?? true;
+  }
+
+  private get consuming(): boolean {
+    return this.options.consume ?? true;
+  }
+
+  /** Attach to the bus, resolve the starting offset and begin consuming (when enabled). */
+  async start(): Promise<void> {
+    if (this.running) return;
+    this.running = true;
+    this.bus.setForwarder(this);
+    if (!this.consuming) return;
+
+    const { replayFrom, consumer } = this.options;
+    try {
+      if (replayFrom === "earliest") this.offset = null;
+      else if (replayFrom) this.offset = replayFrom;
+      else this.offset = (consumer ? await this.transport.loadOffset(consumer) : null) ?? await this.transport.latestOffset();
+    } catch (err) {
+      // Leave the bus in-process rather than queueing for a bridge nobody holds
+      this.running = false;
+      this.bus.setForwarder(null);
+      await this.transport.close();
+      throw err;
+    }
+
+    logger.info("Event bridge consuming", {
+      transport: this.transport.name,
+      consumer,
+      from: this.offset ?? "earliest",
+    });
+    this.loop = this.consumeLoop();
+  }
+
+  async stop(): Promise<void> {
+    this.running = false;
+    this.bus.setForwarder(null);
+    if (this.retryTimer) {
+      clearTimeout(this.retryTimer);
+      this.retryTimer = null;
+    }
+    await this.loop;
+    this.loop = null;
+    // Last attempt at anything still queued
+    if (this.outbox.length > 0) await this.drain();
+    await this.transport.close();
+  }
+
+  /** Called by the bus for each local publish. */
+  forward(payload: EventPayload): void {
+    if (!this.publishing || !this.running) return;
+    const envelope: EventEnvelope = { ...payload, id: randomUUID(), origin: this.origin };
+    const error = validateEnvelope(envelope) ?? serializationError(envelope);
+    if (error) {
+      this.stats.invalid++;
+      logger.warn("Not forwarding invalid event", { event: payload.event, slabAddress: payload.slabAddress, error });
+      return;
+    }
+    if (this.outbox.length >= MAX_OUTBOX) {
+      const dropped = this.outbox.shift()!;
+      logger.error("Event outbox full — dropping oldest unsent event", { event: dropped.event, id: dropped.id });
+    }
+    this.outbox.push(envelope);
+    void this.drain();
+  }
+
+  getStatus(): EventBridgeStatus {
+    return {
+      transport: this.transport.name,
+      origin: this.origin,
+      publishing: this.publishing,
+      consuming: this.consuming,
+      offset: this.offset,
+      outbox: this.outbox.length,
+      ...this.stats,
+    };
+  }
+
+  /** Append queued events in order; on failure keep them and retry with backoff. */
+  private async drain(): Promise<void> {
+    if (this.draining || this.retryTimer) return;
+    this.draining = true;
+    try {
+      while (this.outbox.length > 0) {
+        await this.transport.append(this.outbox[0]);
+        this.outbox.shift();
+        this.stats.appended++;
+      }
+      this.retryDelayMs = RETRY_BASE_MS;
+    } catch (err) {
+      this.stats.appendFailures++;
+      logger.warn("Event append failed — will retry", {
+        transport: this.transport.name,
+        queued: this.outbox.length,
+        retryInMs: this.retryDelayMs,
+        error: err instanceof Error ? err.message : err,
+      });
+      this.retryTimer = setTimeout(() => {
+        this.retryTimer = null;
+        void this.drain();
+      }, this.retryDelayMs);
+      this.retryDelayMs = Math.min(this.retryDelayMs * 2, RETRY_MAX_MS);
+    } finally {
+      this.draining = false;
+    }
+  }
+
+  private async consumeLoop(): Promise<void> {
+    const batchSize = this.options.batchSize ?? 200;
+    const waitMs = this.options.waitMs ?? 5_000;
+    let backoffMs = RETRY_BASE_MS;
+
+    while (this.running) {
+      try {
+        const batch = await this.transport.read(this.offset, batchSize, waitMs);
+        if (!this.running) break;
+        for (const stored of batch) {
+          this.handle(stored);
+          this.offset = stored.offset;
+        }
+        if (batch.length > 0 && this.options.consumer) {
+          await this.transport.saveOffset(this.options.consumer, this.offset!);
+        }
+        backoffMs = RETRY_BASE_MS;
+      } catch (err) {
+        logger.warn("Event read failed", {
+          transport: this.transport.name,
+          offset: this.offset,
+          error: err instanceof Error ? err.message : err,
+        });
+        await new Promise((resolve) => setTimeout(resolve, backoffMs));
+        backoffMs = Math.min(backoffMs * 2, RETRY_MAX_MS);
+      }
+    }
+  }
+
+  private handle(stored: StoredEvent): void {
+    const error = validateEnvelope(stored.envelope);
+    if (error) {
+      this.stats.invalid++;
+      logger.warn("Skipping invalid event", { offset: stored.offset, error });
+      return;
+    }
+    const { id, origin, event, slabAddress, timestamp, data } = stored.envelope as EventEnvelope;
+    if (origin === this.origin) return; // already emitted locally
+    if (this.recentIds.has(id)) {
+      this.stats.duplicates++;
+      return;
+    }
+    this.recentIds.add(id);
+    if (this.recentIds.size > DEDUPE_WINDOW) {
+      this.recentIds.delete(this.recentIds.values().next().value!);
+    }
+
+    try {
+      this.bus.deliver({ event, slabAddress, timestamp, data });
+      this.stats.delivered++;
+    } catch (err) {
+      // A throwing listener must not stall the stream
+      logger.error("Event listener failed", { event, offset: stored.offset, error: err instanceof Error ? err.message : err });
+    }
+  }
+}
+
+/**
+ * Bridge `eventBus` over `transport` and start it. Returns null (in-process
+ * only) when there is no transport.
+ */
+export async function connectEventBus(
+  transport: EventTransport | null,
+  options: EventBridgeOptions = {},
+): Promise<EventBridge | null> {
+  if (!transport) return null;
+  const bridge = new EventBridge(transport, options);
+  await bridge.start();
+  logger.info("Event bus connected", {
+    transport: transport.name,
+    origin: bridge.origin,
+    publish: options.publish ?? true,
+    consume: options.consume ?? true,
+  });
+  return bridge;
+}
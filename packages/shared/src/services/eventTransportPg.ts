@@ -0,0 +1,197 @@
+import pg from "pg";
+import { createLogger } from "../logger.js";
+import type { EventEnvelope, EventTransport, StoredEvent } from "./eventTransport.js";
+
+/**
+ * Postgres event transport — `server_events` table plus LISTEN/NOTIFY.
+ *
+ * Appends insert a row (idempotent on the event ID) and NOTIFY its id in the
+ * same statement; readers wake on the notification and read rows by id.
+ * NOTIFY is only a wake-up — the table is the log, so a reader that was
+ * disconnected catches up from its offset (the last id it delivered).
+ *
+ * Ids come from a sequence, so a later id can commit before an earlier one.
+ * A read stops at a gap in the ids until the row after it is older than
+ * `gapGraceMs`; past that the gap is treated as a rolled-back insert.
+ *
+ * The LISTEN connection must be a direct/session connection — LISTEN doesn't
+ * work through a transaction-mode pooler (Supabase port 6543).
+ * Schema: supabase/migrations/043_server_events.sql.
+ */
+
+const logger = createLogger("event-transport:pg");
+
+export const PG_EVENT_CHANNEL = "percolator_events";
+
+interface EventRow {
+  id: string;
+  envelope: unknown;
+  age_ms: number;
+}
+
+export class PostgresEventTransport implements EventTransport {
+  readonly name = "postgres";
+  private pool: pg.Pool;
+  private listener: pg.Client | null = null;
+  private waiters = new Set<() => void>();
+  private lastPruneAt = 0;
+
+  constructor(
+    private readonly connectionString: string,
+    private readonly options: { gapGraceMs?: number; retentionMs?: number } = {},
+  ) {
+    this.pool = new pg.Pool({
+      connectionString,
+      application_name: "percolator-events",
+      max: 2,
+      statement_timeout: 5_000,
+      connectionTimeoutMillis: 5_000,
+    });
+    this.pool.on("error", (err) => logger.warn("Idle event connection error", { error: err.message }));
+  }
+
+  async append(envelope: EventEnvelope): Promise<string> {
+    const { rows } = await this.pool.query<{ id: string }>(
+      `WITH ins AS (
+         INSERT INTO server_events (event_id, event, slab_address, origin, envelope)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (event_id) DO NOTHING
+         RETURNING id
+       )
+       SELECT id::text AS id, pg_notify($6, id::text) FROM ins`,
+      [envelope.id, envelope.event, envelope.slabAddress, envelope.origin, JSON.stringify(envelope), PG_EVENT_CHANNEL],
+    );
+    void this.maybePrune();
+    if (rows[0]) return rows[0].id;
+
+    // Retried append whose first attempt did land
+    const existing = await this.pool.query<{ id: string }>(
+      "SELECT id::text AS id FROM server_events WHERE event_id = $1",
+      [envelope.id],
+    );
+    return existing.rows[0].id;
+  }
+
+  async read(offset: string | null, limit: number, waitMs: number): Promise<StoredEvent[]> {
+    let rows = await this.fetch(offset, limit);
+    if (rows.length === 0 && waitMs > 0) {
+      await this.waitForNotify(waitMs);
+      rows = await this.fetch(offset, limit);
+    }
+
+    const gapGraceMs = this.options.gapGraceMs ?? 2_000;
+    const events: StoredEvent[] = [];
+    let prev = offset === null ? null : BigInt(offset);
+    for (const row of rows) {
+      const id = BigInt(row.id);
+      // An uncommitted lower id may still appear; wait for it unless it's been too long
+      if (prev !== null && id !== prev + 1n && row.age_ms < gapGraceMs) break;
+      events.push({ offset: row.id, envelope: row.envelope });
+      prev = id;
+    }
+    // Held at a gap: give the straggler a moment instead of re-querying hot
+    if (events.length === 0 && rows.length > 0) await new Promise((resolve) => setTimeout(resolve, 200));
+    return events;
+  }
+
+  async latestOffset(): Promise<string | null> {
+    const { rows } = await this.pool.query<{ id: string | null }>("SELECT max(id)::text AS id FROM server_events");
+    return rows[0]?.id ?? null;
+  }
+
+  async loadOffset(consumer: string): Promise<string | null> {
+    const { rows } = await this.pool.query<{ last_id: string }>(
+      "SELECT last_id::text AS last_id FROM server_event_offsets WHERE consumer = $1",
+      [consumer],
+    );
+    return rows[0]?.last_id ?? null;
+  }
+
+  async saveOffset(consumer: string, offset: string): Promise<void> {
+    await this.pool.query(
+      `INSERT INTO server_event_offsets (consumer, last_id, updated_at) VALUES ($1, $2, NOW())
+       ON CONFLICT (consumer) DO UPDATE SET last_id = EXCLUDED.last_id, updated_at = NOW()`,
+      [consumer, offset],
+    );
+  }
+
+  async close(): Promise<void> {
+    for (const wake of this.waiters) wake();
+    const listener = this.listener;
+    this.listener = null;
+    await listener?.end().catch(() => {});
+    await this.pool.end().catch(() => {});
+  }
+
+  private async fetch(offset: string | null, limit: number): Promise<EventRow[]> {
+    const { rows } = await this.pool.query<EventRow>(
+      `SELECT id::text AS id, envelope,
+              (EXTRACT(EPOCH FROM (NOW() - created_at)) * 1000)::float8 AS age_ms
+         FROM server_events
+        WHERE id > $1
+        ORDER BY id
+        LIMIT $2`,
+      [offset ?? "0", limit],
+    );
+    return rows;
+  }
+
+  private async listen(): Promise<void> {
+    if (this.listener) return;
+    const client = new pg.Client({
+      connectionString: this.connectionString,
+      application_name: "percolator-events-listen",
+      keepAlive: true,
+      connectionTimeoutMillis: 5_000,
+    });
+    client.on("notification", () => {
+      for (const wake of this.waiters) wake();
+    });
+    client.on("error", (err) => {
+      logger.warn("Event LISTEN connection lost", { error: err.message });
+      // Reads fall back to polling every waitMs until the next listen() reconnects
+      if (this.listener === client) this.listener = null;
+    });
+    try {
+      await client.connect();
+      await client.query(`LISTEN ${PG_EVENT_CHANNEL}`);
+    } catch (err) {
+      await client.end().catch(() => {});
+      throw err;
+    }
+    this.listener = client;
+  }
+
+  private async waitForNotify(waitMs: number): Promise<void> {
+    try {
+      await this.listen();
+    } catch (err) {
+      logger.warn("Event LISTEN failed — polling", { error: err instanceof Error ? err.message : err });
+    }
+    await new Promise<void>((resolve) => {
+      const wake = () => {
+        clearTimeout(timer);
+        this.waiters.delete(wake);
+        resolve();
+      };
+      const timer = setTimeout(wake, waitMs);
+      this.waiters.add(wake);
+    });
+  }
+
+  /** Drop events past the retention window, at most hourly. */
+  private async maybePrune(): Promise<void> {
+    const now = Date.now();
+    if (now - this.lastPruneAt < 3_600_000) return;
+    this.lastPruneAt = now;
+    const retentionMs = this.options.retentionMs ?? 7 * 24 * 3_600_000;
+    try {
+      await this.pool.query(
+        "DELETE FROM server_events WHERE created_at < NOW() - make_interval(secs => $1)",
+        [retentionMs / 1000],
+      );
+    } catch (err) {
+      logger.warn("Event prune failed", { error: err instanceof Error ? err.message : err });
+    }
+  }
+}
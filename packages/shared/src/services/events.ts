@@ -12,6 +12,12 @@ export type ServerEvent =
   | "position.liquidated"
   | "liquidation.success"
   | "liquidation.failure"
+  | "adl.triggered"
+  | "position.changed"
+  | "oi.changed"
+  | "insurance.changed"
+  | "config.changed"
+  | "market.paused"
   | "price.engine.degraded"
   | "price.engine.recovered";
 
@@ -22,9 +28,67 @@ export interface EventPayload {
   data: Record<string, unknown>;
 }
 
+/** Where a slab-derived event came from: a live notification or a post-reconnect read */
+export type SlabEventSource = "subscription" | "gap-fill";
+
+type SlabEventBase = {
+  /** Slot of the slab image the change was observed in */
+  slot: number;
+  /** Slot of the previous image it was diffed against */
+  previousSlot: number;
+  source: SlabEventSource;
+};
+
+/** Account fields carried by `position.changed` (bigints as decimal strings) */
+export type PositionSnapshot = {
+  owner: string;
+  kind: number;
+  positionSize: string;
+  entryPrice: string;
+  capital: string;
+  pnl: string;
+};
+
+/**
+ * Data published by the slab watcher for each event, bigints as decimal
+ * strings. `before`/`after` is null when the account was opened or closed.
+ */
+export type SlabEventData = {
+  "position.changed": SlabEventBase & {
+    accountIdx: number;
+    before: PositionSnapshot | null;
+    after: PositionSnapshot | null;
+  };
+  "oi.changed": SlabEventBase & {
+    totalOpenInterest: string;
+    longOi: string;
+    shortOi: string;
+    previous: { totalOpenInterest: string; longOi: string; shortOi: string };
+  };
+  "insurance.changed": SlabEventBase & {
+    balance: string;
+    feeRevenue: string;
+    isolatedBalance: string;
+    previous: { balance: string; feeRevenue: string; isolatedBalance: string };
+  };
+  "config.changed": SlabEventBase & {
+    /** Changed fields as `section.field` (e.g. `params.maintenanceMarginBps`) */
+    changes: { field: string; before: string; after: string }[];
+  };
+  "market.paused": SlabEventBase & { paused: boolean };
+};
+
+export type SlabEvent = keyof SlabEventData;
+
+/** Receives every local publish, e.g. to carry it to other processes (see eventTransport.ts) */
+export interface EventForwarder {
+  forward(payload: EventPayload): void;
+}
+
 class ServerEventBus extends EventEmitter {
   // BM4: Track subscriptions to prevent listener leaks
   private subscriptions = new Map<string, number>();
+  private forwarder: EventForwarder | null = null;
 
   publish(event: ServerEvent, slabAddress: string, data: Record<string, unknown> = {}): void {
     const payload: EventPayload = {
@@ -35,6 +99,17 @@ class ServerEventBus extends EventEmitter {
     };
     this.emit(event, payload);
     this.emit("*", payload);
+    this.forwarder?.forward(payload);
+  }
+
+  /** Emit an event published by another process to local listeners only. */
+  deliver(payload: EventPayload): void {
+    this.emit(payload.event, payload);
+    this.emit("*", payload);
+  }
+
+  setForwarder(forwarder: EventForwarder | null): void {
+    this.forwarder = forwarder;
   }
 
   subscribe(event: ServerEvent | "*", listener: (payload: EventPayload) => void): () => void {
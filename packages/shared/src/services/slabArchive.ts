@@ -0,0 +1,137 @@
+import { createHash } from "node:crypto";
+import { appendFile, mkdir, readFile, rename, stat, writeFile } from "node:fs/promises";
+import { join } from "node:path";
+import { gunzipSync, gzipSync } from "node:zlib";
+
+/**
+ * Local archive of raw slab images.
+ *
+ * Layout under `root`:
+ *   blobs/<hh>/<sha256>.gz   gzip'd slab bytes, content-addressed (shared across slots and slabs)
+ *   index/<slab>.jsonl       one `{slot, hash, at}` line per stored change, in slot order
+ *
+ * An index line is only appended when the slab content differs from the last
+ * stored one, so the state at slot S is the last entry with `slot <= S`.
+ * Resolution is limited to how often the writer polls.
+ */
+
+export interface SlabArchiveEntry {
+  /** RPC context slot the image was read at */
+  slot: number;
+  /** sha256 of the uncompressed bytes (hex) */
+  hash: string;
+  /** ISO time the entry was written */
+  at: string;
+}
+
+const BASE58_ADDRESS = /^[1-9A-HJ-NP-Za-km-z]{32,44}$/;
+
+function assertSlabAddress(slab: string): void {
+  // Slab addresses become file names; reject anything that isn't plain base58
+  if (!BASE58_ADDRESS.test(slab)) throw new Error(`Invalid slab address: ${slab}`);
+}
+
+export function slabContentHash(data: Uint8Array): string {
+  return createHash("sha256").update(data).digest("hex");
+}
+
+export class SlabArchive {
+  /** Writer-side cache of each slab's newest entry */
+  private latest = new Map<string, SlabArchiveEntry | null>();
+
+  constructor(private readonly root: string) {}
+
+  /**
+   * Store `data` as the slab's state at `slot`. Returns `written: false` when
+   * the content is unchanged since the previous entry or the slot is not newer.
+   */
+  async put(slab: string, slot: number, data: Uint8Array): Promise<{ hash: string; written: boolean }> {
+    assertSlabAddress(slab);
+    const hash = slabContentHash(data);
+
+    if (!this.latest.has(slab)) {
+      const { entries, text } = await this.readIndex(slab);
+      // Terminate a torn final line so the next append starts on its own line
+      if (text && !text.endsWith("\n")) await appendFile(this.indexPath(slab), "\n");
+      this.latest.set(slab, entries[entries.length - 1] ?? null);
+    }
+    const last = this.latest.get(slab);
+    if (last && (last.hash === hash || slot <= last.slot)) return { hash, written: false };
+
+    await this.writeBlob(hash, data);
+
+    const entry: SlabArchiveEntry = { slot, hash, at: new Date().toISOString() };
+    await mkdir(join(this.root, "index"), { recursive: true });
+    await appendFile(this.indexPath(slab), JSON.stringify(entry) + "\n");
+    this.latest.set(slab, entry);
+    return { hash, written: true };
+  }
+
+  /** All entries for a slab in slot order (empty if never archived). */
+  async entries(slab: string): Promise<SlabArchiveEntry[]> {
+    assertSlabAddress(slab);
+    return (await this.readIndex(slab)).entries;
+  }
+
+  /** Slab image in effect at `slot`, or null if nothing was archived at or before it. */
+  async getAt(slab: string, slot: number): Promise<{ entry: SlabArchiveEntry; data: Uint8Array } | null> {
+    const entries = await this.entries(slab);
+    let found: SlabArchiveEntry | null = null;
+    for (const e of entries) {
+      if (e.slot > slot) break;
+      found = e;
+    }
+    if (!found) return null;
+    return { entry: found, data: await this.readBlob(found.hash) };
+  }
+
+  async readBlob(hash: string): Promise<Uint8Array> {
+    const data = new Uint8Array(gunzipSync(await readFile(this.blobPath(hash))));
+    if (slabContentHash(data) !== hash) throw new Error(`Archived blob ${hash} is corrupt`);
+    return data;
+  }
+
+  private async writeBlob(hash: string, data: Uint8Array): Promise<void> {
+    const path = this.blobPath(hash);
+    try {
+      await stat(path);
+      return; // identical image already stored (e.g. a slab reverting to an earlier state)
+    } catch {
+      // not stored yet
+    }
+    await mkdir(join(this.root, "blobs", hash.slice(0, 2)), { recursive: true });
+    // Write then rename so readers never see a partial blob
+    const tmp = `${path}.${process.pid}.tmp`;
+    await writeFile(tmp, gzipSync(data));
+    await rename(tmp, path);
+  }
+
+  private async readIndex(slab: string): Promise<{ entries: SlabArchiveEntry[]; text: string }> {
+    let text: string;
+    try {
+      text = await readFile(this.indexPath(slab), "utf8");
+    } catch (err) {
+      if ((err as NodeJS.ErrnoException).code === "ENOENT") return { entries: [], text: "" };
+      throw err;
+    }
+    const entries: SlabArchiveEntry[] = [];
+    for (const line of text.split("\n")) {
+      if (!line) continue;
+      try {
+        entries.push(JSON.parse(line) as SlabArchiveEntry);
+      } catch {
+        // A torn line from a crash mid-append; the rest of the index is still valid
+      }
+    }
+    return { entries: entries.sort((a, b) => a.slot - b.slot), text };
+  }
+
+  private blobPath(hash: string): string {
+    if (!/^[0-9a-f]{64}$/.test(hash)) throw new Error(`Invalid blob hash: ${hash}`);
+    return join(this.root, "blobs", hash.slice(0, 2), `${hash}.gz`);
+  }
+
+  private indexPath(slab: string): string {
+    return join(this.root, "index", `${slab}.jsonl`);
+  }
+}
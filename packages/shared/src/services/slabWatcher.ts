@@ -0,0 +1,400 @@
+import { PublicKey, type Commitment, type Connection } from "@solana/web3.js";
+import { parseSlabState, type Account, type SlabState } from "@percolator/sdk";
+import { createLogger } from "../logger.js";
+import {
+  eventBus,
+  type PositionSnapshot,
+  type SlabEvent,
+  type SlabEventData,
+  type SlabEventSource,
+} from "./events.js";
+
+/**
+ * Push-based slab state for the event bus.
+ *
+ * Slab changes arrive over the RPC websocket, either for every slab a program
+ * owns (`programSubscribe`) or for individual slabs (`accountSubscribe`). Each
+ * image is decoded with `parseSlabState` and diffed against the previous image
+ * of the same slab; the differences are published as typed events (see
+ * `SlabEventData`).
+ *
+ *   - Ordering: an image at or below the slab's last applied slot is dropped
+ *   - The first image of a slab is a baseline and publishes nothing
+ *   - Reconnect: when no notification arrives for `staleAfterMs`, the
+ *     subscriptions are re-opened and every slab is re-read with
+ *     `minContextSlot` set to the highest slot seen. Whatever changed while
+ *     disconnected is published as one net diff with `source: "gap-fill"`
+ */
+
+const logger = createLogger("slab-watcher");
+
+/** Slabs per getMultipleAccountsInfo call (RPC limit is 100) */
+const GAP_FILL_BATCH_SIZE = 100;
+
+/** Config fields that move with every oracle push or crank — not config changes */
+const VOLATILE_CONFIG_FIELDS = new Set([
+  "authorityPriceE6",
+  "authorityTimestamp",
+  "lastEffectivePriceE6",
+  "cumulativeVolumeE6",
+  "threshFloor",
+]);
+
+/** Header fields that are admin-controlled; the rest are counters or layout */
+const HEADER_CONFIG_FIELDS = new Set(["admin", "resolved"]);
+
+export type SlabChange = { [E in SlabEvent]: { event: E; data: SlabEventData[E] } }[SlabEvent];
+
+interface DiffMeta {
+  slot: number;
+  previousSlot: number;
+  source: SlabEventSource;
+}
+
+// ---------------------------------------------------------------------------
+// Diff
+// ---------------------------------------------------------------------------
+
+function fmt(value: unknown): string {
+  if (value instanceof PublicKey) return value.toBase58();
+  return String(value);
+}
+
+function positionOf(account: Account): PositionSnapshot {
+  return {
+    owner: account.owner.toBase58(),
+    kind: account.kind,
+    positionSize: account.positionSize.toString(),
+    entryPrice: account.entryPrice.toString(),
+    capital: account.capital.toString(),
+    pnl: account.pnl.toString(),
+  };
+}
+
+/** Same account, same position — capital and PnL moves alone are not position changes */
+function samePosition(a: Account, b: Account): boolean {
+  return (
+    a.accountId === b.accountId &&
+    a.owner.equals(b.owner) &&
+    a.positionSize === b.positionSize &&
+    a.entryPrice === b.entryPrice
+  );
+}
+
+function changedFields(
+  section: string,
+  before: object,
+  after: object,
+  include: (field: string) => boolean,
+): { field: string; before: string; after: string }[] {
+  const changes: { field: string; before: string; after: string }[] = [];
+  const prev = before as Record<string, unknown>;
+  for (const [field, value] of Object.entries(after)) {
+    if (!include(field)) continue;
+    const b = fmt(prev[field]);
+    const a = fmt(value);
+    if (b !== a) changes.push({ field: `${section}.${field}`, before: b, after: a });
+  }
+  return changes;
+}
+
+/**
+ * Events for the difference between two decoded images of one slab, in a
+ * stable order: pause state, config, insurance, OI, then positions by index.
+ */
+export function diffSlabState(prev: SlabState, next: SlabState, meta: DiffMeta): SlabChange[] {
+  const changes: SlabChange[] = [];
+
+  if (prev.header.paused !== next.header.paused) {
+    changes.push({ event: "market.paused", data: { ...meta, paused: next.header.paused } });
+  }
+
+  const configChanges = [
+    ...changedFields("header", prev.header, next.header, (f) => HEADER_CONFIG_FIELDS.has(f)),
+    ...changedFields("config", prev.config, next.config, (f) => !VOLATILE_CONFIG_FIELDS.has(f)),
+    ...changedFields("params", prev.params, next.params, () => true),
+  ];
+  if (configChanges.length > 0) {
+    changes.push({ event: "config.changed", data: { ...meta, changes: configChanges } });
+  }
+
+  const pi = prev.engine.insuranceFund;
+  const ni = next.engine.insuranceFund;
+  if (pi.balance !== ni.balance || pi.feeRevenue !== ni.feeRevenue || pi.isolatedBalance !== ni.isolatedBalance) {
+    changes.push({
+      event: "insurance.changed",
+      data: {
+        ...meta,
+        balance: ni.balance.toString(),
+        feeRevenue: ni.feeRevenue.toString(),
+        isolatedBalance: ni.isolatedBalance.toString(),
+        previous: {
+          balance: pi.balance.toString(),
+          feeRevenue: pi.feeRevenue.toString(),
+          isolatedBalance: pi.isolatedBalance.toString(),
+        },
+      },
+    });
+  }
+
+  const pe = prev.engine;
+  const ne = next.engine;
+  if (pe.totalOpenInterest !== ne.totalOpenInterest || pe.longOi !== ne.longOi || pe.shortOi !== ne.shortOi) {
+    changes.push({
+      event: "oi.changed",
+      data: {
+        ...meta,
+        totalOpenInterest: ne.totalOpenInterest.toString(),
+        longOi: ne.longOi.toString(),
+        shortOi: ne.shortOi.toString(),
+        previous: {
+          totalOpenInterest: pe.totalOpenInterest.toString(),
+          longOi: pe.longOi.toString(),
+          shortOi: pe.shortOi.toString(),
+        },
+      },
+    });
+  }
+
+  const before = new Map(prev.accounts.map(({ idx, account }) => [idx, account]));
+  const after = new Map(next.accounts.map(({ idx, account }) => [idx, account]));
+  const indices = [...new Set([...before.keys(), ...after.keys()])].sort((a, b) => a - b);
+  for (const accountIdx of indices) {
+    const b = before.get(accountIdx);
+    const a = after.get(accountIdx);
+    if (b && a && samePosition(b, a)) continue;
+    changes.push({
+      event: "position.changed",
+      data: {
+        ...meta,
+        accountIdx,
+        before: b ? positionOf(b) : null,
+        after: a ? positionOf(a) : null,
+      },
+    });
+  }
+
+  return changes;
+}
+
+// ---------------------------------------------------------------------------
+// Watcher
+// ---------------------------------------------------------------------------
+
+export interface SlabWatcherOptions {
+  /** Programs whose slabs are all watched (programSubscribe) */
+  programIds?: string[];
+  /** Individual slabs to watch (accountSubscribe) */
+  slabs?: string[];
+  commitment?: Commitment;
+  /** Re-subscribe and gap-fill after this long without a notification */
+  staleAfterMs?: number;
+}
+
+export interface SlabWatcherStatus {
+  running: boolean;
+  watchedSlabs: number;
+  highestSlot: number;
+  lastNotificationAt: number;
+  reconnects: number;
+  decodeFailures: number;
+}
+
+interface WatchedSlab {
+  slot: number;
+  state: SlabState;
+}
+
+export class SlabWatcher {
+  private states = new Map<string, WatchedSlab>();
+  private subscriptions: { kind: "program" | "account"; id: number }[] = [];
+  private watchdog: ReturnType<typeof setInterval> | null = null;
+  private highestSlot = 0;
+  private lastNotificationAt = 0;
+  private reconnects = 0;
+  private decodeFailures = 0;
+  private _gapFilling = false;
+  private readonly commitment: Commitment;
+  private readonly staleAfterMs: number;
+
+  constructor(
+    private readonly getConnection: () => Connection,
+    private readonly options: SlabWatcherOptions,
+    private readonly bus: Pick<typeof eventBus, "publish"> = eventBus,
+  ) {
+    this.commitment = options.commitment ?? "confirmed";
+    this.staleAfterMs = options.staleAfterMs ?? 60_000;
+  }
+
+  /** Subscribe, then read every slab once as the baseline. */
+  async start(): Promise<void> {
+    if (this.watchdog) return;
+    this.subscribe();
+    this.watchdog = setInterval(() => void this.checkStale(), Math.max(1_000, this.staleAfterMs / 2));
+    await this.gapFill();
+    logger.info("SlabWatcher started", {
+      programs: this.options.programIds?.length ?? 0,
+      slabs: this.options.slabs?.length ?? 0,
+      watchedSlabs: this.states.size,
+    });
+  }
+
+  async stop(): Promise<void> {
+    if (this.watchdog) {
+      clearInterval(this.watchdog);
+      this.watchdog = null;
+    }
+    await this.unsubscribe();
+    logger.info("SlabWatcher stopped");
+  }
+
+  getStatus(): SlabWatcherStatus {
+    return {
+      running: this.watchdog !== null,
+      watchedSlabs: this.states.size,
+      highestSlot: this.highestSlot,
+      lastNotificationAt: this.lastNotificationAt,
+      reconnects: this.reconnects,
+      decodeFailures: this.decodeFailures,
+    };
+  }
+
+  /**
+   * Apply one slab image. Returns false when it was dropped — older than the
+   * last applied image, or not a decodable slab.
+   */
+  apply(slab: string, slot: number, data: Uint8Array, source: SlabEventSource): boolean {
+    if (slot > this.highestSlot) this.highestSlot = slot;
+
+    const prev = this.states.get(slab);
+    if (prev && slot <= prev.slot) return false;
+
+    let state: SlabState;
+    try {
+      state = parseSlabState(new Uint8Array(data));
+    } catch (err) {
+      this.decodeFailures++;
+      logger.debug("Skipping undecodable account", { slab, error: err instanceof Error ? err.message : err });
+      return false;
+    }
+
+    this.states.set(slab, { slot, state });
+    if (!prev) return true;
+
+    for (const change of diffSlabState(prev.state, state, { slot, previousSlot: prev.slot, source })) {
+      this.bus.publish(change.event, slab, change.data);
+    }
+    return true;
+  }
+
+  /**
+   * Re-read every watched slab no older than the highest slot seen and apply
+   * it. Returns the number of images applied.
+   */
+  async gapFill(): Promise<number> {
+    if (this._gapFilling) return 0;
+    this._gapFilling = true;
+
+    let applied = 0;
+    try {
+      const connection = this.getConnection();
+      const minContextSlot = this.highestSlot > 0 ? this.highestSlot : undefined;
+
+      for (const programId of this.options.programIds ?? []) {
+        try {
+          const { context, value } = await connection.getProgramAccounts(new PublicKey(programId), {
+            commitment: this.commitment,
+            minContextSlot,
+            withContext: true,
+          });
+          for (const { pubkey, account } of value) {
+            if (this.apply(pubkey.toBase58(), context.slot, account.data, "gap-fill")) applied++;
+          }
+        } catch (err) {
+          logger.warn("Gap-fill failed for program", { programId, error: err instanceof Error ? err.message : err });
+        }
+      }
+
+      const slabs = this.options.slabs ?? [];
+      for (let i = 0; i < slabs.length; i += GAP_FILL_BATCH_SIZE) {
+        const batch = slabs.slice(i, i + GAP_FILL_BATCH_SIZE);
+        try {
+          const { context, value } = await connection.getMultipleAccountsInfoAndContext(
+            batch.map((s) => new PublicKey(s)),
+            { commitment: this.commitment, minContextSlot },
+          );
+          for (const [j, info] of value.entries()) {
+            if (info?.data && this.apply(batch[j], context.slot, info.data, "gap-fill")) applied++;
+          }
+        } catch (err) {
+          logger.warn("Gap-fill failed for slab batch", {
+            batch: i / GAP_FILL_BATCH_SIZE + 1,
+            error: err instanceof Error ? err.message : err,
+          });
+        }
+      }
+    } finally {
+      this._gapFilling = false;
+    }
+
+    if (applied > 0) logger.debug("Gap-fill applied", { applied, minSlot: this.highestSlot });
+    return applied;
+  }
+
+  private subscribe(): void {
+    const connection = this.getConnection();
+    for (const programId of this.options.programIds ?? []) {
+      const id = connection.onProgramAccountChange(
+        new PublicKey(programId),
+        (info, ctx) => {
+          this.lastNotificationAt = Date.now();
+          this.apply(info.accountId.toBase58(), ctx.slot, info.accountInfo.data, "subscription");
+        },
+        this.commitment,
+      );
+      this.subscriptions.push({ kind: "program", id });
+    }
+    for (const slab of this.options.slabs ?? []) {
+      const id = connection.onAccountChange(
+        new PublicKey(slab),
+        (info, ctx) => {
+          this.lastNotificationAt = Date.now();
+          this.apply(slab, ctx.slot, info.data, "subscription");
+        },
+        this.commitment,
+      );
+      this.subscriptions.push({ kind: "account", id });
+    }
+    this.lastNotificationAt = Date.now();
+  }
+
+  private async unsubscribe(): Promise<void> {
+    const connection = this.getConnection();
+    const subscriptions = this.subscriptions;
+    this.subscriptions = [];
+    await Promise.all(subscriptions.map(async ({ kind, id }) => {
+      try {
+        if (kind === "program") await connection.removeProgramAccountChangeListener(id);
+        else await connection.removeAccountChangeListener(id);
+      } catch (err) {
+        logger.debug("Failed to remove listener", { kind, id, error: err instanceof Error ? err.message : err });
+      }
+    }));
+  }
+
+  /** Re-open the subscriptions and gap-fill when notifications have stopped. */
+  private async checkStale(): Promise<void> {
+    if (this._gapFilling || Date.now() - this.lastNotificationAt < this.staleAfterMs) return;
+
+    this.reconnects++;
+    logger.warn("No slab notifications — resubscribing", {
+      silentMs: Date.now() - this.lastNotificationAt,
+      highestSlot: this.highestSlot,
+      reconnects: this.reconnects,
+    });
+    await this.unsubscribe();
+    if (!this.watchdog) return; // stopped meanwhile
+    this.subscribe();
+    await this.gapFill();
+  }
+}
@@ -49,6 +49,35 @@ const envSchemaBase = z.object({
   DISCOVERY_INTERVAL_MS: z.coerce.number().int().positive().optional(),
   HELIUS_WEBHOOK_SECRET: z.string().optional(),
   WEBHOOK_URL: z.string().url().optional(),
+  SLAB_ARCHIVE_DIR: z.string().optional(),
+  SLAB_ARCHIVE_INTERVAL_MS: z.coerce.number().int().positive().optional(),
+  SLAB_WATCHER_ENABLED: z.enum(["true", "false"]).optional(),
+  SLAB_WATCHER_STALE_MS: z.coerce.number().int().positive().optional(),
+  EVENT_BUS_TRANSPORT: z.enum(["memory", "postgres", "redis"]).optional(),
+  EVENT_BUS_DATABASE_URL: z.string().optional(),
+  EVENT_BUS_REDIS_URL: z.string().optional(),
+  EVENT_BUS_CONSUMER: z.string().optional(),
+  EVENT_BUS_REPLAY_FROM: z.string().optional(),
+  WEBHOOK_DELIVERY_ENABLED: z.enum(["true", "false"]).optional(),
+  WEBHOOK_MAX_ATTEMPTS: z.coerce.number().int().positive().optional(),
+  WEBHOOK_TIMEOUT_MS: z.coerce.number().int().positive().optional(),
+  STAKE_PROGRAM_ID: z.string().optional(),
+  ORACLE_PRICE_MODE: z.enum(["median", "twap"]).optional(),
+  TWAP_WINDOW_SLOTS: z.coerce.number().int().positive().optional(),
+  TWAP_MIN_SAMPLES: z.coerce.number().int().positive().optional(),
+  TWAP_MIN_DEPTH_USD: z.coerce.number().nonnegative().optional(),
+  TWAP_SAMPLE_INTERVAL_MS: z.coerce.number().int().positive().optional(),
+  CRANK_CU_BUDGET: z.coerce.number().int().positive().optional(),
+  CRANK_FEE_BUDGET_LAMPORTS: z.coerce.number().int().positive().optional(),
+  CRANK_CONCURRENCY: z.coerce.number().int().positive().optional(),
+  KEEPER_COORDINATION: z.enum(["postgres", "memory"]).optional(),
+  KEEPER_DATABASE_URL: z.string().optional(),
+  KEEPER_COORDINATION_REFRESH_MS: z.coerce.number().int().positive().optional(),
+  LIQUIDATION_MAX_PER_TX: z.coerce.number().int().positive().optional(),
+  LIQUIDATION_PROFIT_GATE: z.enum(["true", "false"]).optional(),
+  KEEPER_SHADOW_MODE: z.enum(["true", "false"]).optional(),
+  KEEPER_SHADOW_LIVE_WALLETS: z.string().optional(),
+  KEEPER_SHADOW_REPORT_INTERVAL_MS: z.coerce.number().int().positive().optional(),
 });
 
 export type EnvSchema = z.infer<typeof envSchemaBase>;
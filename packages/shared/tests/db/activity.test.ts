@@ -0,0 +1,92 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+
+const tables: Record<string, unknown[]> = {};
+const calls: { table: string; select: string; filters: unknown[][]; order?: string }[] = [];
+
+/** Chainable, awaitable query builder resolving to `tables[table]`. */
+function builder(table: string) {
+  const call: (typeof calls)[number] = { table, select: "", filters: [] };
+  calls.push(call);
+  const b: any = {
+    select: (cols: string) => ((call.select = cols), b),
+    order: (col: string) => ((call.order = col), b),
+    limit: () => b,
+    range: () => b,
+    then: (resolve: (v: unknown) => unknown) => resolve({ data: tables[call.select.includes("event_type, slot") ? "ranges" : table] ?? [], error: null }),
+  };
+  for (const op of ["eq", "not", "lt", "in", "or"]) b[op] = (...args: unknown[]) => (call.filters.push([op, ...args]), b);
+  return b;
+}
+
+vi.mock("../../src/db/client.js", () => ({
+  getSupabase: vi.fn(() => ({ from: (table: string) => builder(table) })),
+}));
+
+const { getAccountActivity } = await import("../../src/db/activity.js");
+
+const OWNER = "Owner1111111111111111111111111111111111111";
+const SLAB = "Slab11111111111111111111111111111111111111";
+
+function liquidation(slot: number, created_at: string) {
+  return {
+    slab_address: SLAB,
+    event_type: "liquidation",
+    target_idx: 3,
+    caller: "keeper",
+    tx_signature: `liq-${slot}`,
+    slot,
+    block_time: null,
+    created_at,
+  };
+}
+
+describe("getAccountActivity", () => {
+  beforeEach(() => {
+    calls.length = 0;
+    for (const key of Object.keys(tables)) delete tables[key];
+  });
+
+  it("queries liquidations by every range the wallet held the index", async () => {
+    // Held idx 3 over slots 100–200 and again from 500; someone else had it in between
+    tables.ranges = [
+      { slab_address: SLAB, account_idx: 3, event_type: "init_user", slot: 100 },
+      { slab_address: SLAB, account_idx: 3, event_type: "close_account", slot: 200 },
+      { slab_address: SLAB, account_idx: 3, event_type: "init_user", slot: 500 },
+    ];
+    tables.liquidation_events = [
+      liquidation(600, "2026-01-04T00:00:00.000Z"),
+      liquidation(200, "2026-01-02T00:00:00.000Z"),
+    ];
+
+    const entries = await getAccountActivity(OWNER, { limit: 10 });
+
+    const ranges = calls.find((c) => c.select.includes("event_type, slot"))!;
+    expect(ranges.filters).toContainEqual(["in", "event_type", ["init_user", "init_lp", "close_account"]]);
+    const liqs = calls.find((c) => c.table === "liquidation_events")!;
+    expect(liqs.filters).toContainEqual([
+      "or",
+      `and(slab_address.eq.${SLAB},target_idx.eq.3,slot.gte.100,slot.lte.200),` +
+        `and(slab_address.eq.${SLAB},target_idx.eq.3,slot.gte.500)`,
+    ]);
+    expect(entries.map((e) => e.slot)).toEqual([600, 200]);
+  });
+
+  it("pages and orders every source on created_at", async () => {
+    tables.trades = [
+      { slab_address: SLAB, tx_signature: "t", side: "long", size: 5, price: 1, fee: 0, block_time: null, created_at: "2026-01-02T00:00:00.000Z" },
+    ];
+    tables.collateral_events = [
+      { slab_address: SLAB, event_type: "deposit", owner: OWNER, account_idx: 3, amount: "1", tx_signature: "c", slot: 1, block_time: "2025-12-01T00:00:00.000Z", created_at: "2026-01-03T00:00:00.000Z" },
+    ];
+
+    const entries = await getAccountActivity(OWNER, { before: "2026-02-01T00:00:00.000Z", limit: 10 });
+
+    for (const call of calls.filter((c) => !c.select.includes("event_type, slot"))) {
+      expect(call.order).toBe("created_at");
+      expect(call.filters).toContainEqual(["lt", "created_at", "2026-02-01T00:00:00.000Z"]);
+    }
+    // Backfilled deposit: old block time, but indexed last, so it sorts first
+    expect(entries.map((e) => e.type)).toEqual(["deposit", "trade"]);
+    expect(entries[0]).toMatchObject({ time: "2025-12-01T00:00:00.000Z", indexed_at: "2026-01-03T00:00:00.000Z" });
+  });
+});
@@ -0,0 +1,61 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+
+const resolved: { address: string; family: number }[] = [];
+
+vi.mock("node:dns", () => ({
+  lookup: vi.fn((_host: string, _opts: unknown, cb: (err: Error | null, addrs: unknown) => void) => cb(null, resolved)),
+  promises: { lookup: vi.fn(async () => resolved) },
+}));
+
+const { assertPublicHost, isPrivateAddress, isPrivateHostname, publicLookup, PrivateAddressError } = await import(
+  "../src/publicAddress.js"
+);
+
+describe("publicAddress", () => {
+  beforeEach(() => {
+    resolved.length = 0;
+  });
+
+  it("flags loopback, private, link-local and CGNAT addresses", () => {
+    for (const ip of ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0"]) {
+      expect(isPrivateAddress(ip)).toBe(true);
+    }
+    for (const ip of ["8.8.8.8", "172.32.0.1", "100.128.0.1"]) {
+      expect(isPrivateAddress(ip)).toBe(false);
+    }
+  });
+
+  it("checks IPv6 and IPv4 embedded in it", () => {
+    for (const ip of ["::", "::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1", "::ffff:7f00:1", "64:ff9b::a9fe:a9fe"]) {
+      expect(isPrivateAddress(ip)).toBe(true);
+    }
+    expect(isPrivateAddress("::ffff:8.8.8.8")).toBe(false);
+    expect(isPrivateAddress("2606:4700::1111")).toBe(false);
+  });
+
+  it("flags local names and bracketed literals", () => {
+    expect(isPrivateHostname("localhost")).toBe(true);
+    expect(isPrivateHostname("metadata.google.internal")).toBe(true);
+    expect(isPrivateHostname("[::1]")).toBe(true);
+    expect(isPrivateHostname("hooks.example.com")).toBe(false);
+  });
+
+  it("rejects a name if any address it resolves to is private", async () => {
+    resolved.push({ address: "93.184.216.34", family: 4 }, { address: "10.0.0.5", family: 4 });
+
+    await expect(assertPublicHost("rebind.example.com")).rejects.toBeInstanceOf(PrivateAddressError);
+    const err = await new Promise<Error | null>((resolve) => publicLookup("rebind.example.com", {}, (e) => resolve(e)));
+    expect(err).toBeInstanceOf(PrivateAddressError);
+  });
+
+  it("hands back the checked address for the connection", async () => {
+    resolved.push({ address: "93.184.216.34", family: 4 });
+
+    await expect(assertPublicHost("hooks.example.com")).resolves.toBeUndefined();
+    const [address, family] = await new Promise<[unknown, unknown]>((resolve) =>
+      publicLookup("hooks.example.com", {}, (_e, a, f) => resolve([a, f])),
+    );
+    expect(address).toBe("93.184.216.34");
+    expect(family).toBe(4);
+  });
+});
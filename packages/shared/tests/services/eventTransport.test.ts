@@ -0,0 +1,213 @@
+import { describe, it, expect, vi, afterEach } from "vitest";
+import { eventBus } from "../../src/services/events.js";
+import {
+  EventBridge,
+  validateEnvelope,
+  type EventEnvelope,
+  type EventTransport,
+  type StoredEvent,
+} from "../../src/services/eventTransport.js";
+
+const SLAB = "11111111111111111111111111111111";
+
+/** In-memory log with numeric offsets, standing in for Postgres/Redis */
+class MemoryTransport implements EventTransport {
+  readonly name = "test";
+  log: StoredEvent[] = [];
+  offsets = new Map<string, string>();
+  failAppends = 0;
+
+  async append(envelope: EventEnvelope): Promise<string> {
+    if (this.failAppends > 0) {
+      this.failAppends--;
+      throw new Error("connection refused");
+    }
+    return this.push(envelope);
+  }
+
+  push(envelope: unknown): string {
+    const offset = String(this.log.length + 1);
+    this.log.push({ offset, envelope: JSON.parse(JSON.stringify(envelope)) });
+    return offset;
+  }
+
+  async read(offset: string | null, limit: number, waitMs: number): Promise<StoredEvent[]> {
+    const after = (o: string | null) => this.log.slice(o === null ? 0 : Number(o)).slice(0, limit);
+    const ready = after(offset);
+    if (ready.length > 0) return ready;
+    await new Promise((resolve) => setTimeout(resolve, waitMs));
+    return after(offset);
+  }
+
+  async latestOffset() {
+    return this.log.length > 0 ? String(this.log.length) : null;
+  }
+
+  async loadOffset(consumer: string) {
+    return this.offsets.get(consumer) ?? null;
+  }
+
+  async saveOffset(consumer: string, offset: string) {
+    this.offsets.set(consumer, offset);
+  }
+
+  async close() {}
+}
+
+function envelope(id: string, origin = "indexer", overrides: Partial<EventEnvelope> = {}): EventEnvelope {
+  return {
+    id,
+    origin,
+    event: "crank.success",
+    slabAddress: SLAB,
+    timestamp: 1_700_000_000_000,
+    data: { signature: `sig-${id}` },
+    ...overrides,
+  };
+}
+
+function fakeBus() {
+  return { deliver: vi.fn(), setForwarder: vi.fn() };
+}
+
+describe("validateEnvelope", () => {
+  it("accepts known events whose data matches the schema", () => {
+    expect(validateEnvelope(envelope("a"))).toBeNull();
+    expect(validateEnvelope(envelope("b", "x", {
+      event: "oi.changed",
+      data: {
+        slot: 20, previousSlot: 10, source: "subscription",
+        totalOpenInterest: "250", longOi: "150", shortOi: "100",
+        previous: { totalOpenInterest: "200", longOi: "100", shortOi: "100" },
+      },
+    }))).toBeNull();
+  });
+
+  it("rejects unknown events, missing fields and mistyped data", () => {
+    expect(validateEnvelope({ ...envelope("a"), event: "nope" })).toMatch(/event/);
+    expect(validateEnvelope({ ...envelope("a"), id: undefined })).toMatch(/id/);
+    expect(validateEnvelope(envelope("a", "x", { data: {} }))).toMatch(/^crank\.success: data\.signature/);
+    expect(validateEnvelope(envelope("a", "x", {
+      event: "oi.changed",
+      data: { slot: 1, previousSlot: 0, source: "subscription", totalOpenInterest: 5, longOi: "1", shortOi: "1", previous: {} },
+    }))).toMatch(/totalOpenInterest/);
+  });
+});
+
+describe("EventBridge", () => {
+  const bridges: EventBridge[] = [];
+
+  afterEach(async () => {
+    vi.useRealTimers();
+    await Promise.all(bridges.splice(0).map((b) => b.stop()));
+  });
+
+  function bridge(transport: EventTransport, options = {}, bus = fakeBus()) {
+    const b = new EventBridge(transport, { origin: "keeper", waitMs: 5, ...options }, bus);
+    bridges.push(b);
+    return b;
+  }
+
+  it("forwards local publishes and leaves the bus when stopped", async () => {
+    const transport = new MemoryTransport();
+    const b = new EventBridge(transport, { origin: "keeper", consume: false });
+    await b.start();
+
+    eventBus.publish("crank.success", SLAB, { signature: "abc" });
+    eventBus.publish("crank.success", SLAB, { notASignature: 1 });
+    await vi.waitFor(() => expect(transport.log).toHaveLength(1));
+
+    expect(transport.log[0].envelope).toMatchObject({ origin: "keeper", event: "crank.success", data: { signature: "abc" } });
+    expect(b.getStatus()).toMatchObject({ appended: 1, invalid: 1 });
+
+    await b.stop();
+    eventBus.publish("crank.success", SLAB, { signature: "def" });
+    expect(transport.log).toHaveLength(1);
+  });
+
+  it("does not append when publishing is off", async () => {
+    const transport = new MemoryTransport();
+    const b = bridge(transport, { publish: false, consume: false });
+    await b.start();
+
+    b.forward({ event: "crank.success", slabAddress: SLAB, timestamp: 1, data: { signature: "abc" } });
+    await Promise.resolve();
+
+    expect(transport.log).toHaveLength(0);
+  });
+
+  it("keeps failed appends queued and retries them in order", async () => {
+    vi.useFakeTimers();
+    const transport = new MemoryTransport();
+    transport.failAppends = 2;
+    const b = bridge(transport, { consume: false });
+    await b.start();
+
+    b.forward({ event: "crank.success", slabAddress: SLAB, timestamp: 1, data: { signature: "first" } });
+    b.forward({ event: "crank.success", slabAddress: SLAB, timestamp: 2, data: { signature: "second" } });
+    await vi.advanceTimersByTimeAsync(0);
+    expect(b.getStatus()).toMatchObject({ outbox: 2, appendFailures: 1 });
+
+    // 1s then 2s backoff
+    await vi.advanceTimersByTimeAsync(3_000);
+    expect(transport.log.map((e) => (e.envelope as EventEnvelope).data.signature)).toEqual(["first", "second"]);
+    expect(b.getStatus()).toMatchObject({ outbox: 0, appended: 2, appendFailures: 2 });
+  });
+
+  it("delivers other origins once, skips its own and saves the offset", async () => {
+    const transport = new MemoryTransport();
+    transport.push(envelope("old"));
+    const bus = fakeBus();
+    const b = bridge(transport, { consumer: "api" }, bus);
+    await b.start();
+
+    transport.push(envelope("e1"));
+    transport.push(envelope("e1")); // retried append landing twice
+    transport.push(envelope("mine", "keeper"));
+    transport.push({ id: "bad" });
+    transport.push(envelope("e2"));
+
+    await vi.waitFor(() => expect(transport.offsets.get("api")).toBe("6"));
+    // Started at the newest event: "old" is not replayed
+    expect(bus.deliver.mock.calls.map(([p]) => p.data.signature)).toEqual(["sig-e1", "sig-e2"]);
+    expect(bus.deliver.mock.calls[0][0]).toEqual({
+      event: "crank.success",
+      slabAddress: SLAB,
+      timestamp: 1_700_000_000_000,
+      data: { signature: "sig-e1" },
+    });
+    expect(b.getStatus()).toMatchObject({ delivered: 2, duplicates: 1, invalid: 1, offset: "6" });
+  });
+
+  it("resumes from the saved offset, or replays from the earliest event", async () => {
+    const transport = new MemoryTransport();
+    transport.push(envelope("e1"));
+    transport.push(envelope("e2"));
+    transport.push(envelope("e3"));
+    transport.offsets.set("api", "2");
+
+    const resumed = fakeBus();
+    await bridge(transport, { consumer: "api" }, resumed).start();
+    await vi.waitFor(() => expect(resumed.deliver).toHaveBeenCalledTimes(1));
+    expect(resumed.deliver.mock.calls[0][0].data.signature).toBe("sig-e3");
+
+    const replayed = fakeBus();
+    await bridge(transport, { consumer: "api", replayFrom: "earliest" }, replayed).start();
+    await vi.waitFor(() => expect(replayed.deliver).toHaveBeenCalledTimes(3));
+  });
+
+  it("keeps consuming when a listener throws", async () => {
+    const transport = new MemoryTransport();
+    const bus = fakeBus();
+    bus.deliver.mockImplementationOnce(() => {
+      throw new Error("listener bug");
+    });
+    const b = bridge(transport, { replayFrom: "earliest" }, bus);
+    transport.push(envelope("e1"));
+    transport.push(envelope("e2"));
+    await b.start();
+
+    await vi.waitFor(() => expect(bus.deliver).toHaveBeenCalledTimes(2));
+    expect(b.getStatus().offset).toBe("2");
+  });
+});
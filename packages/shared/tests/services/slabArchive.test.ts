@@ -0,0 +1,62 @@
+import { describe, it, expect, beforeEach, afterEach } from "vitest";
+import { mkdtempSync, rmSync, readdirSync, appendFileSync } from "node:fs";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+import { SlabArchive } from "../../src/services/slabArchive.js";
+
+const SLAB = "FxfD37s1AZTeWfFQps9Zpebi2dNQ9QSSDtfMKdbsfKrD";
+
+function image(fill: number): Uint8Array {
+  return new Uint8Array(4096).fill(fill);
+}
+
+describe("SlabArchive", () => {
+  let root: string;
+
+  beforeEach(() => {
+    root = mkdtempSync(join(tmpdir(), "slab-archive-"));
+  });
+
+  afterEach(() => {
+    rmSync(root, { recursive: true, force: true });
+  });
+
+  it("stores only changed images and resolves state at a slot", async () => {
+    const archive = new SlabArchive(root);
+
+    expect((await archive.put(SLAB, 100, image(1))).written).toBe(true);
+    expect((await archive.put(SLAB, 110, image(1))).written).toBe(false);
+    expect((await archive.put(SLAB, 120, image(2))).written).toBe(true);
+
+    expect((await archive.entries(SLAB)).map((e) => e.slot)).toEqual([100, 120]);
+    expect(await archive.getAt(SLAB, 99)).toBeNull();
+    expect((await archive.getAt(SLAB, 115))!.data[0]).toBe(1);
+    expect((await archive.getAt(SLAB, 120))!.entry.slot).toBe(120);
+    expect((await archive.getAt(SLAB, 10_000))!.data).toEqual(image(2));
+  });
+
+  it("shares blobs between identical images", async () => {
+    const archive = new SlabArchive(root);
+    await archive.put(SLAB, 100, image(1));
+    await archive.put(SLAB, 120, image(2));
+    await archive.put(SLAB, 130, image(1));
+
+    expect(await archive.entries(SLAB)).toHaveLength(3);
+    const blobs = readdirSync(join(root, "blobs"), { recursive: true }).filter((f) => String(f).endsWith(".gz"));
+    expect(blobs).toHaveLength(2);
+  });
+
+  it("picks up the previous index after a restart and ignores a torn line", async () => {
+    await new SlabArchive(root).put(SLAB, 100, image(1));
+    appendFileSync(join(root, "index", `${SLAB}.jsonl`), '{"slot":1');
+
+    const reopened = new SlabArchive(root);
+    expect((await reopened.put(SLAB, 105, image(1))).written).toBe(false);
+    expect((await reopened.put(SLAB, 130, image(2))).written).toBe(true);
+    expect((await reopened.entries(SLAB)).map((e) => e.slot)).toEqual([100, 130]);
+  });
+
+  it("rejects slab names that are not base58 addresses", async () => {
+    await expect(new SlabArchive(root).entries("../etc")).rejects.toThrow("Invalid slab address");
+  });
+});
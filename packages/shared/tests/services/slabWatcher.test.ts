@@ -0,0 +1,186 @@
+import { describe, it, expect, vi, beforeEach, afterEach } from "vitest";
+import { PublicKey } from "@solana/web3.js";
+
+vi.mock("@percolator/sdk", () => ({
+  parseSlabState: vi.fn(),
+}));
+
+import { parseSlabState } from "@percolator/sdk";
+import { SlabWatcher, diffSlabState } from "../../src/services/slabWatcher.js";
+
+const SLAB = "11111111111111111111111111111111";
+const PROGRAM = "FxfD37s1AZTeWfFQps9Zpebi2dNQ9QSSDtfMKdbsfKrD";
+const OWNER = new PublicKey("So11111111111111111111111111111111111111112");
+const META = { slot: 20, previousSlot: 10, source: "subscription" as const };
+
+function account(positionSize: bigint, capital = 1_000n) {
+  return {
+    kind: 0,
+    accountId: 1n,
+    owner: OWNER,
+    capital,
+    pnl: 0n,
+    positionSize,
+    entryPrice: 1_000_000n,
+  };
+}
+
+function state(opts: {
+  paused?: boolean;
+  maintenanceMarginBps?: bigint;
+  price?: bigint;
+  insurance?: bigint;
+  longOi?: bigint;
+  accounts?: { idx: number; account: ReturnType<typeof account> }[];
+} = {}): any {
+  const longOi = opts.longOi ?? 100n;
+  return {
+    header: { paused: opts.paused ?? false, resolved: false, admin: OWNER, nonce: 1n },
+    config: { lastEffectivePriceE6: opts.price ?? 1_000_000n, maxStalenessSlots: 50n },
+    params: { maintenanceMarginBps: opts.maintenanceMarginBps ?? 500n },
+    engine: {
+      insuranceFund: { balance: opts.insurance ?? 5_000n, feeRevenue: 0n, isolatedBalance: 0n, isolationBps: 0 },
+      totalOpenInterest: longOi + 100n,
+      longOi,
+      shortOi: 100n,
+    },
+    accounts: opts.accounts ?? [{ idx: 0, account: account(10n) }],
+  };
+}
+
+describe("diffSlabState", () => {
+  it("reports nothing for price-only and capital-only moves", () => {
+    const prev = state();
+    const next = state({ price: 2_000_000n, accounts: [{ idx: 0, account: account(10n, 900n) }] });
+
+    expect(diffSlabState(prev, next, META)).toEqual([]);
+  });
+
+  it("publishes one typed event per kind of change", () => {
+    const prev = state();
+    const next = state({
+      paused: true,
+      maintenanceMarginBps: 600n,
+      insurance: 4_000n,
+      longOi: 150n,
+      accounts: [{ idx: 0, account: account(15n) }, { idx: 2, account: account(0n) }],
+    });
+
+    const changes = diffSlabState(prev, next, META);
+
+    expect(changes.map((c) => c.event)).toEqual([
+      "market.paused",
+      "config.changed",
+      "insurance.changed",
+      "oi.changed",
+      "position.changed",
+      "position.changed",
+    ]);
+    expect(changes[0].data).toEqual({ ...META, paused: true });
+    expect(changes[1].data).toMatchObject({
+      changes: [{ field: "params.maintenanceMarginBps", before: "500", after: "600" }],
+    });
+    expect(changes[2].data).toMatchObject({ balance: "4000", previous: { balance: "5000" } });
+    expect(changes[3].data).toMatchObject({ longOi: "150", totalOpenInterest: "250", previous: { longOi: "100" } });
+    expect(changes[4].data).toMatchObject({
+      accountIdx: 0,
+      before: { positionSize: "10", owner: OWNER.toBase58() },
+      after: { positionSize: "15" },
+    });
+    expect(changes[5].data).toMatchObject({ accountIdx: 2, before: null, after: { positionSize: "0" } });
+  });
+});
+
+describe("SlabWatcher", () => {
+  let connection: any;
+  let bus: { publish: ReturnType<typeof vi.fn> };
+  let onProgram: (info: any, ctx: { slot: number }) => void;
+
+  // Slab images are keyed by their first byte
+  const images = new Map<number, any>();
+  const image = (n: number) => Buffer.from([n]);
+
+  beforeEach(() => {
+    vi.useFakeTimers();
+    images.clear();
+    images.set(1, state());
+    images.set(2, state({ longOi: 150n }));
+    images.set(3, state({ longOi: 150n, insurance: 1_000n }));
+    vi.mocked(parseSlabState).mockImplementation((data: Uint8Array) => {
+      const s = images.get(data[0]);
+      if (!s) throw new Error("Unrecognized slab size");
+      return s;
+    });
+
+    connection = {
+      onProgramAccountChange: vi.fn((_pid, cb) => {
+        onProgram = cb;
+        return connection.onProgramAccountChange.mock.calls.length;
+      }),
+      removeProgramAccountChangeListener: vi.fn(async () => {}),
+      getProgramAccounts: vi.fn(async () => ({
+        context: { slot: 10 },
+        value: [{ pubkey: new PublicKey(SLAB), account: { data: image(1) } }],
+      })),
+    };
+    bus = { publish: vi.fn() };
+  });
+
+  afterEach(() => {
+    vi.useRealTimers();
+  });
+
+  function notify(n: number, slot: number) {
+    onProgram({ accountId: new PublicKey(SLAB), accountInfo: { data: image(n) } }, { slot });
+  }
+
+  it("baselines on start, then publishes diffs from notifications in slot order", async () => {
+    const watcher = new SlabWatcher(() => connection, { programIds: [PROGRAM] }, bus);
+    await watcher.start();
+
+    expect(bus.publish).not.toHaveBeenCalled();
+    expect(watcher.getStatus()).toMatchObject({ watchedSlabs: 1, highestSlot: 10 });
+
+    notify(2, 12);
+    expect(bus.publish).toHaveBeenCalledWith(
+      "oi.changed",
+      SLAB,
+      expect.objectContaining({ slot: 12, previousSlot: 10, source: "subscription", longOi: "150" }),
+    );
+
+    // Older image arriving late is dropped
+    notify(1, 11);
+    expect(bus.publish).toHaveBeenCalledTimes(1);
+
+    // Non-slab accounts owned by the program are skipped
+    onProgram({ accountId: PublicKey.default, accountInfo: { data: image(9) } }, { slot: 13 });
+    expect(watcher.getStatus().decodeFailures).toBe(1);
+
+    await watcher.stop();
+    expect(connection.removeProgramAccountChangeListener).toHaveBeenCalledWith(1);
+  });
+
+  it("re-subscribes after silence and gap-fills from the highest slot seen", async () => {
+    const watcher = new SlabWatcher(() => connection, { programIds: [PROGRAM], staleAfterMs: 10_000 }, bus);
+    await watcher.start();
+    notify(2, 12);
+    bus.publish.mockClear();
+
+    connection.getProgramAccounts.mockResolvedValueOnce({
+      context: { slot: 30 },
+      value: [{ pubkey: new PublicKey(SLAB), account: { data: image(3) } }],
+    });
+    await vi.advanceTimersByTimeAsync(15_000);
+
+    expect(connection.onProgramAccountChange).toHaveBeenCalledTimes(2);
+    expect(connection.getProgramAccounts.mock.calls[1][1]).toMatchObject({ minContextSlot: 12, withContext: true });
+    expect(bus.publish).toHaveBeenCalledWith(
+      "insurance.changed",
+      SLAB,
+      expect.objectContaining({ slot: 30, previousSlot: 12, source: "gap-fill", balance: "1000" }),
+    );
+    expect(watcher.getStatus().reconnects).toBe(1);
+
+    await watcher.stop();
+  });
+});
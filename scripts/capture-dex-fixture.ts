@@ -0,0 +1,77 @@
+#!/usr/bin/env npx tsx
+/**
+ * Capture a DEX pool (and its vaults, for reserve-priced pools) as a fixture
+ * for packages/core/test/dex-pools.test.ts.
+ *
+ * Usage: npx tsx scripts/capture-dex-fixture.ts <POOL_PUBKEY> [fixture-name]
+ *
+ * Pool, vaults and mints are read at one slot, so the recorded price is
+ * consistent with the recorded reserves. Point RPC_URL at mainnet.
+ */
+import { Connection, PublicKey } from "@solana/web3.js";
+import { writeFileSync } from "node:fs";
+import { join } from "node:path";
+import * as dotenv from "dotenv";
+import {
+  detectDexType,
+  parseDexPool,
+  computeDexSpotPriceE6,
+  dexRequiresVaultData,
+} from "../packages/core/src/solana/dex-oracle.js";
+dotenv.config();
+
+const RPC = process.env.RPC_URL ?? "https://api.mainnet-beta.solana.com";
+const poolArg = process.argv[2];
+if (!poolArg) throw new Error("Usage: npx tsx scripts/capture-dex-fixture.ts <POOL_PUBKEY> [fixture-name]");
+
+const image = (data: Uint8Array) => ({
+  dataLen: data.length,
+  writes: [{ offset: 0, hex: Buffer.from(data).toString("hex") }],
+});
+
+async function main() {
+  const conn = new Connection(RPC, "confirmed");
+  const poolKey = new PublicKey(poolArg);
+  const pool = await conn.getAccountInfo(poolKey);
+  if (!pool) throw new Error(`Pool ${poolArg} not found`);
+
+  const dexType = detectDexType(pool.owner);
+  if (!dexType) throw new Error(`Unsupported pool owner ${pool.owner.toBase58()}`);
+  const info = parseDexPool(dexType, poolKey, new Uint8Array(pool.data));
+
+  // Re-read everything at one slot
+  const keys = [poolKey, info.baseMint, info.quoteMint];
+  if (dexRequiresVaultData(dexType)) keys.push(info.baseVault!, info.quoteVault!);
+  const { context, value } = await conn.getMultipleAccountsInfoAndContext(keys);
+  if (value.some((a) => !a)) throw new Error("Pool, mint or vault account missing");
+  const [poolAcc, baseMintAcc, quoteMintAcc, baseVault, quoteVault] = value.map((a) => new Uint8Array(a!.data));
+
+  const vaultData = baseVault && quoteVault ? { base: baseVault, quote: quoteVault } : undefined;
+  const priceE6 = computeDexSpotPriceE6(dexType, poolAcc, vaultData);
+
+  const fixture = {
+    description: `${dexType} pool captured at slot ${context.slot} (base decimals ${baseMintAcc[44]}, quote decimals ${quoteMintAcc[44]})`,
+    dexType,
+    address: poolKey.toBase58(),
+    owner: pool.owner.toBase58(),
+    ...image(poolAcc),
+    ...(vaultData ? { vaults: { base: image(vaultData.base), quote: image(vaultData.quote) } } : {}),
+    expected: {
+      baseMint: info.baseMint.toBase58(),
+      quoteMint: info.quoteMint.toBase58(),
+      ...(info.baseVault ? { baseVault: info.baseVault.toBase58() } : {}),
+      ...(info.quoteVault ? { quoteVault: info.quoteVault.toBase58() } : {}),
+      priceE6: priceE6.toString(),
+    },
+  };
+
+  const name = process.argv[3] ?? `${dexType}-${poolArg.slice(0, 8)}`;
+  const out = join("packages/core/test/fixtures/dex", `${name}.json`);
+  writeFileSync(out, JSON.stringify(fixture, null, 2) + "\n");
+  console.log(`Wrote ${out} (priceE6=${priceE6}, slot=${context.slot})`);
+}
+
+main().catch((e) => {
+  console.error(e);
+  process.exit(1);
+});
@@ -0,0 +1,184 @@
+#!/usr/bin/env npx tsx
+/**
+ * execute-adl-plan.ts — Execute a keeper ADL plan via AdminForceClose.
+ *
+ * Native auto-deleveraging (PERC-305) is not on-chain yet. The keeper plans
+ * it instead (GET /adl on its health port); this script fetches a market's
+ * plan and force-closes the selected positions, one transaction each, in
+ * ranked order. Each position is re-read first and skipped if it has been
+ * closed or flipped since the plan was made.
+ *
+ * Usage:
+ *   # Show the plan and the instructions without sending
+ *   npx tsx scripts/execute-adl-plan.ts --market <SLAB_PUBKEY> --dry-run
+ *
+ *   # Execute (optionally only the first N positions)
+ *   npx tsx scripts/execute-adl-plan.ts --market <SLAB_PUBKEY> [--max 3]
+ *
+ * Prerequisites:
+ *   - Market admin keypair at ADMIN_KEYPAIR_PATH or
+ *     ~/.config/solana/percolator-upgrade-authority.json
+ *   - KEEPER_URL (or --keeper), defaults to http://localhost:8081
+ *   - RPC_URL env var, or defaults to devnet
+ */
+
+import {
+  Connection,
+  Keypair,
+  PublicKey,
+  Transaction,
+  sendAndConfirmTransaction,
+  ComputeBudgetProgram,
+} from "@solana/web3.js";
+import * as fs from "fs";
+import { parseArgs } from "node:util";
+
+import { fetchSlab, parseAccount } from "../packages/core/src/index.js";
+import { ADL_EXECUTOR } from "../packages/keeper/src/services/adl-planner.js";
+
+// ---------------------------------------------------------------------------
+// Args
+// ---------------------------------------------------------------------------
+const { values: args } = parseArgs({
+  options: {
+    market:    { type: "string" },
+    keeper:    { type: "string" },
+    max:       { type: "string" },
+    "dry-run": { type: "boolean", default: false },
+  },
+  strict: true,
+});
+
+if (!args.market) {
+  console.error("❌  --market <SLAB_PUBKEY> is required");
+  process.exit(1);
+}
+
+const DRY_RUN    = args["dry-run"] ?? false;
+const RPC_URL    = process.env.RPC_URL ?? "https://api.devnet.solana.com";
+const KEEPER_URL = args.keeper ?? process.env.KEEPER_URL ?? "http://localhost:8081";
+const MAX        = args.max ? Number(args.max) : Infinity;
+
+/** Plan as served by the keeper — bigints arrive as decimal strings */
+interface ServedAdlPlan {
+  plan: {
+    assessment: { triggers: string[]; badDebt: string; insuranceAvailable: string; shortfall: string };
+    side: "long" | "short" | null;
+    target: string;
+    targetKind: "pnl" | "oi" | null;
+    covered: string;
+    exhausted: boolean;
+    selected: { accountIdx: number; owner: string; positionSize: string; unrealizedPnl: string; leverageBps: string }[];
+  };
+  programId: string;
+  oracle: string;
+  observedAt: number;
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+function loadKeypair(path: string): Keypair {
+  const resolved = path.startsWith("~")
+    ? path.replace("~", process.env.HOME ?? "")
+    : path;
+  return Keypair.fromSecretKey(
+    Uint8Array.from(JSON.parse(fs.readFileSync(resolved, "utf8")))
+  );
+}
+
+async function fetchPlan(market: string): Promise<ServedAdlPlan | null> {
+  const res = await fetch(`${KEEPER_URL}/adl`);
+  if (!res.ok) throw new Error(`Keeper /adl returned ${res.status}`);
+  const body = (await res.json()) as { markets: Record<string, ServedAdlPlan> };
+  return body.markets[market] ?? null;
+}
+
+// ---------------------------------------------------------------------------
+// Main
+// ---------------------------------------------------------------------------
+async function main() {
+  console.log("=".repeat(60));
+  console.log("Percolator: EXECUTE ADL PLAN");
+  if (DRY_RUN) console.log("⚠️  DRY-RUN MODE — no transaction will be sent");
+  console.log("=".repeat(60));
+
+  if (ADL_EXECUTOR.authority !== "admin") {
+    console.error(`❌ Executor ${ADL_EXECUTOR.name} is keeper-signed — the keeper runs it, not this script`);
+    process.exit(1);
+  }
+
+  const served = await fetchPlan(args.market!);
+  if (!served) {
+    console.log(`\n✅ No ADL plan for ${args.market} — nothing to do.`);
+    return;
+  }
+
+  const { plan } = served;
+  console.log(`\nSlab      : ${args.market}`);
+  console.log(`Keeper    : ${KEEPER_URL} (plan from ${new Date(served.observedAt).toISOString()})`);
+  console.log(`Triggers  : ${plan.assessment.triggers.join(", ")}`);
+  console.log(`Bad debt  : ${plan.assessment.badDebt}  insurance: ${plan.assessment.insuranceAvailable}  shortfall: ${plan.assessment.shortfall}`);
+  console.log(`Target    : ${plan.target} ${plan.targetKind ?? ""} on the ${plan.side ?? "-"} side, covered ${plan.covered}`);
+  if (plan.exhausted) console.log("⚠️  Every eligible position together does not cover the target");
+  console.log(`Executor  : ${ADL_EXECUTOR.name}\n`);
+
+  const conn = new Connection(RPC_URL, "confirmed");
+  const adminPath =
+    process.env.ADMIN_KEYPAIR_PATH ??
+    `${process.env.HOME}/.config/solana/percolator-upgrade-authority.json`;
+
+  let admin: Keypair;
+  try {
+    admin = loadKeypair(adminPath);
+  } catch {
+    console.error(`❌ Cannot load admin keypair from ${adminPath}`);
+    console.error("   Set ADMIN_KEYPAIR_PATH env var to point to the correct file.");
+    process.exit(1);
+  }
+
+  const slab = new PublicKey(args.market!);
+  const ctx = {
+    programId: new PublicKey(served.programId),
+    slab,
+    oracle: new PublicKey(served.oracle),
+    authority: admin.publicKey,
+  };
+
+  let sent = 0;
+  for (const target of plan.selected.slice(0, MAX)) {
+    const planned = BigInt(target.positionSize);
+    const live = parseAccount(await fetchSlab(conn, slab), target.accountIdx);
+    const label = `#${target.accountIdx} (${target.owner.slice(0, 8)}…, pnl ${target.unrealizedPnl}, ${Number(target.leverageBps) / 10_000}x)`;
+    if (live.owner.toBase58() !== target.owner) {
+      console.log(`  skip ${label}: index now belongs to ${live.owner.toBase58()}`);
+      continue;
+    }
+    if (live.positionSize === 0n || (live.positionSize > 0n) !== (planned > 0n)) {
+      console.log(`  skip ${label}: position closed or flipped since the plan`);
+      continue;
+    }
+
+    const ix = ADL_EXECUTOR.instruction(ctx, target.accountIdx);
+    if (DRY_RUN) {
+      console.log(`  ${label}: data ${Buffer.from(ix.data).toString("hex")}`);
+      continue;
+    }
+
+    const tx = new Transaction().add(ComputeBudgetProgram.setComputeUnitLimit({ units: 200_000 }), ix);
+    try {
+      const sig = await sendAndConfirmTransaction(conn, tx, [admin], { commitment: "confirmed" });
+      sent++;
+      console.log(`  ✅ ${label}: ${sig}`);
+    } catch (err) {
+      console.error(`  ❌ ${label}: ${err instanceof Error ? err.message : String(err)}`);
+    }
+  }
+
+  console.log(DRY_RUN ? "\n✅ Dry-run complete — no changes made." : `\n✅ Force-closed ${sent} position(s).`);
+}
+
+main().catch((err) => {
+  console.error("Fatal:", err);
+  process.exit(1);
+});
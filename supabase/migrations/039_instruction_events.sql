@@ -0,0 +1,39 @@
+-- Migration: 039_instruction_events
+-- Every percolator instruction seen by the indexer (poller and Helius webhook),
+-- not only trades. One row per instruction, including CPIs into percolator.
+-- Typed per-category tables live in 040_account_activity_tables.sql; this table
+-- is the catch-all so no instruction type is ever dropped.
+-- Written by the indexer via service_role (bypasses RLS); no public access.
+
+CREATE TABLE IF NOT EXISTS instruction_events (
+  id            BIGSERIAL     PRIMARY KEY,
+  tx_signature  TEXT          NOT NULL,
+  -- Top-level instruction index, and position among its CPIs (-1 = top-level)
+  ix_index      SMALLINT      NOT NULL,
+  inner_index   SMALLINT      NOT NULL DEFAULT -1,
+  slot          BIGINT        NOT NULL,
+  block_time    TIMESTAMPTZ,
+  slab_address  TEXT,
+  tag           SMALLINT      NOT NULL,
+  name          TEXT          NOT NULL,
+  -- First signer in the instruction's account spec (user, admin, caller, ...)
+  signer        TEXT,
+  -- Decoded instruction args; u64/i128 values are stored as decimal strings
+  args          JSONB,
+  created_at    TIMESTAMPTZ   NOT NULL DEFAULT NOW(),
+  CONSTRAINT instruction_events_unique UNIQUE (tx_signature, ix_index, inner_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_instruction_events_slab_slot
+  ON instruction_events(slab_address, slot DESC);
+CREATE INDEX IF NOT EXISTS idx_instruction_events_signer_slot
+  ON instruction_events(signer, slot DESC);
+CREATE INDEX IF NOT EXISTS idx_instruction_events_name
+  ON instruction_events(name);
+
+ALTER TABLE instruction_events ENABLE ROW LEVEL SECURITY;
+-- No policies: service_role bypasses RLS; reads go through the API.
+
+COMMENT ON TABLE instruction_events IS 'Every decoded percolator instruction (top-level and CPI) from successful transactions';
+COMMENT ON COLUMN instruction_events.inner_index IS 'Index among the CPIs of ix_index, or -1 for a top-level instruction';
+COMMENT ON COLUMN instruction_events.args IS 'Decoded instruction arguments (bigints as decimal strings, pubkeys as base58)';
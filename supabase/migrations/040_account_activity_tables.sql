@@ -0,0 +1,108 @@
+-- Migration: 040_account_activity_tables
+-- Typed history for non-trade instructions, populated by the indexer alongside
+-- instruction_events (039). Backs the per-account activity timeline API.
+--
+--   collateral_events    InitUser / InitLP / DepositCollateral / WithdrawCollateral / CloseAccount
+--   liquidation_events   LiquidateAtOracle / AdminForceClose
+--   insurance_movements  TopUpInsurance / DepositInsuranceLP / WithdrawInsuranceLP /
+--                        FundMarketInsurance / WithdrawInsurance
+--   market_admin_events  pauses, config/risk/oracle updates, admin changes, resolution
+--
+-- Amounts are on-chain u64 values and use NUMERIC (see 024_bigint_overflow_fix).
+-- Rows are keyed like instruction_events so re-indexing a transaction is a no-op.
+
+CREATE TABLE IF NOT EXISTS collateral_events (
+  id            BIGSERIAL     PRIMARY KEY,
+  tx_signature  TEXT          NOT NULL,
+  ix_index      SMALLINT      NOT NULL,
+  inner_index   SMALLINT      NOT NULL DEFAULT -1,
+  slot          BIGINT        NOT NULL,
+  block_time    TIMESTAMPTZ,
+  slab_address  TEXT          NOT NULL,
+  event_type    TEXT          NOT NULL
+    CHECK (event_type IN ('init_user', 'init_lp', 'deposit', 'withdraw', 'close_account')),
+  owner         TEXT          NOT NULL,
+  -- Slab account index; null for InitUser/InitLP (assigned on-chain)
+  account_idx   INTEGER,
+  -- Collateral amount, or the account-creation fee for init_*; null for close_account
+  amount        NUMERIC,
+  created_at    TIMESTAMPTZ   NOT NULL DEFAULT NOW(),
+  CONSTRAINT collateral_events_unique UNIQUE (tx_signature, ix_index, inner_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_collateral_events_owner_slot
+  ON collateral_events(owner, slot DESC);
+CREATE INDEX IF NOT EXISTS idx_collateral_events_slab_idx
+  ON collateral_events(slab_address, account_idx);
+
+CREATE TABLE IF NOT EXISTS liquidation_events (
+  id            BIGSERIAL     PRIMARY KEY,
+  tx_signature  TEXT          NOT NULL,
+  ix_index      SMALLINT      NOT NULL,
+  inner_index   SMALLINT      NOT NULL DEFAULT -1,
+  slot          BIGINT        NOT NULL,
+  block_time    TIMESTAMPTZ,
+  slab_address  TEXT          NOT NULL,
+  event_type    TEXT          NOT NULL CHECK (event_type IN ('liquidation', 'force_close')),
+  -- Keeper (liquidation) or admin (force_close) that sent the instruction
+  caller        TEXT,
+  target_idx    INTEGER       NOT NULL,
+  created_at    TIMESTAMPTZ   NOT NULL DEFAULT NOW(),
+  CONSTRAINT liquidation_events_unique UNIQUE (tx_signature, ix_index, inner_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_liquidation_events_slab_target
+  ON liquidation_events(slab_address, target_idx, slot DESC);
+
+CREATE TABLE IF NOT EXISTS insurance_movements (
+  id            BIGSERIAL     PRIMARY KEY,
+  tx_signature  TEXT          NOT NULL,
+  ix_index      SMALLINT      NOT NULL,
+  inner_index   SMALLINT      NOT NULL DEFAULT -1,
+  slot          BIGINT        NOT NULL,
+  block_time    TIMESTAMPTZ,
+  slab_address  TEXT          NOT NULL,
+  event_type    TEXT          NOT NULL
+    CHECK (event_type IN ('top_up', 'lp_deposit', 'lp_withdraw', 'fund_market', 'admin_withdraw')),
+  actor         TEXT,
+  -- Collateral for deposits/top-ups, LP tokens burned for lp_withdraw; null when the
+  -- instruction carries no amount (full admin withdrawal)
+  amount        NUMERIC,
+  created_at    TIMESTAMPTZ   NOT NULL DEFAULT NOW(),
+  CONSTRAINT insurance_movements_unique UNIQUE (tx_signature, ix_index, inner_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_insurance_movements_slab_slot
+  ON insurance_movements(slab_address, slot DESC);
+CREATE INDEX IF NOT EXISTS idx_insurance_movements_actor_slot
+  ON insurance_movements(actor, slot DESC);
+
+CREATE TABLE IF NOT EXISTS market_admin_events (
+  id            BIGSERIAL     PRIMARY KEY,
+  tx_signature  TEXT          NOT NULL,
+  ix_index      SMALLINT      NOT NULL,
+  inner_index   SMALLINT      NOT NULL DEFAULT -1,
+  slot          BIGINT        NOT NULL,
+  block_time    TIMESTAMPTZ,
+  slab_address  TEXT          NOT NULL,
+  -- Instruction name, e.g. PauseMarket, UpdateConfig, SetOracleAuthority
+  name          TEXT          NOT NULL,
+  authority     TEXT,
+  args          JSONB,
+  created_at    TIMESTAMPTZ   NOT NULL DEFAULT NOW(),
+  CONSTRAINT market_admin_events_unique UNIQUE (tx_signature, ix_index, inner_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_market_admin_events_slab_slot
+  ON market_admin_events(slab_address, slot DESC);
+
+-- Service role only, same as instruction_events
+ALTER TABLE collateral_events ENABLE ROW LEVEL SECURITY;
+ALTER TABLE liquidation_events ENABLE ROW LEVEL SECURITY;
+ALTER TABLE insurance_movements ENABLE ROW LEVEL SECURITY;
+ALTER TABLE market_admin_events ENABLE ROW LEVEL SECURITY;
+
+COMMENT ON TABLE collateral_events IS 'Account opens, collateral deposits/withdrawals and closes per wallet';
+COMMENT ON TABLE liquidation_events IS 'Liquidations and admin force-closes, keyed by target account index';
+COMMENT ON TABLE insurance_movements IS 'Insurance fund top-ups, LP deposits/withdrawals and admin withdrawals';
+COMMENT ON TABLE market_admin_events IS 'Admin and oracle-authority instructions (pause, config, oracle, admin changes)';
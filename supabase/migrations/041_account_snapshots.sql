@@ -0,0 +1,40 @@
+-- Migration: 041_account_snapshots
+-- Per-account state history written by StatsCollector. Each collection cycle
+-- parses every slab account and stores a row only for accounts whose state
+-- changed since the last stored snapshot (or that disappeared, as closed = true).
+-- Used by portfolio PnL charts and dispute investigations.
+-- Service role only (RLS enabled, no policies); reads go through the API.
+
+CREATE TABLE IF NOT EXISTS account_snapshots (
+  id              BIGSERIAL     PRIMARY KEY,
+  slab_address    TEXT          NOT NULL,
+  account_idx     INTEGER       NOT NULL,
+  owner           TEXT          NOT NULL,
+  -- 0 = user, 1 = LP
+  kind            SMALLINT      NOT NULL,
+  -- Engine slot when the slab was read
+  slot            BIGINT        NOT NULL,
+  -- i128/u128 on-chain values: NUMERIC (see 024_bigint_overflow_fix)
+  capital         NUMERIC       NOT NULL,
+  pnl             NUMERIC       NOT NULL,
+  reserved_pnl    NUMERIC       NOT NULL,
+  position_size   NUMERIC       NOT NULL,
+  entry_price_e6  NUMERIC       NOT NULL,
+  funding_index   NUMERIC       NOT NULL,
+  fee_credits     NUMERIC       NOT NULL,
+  -- Market price used by StatsCollector in the same cycle (for mark-to-market charts)
+  price_e6        NUMERIC,
+  -- Account no longer present in the slab (closed, or reclaimed)
+  closed          BOOLEAN       NOT NULL DEFAULT FALSE,
+  created_at      TIMESTAMPTZ   NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_snapshots_owner_time
+  ON account_snapshots(owner, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_account_snapshots_slab_idx_time
+  ON account_snapshots(slab_address, account_idx, created_at DESC);
+
+ALTER TABLE account_snapshots ENABLE ROW LEVEL SECURITY;
+
+COMMENT ON TABLE account_snapshots IS 'Changed-only per-account state snapshots from StatsCollector';
+COMMENT ON COLUMN account_snapshots.closed IS 'True when the account index was no longer in use at snapshot time';
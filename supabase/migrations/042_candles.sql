@@ -0,0 +1,47 @@
+-- Migration: 042_candles
+-- OHLCV candles built by the indexer's CandleBuilder from trades and oracle
+-- pushes (oracle_prices). 1m candles are computed from raw points; 5m/15m/1h/
+-- 4h/1d are rolled up from 1m. Buckets are rebuilt whenever a point lands in
+-- them, including trades indexed late (backfill, webhook retries).
+--
+-- trades gains block_time so late trades are bucketed by when they executed,
+-- not when they were indexed (created_at). Older rows keep NULL and fall back
+-- to created_at.
+--
+-- CandleBuilder pages through newly indexed rows on (created_at, id), hence
+-- the indexes on both source tables.
+
+ALTER TABLE trades ADD COLUMN IF NOT EXISTS block_time TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_trades_slab_block_time
+  ON trades(slab_address, block_time);
+
+CREATE INDEX IF NOT EXISTS idx_trades_created_at_id
+  ON trades(created_at, id);
+
+CREATE INDEX IF NOT EXISTS idx_oracle_prices_created_at_id
+  ON oracle_prices(created_at, id);
+
+-- Same exposure as created_at (see 030_trades_anon_column_grant)
+GRANT SELECT (block_time) ON trades TO anon;
+
+CREATE TABLE IF NOT EXISTS candles (
+  slab_address  TEXT          NOT NULL,
+  resolution    TEXT          NOT NULL CHECK (resolution IN ('1m', '5m', '15m', '1h', '4h', '1d')),
+  bucket_start  TIMESTAMPTZ   NOT NULL,
+  -- Prices in USD, same unit as trades.price (oracle price_e6 / 1e6)
+  open          NUMERIC       NOT NULL,
+  high          NUMERIC       NOT NULL,
+  low           NUMERIC       NOT NULL,
+  close         NUMERIC       NOT NULL,
+  -- Sum of |trade size| in native units; 0 for oracle-only buckets
+  volume        NUMERIC       NOT NULL DEFAULT 0,
+  trade_count   INTEGER       NOT NULL DEFAULT 0,
+  updated_at    TIMESTAMPTZ   NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (slab_address, resolution, bucket_start)
+);
+
+-- Service role only (RLS enabled, no policies); reads go through the API
+ALTER TABLE candles ENABLE ROW LEVEL SECURITY;
+
+COMMENT ON TABLE candles IS 'OHLCV candles per market and resolution, from trades and oracle prices';
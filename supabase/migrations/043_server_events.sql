@@ -0,0 +1,35 @@
+-- Migration: 043_server_events
+-- Cross-process eventBus log for EVENT_BUS_TRANSPORT=postgres (shared
+-- PostgresEventTransport). Each publish is one row; publishers NOTIFY
+-- percolator_events with the new id and readers fetch rows after their
+-- offset. event_id is unique so a retried append doesn't duplicate.
+--
+-- Rows past the retention window (7 days) are pruned by the publishers.
+
+CREATE TABLE IF NOT EXISTS server_events (
+  id            BIGSERIAL     PRIMARY KEY,
+  event_id      TEXT          NOT NULL UNIQUE,
+  event         TEXT          NOT NULL,
+  slab_address  TEXT          NOT NULL,
+  -- Publishing process; readers skip their own events
+  origin        TEXT          NOT NULL,
+  envelope      JSONB         NOT NULL,
+  created_at    TIMESTAMPTZ   NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_server_events_created_at
+  ON server_events(created_at);
+
+-- Saved read offset per named consumer (EVENT_BUS_CONSUMER)
+CREATE TABLE IF NOT EXISTS server_event_offsets (
+  consumer      TEXT          PRIMARY KEY,
+  last_id       BIGINT        NOT NULL,
+  updated_at    TIMESTAMPTZ   NOT NULL DEFAULT NOW()
+);
+
+-- Service role only (RLS enabled, no policies)
+ALTER TABLE server_events ENABLE ROW LEVEL SECURITY;
+ALTER TABLE server_event_offsets ENABLE ROW LEVEL SECURITY;
+
+COMMENT ON TABLE server_events IS 'Cross-process eventBus log (postgres transport), pruned after 7 days';
+COMMENT ON TABLE server_event_offsets IS 'Last delivered server_events id per eventBus consumer';
@@ -0,0 +1,90 @@
+-- Migration: 044_webhooks
+-- Outbound webhooks for eventBus events (indexer WebhookDispatcher; managed
+-- through the API's /webhooks endpoints).
+--
+-- A subscription matching an event gets one webhook_deliveries row; event_key
+-- is derived from the event itself, so replicas seeing the same event enqueue
+-- it once. Each POST is logged in webhook_delivery_attempts. Failed deliveries
+-- are retried with exponential backoff; after the last attempt the delivery
+-- is marked dead and copied to webhook_dead_letters.
+
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+  id            UUID          PRIMARY KEY DEFAULT gen_random_uuid(),
+  url           TEXT          NOT NULL,
+  -- HMAC-SHA256 signing key, returned once on creation
+  secret        TEXT          NOT NULL,
+  -- Empty = every event / every slab
+  events        TEXT[]        NOT NULL DEFAULT '{}',
+  slabs         TEXT[]        NOT NULL DEFAULT '{}',
+  description   TEXT,
+  active        BOOLEAN       NOT NULL DEFAULT TRUE,
+  created_at    TIMESTAMPTZ   NOT NULL DEFAULT NOW(),
+  updated_at    TIMESTAMPTZ   NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+  id               BIGSERIAL     PRIMARY KEY,
+  subscription_id  UUID          NOT NULL REFERENCES webhook_subscriptions(id) ON DELETE CASCADE,
+  event_key        TEXT          NOT NULL,
+  event            TEXT          NOT NULL,
+  slab_address     TEXT          NOT NULL,
+  -- Exact body POSTed (signed as-is)
+  payload          JSONB         NOT NULL,
+  status           TEXT          NOT NULL DEFAULT 'pending'
+                                 CHECK (status IN ('pending', 'delivering', 'delivered', 'dead')),
+  attempts         INTEGER       NOT NULL DEFAULT 0,
+  next_attempt_at  TIMESTAMPTZ   NOT NULL DEFAULT NOW(),
+  last_status_code INTEGER,
+  last_error       TEXT,
+  delivered_at     TIMESTAMPTZ,
+  created_at       TIMESTAMPTZ   NOT NULL DEFAULT NOW(),
+  updated_at       TIMESTAMPTZ   NOT NULL DEFAULT NOW(),
+  UNIQUE (subscription_id, event_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+  ON webhook_deliveries(status, next_attempt_at);
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_subscription
+  ON webhook_deliveries(subscription_id, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS webhook_delivery_attempts (
+  id               BIGSERIAL     PRIMARY KEY,
+  delivery_id      BIGINT        NOT NULL REFERENCES webhook_deliveries(id) ON DELETE CASCADE,
+  subscription_id  UUID          NOT NULL,
+  attempt          INTEGER       NOT NULL,
+  -- NULL when no response was received (timeout, DNS, connection refused)
+  status_code      INTEGER,
+  error            TEXT,
+  duration_ms      INTEGER       NOT NULL,
+  created_at       TIMESTAMPTZ   NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_delivery_attempts_delivery
+  ON webhook_delivery_attempts(delivery_id, attempt);
+
+CREATE TABLE IF NOT EXISTS webhook_dead_letters (
+  id               BIGSERIAL     PRIMARY KEY,
+  delivery_id      BIGINT        NOT NULL UNIQUE REFERENCES webhook_deliveries(id) ON DELETE CASCADE,
+  subscription_id  UUID          NOT NULL,
+  event            TEXT          NOT NULL,
+  slab_address     TEXT          NOT NULL,
+  payload          JSONB         NOT NULL,
+  attempts         INTEGER       NOT NULL,
+  last_status_code INTEGER,
+  last_error       TEXT,
+  created_at       TIMESTAMPTZ   NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_dead_letters_subscription
+  ON webhook_dead_letters(subscription_id, created_at DESC);
+
+-- Service role only (RLS enabled, no policies); secrets must never reach anon
+ALTER TABLE webhook_subscriptions ENABLE ROW LEVEL SECURITY;
+ALTER TABLE webhook_deliveries ENABLE ROW LEVEL SECURITY;
+ALTER TABLE webhook_delivery_attempts ENABLE ROW LEVEL SECURITY;
+ALTER TABLE webhook_dead_letters ENABLE ROW LEVEL SECURITY;
+
+COMMENT ON TABLE webhook_subscriptions IS 'Outbound webhook endpoints with event/slab filters';
+COMMENT ON TABLE webhook_deliveries IS 'One row per event per matching subscription; retry state';
+COMMENT ON TABLE webhook_delivery_attempts IS 'Log of every webhook POST and its outcome';
+COMMENT ON TABLE webhook_dead_letters IS 'Webhook deliveries that exhausted their retries';
@@ -0,0 +1,70 @@
+-- Migration: 045_stake_pool_snapshots
+-- Stake pool history written by the indexer's StakePoolService. Every poll
+-- decodes each percolator-stake pool and stores its value, LP share price
+-- (overall and per tranche), flush/return/fee totals and HWM state. Flows to
+-- and from insurance and HWM floor breaches seen between two polls are
+-- recorded in stake_pool_events.
+-- Served by the API's /stake/:pool/history and /stake/:pool/apy.
+-- Service role only (RLS enabled, no policies); reads go through the API.
+
+CREATE TABLE IF NOT EXISTS stake_pool_snapshots (
+  id                     BIGSERIAL     PRIMARY KEY,
+  pool_address           TEXT          NOT NULL,
+  slab_address           TEXT          NOT NULL,
+  -- Chain slot when the pools were read
+  slot                   BIGINT        NOT NULL,
+  -- 0 = insurance LP, 1 = trading LP
+  pool_mode              SMALLINT      NOT NULL,
+  -- u64/u128 on-chain values: NUMERIC (see 024_bigint_overflow_fix)
+  total_deposited        NUMERIC       NOT NULL,
+  total_withdrawn        NUMERIC       NOT NULL,
+  total_flushed          NUMERIC       NOT NULL,
+  total_returned         NUMERIC       NOT NULL,
+  total_fees_earned      NUMERIC       NOT NULL,
+  total_lp_supply        NUMERIC       NOT NULL,
+  pool_value             NUMERIC       NOT NULL,
+  -- Collateral per LP token, 6 decimals
+  share_price_e6         NUMERIC       NOT NULL,
+  -- Senior = whole pool when tranches are disabled; junior columns are then NULL
+  tranche_enabled        BOOLEAN       NOT NULL,
+  senior_value           NUMERIC       NOT NULL,
+  senior_lp_supply       NUMERIC       NOT NULL,
+  senior_share_price_e6  NUMERIC       NOT NULL,
+  junior_value           NUMERIC,
+  junior_lp_supply       NUMERIC,
+  junior_share_price_e6  NUMERIC,
+  junior_fee_mult_bps    INTEGER       NOT NULL,
+  hwm_enabled            BOOLEAN       NOT NULL,
+  epoch_high_water_tvl   NUMERIC       NOT NULL,
+  hwm_floor_bps          INTEGER       NOT NULL,
+  hwm_floor              NUMERIC,
+  hwm_breached           BOOLEAN       NOT NULL DEFAULT FALSE,
+  created_at             TIMESTAMPTZ   NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_stake_pool_snapshots_pool_time
+  ON stake_pool_snapshots(pool_address, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS stake_pool_events (
+  id              BIGSERIAL     PRIMARY KEY,
+  pool_address    TEXT          NOT NULL,
+  slab_address    TEXT          NOT NULL,
+  slot            BIGINT        NOT NULL,
+  -- flush: pool → insurance, return: insurance → pool,
+  -- hwm_breach / hwm_recovered: pool value crossed its HWM floor
+  kind            TEXT          NOT NULL CHECK (kind IN ('flush', 'return', 'hwm_breach', 'hwm_recovered')),
+  -- Flow amount for flush/return; pool value for HWM events
+  amount          NUMERIC       NOT NULL,
+  pool_value      NUMERIC       NOT NULL,
+  hwm_floor       NUMERIC,
+  created_at      TIMESTAMPTZ   NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_stake_pool_events_pool_time
+  ON stake_pool_events(pool_address, created_at DESC);
+
+ALTER TABLE stake_pool_snapshots ENABLE ROW LEVEL SECURITY;
+ALTER TABLE stake_pool_events ENABLE ROW LEVEL SECURITY;
+
+COMMENT ON TABLE stake_pool_snapshots IS 'Per-poll stake pool state, share prices and HWM status from StakePoolService';
+COMMENT ON TABLE stake_pool_events IS 'Stake pool flushes to / returns from insurance and HWM floor breaches';
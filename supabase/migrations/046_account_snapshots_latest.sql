@@ -0,0 +1,14 @@
+-- Migration: 046_account_snapshots_latest
+-- Newest stored snapshot per (slab, account index). StatsCollector seeds its
+-- change detection from this the first time it sees a slab, so a restart only
+-- stores accounts that changed while the indexer was down instead of every
+-- open account again. Served by idx_account_snapshots_slab_idx_time.
+-- security_invoker keeps account_snapshots' RLS (service role only) in force.
+
+CREATE OR REPLACE VIEW account_snapshots_latest
+WITH (security_invoker = true) AS
+SELECT DISTINCT ON (slab_address, account_idx) *
+FROM account_snapshots
+ORDER BY slab_address, account_idx, created_at DESC, id DESC;
+
+COMMENT ON VIEW account_snapshots_latest IS 'Latest account_snapshots row per slab account index';
@@ -0,0 +1,32 @@
+-- Migration: 047_webhook_owners
+-- Wallet-owned webhook subscriptions. Besides the operator (API key), a
+-- wallet can register and manage its own subscriptions by signing a
+-- single-use server challenge; it only ever sees rows with its owner.
+-- owner is NULL for operator-created subscriptions.
+--
+-- webhook_owner_challenges holds the issued challenges. A request consumes
+-- one by setting used_at, so each challenge authorizes exactly one request.
+-- Expired rows are purged when new challenges are issued.
+
+ALTER TABLE webhook_subscriptions ADD COLUMN IF NOT EXISTS owner TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_owner
+  ON webhook_subscriptions(owner, created_at)
+  WHERE owner IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS webhook_owner_challenges (
+  challenge     TEXT          PRIMARY KEY,
+  owner         TEXT          NOT NULL,
+  expires_at    TIMESTAMPTZ   NOT NULL,
+  used_at       TIMESTAMPTZ,
+  created_at    TIMESTAMPTZ   NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_owner_challenges_expiry
+  ON webhook_owner_challenges(expires_at);
+
+-- Service role only (RLS enabled, no policies)
+ALTER TABLE webhook_owner_challenges ENABLE ROW LEVEL SECURITY;
+
+COMMENT ON COLUMN webhook_subscriptions.owner IS 'Wallet that registered the subscription; NULL = operator';
+COMMENT ON TABLE webhook_owner_challenges IS 'Single-use challenges wallets sign to manage their webhooks';
@@ -0,0 +1,341 @@
+/**
+ * Execution backends for the local (no-network) test suite.
+ *
+ * - bankrun:   in-process solana-program-test bank with the percolator program
+ *              loaded from PERCOLATOR_PROGRAM_SO. Slots and the clock only move
+ *              when the harness says so, so every run produces identical state.
+ * - validator: a `solana-test-validator` on SOLANA_RPC_URL (default localhost)
+ *              started with `--bpf-program`. Slots advance in real time; the
+ *              harness waits instead of warping.
+ *
+ * Both expose the same small surface so scenarios never touch a Connection.
+ */
+
+import {
+  BPF_LOADER_PROGRAM_ID,
+  ComputeBudgetProgram,
+  Connection,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_CLOCK_PUBKEY,
+  Transaction,
+  TransactionInstruction,
+  sendAndConfirmTransaction,
+} from "@solana/web3.js";
+import * as crypto from "crypto";
+import * as fs from "fs";
+
+import { parseErrorFromLogs } from "@percolator/sdk";
+
+export type BackendKind = "bankrun" | "validator";
+
+/** Genesis clock for bankrun — fixed so oracle timestamps are reproducible. */
+export const GENESIS_SLOT = 1_000n;
+export const GENESIS_UNIX_TS = 1_700_000_000n;
+const MS_PER_SLOT = 400n;
+
+export interface LocalAccount {
+  data: Uint8Array;
+  lamports: number;
+  owner: PublicKey;
+}
+
+export interface TxReceipt {
+  slot: bigint;
+  logs: string[];
+  computeUnits: number;
+}
+
+/** A rejected transaction, with the program's custom error code when there is one. */
+export class LocalTxError extends Error {
+  readonly logs: string[];
+  readonly code: number | null;
+
+  constructor(message: string, logs: string[]) {
+    super(message);
+    this.name = "LocalTxError";
+    this.logs = logs;
+    this.code = parseErrorFromLogs([...logs, message])?.code ?? null;
+  }
+}
+
+export interface LocalBackend {
+  readonly kind: BackendKind;
+  readonly payer: Keypair;
+  send(ixs: TransactionInstruction[], signers: Keypair[], cuLimit?: number): Promise<TxReceipt>;
+  getAccount(address: PublicKey): Promise<LocalAccount | null>;
+  getSlot(): Promise<bigint>;
+  getUnixTimestamp(): Promise<bigint>;
+  /** Advance at least `count` slots. Exact on bankrun. */
+  advanceSlots(count: bigint): Promise<bigint>;
+  rentExempt(space: number): Promise<number>;
+  fund(address: PublicKey, lamports: number): Promise<void>;
+  close(): Promise<void>;
+}
+
+/** Keypair derived from a label, so account addresses are stable across runs. */
+export function seededKeypair(label: string): Keypair {
+  return Keypair.fromSeed(crypto.createHash("sha256").update(`percolator-local:${label}`).digest());
+}
+
+function uniqueSigners(payer: Keypair, signers: Keypair[]): Keypair[] {
+  const seen = new Map<string, Keypair>([[payer.publicKey.toBase58(), payer]]);
+  for (const s of signers) seen.set(s.publicKey.toBase58(), s);
+  return [...seen.values()];
+}
+
+function withBudget(ixs: TransactionInstruction[], cuLimit: number): Transaction {
+  const tx = new Transaction();
+  tx.add(ComputeBudgetProgram.setComputeUnitLimit({ units: cuLimit }));
+  for (const ix of ixs) tx.add(ix);
+  return tx;
+}
+
+// ============================================================================
+// BANKRUN
+// ============================================================================
+
+// Minimal surface of solana-bankrun we rely on. The package (a root devDependency)
+// is loaded lazily so the validator backend and the devnet scripts never load
+// its native binary.
+interface BankrunClock {
+  slot: bigint;
+  epochStartTimestamp: bigint;
+  epoch: bigint;
+  leaderScheduleEpoch: bigint;
+  unixTimestamp: bigint;
+}
+interface BankrunClient {
+  tryProcessTransaction(tx: Transaction): Promise<{
+    result: string | null;
+    meta: { logMessages: string[]; computeUnitsConsumed: bigint } | null;
+  }>;
+  getAccount(address: PublicKey): Promise<LocalAccount | null>;
+  getLatestBlockhash(): Promise<[string, bigint] | null>;
+  getSlot(): Promise<bigint>;
+  getClock(): Promise<BankrunClock>;
+  getRent(): Promise<{ minimumBalance(size: bigint): bigint }>;
+}
+interface BankrunContext {
+  banksClient: BankrunClient;
+  warpToSlot(slot: bigint): void;
+  setClock(clock: BankrunClock): void;
+  setAccount(address: PublicKey, info: LocalAccount & { executable: boolean }): void;
+}
+interface BankrunModule {
+  start(
+    programs: { name: string; programId: PublicKey }[],
+    accounts: { address: PublicKey; info: LocalAccount & { executable: boolean } }[],
+  ): Promise<BankrunContext>;
+  Clock: new (
+    slot: bigint,
+    epochStartTimestamp: bigint,
+    epoch: bigint,
+    leaderScheduleEpoch: bigint,
+    unixTimestamp: bigint,
+  ) => BankrunClock;
+}
+
+class BankrunBackend implements LocalBackend {
+  readonly kind = "bankrun" as const;
+
+  constructor(
+    private readonly bankrun: BankrunModule,
+    private readonly ctx: BankrunContext,
+    readonly payer: Keypair,
+  ) {}
+
+  async send(ixs: TransactionInstruction[], signers: Keypair[], cuLimit = 200_000): Promise<TxReceipt> {
+    // One transaction per slot: keeps slot arithmetic in scenarios exact and
+    // gives every transaction a fresh blockhash, so identical retries aren't
+    // rejected as already processed.
+    const slot = await this.advanceSlots(1n);
+    const tx = withBudget(ixs, cuLimit);
+    const latest = await this.ctx.banksClient.getLatestBlockhash();
+    if (!latest) throw new Error("bankrun returned no blockhash");
+    tx.recentBlockhash = latest[0];
+    tx.feePayer = this.payer.publicKey;
+    tx.sign(...uniqueSigners(this.payer, signers));
+
+    const res = await this.ctx.banksClient.tryProcessTransaction(tx);
+    const logs = res.meta?.logMessages ?? [];
+    if (res.result) throw new LocalTxError(res.result, logs);
+    return { slot, logs, computeUnits: Number(res.meta?.computeUnitsConsumed ?? 0n) };
+  }
+
+  getAccount(address: PublicKey): Promise<LocalAccount | null> {
+    return this.ctx.banksClient.getAccount(address);
+  }
+
+  getSlot(): Promise<bigint> {
+    return this.ctx.banksClient.getSlot();
+  }
+
+  async getUnixTimestamp(): Promise<bigint> {
+    return (await this.ctx.banksClient.getClock()).unixTimestamp;
+  }
+
+  async advanceSlots(count: bigint): Promise<bigint> {
+    const target = (await this.getSlot()) + count;
+    this.ctx.warpToSlot(target);
+    const clock = await this.ctx.banksClient.getClock();
+    this.ctx.setClock(
+      new this.bankrun.Clock(
+        target,
+        clock.epochStartTimestamp,
+        clock.epoch,
+        clock.leaderScheduleEpoch,
+        GENESIS_UNIX_TS + ((target - GENESIS_SLOT) * MS_PER_SLOT) / 1000n,
+      ),
+    );
+    return target;
+  }
+
+  async rentExempt(space: number): Promise<number> {
+    const rent = await this.ctx.banksClient.getRent();
+    return Number(rent.minimumBalance(BigInt(space)));
+  }
+
+  async fund(address: PublicKey, lamports: number): Promise<void> {
+    this.ctx.setAccount(address, {
+      lamports,
+      data: new Uint8Array(0),
+      owner: SystemProgram.programId,
+      executable: false,
+    });
+  }
+
+  async close(): Promise<void> {}
+}
+
+// ============================================================================
+// LOCAL VALIDATOR
+// ============================================================================
+
+class ValidatorBackend implements LocalBackend {
+  readonly kind = "validator" as const;
+
+  constructor(
+    private readonly connection: Connection,
+    readonly payer: Keypair,
+  ) {}
+
+  async send(ixs: TransactionInstruction[], signers: Keypair[], cuLimit = 200_000): Promise<TxReceipt> {
+    const tx = withBudget(ixs, cuLimit);
+    try {
+      const sig = await sendAndConfirmTransaction(
+        this.connection,
+        tx,
+        uniqueSigners(this.payer, signers),
+        { commitment: "confirmed" },
+      );
+      const info = await this.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return {
+        slot: BigInt(info?.slot ?? 0),
+        logs: info?.meta?.logMessages ?? [],
+        computeUnits: info?.meta?.computeUnitsConsumed ?? 0,
+      };
+    } catch (e: any) {
+      throw new LocalTxError(e.message ?? String(e), e.logs ?? []);
+    }
+  }
+
+  async getAccount(address: PublicKey): Promise<LocalAccount | null> {
+    const info = await this.connection.getAccountInfo(address, "confirmed");
+    if (!info) return null;
+    return { data: new Uint8Array(info.data), lamports: info.lamports, owner: info.owner };
+  }
+
+  async getSlot(): Promise<bigint> {
+    return BigInt(await this.connection.getSlot("confirmed"));
+  }
+
+  async getUnixTimestamp(): Promise<bigint> {
+    const info = await this.connection.getAccountInfo(SYSVAR_CLOCK_PUBKEY, "confirmed");
+    if (!info) throw new Error("Clock sysvar not found");
+    return Buffer.from(info.data).readBigInt64LE(32);
+  }
+
+  async advanceSlots(count: bigint): Promise<bigint> {
+    const target = (await this.getSlot()) + count;
+    for (;;) {
+      const current = await this.getSlot();
+      if (current >= target) return current;
+      await new Promise((r) => setTimeout(r, 200));
+    }
+  }
+
+  rentExempt(space: number): Promise<number> {
+    return this.connection.getMinimumBalanceForRentExemption(space);
+  }
+
+  async fund(address: PublicKey, lamports: number): Promise<void> {
+    const sig = await this.connection.requestAirdrop(address, lamports);
+    const latest = await this.connection.getLatestBlockhash();
+    await this.connection.confirmTransaction({ signature: sig, ...latest }, "confirmed");
+  }
+
+  async close(): Promise<void> {}
+}
+
+// ============================================================================
+// FACTORY
+// ============================================================================
+
+export interface BackendOptions {
+  kind?: BackendKind;
+  programId: PublicKey;
+  /** Path to the percolator program binary (bankrun only) */
+  programSo?: string;
+}
+
+export async function createLocalBackend(opts: BackendOptions): Promise<LocalBackend> {
+  const kind = opts.kind ?? ((process.env.PERCOLATOR_TEST_BACKEND as BackendKind | undefined) ?? "bankrun");
+
+  if (kind === "validator") {
+    const url = process.env.SOLANA_RPC_URL ?? "http://127.0.0.1:8899";
+    const connection = new Connection(url, "confirmed");
+    const payer = seededKeypair("payer");
+    const backend = new ValidatorBackend(connection, payer);
+    await backend.fund(payer.publicKey, 100 * LAMPORTS_PER_SOL);
+    return backend;
+  }
+
+  const soPath = opts.programSo ?? process.env.PERCOLATOR_PROGRAM_SO;
+  if (!soPath || !fs.existsSync(soPath)) {
+    throw new Error(
+      "PERCOLATOR_PROGRAM_SO must point at a percolator program binary built with " +
+        "`cargo build-sbf --features test` (see tests/local/README.md)",
+    );
+  }
+
+  let bankrun: BankrunModule;
+  try {
+    bankrun = (await import("solana-bankrun" as string)) as BankrunModule;
+  } catch {
+    throw new Error("solana-bankrun is not installed — run `pnpm install` or use PERCOLATOR_TEST_BACKEND=validator");
+  }
+
+  const elf = new Uint8Array(fs.readFileSync(soPath));
+  const ctx = await bankrun.start(
+    [],
+    [
+      {
+        address: opts.programId,
+        info: { data: elf, lamports: LAMPORTS_PER_SOL, owner: BPF_LOADER_PROGRAM_ID, executable: true },
+      },
+    ],
+  );
+  // The bank's own payer is random per run; a seeded one keeps admin keys
+  // (and therefore slab bytes) identical between runs.
+  const backend = new BankrunBackend(bankrun, ctx, seededKeypair("payer"));
+  await backend.fund(backend.payer.publicKey, 100 * LAMPORTS_PER_SOL);
+  ctx.warpToSlot(GENESIS_SLOT - 1n);
+  await backend.advanceSlots(1n);
+  return backend;
+}
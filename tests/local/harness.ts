@@ -0,0 +1,527 @@
+/**
+ * Local Test Harness for Percolator Launch
+ *
+ * Same scenarios as the devnet harness (tests/harness.ts), but every action
+ * goes through a LocalBackend: an in-process bankrun bank or a local
+ * validator. No devnet RPC, no funded keypairs on disk.
+ *
+ * Determinism:
+ * - Payer, users, mints and slabs are derived from labels
+ * - On bankrun each transaction lands in its own slot and the clock follows
+ *   the slot, so oracle timestamps and snapshot hashes repeat exactly
+ * - Oracle pushes use the bank clock, never Date.now()
+ */
+
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import {
+  MINT_SIZE,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import * as crypto from "crypto";
+
+import {
+  encodeInitMarket,
+  encodeInitUser,
+  encodeInitLP,
+  encodeDepositCollateral,
+  encodeWithdrawCollateral,
+  encodeKeeperCrank,
+  encodeTradeNoCpi,
+  encodeLiquidateAtOracle,
+  encodeCloseAccount,
+  encodePushOraclePrice,
+  encodeSetOracleAuthority,
+  encodePauseMarket,
+  encodeUnpauseMarket,
+  encodeResolveMarket,
+  encodeWithdrawInsurance,
+  buildAccountMetas,
+  buildIx,
+  detectSlabLayout,
+  ACCOUNTS_INIT_MARKET,
+  ACCOUNTS_INIT_USER,
+  ACCOUNTS_INIT_LP,
+  ACCOUNTS_DEPOSIT_COLLATERAL,
+  ACCOUNTS_WITHDRAW_COLLATERAL,
+  ACCOUNTS_KEEPER_CRANK,
+  ACCOUNTS_TRADE_NOCPI,
+  ACCOUNTS_LIQUIDATE_AT_ORACLE,
+  ACCOUNTS_CLOSE_ACCOUNT,
+  ACCOUNTS_PUSH_ORACLE_PRICE,
+  ACCOUNTS_SET_ORACLE_AUTHORITY,
+  ACCOUNTS_PAUSE_MARKET,
+  ACCOUNTS_UNPAUSE_MARKET,
+  ACCOUNTS_RESOLVE_MARKET,
+  ACCOUNTS_UPDATE_CONFIG,
+  ACCOUNTS_WITHDRAW_INSURANCE,
+  WELL_KNOWN,
+  parseHeader,
+  parseConfig,
+  parseEngine,
+  parseParams,
+  parseAllAccounts,
+  parseUsedIndices,
+  getErrorName,
+} from "@percolator/sdk";
+
+import { CRANK_NO_CALLER, PROGRAM_ID, SLAB_SIZE, type SlabSnapshot, type TestResult } from "../harness.js";
+import { LocalTxError, seededKeypair, type LocalBackend, type TxReceipt } from "./backend.js";
+
+export interface LocalMarket {
+  slab: Keypair;
+  mint: Keypair;
+  vault: PublicKey;
+  vaultPda: PublicKey;
+  /** Admin's collateral token account (insurance withdrawals land here) */
+  adminAta: PublicKey;
+  users: Map<string, LocalUser>;
+}
+
+export interface LocalUser {
+  keypair: Keypair;
+  ata: PublicKey;
+  accountIndex: number;
+}
+
+export interface LocalMarketOptions {
+  initialPriceE6?: bigint;
+  decimals?: number;
+  warmupPeriodSlots?: bigint;
+  tradingFeeBps?: bigint;
+}
+
+export class LocalHarness {
+  private results: TestResult[] = [];
+  /** rawHash of every snapshot taken, in order — compared across runs. */
+  readonly trail: string[] = [];
+  private labelSeq = 0;
+
+  constructor(
+    readonly backend: LocalBackend,
+    readonly programId: PublicKey = PROGRAM_ID,
+  ) {}
+
+  get payer(): Keypair {
+    return this.backend.payer;
+  }
+
+  private keypair(label: string): Keypair {
+    return seededKeypair(`${label}#${this.labelSeq++}`);
+  }
+
+  private send(ixs: TransactionInstruction[], signers: Keypair[] = [], cuLimit?: number): Promise<TxReceipt> {
+    return this.backend.send(ixs, signers, cuLimit);
+  }
+
+  // ==========================================================================
+  // MARKET SETUP
+  // ==========================================================================
+
+  /**
+   * Create a mint, slab and vault, init the market in admin-oracle mode,
+   * hand oracle authority to the payer, push the initial price and crank.
+   */
+  async createMarket(opts: LocalMarketOptions = {}): Promise<LocalMarket> {
+    const initialPriceE6 = opts.initialPriceE6 ?? 1_000_000n;
+    const layout = detectSlabLayout(SLAB_SIZE);
+    if (!layout) throw new Error(`SLAB_SIZE ${SLAB_SIZE} is not a known slab size`);
+
+    const mint = this.keypair("mint");
+    const slab = this.keypair("slab");
+    const [vaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), slab.publicKey.toBuffer()],
+      this.programId,
+    );
+    const vault = getAssociatedTokenAddressSync(mint.publicKey, vaultPda, true);
+    const adminAta = getAssociatedTokenAddressSync(mint.publicKey, this.payer.publicKey);
+
+    await this.send(
+      [
+        SystemProgram.createAccount({
+          fromPubkey: this.payer.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports: await this.backend.rentExempt(MINT_SIZE),
+          space: MINT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeMint2Instruction(mint.publicKey, opts.decimals ?? 6, this.payer.publicKey, null),
+        createAssociatedTokenAccountIdempotentInstruction(this.payer.publicKey, vault, vaultPda, mint.publicKey),
+        createAssociatedTokenAccountIdempotentInstruction(
+          this.payer.publicKey,
+          adminAta,
+          this.payer.publicKey,
+          mint.publicKey,
+        ),
+      ],
+      [mint],
+    );
+
+    await this.send(
+      [
+        SystemProgram.createAccount({
+          fromPubkey: this.payer.publicKey,
+          newAccountPubkey: slab.publicKey,
+          lamports: await this.backend.rentExempt(SLAB_SIZE),
+          space: SLAB_SIZE,
+          programId: this.programId,
+        }),
+      ],
+      [slab],
+      100_000,
+    );
+
+    const initData = encodeInitMarket({
+      admin: this.payer.publicKey,
+      collateralMint: mint.publicKey,
+      indexFeedId: "0".repeat(64), // All zeros = admin oracle mode
+      maxStalenessSecs: "100000000",
+      confFilterBps: 200,
+      invert: 0,
+      unitScale: 0,
+      initialMarkPriceE6: initialPriceE6.toString(),
+      warmupPeriodSlots: (opts.warmupPeriodSlots ?? 10n).toString(),
+      maintenanceMarginBps: "500",
+      initialMarginBps: "1000",
+      tradingFeeBps: (opts.tradingFeeBps ?? 10n).toString(),
+      maxAccounts: layout.maxAccounts.toString(),
+      newAccountFee: "1000000",
+      riskReductionThreshold: "0",
+      maintenanceFeePerSlot: "0",
+      maxCrankStalenessSlots: "200",
+      liquidationFeeBps: "100",
+      liquidationFeeCap: "1000000000",
+      liquidationBufferBps: "50",
+      minLiquidationAbs: "100000",
+    });
+    const initKeys = buildAccountMetas(ACCOUNTS_INIT_MARKET, {
+      admin: this.payer.publicKey,
+      slab: slab.publicKey,
+      mint: mint.publicKey,
+      vault,
+      tokenProgram: WELL_KNOWN.tokenProgram,
+      clock: WELL_KNOWN.clock,
+      rent: WELL_KNOWN.rent,
+      dummyAta: vaultPda,
+      systemProgram: WELL_KNOWN.systemProgram,
+    });
+    await this.send([buildIx({ programId: this.programId, keys: initKeys, data: initData })], [], 200_000);
+
+    const market: LocalMarket = { slab, mint, vault, vaultPda, adminAta, users: new Map() };
+    await this.setOracleAuthority(market, this.payer.publicKey);
+    await this.pushOraclePrice(market, initialPriceE6);
+    await this.keeperCrank(market);
+    return market;
+  }
+
+  // ==========================================================================
+  // ORACLE
+  // ==========================================================================
+
+  async setOracleAuthority(market: LocalMarket, authority: PublicKey): Promise<TxReceipt> {
+    const data = encodeSetOracleAuthority({ newAuthority: authority });
+    const keys = buildAccountMetas(ACCOUNTS_SET_ORACLE_AUTHORITY, [this.payer.publicKey, market.slab.publicKey]);
+    return this.send([buildIx({ programId: this.programId, keys, data })], [], 50_000);
+  }
+
+  async pushOraclePrice(market: LocalMarket, priceE6: bigint): Promise<TxReceipt> {
+    const ts = await this.backend.getUnixTimestamp();
+    const data = encodePushOraclePrice({ priceE6: priceE6.toString(), timestamp: ts.toString() });
+    const keys = buildAccountMetas(ACCOUNTS_PUSH_ORACLE_PRICE, [this.payer.publicKey, market.slab.publicKey]);
+    return this.send([buildIx({ programId: this.programId, keys, data })], [], 50_000);
+  }
+
+  // ==========================================================================
+  // USER OPERATIONS
+  // ==========================================================================
+
+  /** Fund a fresh wallet with SOL and `tokens` of collateral. */
+  async createUser(market: LocalMarket, name: string, tokens: bigint): Promise<LocalUser> {
+    const keypair = this.keypair(`user:${name}`);
+    await this.backend.fund(keypair.publicKey, LAMPORTS_PER_SOL);
+    const ata = getAssociatedTokenAddressSync(market.mint.publicKey, keypair.publicKey);
+    const ixs = [
+      createAssociatedTokenAccountIdempotentInstruction(
+        this.payer.publicKey,
+        ata,
+        keypair.publicKey,
+        market.mint.publicKey,
+      ),
+    ];
+    if (tokens > 0n) {
+      ixs.push(createMintToInstruction(market.mint.publicKey, ata, this.payer.publicKey, tokens));
+    }
+    await this.send(ixs);
+
+    const user: LocalUser = { keypair, ata, accountIndex: -1 };
+    market.users.set(name, user);
+    return user;
+  }
+
+  /** Run `ixs` and record the slab index the program allocated for `user`. */
+  private async allocate(market: LocalMarket, user: LocalUser, ix: TransactionInstruction): Promise<TxReceipt> {
+    const before = new Set((await this.snapshot(market, false)).usedIndices);
+    const receipt = await this.send([ix], [user.keypair], 100_000);
+    const after = (await this.snapshot(market, false)).usedIndices;
+    const idx = after.find((i) => !before.has(i));
+    if (idx === undefined) throw new Error("No new account index allocated");
+    user.accountIndex = idx;
+    return receipt;
+  }
+
+  initUserIx(market: LocalMarket, user: LocalUser, feePayment = 1_000_000n): TransactionInstruction {
+    const data = encodeInitUser({ feePayment: feePayment.toString() });
+    const keys = buildAccountMetas(ACCOUNTS_INIT_USER, [
+      user.keypair.publicKey,
+      market.slab.publicKey,
+      user.ata,
+      market.vault,
+      WELL_KNOWN.tokenProgram,
+    ]);
+    return buildIx({ programId: this.programId, keys, data });
+  }
+
+  initUser(market: LocalMarket, user: LocalUser, feePayment = 1_000_000n): Promise<TxReceipt> {
+    return this.allocate(market, user, this.initUserIx(market, user, feePayment));
+  }
+
+  /**
+   * Init an LP account. TradeNoCpi never calls the matcher, so the matcher
+   * program/context are placeholders derived from the LP's label.
+   */
+  initLp(market: LocalMarket, lp: LocalUser, feePayment = 1_000_000n): Promise<TxReceipt> {
+    const data = encodeInitLP({
+      matcherProgram: seededKeypair("matcher-program").publicKey,
+      matcherContext: seededKeypair(`matcher-ctx:${lp.keypair.publicKey.toBase58()}`).publicKey,
+      feePayment: feePayment.toString(),
+    });
+    const keys = buildAccountMetas(ACCOUNTS_INIT_LP, [
+      lp.keypair.publicKey,
+      market.slab.publicKey,
+      lp.ata,
+      market.vault,
+      WELL_KNOWN.tokenProgram,
+    ]);
+    return this.allocate(market, lp, buildIx({ programId: this.programId, keys, data }));
+  }
+
+  depositIx(market: LocalMarket, user: LocalUser, amount: bigint): TransactionInstruction {
+    const data = encodeDepositCollateral({ userIdx: user.accountIndex, amount: amount.toString() });
+    const keys = buildAccountMetas(ACCOUNTS_DEPOSIT_COLLATERAL, [
+      user.keypair.publicKey,
+      market.slab.publicKey,
+      user.ata,
+      market.vault,
+      WELL_KNOWN.tokenProgram,
+      WELL_KNOWN.clock,
+    ]);
+    return buildIx({ programId: this.programId, keys, data });
+  }
+
+  deposit(market: LocalMarket, user: LocalUser, amount: bigint): Promise<TxReceipt> {
+    return this.send([this.depositIx(market, user, amount)], [user.keypair], 50_000);
+  }
+
+  withdrawIx(market: LocalMarket, user: LocalUser, amount: bigint): TransactionInstruction {
+    const data = encodeWithdrawCollateral({ userIdx: user.accountIndex, amount: amount.toString() });
+    const keys = buildAccountMetas(ACCOUNTS_WITHDRAW_COLLATERAL, [
+      user.keypair.publicKey,
+      market.slab.publicKey,
+      market.vault,
+      user.ata,
+      market.vaultPda,
+      WELL_KNOWN.tokenProgram,
+      WELL_KNOWN.clock,
+      market.slab.publicKey, // oracle = slab for admin oracle
+    ]);
+    return buildIx({ programId: this.programId, keys, data });
+  }
+
+  withdraw(market: LocalMarket, user: LocalUser, amount: bigint): Promise<TxReceipt> {
+    return this.send([this.withdrawIx(market, user, amount)], [user.keypair], 100_000);
+  }
+
+  /** TradeNoCpi — requires a program built with the `test` feature. */
+  trade(market: LocalMarket, user: LocalUser, lp: LocalUser, size: bigint): Promise<TxReceipt> {
+    const data = encodeTradeNoCpi({ lpIdx: lp.accountIndex, userIdx: user.accountIndex, size: size.toString() });
+    const keys = buildAccountMetas(ACCOUNTS_TRADE_NOCPI, [
+      user.keypair.publicKey,
+      lp.keypair.publicKey,
+      market.slab.publicKey,
+      market.slab.publicKey, // oracle = slab for admin oracle
+    ]);
+    return this.send([buildIx({ programId: this.programId, keys, data })], [user.keypair, lp.keypair]);
+  }
+
+  closeAccount(market: LocalMarket, user: LocalUser): Promise<TxReceipt> {
+    const data = encodeCloseAccount({ userIdx: user.accountIndex });
+    const keys = buildAccountMetas(ACCOUNTS_CLOSE_ACCOUNT, [
+      user.keypair.publicKey,
+      market.slab.publicKey,
+      market.vault,
+      user.ata,
+      market.vaultPda,
+      WELL_KNOWN.tokenProgram,
+      WELL_KNOWN.clock,
+      market.slab.publicKey,
+    ]);
+    return this.send([buildIx({ programId: this.programId, keys, data })], [user.keypair], 100_000);
+  }
+
+  // ==========================================================================
+  // KEEPER / ADMIN
+  // ==========================================================================
+
+  keeperCrank(market: LocalMarket, cuLimit = 400_000): Promise<TxReceipt> {
+    const data = encodeKeeperCrank({ callerIdx: CRANK_NO_CALLER, allowPanic: false });
+    const keys = buildAccountMetas(ACCOUNTS_KEEPER_CRANK, [
+      this.payer.publicKey,
+      market.slab.publicKey,
+      WELL_KNOWN.clock,
+      market.slab.publicKey, // oracle = slab for admin oracle
+    ]);
+    return this.send([buildIx({ programId: this.programId, keys, data })], [], cuLimit);
+  }
+
+  liquidateAtOracle(market: LocalMarket, targetIdx: number): Promise<TxReceipt> {
+    const data = encodeLiquidateAtOracle({ targetIdx });
+    const keys = buildAccountMetas(ACCOUNTS_LIQUIDATE_AT_ORACLE, [
+      this.payer.publicKey,
+      market.slab.publicKey,
+      WELL_KNOWN.clock,
+      market.slab.publicKey,
+    ]);
+    return this.send([buildIx({ programId: this.programId, keys, data })]);
+  }
+
+  pauseMarket(market: LocalMarket, admin: Keypair = this.payer): Promise<TxReceipt> {
+    const keys = buildAccountMetas(ACCOUNTS_PAUSE_MARKET, [admin.publicKey, market.slab.publicKey]);
+    return this.send([buildIx({ programId: this.programId, keys, data: encodePauseMarket() })], [admin], 50_000);
+  }
+
+  unpauseMarket(market: LocalMarket): Promise<TxReceipt> {
+    const keys = buildAccountMetas(ACCOUNTS_UNPAUSE_MARKET, [this.payer.publicKey, market.slab.publicKey]);
+    return this.send([buildIx({ programId: this.programId, keys, data: encodeUnpauseMarket() })], [], 50_000);
+  }
+
+  /** UpdateRiskParams with raw instruction data (admin + slab, same shape as UpdateConfig). */
+  updateRiskParams(market: LocalMarket, data: Uint8Array): Promise<TxReceipt> {
+    const keys = buildAccountMetas(ACCOUNTS_UPDATE_CONFIG, [this.payer.publicKey, market.slab.publicKey]);
+    return this.send([buildIx({ programId: this.programId, keys, data })], [], 50_000);
+  }
+
+  resolveMarket(market: LocalMarket): Promise<TxReceipt> {
+    const keys = buildAccountMetas(ACCOUNTS_RESOLVE_MARKET, [this.payer.publicKey, market.slab.publicKey]);
+    return this.send([buildIx({ programId: this.programId, keys, data: encodeResolveMarket() })], [], 50_000);
+  }
+
+  withdrawInsurance(market: LocalMarket): Promise<TxReceipt> {
+    const keys = buildAccountMetas(ACCOUNTS_WITHDRAW_INSURANCE, [
+      this.payer.publicKey,
+      market.slab.publicKey,
+      market.adminAta,
+      market.vault,
+      WELL_KNOWN.tokenProgram,
+      market.vaultPda,
+    ]);
+    return this.send([buildIx({ programId: this.programId, keys, data: encodeWithdrawInsurance() })], [], 100_000);
+  }
+
+  /** Send arbitrary instructions — for cases that deliberately break the helpers. */
+  sendRaw(ixs: TransactionInstruction[], signers: Keypair[] = [], cuLimit?: number): Promise<TxReceipt> {
+    return this.send(ixs, signers, cuLimit);
+  }
+
+  // ==========================================================================
+  // SLOTS / STATE
+  // ==========================================================================
+
+  slot(): Promise<bigint> {
+    return this.backend.getSlot();
+  }
+
+  advanceSlots(count: bigint): Promise<bigint> {
+    return this.backend.advanceSlots(count);
+  }
+
+  /** Decode the slab. Recorded snapshots feed the determinism trail. */
+  async snapshot(market: LocalMarket, record = true): Promise<SlabSnapshot> {
+    const info = await this.backend.getAccount(market.slab.publicKey);
+    if (!info) throw new Error("Slab account not found");
+    const data = info.data;
+    const rawHash = crypto.createHash("sha256").update(data).digest("hex");
+    if (record) this.trail.push(rawHash);
+
+    return {
+      slot: Number(await this.backend.getSlot()),
+      header: parseHeader(data),
+      config: parseConfig(data),
+      engine: parseEngine(data),
+      params: parseParams(data),
+      accounts: parseAllAccounts(data),
+      usedIndices: parseUsedIndices(data),
+      rawHash,
+    };
+  }
+
+  async tokenBalance(ata: PublicKey): Promise<bigint> {
+    const info = await this.backend.getAccount(ata);
+    if (!info) return 0n;
+    return Buffer.from(info.data).readBigUInt64LE(64);
+  }
+
+  // ==========================================================================
+  // TEST RUNNER
+  // ==========================================================================
+
+  async runTest(name: string, testFn: () => Promise<void>): Promise<TestResult> {
+    const start = Date.now();
+    try {
+      await testFn();
+      const result: TestResult = { name, passed: true, duration: Date.now() - start };
+      this.results.push(result);
+      console.log(`  ✅ ${name} (${result.duration}ms)`);
+      return result;
+    } catch (e: any) {
+      const result: TestResult = { name, passed: false, error: e.message || String(e), duration: Date.now() - start };
+      this.results.push(result);
+      console.log(`  ❌ ${name}: ${result.error}`);
+      return result;
+    }
+  }
+
+  getSummary() {
+    const passed = this.results.filter((r) => r.passed).length;
+    const failed = this.results.filter((r) => !r.passed).length;
+    return { passed, failed, total: this.results.length, results: this.results };
+  }
+
+  static assert(condition: boolean, message: string): void {
+    if (!condition) throw new Error(`Assertion failed: ${message}`);
+  }
+
+  static assertEqual<T>(actual: T, expected: T, message: string): void {
+    if (actual !== expected) throw new Error(`${message}: expected ${expected}, got ${actual}`);
+  }
+
+  /** Await `promise` and require it to fail with the given program error code. */
+  static async expectProgramError(promise: Promise<unknown>, code: number, label: string): Promise<void> {
+    try {
+      await promise;
+    } catch (e) {
+      if (e instanceof LocalTxError && e.code === code) return;
+      const got = e instanceof LocalTxError && e.code !== null ? getErrorName(e.code) : (e as Error).message;
+      throw new Error(`${label}: expected ${getErrorName(code)} (${code}), got ${got}`);
+    }
+    throw new Error(`${label}: expected ${getErrorName(code)} (${code}) but the transaction succeeded`);
+  }
+}
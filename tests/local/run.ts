@@ -0,0 +1,73 @@
+/**
+ * Local integration runner — no network, no funded wallets.
+ *
+ * Usage:
+ *   PERCOLATOR_PROGRAM_SO=path/to/percolator_prog.so npx tsx tests/local/run.ts
+ *   npx tsx tests/local/run.ts liquidation market-pause      # subset
+ *   npx tsx tests/local/run.ts --repeat                      # determinism check
+ *   PERCOLATOR_TEST_BACKEND=validator npx tsx tests/local/run.ts
+ *
+ * --repeat runs every scenario twice on fresh banks and fails if any slab
+ * snapshot hash differs between the runs (bankrun only).
+ */
+
+import { PROGRAM_ID } from "../harness.js";
+import { createLocalBackend } from "./backend.js";
+import { LocalHarness } from "./harness.js";
+import { SCENARIOS, type Scenario } from "./scenarios.js";
+
+async function runOnce(scenario: Scenario): Promise<{ failed: number; total: number; trail: string[] }> {
+  const backend = await createLocalBackend({ programId: PROGRAM_ID });
+  const h = new LocalHarness(backend, PROGRAM_ID);
+  try {
+    await h.runTest(`${scenario.name} completed`, () => scenario.run(h));
+  } finally {
+    await backend.close();
+  }
+  const summary = h.getSummary();
+  return { failed: summary.failed, total: summary.total, trail: h.trail };
+}
+
+async function main() {
+  const args = process.argv.slice(2);
+  const repeat = args.includes("--repeat");
+  const names = args.filter((a) => !a.startsWith("--"));
+
+  const unknown = names.filter((n) => !SCENARIOS.some((s) => s.name === n));
+  if (unknown.length > 0) {
+    console.error(`Unknown scenario(s): ${unknown.join(", ")}`);
+    console.error(`Available: ${SCENARIOS.map((s) => s.name).join(", ")}`);
+    process.exit(2);
+  }
+  const selected = names.length > 0 ? SCENARIOS.filter((s) => names.includes(s.name)) : SCENARIOS;
+
+  let failed = 0;
+  let total = 0;
+  for (const scenario of selected) {
+    console.log(`\n=== ${scenario.name} ===\n`);
+    const first = await runOnce(scenario);
+    failed += first.failed;
+    total += first.total;
+
+    if (repeat) {
+      const second = await runOnce(scenario);
+      const diverged = first.trail.findIndex((hash, i) => hash !== second.trail[i]);
+      total += 1;
+      if (diverged !== -1 || first.trail.length !== second.trail.length) {
+        failed += 1;
+        const at = diverged === -1 ? Math.min(first.trail.length, second.trail.length) : diverged;
+        console.log(`  ❌ ${scenario.name}: snapshot ${at} differs between runs`);
+      } else {
+        console.log(`  ✅ ${scenario.name}: ${first.trail.length} snapshots identical across runs`);
+      }
+    }
+  }
+
+  console.log(`\n  Results: ${total - failed}/${total} passed, ${failed} failed\n`);
+  process.exit(failed > 0 ? 1 : 0);
+}
+
+main().catch((err) => {
+  console.error("Fatal:", err);
+  process.exit(1);
+});
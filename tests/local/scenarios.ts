@@ -0,0 +1,375 @@
+/**
+ * Local ports of the devnet integration scenarios (t1–t8).
+ *
+ * Each scenario gets a fresh backend, so state never leaks between them and
+ * exact slot / count assertions are safe.
+ */
+
+import { encodeUpdateRiskParams, IX_TAG } from "@percolator/sdk";
+
+import { LocalHarness, type LocalMarket, type LocalUser } from "./harness.js";
+import { seededKeypair } from "./backend.js";
+
+const { assert, assertEqual, expectProgramError } = LocalHarness;
+
+// Program error codes (see packages/core/src/abi/errors.ts)
+const ERR_UNAUTHORIZED = 15;
+const ERR_MARKET_PAUSED = 33;
+
+export interface Scenario {
+  name: string;
+  run(h: LocalHarness): Promise<void>;
+}
+
+function abs(x: bigint): bigint {
+  return x < 0n ? -x : x;
+}
+
+async function positionOf(h: LocalHarness, market: LocalMarket, user: LocalUser): Promise<bigint> {
+  const snap = await h.snapshot(market);
+  return snap.accounts.find((a) => a.idx === user.accountIndex)?.account.positionSize ?? 0n;
+}
+
+/** Market with a funded LP and trader, ready to trade. */
+async function marketWithTrader(h: LocalHarness, priceE6 = 1_000_000n) {
+  const market = await h.createMarket({ initialPriceE6: priceE6, warmupPeriodSlots: 0n });
+  const lp = await h.createUser(market, "lp", 1_000_000_000n);
+  await h.initLp(market, lp);
+  await h.deposit(market, lp, 500_000_000n);
+  const trader = await h.createUser(market, "trader", 200_000_000n);
+  await h.initUser(market, trader);
+  await h.deposit(market, trader, 50_000_000n);
+  await h.keeperCrank(market);
+  return { market, lp, trader };
+}
+
+// ============================================================================
+// T1: Market boot
+// ============================================================================
+
+const marketBoot: Scenario = {
+  name: "market-boot",
+  async run(h) {
+    let market!: LocalMarket;
+
+    await h.runTest("Create market with admin oracle", async () => {
+      market = await h.createMarket({ initialPriceE6: 91_000_000_000n });
+    });
+
+    await h.runTest("Slab header, config and params", async () => {
+      const snap = await h.snapshot(market);
+      assert(snap.header.magic !== 0n, "Magic is set");
+      assert(snap.header.admin.equals(h.payer.publicKey), "Admin matches payer");
+      assert(snap.config.collateralMint.equals(market.mint.publicKey), "Collateral mint matches");
+      assertEqual(snap.params.initialMarginBps, 1000n, "Initial margin");
+      assertEqual(snap.params.maintenanceMarginBps, 500n, "Maintenance margin");
+      assertEqual(snap.params.tradingFeeBps, 10n, "Trading fee");
+      assertEqual(snap.config.authorityPriceE6, 91_000_000_000n, "Oracle price");
+      assertEqual(snap.engine.numUsedAccounts, 0, "No accounts yet");
+    });
+
+    await h.runTest("Crank records the exact slot it ran in", async () => {
+      await h.advanceSlots(25n);
+      const { slot } = await h.keeperCrank(market);
+      const snap = await h.snapshot(market);
+      if (h.backend.kind === "bankrun") {
+        assertEqual(snap.engine.lastCrankSlot, slot, "Last crank slot");
+      } else {
+        assert(snap.engine.lastCrankSlot > 0n, "Last crank slot is set");
+      }
+    });
+  },
+};
+
+// ============================================================================
+// T2: User lifecycle
+// ============================================================================
+
+const userLifecycle: Scenario = {
+  name: "user-lifecycle",
+  async run(h) {
+    const market = await h.createMarket({ warmupPeriodSlots: 0n });
+    let alice!: LocalUser;
+
+    await h.runTest("Init user charges the account fee", async () => {
+      alice = await h.createUser(market, "alice", 10_000_000n);
+      await h.initUser(market, alice, 1_000_000n);
+      const snap = await h.snapshot(market);
+      assertEqual(snap.engine.numUsedAccounts, 1, "1 account used");
+      assertEqual(await h.tokenBalance(alice.ata), 9_000_000n, "Fee left the wallet");
+    });
+
+    await h.runTest("Deposit moves tokens into the vault", async () => {
+      const vaultBefore = await h.tokenBalance(market.vault);
+      await h.deposit(market, alice, 5_000_000n);
+      assertEqual(await h.tokenBalance(market.vault), vaultBefore + 5_000_000n, "Vault balance");
+      const snap = await h.snapshot(market);
+      const acct = snap.accounts.find((a) => a.idx === alice.accountIndex)!;
+      assert(acct.account.capital >= 5_000_000n, "Capital includes the deposit");
+    });
+
+    await h.runTest("Withdraw after crank", async () => {
+      await h.keeperCrank(market);
+      await h.withdraw(market, alice, 1_000_000n);
+      assertEqual(await h.tokenBalance(alice.ata), 5_000_000n, "Wallet after withdraw");
+    });
+
+    await h.runTest("Second user gets its own index", async () => {
+      const bob = await h.createUser(market, "bob", 10_000_000n);
+      await h.initUser(market, bob);
+      assert(bob.accountIndex !== alice.accountIndex, "Distinct indices");
+      assertEqual((await h.snapshot(market)).engine.numUsedAccounts, 2, "2 accounts");
+    });
+
+    await h.runTest("Close account returns capital and frees the slot", async () => {
+      await h.keeperCrank(market);
+      await h.closeAccount(market, alice);
+      const snap = await h.snapshot(market);
+      assert(!snap.usedIndices.includes(alice.accountIndex), "Index freed");
+      assertEqual(snap.engine.numUsedAccounts, 1, "1 account left");
+    });
+  },
+};
+
+// ============================================================================
+// T3: Hyperp lifecycle (trade → resolve → force-close → withdraw insurance)
+// ============================================================================
+
+const hyperpLifecycle: Scenario = {
+  name: "hyperp-lifecycle",
+  async run(h) {
+    const { market, lp, trader } = await marketWithTrader(h, 500_000n);
+
+    await h.runTest("Trade opens offsetting positions", async () => {
+      await h.trade(market, trader, lp, 10_000_000n);
+      assertEqual(await positionOf(h, market, trader), 10_000_000n, "Trader long");
+      assertEqual(await positionOf(h, market, lp), -10_000_000n, "LP short");
+    });
+
+    await h.runTest("Crank after slots accrues funding state", async () => {
+      await h.advanceSlots(50n);
+      await h.keeperCrank(market);
+      const snap = await h.snapshot(market);
+      assert(snap.engine.lastFundingSlot > 0n, "Funding slot advanced");
+    });
+
+    await h.runTest("Push settlement price and resolve", async () => {
+      await h.pushOraclePrice(market, 1_000_000n);
+      await h.resolveMarket(market);
+      assert((await h.snapshot(market)).header.resolved, "Resolved flag set");
+    });
+
+    await h.runTest("Trading is blocked once resolved", async () => {
+      let rejected = false;
+      try {
+        await h.trade(market, trader, lp, 1_000_000n);
+      } catch {
+        rejected = true;
+      }
+      assert(rejected, "Trade after resolve rejected");
+    });
+
+    await h.runTest("Crank force-closes every position", async () => {
+      for (let i = 0; i < 10; i++) {
+        await h.keeperCrank(market);
+        const snap = await h.snapshot(market);
+        if (snap.accounts.every((a) => a.account.positionSize === 0n)) return;
+      }
+      throw new Error("Positions still open after 10 cranks");
+    });
+
+    await h.runTest("Withdraw insurance to admin", async () => {
+      const insurance = (await h.snapshot(market)).engine.insuranceFund.balance;
+      assert(insurance > 0n, "Trading fees reached insurance");
+      const before = await h.tokenBalance(market.adminAta);
+      await h.withdrawInsurance(market);
+      assertEqual(await h.tokenBalance(market.adminAta), before + insurance, "Admin received insurance");
+      assertEqual((await h.snapshot(market)).engine.insuranceFund.balance, 0n, "Insurance drained");
+    });
+  },
+};
+
+// ============================================================================
+// T4: Liquidation
+// ============================================================================
+
+const liquidation: Scenario = {
+  name: "liquidation",
+  async run(h) {
+    const { market, lp, trader } = await marketWithTrader(h);
+
+    await h.runTest("Open a 5x long", async () => {
+      await h.trade(market, trader, lp, 250_000_000n);
+      assertEqual(await positionOf(h, market, trader), 250_000_000n, "Trader long");
+    });
+
+    await h.runTest("Price crash makes the trader liquidatable", async () => {
+      // The engine smooths the oracle across cranks; push + crank until the
+      // crank's own sweep closes the position or it is clearly underwater.
+      for (let i = 0; i < 15; i++) {
+        await h.pushOraclePrice(market, 700_000n);
+        await h.keeperCrank(market);
+        const snap = await h.snapshot(market);
+        if (snap.config.authorityPriceE6 === 700_000n) break;
+      }
+      assertEqual((await h.snapshot(market)).config.authorityPriceE6, 700_000n, "Oracle at crash price");
+    });
+
+    await h.runTest("LiquidateAtOracle closes what the crank left", async () => {
+      const posBefore = await positionOf(h, market, trader);
+      if (posBefore !== 0n) await h.liquidateAtOracle(market, trader.accountIndex);
+      const posAfter = await positionOf(h, market, trader);
+      assert(posAfter === 0n || abs(posAfter) < abs(posBefore), "Position reduced");
+      assert((await h.snapshot(market)).engine.lifetimeLiquidations > 0n, "Liquidation recorded");
+    });
+
+    await h.runTest("Market keeps cranking after liquidation", async () => {
+      const { slot } = await h.keeperCrank(market);
+      assert((await h.snapshot(market)).engine.lastCrankSlot >= slot || h.backend.kind === "validator",
+        "Crank slot advanced");
+    });
+  },
+};
+
+// ============================================================================
+// T6: Risk gate (immediate direction flips)
+// ============================================================================
+
+const riskGate: Scenario = {
+  name: "risk-gate",
+  async run(h) {
+    const { market, lp, trader } = await marketWithTrader(h);
+
+    await h.runTest("Open and close a large long", async () => {
+      await h.trade(market, trader, lp, 100_000_000n);
+      assertEqual(await positionOf(h, market, trader), 100_000_000n, "Long");
+      await h.trade(market, trader, lp, -100_000_000n);
+      assertEqual(await positionOf(h, market, trader), 0n, "Flat");
+    });
+
+    await h.runTest("Open a short in the very next slot", async () => {
+      await h.trade(market, trader, lp, -100_000_000n);
+      assertEqual(await positionOf(h, market, trader), -100_000_000n, "Short");
+    });
+
+    await h.runTest("Rapid flips end flat", async () => {
+      await h.trade(market, trader, lp, 200_000_000n);
+      await h.trade(market, trader, lp, -200_000_000n);
+      await h.trade(market, trader, lp, 100_000_000n);
+      assertEqual(await positionOf(h, market, trader), 0n, "Flat after flips");
+      await h.keeperCrank(market);
+    });
+  },
+};
+
+// ============================================================================
+// T7: Market pause
+// ============================================================================
+
+const marketPause: Scenario = {
+  name: "market-pause",
+  async run(h) {
+    const market = await h.createMarket();
+    const trader = await h.createUser(market, "trader", 100_000_000n);
+    await h.initUser(market, trader);
+    await h.deposit(market, trader, 50_000_000n);
+    await h.keeperCrank(market);
+
+    await h.runTest("Non-admin cannot pause", async () => {
+      const fakeAdmin = seededKeypair("fake-admin");
+      await h.backend.fund(fakeAdmin.publicKey, 1_000_000_000);
+      await expectProgramError(h.pauseMarket(market, fakeAdmin), ERR_UNAUTHORIZED, "Non-admin PauseMarket");
+    });
+
+    await h.runTest("Pause blocks deposit, withdraw and init user", async () => {
+      await h.pauseMarket(market);
+      await expectProgramError(h.deposit(market, trader, 1_000_000n), ERR_MARKET_PAUSED, "Deposit");
+      await expectProgramError(h.withdraw(market, trader, 1_000_000n), ERR_MARKET_PAUSED, "Withdraw");
+      const newcomer = await h.createUser(market, "newcomer", 10_000_000n);
+      await expectProgramError(
+        h.sendRaw([h.initUserIx(market, newcomer)], [newcomer.keypair]),
+        ERR_MARKET_PAUSED,
+        "InitUser",
+      );
+    });
+
+    await h.runTest("Crank still runs while paused", async () => {
+      await h.keeperCrank(market);
+    });
+
+    await h.runTest("Pause is idempotent", async () => {
+      await h.pauseMarket(market);
+    });
+
+    await h.runTest("Unpause restores deposits", async () => {
+      await h.unpauseMarket(market);
+      await h.deposit(market, trader, 5_000_000n);
+    });
+  },
+};
+
+// ============================================================================
+// T8: Trading fee update via UpdateRiskParams
+// ============================================================================
+
+function updateRiskParamsRaw(initialMarginBps: bigint, maintenanceMarginBps: bigint, tradingFeeBps?: bigint) {
+  const buf = new Uint8Array(tradingFeeBps === undefined ? 17 : 25);
+  const dv = new DataView(buf.buffer);
+  buf[0] = IX_TAG.UpdateRiskParams;
+  dv.setBigUint64(1, initialMarginBps, true);
+  dv.setBigUint64(9, maintenanceMarginBps, true);
+  if (tradingFeeBps !== undefined) dv.setBigUint64(17, tradingFeeBps, true);
+  return buf;
+}
+
+const tradingFeeUpdate: Scenario = {
+  name: "trading-fee-update",
+  async run(h) {
+    const market = await h.createMarket();
+    const params = async () => (await h.snapshot(market)).params;
+
+    await h.runTest("17-byte format changes margins only", async () => {
+      await h.updateRiskParams(market, updateRiskParamsRaw(1200n, 600n));
+      const p = await params();
+      assertEqual(p.initialMarginBps, 1200n, "Initial margin");
+      assertEqual(p.maintenanceMarginBps, 600n, "Maintenance margin");
+      assertEqual(p.tradingFeeBps, 10n, "Fee unchanged");
+    });
+
+    await h.runTest("25-byte format updates the fee", async () => {
+      await h.updateRiskParams(market, updateRiskParamsRaw(1200n, 600n, 50n));
+      assertEqual((await params()).tradingFeeBps, 50n, "Fee updated");
+    });
+
+    await h.runTest("Fee above 1000 bps is rejected", async () => {
+      let rejected = false;
+      try {
+        await h.updateRiskParams(market, updateRiskParamsRaw(1200n, 600n, 1500n));
+      } catch {
+        rejected = true;
+      }
+      assert(rejected, "Fee 1500 rejected");
+      assertEqual((await params()).tradingFeeBps, 50n, "Fee unchanged after rejection");
+    });
+
+    await h.runTest("SDK encoder round-trips", async () => {
+      await h.updateRiskParams(
+        market,
+        encodeUpdateRiskParams({ initialMarginBps: 1000n, maintenanceMarginBps: 500n, tradingFeeBps: 25n }),
+      );
+      const p = await params();
+      assertEqual(p.tradingFeeBps, 25n, "Fee");
+      assertEqual(p.initialMarginBps, 1000n, "Initial margin restored");
+    });
+  },
+};
+
+export const SCENARIOS: Scenario[] = [
+  marketBoot,
+  userLifecycle,
+  hyperpLifecycle,
+  liquidation,
+  riskGate,
+  marketPause,
+  tradingFeeUpdate,
+];